@@ -0,0 +1,4 @@
+// マイグレーションを追加・変更した際に、埋め込み(`sqlx::migrate!`)をやり直させる
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
@@ -0,0 +1,7 @@
+ALTER TABLE books DROP CONSTRAINT IF EXISTS books_user_id_fkey;
+ALTER TABLE books
+    ADD CONSTRAINT books_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(user_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE;
+
+ALTER TABLE users DROP COLUMN IF EXISTS deactivated_at;
@@ -0,0 +1,10 @@
+-- ユーザーの無効化(論理削除)日時
+-- NULL の場合は有効なユーザーとして扱う
+ALTER TABLE users ADD COLUMN IF NOT EXISTS deactivated_at TIMESTAMP(3) WITH TIME ZONE;
+
+-- ユーザーの物理削除に伴い蔵書が連鎖削除されないよう、外部キー制約を RESTRICT に変更
+ALTER TABLE books DROP CONSTRAINT IF EXISTS books_user_id_fkey;
+ALTER TABLE books
+    ADD CONSTRAINT books_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(user_id)
+        ON UPDATE CASCADE
+        ON DELETE RESTRICT;
@@ -0,0 +1,19 @@
+-- api_keys テーブルの作成(存在しない場合のみ)
+-- キー本体は保存せず、SHA-256 ハッシュのみを保持する
+CREATE TABLE IF NOT EXISTS api_keys (
+    api_key_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    key_prefix VARCHAR(32) NOT NULL,
+    key_hash VARCHAR(64) NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    last_used_at TIMESTAMP(3) WITH TIME ZONE,
+    revoked_at TIMESTAMP(3) WITH TIME ZONE,
+
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_user_id ON api_keys (user_id);
@@ -0,0 +1,6 @@
+-- パスワードを持たないユーザーが存在する場合は、ロールバック前に削除またはパスワードを設定すること
+ALTER TABLE users ALTER COLUMN password_hash SET NOT NULL;
+
+DROP INDEX IF EXISTS idx_users_oidc_identity;
+ALTER TABLE users DROP COLUMN IF EXISTS oidc_subject;
+ALTER TABLE users DROP COLUMN IF EXISTS oidc_issuer;
@@ -0,0 +1,7 @@
+-- OpenID Connect の ID プロバイダーで認証されたユーザーとの紐付け
+ALTER TABLE users ADD COLUMN IF NOT EXISTS oidc_issuer VARCHAR(255);
+ALTER TABLE users ADD COLUMN IF NOT EXISTS oidc_subject VARCHAR(255);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_oidc_identity ON users (oidc_issuer, oidc_subject);
+
+-- シングルサインオンのみを利用するユーザーはパスワードを持たない
+ALTER TABLE users ALTER COLUMN password_hash DROP NOT NULL;
@@ -0,0 +1,3 @@
+ALTER INDEX idx_users_external_identity RENAME TO idx_users_oidc_identity;
+ALTER TABLE users RENAME COLUMN external_subject TO oidc_subject;
+ALTER TABLE users RENAME COLUMN external_issuer TO oidc_issuer;
@@ -0,0 +1,4 @@
+-- OIDC に加えて LDAP で認証されたユーザーも紐付けられるよう、列名を一般化する
+ALTER TABLE users RENAME COLUMN oidc_issuer TO external_issuer;
+ALTER TABLE users RENAME COLUMN oidc_subject TO external_subject;
+ALTER INDEX idx_users_oidc_identity RENAME TO idx_users_external_identity;
@@ -0,0 +1,6 @@
+ALTER TABLE returned_checkouts DROP COLUMN renewal_count, DROP COLUMN due_at;
+ALTER TABLE checkouts DROP COLUMN renewal_count, DROP COLUMN due_at;
+DROP TRIGGER IF EXISTS user_loan_policies_updated_at_trigger ON user_loan_policies;
+DROP TABLE IF EXISTS user_loan_policies;
+DROP TRIGGER IF EXISTS loan_policies_updated_at_trigger ON loan_policies;
+DROP TABLE IF EXISTS loan_policies;
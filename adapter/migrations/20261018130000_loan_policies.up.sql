@@ -0,0 +1,51 @@
+-- loan_policies テーブルの作成(存在しない場合のみ)
+-- ロールごとの貸出ポリシー。行が存在しないロールにはアプリケーションの既定値が適用される
+CREATE TABLE IF NOT EXISTS loan_policies (
+    role_id UUID PRIMARY KEY,
+    max_loans INTEGER NOT NULL CHECK (max_loans >= 0),
+    loan_period_days INTEGER NOT NULL CHECK (loan_period_days > 0),
+    max_renewals INTEGER NOT NULL CHECK (max_renewals >= 0),
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    updated_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    FOREIGN KEY (role_id) REFERENCES roles(role_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+CREATE TRIGGER loan_policies_updated_at_trigger
+    BEFORE UPDATE ON loan_policies FOR EACH ROW
+    EXECUTE FUNCTION set_updated_at();
+
+-- user_loan_policies テーブルの作成(存在しない場合のみ)
+-- ユーザーごとの上書き設定。NULLの項目はロールのポリシーに従う
+CREATE TABLE IF NOT EXISTS user_loan_policies (
+    user_id UUID PRIMARY KEY,
+    max_loans INTEGER CHECK (max_loans >= 0),
+    loan_period_days INTEGER CHECK (loan_period_days > 0),
+    max_renewals INTEGER CHECK (max_renewals >= 0),
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    updated_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+CREATE TRIGGER user_loan_policies_updated_at_trigger
+    BEFORE UPDATE ON user_loan_policies FOR EACH ROW
+    EXECUTE FUNCTION set_updated_at();
+
+-- 貸出期限と延長回数
+-- 既存の貸出には既定の貸出期間(14日)を適用する
+ALTER TABLE checkouts
+    ADD COLUMN due_at TIMESTAMP(3) WITH TIME ZONE,
+    ADD COLUMN renewal_count INTEGER NOT NULL DEFAULT 0;
+UPDATE checkouts SET due_at = checked_out_at + INTERVAL '14 days';
+ALTER TABLE checkouts ALTER COLUMN due_at SET NOT NULL;
+
+ALTER TABLE returned_checkouts
+    ADD COLUMN due_at TIMESTAMP(3) WITH TIME ZONE,
+    ADD COLUMN renewal_count INTEGER NOT NULL DEFAULT 0;
+UPDATE returned_checkouts SET due_at = checked_out_at + INTERVAL '14 days';
+ALTER TABLE returned_checkouts ALTER COLUMN due_at SET NOT NULL;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS book_status_histories;
+ALTER TABLE books DROP COLUMN status;
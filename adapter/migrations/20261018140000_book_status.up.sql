@@ -0,0 +1,27 @@
+-- 蔵書の状態
+-- Available: 貸出可能, OnLoan: 貸出中, Lost: 紛失, Damaged: 破損, InRepair: 修理中, Withdrawn: 除籍
+ALTER TABLE books
+    ADD COLUMN status VARCHAR(32) NOT NULL DEFAULT 'Available'
+    CHECK (status IN ('Available', 'OnLoan', 'Lost', 'Damaged', 'InRepair', 'Withdrawn'));
+UPDATE books SET status = 'OnLoan' WHERE book_id IN (SELECT book_id FROM checkouts);
+
+-- book_status_histories テーブルの作成(存在しない場合のみ)
+-- 蔵書の状態の変更履歴。previous_status が NULL のものは登録時点の状態を表す
+CREATE TABLE IF NOT EXISTS book_status_histories (
+    book_status_history_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    book_id UUID NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    previous_status VARCHAR(32),
+    changed_by UUID,
+    note VARCHAR(1024),
+    changed_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    FOREIGN KEY (book_id) REFERENCES books(book_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE,
+    FOREIGN KEY (changed_by) REFERENCES users(user_id)
+        ON UPDATE CASCADE
+        ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_book_status_histories_book_id ON book_status_histories (book_id, changed_at);
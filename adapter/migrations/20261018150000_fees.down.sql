@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS fee_entries;
+DROP TRIGGER IF EXISTS fee_schedules_updated_at_trigger ON fee_schedules;
+DROP TABLE IF EXISTS fee_schedules;
@@ -0,0 +1,46 @@
+-- fee_schedules テーブルの作成(存在しない場合のみ)
+-- 延滞・紛失の料金表。行は常に1件のみで、存在しない場合は料金を課さない
+CREATE TABLE IF NOT EXISTS fee_schedules (
+    fee_schedule_id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (fee_schedule_id),
+    overdue_fee_per_day BIGINT NOT NULL CHECK (overdue_fee_per_day >= 0),
+    max_overdue_fee BIGINT CHECK (max_overdue_fee >= 0),
+    lost_book_fee BIGINT NOT NULL CHECK (lost_book_fee >= 0),
+    checkout_block_threshold BIGINT CHECK (checkout_block_threshold >= 0),
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    updated_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3)
+);
+
+CREATE TRIGGER fee_schedules_updated_at_trigger
+    BEFORE UPDATE ON fee_schedules FOR EACH ROW
+    EXECUTE FUNCTION set_updated_at();
+
+-- fee_entries テーブルの作成(存在しない場合のみ)
+-- ユーザーごとの料金の台帳。請求(延滞・紛失)は正、支払い・免除は負の金額で記録し、合計を残高とする
+CREATE TABLE IF NOT EXISTS fee_entries (
+    fee_entry_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL,
+    kind VARCHAR(32) NOT NULL
+        CHECK (kind IN ('Overdue', 'Lost', 'Payment', 'Waiver')),
+    amount BIGINT NOT NULL CHECK (
+        (kind IN ('Overdue', 'Lost') AND amount > 0)
+        OR (kind IN ('Payment', 'Waiver') AND amount < 0)
+    ),
+    -- 貸出は返却時にreturned_checkoutsへ移動するため、外部キーは設定しない
+    checkout_id UUID,
+    book_id UUID,
+    note VARCHAR(1024),
+    recorded_by UUID,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE,
+    FOREIGN KEY (book_id) REFERENCES books(book_id)
+        ON UPDATE CASCADE
+        ON DELETE SET NULL,
+    FOREIGN KEY (recorded_by) REFERENCES users(user_id)
+        ON UPDATE CASCADE
+        ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_fee_entries_user_id ON fee_entries (user_id, created_at);
@@ -0,0 +1,4 @@
+ALTER TABLE returned_checkouts DROP COLUMN returned_via, DROP COLUMN checked_out_via;
+ALTER TABLE checkouts DROP COLUMN checked_out_via;
+ALTER TABLE users DROP COLUMN badge_code;
+DROP TABLE IF EXISTS kiosks;
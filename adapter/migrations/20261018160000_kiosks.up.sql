@@ -0,0 +1,27 @@
+-- kiosks テーブルの作成(存在しない場合のみ)
+-- 共用端末(キオスク)の資格情報。APIキーと同様に、キー本体は保存せずSHA-256ハッシュのみを保持する
+CREATE TABLE IF NOT EXISTS kiosks (
+    kiosk_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    key_prefix VARCHAR(32) NOT NULL,
+    key_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_by UUID,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    last_used_at TIMESTAMP(3) WITH TIME ZONE,
+    revoked_at TIMESTAMP(3) WITH TIME ZONE,
+
+    FOREIGN KEY (created_by) REFERENCES users(user_id)
+        ON UPDATE CASCADE
+        ON DELETE SET NULL
+);
+
+-- キオスクでユーザーを識別するための社員証・利用者カードのコード
+ALTER TABLE users ADD COLUMN badge_code VARCHAR(255) UNIQUE;
+
+-- 貸出・返却を行ったキオスク。利用者自身の操作の場合はNULL
+ALTER TABLE checkouts
+    ADD COLUMN checked_out_via UUID REFERENCES kiosks(kiosk_id) ON DELETE SET NULL;
+
+ALTER TABLE returned_checkouts
+    ADD COLUMN checked_out_via UUID REFERENCES kiosks(kiosk_id) ON DELETE SET NULL,
+    ADD COLUMN returned_via UUID REFERENCES kiosks(kiosk_id) ON DELETE SET NULL;
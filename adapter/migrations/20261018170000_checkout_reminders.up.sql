@@ -0,0 +1,15 @@
+-- checkout_reminders テーブルの作成(存在しない場合のみ)
+-- 送信済みの返却期限のリマインダー。同じ貸出に同じリマインダーを繰り返し送らないために用いる
+-- 返却期限が延長された場合は新しい期限に対して改めて送るため、期限も主キーに含める
+CREATE TABLE IF NOT EXISTS checkout_reminders (
+    checkout_id UUID NOT NULL,
+    kind VARCHAR(32) NOT NULL CHECK (kind IN ('DueSoon', 'Overdue')),
+    due_at TIMESTAMP(3) WITH TIME ZONE NOT NULL,
+    sent_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    PRIMARY KEY (checkout_id, kind, due_at),
+    -- 返却された貸出の記録は不要となるため、一緒に削除する
+    FOREIGN KEY (checkout_id) REFERENCES checkouts(checkout_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
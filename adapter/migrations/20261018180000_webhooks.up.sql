@@ -0,0 +1,42 @@
+-- webhooks テーブルの作成(存在しない場合のみ)
+-- イベントの通知先。シークレットは配信の署名に用いるため、ハッシュ化せずに保持する
+CREATE TABLE IF NOT EXISTS webhooks (
+    webhook_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    url VARCHAR(2048) NOT NULL,
+    events VARCHAR(64)[] NOT NULL,
+    secret VARCHAR(255) NOT NULL,
+    created_by UUID,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    FOREIGN KEY (created_by) REFERENCES users(user_id)
+        ON UPDATE CASCADE
+        ON DELETE SET NULL
+);
+
+-- webhook_deliveries テーブルの作成(存在しない場合のみ)
+-- Webhookごとのイベントの配信と、直近の送信結果
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    delivery_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_id UUID NOT NULL,
+    event_id UUID NOT NULL,
+    event_type VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(32) NOT NULL DEFAULT 'Pending'
+        CHECK (status IN ('Pending', 'Succeeded', 'Failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    response_status INTEGER,
+    last_error TEXT,
+    next_attempt_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    delivered_at TIMESTAMP(3) WITH TIME ZONE,
+
+    FOREIGN KEY (webhook_id) REFERENCES webhooks(webhook_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+-- 送信待ちの配信を送信時刻順に取得するためのインデックス
+CREATE INDEX IF NOT EXISTS webhook_deliveries_pending_idx
+    ON webhook_deliveries(next_attempt_at) WHERE status = 'Pending';
+CREATE INDEX IF NOT EXISTS webhook_deliveries_webhook_id_idx
+    ON webhook_deliveries(webhook_id, created_at DESC);
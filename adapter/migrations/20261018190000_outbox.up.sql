@@ -0,0 +1,34 @@
+-- outbox テーブルの作成(存在しない場合のみ)
+-- 状態の変更と同じトランザクションで記録するドメインイベント。
+-- すべてのコンシューマーへの配信が終わると processed_at が設定される
+CREATE TABLE IF NOT EXISTS outbox (
+    event_id UUID PRIMARY KEY,
+    event_type VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    occurred_at TIMESTAMP(3) WITH TIME ZONE NOT NULL,
+    processed_at TIMESTAMP(3) WITH TIME ZONE,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3)
+);
+
+-- 未処理のイベントを配信時刻順に取得するためのインデックス
+CREATE INDEX IF NOT EXISTS outbox_pending_idx
+    ON outbox(next_attempt_at) WHERE processed_at IS NULL;
+
+-- outbox_consumptions テーブルの作成(存在しない場合のみ)
+-- コンシューマーごとの処理済みのイベント。配信し直す際に、処理済みのコンシューマーを除くために用いる
+CREATE TABLE IF NOT EXISTS outbox_consumptions (
+    event_id UUID NOT NULL,
+    consumer VARCHAR(64) NOT NULL,
+    consumed_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    PRIMARY KEY (event_id, consumer),
+    FOREIGN KEY (event_id) REFERENCES outbox(event_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+-- 同じイベントの配信を重複して登録しないよう、イベントごとに配信を探すためのインデックス
+CREATE INDEX IF NOT EXISTS webhook_deliveries_event_id_idx
+    ON webhook_deliveries(event_id);
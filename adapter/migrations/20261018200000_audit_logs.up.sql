@@ -0,0 +1,35 @@
+-- audit_logs テーブルの作成(存在しない場合のみ)
+-- 変更を伴う操作の記録。操作したユーザーが削除された後も記録を残すため、
+-- ユーザーへの外部キーは設定しない
+CREATE TABLE IF NOT EXISTS audit_logs (
+    audit_log_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    actor_id UUID,
+    action VARCHAR(64) NOT NULL,
+    target_type VARCHAR(32) NOT NULL,
+    target_id VARCHAR(255),
+    before JSONB,
+    after JSONB,
+    request_id VARCHAR(255),
+    ip_address VARCHAR(64),
+    occurred_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3)
+);
+
+CREATE INDEX IF NOT EXISTS audit_logs_occurred_at_idx
+    ON audit_logs(occurred_at DESC);
+CREATE INDEX IF NOT EXISTS audit_logs_actor_id_idx
+    ON audit_logs(actor_id, occurred_at DESC);
+CREATE INDEX IF NOT EXISTS audit_logs_target_idx
+    ON audit_logs(target_type, target_id, occurred_at DESC);
+
+-- 監査ログは追記のみとし、記録後の変更・削除を拒否する関数
+CREATE OR REPLACE FUNCTION reject_audit_log_modification() RETURNS trigger AS '
+    BEGIN
+        RAISE EXCEPTION ''audit_logs is append-only'';
+    END;
+' LANGUAGE 'plpgsql';
+
+-- audit_logs テーブルの変更・削除を拒否するトリガー
+CREATE TRIGGER audit_logs_append_only_trigger
+    BEFORE UPDATE OR DELETE OR TRUNCATE ON audit_logs
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION reject_audit_log_modification();
@@ -0,0 +1,2 @@
+-- ロールはユーザーから参照されており、削除するとユーザーも連鎖削除されるため、何もしない
+SELECT 1;
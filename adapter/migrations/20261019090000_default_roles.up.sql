@@ -0,0 +1,7 @@
+-- アプリケーションが前提とするロールの登録
+-- 初回のユーザーを管理用コマンドから作成できるよう、マイグレーションで登録する
+INSERT INTO roles (name)
+VALUES
+    ('Admin'),
+    ('User')
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,4 @@
+-- メールアドレスの大文字・小文字の違いを無視して、ユーザーのメールアドレスを一意にする
+-- 同じメールアドレスのユーザーが複数あると、パスワード認証やシングルサインオンの紐付けの対象が定まらないため
+-- 既に重複がある場合はこのマイグレーションが失敗するため、事前に解消しておくこと
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email_lower ON users (lower(email));
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    consumer::DomainEventConsumer,
+    live::LiveEventBus,
+    model::{
+        domain_event::DomainEvent,
+        live::{LiveEventKind, PublishLiveEvent},
+    },
+};
+use shared::error::AppResult;
+
+/// 蔵書と貸出のドメインイベントを、接続中のクライアントに配信する
+///
+/// 同じドメインイベントを再び受け取った場合は再び配信されるため、
+/// クライアントは変換元のドメインイベントのIDで重複を判定する
+#[derive(new)]
+pub struct LiveEventConsumer {
+    bus: Arc<dyn LiveEventBus>,
+}
+
+#[async_trait]
+impl DomainEventConsumer for LiveEventConsumer {
+    fn name(&self) -> &'static str {
+        "live"
+    }
+
+    async fn consume(&self, event: &DomainEvent) -> AppResult<()> {
+        for kind in LiveEventKind::from_domain(event) {
+            self.bus
+                .publish(PublishLiveEvent {
+                    source_id: event.id,
+                    occurred_at: event.occurred_at,
+                    kind,
+                })
+                .await?;
+        }
+        Ok(())
+    }
+}
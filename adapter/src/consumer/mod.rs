@@ -0,0 +1,3 @@
+pub mod live;
+pub mod notification;
+pub mod webhook;
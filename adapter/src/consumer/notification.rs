@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    consumer::DomainEventConsumer,
+    model::{
+        checkout::Checkout,
+        domain_event::{DomainEvent, DomainEventKind},
+        id::UserId,
+        notification::{Notification, NotificationMessage},
+    },
+    notifier::Notifier,
+    repository::user::UserRepository,
+};
+use shared::error::AppResult;
+
+/// 貸出・返却の完了を利用者に通知する
+#[derive(new)]
+pub struct NotificationConsumer {
+    user_repository: Arc<dyn UserRepository>,
+    notifier: Arc<dyn Notifier>,
+}
+
+impl NotificationConsumer {
+    async fn notify(
+        &self,
+        user_id: UserId,
+        checkouts: &[Checkout],
+        message: fn(Vec<Checkout>) -> NotificationMessage,
+    ) -> AppResult<()> {
+        if checkouts.is_empty() {
+            return Ok(());
+        }
+        // 通知までの間に削除されたユーザーには通知しない
+        let Some(user) = self.user_repository.find_current_user(user_id).await? else {
+            return Ok(());
+        };
+        self.notifier
+            .notify(&Notification {
+                recipient: (&user).into(),
+                message: message(checkouts.to_vec()),
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl DomainEventConsumer for NotificationConsumer {
+    fn name(&self) -> &'static str {
+        "notification"
+    }
+
+    async fn consume(&self, event: &DomainEvent) -> AppResult<()> {
+        match &event.kind {
+            DomainEventKind::CheckedOut { user_id, checkouts } => {
+                self.notify(*user_id, checkouts, |checkouts| {
+                    NotificationMessage::CheckoutConfirmed { checkouts }
+                })
+                .await
+            }
+            DomainEventKind::Returned { user_id, checkouts } => {
+                self.notify(*user_id, checkouts, |checkouts| {
+                    NotificationMessage::ReturnConfirmed { checkouts }
+                })
+                .await
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use kernel::{
+        model::{
+            checkout::CheckoutBook,
+            id::{BookId, CheckoutId},
+            role::Role,
+            user::User,
+        },
+        notifier::MockNotifier,
+        repository::user::MockUserRepository,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_notifies_checkouts_and_returns() -> anyhow::Result<()> {
+        let user_id = UserId::new();
+        let mut users = MockUserRepository::new();
+        users.expect_find_current_user().returning(move |id| {
+            Ok(Some(User {
+                id,
+                name: "Alice".into(),
+                email: "alice@example.com".into(),
+                role: Role::User,
+                deactivated_at: None,
+            }))
+        });
+        let mut notifier = MockNotifier::new();
+        notifier
+            .expect_notify()
+            .withf(move |n| {
+                n.recipient.user_id == user_id
+                    && matches!(
+                        &n.message,
+                        NotificationMessage::CheckoutConfirmed { checkouts } if checkouts.len() == 1
+                    )
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+        let consumer = NotificationConsumer::new(Arc::new(users), Arc::new(notifier));
+
+        let now = Utc::now();
+        let checkout = Checkout {
+            id: CheckoutId::new(),
+            checked_out_by: user_id,
+            checked_out_at: now,
+            returned_at: None,
+            due_at: now,
+            renewal_count: 0,
+            checked_out_via: None,
+            returned_via: None,
+            book: CheckoutBook {
+                book_id: BookId::new(),
+                title: "Rust in Action".into(),
+                author: "Tim McNamara".into(),
+                isbn: "9781617294556".into(),
+            },
+        };
+        consumer
+            .consume(&DomainEvent::new(DomainEventKind::CheckedOut {
+                user_id,
+                checkouts: vec![checkout.clone()],
+            }))
+            .await?;
+
+        // 返却された冊数が0の場合や、通知の対象でないイベントでは通知しない
+        consumer
+            .consume(&DomainEvent::new(DomainEventKind::Returned {
+                user_id,
+                checkouts: vec![],
+            }))
+            .await?;
+        consumer
+            .consume(&DomainEvent::new(DomainEventKind::Renewed(checkout)))
+            .await?;
+
+        Ok(())
+    }
+}
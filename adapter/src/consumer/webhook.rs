@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    consumer::DomainEventConsumer,
+    model::{domain_event::DomainEvent, webhook::WebhookEvent},
+    repository::webhook::WebhookRepository,
+};
+use shared::error::AppResult;
+
+/// ドメインイベントを、購読しているWebhookへの配信として登録する
+///
+/// 変換後のイベントのIDはドメインイベントから決まり、登録済みの配信は重複して登録されないため、
+/// 同じドメインイベントを何度受け取っても配信は1回ずつとなる
+#[derive(new)]
+pub struct WebhookConsumer {
+    webhook_repository: Arc<dyn WebhookRepository>,
+}
+
+#[async_trait]
+impl DomainEventConsumer for WebhookConsumer {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn consume(&self, event: &DomainEvent) -> AppResult<()> {
+        for webhook_event in WebhookEvent::from_domain(event) {
+            self.webhook_repository.enqueue(&webhook_event).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+//! アプリケーションに埋め込んだマイグレーションの確認・適用
+
+use kernel::model::migration::PendingMigration;
+use shared::error::{AppError, AppResult};
+use sqlx::migrate::Migrator;
+
+use super::ConnectionPool;
+
+/// `adapter/migrations`のマイグレーション
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// 未適用のマイグレーションを、適用する順に取得する
+/// マイグレーションの履歴のテーブルがない場合は、すべてを未適用とみなす
+pub async fn pending_migrations(db: &ConnectionPool) -> AppResult<Vec<PendingMigration>> {
+    let applied: Vec<i64> =
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(db.inner_ref())
+            .await
+            .or_else(|e| match e {
+                // 履歴のテーブルが存在しない(未定義のテーブル)
+                sqlx::Error::Database(db) if db.code().as_deref() == Some("42P01") => Ok(vec![]),
+                e => Err(AppError::DatabaseOperationError(e)),
+            })?;
+
+    Ok(MIGRATOR
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration() && !applied.contains(&m.version))
+        .map(|m| PendingMigration {
+            version: m.version,
+            description: m.description.to_string(),
+        })
+        .collect())
+}
+
+/// 未適用のマイグレーションを適用し、適用したマイグレーションを返す
+pub async fn run_migrations(db: &ConnectionPool) -> AppResult<Vec<PendingMigration>> {
+    let pending = pending_migrations(db).await?;
+    MIGRATOR
+        .run(db.inner_ref())
+        .await
+        .map_err(|e| AppError::InternalError(e.into()))?;
+    Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test]
+    async fn test_no_pending_migrations_after_run(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool);
+
+        // テスト用のデータベースには、すべてのマイグレーションが適用済み
+        assert!(pending_migrations(&db).await?.is_empty());
+        assert!(run_migrations(&db).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_all_pending_without_history(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool);
+
+        let pending = pending_migrations(&db).await?;
+        assert_eq!(
+            pending.len(),
+            MIGRATOR
+                .iter()
+                .filter(|m| m.migration_type.is_up_migration())
+                .count()
+        );
+        assert!(pending.windows(2).all(|w| w[0].version < w[1].version));
+
+        Ok(())
+    }
+}
@@ -1,11 +1,22 @@
+use rand::Rng;
 use shared::{
     config::DatabaseConfig,
     error::{AppError, AppResult},
 };
-use sqlx::{postgres::PgConnectOptions, PgPool};
+use sqlx::{postgres::PgConnectOptions, PgConnection, PgPool};
+use std::{future::Future, pin::Pin, time::Duration};
 
+pub mod migration;
 pub mod model;
 
+/// 競合によって失敗したトランザクションを試行する回数の上限
+const MAX_TRANSACTION_ATTEMPTS: u32 = 5;
+/// 再試行までの待ち時間の基準値(試行ごとに倍になる)
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(10);
+
+/// トランザクション内で実行する処理が返すFuture
+pub type TransactionFuture<'c, T> = Pin<Box<dyn Future<Output = AppResult<T>> + Send + 'c>>;
+
 fn make_pg_connect_options(cfg: &DatabaseConfig) -> PgConnectOptions {
     PgConnectOptions::new()
         .host(&cfg.host)
@@ -29,6 +40,80 @@ impl ConnectionPool {
     pub async fn begin(&self) -> AppResult<sqlx::Transaction<'_, sqlx::Postgres>> {
         self.0.begin().await.map_err(AppError::TransactionError)
     }
+
+    /// SERIALIZABLEのトランザクション内で`f`を実行し、コミットする
+    ///
+    /// 同時に実行された他のトランザクションとの競合(シリアライゼーション失敗・デッドロック)で
+    /// 失敗した場合は、ランダムな待ち時間を挟んでトランザクション全体をやり直す。
+    /// 試行回数の上限まで競合し続けた場合は`AppError::TransactionConflictError`を返す。
+    /// `f`は複数回呼び出されることがあるため、データベース以外への副作用を持たせないこと
+    pub async fn serializable<T, F>(&self, f: F) -> AppResult<T>
+    where
+        F: for<'c> Fn(&'c mut PgConnection) -> TransactionFuture<'c, T>,
+    {
+        let mut attempt = 1;
+        loop {
+            match self.try_serializable(&f).await {
+                Err(e) if is_serialization_failure(&e) => {
+                    // 再試行を使い切った場合は、時間をおいて再度試すよう求める
+                    if attempt >= MAX_TRANSACTION_ATTEMPTS {
+                        tracing::warn!(attempt, error = ?e, "serializable transaction gave up");
+                        return Err(AppError::TransactionConflictError);
+                    }
+                    tracing::warn!(attempt, error = ?e, "retrying serializable transaction");
+                    tokio::time::sleep(retry_delay(attempt)).await;
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    async fn try_serializable<T, F>(&self, f: &F) -> AppResult<T>
+    where
+        F: for<'c> Fn(&'c mut PgConnection) -> TransactionFuture<'c, T>,
+    {
+        let mut tx = self.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE")
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::DatabaseOperationError)?;
+        let value = f(&mut tx).await?;
+        tx.commit().await.map_err(AppError::TransactionError)?;
+        Ok(value)
+    }
+}
+
+/// 同時に実行されたトランザクションが同じ値を書き込もうとして違反しうる一意制約
+/// 同じ蔵書の貸出(`checkouts.book_id`)と、同じ貸出の返却(`returned_checkouts.checkout_id`)
+const CONTENDED_CONSTRAINTS: [&str; 2] = ["checkouts_book_id_key", "returned_checkouts_pkey"];
+
+/// トランザクションをやり直せば成功しうるエラーかどうか
+/// 40001: serialization_failure, 40P01: deadlock_detected
+///
+/// 同時に挿入された行との一意制約の違反は、SERIALIZABLEでも40001ではなく
+/// 23505(unique_violation)として報告されることがある
+/// (PostgreSQLのドキュメント「13.2.3. Serializable Isolation Level」を参照)。
+/// やり直しても同じく違反する通常の制約違反と区別するため、競合しうる制約に限って再試行する
+pub(crate) fn is_serialization_failure(e: &AppError) -> bool {
+    match e {
+        AppError::TransactionError(sqlx::Error::Database(e))
+        | AppError::DatabaseOperationError(sqlx::Error::Database(e)) => match e.code().as_deref() {
+            Some("40001" | "40P01") => true,
+            Some("23505") => e
+                .constraint()
+                .is_some_and(|constraint| CONTENDED_CONSTRAINTS.contains(&constraint)),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// 指数的に伸ばした上限までのランダムな待ち時間
+/// 同時に失敗したトランザクション同士が、再試行で再び衝突しにくくする
+fn retry_delay(attempt: u32) -> Duration {
+    let max = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+    rand::thread_rng().gen_range(Duration::ZERO..=max)
 }
 
 pub fn connect_database_with(cfg: &DatabaseConfig) -> ConnectionPool {
@@ -0,0 +1,88 @@
+use kernel::model::{
+    api_key::{ApiKey, ApiKeyScope},
+    id::{ApiKeyId, UserId},
+};
+use sha2::{Digest, Sha256};
+use shared::error::AppError;
+use sqlx::types::chrono::{DateTime, Utc};
+use std::str::FromStr;
+
+/// api_keys レコード型定義
+pub struct ApiKeyRow {
+    pub api_key_id: ApiKeyId,
+    pub user_id: UserId,
+    pub name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<ApiKeyRow> for ApiKey {
+    type Error = AppError;
+    fn try_from(value: ApiKeyRow) -> Result<Self, Self::Error> {
+        let ApiKeyRow {
+            api_key_id,
+            user_id,
+            name,
+            key_prefix,
+            scopes,
+            created_at,
+            last_used_at,
+            revoked_at,
+        } = value;
+        let scopes = scopes
+            .iter()
+            .map(|s| ApiKeyScope::from_str(s))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+        Ok(ApiKey {
+            id: api_key_id,
+            user_id,
+            name,
+            key_prefix,
+            scopes,
+            created_at,
+            last_used_at,
+            revoked_at,
+        })
+    }
+}
+
+/// APIキー本体から、保存・照合用のSHA-256ハッシュ(16進文字列)を生成する
+///
+/// APIキーは十分なエントロピーを持つランダム文字列のため、
+/// パスワードのようなストレッチング(bcrypt等)は行わず、リクエストごとの照合を高速に保つ
+pub fn hash_api_key(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_api_key() {
+        let hash = hash_api_key("bmk_test");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_api_key("bmk_test"));
+        assert_ne!(hash, hash_api_key("bmk_test2"));
+    }
+
+    #[test]
+    fn test_api_key_row_into_api_key_invalid_scope() {
+        let row = ApiKeyRow {
+            api_key_id: ApiKeyId::new(),
+            user_id: UserId::new(),
+            name: "test".into(),
+            key_prefix: "bmk_12345678".into(),
+            scopes: vec!["read".into(), "unknown".into()],
+            created_at: Utc::now(),
+            last_used_at: None,
+            revoked_at: None,
+        };
+        let res = ApiKey::try_from(row);
+        assert!(matches!(res, Err(AppError::ConversionEntityError(_))));
+    }
+}
@@ -0,0 +1,60 @@
+use std::str::FromStr;
+
+use kernel::model::{
+    audit::{AuditAction, AuditLog, AuditTargetType},
+    id::{AuditLogId, UserId},
+};
+use shared::error::AppError;
+use sqlx::types::chrono::{DateTime, Utc};
+
+/// 監査ログを総件数付きで取得する際のレコード型
+pub struct PaginatedAuditLogRow {
+    pub total: i64,
+    pub audit_log_id: AuditLogId,
+    pub actor_id: Option<UserId>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+    pub ip_address: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl TryFrom<PaginatedAuditLogRow> for AuditLog {
+    type Error = AppError;
+    fn try_from(value: PaginatedAuditLogRow) -> Result<Self, Self::Error> {
+        let PaginatedAuditLogRow {
+            audit_log_id,
+            actor_id,
+            action,
+            target_type,
+            target_id,
+            before,
+            after,
+            request_id,
+            ip_address,
+            occurred_at,
+            ..
+        } = value;
+        Ok(AuditLog {
+            id: audit_log_id,
+            actor_id,
+            action: AuditAction::from_str(&action).map_err(|e| {
+                AppError::ConversionEntityError(format!("invalid audit action '{action}': {e}"))
+            })?,
+            target_type: AuditTargetType::from_str(&target_type).map_err(|e| {
+                AppError::ConversionEntityError(format!(
+                    "invalid audit target type '{target_type}': {e}"
+                ))
+            })?,
+            target_id,
+            before,
+            after,
+            request_id,
+            ip_address,
+            occurred_at,
+        })
+    }
+}
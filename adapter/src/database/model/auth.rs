@@ -10,7 +10,8 @@ use crate::redis::model::{RedisKey, RedisValue};
 
 pub struct UserItem {
     pub user_id: UserId,
-    pub password_hash: String,
+    /// シングルサインオンのみを利用するユーザーの場合は`None`
+    pub password_hash: Option<String>,
 }
 
 pub struct AuthorizationKey(String);
@@ -24,6 +25,12 @@ pub fn from(event: CreateToken) -> (AuthorizationKey, AuthorizedUserId) {
     )
 }
 
+/// ユーザーごとのアクセストークンの索引のキー
+/// ユーザーのトークンをまとめて削除するために用いる
+pub fn user_tokens_index(user_id: UserId) -> String {
+    format!("user_tokens:{user_id}")
+}
+
 impl From<AuthorizationKey> for AccessToken {
     fn from(key: AuthorizationKey) -> Self {
         Self(key.0)
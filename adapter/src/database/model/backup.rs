@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use kernel::model::{
+    backup::BackupBook,
+    id::{BookId, UserId},
+};
+use shared::error::AppError;
+
+use super::book::parse_book_status;
+
+/// バックアップに含める蔵書のレコード
+pub struct BackupBookRow {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub description: String,
+    pub user_id: UserId,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<BackupBookRow> for BackupBook {
+    type Error = AppError;
+
+    fn try_from(value: BackupBookRow) -> Result<Self, Self::Error> {
+        let BackupBookRow {
+            book_id,
+            title,
+            author,
+            isbn,
+            description,
+            user_id,
+            status,
+            created_at,
+            updated_at,
+        } = value;
+        Ok(Self {
+            book_id,
+            title,
+            author,
+            isbn,
+            description,
+            user_id,
+            status: parse_book_status(&status)?,
+            created_at,
+            updated_at,
+        })
+    }
+}
@@ -1,9 +1,18 @@
 use chrono::{DateTime, Utc};
 use kernel::model::{
-    book::{Book, CheckoutInfo},
+    book::{
+        status::{BookStatus, BookStatusHistory},
+        Book, CheckoutInfo,
+    },
     id::{BookId, CheckoutId, UserId},
     user::{BookOwner, CheckOutUser},
 };
+use shared::error::{AppError, AppResult};
+use std::str::FromStr;
+
+pub fn parse_book_status(value: &str) -> AppResult<BookStatus> {
+    BookStatus::from_str(value).map_err(|e| AppError::ConversionEntityError(e.to_string()))
+}
 
 /// book レコード型定義
 pub struct BookRow {
@@ -14,9 +23,10 @@ pub struct BookRow {
     pub description: String,
     pub owned_by: UserId,
     pub owner_name: String,
+    pub status: String,
 }
 impl BookRow {
-    pub fn into_book(self, checkout: Option<CheckoutInfo>) -> Book {
+    pub fn into_book(self, checkout: Option<CheckoutInfo>) -> AppResult<Book> {
         let BookRow {
             book_id,
             title,
@@ -25,8 +35,9 @@ impl BookRow {
             description,
             owned_by,
             owner_name,
+            status,
         } = self;
-        Book {
+        Ok(Book {
             id: book_id,
             title,
             author,
@@ -36,8 +47,9 @@ impl BookRow {
                 id: owned_by,
                 name: owner_name,
             },
+            status: parse_book_status(&status)?,
             checkout_info: checkout,
-        }
+        })
     }
 }
 pub struct PaginatedBookRow {
@@ -54,10 +66,11 @@ pub struct PaginatedBookDetailRow {
     pub description: String,
     pub owned_by: UserId,
     pub owner_name: String,
+    pub status: String,
 }
 
 impl PaginatedBookDetailRow {
-    pub fn into_book(self, checkout: Option<CheckoutInfo>) -> Book {
+    pub fn into_book(self, checkout: Option<CheckoutInfo>) -> AppResult<Book> {
         let PaginatedBookDetailRow {
             book_id,
             title,
@@ -66,9 +79,10 @@ impl PaginatedBookDetailRow {
             description,
             owned_by,
             owner_name,
+            status,
             ..
         } = self;
-        Book {
+        Ok(Book {
             id: book_id,
             title,
             author,
@@ -78,10 +92,81 @@ impl PaginatedBookDetailRow {
                 id: owned_by,
                 name: owner_name,
             },
+            status: parse_book_status(&status)?,
             checkout_info: checkout,
-        }
+        })
+    }
+}
+/// 現在の貸出を含めた蔵書のレコード型定義
+/// 貸出中でない場合、貸出の項目はいずれも`None`となる
+pub struct BookWithCheckoutRow {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub description: String,
+    pub owned_by: UserId,
+    pub owner_name: String,
+    pub status: String,
+    pub checkout_id: Option<CheckoutId>,
+    pub checked_out_by: Option<UserId>,
+    pub checked_out_by_name: Option<String>,
+    pub checked_out_at: Option<DateTime<Utc>>,
+    pub due_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<BookWithCheckoutRow> for Book {
+    type Error = AppError;
+
+    fn try_from(value: BookWithCheckoutRow) -> Result<Self, Self::Error> {
+        let BookWithCheckoutRow {
+            book_id,
+            title,
+            author,
+            isbn,
+            description,
+            owned_by,
+            owner_name,
+            status,
+            checkout_id,
+            checked_out_by,
+            checked_out_by_name,
+            checked_out_at,
+            due_at,
+        } = value;
+        let checkout_info = match (
+            checkout_id,
+            checked_out_by,
+            checked_out_by_name,
+            checked_out_at,
+            due_at,
+        ) {
+            (Some(checkout_id), Some(id), Some(name), Some(checked_out_at), Some(due_at)) => {
+                Some(CheckoutInfo {
+                    checkout_id,
+                    checked_out_by: CheckOutUser { id, name },
+                    checked_out_at,
+                    due_at,
+                })
+            }
+            _ => None,
+        };
+        Ok(Book {
+            id: book_id,
+            title,
+            author,
+            isbn,
+            description,
+            owner: BookOwner {
+                id: owned_by,
+                name: owner_name,
+            },
+            status: parse_book_status(&status)?,
+            checkout_info,
+        })
     }
 }
+
 ///貸出し情報を含めた本のレコード型定義
 pub struct BookCheckoutRow {
     pub checkout_id: CheckoutId,
@@ -89,6 +174,7 @@ pub struct BookCheckoutRow {
     pub user_id: UserId,
     pub user_name: String,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
 }
 // CheckoutInfo型へ変換するFromトレイトの実装
 impl From<BookCheckoutRow> for CheckoutInfo {
@@ -99,6 +185,7 @@ impl From<BookCheckoutRow> for CheckoutInfo {
             user_id,
             user_name,
             checked_out_at,
+            due_at,
         } = value;
         CheckoutInfo {
             checkout_id,
@@ -107,6 +194,41 @@ impl From<BookCheckoutRow> for CheckoutInfo {
                 name: user_name,
             },
             checked_out_at,
+            due_at,
         }
     }
 }
+
+/// 蔵書の状態の変更履歴のレコード型定義
+pub struct BookStatusHistoryRow {
+    pub book_id: BookId,
+    pub status: String,
+    pub previous_status: Option<String>,
+    pub changed_by: Option<UserId>,
+    pub note: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+impl TryFrom<BookStatusHistoryRow> for BookStatusHistory {
+    type Error = AppError;
+    fn try_from(value: BookStatusHistoryRow) -> Result<Self, Self::Error> {
+        let BookStatusHistoryRow {
+            book_id,
+            status,
+            previous_status,
+            changed_by,
+            note,
+            changed_at,
+        } = value;
+        Ok(BookStatusHistory {
+            book_id,
+            status: parse_book_status(&status)?,
+            previous_status: previous_status
+                .as_deref()
+                .map(parse_book_status)
+                .transpose()?,
+            changed_by,
+            note,
+            changed_at,
+        })
+    }
+}
@@ -1,20 +1,31 @@
 use kernel::model::{
     checkout::{Checkout, CheckoutBook},
-    id::{BookId, CheckoutId, UserId},
+    id::{BookId, CheckoutId, KioskId, UserId},
 };
 use sqlx::types::chrono::{DateTime, Utc};
 
 pub struct CheckoutStateRow {
     pub book_id: BookId,
+    pub book_status: String,
     pub checkout_id: Option<CheckoutId>,
     pub user_id: Option<UserId>,
 }
 
+/// 貸出延長の可否を判定する際のレコード型
+pub struct CheckoutRenewalStateRow {
+    pub user_id: UserId,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+}
+
 pub struct CheckoutRow {
     pub checkout_id: CheckoutId,
     pub book_id: BookId,
     pub user_id: UserId,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+    pub checked_out_via: Option<KioskId>,
     pub title: String,
     pub author: String,
     pub isbn: String,
@@ -26,6 +37,9 @@ impl From<CheckoutRow> for Checkout {
             book_id,
             user_id,
             checked_out_at,
+            due_at,
+            renewal_count,
+            checked_out_via,
             title,
             author,
             isbn,
@@ -35,6 +49,10 @@ impl From<CheckoutRow> for Checkout {
             checked_out_by: user_id,
             checked_out_at,
             returned_at: None,
+            due_at,
+            renewal_count,
+            checked_out_via,
+            returned_via: None,
             book: CheckoutBook {
                 book_id,
                 title,
@@ -51,6 +69,10 @@ pub struct ReturnedCheckoutRow {
     pub user_id: UserId,
     pub checked_out_at: DateTime<Utc>,
     pub returned_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+    pub checked_out_via: Option<KioskId>,
+    pub returned_via: Option<KioskId>,
     pub title: String,
     pub author: String,
     pub isbn: String,
@@ -64,6 +86,10 @@ impl From<ReturnedCheckoutRow> for Checkout {
             user_id,
             checked_out_at,
             returned_at,
+            due_at,
+            renewal_count,
+            checked_out_via,
+            returned_via,
             title,
             author,
             isbn,
@@ -73,6 +99,114 @@ impl From<ReturnedCheckoutRow> for Checkout {
             checked_out_by: user_id,
             checked_out_at,
             returned_at: Some(returned_at),
+            due_at,
+            renewal_count,
+            checked_out_via,
+            returned_via,
+            book: CheckoutBook {
+                book_id,
+                title,
+                author,
+                isbn,
+            },
+        }
+    }
+}
+
+/// ユーザーの貸出履歴(未返却・返却済み)を総件数付きで取得する際のレコード型
+pub struct PaginatedCheckoutHistoryRow {
+    pub total: i64,
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub user_id: UserId,
+    pub checked_out_at: DateTime<Utc>,
+    pub returned_at: Option<DateTime<Utc>>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+    pub checked_out_via: Option<KioskId>,
+    pub returned_via: Option<KioskId>,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+}
+
+impl From<PaginatedCheckoutHistoryRow> for Checkout {
+    fn from(value: PaginatedCheckoutHistoryRow) -> Self {
+        let PaginatedCheckoutHistoryRow {
+            checkout_id,
+            book_id,
+            user_id,
+            checked_out_at,
+            returned_at,
+            due_at,
+            renewal_count,
+            checked_out_via,
+            returned_via,
+            title,
+            author,
+            isbn,
+            ..
+        } = value;
+        Checkout {
+            id: checkout_id,
+            checked_out_by: user_id,
+            checked_out_at,
+            returned_at,
+            due_at,
+            renewal_count,
+            checked_out_via,
+            returned_via,
+            book: CheckoutBook {
+                book_id,
+                title,
+                author,
+                isbn,
+            },
+        }
+    }
+}
+
+/// 貸出履歴(未返却・返却済み)のレコード型
+pub struct CheckoutHistoryRow {
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub user_id: UserId,
+    pub checked_out_at: DateTime<Utc>,
+    pub returned_at: Option<DateTime<Utc>>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+    pub checked_out_via: Option<KioskId>,
+    pub returned_via: Option<KioskId>,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+}
+
+impl From<CheckoutHistoryRow> for Checkout {
+    fn from(value: CheckoutHistoryRow) -> Self {
+        let CheckoutHistoryRow {
+            checkout_id,
+            book_id,
+            user_id,
+            checked_out_at,
+            returned_at,
+            due_at,
+            renewal_count,
+            checked_out_via,
+            returned_via,
+            title,
+            author,
+            isbn,
+        } = value;
+        Checkout {
+            id: checkout_id,
+            checked_out_by: user_id,
+            checked_out_at,
+            returned_at,
+            due_at,
+            renewal_count,
+            checked_out_via,
+            returned_via,
             book: CheckoutBook {
                 book_id,
                 title,
@@ -100,6 +234,9 @@ mod tests {
             book_id,
             user_id,
             checked_out_at: now,
+            due_at: now,
+            renewal_count: 0,
+            checked_out_via: None,
             title: "Test Book".to_string(),
             author: "Test Author".to_string(),
             isbn: "1234567890".to_string(),
@@ -131,6 +268,10 @@ mod tests {
             user_id,
             checked_out_at: now,
             returned_at,
+            due_at: returned_at,
+            renewal_count: 1,
+            checked_out_via: None,
+            returned_via: None,
             title: "Test Book".to_string(),
             author: "Test Author".to_string(),
             isbn: "1234567890".to_string(),
@@ -162,6 +303,10 @@ mod tests {
             user_id,
             checked_out_at,
             returned_at,
+            due_at: checked_out_at,
+            renewal_count: 1,
+            checked_out_via: None,
+            returned_via: None,
             title: "Test Title".to_string(),
             author: "Test Author".to_string(),
             isbn: "Test ISBN".to_string(),
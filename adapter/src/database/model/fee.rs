@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use kernel::model::{
+    fee::{FeeEntry, FeeEntryKind, FeeSchedule},
+    id::{BookId, CheckoutId, FeeEntryId, UserId},
+};
+use shared::error::AppError;
+use std::str::FromStr;
+
+pub struct FeeScheduleRow {
+    pub overdue_fee_per_day: i64,
+    pub max_overdue_fee: Option<i64>,
+    pub lost_book_fee: i64,
+    pub checkout_block_threshold: Option<i64>,
+}
+impl From<FeeScheduleRow> for FeeSchedule {
+    fn from(value: FeeScheduleRow) -> Self {
+        let FeeScheduleRow {
+            overdue_fee_per_day,
+            max_overdue_fee,
+            lost_book_fee,
+            checkout_block_threshold,
+        } = value;
+        FeeSchedule {
+            overdue_fee_per_day,
+            max_overdue_fee,
+            lost_book_fee,
+            checkout_block_threshold,
+        }
+    }
+}
+
+pub struct FeeEntryRow {
+    pub fee_entry_id: FeeEntryId,
+    pub user_id: UserId,
+    pub kind: String,
+    pub amount: i64,
+    pub checkout_id: Option<CheckoutId>,
+    pub book_id: Option<BookId>,
+    pub note: Option<String>,
+    pub recorded_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+}
+impl TryFrom<FeeEntryRow> for FeeEntry {
+    type Error = AppError;
+    fn try_from(value: FeeEntryRow) -> Result<Self, Self::Error> {
+        let FeeEntryRow {
+            fee_entry_id,
+            user_id,
+            kind,
+            amount,
+            checkout_id,
+            book_id,
+            note,
+            recorded_by,
+            created_at,
+        } = value;
+        Ok(FeeEntry {
+            id: fee_entry_id,
+            user_id,
+            kind: FeeEntryKind::from_str(&kind)
+                .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+            amount,
+            checkout_id,
+            book_id,
+            note,
+            recorded_by,
+            created_at,
+        })
+    }
+}
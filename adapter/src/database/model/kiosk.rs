@@ -0,0 +1,112 @@
+use kernel::model::{
+    id::{KioskId, UserId},
+    kiosk::Kiosk,
+};
+use shared::error::AppError;
+use sqlx::types::chrono::{DateTime, Utc};
+
+use crate::redis::model::{RedisKey, RedisValue};
+
+/// kiosks レコード型定義
+pub struct KioskRow {
+    pub kiosk_id: KioskId,
+    pub name: String,
+    pub key_prefix: String,
+    pub created_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<KioskRow> for Kiosk {
+    fn from(value: KioskRow) -> Self {
+        let KioskRow {
+            kiosk_id,
+            name,
+            key_prefix,
+            created_by,
+            created_at,
+            last_used_at,
+            revoked_at,
+        } = value;
+        Kiosk {
+            id: kiosk_id,
+            name,
+            key_prefix,
+            created_by,
+            created_at,
+            last_used_at,
+            revoked_at,
+        }
+    }
+}
+
+/// キオスクごと・時間枠ごとのリクエスト数を保持するキー
+pub struct KioskRateLimitKey {
+    kiosk_id: KioskId,
+    /// UNIX時間を時間枠の長さで割った値
+    window: i64,
+}
+
+impl KioskRateLimitKey {
+    pub fn new(kiosk_id: KioskId, now: DateTime<Utc>, window_secs: u64) -> Self {
+        Self {
+            kiosk_id,
+            window: now.timestamp().div_euclid(window_secs as i64),
+        }
+    }
+}
+
+impl RedisKey for KioskRateLimitKey {
+    type Value = RequestCount;
+
+    fn inner(&self) -> String {
+        format!("kiosk-rate-limit:{}:{}", self.kiosk_id, self.window)
+    }
+}
+
+/// 時間枠内のリクエスト数
+pub struct RequestCount(pub u64);
+
+impl RedisValue for RequestCount {
+    fn inner(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl TryFrom<String> for RequestCount {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value
+            .parse()
+            .map(Self)
+            .map_err(|e: std::num::ParseIntError| AppError::ConversionEntityError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_kiosk_rate_limit_key_window() {
+        let kiosk_id = KioskId::new();
+        let at = |s| Utc.with_ymd_and_hms(2026, 10, 18, 9, 0, s).unwrap();
+
+        // 同じ時間枠のリクエストは同じキーで数える
+        assert_eq!(
+            KioskRateLimitKey::new(kiosk_id, at(0), 60).inner(),
+            KioskRateLimitKey::new(kiosk_id, at(59), 60).inner()
+        );
+        assert_ne!(
+            KioskRateLimitKey::new(kiosk_id, at(0), 30).inner(),
+            KioskRateLimitKey::new(kiosk_id, at(30), 30).inner()
+        );
+        assert_ne!(
+            KioskRateLimitKey::new(kiosk_id, at(0), 60).inner(),
+            KioskRateLimitKey::new(KioskId::new(), at(0), 60).inner()
+        );
+    }
+}
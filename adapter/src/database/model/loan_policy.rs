@@ -0,0 +1,81 @@
+use kernel::model::{
+    id::UserId,
+    loan_policy::{LoanPolicy, LoanPolicyOverride, RoleLoanPolicy, UserLoanPolicy},
+    role::Role,
+};
+use shared::error::AppError;
+use std::str::FromStr;
+
+/// loan_policiesテーブルの各列から貸出ポリシーを組み立てる
+/// ロールにポリシーが設定されていない(外部結合で`NULL`となる)場合は既定値を用いる
+fn role_policy(
+    max_loans: Option<i32>,
+    loan_period_days: Option<i32>,
+    max_renewals: Option<i32>,
+) -> LoanPolicy {
+    match (max_loans, loan_period_days, max_renewals) {
+        (Some(max_loans), Some(loan_period_days), Some(max_renewals)) => LoanPolicy {
+            max_loans,
+            loan_period_days,
+            max_renewals,
+        },
+        _ => LoanPolicy::default(),
+    }
+}
+
+pub struct RoleLoanPolicyRow {
+    pub role_name: String,
+    pub max_loans: Option<i32>,
+    pub loan_period_days: Option<i32>,
+    pub max_renewals: Option<i32>,
+}
+impl TryFrom<RoleLoanPolicyRow> for RoleLoanPolicy {
+    type Error = AppError;
+    fn try_from(value: RoleLoanPolicyRow) -> Result<Self, Self::Error> {
+        let RoleLoanPolicyRow {
+            role_name,
+            max_loans,
+            loan_period_days,
+            max_renewals,
+        } = value;
+        Ok(RoleLoanPolicy {
+            role: Role::from_str(role_name.as_str())
+                .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+            policy: role_policy(max_loans, loan_period_days, max_renewals),
+        })
+    }
+}
+
+pub struct UserLoanPolicyRow {
+    pub user_id: UserId,
+    pub role_max_loans: Option<i32>,
+    pub role_loan_period_days: Option<i32>,
+    pub role_max_renewals: Option<i32>,
+    pub has_override: bool,
+    pub max_loans: Option<i32>,
+    pub loan_period_days: Option<i32>,
+    pub max_renewals: Option<i32>,
+}
+impl From<UserLoanPolicyRow> for UserLoanPolicy {
+    fn from(value: UserLoanPolicyRow) -> Self {
+        let UserLoanPolicyRow {
+            user_id,
+            role_max_loans,
+            role_loan_period_days,
+            role_max_renewals,
+            has_override,
+            max_loans,
+            loan_period_days,
+            max_renewals,
+        } = value;
+        UserLoanPolicy {
+            user_id,
+            role_policy: role_policy(role_max_loans, role_loan_period_days, role_max_renewals),
+            user_override: has_override.then_some(LoanPolicyOverride {
+                max_loans,
+                loan_period_days,
+                max_renewals,
+            }),
+        }
+    }
+}
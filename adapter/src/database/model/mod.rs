@@ -1,4 +1,12 @@
+pub mod api_key;
+pub mod audit;
 pub mod auth;
+pub mod backup;
 pub mod book;
 pub mod checkout;
+pub mod fee;
+pub mod kiosk;
+pub mod loan_policy;
+pub mod outbox;
 pub mod user;
+pub mod webhook;
@@ -0,0 +1,39 @@
+use kernel::model::{
+    domain_event::{DomainEvent, DomainEventKind, PendingEvent},
+    id::DomainEventId,
+};
+use shared::error::AppError;
+use sqlx::types::chrono::{DateTime, Utc};
+
+/// outbox レコードと、処理済みのコンシューマーの名前
+pub struct PendingEventRow {
+    pub event_id: DomainEventId,
+    pub payload: String,
+    pub occurred_at: DateTime<Utc>,
+    pub attempts: i32,
+    pub consumed_by: Vec<String>,
+}
+
+impl TryFrom<PendingEventRow> for PendingEvent {
+    type Error = AppError;
+    fn try_from(value: PendingEventRow) -> Result<Self, Self::Error> {
+        let PendingEventRow {
+            event_id,
+            payload,
+            occurred_at,
+            attempts,
+            consumed_by,
+        } = value;
+        let kind: DomainEventKind = serde_json::from_str(&payload)
+            .map_err(|e| AppError::ConversionEntityError(format!("invalid outbox payload: {e}")))?;
+        Ok(PendingEvent {
+            event: DomainEvent {
+                id: event_id,
+                occurred_at,
+                kind,
+            },
+            consumed_by,
+            attempts,
+        })
+    }
+}
@@ -1,4 +1,8 @@
-use kernel::model::{id::UserId, role::Role, user::User};
+use kernel::model::{
+    id::UserId,
+    role::Role,
+    user::{User, UserDeletionBlockers},
+};
 use shared::error::AppError;
 use sqlx::types::chrono::{DateTime, Utc};
 use std::str::FromStr;
@@ -10,6 +14,7 @@ pub struct UserRow {
     pub role_name: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub deactivated_at: Option<DateTime<Utc>>,
 }
 impl TryFrom<UserRow> for User {
     type Error = AppError;
@@ -19,6 +24,7 @@ impl TryFrom<UserRow> for User {
             name,
             email,
             role_name,
+            deactivated_at,
             ..
         } = value;
         Ok(User {
@@ -27,6 +33,28 @@ impl TryFrom<UserRow> for User {
             email,
             role: Role::from_str(role_name.as_str())
                 .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+            deactivated_at,
         })
     }
 }
+
+/// ユーザーの物理削除を妨げている要因のレコード型定義
+pub struct UserDeletionBlockersRow {
+    pub owned_books: i64,
+    pub unreturned_checkouts: i64,
+    pub fee_entries: i64,
+}
+impl From<UserDeletionBlockersRow> for UserDeletionBlockers {
+    fn from(value: UserDeletionBlockersRow) -> Self {
+        let UserDeletionBlockersRow {
+            owned_books,
+            unreturned_checkouts,
+            fee_entries,
+        } = value;
+        Self {
+            owned_books,
+            unreturned_checkouts,
+            fee_entries,
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::str::FromStr;
+
+use kernel::model::{
+    id::{UserId, WebhookDeliveryId, WebhookEventId, WebhookId},
+    webhook::{
+        PendingDelivery, Webhook, WebhookDelivery, WebhookDeliveryStatus, WebhookEventType,
+        WebhookSecret,
+    },
+};
+use shared::error::AppError;
+use sqlx::types::chrono::{DateTime, Utc};
+
+/// webhooks レコード型定義
+pub struct WebhookRow {
+    pub webhook_id: WebhookId,
+    pub url: String,
+    pub events: Vec<String>,
+    pub created_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<WebhookRow> for Webhook {
+    type Error = AppError;
+    fn try_from(value: WebhookRow) -> Result<Self, Self::Error> {
+        let WebhookRow {
+            webhook_id,
+            url,
+            events,
+            created_by,
+            created_at,
+        } = value;
+        let events = events
+            .iter()
+            .map(|e| event_type(e))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Webhook {
+            id: webhook_id,
+            url,
+            events,
+            created_by,
+            created_at,
+        })
+    }
+}
+
+/// webhook_deliveries レコード型定義
+pub struct WebhookDeliveryRow {
+    pub delivery_id: WebhookDeliveryId,
+    pub webhook_id: WebhookId,
+    pub event_id: WebhookEventId,
+    pub event_type: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<WebhookDeliveryRow> for WebhookDelivery {
+    type Error = AppError;
+    fn try_from(value: WebhookDeliveryRow) -> Result<Self, Self::Error> {
+        let WebhookDeliveryRow {
+            delivery_id,
+            webhook_id,
+            event_id,
+            event_type: event_type_name,
+            payload,
+            status,
+            attempts,
+            response_status,
+            last_error,
+            next_attempt_at,
+            created_at,
+            delivered_at,
+        } = value;
+        Ok(WebhookDelivery {
+            id: delivery_id,
+            webhook_id,
+            event_id,
+            event_type: event_type(&event_type_name)?,
+            payload,
+            status: WebhookDeliveryStatus::from_str(&status)
+                .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+            attempts,
+            response_status,
+            last_error,
+            next_attempt_at,
+            created_at,
+            delivered_at,
+        })
+    }
+}
+
+/// 送信する配信と、配信先の webhooks レコードを結合した型
+pub struct PendingDeliveryRow {
+    pub delivery_id: WebhookDeliveryId,
+    pub webhook_id: WebhookId,
+    pub event_id: WebhookEventId,
+    pub event_type: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub url: String,
+    pub secret: String,
+}
+
+impl TryFrom<PendingDeliveryRow> for PendingDelivery {
+    type Error = AppError;
+    fn try_from(value: PendingDeliveryRow) -> Result<Self, Self::Error> {
+        let PendingDeliveryRow {
+            delivery_id,
+            webhook_id,
+            event_id,
+            event_type,
+            payload,
+            status,
+            attempts,
+            response_status,
+            last_error,
+            next_attempt_at,
+            created_at,
+            delivered_at,
+            url,
+            secret,
+        } = value;
+        let delivery = WebhookDeliveryRow {
+            delivery_id,
+            webhook_id,
+            event_id,
+            event_type,
+            payload,
+            status,
+            attempts,
+            response_status,
+            last_error,
+            next_attempt_at,
+            created_at,
+            delivered_at,
+        }
+        .try_into()?;
+        Ok(PendingDelivery {
+            delivery,
+            url,
+            secret: WebhookSecret(secret),
+        })
+    }
+}
+
+fn event_type(value: &str) -> Result<WebhookEventType, AppError> {
+    WebhookEventType::from_str(value).map_err(|e| AppError::ConversionEntityError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_row_into_webhook_invalid_event() {
+        let row = WebhookRow {
+            webhook_id: WebhookId::new(),
+            url: "https://example.com/hook".into(),
+            events: vec!["book.created".into(), "book.deleted".into()],
+            created_by: None,
+            created_at: Utc::now(),
+        };
+        let res = Webhook::try_from(row);
+        assert!(matches!(res, Err(AppError::ConversionEntityError(_))));
+    }
+}
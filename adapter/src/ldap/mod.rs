@@ -0,0 +1,334 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    model::{id::UserId, role::Role, user::event::ProvisionExternalUser},
+    repository::user::UserRepository,
+};
+use ldap3::{ldap_escape, Ldap, LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry};
+use shared::{
+    config::LdapConfig,
+    error::{AppError, AppResult},
+};
+
+use crate::repository::auth::PasswordVerifier;
+
+/// LDAPの結果コード: 認証情報が正しくない(RFC 4511)
+const RC_INVALID_CREDENTIALS: u32 = 49;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// バインドに成功したユーザーのエントリ
+#[derive(Debug, PartialEq, Eq)]
+pub struct LdapEntry {
+    pub dn: String,
+    pub name: String,
+    /// 所属するグループのDN
+    pub groups: Vec<String>,
+}
+
+/// ユーザーを検索し、そのDNでバインドしてパスワードを検証するクライアント
+pub struct LdapClient {
+    config: LdapConfig,
+}
+
+impl LdapClient {
+    pub fn new(config: LdapConfig) -> Self {
+        Self { config }
+    }
+
+    /// メールアドレスに対応するエントリを検索し、パスワードでバインドできるか確かめる
+    /// 該当するエントリがない、またはパスワードが誤っている場合は`None`を返す
+    pub async fn authenticate(&self, email: &str, password: &str) -> AppResult<Option<LdapEntry>> {
+        // 空のパスワードによるバインドは匿名バインドとして成功してしまうため、事前に弾く
+        if password.is_empty() {
+            return Ok(None);
+        }
+
+        let (conn, mut ldap) = LdapConnAsync::with_settings(
+            LdapConnSettings::new().set_conn_timeout(CONNECT_TIMEOUT),
+            &self.config.url,
+        )
+        .await
+        .map_err(internal)?;
+        ldap3::drive!(conn);
+
+        let result = self.search_and_bind(&mut ldap, email, password).await;
+        let _ = ldap.unbind().await;
+        result
+    }
+
+    async fn search_and_bind(
+        &self,
+        ldap: &mut Ldap,
+        email: &str,
+        password: &str,
+    ) -> AppResult<Option<LdapEntry>> {
+        if let (Some(dn), Some(pw)) = (&self.config.bind_dn, &self.config.bind_password) {
+            ldap.simple_bind(dn, pw)
+                .await
+                .and_then(|r| r.success())
+                .map_err(internal)?;
+        }
+
+        let filter = self
+            .config
+            .user_filter
+            .replace("{email}", &ldap_escape(email));
+        let (entries, _) = ldap
+            .search(
+                &self.config.user_base_dn,
+                Scope::Subtree,
+                &filter,
+                vec![self.config.name_attribute.as_str(), "memberOf"],
+            )
+            .await
+            .and_then(|r| r.success())
+            .map_err(internal)?;
+        // 同じメールアドレスを持つエントリが複数ある場合は、誤ったユーザーを認証しないよう拒否する
+        let mut entries = entries.into_iter();
+        let (Some(entry), None) = (entries.next(), entries.next()) else {
+            return Ok(None);
+        };
+        let entry = SearchEntry::construct(entry);
+
+        let groups = match &self.config.group_base_dn {
+            Some(base) => {
+                let filter = format!(
+                    "(|(member={dn})(uniqueMember={dn}))",
+                    dn = ldap_escape(&entry.dn)
+                );
+                let (groups, _) = ldap
+                    .search(base, Scope::Subtree, &filter, vec!["1.1"])
+                    .await
+                    .and_then(|r| r.success())
+                    .map_err(internal)?;
+                groups
+                    .into_iter()
+                    .map(|g| SearchEntry::construct(g).dn)
+                    .collect()
+            }
+            None => attribute(&entry, "memberOf").to_vec(),
+        };
+
+        match ldap.simple_bind(&entry.dn, password).await {
+            Ok(r) if r.rc == RC_INVALID_CREDENTIALS => return Ok(None),
+            r => {
+                r.and_then(|r| r.success()).map_err(internal)?;
+            }
+        }
+
+        let name = attribute(&entry, &self.config.name_attribute)
+            .first()
+            .cloned()
+            .unwrap_or_else(|| email.to_string());
+        Ok(Some(LdapEntry {
+            dn: entry.dn,
+            name,
+            groups,
+        }))
+    }
+
+    /// 所属グループからロールを判定する
+    /// 管理者グループが設定されていない場合は`None`
+    pub fn map_role(&self, entry: &LdapEntry) -> Option<Role> {
+        if self.config.admin_groups.is_empty() {
+            return None;
+        }
+        // DNの大文字・小文字は区別しない
+        let is_admin = entry.groups.iter().any(|group| {
+            self.config
+                .admin_groups
+                .iter()
+                .any(|admin| admin.eq_ignore_ascii_case(group))
+        });
+        Some(if is_admin { Role::Admin } else { Role::User })
+    }
+}
+
+/// 属性名の大文字・小文字を区別せずに属性値を取得する
+fn attribute<'a>(entry: &'a SearchEntry, name: &str) -> &'a [String] {
+    entry
+        .attrs
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_slice())
+        .unwrap_or_default()
+}
+
+fn internal(e: LdapError) -> AppError {
+    AppError::InternalError(e.into())
+}
+
+/// LDAPへのバインドで認証し、初回ログイン時にユーザーを作成する認証方式
+/// 同じメールアドレスのローカルのユーザーが存在する場合は、紐付けずに認証失敗とする
+#[derive(new)]
+pub struct LdapPasswordVerifier {
+    client: LdapClient,
+    user_repository: Arc<dyn UserRepository>,
+}
+
+#[async_trait]
+impl PasswordVerifier for LdapPasswordVerifier {
+    async fn verify(&self, email: &str, password: &str) -> AppResult<Option<UserId>> {
+        // LDAPサーバーに接続できない場合も、ローカルのパスワードの誤りと同様に認証失敗として扱う
+        let entry = match self.client.authenticate(email, password).await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                tracing::warn!(error.message = %e, "LDAP authentication failed");
+                return Ok(None);
+            }
+        };
+        let role = self.client.map_role(&entry);
+        let res = self
+            .user_repository
+            .provision_external_user(ProvisionExternalUser {
+                issuer: self.client.config.url.clone(),
+                subject: entry.dn,
+                email: email.to_string(),
+                // ディレクトリに登録されたメールアドレスは組織が管理しているため、検証済みとみなす
+                email_verified: true,
+                // ローカルのパスワードを持つユーザー(管理者を含む)を、ディレクトリ側のパスワードで
+                // ログインできるようにしないため、既存のユーザーには紐付けない
+                link_by_email: false,
+                name: entry.name,
+                role,
+            })
+            .await;
+        match res {
+            Ok(user) => Ok(Some(user.id)),
+            Err(AppError::UnprocessableEntity(message)) => {
+                tracing::warn!(
+                    error.message = %message,
+                    "LDAP user was not linked to an existing user"
+                );
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{database::ConnectionPool, repository::user::UserRepositoryImpl};
+
+    fn config() -> LdapConfig {
+        LdapConfig {
+            url: std::env::var("LDAP_URL").unwrap_or_else(|_| "ldap://localhost:1389".into()),
+            bind_dn: Some("cn=admin,dc=example,dc=org".into()),
+            bind_password: Some("adminpassword".into()),
+            user_base_dn: "ou=people,dc=example,dc=org".into(),
+            user_filter: "(&(objectClass=inetOrgPerson)(mail={email}))".into(),
+            name_attribute: "cn".into(),
+            group_base_dn: Some("ou=groups,dc=example,dc=org".into()),
+            admin_groups: vec!["cn=Library-Admins,ou=groups,dc=example,dc=org".into()],
+        }
+    }
+
+    #[test]
+    fn test_map_role() {
+        let entry = |groups: &[&str]| LdapEntry {
+            dn: "uid=alice,ou=people,dc=example,dc=org".into(),
+            name: "Alice".into(),
+            groups: groups.iter().map(|g| g.to_string()).collect(),
+        };
+
+        let client = LdapClient::new(config());
+        assert_eq!(
+            client.map_role(&entry(&["cn=library-admins,ou=groups,dc=example,dc=org"])),
+            Some(Role::Admin)
+        );
+        assert_eq!(
+            client.map_role(&entry(&["cn=staff,ou=groups,dc=example,dc=org"])),
+            Some(Role::User)
+        );
+
+        // 管理者グループが設定されていない場合はロールを変更しない
+        let client = LdapClient::new(LdapConfig {
+            admin_groups: vec![],
+            ..config()
+        });
+        assert_eq!(client.map_role(&entry(&[])), None);
+    }
+
+    #[tokio::test]
+    async fn test_empty_password_is_rejected_without_connecting() -> anyhow::Result<()> {
+        // 接続できないURLでも、空のパスワードは接続前に拒否される
+        let client = LdapClient::new(LdapConfig {
+            url: "ldap://127.0.0.1:1".into(),
+            ..config()
+        });
+        assert!(client
+            .authenticate("alice@example.org", "")
+            .await?
+            .is_none());
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../repository/fixtures", scripts("common")))]
+    async fn test_unreachable_server_is_authentication_failure(
+        pool: sqlx::PgPool,
+    ) -> anyhow::Result<()> {
+        let verifier = LdapPasswordVerifier::new(
+            LdapClient::new(LdapConfig {
+                url: "ldap://127.0.0.1:1".into(),
+                ..config()
+            }),
+            Arc::new(UserRepositoryImpl::new(ConnectionPool::new(pool))),
+        );
+        assert!(verifier
+            .verify("alice@example.org", "alice-password")
+            .await?
+            .is_none());
+        Ok(())
+    }
+
+    // `docker compose --profile ldap up openldap`で起動したOpenLDAPが必要
+    // `cargo test -p adapter -- --ignored ldap`で実行する
+    #[ignore = "requires the local OpenLDAP stand-in"]
+    #[sqlx::test(fixtures(path = "../repository/fixtures", scripts("common")))]
+    async fn test_ldap_password_verifier(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let user_repository = Arc::new(UserRepositoryImpl::new(ConnectionPool::new(pool)));
+        let verifier =
+            LdapPasswordVerifier::new(LdapClient::new(config()), user_repository.clone());
+
+        // 誤ったパスワード、存在しないユーザーは認証できない
+        assert!(verifier
+            .verify("alice@example.org", "wrong-password")
+            .await?
+            .is_none());
+        assert!(verifier
+            .verify("nobody@example.org", "alice-password")
+            .await?
+            .is_none());
+
+        // 初回ログイン時にユーザーが作成され、グループに応じたロールが付与される
+        let alice = verifier
+            .verify("alice@example.org", "alice-password")
+            .await?
+            .expect("alice should be authenticated");
+        let user = user_repository.find_current_user(alice).await?.unwrap();
+        assert_eq!(user.name, "Alice Librarian");
+        assert_eq!(user.role, Role::Admin);
+
+        let bob = verifier
+            .verify("bob@example.org", "bob-password")
+            .await?
+            .expect("bob should be authenticated");
+        let user = user_repository.find_current_user(bob).await?.unwrap();
+        assert_eq!(user.role, Role::User);
+
+        // 2回目以降は同じユーザーとして認証される
+        assert_eq!(
+            verifier
+                .verify("alice@example.org", "alice-password")
+                .await?,
+            Some(alice)
+        );
+
+        Ok(())
+    }
+}
@@ -1,3 +1,10 @@
+pub mod consumer;
 pub mod database;
+pub mod ldap;
+pub mod live;
+pub mod mail;
+pub mod notifier;
+pub mod oidc;
 pub mod redis;
 pub mod repository;
+pub mod webhook;
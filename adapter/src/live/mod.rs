@@ -0,0 +1,256 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use kernel::{
+    live::LiveEventBus,
+    model::{
+        id::DomainEventId,
+        live::{LiveEvent, LiveEventId, LiveEventKind, PublishLiveEvent},
+    },
+};
+use serde::{Deserialize, Serialize};
+use shared::{
+    config::LiveEventConfig,
+    error::{AppError, AppResult},
+};
+use tokio::sync::broadcast;
+use tokio_stream::StreamExt;
+
+use crate::redis::RedisClient;
+
+/// 配信したイベントを保持するストリームのキー
+const STREAM_KEY: &str = "live-events";
+/// 配信したイベントを各インスタンスに届けるチャンネル
+const CHANNEL: &str = "live-events";
+/// ストリームのエントリで、イベントの内容を格納するフィールド
+const EVENT_FIELD: &str = "event";
+/// Redisとの接続が切れてから、購読し直すまでの待ち時間
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+/// Redisを介して、すべてのインスタンスの購読者にイベントを配信する
+///
+/// 配信したイベントはRedis Streamsに保持し、そのエントリ IDをイベントのIDとする。
+/// 各インスタンスはPub/Subでイベントを受け取り、インスタンス内の購読者に配る
+pub struct RedisLiveEventBus {
+    redis: Arc<RedisClient>,
+    sender: broadcast::Sender<LiveEvent>,
+    config: LiveEventConfig,
+}
+
+impl RedisLiveEventBus {
+    /// 他のインスタンスが配信したイベントを受け取るタスクを起動する
+    pub fn spawn(redis: Arc<RedisClient>, config: LiveEventConfig) -> Self {
+        let (sender, _) = broadcast::channel(config.buffer);
+        tokio::spawn(listen(redis.clone(), sender.clone(), config.retention));
+        Self {
+            redis,
+            sender,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl LiveEventBus for RedisLiveEventBus {
+    async fn publish(&self, event: PublishLiveEvent) -> AppResult<LiveEventId> {
+        let stored = StoredLiveEvent::from(event);
+        let id = self
+            .redis
+            .append_to_stream(
+                STREAM_KEY,
+                self.config.retention,
+                EVENT_FIELD,
+                &to_json(&stored)?,
+            )
+            .await?;
+        let event = stored.into_event(LiveEventId(id));
+        self.redis.publish(CHANNEL, &to_json(&event)?).await?;
+        Ok(event.id)
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<LiveEvent> {
+        self.sender.subscribe()
+    }
+
+    async fn find_since(&self, last_event_id: &LiveEventId) -> AppResult<Vec<LiveEvent>> {
+        read_since(&self.redis, last_event_id, self.config.retention).await
+    }
+}
+
+async fn read_since(
+    redis: &RedisClient,
+    last_event_id: &LiveEventId,
+    count: usize,
+) -> AppResult<Vec<LiveEvent>> {
+    // 不正な形式のIDではストリームを読み出せないため、再送するイベントはないものとする
+    if !last_event_id.is_well_formed() {
+        return Ok(vec![]);
+    }
+    redis
+        .read_stream_after(STREAM_KEY, &last_event_id.0, count, EVENT_FIELD)
+        .await?
+        .into_iter()
+        .map(|(id, value)| {
+            let stored: StoredLiveEvent = serde_json::from_str(&value)
+                .map_err(|e| AppError::ConversionEntityError(format!("invalid live event: {e}")))?;
+            Ok(stored.into_event(LiveEventId(id)))
+        })
+        .collect()
+}
+
+/// チャンネルに届いたイベントを、インスタンス内の購読者に配る
+///
+/// 接続が切れた場合は購読し直し、その間に配信されたイベントをストリームから取り出して配る
+async fn listen(redis: Arc<RedisClient>, sender: broadcast::Sender<LiveEvent>, retention: usize) {
+    // これまでに受け取ったうち、最も後に配信されたイベントのID
+    let mut last_event_id: Option<LiveEventId> = None;
+    loop {
+        match redis.subscribe(CHANNEL).await {
+            Ok(messages) => {
+                // 再購読の際にストリームから取り出したイベントのID
+                // チャンネルからも重複して届きうるのはこれらのイベントのみのため、これらに限って除く
+                let mut replayed = HashSet::new();
+                if let Some(id) = &last_event_id {
+                    match read_since(&redis, id, retention).await {
+                        Ok(missed) => {
+                            for event in missed {
+                                advance(&mut last_event_id, &event.id);
+                                replayed.insert(event.id.clone());
+                                // 購読者がいない場合もエラーとなるため、結果は無視する
+                                let _ = sender.send(event);
+                            }
+                        }
+                        Err(e) => tracing::warn!(
+                            error.message = %e,
+                            "Failed to read missed live events",
+                        ),
+                    }
+                }
+                tokio::pin!(messages);
+                while let Some(message) = messages.next().await {
+                    match serde_json::from_str::<LiveEvent>(&message) {
+                        Ok(event) => {
+                            if replayed.remove(&event.id) {
+                                continue;
+                            }
+                            // 配信の順序が前後して届いたイベントも、取りこぼさずに配る
+                            advance(&mut last_event_id, &event.id);
+                            let _ = sender.send(event);
+                        }
+                        Err(e) => tracing::warn!(
+                            error.message = %e,
+                            "Received malformed live event",
+                        ),
+                    }
+                }
+                tracing::warn!("Live event subscription closed");
+            }
+            Err(e) => tracing::error!(
+                error.message = %e,
+                "Failed to subscribe to live events",
+            ),
+        }
+        tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+    }
+}
+
+/// 受け取ったイベントが、これまでで最も後に配信されたものであればIDを更新する
+fn advance(last_event_id: &mut Option<LiveEventId>, id: &LiveEventId) {
+    if last_event_id.as_ref().is_none_or(|last| id.is_after(last)) {
+        *last_event_id = Some(id.clone());
+    }
+}
+
+/// ストリームに保持するイベントの内容
+/// IDはストリームのエントリ IDとなるため、含めない
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredLiveEvent {
+    source_id: DomainEventId,
+    occurred_at: DateTime<Utc>,
+    #[serde(flatten)]
+    kind: LiveEventKind,
+}
+
+impl From<PublishLiveEvent> for StoredLiveEvent {
+    fn from(value: PublishLiveEvent) -> Self {
+        let PublishLiveEvent {
+            source_id,
+            occurred_at,
+            kind,
+        } = value;
+        Self {
+            source_id,
+            occurred_at,
+            kind,
+        }
+    }
+}
+
+impl StoredLiveEvent {
+    fn into_event(self, id: LiveEventId) -> LiveEvent {
+        let Self {
+            source_id,
+            occurred_at,
+            kind,
+        } = self;
+        LiveEvent {
+            id,
+            source_id,
+            occurred_at,
+            kind,
+        }
+    }
+}
+
+fn to_json(value: &impl Serialize) -> AppResult<String> {
+    serde_json::to_string(value).map_err(|e| AppError::ConversionEntityError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kernel::model::id::BookId;
+
+    #[test]
+    fn test_stored_event_round_trip() -> anyhow::Result<()> {
+        let event = PublishLiveEvent {
+            source_id: DomainEventId::new(),
+            occurred_at: Utc::now(),
+            kind: LiveEventKind::BookDeleted {
+                book_id: BookId::new(),
+            },
+        };
+        let json = to_json(&StoredLiveEvent::from(event.clone()))?;
+        let value: serde_json::Value = serde_json::from_str(&json)?;
+        assert_eq!(value["type"], "book.deleted");
+        assert!(value.get("id").is_none());
+
+        // 保持した内容に、ストリームのエントリ IDを付けて復元できる
+        let stored: StoredLiveEvent = serde_json::from_str(&json)?;
+        let restored = stored.into_event(LiveEventId("1700000000000-0".into()));
+        assert_eq!(restored.id.0, "1700000000000-0");
+        assert_eq!(restored.source_id, event.source_id);
+        assert_eq!(restored.kind, event.kind);
+
+        // 各インスタンスに届けたイベントも、同じ内容に復元できる
+        let published: LiveEvent = serde_json::from_str(&to_json(&restored)?)?;
+        assert_eq!(published, restored);
+        Ok(())
+    }
+
+    #[test]
+    fn test_advance_keeps_latest_id() {
+        let mut last = None;
+        advance(&mut last, &LiveEventId("1700000000000-1".into()));
+        assert_eq!(last, Some(LiveEventId("1700000000000-1".into())));
+
+        // 前後して届いた先のイベントでは、再購読の起点を戻さない
+        advance(&mut last, &LiveEventId("1700000000000-0".into()));
+        assert_eq!(last, Some(LiveEventId("1700000000000-1".into())));
+
+        advance(&mut last, &LiveEventId("1700000000001-0".into()));
+        assert_eq!(last, Some(LiveEventId("1700000000001-0".into())));
+    }
+}
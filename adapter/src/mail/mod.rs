@@ -0,0 +1,277 @@
+use std::time::Duration;
+
+use kernel::model::notification::Recipient;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::{self, authentication::Credentials},
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use shared::{
+    config::{SmtpConfig, SmtpTls},
+    error::{AppError, AppResult},
+};
+
+pub mod queue;
+pub mod template;
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 送信するメール
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: Recipient,
+    pub subject: String,
+    pub body: String,
+}
+
+/// SMTPサーバーを介してメールを送信するクライアント
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(config: SmtpConfig) -> AppResult<Self> {
+        let builder = match config.tls {
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                .map_err(internal)?,
+            SmtpTls::Tls => {
+                AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host).map_err(internal)?
+            }
+        }
+        .port(config.port)
+        .timeout(Some(SMTP_TIMEOUT));
+        let builder = match config.credentials {
+            Some(c) => builder.credentials(Credentials::new(c.username, c.password)),
+            None => builder,
+        };
+        Ok(Self {
+            transport: builder.build(),
+            from: config.from.parse().map_err(|e| {
+                AppError::InternalError(anyhow::anyhow!("invalid SMTP_FROM address: {e}"))
+            })?,
+        })
+    }
+
+    /// 送信するメッセージを組み立てる。宛先のメールアドレスが不正な場合はエラーとする
+    pub fn build(&self, mail: &Mail) -> AppResult<Message> {
+        let to = Mailbox::new(
+            Some(mail.to.name.clone()),
+            mail.to.email.parse().map_err(|e| {
+                AppError::UnprocessableEntity(format!("宛先のメールアドレスが不正です: {e}"))
+            })?,
+        );
+        Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(&mail.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(mail.body.clone())
+            .map_err(internal)
+    }
+
+    pub(crate) async fn send(&self, message: Message) -> Result<(), smtp::Error> {
+        self.transport.send(message).await.map(|_| ())
+    }
+}
+
+fn internal(e: impl std::error::Error + Send + Sync + 'static) -> AppError {
+    AppError::InternalError(e.into())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use kernel::model::id::UserId;
+    use shared::config::SmtpCredentials;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+        sync::mpsc,
+    };
+
+    use super::*;
+
+    /// SMTPサーバーのスタブが受け取ったメール
+    #[derive(Debug)]
+    pub(crate) struct ReceivedMail {
+        /// `AUTH`コマンドの引数
+        pub auth: Option<String>,
+        pub rcpt: String,
+        pub data: String,
+    }
+
+    /// 受け取ったメールを記録するだけの、MailHogのようなSMTPサーバー
+    pub(crate) struct SmtpSink {
+        pub port: u16,
+        pub received: mpsc::UnboundedReceiver<ReceivedMail>,
+        /// `MAIL FROM`を受け取った回数
+        pub attempts: Arc<AtomicUsize>,
+    }
+
+    impl SmtpSink {
+        /// `rejections`には、最初の`MAIL FROM`から順に返すエラー応答を指定する
+        pub(crate) async fn start(rejections: &[&'static str]) -> anyhow::Result<Self> {
+            let listener = TcpListener::bind("127.0.0.1:0").await?;
+            let port = listener.local_addr()?.port();
+            let (tx, received) = mpsc::unbounded_channel();
+            let attempts = Arc::new(AtomicUsize::new(0));
+            let rejections = rejections.to_vec();
+            let counter = attempts.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let (tx, counter, rejections) =
+                        (tx.clone(), counter.clone(), rejections.clone());
+                    tokio::spawn(async move {
+                        let (reader, mut writer) = stream.into_split();
+                        let mut lines = BufReader::new(reader).lines();
+                        writer.write_all(b"220 localhost ESMTP sink\r\n").await?;
+                        let (mut auth, mut rcpt) = (None, String::new());
+                        while let Some(line) = lines.next_line().await? {
+                            let command = line.to_ascii_uppercase();
+                            let reply: &[u8] = if command.starts_with("EHLO") {
+                                b"250-localhost\r\n250-AUTH PLAIN LOGIN\r\n250 8BITMIME\r\n"
+                            } else if command.starts_with("AUTH") {
+                                auth = Some(line[5..].to_string());
+                                b"235 Authentication succeeded\r\n"
+                            } else if command.starts_with("MAIL FROM") {
+                                let attempt = counter.fetch_add(1, Ordering::SeqCst);
+                                match rejections.get(attempt) {
+                                    Some(rejection) => {
+                                        writer.write_all(rejection.as_bytes()).await?;
+                                        writer.write_all(b"\r\n").await?;
+                                        continue;
+                                    }
+                                    None => b"250 OK\r\n",
+                                }
+                            } else if command.starts_with("RCPT TO") {
+                                rcpt = line[8..].trim().to_string();
+                                b"250 OK\r\n"
+                            } else if command == "DATA" {
+                                writer
+                                    .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                                    .await?;
+                                let mut data = Vec::new();
+                                while let Some(line) = lines.next_line().await? {
+                                    if line == "." {
+                                        break;
+                                    }
+                                    data.push(line);
+                                }
+                                let _ = tx.send(ReceivedMail {
+                                    auth: auth.clone(),
+                                    rcpt: std::mem::take(&mut rcpt),
+                                    data: data.join("\n"),
+                                });
+                                b"250 OK\r\n"
+                            } else if command == "QUIT" {
+                                writer.write_all(b"221 Bye\r\n").await?;
+                                break;
+                            } else {
+                                b"250 OK\r\n"
+                            };
+                            writer.write_all(reply).await?;
+                        }
+                        anyhow::Ok(())
+                    });
+                }
+            });
+            Ok(Self {
+                port,
+                received,
+                attempts,
+            })
+        }
+
+        pub(crate) fn config(&self) -> SmtpConfig {
+            SmtpConfig {
+                host: "127.0.0.1".into(),
+                port: self.port,
+                from: "Library <library@example.com>".into(),
+                tls: SmtpTls::None,
+                credentials: None,
+            }
+        }
+    }
+
+    pub(crate) fn mail() -> Mail {
+        Mail {
+            to: Recipient {
+                user_id: UserId::new(),
+                name: "Eleazar Fig".into(),
+                email: "eleazar.fig@example.com".into(),
+            },
+            subject: "【図書管理】貸出が完了しました".into(),
+            body: "Eleazar Fig 様\n".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_with_credentials() -> anyhow::Result<()> {
+        let mut sink = SmtpSink::start(&[]).await?;
+        let mailer = SmtpMailer::new(SmtpConfig {
+            credentials: Some(SmtpCredentials {
+                username: "library".into(),
+                password: "secret".into(),
+            }),
+            ..sink.config()
+        })?;
+
+        mailer.send(mailer.build(&mail())?).await?;
+
+        let received = sink.received.recv().await.expect("mail should be received");
+        assert!(received.auth.is_some_and(|auth| auth.starts_with("PLAIN")));
+        assert_eq!(received.rcpt, "<eleazar.fig@example.com>");
+        assert!(received
+            .data
+            .contains("From: Library <library@example.com>"));
+        assert!(received
+            .data
+            .contains("To: \"Eleazar Fig\" <eleazar.fig@example.com>"));
+        assert!(received.data.contains("Subject: =?utf-8?"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_starttls_is_required() -> anyhow::Result<()> {
+        // STARTTLSに対応していないサーバーには、平文でメールを送らない
+        let sink = SmtpSink::start(&[]).await?;
+        let mailer = SmtpMailer::new(SmtpConfig {
+            host: "localhost".into(),
+            tls: SmtpTls::StartTls,
+            ..sink.config()
+        })?;
+        assert!(mailer.send(mailer.build(&mail())?).await.is_err());
+        assert_eq!(sink.attempts.load(Ordering::SeqCst), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_addresses_are_rejected() {
+        let config = SmtpConfig {
+            host: "localhost".into(),
+            port: 25,
+            from: "not an address".into(),
+            tls: SmtpTls::None,
+            credentials: None,
+        };
+        assert!(SmtpMailer::new(config.clone()).is_err());
+
+        let mailer = SmtpMailer::new(SmtpConfig {
+            from: "library@example.com".into(),
+            ..config
+        })
+        .unwrap();
+        let mut invalid = mail();
+        invalid.to.email = "eleazar.fig".into();
+        assert!(matches!(
+            mailer.build(&invalid),
+            Err(AppError::UnprocessableEntity(_))
+        ));
+    }
+}
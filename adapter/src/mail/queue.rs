@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use lettre::Message;
+use shared::{
+    config::MailConfig,
+    error::{AppError, AppResult},
+};
+use tokio::sync::mpsc;
+
+use super::{Mail, SmtpMailer};
+
+/// 送信待ちのメールを受け付け、バックグラウンドで送信するキュー
+/// 一時的なエラーで送信できなかったメールは、待ち時間を倍にしながら再送する
+#[derive(Clone)]
+pub struct MailQueue {
+    mailer: Arc<SmtpMailer>,
+    tx: mpsc::UnboundedSender<Message>,
+}
+
+impl MailQueue {
+    /// キューを作成し、メールを送信するタスクを起動する
+    pub fn spawn(mailer: SmtpMailer, config: MailConfig) -> Self {
+        let mailer = Arc::new(mailer);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let sender = mailer.clone();
+        tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                // 再送を待つ間も、他のメールの送信は続ける
+                tokio::spawn(deliver(sender.clone(), message, config.clone()));
+            }
+        });
+        Self { mailer, tx }
+    }
+
+    /// メールを送信待ちに追加する。送信の完了は待たない
+    pub fn enqueue(&self, mail: &Mail) -> AppResult<()> {
+        let message = self.mailer.build(mail)?;
+        self.tx
+            .send(message)
+            .map_err(|_| AppError::InternalError(anyhow::anyhow!("mail queue is closed")))
+    }
+}
+
+async fn deliver(mailer: Arc<SmtpMailer>, message: Message, config: MailConfig) {
+    let mut delay = config.retry_delay;
+    for attempt in 1..=config.max_attempts {
+        let e = match mailer.send(message.clone()).await {
+            Ok(()) => return,
+            Err(e) => e,
+        };
+        // 宛先の拒否などの恒久的なエラーは、再送しても成功しない
+        if e.is_permanent() || attempt == config.max_attempts {
+            tracing::error!(
+                error.message = %e,
+                attempt,
+                "Failed to send mail",
+            );
+            return;
+        }
+        tracing::warn!(
+            error.message = %e,
+            attempt,
+            retry_in = ?delay,
+            "Failed to send mail, retrying",
+        );
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::atomic::Ordering, time::Duration};
+
+    use shared::config::MailLocale;
+
+    use super::*;
+    use crate::mail::tests::{mail, SmtpSink};
+
+    fn config(max_attempts: u32) -> MailConfig {
+        MailConfig {
+            locale: MailLocale::Ja,
+            max_attempts,
+            retry_delay: Duration::from_millis(10),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() -> anyhow::Result<()> {
+        let mut sink =
+            SmtpSink::start(&["451 Temporary local problem", "421 Service not available"]).await?;
+        let queue = MailQueue::spawn(SmtpMailer::new(sink.config())?, config(3));
+
+        queue.enqueue(&mail())?;
+
+        let received = tokio::time::timeout(Duration::from_secs(5), sink.received.recv())
+            .await?
+            .expect("mail should be received");
+        assert_eq!(received.rcpt, "<eleazar.fig@example.com>");
+        assert_eq!(sink.attempts.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delivery_gives_up() -> anyhow::Result<()> {
+        // 恒久的なエラーは再送しない
+        let mut sink = SmtpSink::start(&["550 Mailbox unavailable"]).await?;
+        let queue = MailQueue::spawn(SmtpMailer::new(sink.config())?, config(3));
+        queue.enqueue(&mail())?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(sink.attempts.load(Ordering::SeqCst), 1);
+        assert!(sink.received.try_recv().is_err());
+
+        // 上限の回数まで失敗した場合も送信をあきらめる
+        let mut sink = SmtpSink::start(&["451 Try again later"; 5]).await?;
+        let queue = MailQueue::spawn(SmtpMailer::new(sink.config())?, config(2));
+        queue.enqueue(&mail())?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(sink.attempts.load(Ordering::SeqCst), 2);
+        assert!(sink.received.try_recv().is_err());
+        Ok(())
+    }
+}
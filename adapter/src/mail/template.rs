@@ -0,0 +1,300 @@
+use chrono::{DateTime, Utc};
+use kernel::model::{
+    checkout::Checkout,
+    notification::{Notification, NotificationMessage},
+    reminder::ReminderKind,
+};
+use shared::config::MailLocale;
+
+/// 通知を変換したメールの件名と本文
+#[derive(Debug, PartialEq, Eq)]
+pub struct RenderedMail {
+    pub subject: String,
+    pub body: String,
+}
+
+/// 通知を、指定された言語のメールの件名と本文に変換する
+pub fn render(notification: &Notification, locale: MailLocale) -> RenderedMail {
+    use MailLocale::*;
+    use NotificationMessage::*;
+
+    let (subject, lead, details) = match (&notification.message, locale) {
+        (Reminder { kind, checkouts }, Ja) => match kind {
+            ReminderKind::DueSoon => (
+                "返却期限が近づいています".to_string(),
+                "以下の蔵書の返却期限が近づいています。期限までに返却または貸出の延長をお願いします。",
+                due_list(checkouts, locale),
+            ),
+            ReminderKind::Overdue => (
+                "返却期限を過ぎた蔵書があります".to_string(),
+                "以下の蔵書は返却期限を過ぎています。速やかに返却をお願いします。",
+                due_list(checkouts, locale),
+            ),
+        },
+        (Reminder { kind, checkouts }, En) => match kind {
+            ReminderKind::DueSoon => (
+                "Your books are due soon".to_string(),
+                "The following books are due soon. Please return or renew them by the due date.",
+                due_list(checkouts, locale),
+            ),
+            ReminderKind::Overdue => (
+                "You have overdue books".to_string(),
+                "The following books are overdue. Please return them as soon as possible.",
+                due_list(checkouts, locale),
+            ),
+        },
+        (CheckoutConfirmed { checkouts }, Ja) => (
+            "貸出が完了しました".to_string(),
+            "以下の蔵書の貸出が完了しました。返却期限までに返却をお願いします。",
+            due_list(checkouts, locale),
+        ),
+        (CheckoutConfirmed { checkouts }, En) => (
+            "Your checkout is complete".to_string(),
+            "You have checked out the following books. Please return them by the due date.",
+            due_list(checkouts, locale),
+        ),
+        (ReturnConfirmed { checkouts }, Ja) => (
+            "返却が完了しました".to_string(),
+            "以下の蔵書の返却を受け付けました。ご利用ありがとうございました。",
+            return_list(checkouts, locale),
+        ),
+        (ReturnConfirmed { checkouts }, En) => (
+            "Your return is complete".to_string(),
+            "We have received the following books. Thank you for using the library.",
+            return_list(checkouts, locale),
+        ),
+        (
+            HoldReady {
+                title,
+                pickup_until,
+            },
+            Ja,
+        ) => (
+            "予約した蔵書の準備ができました".to_string(),
+            "ご予約の蔵書の受け取りの準備ができました。受け取り期限までにお越しください。",
+            format!("- {}\n受け取り期限: {}", title, format_time(pickup_until)),
+        ),
+        (
+            HoldReady {
+                title,
+                pickup_until,
+            },
+            En,
+        ) => (
+            "Your hold is ready for pickup".to_string(),
+            "The book you placed on hold is ready. Please pick it up by the date below.",
+            format!("- {}\nPick up by: {}", title, format_time(pickup_until)),
+        ),
+        (
+            PasswordReset {
+                reset_url,
+                expires_at,
+            },
+            Ja,
+        ) => (
+            "パスワードの再設定".to_string(),
+            "パスワードの再設定を受け付けました。以下のURLから新しいパスワードを設定してください。\nお心当たりがない場合は、このメールを破棄してください。",
+            format!("{}\n有効期限: {}", reset_url, format_time(expires_at)),
+        ),
+        (
+            PasswordReset {
+                reset_url,
+                expires_at,
+            },
+            En,
+        ) => (
+            "Reset your password".to_string(),
+            "We received a request to reset your password. Use the link below to set a new one.\nIf you did not request this, you can ignore this email.",
+            format!("{}\nThis link expires at: {}", reset_url, format_time(expires_at)),
+        ),
+    };
+
+    let name = &notification.recipient.name;
+    let (subject, greeting, footer) = match locale {
+        Ja => (
+            format!("【図書管理】{}", subject),
+            format!("{} 様", name),
+            "※このメールは送信専用のアドレスから送信しています。",
+        ),
+        En => (
+            format!("[Book Manager] {}", subject),
+            format!("Dear {},", name),
+            "This is an automated message. Please do not reply.",
+        ),
+    };
+    RenderedMail {
+        subject,
+        body: format!("{greeting}\n\n{lead}\n\n{details}\n\n{footer}\n"),
+    }
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+fn due_list(checkouts: &[Checkout], locale: MailLocale) -> String {
+    let label = match locale {
+        MailLocale::Ja => "返却期限",
+        MailLocale::En => "Due",
+    };
+    checkouts
+        .iter()
+        .map(|c| {
+            format!(
+                "- {} / {} ({}: {})",
+                c.book.title,
+                c.book.author,
+                label,
+                format_time(&c.due_at)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn return_list(checkouts: &[Checkout], locale: MailLocale) -> String {
+    let label = match locale {
+        MailLocale::Ja => "返却日時",
+        MailLocale::En => "Returned",
+    };
+    checkouts
+        .iter()
+        .map(|c| match &c.returned_at {
+            Some(returned_at) => format!(
+                "- {} / {} ({}: {})",
+                c.book.title,
+                c.book.author,
+                label,
+                format_time(returned_at)
+            ),
+            None => format!("- {} / {}", c.book.title, c.book.author),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use chrono::TimeZone;
+    use kernel::model::{
+        checkout::CheckoutBook,
+        id::{BookId, CheckoutId, UserId},
+        notification::Recipient,
+    };
+
+    use super::*;
+
+    fn time() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 20, 9, 0, 0).unwrap()
+    }
+
+    pub(crate) fn notification(message: NotificationMessage) -> Notification {
+        Notification {
+            recipient: Recipient {
+                user_id: UserId::new(),
+                name: "Eleazar Fig".into(),
+                email: "eleazar.fig@example.com".into(),
+            },
+            message,
+        }
+    }
+
+    pub(crate) fn checkouts() -> Vec<Checkout> {
+        vec![Checkout {
+            id: CheckoutId::new(),
+            checked_out_by: UserId::new(),
+            checked_out_at: time(),
+            returned_at: None,
+            due_at: time(),
+            renewal_count: 0,
+            checked_out_via: None,
+            returned_via: None,
+            book: CheckoutBook {
+                book_id: BookId::new(),
+                title: "実践Rustプログラミング入門".into(),
+                author: "初田直也".into(),
+                isbn: "9784798061702".into(),
+            },
+        }]
+    }
+
+    #[test]
+    fn test_render_reminder() {
+        let due_soon = notification(NotificationMessage::Reminder {
+            kind: ReminderKind::DueSoon,
+            checkouts: checkouts(),
+        });
+        let rendered = render(&due_soon, MailLocale::Ja);
+        assert_eq!(rendered.subject, "【図書管理】返却期限が近づいています");
+        assert!(rendered.body.starts_with("Eleazar Fig 様\n"));
+        assert!(rendered
+            .body
+            .contains("- 実践Rustプログラミング入門 / 初田直也 (返却期限: 2026-10-20 09:00 UTC)"));
+
+        let rendered = render(&due_soon, MailLocale::En);
+        assert_eq!(rendered.subject, "[Book Manager] Your books are due soon");
+        assert!(rendered.body.starts_with("Dear Eleazar Fig,\n"));
+        assert!(rendered.body.contains("(Due: 2026-10-20 09:00 UTC)"));
+
+        let overdue = notification(NotificationMessage::Reminder {
+            kind: ReminderKind::Overdue,
+            checkouts: checkouts(),
+        });
+        assert_eq!(
+            render(&overdue, MailLocale::Ja).subject,
+            "【図書管理】返却期限を過ぎた蔵書があります"
+        );
+    }
+
+    #[test]
+    fn test_render_checkout_and_return_confirmation() {
+        let checkout = notification(NotificationMessage::CheckoutConfirmed {
+            checkouts: checkouts(),
+        });
+        assert_eq!(
+            render(&checkout, MailLocale::Ja).subject,
+            "【図書管理】貸出が完了しました"
+        );
+        assert_eq!(
+            render(&checkout, MailLocale::En).subject,
+            "[Book Manager] Your checkout is complete"
+        );
+
+        let mut returned = checkouts();
+        returned[0].returned_at = Some(time());
+        let rendered = render(
+            &notification(NotificationMessage::ReturnConfirmed {
+                checkouts: returned,
+            }),
+            MailLocale::En,
+        );
+        assert_eq!(rendered.subject, "[Book Manager] Your return is complete");
+        assert!(rendered.body.contains("(Returned: 2026-10-20 09:00 UTC)"));
+    }
+
+    #[test]
+    fn test_render_hold_ready_and_password_reset() {
+        let hold = notification(NotificationMessage::HoldReady {
+            title: "Rust in Action".into(),
+            pickup_until: time(),
+        });
+        let rendered = render(&hold, MailLocale::Ja);
+        assert_eq!(
+            rendered.subject,
+            "【図書管理】予約した蔵書の準備ができました"
+        );
+        assert!(rendered
+            .body
+            .contains("- Rust in Action\n受け取り期限: 2026-10-20 09:00 UTC"));
+
+        let reset = notification(NotificationMessage::PasswordReset {
+            reset_url: "https://library.example.com/reset?token=abc".into(),
+            expires_at: time(),
+        });
+        let rendered = render(&reset, MailLocale::En);
+        assert_eq!(rendered.subject, "[Book Manager] Reset your password");
+        assert!(rendered
+            .body
+            .contains("https://library.example.com/reset?token=abc\nThis link expires at: 2026-10-20 09:00 UTC"));
+    }
+}
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{model::notification::Notification, notifier::Notifier};
+use shared::{config::MailLocale, error::AppResult};
+
+use crate::mail::template::render;
+
+/// 通知を送信せず、ログに出力する
+/// SMTPサーバーが設定されていない開発環境などで用いる
+#[derive(new)]
+pub struct LogNotifier {
+    locale: MailLocale,
+}
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, notification: &Notification) -> AppResult<()> {
+        let rendered = render(notification, self.locale);
+        tracing::info!(
+            notification.to = %notification.recipient.email,
+            notification.subject = %rendered.subject,
+            notification.body = %rendered.body,
+            "Notification",
+        );
+        Ok(())
+    }
+}
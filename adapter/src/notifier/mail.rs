@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{model::notification::Notification, notifier::Notifier};
+use shared::{config::MailLocale, error::AppResult};
+
+use crate::mail::{queue::MailQueue, template::render, Mail};
+
+/// 通知をメールに変換し、送信待ちのキューに追加する
+#[derive(new)]
+pub struct MailNotifier {
+    queue: MailQueue,
+    locale: MailLocale,
+}
+
+#[async_trait]
+impl Notifier for MailNotifier {
+    async fn notify(&self, notification: &Notification) -> AppResult<()> {
+        let rendered = render(notification, self.locale);
+        self.queue.enqueue(&Mail {
+            to: notification.recipient.clone(),
+            subject: rendered.subject,
+            body: rendered.body,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use kernel::model::notification::NotificationMessage;
+    use shared::config::MailConfig;
+
+    use super::*;
+    use crate::mail::{
+        template::tests::{checkouts, notification},
+        tests::SmtpSink,
+        SmtpMailer,
+    };
+
+    #[tokio::test]
+    async fn test_mail_notifier_sends_localized_mail() -> anyhow::Result<()> {
+        let mut sink = SmtpSink::start(&[]).await?;
+        let queue = MailQueue::spawn(SmtpMailer::new(sink.config())?, MailConfig::default());
+        let notifier = MailNotifier::new(queue, MailLocale::En);
+
+        notifier
+            .notify(&notification(NotificationMessage::CheckoutConfirmed {
+                checkouts: checkouts(),
+            }))
+            .await?;
+
+        let received = tokio::time::timeout(Duration::from_secs(5), sink.received.recv())
+            .await?
+            .expect("mail should be received");
+        assert_eq!(received.rcpt, "<eleazar.fig@example.com>");
+        assert!(received
+            .data
+            .contains("Subject: [Book Manager] Your checkout is complete"));
+        Ok(())
+    }
+}
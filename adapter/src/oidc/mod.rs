@@ -0,0 +1,470 @@
+pub mod model;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use kernel::model::{auth::OidcIdentity, role::Role};
+use reqwest::Url;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use shared::{
+    config::OidcConfig,
+    error::{AppError, AppResult},
+};
+use tokio::sync::{OnceCell, RwLock};
+
+use self::model::PendingAuthorization;
+
+/// IDプロバイダーのディスカバリードキュメントのうち、利用する項目
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    name: Option<String>,
+    preferred_username: Option<String>,
+    /// ロールの判定に用いる任意のクレーム
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
+}
+
+/// OpenID Connectの認可コードフロー(PKCE)を扱うクライアント
+pub struct OidcClient {
+    config: OidcConfig,
+    http: reqwest::Client,
+    metadata: OnceCell<ProviderMetadata>,
+    jwks: RwLock<Option<JwkSet>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            metadata: OnceCell::new(),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    /// 利用者をリダイレクトさせる認可エンドポイントのURLを組み立てる
+    pub async fn authorization_url(&self, pending: &PendingAuthorization) -> AppResult<String> {
+        let metadata = self.metadata().await?;
+        let mut url = Url::parse(&metadata.authorization_endpoint)
+            .map_err(|e| AppError::InternalError(e.into()))?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_url)
+            .append_pair("scope", &self.config.scopes.join(" "))
+            .append_pair("state", &pending.state)
+            .append_pair("nonce", &pending.nonce)
+            .append_pair("code_challenge", &pending.code_challenge())
+            .append_pair("code_challenge_method", "S256");
+        Ok(url.into())
+    }
+
+    /// 認可コードをIDトークンと交換し、検証したうえで利用者の情報を返す
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        pending: &PendingAuthorization,
+    ) -> AppResult<OidcIdentity> {
+        let metadata = self.metadata().await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_url.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", pending.code_verifier.as_str()),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let res = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AppError::InternalError(e.into()))?;
+        // 認可コードやコード検証子が不正な場合は、IDプロバイダーがエラーを返す
+        if !res.status().is_success() {
+            tracing::warn!(status = %res.status(), "OIDC token request was rejected");
+            return Err(AppError::UnauthenticatedError);
+        }
+        let token: TokenResponse = res
+            .json()
+            .await
+            .map_err(|e| AppError::InternalError(e.into()))?;
+
+        let claims = self.verify_id_token(&token.id_token, metadata).await?;
+        if claims.nonce.as_deref() != Some(pending.nonce.as_str()) {
+            return Err(AppError::UnauthenticatedError);
+        }
+
+        let role = self.map_role(&claims);
+        let IdTokenClaims {
+            iss,
+            sub,
+            email,
+            email_verified,
+            name,
+            preferred_username,
+            ..
+        } = claims;
+        let email = email.ok_or_else(|| {
+            AppError::UnprocessableEntity("IDトークンにメールアドレスが含まれていません".into())
+        })?;
+        let name = name.or(preferred_username).unwrap_or_else(|| email.clone());
+
+        Ok(OidcIdentity {
+            issuer: iss,
+            subject: sub,
+            email,
+            email_verified,
+            name,
+            role,
+        })
+    }
+
+    async fn metadata(&self) -> AppResult<&ProviderMetadata> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.config.issuer_url.trim_end_matches('/')
+                );
+                let metadata: ProviderMetadata = self.get_json(&url).await?;
+                // ディスカバリードキュメントのissuerは設定値と一致しなければならない
+                if metadata.issuer.trim_end_matches('/')
+                    != self.config.issuer_url.trim_end_matches('/')
+                {
+                    return Err(AppError::InternalError(anyhow::anyhow!(
+                        "OIDC issuer mismatch: {}",
+                        metadata.issuer
+                    )));
+                }
+                Ok(metadata)
+            })
+            .await
+    }
+
+    async fn verify_id_token(
+        &self,
+        id_token: &str,
+        metadata: &ProviderMetadata,
+    ) -> AppResult<IdTokenClaims> {
+        let header =
+            jsonwebtoken::decode_header(id_token).map_err(|_| AppError::UnauthenticatedError)?;
+        // 公開鍵で検証できる署名方式のみ受け付ける
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(AppError::UnauthenticatedError);
+        }
+        let key = self
+            .decoding_key(header.kid.as_deref(), &metadata.jwks_uri)
+            .await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| {
+                tracing::warn!(error = %e, "OIDC ID token validation failed");
+                AppError::UnauthenticatedError
+            })
+    }
+
+    /// JWKSから署名検証用の鍵を取得する
+    /// 鍵が見つからない場合は、鍵のローテーションを考慮してJWKSを取得し直す
+    async fn decoding_key(&self, kid: Option<&str>, jwks_uri: &str) -> AppResult<DecodingKey> {
+        if let Some(jwks) = self.jwks.read().await.as_ref() {
+            if let Some(key) = find_key(jwks, kid)? {
+                return Ok(key);
+            }
+        }
+
+        let jwks: JwkSet = self.get_json(jwks_uri).await?;
+        let key = find_key(&jwks, kid)?;
+        *self.jwks.write().await = Some(jwks);
+        key.ok_or(AppError::UnauthenticatedError)
+    }
+
+    /// 設定されたクレームの値からロールを判定する
+    fn map_role(&self, claims: &IdTokenClaims) -> Option<Role> {
+        let claim = self.config.role_claim.as_ref()?;
+        let is_admin = match claims.other.get(claim) {
+            Some(serde_json::Value::String(v)) => self.config.admin_role_values.contains(v),
+            Some(serde_json::Value::Array(values)) => values.iter().any(|v| {
+                v.as_str()
+                    .is_some_and(|v| self.config.admin_role_values.iter().any(|a| a == v))
+            }),
+            _ => false,
+        };
+        Some(if is_admin { Role::Admin } else { Role::User })
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> AppResult<T> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| AppError::InternalError(e.into()))?
+            .json()
+            .await
+            .map_err(|e| AppError::InternalError(e.into()))
+    }
+}
+
+fn find_key(jwks: &JwkSet, kid: Option<&str>) -> AppResult<Option<DecodingKey>> {
+    let jwk = match kid {
+        Some(kid) => jwks.find(kid),
+        // kidが指定されていない場合は、鍵が1つだけのときに限りその鍵を用いる
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    };
+    jwk.map(DecodingKey::from_jwk)
+        .transpose()
+        .map_err(|e| AppError::InternalError(e.into()))
+}
+
+/// PKCEのコードチャレンジ(S256)を求める
+pub(crate) fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{
+        extract::State,
+        http::StatusCode,
+        routing::{get, post},
+        Form, Json, Router,
+    };
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde_json::json;
+
+    use super::*;
+
+    const MOCK_KEY_ID: &str = "mock-key";
+    const MOCK_KEY_MODULUS: &str = "p1w3gsRdBkET1goy4wICfY9ksn2EdylAx9lBHdKQxdyK5ED0SPfgHyE_DRrZBEuN49SDxtMMaSL-dj0WJoEqRRUI9YtG73H5ekJFaQHPLGBHNvw-4By-kcmx_S1oyh4mrdYb3g2wYjQVWVDdUaf8nYViZIFGhUY6u0miw98p44YMxFkqvRJYXkMROVU0wTCgR9yWb8ViTICMi9ehwa53Yu3i_z0Nwi5u7ihWJgoQzbZgC_MPRLNoZ82SqOEvg6sy1agUmAAuukOF-phLFTOgKQ-vNX3vBizf3v_cFQMNTEAGeNicRby-0vaoJiyON-DlFdLyzpAWD7K6-LkkHDmE-w";
+    const MOCK_CODE: &str = "mock-authorization-code";
+    const CLIENT_ID: &str = "bookmanager";
+
+    /// 認可エンドポイントで受け付けた内容と、発行するIDトークンのクレーム
+    #[derive(Default)]
+    struct MockIssuerState {
+        issuer: String,
+        code_challenge: Option<String>,
+        nonce: Option<String>,
+        claims: serde_json::Value,
+    }
+    type SharedState = Arc<Mutex<MockIssuerState>>;
+
+    async fn discovery(State(state): State<SharedState>) -> Json<serde_json::Value> {
+        let issuer = state.lock().unwrap().issuer.clone();
+        Json(json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{issuer}/authorize"),
+            "token_endpoint": format!("{issuer}/token"),
+            "jwks_uri": format!("{issuer}/jwks"),
+        }))
+    }
+
+    async fn jwks() -> Json<serde_json::Value> {
+        Json(json!({
+            "keys": [{
+                "kty": "RSA",
+                "use": "sig",
+                "alg": "RS256",
+                "kid": MOCK_KEY_ID,
+                "n": MOCK_KEY_MODULUS,
+                "e": "AQAB",
+            }]
+        }))
+    }
+
+    async fn token(
+        State(state): State<SharedState>,
+        Form(form): Form<HashMap<String, String>>,
+    ) -> Result<Json<serde_json::Value>, StatusCode> {
+        let state = state.lock().unwrap();
+        let verifier = form.get("code_verifier").ok_or(StatusCode::BAD_REQUEST)?;
+        if form.get("grant_type").map(String::as_str) != Some("authorization_code")
+            || form.get("code").map(String::as_str) != Some(MOCK_CODE)
+            || state.code_challenge.as_deref() != Some(code_challenge(verifier).as_str())
+        {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let mut claims = json!({
+            "iss": state.issuer,
+            "aud": CLIENT_ID,
+            "sub": "mock-subject",
+            "exp": chrono::Utc::now().timestamp() + 300,
+            "nonce": state.nonce,
+            "email": "sso.user@example.com",
+            "email_verified": true,
+            "name": "SSO User",
+        });
+        claims
+            .as_object_mut()
+            .unwrap()
+            .extend(state.claims.as_object().cloned().unwrap_or_default());
+
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(MOCK_KEY_ID.into());
+        let key =
+            EncodingKey::from_rsa_pem(include_bytes!("fixtures/mock_issuer_key.pem")).unwrap();
+        let id_token = jsonwebtoken::encode(&header, &claims, &key).unwrap();
+        Ok(Json(json!({
+            "access_token": "mock-access-token",
+            "token_type": "Bearer",
+            "id_token": id_token,
+        })))
+    }
+
+    /// ローカルにモックのIDプロバイダーを起動する
+    async fn spawn_mock_issuer(claims: serde_json::Value) -> anyhow::Result<SharedState> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let state = Arc::new(Mutex::new(MockIssuerState {
+            issuer: format!("http://{}", listener.local_addr()?),
+            claims,
+            ..Default::default()
+        }));
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/jwks", get(jwks))
+            .route("/token", post(token))
+            .with_state(state.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Ok(state)
+    }
+
+    fn client_for(state: &SharedState, role_claim: Option<&str>) -> OidcClient {
+        OidcClient::new(OidcConfig {
+            issuer_url: state.lock().unwrap().issuer.clone(),
+            client_id: CLIENT_ID.into(),
+            client_secret: None,
+            redirect_url: "http://localhost:3000/oidc/callback".into(),
+            scopes: vec!["openid".into(), "email".into()],
+            role_claim: role_claim.map(String::from),
+            admin_role_values: vec!["library-admins".into()],
+        })
+    }
+
+    /// 利用者がIDプロバイダーで認証を終え、認可コードを受け取るまでを再現する
+    async fn authorize(
+        client: &OidcClient,
+        state: &SharedState,
+    ) -> anyhow::Result<PendingAuthorization> {
+        let pending = PendingAuthorization::new();
+        let url = Url::parse(&client.authorization_url(&pending).await?)?;
+        let params: HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(params["response_type"], "code");
+        assert_eq!(params["client_id"], CLIENT_ID);
+        assert_eq!(params["state"], pending.state);
+        assert_eq!(params["code_challenge_method"], "S256");
+
+        let mut state = state.lock().unwrap();
+        state.code_challenge = Some(params["code_challenge"].clone());
+        state.nonce = Some(params["nonce"].clone());
+        Ok(pending)
+    }
+
+    #[tokio::test]
+    async fn test_exchange_code_returns_identity() -> anyhow::Result<()> {
+        let state = spawn_mock_issuer(json!({ "groups": ["staff", "library-admins"] })).await?;
+        let client = client_for(&state, Some("groups"));
+        let pending = authorize(&client, &state).await?;
+
+        let identity = client.exchange_code(MOCK_CODE, &pending).await?;
+        assert_eq!(identity.issuer, state.lock().unwrap().issuer);
+        assert_eq!(identity.subject, "mock-subject");
+        assert_eq!(identity.email, "sso.user@example.com");
+        assert!(identity.email_verified);
+        assert_eq!(identity.name, "SSO User");
+        assert_eq!(identity.role, Some(Role::Admin));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_role_is_not_mapped_without_role_claim() -> anyhow::Result<()> {
+        let state = spawn_mock_issuer(json!({ "groups": "library-admins" })).await?;
+        let client = client_for(&state, None);
+        let pending = authorize(&client, &state).await?;
+
+        let identity = client.exchange_code(MOCK_CODE, &pending).await?;
+        assert_eq!(identity.role, None);
+
+        let client = client_for(&state, Some("roles"));
+        let pending = authorize(&client, &state).await?;
+        let identity = client.exchange_code(MOCK_CODE, &pending).await?;
+        assert_eq!(identity.role, Some(Role::User));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_exchange_code_rejects_wrong_verifier_and_nonce() -> anyhow::Result<()> {
+        let state = spawn_mock_issuer(json!({})).await?;
+        let client = client_for(&state, None);
+        let pending = authorize(&client, &state).await?;
+
+        // コード検証子が一致しない
+        let forged = PendingAuthorization::new();
+        let res = client.exchange_code(MOCK_CODE, &forged).await;
+        assert!(matches!(res, Err(AppError::UnauthenticatedError)));
+
+        // IDトークンのnonceが一致しない
+        let replayed = PendingAuthorization {
+            nonce: "other-nonce".into(),
+            ..pending
+        };
+        let res = client.exchange_code(MOCK_CODE, &replayed).await;
+        assert!(matches!(res, Err(AppError::UnauthenticatedError)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_exchange_code_rejects_invalid_audience() -> anyhow::Result<()> {
+        let state = spawn_mock_issuer(json!({ "aud": "another-client" })).await?;
+        let client = client_for(&state, None);
+        let pending = authorize(&client, &state).await?;
+
+        let res = client.exchange_code(MOCK_CODE, &pending).await;
+        assert!(matches!(res, Err(AppError::UnauthenticatedError)));
+
+        Ok(())
+    }
+}
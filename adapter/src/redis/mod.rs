@@ -1,7 +1,11 @@
 pub mod model;
 
-use redis::{AsyncCommands, Client};
+use redis::{
+    streams::{StreamMaxlen, StreamRangeReply},
+    AsyncCommands, Client,
+};
 use shared::{config::RedisConfig, error::AppResult};
+use tokio_stream::{Stream, StreamExt};
 
 use self::model::{RedisKey, RedisValue};
 
@@ -30,6 +34,28 @@ impl RedisClient {
         result.map(T::Value::try_from).transpose()
     }
 
+    /// キーを指定してバリューを取り出し、同時にキーを削除する
+    /// 一度しか使えない値の取り出しに用いる
+    pub async fn take<T: RedisKey>(&self, key: &T) -> AppResult<Option<T::Value>> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let result: Option<String> = conn.get_del(key.inner()).await?;
+        result.map(T::Value::try_from).transpose()
+    }
+
+    /// キーに格納された数値に1を加え、加算後の値を返す
+    /// キーが存在しない場合は0とみなし、期限(ttl)を設定する
+    pub async fn incr_ex<T: RedisKey>(&self, key: &T, ttl: u64) -> AppResult<u64> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let (count,): (u64,) = redis::pipe()
+            .atomic()
+            .incr(key.inner(), 1)
+            .expire(key.inner(), ttl as i64)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        Ok(count)
+    }
+
     /// キーを指定して、Redisから該当のキーとバリューを削除する
     pub async fn delete<T: RedisKey>(&self, key: &T) -> AppResult<()> {
         let mut conn = self.client.get_multiplexed_async_connection().await?;
@@ -37,6 +63,117 @@ impl RedisClient {
         Ok(())
     }
 
+    /// 期限付きでキーとバリューを保存し、同時にキーを索引(セット)に登録する
+    /// 索引の期限は、最後に登録したキーの期限まで延長される
+    pub async fn set_ex_indexed<T: RedisKey>(
+        &self,
+        key: &T,
+        value: &T::Value,
+        ttl: u64,
+        index: &str,
+    ) -> AppResult<()> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        redis::pipe()
+            .atomic()
+            .set_ex(key.inner(), value.inner(), ttl)
+            .ignore()
+            .sadd(index, key.inner())
+            .ignore()
+            .expire(index, ttl as i64)
+            .ignore()
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    /// キーを削除し、同時に索引からも取り除く
+    pub async fn delete_indexed<T: RedisKey>(&self, key: &T, index: &str) -> AppResult<()> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        redis::pipe()
+            .atomic()
+            .del(key.inner())
+            .ignore()
+            .srem(index, key.inner())
+            .ignore()
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    /// 索引に登録されたキーをすべて削除し、削除したキーの数を返す
+    /// 期限切れですでに削除されていたキーは数えない
+    pub async fn delete_all_indexed(&self, index: &str) -> AppResult<u64> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let keys: Vec<String> = conn.smembers(index).await?;
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        let (deleted,): (u64,) = redis::pipe()
+            .atomic()
+            .del(&keys)
+            .srem(index, &keys)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        Ok(deleted)
+    }
+
+    /// ストリームにエントリを追加し、割り当てられたエントリ IDを返す
+    /// ストリームが`max_len`件程度を超えると、古いエントリから削除される
+    pub async fn append_to_stream(
+        &self,
+        key: &str,
+        max_len: usize,
+        field: &str,
+        value: &str,
+    ) -> AppResult<String> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let id = conn
+            .xadd_maxlen(key, StreamMaxlen::Approx(max_len), "*", &[(field, value)])
+            .await?;
+        Ok(id)
+    }
+
+    /// ストリームから、エントリ ID が`after`より後のエントリを古い順に`count`件まで取り出す
+    /// エントリ IDと、`field`に格納された値の組を返す
+    pub async fn read_stream_after(
+        &self,
+        key: &str,
+        after: &str,
+        count: usize,
+        field: &str,
+    ) -> AppResult<Vec<(String, String)>> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let reply: StreamRangeReply = conn
+            .xrange_count(key, format!("({after}"), "+", count)
+            .await?;
+        Ok(reply
+            .ids
+            .into_iter()
+            .filter_map(|entry| {
+                let value = entry.get(field)?;
+                Some((entry.id, value))
+            })
+            .collect())
+    }
+
+    /// チャンネルにメッセージを送信する
+    pub async fn publish(&self, channel: &str, message: &str) -> AppResult<()> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        conn.publish::<_, _, ()>(channel, message).await?;
+        Ok(())
+    }
+
+    /// チャンネルを購読し、届いたメッセージを順に返すストリームを返す
+    /// 接続が切れるとストリームは終了する
+    pub async fn subscribe(&self, channel: &str) -> AppResult<impl Stream<Item = String>> {
+        let mut pubsub = self.client.get_async_pubsub().await?;
+        pubsub.subscribe(channel).await?;
+        Ok(pubsub
+            .into_on_message()
+            .filter_map(|msg| msg.get_payload::<String>().ok()))
+    }
+
     /// Redis接続確認
     pub async fn try_connect(&self) -> AppResult<()> {
         let _ = self.client.get_multiplexed_async_connection().await?;
@@ -0,0 +1,212 @@
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    model::{
+        api_key::{
+            event::{CreateApiKey, RevokeApiKey},
+            ApiKey, ApiKeySecret, IssuedApiKey,
+        },
+        id::{ApiKeyId, UserId},
+    },
+    repository::api_key::ApiKeyRepository,
+};
+use shared::error::{AppError, AppResult};
+
+use crate::database::{
+    model::api_key::{hash_api_key, ApiKeyRow},
+    ConnectionPool,
+};
+
+#[derive(new)]
+pub struct ApiKeyRepositoryImpl {
+    db: ConnectionPool,
+}
+
+#[async_trait]
+impl ApiKeyRepository for ApiKeyRepositoryImpl {
+    async fn create(&self, event: CreateApiKey) -> AppResult<IssuedApiKey> {
+        let CreateApiKey {
+            user_id,
+            name,
+            scopes,
+            secret,
+        } = event;
+        let api_key_id = ApiKeyId::new();
+        let key_prefix = secret.prefix();
+        let key_hash = hash_api_key(&secret.0);
+        let scope_names: Vec<String> = scopes.iter().map(|s| s.as_ref().to_string()).collect();
+
+        let row = sqlx::query_as!(
+            ApiKeyRow,
+            r#"
+                INSERT INTO api_keys(api_key_id, user_id, name, key_prefix, key_hash, scopes)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING
+                    api_key_id,
+                    user_id,
+                    name,
+                    key_prefix,
+                    scopes,
+                    created_at,
+                    last_used_at,
+                    revoked_at
+            "#,
+            api_key_id as _,
+            user_id as _,
+            name,
+            key_prefix,
+            key_hash,
+            &scope_names
+        )
+        .fetch_one(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        Ok(IssuedApiKey {
+            api_key: ApiKey::try_from(row)?,
+            secret,
+        })
+    }
+
+    async fn find_by_user_id(&self, user_id: UserId) -> AppResult<Vec<ApiKey>> {
+        sqlx::query_as!(
+            ApiKeyRow,
+            r#"
+                SELECT
+                    api_key_id,
+                    user_id,
+                    name,
+                    key_prefix,
+                    scopes,
+                    created_at,
+                    last_used_at,
+                    revoked_at
+                FROM api_keys
+                WHERE user_id = $1
+                ORDER BY created_at DESC
+            "#,
+            user_id as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .into_iter()
+        .map(ApiKey::try_from)
+        .collect()
+    }
+
+    async fn revoke(&self, event: RevokeApiKey) -> AppResult<()> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE api_keys
+                SET revoked_at = CURRENT_TIMESTAMP(3)
+                WHERE
+                    api_key_id = $1
+                    AND user_id = $2
+                    AND revoked_at IS NULL
+            "#,
+            event.api_key_id as _,
+            event.requested_user as _
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError(
+                "Specified api key not found".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn verify(&self, secret: &ApiKeySecret) -> AppResult<Option<ApiKey>> {
+        // 照合と同時に最終利用日時を更新する
+        sqlx::query_as!(
+            ApiKeyRow,
+            r#"
+                UPDATE api_keys
+                SET last_used_at = CURRENT_TIMESTAMP(3)
+                WHERE key_hash = $1 AND revoked_at IS NULL
+                RETURNING
+                    api_key_id,
+                    user_id,
+                    name,
+                    key_prefix,
+                    scopes,
+                    created_at,
+                    last_used_at,
+                    revoked_at
+            "#,
+            hash_api_key(&secret.0)
+        )
+        .fetch_optional(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .map(ApiKey::try_from)
+        .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use kernel::model::api_key::ApiKeyScope;
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_create_verify_revoke_api_key(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = ApiKeyRepositoryImpl::new(ConnectionPool::new(pool));
+        // fixtures/common.sqlに記載のユーザーID
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        let issued = repo
+            .create(CreateApiKey::new(
+                user_id,
+                "slack-bot".into(),
+                vec![ApiKeyScope::Read, ApiKeyScope::Write],
+            ))
+            .await?;
+        assert_eq!(issued.api_key.name, "slack-bot");
+        assert!(issued.api_key.last_used_at.is_none());
+        assert!(issued.secret.0.starts_with(&issued.api_key.key_prefix));
+
+        // 照合すると最終利用日時が記録される
+        let verified = repo
+            .verify(&issued.secret)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("api key not verified"))?;
+        assert_eq!(verified.id, issued.api_key.id);
+        assert_eq!(verified.user_id, user_id);
+        assert!(verified.has_scope(ApiKeyScope::Write));
+        assert!(verified.last_used_at.is_some());
+
+        // 誤ったキーは照合できない
+        let wrong = ApiKeySecret(format!("{}x", issued.secret.0));
+        assert!(repo.verify(&wrong).await?.is_none());
+
+        // 他のユーザーのキーは失効できない
+        let res = repo
+            .revoke(RevokeApiKey {
+                api_key_id: issued.api_key.id,
+                requested_user: UserId::new(),
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::NotFoundError(_))));
+
+        repo.revoke(RevokeApiKey {
+            api_key_id: issued.api_key.id,
+            requested_user: user_id,
+        })
+        .await?;
+        assert!(repo.verify(&issued.secret).await?.is_none());
+
+        let keys = repo.find_by_user_id(user_id).await?;
+        assert_eq!(keys.len(), 1);
+        assert!(keys[0].revoked_at.is_some());
+
+        Ok(())
+    }
+}
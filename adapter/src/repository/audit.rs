@@ -0,0 +1,231 @@
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    model::{
+        audit::{event::CreateAuditLog, AuditLog, AuditLogListOptions},
+        id::{AuditLogId, UserId},
+        list::PaginatedList,
+    },
+    repository::audit::AuditLogRepository,
+};
+use shared::error::{AppError, AppResult};
+use sqlx::PgConnection;
+
+use crate::database::{model::audit::PaginatedAuditLogRow, ConnectionPool};
+
+#[derive(new)]
+pub struct AuditLogRepositoryImpl {
+    db: ConnectionPool,
+}
+
+/// 監査ログを記録する
+///
+/// 状態を変更するトランザクションの中で呼び出すことで、
+/// 変更がコミットされた場合にのみ監査ログが残るようにする
+pub(crate) async fn record_audit_log(
+    conn: &mut PgConnection,
+    event: CreateAuditLog,
+) -> AppResult<()> {
+    let CreateAuditLog {
+        actor_id,
+        action,
+        target_type,
+        target_id,
+        before,
+        after,
+        request_id,
+        ip_address,
+    } = event;
+
+    sqlx::query!(
+        r#"
+            INSERT INTO audit_logs(
+                audit_log_id, actor_id, action, target_type, target_id,
+                before, after, request_id, ip_address
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+        AuditLogId::new() as _,
+        actor_id as _,
+        action.as_ref(),
+        target_type.as_ref(),
+        target_id,
+        before,
+        after,
+        request_id,
+        ip_address
+    )
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseOperationError)?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl AuditLogRepository for AuditLogRepositoryImpl {
+    async fn create(&self, event: CreateAuditLog) -> AppResult<()> {
+        let mut conn = self
+            .db
+            .inner_ref()
+            .acquire()
+            .await
+            .map_err(AppError::DatabaseOperationError)?;
+        record_audit_log(&mut conn, event).await
+    }
+
+    async fn find_all(&self, options: AuditLogListOptions) -> AppResult<PaginatedList<AuditLog>> {
+        let AuditLogListOptions {
+            actor_id,
+            target_type,
+            target_id,
+            from,
+            to,
+            limit,
+            offset,
+        } = options;
+
+        let rows = sqlx::query_as!(
+            PaginatedAuditLogRow,
+            r#"
+                SELECT
+                    COUNT(*) OVER() AS "total!",
+                    audit_log_id,
+                    actor_id AS "actor_id: UserId",
+                    action,
+                    target_type,
+                    target_id,
+                    before,
+                    after,
+                    request_id,
+                    ip_address,
+                    occurred_at
+                FROM audit_logs
+                WHERE
+                    ($1::UUID IS NULL OR actor_id = $1)
+                    AND ($2::VARCHAR IS NULL OR target_type = $2)
+                    AND ($3::VARCHAR IS NULL OR target_id = $3)
+                    AND ($4::TIMESTAMPTZ IS NULL OR occurred_at >= $4)
+                    AND ($5::TIMESTAMPTZ IS NULL OR occurred_at < $5)
+                ORDER BY occurred_at DESC, audit_log_id
+                LIMIT $6
+                OFFSET $7
+            "#,
+            actor_id as _,
+            target_type.as_ref().map(AsRef::<str>::as_ref),
+            target_id,
+            from,
+            to,
+            limit,
+            offset,
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        let total = rows.first().map(|r| r.total).unwrap_or_default();
+        let items = rows
+            .into_iter()
+            .map(AuditLog::try_from)
+            .collect::<AppResult<_>>()?;
+
+        Ok(PaginatedList {
+            total,
+            limit,
+            offset,
+            items,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, SubsecRound, Utc};
+    use kernel::model::audit::{AuditAction, AuditTargetType};
+
+    use super::*;
+
+    #[sqlx::test]
+    async fn test_create_and_filter(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = AuditLogRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let admin = UserId::new();
+        let book_id = "9890736e-a4e4-461a-a77d-eac3517ef11b".to_string();
+        // 日時はミリ秒の精度で丸めて保存されるため、記録時刻より確実に前の時刻を用いる
+        let before_all = (Utc::now() - Duration::seconds(1)).trunc_subsecs(3);
+
+        repo.create(CreateAuditLog {
+            before: Some(serde_json::json!({"title": "Old"})),
+            after: Some(serde_json::json!({"title": "New"})),
+            request_id: Some("req-1".into()),
+            ip_address: Some("192.0.2.1".into()),
+            ..CreateAuditLog::new(
+                Some(admin),
+                AuditAction::UpdateBook,
+                AuditTargetType::Book,
+                Some(book_id.clone()),
+            )
+        })
+        .await?;
+        repo.create(CreateAuditLog::new(
+            None,
+            AuditAction::LoginFailed,
+            AuditTargetType::User,
+            None,
+        ))
+        .await?;
+
+        let all = repo
+            .find_all(AuditLogListOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(all.total, 2);
+
+        let by_actor = repo
+            .find_all(AuditLogListOptions {
+                actor_id: Some(admin),
+                limit: 10,
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(by_actor.total, 1);
+        let log = &by_actor.items[0];
+        assert_eq!(log.action, AuditAction::UpdateBook);
+        assert_eq!(log.target_id.as_deref(), Some(book_id.as_str()));
+        assert_eq!(log.before.as_ref().unwrap()["title"], "Old");
+        assert_eq!(log.request_id.as_deref(), Some("req-1"));
+        assert_eq!(log.ip_address.as_deref(), Some("192.0.2.1"));
+
+        let by_target = repo
+            .find_all(AuditLogListOptions {
+                target_type: Some(AuditTargetType::Book),
+                target_id: Some(book_id),
+                from: Some(before_all),
+                limit: 10,
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(by_target.total, 1);
+        let out_of_range = repo
+            .find_all(AuditLogListOptions {
+                to: Some(before_all),
+                limit: 10,
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(out_of_range.total, 0);
+
+        // 記録した監査ログは変更・削除できない
+        assert!(sqlx::query!("UPDATE audit_logs SET action = 'tampered'")
+            .execute(&pool)
+            .await
+            .is_err());
+        assert!(sqlx::query!("DELETE FROM audit_logs")
+            .execute(&pool)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+}
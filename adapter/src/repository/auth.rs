@@ -13,37 +13,35 @@ use shared::error::{AppError, AppResult};
 
 use crate::{
     database::{
-        model::auth::{from, AuthorizationKey, AuthorizedUserId, UserItem},
+        model::auth::{from, user_tokens_index, AuthorizationKey, AuthorizedUserId, UserItem},
         ConnectionPool,
     },
     redis::RedisClient,
 };
 
+/// メールアドレスとパスワードによる認証方式
+/// `AuthRepositoryImpl`は登録された順に認証方式を試し、最初に成功したものを採用する
+#[async_trait]
+pub trait PasswordVerifier: Send + Sync {
+    /// 認証に成功した場合はユーザーIDを返す
+    /// この方式で認証できなかった場合は`None`を返し、次の方式に委ねる
+    async fn verify(&self, email: &str, password: &str) -> AppResult<Option<UserId>>;
+}
+
+/// `users`テーブルに保存されたパスワードのハッシュ値で認証する
 #[derive(new)]
-pub struct AuthRepositoryImpl {
+pub struct LocalPasswordVerifier {
     db: ConnectionPool,
-    kv: Arc<RedisClient>,
-    ttl: u64,
 }
-#[async_trait]
-impl AuthRepository for AuthRepositoryImpl {
-    async fn fetch_user_id_from_token(
-        &self,
-        access_token: &AccessToken,
-    ) -> AppResult<Option<UserId>> {
-        let key: AuthorizationKey = access_token.into();
-        self.kv
-            .get(&key)
-            .await
-            .map(|x| x.map(AuthorizedUserId::into_inner))
-    }
 
-    async fn verify_user(&self, email: &str, password: &str) -> AppResult<UserId> {
+#[async_trait]
+impl PasswordVerifier for LocalPasswordVerifier {
+    async fn verify(&self, email: &str, password: &str) -> AppResult<Option<UserId>> {
         let user_item = sqlx::query_as!(
             UserItem,
             r#"
                 SELECT user_id, password_hash FROM users
-                WHERE email = $1;
+                WHERE email = $1 AND deactivated_at IS NULL;
             "#,
             email
         )
@@ -54,33 +52,178 @@ impl AuthRepository for AuthRepositoryImpl {
         let password = password.to_string();
 
         match user_item {
-            Some(item) => {
-                let hash = item.password_hash;
+            Some(UserItem {
+                user_id,
+                password_hash: Some(hash),
+            }) => {
                 let valid = tokio::task::spawn_blocking(move || bcrypt::verify(password, &hash))
                     .await
                     .map_err(|e| AppError::InternalError(e.into()))??;
-                if !valid {
-                    return Err(AppError::UnauthenticatedError);
-                }
-                Ok(item.user_id)
+                Ok(valid.then_some(user_id))
             }
-            None => {
+            // ユーザーが存在しない、またはパスワードを持たないユーザーの場合も
+            // 応答時間から判別できないよう、ダミーのハッシュで検証を行う
+            _ => {
                 let dummy_hash = "$2b$12$yF3mG.7m759S9kygS9kygS9kuO099v76v76v76v76v76v76v76v76";
                 let _ =
                     tokio::task::spawn_blocking(move || bcrypt::verify(password, dummy_hash)).await;
-                Err(AppError::UnauthenticatedError)
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[derive(new)]
+pub struct AuthRepositoryImpl {
+    kv: Arc<RedisClient>,
+    ttl: u64,
+    /// 試行する順に並べた認証方式
+    verifiers: Vec<Arc<dyn PasswordVerifier>>,
+}
+#[async_trait]
+impl AuthRepository for AuthRepositoryImpl {
+    async fn fetch_user_id_from_token(
+        &self,
+        access_token: &AccessToken,
+    ) -> AppResult<Option<UserId>> {
+        let key: AuthorizationKey = access_token.into();
+        self.kv
+            .get(&key)
+            .await
+            .map(|x| x.map(AuthorizedUserId::into_inner))
+    }
+
+    async fn verify_user(&self, email: &str, password: &str) -> AppResult<UserId> {
+        for verifier in &self.verifiers {
+            if let Some(user_id) = verifier.verify(email, password).await? {
+                return Ok(user_id);
             }
         }
+        Err(AppError::UnauthenticatedError)
     }
 
     async fn create_token(&self, event: CreateToken) -> AppResult<AccessToken> {
+        let index = user_tokens_index(event.user_id);
         let (key, value) = from(event);
-        self.kv.set_ex(&key, &value, self.ttl).await?;
+        self.kv
+            .set_ex_indexed(&key, &value, self.ttl, &index)
+            .await?;
         Ok(key.into())
     }
 
     async fn delete_token(&self, access_token: AccessToken) -> AppResult<()> {
         let key: AuthorizationKey = access_token.into();
-        self.kv.delete(&key).await
+        match self.kv.get(&key).await? {
+            Some(user_id) => {
+                self.kv
+                    .delete_indexed(&key, &user_tokens_index(user_id.into_inner()))
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+
+    async fn delete_tokens_by_user_id(&self, user_id: UserId) -> AppResult<u64> {
+        self.kv
+            .delete_all_indexed(&user_tokens_index(user_id))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::user::UserRepositoryImpl;
+    use kernel::{
+        model::{role::Role, user::event::CreateUser},
+        repository::user::UserRepository,
+    };
+    use shared::config::RedisConfig;
+
+    /// 常に同じ結果を返す認証方式
+    struct StubVerifier(Option<UserId>);
+
+    #[async_trait]
+    impl PasswordVerifier for StubVerifier {
+        async fn verify(&self, _email: &str, _password: &str) -> AppResult<Option<UserId>> {
+            Ok(self.0)
+        }
+    }
+
+    fn auth_repository(
+        pool: sqlx::PgPool,
+        fallback: Option<UserId>,
+    ) -> anyhow::Result<AuthRepositoryImpl> {
+        // RedisClientは接続を遅延して確立するため、トークンを扱わないテストでは接続先は不要
+        let kv = Arc::new(RedisClient::new(&RedisConfig {
+            host: "localhost".into(),
+            port: 6379,
+        })?);
+        Ok(AuthRepositoryImpl::new(
+            kv,
+            60,
+            vec![
+                Arc::new(LocalPasswordVerifier::new(ConnectionPool::new(pool))),
+                Arc::new(StubVerifier(fallback)),
+            ],
+        ))
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_verify_user_chains_verifiers(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let local_user = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()))
+            .create(
+                CreateUser {
+                    name: "Local Admin".into(),
+                    email: "local.admin@example.com".into(),
+                    password: "Pa55w0rd".into(),
+                    role: Role::User,
+                },
+                None,
+            )
+            .await?;
+        let fallback_user_id = UserId::new();
+        let repo = auth_repository(pool.clone(), Some(fallback_user_id))?;
+
+        // ローカルのパスワードで認証できる場合は、後続の方式を試さない
+        let user_id = repo
+            .verify_user("local.admin@example.com", "Pa55w0rd")
+            .await?;
+        assert_eq!(user_id, local_user.id);
+
+        // ローカルで認証できない場合は、後続の方式で認証する
+        let user_id = repo.verify_user("local.admin@example.com", "wrong").await?;
+        assert_eq!(user_id, fallback_user_id);
+
+        // いずれの方式でも認証できない場合はエラーとなる
+        let repo = auth_repository(pool, None)?;
+        let res = repo.verify_user("unknown@example.com", "Pa55w0rd").await;
+        assert!(matches!(res, Err(AppError::UnauthenticatedError)));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires a running Redis server"]
+    async fn test_delete_tokens_by_user_id(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = auth_repository(pool, None)?;
+        let user_id = UserId::new();
+        let other_user_id = UserId::new();
+        let create = |user_id| repo.create_token(CreateToken::new(user_id));
+        let first = create(user_id).await?;
+        let second = create(user_id).await?;
+        let other = create(other_user_id).await?;
+
+        // 個別に削除したトークンは、まとめて削除する対象に含まれない
+        repo.delete_token(first).await?;
+        assert_eq!(repo.delete_tokens_by_user_id(user_id).await?, 1);
+        assert!(repo.fetch_user_id_from_token(&second).await?.is_none());
+        // 他のユーザーのトークンは削除されない
+        assert_eq!(
+            repo.fetch_user_id_from_token(&other).await?,
+            Some(other_user_id)
+        );
+
+        Ok(())
     }
 }
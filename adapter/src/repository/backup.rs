@@ -0,0 +1,514 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    model::{
+        audit::event::CreateAuditLog,
+        backup::{
+            BackupArchive, BackupBook, BackupCheckout, BackupOptions, BackupReturnedCheckout,
+            BackupUser, RestoreMode, RestoreSummary, BACKUP_FORMAT_VERSION,
+        },
+        id::{BookId, CheckoutId, KioskId, UserId},
+    },
+    repository::backup::BackupRepository,
+};
+use shared::error::{AppError, AppResult};
+use sqlx::PgConnection;
+
+use crate::{
+    database::{model::backup::BackupBookRow, ConnectionPool},
+    repository::audit::record_audit_log,
+};
+
+#[derive(new)]
+pub struct BackupRepositoryImpl {
+    db: ConnectionPool,
+}
+
+/// 適用済みの最新のマイグレーションのバージョン
+/// マイグレーションの履歴は`sqlx migrate`が管理するテーブルにあるため、実行時に問い合わせる
+async fn schema_version(conn: &mut PgConnection) -> AppResult<i64> {
+    sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+        .fetch_one(conn)
+        .await
+        .map(Option::unwrap_or_default)
+        .map_err(AppError::DatabaseOperationError)
+}
+
+#[async_trait]
+impl BackupRepository for BackupRepositoryImpl {
+    async fn create(&self, options: BackupOptions) -> AppResult<BackupArchive> {
+        // 取得中に行われた変更が混ざらないよう、ひとつのスナップショットから読み出す
+        let mut tx = self.db.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::DatabaseOperationError)?;
+
+        let schema_version = schema_version(&mut tx).await?;
+        let roles = sqlx::query_scalar!("SELECT name FROM roles ORDER BY name")
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(AppError::DatabaseOperationError)?;
+        let users = sqlx::query_as!(
+            BackupUser,
+            r#"
+                SELECT
+                    u.user_id AS "user_id: UserId",
+                    u.name,
+                    u.email,
+                    CASE WHEN $1 THEN u.password_hash END AS password_hash,
+                    r.name AS role,
+                    u.external_issuer,
+                    u.external_subject,
+                    u.badge_code,
+                    u.deactivated_at,
+                    u.created_at,
+                    u.updated_at
+                FROM users AS u
+                INNER JOIN roles AS r USING(role_id)
+                ORDER BY u.created_at, u.user_id
+            "#,
+            options.include_password_hashes
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        let books = sqlx::query_as!(
+            BackupBookRow,
+            r#"
+                SELECT
+                    book_id AS "book_id: BookId",
+                    title,
+                    author,
+                    isbn,
+                    description,
+                    user_id AS "user_id: UserId",
+                    status,
+                    created_at,
+                    updated_at
+                FROM books
+                ORDER BY created_at, book_id
+            "#
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .into_iter()
+        .map(BackupBook::try_from)
+        .collect::<AppResult<Vec<_>>>()?;
+        let checkouts = sqlx::query_as!(
+            BackupCheckout,
+            r#"
+                SELECT
+                    checkout_id AS "checkout_id: CheckoutId",
+                    book_id AS "book_id: BookId",
+                    user_id AS "user_id: UserId",
+                    checked_out_at,
+                    due_at,
+                    renewal_count,
+                    checked_out_via AS "checked_out_via: KioskId"
+                FROM checkouts
+                ORDER BY checked_out_at, checkout_id
+            "#
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        let returned_checkouts = sqlx::query_as!(
+            BackupReturnedCheckout,
+            r#"
+                SELECT
+                    checkout_id AS "checkout_id: CheckoutId",
+                    book_id AS "book_id: BookId",
+                    user_id AS "user_id: UserId",
+                    checked_out_at,
+                    returned_at,
+                    due_at,
+                    renewal_count,
+                    checked_out_via AS "checked_out_via: KioskId",
+                    returned_via AS "returned_via: KioskId"
+                FROM returned_checkouts
+                ORDER BY checked_out_at, checkout_id
+            "#
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(BackupArchive {
+            format_version: BACKUP_FORMAT_VERSION,
+            schema_version,
+            created_at: chrono::Utc::now(),
+            includes_password_hashes: options.include_password_hashes,
+            roles,
+            users,
+            books,
+            checkouts,
+            returned_checkouts,
+        })
+    }
+
+    async fn restore(
+        &self,
+        archive: BackupArchive,
+        mode: RestoreMode,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<RestoreSummary> {
+        let archive = Arc::new(archive);
+        self.db
+            .serializable(move |tx| {
+                let archive = archive.clone();
+                let audit = audit.clone();
+                Box::pin(async move {
+                    let summary = restore(&mut *tx, &archive, mode).await?;
+                    if let Some(audit) = audit {
+                        let after = serde_json::to_value(&summary)
+                            .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+                        let audit = CreateAuditLog {
+                            after: Some(after),
+                            ..audit
+                        }
+                        .with_after_field("mode", mode.as_ref().into());
+                        record_audit_log(tx, audit).await?;
+                    }
+                    Ok(summary)
+                })
+            })
+            .await
+    }
+}
+
+/// ユーザー・蔵書・貸出に紐づき、削除すると外部キーによって合わせて削除されるが、
+/// バックアップには含まれないテーブル
+const REPLACE_BLOCKING_TABLES: [&str; 5] = [
+    "api_keys",
+    "user_loan_policies",
+    "fee_entries",
+    "book_status_histories",
+    "checkout_reminders",
+];
+
+/// 置き換えによって、バックアップから戻せないレコードが削除されないことを確かめる
+/// 該当するレコードがある場合は、テーブルごとの件数を示してエラーとする
+async fn ensure_replaceable(tx: &mut PgConnection) -> AppResult<()> {
+    let mut blockers = Vec::new();
+    for table in REPLACE_BLOCKING_TABLES {
+        let count = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(AppError::DatabaseOperationError)?;
+        if count > 0 {
+            blockers.push(format!("{table} ({count})"));
+        }
+    }
+    if blockers.is_empty() {
+        return Ok(());
+    }
+    Err(AppError::ConflictError(format!(
+        "Replacing would delete records that are not included in the backup: {}. \
+         Remove them first or restore in merge mode",
+        blockers.join(", ")
+    )))
+}
+
+/// バックアップの内容を書き込む
+///
+/// 既存のレコードと一意制約が衝突するレコードや、参照先のユーザー・蔵書が復元されなかった
+/// レコードは書き込まずに飛ばし、件数として報告する。
+/// 参照先のキオスクはバックアップに含まれないため、復元先にないものは`NULL`とする
+async fn restore(
+    tx: &mut PgConnection,
+    archive: &BackupArchive,
+    mode: RestoreMode,
+) -> AppResult<RestoreSummary> {
+    archive.validate(schema_version(tx).await?, mode)?;
+
+    let mut summary = RestoreSummary::default();
+    if mode == RestoreMode::Replace {
+        ensure_replaceable(tx).await?;
+        // キオスク・Webhookは残すが、作成したユーザーの削除により作成者は`NULL`となるため、件数を報告する
+        summary.kiosks_without_creator = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM kiosks WHERE created_by IS NOT NULL"#
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)? as u64;
+        summary.webhooks_without_creator = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM webhooks WHERE created_by IS NOT NULL"#
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+            as u64;
+        // 参照する側のテーブルから削除する
+        for statement in [
+            "DELETE FROM checkouts",
+            "DELETE FROM returned_checkouts",
+            "DELETE FROM books",
+            "DELETE FROM users",
+        ] {
+            sqlx::query(statement)
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::DatabaseOperationError)?;
+        }
+    }
+
+    for role in &archive.roles {
+        sqlx::query!(
+            "INSERT INTO roles (name) VALUES ($1) ON CONFLICT (name) DO NOTHING",
+            role
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+    }
+
+    for user in &archive.users {
+        let res = sqlx::query!(
+            r#"
+                INSERT INTO users (
+                    user_id, name, email, password_hash, role_id, external_issuer,
+                    external_subject, badge_code, deactivated_at, created_at, updated_at
+                )
+                SELECT $1, $2, $3, $4, role_id, $6, $7, $8, $9, $10, $11
+                FROM roles WHERE name = $5
+                ON CONFLICT DO NOTHING
+            "#,
+            user.user_id as _,
+            user.name,
+            user.email,
+            user.password_hash,
+            user.role,
+            user.external_issuer,
+            user.external_subject,
+            user.badge_code,
+            user.deactivated_at,
+            user.created_at,
+            user.updated_at
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        summary.users.record(res.rows_affected() > 0);
+    }
+
+    for book in &archive.books {
+        let res = sqlx::query!(
+            r#"
+                INSERT INTO books (
+                    book_id, title, author, isbn, description, user_id, status,
+                    created_at, updated_at
+                )
+                SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9
+                WHERE EXISTS (SELECT 1 FROM users WHERE user_id = $6)
+                ON CONFLICT DO NOTHING
+            "#,
+            book.book_id as _,
+            book.title,
+            book.author,
+            book.isbn,
+            book.description,
+            book.user_id as _,
+            book.status.as_ref(),
+            book.created_at,
+            book.updated_at
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        summary.books.record(res.rows_affected() > 0);
+    }
+
+    for checkout in &archive.checkouts {
+        let res = sqlx::query!(
+            r#"
+                INSERT INTO checkouts (
+                    checkout_id, book_id, user_id, checked_out_at, due_at, renewal_count,
+                    checked_out_via
+                )
+                SELECT $1, $2, $3, $4, $5, $6, (SELECT kiosk_id FROM kiosks WHERE kiosk_id = $7)
+                WHERE EXISTS (SELECT 1 FROM books WHERE book_id = $2)
+                    AND EXISTS (SELECT 1 FROM users WHERE user_id = $3)
+                ON CONFLICT DO NOTHING
+            "#,
+            checkout.checkout_id as _,
+            checkout.book_id as _,
+            checkout.user_id as _,
+            checkout.checked_out_at,
+            checkout.due_at,
+            checkout.renewal_count,
+            checkout.checked_out_via as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        summary.checkouts.record(res.rows_affected() > 0);
+    }
+
+    for checkout in &archive.returned_checkouts {
+        let res = sqlx::query!(
+            r#"
+                INSERT INTO returned_checkouts (
+                    checkout_id, book_id, user_id, checked_out_at, returned_at, due_at,
+                    renewal_count, checked_out_via, returned_via
+                )
+                SELECT $1, $2, $3, $4, $5, $6, $7,
+                    (SELECT kiosk_id FROM kiosks WHERE kiosk_id = $8),
+                    (SELECT kiosk_id FROM kiosks WHERE kiosk_id = $9)
+                ON CONFLICT DO NOTHING
+            "#,
+            checkout.checkout_id as _,
+            checkout.book_id as _,
+            checkout.user_id as _,
+            checkout.checked_out_at,
+            checkout.returned_at,
+            checkout.due_at,
+            checkout.renewal_count,
+            checkout.checked_out_via as _,
+            checkout.returned_via as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        summary.returned_checkouts.record(res.rows_affected() > 0);
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::repository::checkout::CheckoutRepositoryImpl;
+    use chrono::Utc;
+    use kernel::{
+        model::checkout::event::CreateCheckout, repository::checkout::CheckoutRepository,
+    };
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_backup_and_restore(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool.clone());
+        let repo = BackupRepositoryImpl::new(db.clone());
+        // fixtures/common.sql, fixtures/book.sqlに記載のユーザーID・蔵書ID
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        CheckoutRepositoryImpl::new(db)
+            .create_checkout(CreateCheckout::new(book_id, user_id, Utc::now()), None)
+            .await?;
+
+        // 既定ではパスワードのハッシュを含めない
+        let archive = repo.create(BackupOptions::default()).await?;
+        assert!(archive.schema_version > 0);
+        assert_eq!(archive.roles, vec!["Admin", "User"]);
+        assert_eq!(archive.users.len(), 1);
+        assert!(archive.users[0].password_hash.is_none());
+        assert_eq!(archive.books.len(), 3);
+        assert_eq!(archive.checkouts.len(), 1);
+
+        let archive = repo
+            .create(BackupOptions {
+                include_password_hashes: true,
+            })
+            .await?;
+        assert_eq!(archive.users[0].password_hash.as_deref(), Some("P@ssw0rd!"));
+
+        // 既存のレコードと重複するものは飛ばす
+        let summary = repo
+            .restore(archive.clone(), RestoreMode::Merge, None)
+            .await?;
+        assert_eq!(summary.users.skipped, 1);
+        assert_eq!(summary.books.skipped, 3);
+        assert_eq!(summary.checkouts.skipped, 1);
+        assert_eq!(summary.books.restored, 0);
+
+        // バックアップに含まれない状態の履歴などが残っている間は、置き換えない
+        sqlx::query!(
+            "INSERT INTO book_status_histories (book_id, status) VALUES ($1, 'Available')",
+            book_id as _
+        )
+        .execute(&pool)
+        .await?;
+        let res = repo
+            .restore(archive.clone(), RestoreMode::Replace, None)
+            .await;
+        assert!(
+            matches!(res, Err(AppError::ConflictError(ref message)) if message.contains("book_status_histories"))
+        );
+        sqlx::query!("DELETE FROM book_status_histories")
+            .execute(&pool)
+            .await?;
+
+        // 貸出に紐づく督促の送信記録も、貸出の削除とともに失われるため置き換えない
+        sqlx::query!(
+            "INSERT INTO checkout_reminders (checkout_id, kind, due_at) SELECT checkout_id, 'Overdue', now() FROM checkouts"
+        )
+        .execute(&pool)
+        .await?;
+        let res = repo
+            .restore(archive.clone(), RestoreMode::Replace, None)
+            .await;
+        assert!(
+            matches!(res, Err(AppError::ConflictError(ref message)) if message.contains("checkout_reminders"))
+        );
+        sqlx::query!("DELETE FROM checkout_reminders")
+            .execute(&pool)
+            .await?;
+
+        // キオスクは残るが、作成者の記録が失われたことを報告する
+        sqlx::query!(
+            "INSERT INTO kiosks (name, key_prefix, key_hash, created_by) VALUES ('shelf', 'bmkiosk_x', 'x', $1)",
+            user_id as _
+        )
+        .execute(&pool)
+        .await?;
+
+        // 置き換える場合は、バックアップにないレコードは残らない
+        let mut partial = archive.clone();
+        partial.checkouts.clear();
+        partial.books.retain(|b| b.book_id == book_id);
+        let summary = repo.restore(partial, RestoreMode::Replace, None).await?;
+        assert_eq!(summary.books.restored, 1);
+        assert_eq!(summary.users.restored, 1);
+        assert_eq!(summary.kiosks_without_creator, 1);
+        assert_eq!(summary.webhooks_without_creator, 0);
+        let creators = sqlx::query_scalar!("SELECT created_by FROM kiosks")
+            .fetch_all(&pool)
+            .await?;
+        assert_eq!(creators, vec![None]);
+        let book_ids = sqlx::query_scalar!(r#"SELECT book_id AS "book_id: BookId" FROM books"#)
+            .fetch_all(&pool)
+            .await?;
+        assert_eq!(book_ids, vec![book_id]);
+        let checkouts = sqlx::query_scalar!("SELECT COUNT(*) FROM checkouts")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(checkouts, Some(0));
+
+        // 統合すると、欠けていた蔵書・貸出が戻る
+        let summary = repo.restore(archive, RestoreMode::Merge, None).await?;
+        assert_eq!(summary.books.restored, 2);
+        assert_eq!(summary.books.skipped, 1);
+        assert_eq!(summary.checkouts.restored, 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_restore_rejects_other_schema_version(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BackupRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let mut archive = repo.create(BackupOptions::default()).await?;
+        archive.schema_version -= 1;
+        archive.users.clear();
+
+        let res = repo.restore(archive, RestoreMode::Merge, None).await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        Ok(())
+    }
+}
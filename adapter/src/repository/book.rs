@@ -1,24 +1,125 @@
 use async_trait::async_trait;
 use derive_new::new;
 use kernel::model::{
+    audit::event::CreateAuditLog,
+    domain_event::{BookRecord, DomainEvent, DomainEventKind},
     id::{BookId, UserId},
     {book::event::DeleteBook, list::PaginatedList},
 };
 use kernel::{
     model::book::{
-        event::{CreateBook, UpdateBook},
+        event::{CreateBook, ReassignBookOwner, UpdateBook, UpdateBookStatus},
+        status::{BookStatus, BookStatusHistory},
         Book, BookListOptions, CheckoutInfo,
     },
     repository::book::BookRepository,
 };
 use shared::error::{AppError, AppResult};
+use sqlx::PgConnection;
 
 use crate::database::model::book::{
-    BookCheckoutRow, BookRow, PaginatedBookDetailRow, PaginatedBookRow,
+    parse_book_status, BookCheckoutRow, BookRow, BookStatusHistoryRow, PaginatedBookDetailRow,
 };
 use crate::database::ConnectionPool;
+use crate::repository::{audit::record_audit_log, outbox::record_event};
 use std::collections::HashMap;
 
+/// 蔵書の状態を変更し、変更履歴を記録する
+/// 変更前の状態が`from`でない場合は、他の処理と競合したものとして中断する
+pub(crate) async fn change_book_status(
+    tx: &mut PgConnection,
+    book_id: BookId,
+    from: BookStatus,
+    to: BookStatus,
+    changed_by: UserId,
+    note: Option<&str>,
+) -> AppResult<()> {
+    let res = sqlx::query!(
+        "UPDATE books SET status = $3 WHERE book_id = $1 AND status = $2",
+        book_id as _,
+        from.as_ref(),
+        to.as_ref(),
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(AppError::DatabaseOperationError)?;
+
+    if res.rows_affected() < 1 {
+        return Err(AppError::UnprocessableEntity(format!(
+            "指定された書籍({})の状態は{}ではありません",
+            book_id,
+            from.as_ref()
+        )));
+    }
+
+    sqlx::query!(
+        r#"
+            INSERT INTO book_status_histories(book_id, status, previous_status, changed_by, note)
+            VALUES ($1, $2, $3, $4, $5)
+        "#,
+        book_id as _,
+        to.as_ref(),
+        from.as_ref(),
+        changed_by as _,
+        note,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(AppError::DatabaseOperationError)?;
+
+    Ok(())
+}
+
+/// 蔵書を登録し、登録時の状態の履歴とドメインイベントを記録する
+async fn insert_book(
+    tx: &mut PgConnection,
+    event: CreateBook,
+    user_id: UserId,
+) -> AppResult<BookId> {
+    // 登録時の状態を、変更履歴の起点として記録する
+    let book_id = sqlx::query_scalar!(
+        r#"
+            WITH inserted AS (
+                INSERT INTO books (title, author, isbn, description, user_id)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING book_id, status, user_id
+            )
+            INSERT INTO book_status_histories (book_id, status, changed_by)
+            SELECT book_id, status, user_id FROM inserted
+            RETURNING book_id AS "book_id!: BookId"
+        "#,
+        event.title,
+        event.author,
+        event.isbn,
+        event.description,
+        user_id as _
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(AppError::DatabaseOperationError)?;
+
+    let CreateBook {
+        title,
+        author,
+        isbn,
+        description,
+    } = event;
+    record_event(
+        tx,
+        &DomainEvent::new(DomainEventKind::BookCreated(BookRecord {
+            book_id,
+            title,
+            author,
+            isbn,
+            description,
+            owned_by: user_id,
+        })),
+    )
+    .await?;
+
+    Ok(book_id)
+}
+
 #[derive(new)]
 pub struct BookRepositoryImpl {
     db: ConnectionPool,
@@ -37,7 +138,8 @@ impl BookRepositoryImpl {
                     c.book_id,
                     u.user_id,
                     u.name AS user_name,
-                    c.checked_out_at
+                    c.checked_out_at,
+                    c.due_at
                 FROM checkouts AS c
                 INNER JOIN users AS u USING(user_id)
                 WHERE book_id = ANY($1);
@@ -58,23 +160,45 @@ impl BookRepositoryImpl {
 #[async_trait]
 impl BookRepository for BookRepositoryImpl {
     /// 蔵書レコード作成
-    async fn create(&self, event: CreateBook, user_id: UserId) -> AppResult<()> {
-        sqlx::query!(
-            r#"
-                INSERT INTO books (title, author, isbn, description, user_id)
-                VALUES ($1, $2, $3, $4, $5)
-            "#,
-            event.title,
-            event.author,
-            event.isbn,
-            event.description,
-            user_id as _
-        )
-        .execute(self.db.inner_ref())
-        .await
-        .map_err(AppError::DatabaseOperationError)?;
+    async fn create(
+        &self,
+        event: CreateBook,
+        user_id: UserId,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<BookId> {
+        let mut tx = self.db.begin().await?;
+        let book_id = insert_book(&mut tx, event, user_id).await?;
+        if let Some(audit) = audit {
+            let audit = CreateAuditLog {
+                target_id: Some(book_id.to_string()),
+                ..audit
+            };
+            record_audit_log(&mut tx, audit).await?;
+        }
+        tx.commit().await.map_err(AppError::TransactionError)?;
 
-        Ok(())
+        Ok(book_id)
+    }
+
+    /// 蔵書レコード一括作成
+    async fn create_many(
+        &self,
+        events: Vec<CreateBook>,
+        user_id: UserId,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<Vec<BookId>> {
+        let mut tx = self.db.begin().await?;
+        let mut book_ids = Vec::with_capacity(events.len());
+        for event in events {
+            book_ids.push(insert_book(&mut tx, event, user_id).await?);
+        }
+        if let Some(audit) = audit {
+            let audit = audit.with_after_field("bookIds", serde_json::json!(book_ids));
+            record_audit_log(&mut tx, audit).await?;
+        }
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(book_ids)
     }
 
     /// 蔵書データ取得
@@ -95,7 +219,8 @@ impl BookRepository for BookRepositoryImpl {
                     b.isbn AS isbn,
                     b.description AS description,
                     u.user_id AS owned_by,
-                    u.name AS owner_name
+                    u.name AS owner_name,
+                    b.status
                 FROM books AS b
                 INNER JOIN users AS u USING(user_id)
                 ORDER BY b.created_at DESC
@@ -119,7 +244,7 @@ impl BookRepository for BookRepositoryImpl {
                 let checkout = checkouts.remove(&row.book_id);
                 row.into_book(checkout)
             })
-            .collect();
+            .collect::<AppResult<_>>()?;
 
         Ok(PaginatedList {
             total,
@@ -141,7 +266,8 @@ impl BookRepository for BookRepositoryImpl {
                     b.isbn AS isbn,
                     b.description AS description,
                     u.user_id AS owned_by,
-                    u.name AS owner_name
+                    u.name AS owner_name,
+                    b.status
                 FROM books AS b
                 INNER JOIN users AS u USING(user_id)
                 WHERE b.book_id = $1
@@ -155,14 +281,15 @@ impl BookRepository for BookRepositoryImpl {
         match row {
             Some(r) => {
                 let checkout = self.find_checkouts(&[r.book_id]).await?.remove(&r.book_id);
-                Ok(Some(r.into_book(checkout)))
+                r.into_book(checkout).map(Some)
             }
             None => Ok(None),
         }
     }
 
     /// 蔵書データ更新
-    async fn update(&self, event: UpdateBook) -> AppResult<()> {
+    async fn update(&self, event: UpdateBook, audit: Option<CreateAuditLog>) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
         let res = sqlx::query!(
             r#"
                 UPDATE books
@@ -182,18 +309,45 @@ impl BookRepository for BookRepositoryImpl {
             event.book_id as _,
             event.requested_user as _
         )
-        .execute(self.db.inner_ref())
+        .execute(&mut *tx)
         .await
         .map_err(AppError::DatabaseOperationError)?;
 
         if res.rows_affected() == 0 {
             return Err(AppError::NotFoundError("specified book not found".into()));
         }
+
+        let UpdateBook {
+            book_id,
+            title,
+            author,
+            isbn,
+            description,
+            requested_user,
+        } = event;
+        record_event(
+            &mut tx,
+            &DomainEvent::new(DomainEventKind::BookUpdated(BookRecord {
+                book_id,
+                title,
+                author,
+                isbn,
+                description,
+                owned_by: requested_user,
+            })),
+        )
+        .await?;
+        if let Some(audit) = audit {
+            record_audit_log(&mut tx, audit).await?;
+        }
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
         Ok(())
     }
 
     /// 蔵書データ削除
-    async fn delete(&self, event: DeleteBook) -> AppResult<()> {
+    async fn delete(&self, event: DeleteBook, audit: Option<CreateAuditLog>) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
         let res = sqlx::query!(
             r#"
                 DELETE FROM books
@@ -204,15 +358,163 @@ impl BookRepository for BookRepositoryImpl {
             event.book_id as _,
             event.requested_user as _
         )
-        .execute(self.db.inner_ref())
+        .execute(&mut *tx)
         .await
         .map_err(AppError::DatabaseOperationError)?;
 
         if res.rows_affected() == 0 {
             return Err(AppError::NotFoundError("specified book not found".into()));
         }
+
+        record_event(
+            &mut tx,
+            &DomainEvent::new(DomainEventKind::BookDeleted {
+                book_id: event.book_id,
+                deleted_by: event.requested_user,
+            }),
+        )
+        .await?;
+        if let Some(audit) = audit {
+            record_audit_log(&mut tx, audit).await?;
+        }
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    /// 蔵書の所有者付け替え
+    /// 付け替え先は有効なユーザーである必要がある
+    async fn reassign_owner(
+        &self,
+        event: ReassignBookOwner,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let new_owner_is_active = sqlx::query!(
+            r#"
+                SELECT user_id FROM users
+                WHERE user_id = $1 AND deactivated_at IS NULL
+                FOR SHARE
+            "#,
+            event.new_owner as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .is_some();
+
+        if !new_owner_is_active {
+            return Err(AppError::NotFoundError(format!(
+                "付け替え先のユーザー({})が見つかりません",
+                event.new_owner
+            )));
+        }
+
+        sqlx::query!(
+            r#"
+                UPDATE books
+                SET user_id = $2
+                WHERE user_id = $1
+            "#,
+            event.current_owner as _,
+            event.new_owner as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        if let Some(audit) = audit {
+            record_audit_log(&mut tx, audit).await?;
+        }
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
         Ok(())
     }
+
+    /// 蔵書の状態変更
+    /// 貸出中への変更、貸出中からの変更は貸出・返却・紛失の届け出でのみ行う
+    async fn update_status(
+        &self,
+        event: UpdateBookStatus,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let current = sqlx::query_scalar!(
+            "SELECT status FROM books WHERE book_id = $1 FOR UPDATE",
+            event.book_id as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .ok_or_else(|| AppError::NotFoundError("specified book not found".into()))?;
+        let current = parse_book_status(&current)?;
+
+        if current.is_managed_by_checkout()
+            || event.status.is_managed_by_checkout()
+            || !current.can_transition_to(event.status)
+        {
+            return Err(AppError::UnprocessableEntity(format!(
+                "蔵書の状態を{}から{}へ変更することはできません",
+                current.as_ref(),
+                event.status.as_ref()
+            )));
+        }
+
+        change_book_status(
+            &mut tx,
+            event.book_id,
+            current,
+            event.status,
+            event.requested_user,
+            event.note.as_deref(),
+        )
+        .await?;
+        record_event(
+            &mut tx,
+            &DomainEvent::new(DomainEventKind::BookStatusChanged {
+                book_id: event.book_id,
+                status: event.status,
+                previous_status: current,
+                changed_by: event.requested_user,
+            }),
+        )
+        .await?;
+        if let Some(audit) = audit {
+            record_audit_log(&mut tx, audit).await?;
+        }
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    /// 蔵書の状態の変更履歴取得
+    async fn find_status_history(&self, book_id: BookId) -> AppResult<Vec<BookStatusHistory>> {
+        sqlx::query_as!(
+            BookStatusHistoryRow,
+            r#"
+                SELECT
+                    book_id,
+                    status,
+                    previous_status,
+                    changed_by AS "changed_by: UserId",
+                    note,
+                    changed_at
+                FROM book_status_histories
+                WHERE book_id = $1
+                ORDER BY changed_at ASC, book_status_history_id
+            "#,
+            book_id as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .into_iter()
+        .map(BookStatusHistory::try_from)
+        .collect()
+    }
 }
 
 #[cfg(test)]
@@ -221,7 +523,14 @@ mod tests {
 
     use super::*;
     use crate::repository::user::UserRepositoryImpl;
-    use kernel::{model::user::event::CreateUser, repository::user::UserRepository};
+    use kernel::{
+        model::{
+            audit::{AuditAction, AuditTargetType},
+            role::Role,
+            user::event::CreateUser,
+        },
+        repository::user::UserRepository,
+    };
 
     #[sqlx::test]
     async fn test_register_book(pool: sqlx::PgPool) -> anyhow::Result<()> {
@@ -229,28 +538,42 @@ mod tests {
         sqlx::query!(
             r#"
             INSERT INTO roles(name)
-            VALUES ('Admin'), ('User');
+            VALUES ('Admin'), ('User')
+            ON CONFLICT (name) DO NOTHING;
         "#
         )
         .execute(&pool)
         .await?;
         let user_repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
         let user = user_repo
-            .create(CreateUser {
-                name: "Test User".into(),
-                email: "test@example.com".into(),
-                password: "test_password".into(),
-            })
+            .create(
+                CreateUser {
+                    name: "Test User".into(),
+                    email: "test@example.com".into(),
+                    password: "test_password".into(),
+                    role: Role::User,
+                },
+                None,
+            )
             .await?;
 
-        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
         let book = CreateBook {
             title: "Test Title".into(),
             author: "Test Author".into(),
             isbn: "Test ISBN".into(),
             description: "Test Description".into(),
         };
-        book_repo.create(book, user.id).await?;
+        let created_id = book_repo.create(book, user.id, None).await?;
+
+        // 登録と同じトランザクションで、ドメインイベントが記録される
+        let payload = sqlx::query_scalar!(
+            r#"SELECT payload AS "payload!" FROM outbox WHERE event_type = 'book.created'"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(payload["data"]["bookId"], serde_json::to_value(created_id)?);
+        assert_eq!(payload["data"]["ownedBy"], serde_json::to_value(user.id)?);
 
         let options = BookListOptions {
             limit: 20,
@@ -283,6 +606,69 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test(fixtures("common"))]
+    async fn test_create_many(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        // fixtures/common.sqlに記載のユーザーIDを指定
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let book = |title: &str| CreateBook {
+            title: title.into(),
+            author: "Test Author".into(),
+            isbn: "Test ISBN".into(),
+            description: "".into(),
+        };
+        let audit = || {
+            Some(CreateAuditLog::new(
+                Some(user_id),
+                AuditAction::ImportBooks,
+                AuditTargetType::Book,
+                None,
+            ))
+        };
+
+        let book_ids = repo
+            .create_many(vec![book("Book 1"), book("Book 2")], user_id, audit())
+            .await?;
+        assert_eq!(book_ids.len(), 2);
+        let created = repo.find_by_id(book_ids[1]).await?.unwrap();
+        assert_eq!(created.title, "Book 2");
+        assert_eq!(created.owner.id, user_id);
+        let events = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM outbox WHERE event_type = 'book.created'"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(events, 2);
+        // 登録した蔵書のIDが監査ログに記録される
+        let after = sqlx::query_scalar!(r#"SELECT after AS "after!" FROM audit_logs"#)
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(after["bookIds"], serde_json::to_value(&book_ids)?);
+
+        // 途中の蔵書を登録できない場合は、それまでの登録も監査ログの記録も取り消す
+        let res = repo
+            .create_many(
+                vec![book("Book 3"), book(&"x".repeat(256))],
+                user_id,
+                audit(),
+            )
+            .await;
+        assert!(res.is_err());
+        let books = repo
+            .find_all(BookListOptions {
+                limit: 10,
+                offset: 0,
+            })
+            .await?;
+        assert_eq!(books.total, 2);
+        let logs = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM audit_logs"#)
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(logs, 1);
+
+        Ok(())
+    }
+
     #[sqlx::test(fixtures("common", "book"))]
     async fn test_update_book_not_found(pool: sqlx::PgPool) -> anyhow::Result<()> {
         let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
@@ -314,7 +700,7 @@ mod tests {
             // fixtures/common.sqlに記載のユーザーIDを指定
             requested_user: UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?,
         };
-        repo.update(update_book).await?;
+        repo.update(update_book, None).await?;
 
         let book = repo
             .find_by_id(book_id)
@@ -324,4 +710,80 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_update_status(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let update = |status, note: Option<&str>| UpdateBookStatus {
+            book_id,
+            status,
+            note: note.map(String::from),
+            requested_user: user_id,
+        };
+
+        repo.update_status(update(BookStatus::Damaged, Some("表紙が破れている")), None)
+            .await?;
+        repo.update_status(update(BookStatus::InRepair, None), None)
+            .await?;
+        let book = repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.status, BookStatus::InRepair);
+
+        // 許可されていない遷移、貸出に関わる遷移はできない
+        for status in [BookStatus::Lost, BookStatus::OnLoan] {
+            let res = repo.update_status(update(status, None), None).await;
+            assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+        }
+
+        repo.update_status(update(BookStatus::Withdrawn, None), None)
+            .await?;
+        let res = repo
+            .update_status(update(BookStatus::Available, None), None)
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        let history = repo.find_status_history(book_id).await?;
+        let statuses: Vec<_> = history
+            .iter()
+            .map(|h| (h.previous_status, h.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (Some(BookStatus::Available), BookStatus::Damaged),
+                (Some(BookStatus::Damaged), BookStatus::InRepair),
+                (Some(BookStatus::InRepair), BookStatus::Withdrawn),
+            ]
+        );
+        assert_eq!(history[0].note.as_deref(), Some("表紙が破れている"));
+        assert_eq!(history[0].changed_by, Some(user_id));
+
+        // 変更ごとにドメインイベントが記録される
+        let payloads = sqlx::query_scalar!(
+            r#"SELECT payload AS "payload!" FROM outbox WHERE event_type = 'book.status_changed'"#
+        )
+        .fetch_all(&pool)
+        .await?;
+        assert_eq!(payloads.len(), 3);
+        assert!(payloads.iter().any(|payload| {
+            payload["data"]["status"] == "Damaged"
+                && payload["data"]["previousStatus"] == "Available"
+                && payload["data"]["changedBy"] == serde_json::to_value(user_id).unwrap()
+        }));
+
+        // 存在しない蔵書
+        let res = repo
+            .update_status(
+                UpdateBookStatus {
+                    book_id: BookId::new(),
+                    ..update(BookStatus::Damaged, None)
+                },
+                None,
+            )
+            .await;
+        assert!(matches!(res, Err(AppError::NotFoundError(_))));
+
+        Ok(())
+    }
 }
@@ -1,37 +1,49 @@
-use crate::database::{
-    model::checkout::{CheckoutRow, CheckoutStateRow, ReturnedCheckoutRow},
-    ConnectionPool,
+use crate::{
+    database::{
+        is_serialization_failure,
+        model::{
+            book::parse_book_status,
+            checkout::{
+                CheckoutRenewalStateRow, CheckoutRow, CheckoutStateRow,
+                PaginatedCheckoutHistoryRow, ReturnedCheckoutRow,
+            },
+        },
+        ConnectionPool, TransactionFuture,
+    },
+    repository::{
+        audit::record_audit_log,
+        book::change_book_status,
+        fee::{charge_fee, fetch_fee_balance, fetch_fee_schedule},
+        loan_policy::fetch_user_loan_policy,
+        outbox::record_event,
+    },
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use derive_new::new;
 use kernel::model::checkout::{
-    event::{CreateCheckout, UpdateReturned},
-    Checkout,
+    event::{
+        CreateCheckout, CreateCheckouts, DeclareLost, RenewCheckout, ReturnBooks, UpdateReturned,
+    },
+    BatchItemOutcome, BatchItemResult, BatchMode, Checkout, CheckoutHistoryOptions,
+};
+use kernel::model::{
+    audit::event::CreateAuditLog,
+    book::status::BookStatus,
+    domain_event::{DomainEvent, DomainEventKind},
+    fee::FeeEntryKind,
+    id::{BookId, CheckoutId, KioskId, UserId},
+    list::PaginatedList,
 };
-use kernel::model::id::{BookId, CheckoutId, UserId};
 use kernel::repository::checkout::CheckoutRepository;
 use shared::error::{AppError, AppResult};
+use sqlx::{Connection, PgConnection};
 
 #[derive(new)]
 pub struct CheckoutRepositoryImpl {
     db: ConnectionPool,
 }
 impl CheckoutRepositoryImpl {
-    /// トランザクション分離レベルをSERIALIZABLEに設定する
-    ///
-    /// SERIALIZABLEに設定する必要がある理由は以下
-    /// - 貸出処理と返却処理は、同時に実行されることがあるため
-    /// - SERIALIZABLEは、最も厳格なトランザクション分離レベルであり、同時実行性を制限することで、データの整合性を保つことができる
-    async fn set_transaction_serializable(
-        &self,
-        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
-    ) -> AppResult<()> {
-        sqlx::query("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE")
-            .execute(&mut **tx)
-            .await
-            .map_err(AppError::DatabaseOperationError)?;
-        Ok(())
-    }
     /// 本のIDから未返却のレコードを取得する
     async fn find_unreturned_by_book_id(&self, book_id: BookId) -> AppResult<Option<Checkout>> {
         let res = sqlx::query_as!(
@@ -42,6 +54,9 @@ impl CheckoutRepositoryImpl {
                     c.book_id,
                     c.user_id,
                     c.checked_out_at,
+                    c.due_at,
+                    c.renewal_count,
+                    c.checked_out_via AS "checked_out_via: KioskId",
                     b.title,
                     b.author,
                     b.isbn
@@ -60,27 +75,33 @@ impl CheckoutRepositoryImpl {
 
         Ok(res)
     }
-}
 
-#[async_trait]
-impl CheckoutRepository for CheckoutRepositoryImpl {
-    /// 貸出操作
-    async fn create_checkout(&self, event: CreateCheckout) -> AppResult<()> {
-        let mut tx = self.db.begin().await?;
-
-        self.set_transaction_serializable(&mut tx).await?;
+    // 以下の貸出・返却・延長の処理は、SERIALIZABLEのトランザクション内で実行する
+    //
+    // SERIALIZABLEに設定する必要がある理由は以下
+    // - 貸出処理と返却処理は、同時に実行されることがあるため
+    // - SERIALIZABLEは、最も厳格なトランザクション分離レベルであり、同時実行性を制限することで、データの整合性を保つことができる
+    //
+    // 競合した場合はトランザクション全体がやり直されるため、各処理は何度実行されてもよいように書く
 
+    /// 貸出処理
+    async fn try_create_checkout(
+        tx: &mut PgConnection,
+        event: CreateCheckout,
+    ) -> AppResult<CheckoutId> {
         // 事前チェック
         // 以下のいずれかの条件がNGの場合は処理を中断する
         //
         // - 指定されたIDを持つ蔵書が存在すること
         // - 存在した場合、蔵書が貸出中でないこと
+        // - 蔵書が貸出可能な状態であること(紛失・破損・修理中・除籍でないこと)
         {
             let res = sqlx::query_as!(
                 CheckoutStateRow,
                 r#"
                     SELECT
                         b.book_id,
+                        b.status AS book_status,
                         c.checkout_id AS "checkout_id?: CheckoutId",
                         NULL AS "user_id?: UserId"
                     FROM
@@ -114,11 +135,57 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                         event.book_id
                     )))
                 }
+                // 蔵書が貸出可能な状態でない場合
+                Some(CheckoutStateRow { book_status, .. })
+                    if parse_book_status(&book_status)? != BookStatus::Available =>
+                {
+                    return Err(AppError::UnprocessableEntity(format!(
+                        "指定された書籍({})は貸出できない状態({})です",
+                        event.book_id, book_status
+                    )))
+                }
                 // それ以外
                 _ => {}
             }
         }
 
+        // 貸出ポリシーのチェック
+        // 同時に貸出処理が行われても上限を超えないよう、同じトランザクション内で貸出中の冊数を数える
+        let policy = fetch_user_loan_policy(&mut *tx, event.checked_out_by)
+            .await?
+            .map(|p| p.effective())
+            .unwrap_or_default();
+        {
+            let loans = sqlx::query_scalar!(
+                r#"SELECT COUNT(*) AS "count!" FROM checkouts WHERE user_id = $1"#,
+                event.checked_out_by as _,
+            )
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(AppError::DatabaseOperationError)?;
+
+            if loans >= i64::from(policy.max_loans) {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "貸出中の書籍が上限({}冊)に達しているため、これ以上借りられません",
+                    policy.max_loans
+                )));
+            }
+        }
+
+        // 未払いの料金のチェック
+        {
+            let schedule = fetch_fee_schedule(&mut *tx).await?;
+            if schedule.checkout_block_threshold.is_some() {
+                let balance = fetch_fee_balance(&mut *tx, event.checked_out_by).await?;
+                if schedule.blocks_checkout(balance) {
+                    return Err(AppError::UnprocessableEntity(format!(
+                        "未払いの料金({}円)があるため、支払うまで借りられません",
+                        balance
+                    )));
+                }
+            }
+        }
+
         // 貸出処理の実行
         let checkout_id = CheckoutId::new();
         let res = sqlx::query!(
@@ -127,19 +194,25 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                     checkout_id,
                     book_id,
                     user_id,
-                    checked_out_at
+                    checked_out_at,
+                    due_at,
+                    checked_out_via
                 )
                 VALUES(
                     $1,
                     $2,
                     $3,
-                    $4
+                    $4,
+                    $5,
+                    $6
                 )
             "#,
             checkout_id as _,
             event.book_id as _,
             event.checked_out_by as _,
             event.checked_out_at,
+            event.checked_out_at + policy.loan_period(),
+            event.checked_out_via as _,
         )
         .execute(&mut *tx)
         .await
@@ -151,18 +224,34 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
             ));
         }
 
-        tx.commit()
-            .await
-            .map_err(AppError::DatabaseOperationError)?;
+        change_book_status(
+            &mut *tx,
+            event.book_id,
+            BookStatus::Available,
+            BookStatus::OnLoan,
+            event.checked_out_by,
+            None,
+        )
+        .await?;
 
-        Ok(())
+        Ok(checkout_id)
     }
 
-    /// 返却操作
-    async fn update_returned(&self, event: UpdateReturned) -> AppResult<()> {
-        let mut tx = self.db.begin().await?;
-
-        self.set_transaction_serializable(&mut tx).await?;
+    /// 貸出の終了処理
+    /// 返却の場合は蔵書を貸出可能に、紛失の届け出の場合は紛失状態にする
+    async fn try_close_checkout(
+        tx: &mut PgConnection,
+        checkout_id: CheckoutId,
+        book_id: BookId,
+        closed_by: UserId,
+        closed_at: DateTime<Utc>,
+        closed_via: Option<KioskId>,
+        next_status: BookStatus,
+    ) -> AppResult<()> {
+        let action = match next_status {
+            BookStatus::Lost => "紛失の届け出",
+            _ => "返却",
+        };
 
         // 事前チェック
         // 以下のいずれかの条件がNGの場合は処理を中断する
@@ -175,6 +264,7 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                 r#"
                     SELECT
                         b.book_id,
+                        b.status AS book_status,
                         c.checkout_id AS "checkout_id?: CheckoutId",
                         c.user_id AS "user_id?: UserId"
                     FROM
@@ -184,7 +274,7 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                     WHERE
                         book_id = $1
                 "#,
-                event.book_id as _,
+                book_id as _,
             )
             .fetch_optional(&mut *tx)
             .await
@@ -195,17 +285,17 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                 None => {
                     return Err(AppError::NotFoundError(format!(
                         "指定された書籍({})が見つかりません",
-                        event.book_id
+                        book_id
                     )))
                 }
                 Some(CheckoutStateRow {
                     checkout_id: Some(c),
                     user_id: Some(u),
                     .. // ignore other fields
-                }) if c != event.checkout_id || u != event.returned_by => {
+                }) if c != checkout_id || u != closed_by => {
                     return Err(AppError::UnprocessableEntity(format!(
-                        "指定の貸出(ID({}), ユーザー({}), 書籍({}))は、返却できません",
-                        event.checkout_id, event.returned_by, event.book_id
+                        "指定の貸出(ID({}), ユーザー({}), 書籍({}))は、{}できません",
+                        checkout_id, closed_by, book_id, action
                     )))
                 }
                 // それ以外
@@ -213,38 +303,370 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
             }
         }
 
-        // 返却処理の実行
-        let res = sqlx::query!(
+        // 貸出の終了処理の実行
+        // 紛失の届け出の場合も、届け出た日時を返却日時として貸出履歴に残す
+        let due_at = sqlx::query_scalar!(
             r#"
                 INSERT INTO returned_checkouts(
                     checkout_id,
                     book_id,
                     user_id,
                     checked_out_at,
-                    returned_at
+                    returned_at,
+                    due_at,
+                    renewal_count,
+                    checked_out_via,
+                    returned_via
                 )
-                SELECT checkout_id, book_id, user_id, checked_out_at, $2
+                SELECT checkout_id, book_id, user_id, checked_out_at, $2, due_at, renewal_count, checked_out_via, $3
                 FROM checkouts
                 WHERE checkout_id = $1
+                RETURNING due_at
             "#,
-            event.checkout_id as _,
-            event.returned_at,
+            checkout_id as _,
+            closed_at,
+            closed_via as _,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .ok_or_else(|| AppError::NoRowsAffectedError(format!("{}処理に失敗しました", action)))?;
+
+        let res = sqlx::query!(
+            r#"
+                DELETE FROM checkouts WHERE checkout_id = $1;
+            "#,
+            checkout_id as _,
         )
         .execute(&mut *tx)
         .await
         .map_err(AppError::DatabaseOperationError)?;
 
         if res.rows_affected() < 1 {
-            return Err(AppError::NoRowsAffectedError(
-                "返却処理に失敗しました".into(),
+            return Err(AppError::NoRowsAffectedError(format!(
+                "{}処理に失敗しました",
+                action
+            )));
+        }
+
+        change_book_status(
+            &mut *tx,
+            book_id,
+            BookStatus::OnLoan,
+            next_status,
+            closed_by,
+            None,
+        )
+        .await?;
+
+        // 料金表に従い、延滞料または紛失の料金を請求する
+        let schedule = fetch_fee_schedule(&mut *tx).await?;
+        let (kind, amount) = match next_status {
+            BookStatus::Lost => (FeeEntryKind::Lost, schedule.lost_book_fee),
+            _ => (
+                FeeEntryKind::Overdue,
+                schedule.overdue_fee(due_at, closed_at),
+            ),
+        };
+        charge_fee(&mut *tx, closed_by, kind, amount, checkout_id, book_id).await?;
+
+        Ok(())
+    }
+
+    /// 蔵書のIDから、返却するユーザーが借りている貸出を特定して返却する
+    async fn try_return_by_book_id(
+        tx: &mut PgConnection,
+        book_id: BookId,
+        returned_by: UserId,
+        returned_at: DateTime<Utc>,
+        returned_via: Option<KioskId>,
+    ) -> AppResult<CheckoutId> {
+        let res = sqlx::query_as!(
+            CheckoutStateRow,
+            r#"
+                SELECT
+                    b.book_id,
+                    b.status AS book_status,
+                    c.checkout_id AS "checkout_id?: CheckoutId",
+                    c.user_id AS "user_id?: UserId"
+                FROM
+                    books AS b
+                    LEFT OUTER JOIN checkouts AS c
+                    USING(book_id)
+                WHERE
+                    book_id = $1
+            "#,
+            book_id as _,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        let checkout_id = match res {
+            None => {
+                return Err(AppError::NotFoundError(format!(
+                    "指定された書籍({})が見つかりません",
+                    book_id
+                )))
+            }
+            Some(CheckoutStateRow {
+                checkout_id: Some(c),
+                user_id: Some(u),
+                ..
+            }) if u == returned_by => c,
+            Some(_) => {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "指定された書籍({})は、ユーザー({})に貸し出されていません",
+                    book_id, returned_by
+                )))
+            }
+        };
+
+        Self::try_close_checkout(
+            tx,
+            checkout_id,
+            book_id,
+            returned_by,
+            returned_at,
+            returned_via,
+            BookStatus::Available,
+        )
+        .await?;
+
+        Ok(checkout_id)
+    }
+
+    /// 貸出中の貸出を、ドメインイベントに含める形で取得する
+    async fn fetch_checkouts(
+        tx: &mut PgConnection,
+        checkout_ids: &[CheckoutId],
+    ) -> AppResult<Vec<Checkout>> {
+        let ids: Vec<_> = checkout_ids.iter().map(|id| id.raw()).collect();
+        sqlx::query_as!(
+            CheckoutRow,
+            r#"
+                SELECT
+                    c.checkout_id,
+                    c.book_id,
+                    c.user_id,
+                    c.checked_out_at,
+                    c.due_at,
+                    c.renewal_count,
+                    c.checked_out_via AS "checked_out_via: KioskId",
+                    b.title,
+                    b.author,
+                    b.isbn
+                FROM
+                    checkouts AS c
+                    INNER JOIN books AS b USING(book_id)
+                WHERE c.checkout_id = ANY($1)
+                ORDER BY c.checked_out_at ASC, c.checkout_id
+            "#,
+            &ids
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map(|rows| rows.into_iter().map(Checkout::from).collect())
+        .map_err(AppError::DatabaseOperationError)
+    }
+
+    /// 返却済みの貸出を、ドメインイベントに含める形で取得する
+    async fn fetch_returned_checkouts(
+        tx: &mut PgConnection,
+        checkout_ids: &[CheckoutId],
+    ) -> AppResult<Vec<Checkout>> {
+        let ids: Vec<_> = checkout_ids.iter().map(|id| id.raw()).collect();
+        sqlx::query_as!(
+            ReturnedCheckoutRow,
+            r#"
+                SELECT
+                    rc.checkout_id,
+                    rc.book_id,
+                    rc.user_id,
+                    rc.checked_out_at,
+                    rc.returned_at,
+                    rc.due_at,
+                    rc.renewal_count,
+                    rc.checked_out_via AS "checked_out_via: KioskId",
+                    rc.returned_via AS "returned_via: KioskId",
+                    b.title,
+                    b.author,
+                    b.isbn
+                FROM
+                    returned_checkouts AS rc
+                    INNER JOIN books AS b USING(book_id)
+                WHERE rc.checkout_id = ANY($1)
+                ORDER BY rc.checked_out_at ASC, rc.checkout_id
+            "#,
+            &ids
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map(|rows| rows.into_iter().map(Checkout::from).collect())
+        .map_err(AppError::DatabaseOperationError)
+    }
+
+    /// 1回の操作で貸し出された蔵書を、ドメインイベントとして記録する
+    async fn record_checked_out(
+        tx: &mut PgConnection,
+        user_id: UserId,
+        checkout_ids: &[CheckoutId],
+    ) -> AppResult<()> {
+        if checkout_ids.is_empty() {
+            return Ok(());
+        }
+        let checkouts = Self::fetch_checkouts(&mut *tx, checkout_ids).await?;
+        record_event(
+            tx,
+            &DomainEvent::new(DomainEventKind::CheckedOut { user_id, checkouts }),
+        )
+        .await
+    }
+
+    /// 1回の操作で返却された蔵書を、ドメインイベントとして記録する
+    async fn record_returned(
+        tx: &mut PgConnection,
+        user_id: UserId,
+        checkout_ids: &[CheckoutId],
+    ) -> AppResult<()> {
+        if checkout_ids.is_empty() {
+            return Ok(());
+        }
+        let checkouts = Self::fetch_returned_checkouts(&mut *tx, checkout_ids).await?;
+        record_event(
+            tx,
+            &DomainEvent::new(DomainEventKind::Returned { user_id, checkouts }),
+        )
+        .await
+    }
+
+    /// 操作した貸出ごとに監査ログを記録する
+    async fn record_audit_logs(
+        tx: &mut PgConnection,
+        audit: Option<CreateAuditLog>,
+        checkouts: &[(BookId, CheckoutId)],
+    ) -> AppResult<()> {
+        let Some(audit) = audit else {
+            return Ok(());
+        };
+        for (book_id, checkout_id) in checkouts {
+            let audit = CreateAuditLog {
+                target_id: Some(checkout_id.to_string()),
+                ..audit.clone()
+            }
+            .with_after_field("bookId", serde_json::json!(book_id));
+            record_audit_log(&mut *tx, audit).await?;
+        }
+        Ok(())
+    }
+
+    /// 複数の蔵書に対して`f`を順に実行する
+    ///
+    /// 蔵書ごとにセーブポイントを設け、失敗した蔵書の処理のみを取り消して残りの蔵書の処理を続ける。
+    /// `BatchMode::AllOrNothing`の場合は、1冊でも失敗していれば最後にすべての処理を取り消す。
+    /// 競合による失敗はトランザクション全体をやり直すため、そのままエラーとして返す
+    async fn try_batch<F>(
+        tx: &mut PgConnection,
+        book_ids: &[BookId],
+        mode: BatchMode,
+        f: F,
+    ) -> AppResult<Vec<BatchItemResult>>
+    where
+        F: for<'c> Fn(&'c mut PgConnection, BookId) -> TransactionFuture<'c, CheckoutId>,
+    {
+        let mut batch = tx.begin().await.map_err(AppError::TransactionError)?;
+        let mut results = Vec::with_capacity(book_ids.len());
+        for &book_id in book_ids {
+            let mut item = batch.begin().await.map_err(AppError::TransactionError)?;
+            let outcome = match f(&mut item, book_id).await {
+                Ok(checkout_id) => {
+                    item.commit().await.map_err(AppError::TransactionError)?;
+                    BatchItemOutcome::Succeeded(checkout_id)
+                }
+                Err(e) if is_serialization_failure(&e) => return Err(e),
+                Err(e) => {
+                    item.rollback().await.map_err(AppError::TransactionError)?;
+                    BatchItemOutcome::Failed(e)
+                }
+            };
+            results.push(BatchItemResult { book_id, outcome });
+        }
+
+        if mode == BatchMode::AllOrNothing && !results.iter().all(BatchItemResult::is_succeeded) {
+            batch.rollback().await.map_err(AppError::TransactionError)?;
+            for result in results.iter_mut() {
+                if let BatchItemOutcome::Succeeded(checkout_id) = result.outcome {
+                    result.outcome = BatchItemOutcome::RolledBack(checkout_id);
+                }
+            }
+        } else {
+            batch.commit().await.map_err(AppError::TransactionError)?;
+        }
+
+        Ok(results)
+    }
+
+    /// 貸出延長処理
+    async fn try_renew(tx: &mut PgConnection, event: RenewCheckout) -> AppResult<()> {
+        // 事前チェック
+        // 以下のいずれかの条件がNGの場合は処理を中断する
+        //
+        // - 指定された貸出が存在し、延長するユーザーが借りたものであること
+        // - 返却期限を過ぎていないこと
+        // - 延長回数が貸出ポリシーの上限に達していないこと
+        let state = sqlx::query_as!(
+            CheckoutRenewalStateRow,
+            r#"
+                SELECT user_id, due_at, renewal_count
+                FROM checkouts
+                WHERE checkout_id = $1 AND book_id = $2
+            "#,
+            event.checkout_id as _,
+            event.book_id as _,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .ok_or_else(|| {
+            AppError::NotFoundError(format!(
+                "指定された貸出(ID({}), 書籍({}))が見つかりません",
+                event.checkout_id, event.book_id
+            ))
+        })?;
+
+        if state.user_id != event.renewed_by {
+            return Err(AppError::UnprocessableEntity(format!(
+                "指定の貸出(ID({}), ユーザー({}), 書籍({}))は、延長できません",
+                event.checkout_id, event.renewed_by, event.book_id
+            )));
+        }
+        if state.due_at < event.renewed_at {
+            return Err(AppError::UnprocessableEntity(
+                "返却期限を過ぎた貸出は延長できません".into(),
             ));
         }
 
+        let policy = fetch_user_loan_policy(&mut *tx, event.renewed_by)
+            .await?
+            .map(|p| p.effective())
+            .unwrap_or_default();
+        if state.renewal_count >= policy.max_renewals {
+            return Err(AppError::UnprocessableEntity(format!(
+                "延長回数が上限({}回)に達しているため、これ以上延長できません",
+                policy.max_renewals
+            )));
+        }
+
+        // 延長した日から貸出期間を数え直す(現在の返却期限より早まることはない)
+        let due_at = state.due_at.max(event.renewed_at + policy.loan_period());
         let res = sqlx::query!(
             r#"
-                DELETE FROM checkouts WHERE checkout_id = $1;
+                UPDATE checkouts
+                SET due_at = $2, renewal_count = renewal_count + 1
+                WHERE checkout_id = $1
             "#,
             event.checkout_id as _,
+            due_at,
         )
         .execute(&mut *tx)
         .await
@@ -252,14 +674,211 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
 
         if res.rows_affected() < 1 {
             return Err(AppError::NoRowsAffectedError(
-                "返却処理に失敗しました".into(),
+                "延長処理に失敗しました".into(),
             ));
         }
 
-        tx.commit().await.map_err(AppError::TransactionError)?;
-
         Ok(())
     }
+}
+
+/// 一括処理の結果のうち、反映された貸出の蔵書のIDと貸出のID
+fn succeeded(results: &[BatchItemResult]) -> Vec<(BookId, CheckoutId)> {
+    results
+        .iter()
+        .filter_map(|result| match result.outcome {
+            BatchItemOutcome::Succeeded(checkout_id) => Some((result.book_id, checkout_id)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[async_trait]
+impl CheckoutRepository for CheckoutRepositoryImpl {
+    /// 貸出操作
+    async fn create_checkout(
+        &self,
+        event: CreateCheckout,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<CheckoutId> {
+        self.db
+            .serializable(move |tx| {
+                let audit = audit.clone();
+                Box::pin(async move {
+                    let checkout_id = Self::try_create_checkout(&mut *tx, event).await?;
+                    Self::record_checked_out(&mut *tx, event.checked_out_by, &[checkout_id])
+                        .await?;
+                    Self::record_audit_logs(tx, audit, &[(event.book_id, checkout_id)]).await?;
+                    Ok(checkout_id)
+                })
+            })
+            .await
+    }
+
+    /// 返却操作
+    async fn update_returned(
+        &self,
+        event: UpdateReturned,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()> {
+        self.db
+            .serializable(move |tx| {
+                let audit = audit.clone();
+                Box::pin(async move {
+                    Self::try_close_checkout(
+                        &mut *tx,
+                        event.checkout_id,
+                        event.book_id,
+                        event.returned_by,
+                        event.returned_at,
+                        None,
+                        BookStatus::Available,
+                    )
+                    .await?;
+                    Self::record_returned(&mut *tx, event.returned_by, &[event.checkout_id])
+                        .await?;
+                    Self::record_audit_logs(tx, audit, &[(event.book_id, event.checkout_id)]).await
+                })
+            })
+            .await
+    }
+
+    /// 紛失の届け出操作
+    async fn declare_lost(
+        &self,
+        event: DeclareLost,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()> {
+        self.db
+            .serializable(move |tx| {
+                let audit = audit.clone();
+                Box::pin(async move {
+                    Self::try_close_checkout(
+                        &mut *tx,
+                        event.checkout_id,
+                        event.book_id,
+                        event.declared_by,
+                        event.declared_at,
+                        None,
+                        BookStatus::Lost,
+                    )
+                    .await?;
+                    let checkout = Self::fetch_returned_checkouts(&mut *tx, &[event.checkout_id])
+                        .await?
+                        .pop()
+                        .ok_or_else(|| {
+                            AppError::NoRowsAffectedError("紛失の届け出処理に失敗しました".into())
+                        })?;
+                    record_event(
+                        &mut *tx,
+                        &DomainEvent::new(DomainEventKind::DeclaredLost(checkout)),
+                    )
+                    .await?;
+                    Self::record_audit_logs(tx, audit, &[(event.book_id, event.checkout_id)]).await
+                })
+            })
+            .await
+    }
+
+    /// 一括貸出操作
+    async fn create_checkouts(
+        &self,
+        event: CreateCheckouts,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<Vec<BatchItemResult>> {
+        let CreateCheckouts {
+            book_ids,
+            checked_out_by,
+            checked_out_at,
+            mode,
+            checked_out_via,
+        } = event;
+        self.db
+            .serializable(move |tx| {
+                let book_ids = book_ids.clone();
+                let audit = audit.clone();
+                Box::pin(async move {
+                    let results = Self::try_batch(&mut *tx, &book_ids, mode, |item, book_id| {
+                        Box::pin(Self::try_create_checkout(
+                            item,
+                            CreateCheckout {
+                                checked_out_via,
+                                ..CreateCheckout::new(book_id, checked_out_by, checked_out_at)
+                            },
+                        ))
+                    })
+                    .await?;
+                    let checkouts = succeeded(&results);
+                    let checkout_ids = checkouts.iter().map(|(_, id)| *id).collect::<Vec<_>>();
+                    Self::record_checked_out(&mut *tx, checked_out_by, &checkout_ids).await?;
+                    Self::record_audit_logs(tx, audit, &checkouts).await?;
+                    Ok(results)
+                })
+            })
+            .await
+    }
+
+    /// 一括返却操作
+    async fn return_books(
+        &self,
+        event: ReturnBooks,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<Vec<BatchItemResult>> {
+        let ReturnBooks {
+            book_ids,
+            returned_by,
+            returned_at,
+            mode,
+            returned_via,
+        } = event;
+        self.db
+            .serializable(move |tx| {
+                let book_ids = book_ids.clone();
+                let audit = audit.clone();
+                Box::pin(async move {
+                    let results = Self::try_batch(&mut *tx, &book_ids, mode, |item, book_id| {
+                        Box::pin(Self::try_return_by_book_id(
+                            item,
+                            book_id,
+                            returned_by,
+                            returned_at,
+                            returned_via,
+                        ))
+                    })
+                    .await?;
+                    let checkouts = succeeded(&results);
+                    let checkout_ids = checkouts.iter().map(|(_, id)| *id).collect::<Vec<_>>();
+                    Self::record_returned(&mut *tx, returned_by, &checkout_ids).await?;
+                    Self::record_audit_logs(tx, audit, &checkouts).await?;
+                    Ok(results)
+                })
+            })
+            .await
+    }
+
+    /// 貸出延長操作
+    async fn renew(&self, event: RenewCheckout, audit: Option<CreateAuditLog>) -> AppResult<()> {
+        self.db
+            .serializable(move |tx| {
+                let audit = audit.clone();
+                Box::pin(async move {
+                    Self::try_renew(&mut *tx, event).await?;
+                    let checkout = Self::fetch_checkouts(&mut *tx, &[event.checkout_id])
+                        .await?
+                        .pop()
+                        .ok_or_else(|| {
+                            AppError::NoRowsAffectedError("延長処理に失敗しました".into())
+                        })?;
+                    record_event(
+                        &mut *tx,
+                        &DomainEvent::new(DomainEventKind::Renewed(checkout)),
+                    )
+                    .await?;
+                    Self::record_audit_logs(tx, audit, &[(event.book_id, event.checkout_id)]).await
+                })
+            })
+            .await
+    }
 
     /// 全ての未返却の貸出し情報を取得する
     async fn find_unreturned_all(&self) -> AppResult<Vec<Checkout>> {
@@ -274,6 +893,9 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                     c.book_id,
                     c.user_id,
                     c.checked_out_at,
+                    c.due_at,
+                    c.renewal_count,
+                    c.checked_out_via AS "checked_out_via: KioskId",
                     b.title,
                     b.author,
                     b.isbn
@@ -289,6 +911,38 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
         .map_err(AppError::DatabaseOperationError)
     }
 
+    async fn find_unreturned_due_before(
+        &self,
+        due_before: DateTime<Utc>,
+    ) -> AppResult<Vec<Checkout>> {
+        sqlx::query_as!(
+            CheckoutRow,
+            r#"
+                SELECT
+                    c.checkout_id,
+                    c.book_id,
+                    c.user_id,
+                    c.checked_out_at,
+                    c.due_at,
+                    c.renewal_count,
+                    c.checked_out_via AS "checked_out_via: KioskId",
+                    b.title,
+                    b.author,
+                    b.isbn
+                FROM
+                    checkouts AS c
+                    INNER JOIN books AS b USING(book_id)
+                WHERE c.due_at < $1
+                ORDER BY c.due_at ASC
+            "#,
+            due_before
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map(|rows| rows.into_iter().map(Checkout::from).collect())
+        .map_err(AppError::DatabaseOperationError)
+    }
+
     // 特定ユーザーの未返却の貸出し情報を取得する
     async fn find_unreturned_by_user_id(&self, user_id: UserId) -> AppResult<Vec<Checkout>> {
         sqlx::query_as!(
@@ -299,6 +953,9 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                     c.book_id,
                     c.user_id,
                     c.checked_out_at,
+                    c.due_at,
+                    c.renewal_count,
+                    c.checked_out_via AS "checked_out_via: KioskId",
                     b.title,
                     b.author,
                     b.isbn
@@ -334,6 +991,10 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                     rc.user_id,
                     rc.checked_out_at,
                     rc.returned_at,
+                    rc.due_at,
+                    rc.renewal_count,
+                    rc.checked_out_via AS "checked_out_via: KioskId",
+                    rc.returned_via AS "returned_via: KioskId",
                     b.title,
                     b.author,
                     b.isbn
@@ -363,4 +1024,724 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
 
         Ok(checkout_histories)
     }
+
+    // 特定ユーザーの貸出履歴(返却済みも含む)を取得する
+    async fn find_history_by_user_id(
+        &self,
+        user_id: UserId,
+        options: CheckoutHistoryOptions,
+    ) -> AppResult<PaginatedList<Checkout>> {
+        let CheckoutHistoryOptions {
+            limit,
+            offset,
+            from,
+            to,
+        } = options;
+
+        // 未返却の貸出と返却済みの貸出履歴をまとめ、貸出日時の新しい順に並べる
+        let rows = sqlx::query_as!(
+            PaginatedCheckoutHistoryRow,
+            r#"
+                SELECT
+                    COUNT(*) OVER() AS "total!",
+                    h.checkout_id AS "checkout_id!: CheckoutId",
+                    h.book_id AS "book_id!: BookId",
+                    h.user_id AS "user_id!: UserId",
+                    h.checked_out_at AS "checked_out_at!",
+                    h.returned_at,
+                    h.due_at AS "due_at!",
+                    h.renewal_count AS "renewal_count!",
+                    h.checked_out_via AS "checked_out_via?: KioskId",
+                    h.returned_via AS "returned_via?: KioskId",
+                    b.title,
+                    b.author,
+                    b.isbn
+                FROM (
+                    SELECT checkout_id, book_id, user_id, checked_out_at, NULL::TIMESTAMPTZ AS returned_at, due_at, renewal_count, checked_out_via, NULL::UUID AS returned_via
+                    FROM checkouts
+                    WHERE user_id = $1
+                    UNION ALL
+                    SELECT checkout_id, book_id, user_id, checked_out_at, returned_at, due_at, renewal_count, checked_out_via, returned_via
+                    FROM returned_checkouts
+                    WHERE user_id = $1
+                ) AS h
+                INNER JOIN books AS b USING(book_id)
+                WHERE
+                    ($2::TIMESTAMPTZ IS NULL OR h.checked_out_at >= $2)
+                    AND ($3::TIMESTAMPTZ IS NULL OR h.checked_out_at < $3)
+                ORDER BY h.checked_out_at DESC, h.checkout_id
+                LIMIT $4
+                OFFSET $5
+            "#,
+            user_id as _,
+            from,
+            to,
+            limit,
+            offset,
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        let total = rows.first().map(|r| r.total).unwrap_or_default();
+        let items = rows.into_iter().map(Checkout::from).collect();
+
+        Ok(PaginatedList {
+            total,
+            limit,
+            offset,
+            items,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, sync::Arc};
+
+    use chrono::{Duration, TimeZone, Utc};
+    use kernel::{
+        model::{
+            audit::{AuditAction, AuditTargetType},
+            book::event::UpdateBookStatus,
+            loan_policy::{
+                event::{UpdateRoleLoanPolicy, UpdateUserLoanPolicy},
+                LoanPolicy, LoanPolicyOverride,
+            },
+            role::Role,
+        },
+        repository::{book::BookRepository, loan_policy::LoanPolicyRepository},
+    };
+
+    use super::*;
+    use crate::repository::{book::BookRepositoryImpl, loan_policy::LoanPolicyRepositoryImpl};
+
+    // fixtures/common.sql、fixtures/book.sqlに記載のID
+    const FIXTURE_USER_ID: &str = "5b4c96ac-316a-4bee-8e69-cac5eb84ff4c";
+    const FIXTURE_BOOK_IDS: [&str; 3] = [
+        "9890736e-a4e4-461a-a77d-eac3517ef11b",
+        "f397b83a-dd2a-4a01-9e77-db1eea7de5b6",
+        "17afb850-c786-49c5-a303-a3a443a2212c",
+    ];
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_find_history_by_user_id(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = CheckoutRepositoryImpl::new(ConnectionPool::new(pool));
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+        let base = Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
+
+        // 1冊目・2冊目は借りて返却済み、3冊目は貸出中
+        for (i, book_id) in FIXTURE_BOOK_IDS.iter().enumerate() {
+            let book_id = BookId::from_str(book_id)?;
+            let checked_out_at = base + Duration::days(i as i64 * 10);
+            repo.create_checkout(CreateCheckout::new(book_id, user_id, checked_out_at), None)
+                .await?;
+            if i < 2 {
+                let checkout = repo
+                    .find_unreturned_by_user_id(user_id)
+                    .await?
+                    .into_iter()
+                    .find(|c| c.book.book_id == book_id)
+                    .unwrap();
+                repo.update_returned(
+                    UpdateReturned::new(
+                        checkout.id,
+                        book_id,
+                        user_id,
+                        checked_out_at + Duration::days(3),
+                    ),
+                    None,
+                )
+                .await?;
+            }
+        }
+
+        let history = repo
+            .find_history_by_user_id(
+                user_id,
+                CheckoutHistoryOptions {
+                    limit: 10,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        assert_eq!(history.total, 3);
+        // 貸出日時の新しい順に、貸出中のものも含めて取得できる
+        let book_ids: Vec<_> = history.items.iter().map(|c| c.book.book_id).collect();
+        assert_eq!(
+            book_ids,
+            FIXTURE_BOOK_IDS
+                .iter()
+                .rev()
+                .map(|id| BookId::from_str(id))
+                .collect::<Result<Vec<_>, _>>()?
+        );
+        assert!(history.items[0].returned_at.is_none());
+        assert!(history.items[1].returned_at.is_some());
+
+        // ページング
+        let page = repo
+            .find_history_by_user_id(
+                user_id,
+                CheckoutHistoryOptions {
+                    limit: 1,
+                    offset: 1,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].book.book_id, history.items[1].book.book_id);
+
+        // 期間による絞り込み(開始は含み、終了は含まない)
+        let filtered = repo
+            .find_history_by_user_id(
+                user_id,
+                CheckoutHistoryOptions {
+                    limit: 10,
+                    offset: 0,
+                    from: Some(base + Duration::days(10)),
+                    to: Some(base + Duration::days(20)),
+                },
+            )
+            .await?;
+        assert_eq!(filtered.total, 1);
+        assert_eq!(
+            filtered.items[0].book.book_id,
+            BookId::from_str(FIXTURE_BOOK_IDS[1])?
+        );
+
+        // 他のユーザーの履歴は含まれない
+        let others = repo
+            .find_history_by_user_id(
+                UserId::new(),
+                CheckoutHistoryOptions {
+                    limit: 10,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        assert_eq!(others.total, 0);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_create_checkout_enforces_loan_policy(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool);
+        let repo = CheckoutRepositoryImpl::new(db.clone());
+        let policies = LoanPolicyRepositoryImpl::new(db);
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+        let now = Utc::now();
+
+        policies
+            .update_user_policy(UpdateUserLoanPolicy {
+                user_id,
+                user_override: LoanPolicyOverride {
+                    max_loans: Some(2),
+                    loan_period_days: Some(7),
+                    max_renewals: Some(1),
+                },
+            })
+            .await?;
+
+        let book_ids = FIXTURE_BOOK_IDS
+            .iter()
+            .map(|id| BookId::from_str(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        for book_id in &book_ids[..2] {
+            repo.create_checkout(CreateCheckout::new(*book_id, user_id, now), None)
+                .await?;
+        }
+
+        // 返却期限は貸出期間から計算される
+        let checkouts = repo.find_unreturned_by_user_id(user_id).await?;
+        assert!(checkouts
+            .iter()
+            .all(|c| c.due_at == c.checked_out_at + Duration::days(7) && c.renewal_count == 0));
+
+        // 上限を超える貸出はできない
+        let res = repo
+            .create_checkout(CreateCheckout::new(book_ids[2], user_id, now), None)
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 返却すれば再び借りられる
+        let returned = &checkouts[0];
+        repo.update_returned(
+            UpdateReturned::new(returned.id, returned.book.book_id, user_id, now),
+            None,
+        )
+        .await?;
+        repo.create_checkout(CreateCheckout::new(book_ids[2], user_id, now), None)
+            .await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_renew(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool);
+        let repo = CheckoutRepositoryImpl::new(db.clone());
+        let policies = LoanPolicyRepositoryImpl::new(db);
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+        let book_id = BookId::from_str(FIXTURE_BOOK_IDS[0])?;
+        let checked_out_at = Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
+
+        policies
+            .update_role_policy(UpdateRoleLoanPolicy {
+                role: Role::Admin,
+                policy: LoanPolicy {
+                    max_loans: 5,
+                    loan_period_days: 14,
+                    max_renewals: 1,
+                },
+            })
+            .await?;
+        repo.create_checkout(CreateCheckout::new(book_id, user_id, checked_out_at), None)
+            .await?;
+        let checkout = repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+
+        // 他のユーザーは延長できない
+        let res = repo
+            .renew(
+                RenewCheckout::new(checkout.id, book_id, UserId::new(), checked_out_at),
+                None,
+            )
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 延長した日から貸出期間を数え直す
+        let renewed_at = checked_out_at + Duration::days(10);
+        repo.renew(
+            RenewCheckout::new(checkout.id, book_id, user_id, renewed_at),
+            None,
+        )
+        .await?;
+        let renewed = repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+        assert_eq!(renewed.due_at, renewed_at + Duration::days(14));
+        assert_eq!(renewed.renewal_count, 1);
+
+        // 延長回数の上限を超えては延長できない
+        let res = repo
+            .renew(
+                RenewCheckout::new(checkout.id, book_id, user_id, renewed_at),
+                None,
+            )
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 延長回数は返却後の履歴にも残る
+        repo.update_returned(
+            UpdateReturned::new(checkout.id, book_id, user_id, renewed_at),
+            None,
+        )
+        .await?;
+        let history = repo.find_history_by_book_id(book_id).await?;
+        assert_eq!(history[0].renewal_count, 1);
+        assert_eq!(history[0].due_at, renewed.due_at);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_concurrent_checkouts_of_one_book(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = Arc::new(CheckoutRepositoryImpl::new(ConnectionPool::new(pool)));
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+        let book_id = BookId::from_str(FIXTURE_BOOK_IDS[0])?;
+
+        // 同じ蔵書を、試行回数の上限を超える数のタスクから同時に借りようとする
+        let tasks: Vec<_> = (0..16)
+            .map(|_| {
+                let repo = repo.clone();
+                tokio::spawn(async move {
+                    repo.create_checkout(CreateCheckout::new(book_id, user_id, Utc::now()), None)
+                        .await
+                })
+            })
+            .collect();
+
+        let mut succeeded = 0;
+        for task in tasks {
+            match task.await? {
+                Ok(_) => succeeded += 1,
+                // 競合が再試行で解消した場合は、貸出中である旨のエラーになる
+                Err(AppError::UnprocessableEntity(_)) => {}
+                // 再試行を使い切った場合も、内部エラーではなく再度試せる旨のエラーになる
+                Err(AppError::TransactionConflictError) => {}
+                Err(e) => panic!("unexpected error: {e:?}"),
+            }
+        }
+        assert_eq!(succeeded, 1);
+        assert_eq!(repo.find_unreturned_by_user_id(user_id).await?.len(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_concurrent_checkouts_respect_loan_policy(
+        pool: sqlx::PgPool,
+    ) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool);
+        let repo = Arc::new(CheckoutRepositoryImpl::new(db.clone()));
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+
+        LoanPolicyRepositoryImpl::new(db)
+            .update_user_policy(UpdateUserLoanPolicy {
+                user_id,
+                user_override: LoanPolicyOverride {
+                    max_loans: Some(2),
+                    ..Default::default()
+                },
+            })
+            .await?;
+
+        // 別々の蔵書を同時に借りても、上限を超えて貸し出されない
+        let tasks: Vec<_> = FIXTURE_BOOK_IDS
+            .iter()
+            .map(|id| {
+                let repo = repo.clone();
+                let book_id = BookId::from_str(id).unwrap();
+                tokio::spawn(async move {
+                    repo.create_checkout(CreateCheckout::new(book_id, user_id, Utc::now()), None)
+                        .await
+                })
+            })
+            .collect();
+
+        let mut succeeded = 0;
+        for task in tasks {
+            match task.await? {
+                Ok(_) => succeeded += 1,
+                Err(AppError::UnprocessableEntity(_)) => {}
+                Err(e) => panic!("unexpected error: {e:?}"),
+            }
+        }
+        assert_eq!(succeeded, 2);
+        assert_eq!(repo.find_unreturned_by_user_id(user_id).await?.len(), 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_book_status_follows_checkouts(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool);
+        let repo = CheckoutRepositoryImpl::new(db.clone());
+        let books = BookRepositoryImpl::new(db);
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+        let book_ids = FIXTURE_BOOK_IDS
+            .iter()
+            .map(|id| BookId::from_str(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let now = Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
+        let status = |book_id| {
+            let books = &books;
+            async move { anyhow::Ok(books.find_by_id(book_id).await?.unwrap().status) }
+        };
+
+        // 貸出・返却に合わせて状態が変わる
+        repo.create_checkout(CreateCheckout::new(book_ids[0], user_id, now), None)
+            .await?;
+        assert_eq!(status(book_ids[0]).await?, BookStatus::OnLoan);
+        let checkout = repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+        repo.update_returned(
+            UpdateReturned::new(checkout.id, book_ids[0], user_id, now),
+            None,
+        )
+        .await?;
+        assert_eq!(status(book_ids[0]).await?, BookStatus::Available);
+
+        // 紛失を届け出ると貸出が終了し、紛失状態になる
+        repo.create_checkout(CreateCheckout::new(book_ids[1], user_id, now), None)
+            .await?;
+        let checkout = repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+        let res = repo
+            .declare_lost(
+                DeclareLost::new(checkout.id, book_ids[1], UserId::new(), now),
+                None,
+            )
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+        repo.declare_lost(
+            DeclareLost::new(checkout.id, book_ids[1], user_id, now),
+            None,
+        )
+        .await?;
+        assert_eq!(status(book_ids[1]).await?, BookStatus::Lost);
+        assert!(repo.find_unreturned_by_user_id(user_id).await?.is_empty());
+        let history = repo.find_history_by_book_id(book_ids[1]).await?;
+        assert_eq!(history[0].returned_at, Some(now));
+
+        // 貸出可能でない蔵書は借りられない
+        let res = repo
+            .create_checkout(CreateCheckout::new(book_ids[1], user_id, now), None)
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+        books
+            .update_status(
+                UpdateBookStatus {
+                    book_id: book_ids[2],
+                    status: BookStatus::Damaged,
+                    note: None,
+                    requested_user: user_id,
+                },
+                None,
+            )
+            .await?;
+        let res = repo
+            .create_checkout(CreateCheckout::new(book_ids[2], user_id, now), None)
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        let transitions: Vec<_> = books
+            .find_status_history(book_ids[1])
+            .await?
+            .into_iter()
+            .map(|h| h.status)
+            .collect();
+        assert_eq!(transitions, vec![BookStatus::OnLoan, BookStatus::Lost]);
+
+        Ok(())
+    }
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_batch_checkouts_and_returns(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool.clone());
+        let repo = CheckoutRepositoryImpl::new(db.clone());
+        let policies = LoanPolicyRepositoryImpl::new(db);
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+        let now = Utc::now();
+        let book_ids = FIXTURE_BOOK_IDS
+            .iter()
+            .map(|id| BookId::from_str(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let audit = |action| {
+            Some(CreateAuditLog::new(
+                Some(user_id),
+                action,
+                AuditTargetType::Checkout,
+                None,
+            ))
+        };
+
+        // 一括で処理される蔵書も、同じトランザクション内での貸出冊数として数えられる
+        policies
+            .update_user_policy(UpdateUserLoanPolicy {
+                user_id,
+                user_override: LoanPolicyOverride {
+                    max_loans: Some(2),
+                    ..Default::default()
+                },
+            })
+            .await?;
+
+        // すべて成功しない場合は何も貸し出されない
+        let results = repo
+            .create_checkouts(
+                CreateCheckouts::new(book_ids.clone(), user_id, now, BatchMode::AllOrNothing),
+                audit(AuditAction::Checkout),
+            )
+            .await?;
+        assert!(matches!(
+            results[0].outcome,
+            BatchItemOutcome::RolledBack(_)
+        ));
+        assert!(matches!(
+            results[1].outcome,
+            BatchItemOutcome::RolledBack(_)
+        ));
+        assert!(matches!(
+            results[2].outcome,
+            BatchItemOutcome::Failed(AppError::UnprocessableEntity(_))
+        ));
+        assert!(repo.find_unreturned_by_user_id(user_id).await?.is_empty());
+
+        // 失敗した蔵書以外は貸し出される
+        let missing = BookId::new();
+        let results = repo
+            .create_checkouts(
+                CreateCheckouts::new(
+                    vec![book_ids[0], missing, book_ids[1]],
+                    user_id,
+                    now,
+                    BatchMode::BestEffort,
+                ),
+                audit(AuditAction::Checkout),
+            )
+            .await?;
+        assert_eq!(
+            results.iter().map(|r| r.book_id).collect::<Vec<_>>(),
+            vec![book_ids[0], missing, book_ids[1]]
+        );
+        assert!(results[0].is_succeeded() && results[2].is_succeeded());
+        assert!(matches!(
+            results[1].outcome,
+            BatchItemOutcome::Failed(AppError::NotFoundError(_))
+        ));
+        let checkouts = repo.find_unreturned_by_user_id(user_id).await?;
+        assert_eq!(checkouts.len(), 2);
+        assert!(matches!(
+            results[0].outcome,
+            BatchItemOutcome::Succeeded(id) if checkouts.iter().any(|c| c.id == id)
+        ));
+
+        // 借りていない蔵書を含む場合は、すべての返却が取り消される
+        let results = repo
+            .return_books(
+                ReturnBooks::new(
+                    vec![book_ids[0], book_ids[2]],
+                    user_id,
+                    now,
+                    BatchMode::AllOrNothing,
+                ),
+                audit(AuditAction::Return),
+            )
+            .await?;
+        assert!(!results[0].is_succeeded());
+        assert!(matches!(
+            results[1].outcome,
+            BatchItemOutcome::Failed(AppError::UnprocessableEntity(_))
+        ));
+        assert_eq!(repo.find_unreturned_by_user_id(user_id).await?.len(), 2);
+
+        let results = repo
+            .return_books(
+                ReturnBooks::new(
+                    vec![book_ids[0], book_ids[1]],
+                    user_id,
+                    now,
+                    BatchMode::AllOrNothing,
+                ),
+                audit(AuditAction::Return),
+            )
+            .await?;
+        assert!(results.iter().all(BatchItemResult::is_succeeded));
+        assert!(repo.find_unreturned_by_user_id(user_id).await?.is_empty());
+
+        // 反映された操作についてのみ、反映された貸出を含むドメインイベントが記録される
+        let events = sqlx::query!(
+            r#"
+                SELECT event_type, jsonb_array_length(payload->'data'->'checkouts') AS "count!"
+                FROM outbox
+                ORDER BY occurred_at
+            "#
+        )
+        .fetch_all(&pool)
+        .await?;
+        let events: Vec<_> = events
+            .into_iter()
+            .map(|e| (e.event_type, e.count))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                ("checkout.created".to_string(), 2),
+                ("checkout.returned".to_string(), 2)
+            ]
+        );
+
+        // 監査ログも、反映された貸出についてのみ貸出ごとに記録される
+        let logs = sqlx::query!(
+            r#"
+                SELECT action, target_id AS "target_id!", after->>'bookId' AS "book_id!"
+                FROM audit_logs
+            "#
+        )
+        .fetch_all(&pool)
+        .await?;
+        let mut logs: Vec<_> = logs
+            .into_iter()
+            .map(|l| (l.action, l.target_id, l.book_id))
+            .collect();
+        logs.sort();
+        let mut expected = [AuditAction::Checkout, AuditAction::Return]
+            .into_iter()
+            .flat_map(|action| {
+                checkouts.iter().map(move |c| {
+                    (
+                        action.as_ref().to_string(),
+                        c.id.to_string(),
+                        c.book.book_id.to_string(),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(logs, expected);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_checkouts_via_kiosk_are_recorded(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = CheckoutRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+        let book_id = BookId::from_str(FIXTURE_BOOK_IDS[0])?;
+        let now = Utc::now();
+        let kiosk_id = KioskId::new();
+        sqlx::query!(
+            "INSERT INTO kiosks(kiosk_id, name, key_prefix, key_hash) VALUES ($1, 'shelf', 'bmkiosk_x', 'x')",
+            kiosk_id as _
+        )
+        .execute(&pool)
+        .await?;
+
+        repo.create_checkouts(
+            CreateCheckouts {
+                checked_out_via: Some(kiosk_id),
+                ..CreateCheckouts::new(vec![book_id], user_id, now, BatchMode::AllOrNothing)
+            },
+            None,
+        )
+        .await?;
+        let checkouts = repo.find_unreturned_by_user_id(user_id).await?;
+        assert_eq!(checkouts[0].checked_out_via, Some(kiosk_id));
+        assert_eq!(checkouts[0].returned_via, None);
+
+        // 利用者自身で返却した場合は、返却したキオスクは記録されない
+        repo.return_books(
+            ReturnBooks::new(vec![book_id], user_id, now, BatchMode::AllOrNothing),
+            None,
+        )
+        .await?;
+        repo.create_checkout(CreateCheckout::new(book_id, user_id, now), None)
+            .await?;
+        repo.return_books(
+            ReturnBooks {
+                returned_via: Some(kiosk_id),
+                ..ReturnBooks::new(vec![book_id], user_id, now, BatchMode::AllOrNothing)
+            },
+            None,
+        )
+        .await?;
+
+        let history = repo.find_history_by_book_id(book_id).await?;
+        let vias: Vec<_> = history
+            .iter()
+            .map(|c| (c.checked_out_via, c.returned_via))
+            .collect();
+        assert_eq!(vias.len(), 2);
+        assert!(vias.contains(&(Some(kiosk_id), None)));
+        assert!(vias.contains(&(None, Some(kiosk_id))));
+
+        let history = repo
+            .find_history_by_user_id(
+                user_id,
+                CheckoutHistoryOptions {
+                    limit: 10,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        assert_eq!(
+            history
+                .items
+                .iter()
+                .filter(|c| c.checked_out_via == Some(kiosk_id))
+                .count(),
+            1
+        );
+
+        Ok(())
+    }
 }
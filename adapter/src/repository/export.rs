@@ -0,0 +1,192 @@
+use derive_new::new;
+use kernel::{
+    model::{
+        book::Book,
+        checkout::Checkout,
+        id::{BookId, CheckoutId, KioskId, UserId},
+    },
+    repository::export::{ExportRepository, RecordStream},
+};
+use shared::error::{AppError, AppResult};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
+
+use crate::database::{
+    model::{book::BookWithCheckoutRow, checkout::CheckoutHistoryRow},
+    ConnectionPool,
+};
+
+/// 取得したレコードを、受け取り側へ渡すまでに溜めておく件数
+const STREAM_BUFFER: usize = 64;
+
+#[derive(new)]
+pub struct ExportRepositoryImpl {
+    db: ConnectionPool,
+}
+
+/// クエリの結果を、バックグラウンドのタスクから1件ずつ受け渡す
+///
+/// 受け取り側が読み進めるまで次の行の取得を待つため、結果全体をメモリに載せることはない。
+/// 受け取り側が途中で破棄された場合や、取得に失敗した場合は、クエリを打ち切る
+async fn forward<R, T>(
+    rows: impl Stream<Item = Result<R, sqlx::Error>>,
+    convert: impl Fn(R) -> AppResult<T>,
+    sender: mpsc::Sender<AppResult<T>>,
+) {
+    tokio::pin!(rows);
+    while let Some(row) = rows.next().await {
+        let item = row
+            .map_err(AppError::DatabaseOperationError)
+            .and_then(&convert);
+        let failed = item.is_err();
+        if sender.send(item).await.is_err() || failed {
+            break;
+        }
+    }
+}
+
+impl ExportRepository for ExportRepositoryImpl {
+    fn stream_books(&self) -> RecordStream<Book> {
+        let db = self.db.clone();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        tokio::spawn(async move {
+            let rows = sqlx::query_as!(
+                BookWithCheckoutRow,
+                r#"
+                    SELECT
+                        b.book_id AS "book_id: BookId",
+                        b.title,
+                        b.author,
+                        b.isbn,
+                        b.description,
+                        o.user_id AS "owned_by: UserId",
+                        o.name AS owner_name,
+                        b.status,
+                        c.checkout_id AS "checkout_id?: CheckoutId",
+                        c.user_id AS "checked_out_by?: UserId",
+                        u.name AS "checked_out_by_name?",
+                        c.checked_out_at AS "checked_out_at?",
+                        c.due_at AS "due_at?"
+                    FROM books AS b
+                    INNER JOIN users AS o ON o.user_id = b.user_id
+                    LEFT JOIN checkouts AS c ON c.book_id = b.book_id
+                    LEFT JOIN users AS u ON u.user_id = c.user_id
+                    ORDER BY b.created_at, b.book_id
+                "#
+            )
+            .fetch(db.inner_ref());
+            forward(rows, Book::try_from, sender).await;
+        });
+        Box::pin(ReceiverStream::new(receiver))
+    }
+
+    fn stream_checkouts(&self) -> RecordStream<Checkout> {
+        let db = self.db.clone();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        tokio::spawn(async move {
+            let rows = sqlx::query_as!(
+                CheckoutHistoryRow,
+                r#"
+                    SELECT
+                        h.checkout_id AS "checkout_id!: CheckoutId",
+                        h.book_id AS "book_id!: BookId",
+                        h.user_id AS "user_id!: UserId",
+                        h.checked_out_at AS "checked_out_at!",
+                        h.returned_at,
+                        h.due_at AS "due_at!",
+                        h.renewal_count AS "renewal_count!",
+                        h.checked_out_via AS "checked_out_via?: KioskId",
+                        h.returned_via AS "returned_via?: KioskId",
+                        b.title,
+                        b.author,
+                        b.isbn
+                    FROM (
+                        SELECT checkout_id, book_id, user_id, checked_out_at, NULL::TIMESTAMPTZ AS returned_at, due_at, renewal_count, checked_out_via, NULL::UUID AS returned_via
+                        FROM checkouts
+                        UNION ALL
+                        SELECT checkout_id, book_id, user_id, checked_out_at, returned_at, due_at, renewal_count, checked_out_via, returned_via
+                        FROM returned_checkouts
+                    ) AS h
+                    INNER JOIN books AS b USING(book_id)
+                    ORDER BY h.checked_out_at, h.checkout_id
+                "#
+            )
+            .fetch(db.inner_ref());
+            forward(rows, |row| Ok(Checkout::from(row)), sender).await;
+        });
+        Box::pin(ReceiverStream::new(receiver))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::repository::checkout::CheckoutRepositoryImpl;
+    use chrono::{Duration, TimeZone, Utc};
+    use kernel::{
+        model::checkout::event::{CreateCheckout, UpdateReturned},
+        repository::checkout::CheckoutRepository,
+    };
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_stream_books_and_checkouts(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool);
+        let checkout_repo = CheckoutRepositoryImpl::new(db.clone());
+        let repo = ExportRepositoryImpl::new(db);
+        // fixtures/common.sql, fixtures/book.sqlに記載のユーザーID・蔵書ID
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let returned_book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let on_loan_book_id = BookId::from_str("f397b83a-dd2a-4a01-9e77-db1eea7de5b6")?;
+        let base = Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
+
+        checkout_repo
+            .create_checkout(CreateCheckout::new(returned_book_id, user_id, base), None)
+            .await?;
+        let checkout = checkout_repo.find_unreturned_by_user_id(user_id).await?[0].clone();
+        checkout_repo
+            .update_returned(
+                UpdateReturned::new(
+                    checkout.id,
+                    returned_book_id,
+                    user_id,
+                    base + Duration::days(3),
+                ),
+                None,
+            )
+            .await?;
+        checkout_repo
+            .create_checkout(
+                CreateCheckout::new(on_loan_book_id, user_id, base + Duration::days(10)),
+                None,
+            )
+            .await?;
+
+        // 貸出中の蔵書のみ、現在の貸出を含む
+        let books: Vec<Book> = repo.stream_books().collect::<AppResult<_>>().await?;
+        assert_eq!(books.len(), 3);
+        for book in &books {
+            assert_eq!(
+                book.checkout_info.is_some(),
+                book.id == on_loan_book_id,
+                "{:?}",
+                book.id
+            );
+        }
+        let on_loan = books.iter().find(|b| b.id == on_loan_book_id).unwrap();
+        assert_eq!(
+            on_loan.checkout_info.as_ref().unwrap().checked_out_by.id,
+            user_id
+        );
+
+        // 返却済みの貸出も含め、貸出日時の古い順に取得する
+        let checkouts: Vec<Checkout> = repo.stream_checkouts().collect::<AppResult<_>>().await?;
+        let book_ids: Vec<_> = checkouts.iter().map(|c| c.book.book_id).collect();
+        assert_eq!(book_ids, vec![returned_book_id, on_loan_book_id]);
+        assert!(checkouts[0].returned_at.is_some());
+        assert!(checkouts[1].returned_at.is_none());
+
+        Ok(())
+    }
+}
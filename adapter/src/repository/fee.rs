@@ -0,0 +1,428 @@
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    model::{
+        fee::{
+            event::{RecordFeePayment, UpdateFeeSchedule, WaiveFee},
+            FeeEntry, FeeEntryKind, FeeLedger, FeeSchedule,
+        },
+        id::{BookId, CheckoutId, UserId},
+    },
+    repository::fee::FeeRepository,
+};
+use shared::error::{AppError, AppResult};
+use sqlx::{PgConnection, PgExecutor};
+
+use crate::database::{
+    model::fee::{FeeEntryRow, FeeScheduleRow},
+    ConnectionPool,
+};
+
+/// 料金表を取得する。未設定の場合は料金を課さない既定値を返す
+/// 貸出・返却処理のトランザクション内からも呼び出せるよう、Executorを引数に取る
+pub(crate) async fn fetch_fee_schedule<'e>(
+    executor: impl PgExecutor<'e>,
+) -> AppResult<FeeSchedule> {
+    let row = sqlx::query_as!(
+        FeeScheduleRow,
+        r#"
+            SELECT
+                overdue_fee_per_day,
+                max_overdue_fee,
+                lost_book_fee,
+                checkout_block_threshold
+            FROM fee_schedules
+        "#
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(AppError::DatabaseOperationError)?;
+
+    Ok(row.map(FeeSchedule::from).unwrap_or_default())
+}
+
+/// ユーザーの未払いの残高を取得する
+pub(crate) async fn fetch_fee_balance<'e>(
+    executor: impl PgExecutor<'e>,
+    user_id: UserId,
+) -> AppResult<i64> {
+    sqlx::query_scalar!(
+        r#"
+            SELECT COALESCE(SUM(amount), 0)::BIGINT AS "balance!"
+            FROM fee_entries
+            WHERE user_id = $1
+        "#,
+        user_id as _
+    )
+    .fetch_one(executor)
+    .await
+    .map_err(AppError::DatabaseOperationError)
+}
+
+/// 延滞・紛失の料金を請求として台帳に記録する
+/// 金額が0の場合は何も記録しない
+pub(crate) async fn charge_fee<'e>(
+    executor: impl PgExecutor<'e>,
+    user_id: UserId,
+    kind: FeeEntryKind,
+    amount: i64,
+    checkout_id: CheckoutId,
+    book_id: BookId,
+) -> AppResult<()> {
+    if amount <= 0 {
+        return Ok(());
+    }
+    sqlx::query!(
+        r#"
+            INSERT INTO fee_entries(user_id, kind, amount, checkout_id, book_id)
+            VALUES($1, $2, $3, $4, $5)
+        "#,
+        user_id as _,
+        kind.as_ref(),
+        amount,
+        checkout_id as _,
+        book_id as _,
+    )
+    .execute(executor)
+    .await
+    .map_err(AppError::DatabaseOperationError)?;
+
+    Ok(())
+}
+
+#[derive(new)]
+pub struct FeeRepositoryImpl {
+    db: ConnectionPool,
+}
+
+impl FeeRepositoryImpl {
+    /// 支払い・免除を記録する
+    /// 残高を超える金額は記録できないため、残高の確認と記録を同じトランザクション内で行う
+    async fn try_record_credit(
+        tx: &mut PgConnection,
+        user_id: UserId,
+        kind: FeeEntryKind,
+        amount: i64,
+        note: Option<String>,
+        recorded_by: UserId,
+    ) -> AppResult<()> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM users WHERE user_id = $1) AS "exists!""#,
+            user_id as _
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        if !exists {
+            return Err(AppError::NotFoundError("Specified user not found".into()));
+        }
+
+        let balance = fetch_fee_balance(&mut *tx, user_id).await?;
+        if amount > balance {
+            return Err(AppError::UnprocessableEntity(format!(
+                "未払いの残高({}円)を超える金額は記録できません",
+                balance
+            )));
+        }
+
+        sqlx::query!(
+            r#"
+                INSERT INTO fee_entries(user_id, kind, amount, note, recorded_by)
+                VALUES($1, $2, $3, $4, $5)
+            "#,
+            user_id as _,
+            kind.as_ref(),
+            -amount,
+            note,
+            recorded_by as _,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FeeRepository for FeeRepositoryImpl {
+    async fn find_schedule(&self) -> AppResult<FeeSchedule> {
+        fetch_fee_schedule(self.db.inner_ref()).await
+    }
+
+    async fn update_schedule(&self, event: UpdateFeeSchedule) -> AppResult<()> {
+        let FeeSchedule {
+            overdue_fee_per_day,
+            max_overdue_fee,
+            lost_book_fee,
+            checkout_block_threshold,
+        } = event.schedule;
+        sqlx::query!(
+            r#"
+                INSERT INTO fee_schedules(
+                    overdue_fee_per_day,
+                    max_overdue_fee,
+                    lost_book_fee,
+                    checkout_block_threshold
+                )
+                VALUES($1, $2, $3, $4)
+                ON CONFLICT (fee_schedule_id) DO UPDATE SET
+                    overdue_fee_per_day = EXCLUDED.overdue_fee_per_day,
+                    max_overdue_fee = EXCLUDED.max_overdue_fee,
+                    lost_book_fee = EXCLUDED.lost_book_fee,
+                    checkout_block_threshold = EXCLUDED.checkout_block_threshold
+            "#,
+            overdue_fee_per_day,
+            max_overdue_fee,
+            lost_book_fee,
+            checkout_block_threshold,
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        Ok(())
+    }
+
+    async fn find_ledger_by_user_id(&self, user_id: UserId) -> AppResult<Option<FeeLedger>> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM users WHERE user_id = $1) AS "exists!""#,
+            user_id as _
+        )
+        .fetch_one(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        if !exists {
+            return Ok(None);
+        }
+
+        let entries = sqlx::query_as!(
+            FeeEntryRow,
+            r#"
+                SELECT
+                    fee_entry_id,
+                    user_id,
+                    kind,
+                    amount,
+                    checkout_id AS "checkout_id: CheckoutId",
+                    book_id AS "book_id: BookId",
+                    note,
+                    recorded_by AS "recorded_by: UserId",
+                    created_at
+                FROM fee_entries
+                WHERE user_id = $1
+                ORDER BY created_at DESC, fee_entry_id
+            "#,
+            user_id as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .into_iter()
+        .map(FeeEntry::try_from)
+        .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(Some(FeeLedger {
+            user_id,
+            balance: entries.iter().map(|e| e.amount).sum(),
+            entries,
+        }))
+    }
+
+    async fn record_payment(&self, event: RecordFeePayment) -> AppResult<()> {
+        self.db
+            .serializable(move |tx| {
+                let event = event.clone();
+                Box::pin(Self::try_record_credit(
+                    tx,
+                    event.user_id,
+                    FeeEntryKind::Payment,
+                    event.amount,
+                    event.note,
+                    event.recorded_by,
+                ))
+            })
+            .await
+    }
+
+    async fn waive(&self, event: WaiveFee) -> AppResult<()> {
+        self.db
+            .serializable(move |tx| {
+                let event = event.clone();
+                Box::pin(Self::try_record_credit(
+                    tx,
+                    event.user_id,
+                    FeeEntryKind::Waiver,
+                    event.amount,
+                    event.note,
+                    event.recorded_by,
+                ))
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::{Duration, TimeZone, Utc};
+    use kernel::{
+        model::{
+            book::{event::UpdateBookStatus, status::BookStatus},
+            checkout::event::{CreateCheckout, DeclareLost, UpdateReturned},
+        },
+        repository::{book::BookRepository, checkout::CheckoutRepository},
+    };
+
+    use super::*;
+    use crate::repository::{book::BookRepositoryImpl, checkout::CheckoutRepositoryImpl};
+
+    // fixtures/common.sql、fixtures/book.sqlに記載のID
+    const FIXTURE_USER_ID: &str = "5b4c96ac-316a-4bee-8e69-cac5eb84ff4c";
+    const FIXTURE_BOOK_IDS: [&str; 2] = [
+        "9890736e-a4e4-461a-a77d-eac3517ef11b",
+        "f397b83a-dd2a-4a01-9e77-db1eea7de5b6",
+    ];
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_fee_ledger(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool);
+        let repo = FeeRepositoryImpl::new(db.clone());
+        let checkouts = CheckoutRepositoryImpl::new(db);
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+        let book_ids = FIXTURE_BOOK_IDS
+            .iter()
+            .map(|id| BookId::from_str(id))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        // 料金表が未設定の場合は料金を課さない
+        assert_eq!(repo.find_schedule().await?, FeeSchedule::default());
+
+        let schedule = FeeSchedule {
+            overdue_fee_per_day: 10,
+            max_overdue_fee: Some(200),
+            lost_book_fee: 1500,
+            checkout_block_threshold: Some(100),
+        };
+        repo.update_schedule(UpdateFeeSchedule { schedule }).await?;
+        assert_eq!(repo.find_schedule().await?, schedule);
+
+        // 返却期限(既定の貸出期間は14日)を3日過ぎて返却すると延滞料が課される
+        let checked_out_at = Utc.with_ymd_and_hms(2026, 4, 1, 0, 0, 0).unwrap();
+        checkouts
+            .create_checkout(
+                CreateCheckout::new(book_ids[0], user_id, checked_out_at),
+                None,
+            )
+            .await?;
+        let checkout = checkouts.find_unreturned_by_user_id(user_id).await?;
+        checkouts
+            .update_returned(
+                UpdateReturned::new(
+                    checkout[0].id,
+                    book_ids[0],
+                    user_id,
+                    checked_out_at + Duration::days(17),
+                ),
+                None,
+            )
+            .await?;
+
+        let ledger = repo.find_ledger_by_user_id(user_id).await?.unwrap();
+        assert_eq!(ledger.balance, 30);
+        assert_eq!(ledger.entries.len(), 1);
+        assert_eq!(ledger.entries[0].kind, FeeEntryKind::Overdue);
+        assert_eq!(ledger.entries[0].checkout_id, Some(checkout[0].id));
+
+        // 紛失を届け出ると紛失の料金が課される
+        checkouts
+            .create_checkout(
+                CreateCheckout::new(book_ids[1], user_id, checked_out_at),
+                None,
+            )
+            .await?;
+        let checkout = checkouts.find_unreturned_by_user_id(user_id).await?;
+        checkouts
+            .declare_lost(
+                DeclareLost::new(
+                    checkout[0].id,
+                    book_ids[1],
+                    user_id,
+                    checked_out_at + Duration::days(1),
+                ),
+                None,
+            )
+            .await?;
+        let ledger = repo.find_ledger_by_user_id(user_id).await?.unwrap();
+        assert_eq!(ledger.balance, 1530);
+
+        // 残高が基準を超えている間は借りられない
+        BookRepositoryImpl::new(repo.db.clone())
+            .update_status(
+                UpdateBookStatus {
+                    book_id: book_ids[1],
+                    status: BookStatus::Available,
+                    note: Some("見つかった".into()),
+                    requested_user: user_id,
+                },
+                None,
+            )
+            .await?;
+        let res = checkouts
+            .create_checkout(CreateCheckout::new(book_ids[1], user_id, Utc::now()), None)
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 残高を超える支払いは記録できない
+        let res = repo
+            .record_payment(RecordFeePayment {
+                user_id,
+                amount: 2000,
+                note: None,
+                recorded_by: user_id,
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 支払いと免除により残高が減り、再び借りられるようになる
+        repo.record_payment(RecordFeePayment {
+            user_id,
+            amount: 1000,
+            note: Some("窓口で支払い".into()),
+            recorded_by: user_id,
+        })
+        .await?;
+        repo.waive(WaiveFee {
+            user_id,
+            amount: 430,
+            note: None,
+            recorded_by: user_id,
+        })
+        .await?;
+        let ledger = repo.find_ledger_by_user_id(user_id).await?.unwrap();
+        assert_eq!(ledger.balance, 100);
+        assert_eq!(ledger.entries.len(), 4);
+        assert!(ledger
+            .entries
+            .iter()
+            .any(|e| e.kind == FeeEntryKind::Waiver && e.amount == -430));
+        checkouts
+            .create_checkout(CreateCheckout::new(book_ids[1], user_id, Utc::now()), None)
+            .await?;
+
+        // 存在しないユーザー
+        assert!(repo.find_ledger_by_user_id(UserId::new()).await?.is_none());
+        let res = repo
+            .waive(WaiveFee {
+                user_id: UserId::new(),
+                amount: 1,
+                note: None,
+                recorded_by: user_id,
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::NotFoundError(_))));
+
+        Ok(())
+    }
+}
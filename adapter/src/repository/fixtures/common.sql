@@ -1,5 +1,6 @@
 INSERT INTO roles(name)
-VALUES ('Admin'), ('User');
+VALUES ('Admin'), ('User')
+ON CONFLICT (name) DO NOTHING;
 
 INSERT INTO users(user_id, name, email, password_hash, role_id)
 SELECT
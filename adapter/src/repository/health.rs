@@ -1,7 +1,8 @@
-use crate::database::ConnectionPool;
+use crate::database::{migration, ConnectionPool};
 use async_trait::async_trait;
 use derive_new::new;
-use kernel::repository::health::HealthCheckRepository;
+use kernel::{model::migration::PendingMigration, repository::health::HealthCheckRepository};
+use shared::error::AppResult;
 
 #[derive(new)]
 pub struct HealthCheckRepositoryImpl {
@@ -16,4 +17,8 @@ impl HealthCheckRepository for HealthCheckRepositoryImpl {
             .await
             .is_ok()
     }
+
+    async fn pending_migrations(&self) -> AppResult<Vec<PendingMigration>> {
+        migration::pending_migrations(&self.db).await
+    }
 }
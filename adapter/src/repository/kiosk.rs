@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use derive_new::new;
+use kernel::{
+    model::{
+        id::{KioskId, UserId},
+        kiosk::{
+            event::{CreateKiosk, RevokeKiosk},
+            IssuedKiosk, Kiosk, KioskSecret,
+        },
+    },
+    repository::kiosk::KioskRepository,
+};
+use shared::{
+    config::KioskConfig,
+    error::{AppError, AppResult},
+};
+
+use crate::{
+    database::{
+        model::{
+            api_key::hash_api_key,
+            kiosk::{KioskRateLimitKey, KioskRow},
+        },
+        ConnectionPool,
+    },
+    redis::RedisClient,
+};
+
+#[derive(new)]
+pub struct KioskRepositoryImpl {
+    db: ConnectionPool,
+    kv: Arc<RedisClient>,
+    config: KioskConfig,
+}
+
+#[async_trait]
+impl KioskRepository for KioskRepositoryImpl {
+    async fn create(&self, event: CreateKiosk) -> AppResult<IssuedKiosk> {
+        let CreateKiosk {
+            name,
+            created_by,
+            secret,
+        } = event;
+        let kiosk_id = KioskId::new();
+
+        let row = sqlx::query_as!(
+            KioskRow,
+            r#"
+                INSERT INTO kiosks(kiosk_id, name, key_prefix, key_hash, created_by)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING
+                    kiosk_id,
+                    name,
+                    key_prefix,
+                    created_by AS "created_by: UserId",
+                    created_at,
+                    last_used_at,
+                    revoked_at
+            "#,
+            kiosk_id as _,
+            name,
+            secret.prefix(),
+            hash_api_key(&secret.0),
+            created_by as _
+        )
+        .fetch_one(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        Ok(IssuedKiosk {
+            kiosk: row.into(),
+            secret,
+        })
+    }
+
+    async fn find_all(&self) -> AppResult<Vec<Kiosk>> {
+        let rows = sqlx::query_as!(
+            KioskRow,
+            r#"
+                SELECT
+                    kiosk_id,
+                    name,
+                    key_prefix,
+                    created_by AS "created_by: UserId",
+                    created_at,
+                    last_used_at,
+                    revoked_at
+                FROM kiosks
+                ORDER BY created_at DESC
+            "#
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        Ok(rows.into_iter().map(Kiosk::from).collect())
+    }
+
+    async fn revoke(&self, event: RevokeKiosk) -> AppResult<()> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE kiosks
+                SET revoked_at = CURRENT_TIMESTAMP(3)
+                WHERE kiosk_id = $1 AND revoked_at IS NULL
+            "#,
+            event.kiosk_id as _
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError("Specified kiosk not found".into()));
+        }
+
+        Ok(())
+    }
+
+    async fn verify(&self, secret: &KioskSecret) -> AppResult<Option<Kiosk>> {
+        // 照合と同時に最終利用日時を更新する
+        let row = sqlx::query_as!(
+            KioskRow,
+            r#"
+                UPDATE kiosks
+                SET last_used_at = CURRENT_TIMESTAMP(3)
+                WHERE key_hash = $1 AND revoked_at IS NULL
+                RETURNING
+                    kiosk_id,
+                    name,
+                    key_prefix,
+                    created_by AS "created_by: UserId",
+                    created_at,
+                    last_used_at,
+                    revoked_at
+            "#,
+            hash_api_key(&secret.0)
+        )
+        .fetch_optional(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        Ok(row.map(Kiosk::from))
+    }
+
+    async fn consume_rate_limit(&self, kiosk_id: KioskId) -> AppResult<()> {
+        // 固定の時間枠ごとにリクエスト数を数える
+        // 複数のAPIサーバーで同じ上限を共有するため、Redisで数える
+        let KioskConfig {
+            rate_limit,
+            rate_limit_window,
+        } = self.config;
+        let key = KioskRateLimitKey::new(kiosk_id, Utc::now(), rate_limit_window);
+        let count = self.kv.incr_ex(&key, rate_limit_window).await?;
+        if count > rate_limit {
+            return Err(AppError::TooManyRequestsError);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use shared::config::RedisConfig;
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_create_verify_revoke_kiosk(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        // Redisはリクエスト数の制限にのみ用いるため、接続できなくてもよい
+        let kv = Arc::new(RedisClient::new(&RedisConfig {
+            host: "localhost".into(),
+            port: 6379,
+        })?);
+        let repo = KioskRepositoryImpl::new(ConnectionPool::new(pool), kv, KioskConfig::default());
+        // fixtures/common.sqlに記載のユーザーID
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        let issued = repo
+            .create(CreateKiosk::new("2F shelf".into(), admin_id))
+            .await?;
+        assert_eq!(issued.kiosk.name, "2F shelf");
+        assert_eq!(issued.kiosk.created_by, Some(admin_id));
+        assert!(issued.secret.0.starts_with(&issued.kiosk.key_prefix));
+
+        // 照合すると最終利用日時が記録される
+        let verified = repo
+            .verify(&issued.secret)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("kiosk not verified"))?;
+        assert_eq!(verified.id, issued.kiosk.id);
+        assert!(verified.last_used_at.is_some());
+
+        // 誤ったキーは照合できない
+        let wrong = KioskSecret(format!("{}x", issued.secret.0));
+        assert!(repo.verify(&wrong).await?.is_none());
+
+        repo.revoke(RevokeKiosk {
+            kiosk_id: issued.kiosk.id,
+        })
+        .await?;
+        assert!(repo.verify(&issued.secret).await?.is_none());
+
+        // 失効済みのキオスクは再度失効できない
+        let res = repo
+            .revoke(RevokeKiosk {
+                kiosk_id: issued.kiosk.id,
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::NotFoundError(_))));
+
+        let kiosks = repo.find_all().await?;
+        assert_eq!(kiosks.len(), 1);
+        assert!(kiosks[0].revoked_at.is_some());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    model::{
+        id::UserId,
+        loan_policy::{
+            event::{DeleteUserLoanPolicy, UpdateRoleLoanPolicy, UpdateUserLoanPolicy},
+            RoleLoanPolicy, UserLoanPolicy,
+        },
+    },
+    repository::loan_policy::LoanPolicyRepository,
+};
+use shared::error::{AppError, AppResult};
+use sqlx::PgExecutor;
+
+use crate::database::{
+    model::loan_policy::{RoleLoanPolicyRow, UserLoanPolicyRow},
+    ConnectionPool,
+};
+
+/// ユーザーに適用される貸出ポリシーを取得する
+/// 貸出処理のトランザクション内からも呼び出せるよう、Executorを引数に取る
+pub(crate) async fn fetch_user_loan_policy<'e>(
+    executor: impl PgExecutor<'e>,
+    user_id: UserId,
+) -> AppResult<Option<UserLoanPolicy>> {
+    let row = sqlx::query_as!(
+        UserLoanPolicyRow,
+        r#"
+            SELECT
+                u.user_id,
+                lp.max_loans AS "role_max_loans?",
+                lp.loan_period_days AS "role_loan_period_days?",
+                lp.max_renewals AS "role_max_renewals?",
+                ulp.user_id IS NOT NULL AS "has_override!",
+                ulp.max_loans AS "max_loans?",
+                ulp.loan_period_days AS "loan_period_days?",
+                ulp.max_renewals AS "max_renewals?"
+            FROM users AS u
+            LEFT OUTER JOIN loan_policies AS lp USING(role_id)
+            LEFT OUTER JOIN user_loan_policies AS ulp USING(user_id)
+            WHERE u.user_id = $1
+        "#,
+        user_id as _
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(AppError::DatabaseOperationError)?;
+
+    Ok(row.map(UserLoanPolicy::from))
+}
+
+#[derive(new)]
+pub struct LoanPolicyRepositoryImpl {
+    db: ConnectionPool,
+}
+
+#[async_trait]
+impl LoanPolicyRepository for LoanPolicyRepositoryImpl {
+    async fn find_all_role_policies(&self) -> AppResult<Vec<RoleLoanPolicy>> {
+        let rows = sqlx::query_as!(
+            RoleLoanPolicyRow,
+            r#"
+                SELECT
+                    r.name AS role_name,
+                    lp.max_loans AS "max_loans?",
+                    lp.loan_period_days AS "loan_period_days?",
+                    lp.max_renewals AS "max_renewals?"
+                FROM roles AS r
+                LEFT OUTER JOIN loan_policies AS lp USING(role_id)
+                ORDER BY r.name
+            "#
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        rows.into_iter().map(RoleLoanPolicy::try_from).collect()
+    }
+
+    async fn update_role_policy(&self, event: UpdateRoleLoanPolicy) -> AppResult<()> {
+        let res = sqlx::query!(
+            r#"
+                INSERT INTO loan_policies(role_id, max_loans, loan_period_days, max_renewals)
+                SELECT role_id, $2, $3, $4 FROM roles WHERE name = $1
+                ON CONFLICT (role_id) DO UPDATE SET
+                    max_loans = EXCLUDED.max_loans,
+                    loan_period_days = EXCLUDED.loan_period_days,
+                    max_renewals = EXCLUDED.max_renewals
+            "#,
+            event.role.as_ref(),
+            event.policy.max_loans,
+            event.policy.loan_period_days,
+            event.policy.max_renewals,
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError("Specified role not found".into()));
+        }
+
+        Ok(())
+    }
+
+    async fn find_by_user_id(&self, user_id: UserId) -> AppResult<Option<UserLoanPolicy>> {
+        fetch_user_loan_policy(self.db.inner_ref(), user_id).await
+    }
+
+    async fn update_user_policy(&self, event: UpdateUserLoanPolicy) -> AppResult<()> {
+        let UpdateUserLoanPolicy {
+            user_id,
+            user_override,
+        } = event;
+        let res = sqlx::query!(
+            r#"
+                INSERT INTO user_loan_policies(user_id, max_loans, loan_period_days, max_renewals)
+                SELECT user_id, $2, $3, $4 FROM users WHERE user_id = $1
+                ON CONFLICT (user_id) DO UPDATE SET
+                    max_loans = EXCLUDED.max_loans,
+                    loan_period_days = EXCLUDED.loan_period_days,
+                    max_renewals = EXCLUDED.max_renewals
+            "#,
+            user_id as _,
+            user_override.max_loans,
+            user_override.loan_period_days,
+            user_override.max_renewals,
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError("Specified user not found".into()));
+        }
+
+        Ok(())
+    }
+
+    async fn delete_user_policy(&self, event: DeleteUserLoanPolicy) -> AppResult<()> {
+        let res = sqlx::query!(
+            "DELETE FROM user_loan_policies WHERE user_id = $1",
+            event.user_id as _
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError(
+                "Specified user has no loan policy override".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use kernel::model::{
+        loan_policy::{LoanPolicy, LoanPolicyOverride},
+        role::Role,
+    };
+
+    use super::*;
+
+    // fixtures/common.sqlに記載のID
+    const FIXTURE_USER_ID: &str = "5b4c96ac-316a-4bee-8e69-cac5eb84ff4c";
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_loan_policies(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = LoanPolicyRepositoryImpl::new(ConnectionPool::new(pool));
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+
+        // 何も設定されていない場合は既定値
+        let roles = repo.find_all_role_policies().await?;
+        assert_eq!(roles.len(), 2);
+        assert!(roles.iter().all(|r| r.policy == LoanPolicy::default()));
+        let policy = repo.find_by_user_id(user_id).await?.unwrap();
+        assert_eq!(policy.user_override, None);
+        assert_eq!(policy.effective(), LoanPolicy::default());
+
+        // ロールのポリシーを変更すると、そのロールのユーザーに反映される
+        let admin_policy = LoanPolicy {
+            max_loans: 20,
+            loan_period_days: 30,
+            max_renewals: 5,
+        };
+        repo.update_role_policy(UpdateRoleLoanPolicy {
+            role: Role::Admin,
+            policy: admin_policy,
+        })
+        .await?;
+        let policy = repo.find_by_user_id(user_id).await?.unwrap();
+        assert_eq!(policy.effective(), admin_policy);
+
+        // ユーザーごとの上書きは指定した項目のみに適用される
+        let user_override = LoanPolicyOverride {
+            max_loans: Some(1),
+            ..Default::default()
+        };
+        repo.update_user_policy(UpdateUserLoanPolicy {
+            user_id,
+            user_override,
+        })
+        .await?;
+        let policy = repo.find_by_user_id(user_id).await?.unwrap();
+        assert_eq!(policy.user_override, Some(user_override));
+        assert_eq!(
+            policy.effective(),
+            LoanPolicy {
+                max_loans: 1,
+                ..admin_policy
+            }
+        );
+
+        // 上書きを削除するとロールのポリシーに戻る
+        repo.delete_user_policy(DeleteUserLoanPolicy { user_id })
+            .await?;
+        let policy = repo.find_by_user_id(user_id).await?.unwrap();
+        assert_eq!(policy.effective(), admin_policy);
+        assert!(matches!(
+            repo.delete_user_policy(DeleteUserLoanPolicy { user_id })
+                .await,
+            Err(AppError::NotFoundError(_))
+        ));
+
+        // 存在しないユーザー
+        assert!(repo.find_by_user_id(UserId::new()).await?.is_none());
+        assert!(matches!(
+            repo.update_user_policy(UpdateUserLoanPolicy {
+                user_id: UserId::new(),
+                user_override,
+            })
+            .await,
+            Err(AppError::NotFoundError(_))
+        ));
+
+        Ok(())
+    }
+}
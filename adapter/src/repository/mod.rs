@@ -1,5 +1,16 @@
+pub mod api_key;
+pub mod audit;
 pub mod auth;
+pub mod backup;
 pub mod book;
 pub mod checkout;
+pub mod export;
+pub mod fee;
 pub mod health;
+pub mod kiosk;
+pub mod loan_policy;
+pub mod oidc;
+pub mod outbox;
+pub mod reminder;
 pub mod user;
+pub mod webhook;
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    model::auth::{event::VerifyOidcCallback, OidcAuthorization, OidcIdentity},
+    repository::oidc::OidcRepository,
+};
+use shared::error::{AppError, AppResult};
+
+use crate::{
+    oidc::{
+        model::{OidcStateKey, PendingAuthorization},
+        OidcClient,
+    },
+    redis::RedisClient,
+};
+
+/// 認可リクエストを開始してから、コールバックを受けるまでの猶予(秒)
+const PENDING_AUTHORIZATION_TTL: u64 = 600;
+
+#[derive(new)]
+pub struct OidcRepositoryImpl {
+    /// OIDCが設定されていない場合は`None`
+    client: Option<OidcClient>,
+    kv: Arc<RedisClient>,
+}
+
+impl OidcRepositoryImpl {
+    fn client(&self) -> AppResult<&OidcClient> {
+        self.client
+            .as_ref()
+            .ok_or_else(|| AppError::NotFoundError("OIDC login is not configured".into()))
+    }
+}
+
+#[async_trait]
+impl OidcRepository for OidcRepositoryImpl {
+    async fn authorize(&self) -> AppResult<OidcAuthorization> {
+        let client = self.client()?;
+        let pending = PendingAuthorization::new();
+        let authorization_url = client.authorization_url(&pending).await?;
+        self.kv
+            .set_ex(&pending.key(), &pending, PENDING_AUTHORIZATION_TTL)
+            .await?;
+        Ok(OidcAuthorization {
+            authorization_url,
+            client_binding: pending.client_binding,
+        })
+    }
+
+    async fn verify_callback(&self, event: VerifyOidcCallback) -> AppResult<OidcIdentity> {
+        let client = self.client()?;
+        // stateは一度しか使えないよう、取り出すと同時に削除する
+        let pending = self
+            .kv
+            .take(&OidcStateKey::from(event.state.as_str()))
+            .await?
+            .ok_or(AppError::UnauthenticatedError)?;
+        // 他のクライアントが開始した認可リクエストの認可コードでは、ログインさせない
+        if !pending.is_bound_to(&event.client_binding) {
+            return Err(AppError::UnauthenticatedError);
+        }
+        client.exchange_code(&event.code, &pending).await
+    }
+}
@@ -0,0 +1,230 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use derive_new::new;
+use kernel::{
+    model::{
+        domain_event::{DomainEvent, PendingEvent},
+        id::DomainEventId,
+    },
+    repository::outbox::OutboxRepository,
+};
+use shared::error::{AppError, AppResult};
+use sqlx::PgConnection;
+
+use crate::database::{model::outbox::PendingEventRow, ConnectionPool};
+
+/// 取得したイベントを他のインスタンスが取得しないよう確保しておく時間
+/// 配信中にプロセスが停止した場合は、この時間が過ぎると再び配信される
+const CLAIM_LEASE: Duration = Duration::minutes(5);
+
+/// ドメインイベントをアウトボックスに記録する
+///
+/// 状態を変更するトランザクションの中で呼び出すことで、
+/// 変更がコミットされた場合にのみイベントが残るようにする
+pub(crate) async fn record_event(conn: &mut PgConnection, event: &DomainEvent) -> AppResult<()> {
+    let payload = serde_json::to_value(&event.kind)
+        .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+    sqlx::query!(
+        r#"
+            INSERT INTO outbox(event_id, event_type, payload, occurred_at)
+            VALUES ($1, $2, $3, $4)
+        "#,
+        event.id as _,
+        event.kind.name(),
+        payload,
+        event.occurred_at
+    )
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseOperationError)?;
+
+    Ok(())
+}
+
+#[derive(new)]
+pub struct OutboxRepositoryImpl {
+    db: ConnectionPool,
+}
+
+#[async_trait]
+impl OutboxRepository for OutboxRepositoryImpl {
+    async fn claim_pending(&self, now: DateTime<Utc>, limit: i64) -> AppResult<Vec<PendingEvent>> {
+        // 次の配信時刻を先に延ばすことで、配信中のイベントを他のインスタンスが取得しないようにする
+        // 他のトランザクションがロックしているイベントは待たずに飛ばす
+        let rows = sqlx::query_as!(
+            PendingEventRow,
+            r#"
+                WITH claimed AS (
+                    UPDATE outbox
+                    SET next_attempt_at = $2
+                    WHERE event_id IN (
+                        SELECT event_id
+                        FROM outbox
+                        WHERE processed_at IS NULL AND next_attempt_at <= $1
+                        ORDER BY occurred_at
+                        LIMIT $3
+                        FOR UPDATE SKIP LOCKED
+                    )
+                    RETURNING event_id, payload, occurred_at, attempts
+                )
+                SELECT
+                    c.event_id AS "event_id: DomainEventId",
+                    c.payload::TEXT AS "payload!",
+                    c.occurred_at,
+                    c.attempts,
+                    ARRAY(
+                        SELECT consumer
+                        FROM outbox_consumptions AS oc
+                        WHERE oc.event_id = c.event_id
+                    ) AS "consumed_by!"
+                FROM claimed AS c
+                ORDER BY c.occurred_at
+            "#,
+            now,
+            now + CLAIM_LEASE,
+            limit
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        rows.into_iter().map(PendingEvent::try_from).collect()
+    }
+
+    async fn mark_consumed(&self, event_id: DomainEventId, consumer: &str) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO outbox_consumptions(event_id, consumer)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+            "#,
+            event_id as _,
+            consumer
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        Ok(())
+    }
+
+    async fn complete(&self, event_id: DomainEventId, at: DateTime<Utc>) -> AppResult<()> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE outbox
+                SET processed_at = $2, attempts = attempts + 1, last_error = NULL
+                WHERE event_id = $1
+            "#,
+            event_id as _,
+            at
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError("Specified event not found".into()));
+        }
+
+        Ok(())
+    }
+
+    async fn record_failure(
+        &self,
+        event_id: DomainEventId,
+        error: &str,
+        retry_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE outbox
+                SET attempts = attempts + 1, last_error = $2, next_attempt_at = $3
+                WHERE event_id = $1
+            "#,
+            event_id as _,
+            error,
+            retry_at
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError("Specified event not found".into()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::SubsecRound;
+    use kernel::model::{
+        domain_event::DomainEventKind,
+        id::{BookId, UserId},
+    };
+
+    use super::*;
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_claim_consume_and_complete(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool);
+        let repo = OutboxRepositoryImpl::new(db.clone());
+        // fixtures/common.sqlに記載のユーザーID
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        // ロールバックしたトランザクションで記録したイベントは残らない
+        let mut tx = db.begin().await?;
+        record_event(
+            &mut tx,
+            &DomainEvent::new(DomainEventKind::BookDeleted {
+                book_id: BookId::new(),
+                deleted_by: admin_id,
+            }),
+        )
+        .await?;
+        tx.rollback().await?;
+
+        let event = DomainEvent::new(DomainEventKind::BookDeleted {
+            book_id: BookId::new(),
+            deleted_by: admin_id,
+        });
+        let mut tx = db.begin().await?;
+        record_event(&mut tx, &event).await?;
+        tx.commit().await?;
+
+        // 取得したイベントは、確保している間は再び取得されない
+        // 日時はミリ秒の精度で丸めて保存されるため、記録時刻より確実に後の時刻を用いる
+        let now = (Utc::now() + Duration::seconds(1)).trunc_subsecs(3);
+        let claimed = repo.claim_pending(now, 10).await?;
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(claimed[0].event.id, event.id);
+        assert_eq!(claimed[0].event.kind.name(), "book.deleted");
+        assert!(claimed[0].consumed_by.is_empty());
+        assert!(repo.claim_pending(now, 10).await?.is_empty());
+
+        // 失敗を記録すると、処理済みのコンシューマーとともに指定した時刻に再び取得される
+        repo.mark_consumed(event.id, "webhook").await?;
+        repo.mark_consumed(event.id, "webhook").await?;
+        let retry_at = now + Duration::minutes(1);
+        repo.record_failure(event.id, "notifier unavailable", retry_at)
+            .await?;
+        assert!(repo.claim_pending(now, 10).await?.is_empty());
+        let claimed = repo.claim_pending(retry_at, 10).await?;
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(claimed[0].attempts, 1);
+        assert_eq!(claimed[0].consumed_by, ["webhook"]);
+
+        // 処理済みのイベントは取得されない
+        repo.complete(event.id, retry_at).await?;
+        assert!(repo
+            .claim_pending(retry_at + CLAIM_LEASE, 10)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{model::reminder::CheckoutReminder, repository::reminder::ReminderRepository};
+use shared::error::{AppError, AppResult};
+
+use crate::database::ConnectionPool;
+
+#[derive(new)]
+pub struct ReminderRepositoryImpl {
+    db: ConnectionPool,
+}
+
+#[async_trait]
+impl ReminderRepository for ReminderRepositoryImpl {
+    async fn claim(&self, reminder: &CheckoutReminder) -> AppResult<bool> {
+        // 送信前に記録することで、複数のインスタンスでジョブを動かしても二重に送信しない
+        // 貸出が返却済み、または返却期限が変わっている場合は記録しない
+        let res = sqlx::query!(
+            r#"
+                INSERT INTO checkout_reminders(checkout_id, kind, due_at)
+                SELECT checkout_id, $2, due_at
+                FROM checkouts
+                WHERE checkout_id = $1 AND due_at = $3
+                ON CONFLICT DO NOTHING
+            "#,
+            reminder.checkout_id as _,
+            reminder.kind.as_ref(),
+            reminder.due_at
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    async fn release(&self, reminder: &CheckoutReminder) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+                DELETE FROM checkout_reminders
+                WHERE checkout_id = $1 AND kind = $2 AND due_at = $3
+            "#,
+            reminder.checkout_id as _,
+            reminder.kind.as_ref(),
+            reminder.due_at
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::{Duration, Utc};
+    use kernel::{
+        model::{
+            checkout::event::{CreateCheckout, RenewCheckout, UpdateReturned},
+            id::{BookId, UserId},
+            reminder::ReminderKind,
+        },
+        repository::checkout::CheckoutRepository,
+    };
+
+    use super::*;
+    use crate::repository::checkout::CheckoutRepositoryImpl;
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_claim_and_release(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool);
+        let checkouts = CheckoutRepositoryImpl::new(db.clone());
+        let repo = ReminderRepositoryImpl::new(db);
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let now = Utc::now();
+
+        checkouts
+            .create_checkout(CreateCheckout::new(book_id, user_id, now), None)
+            .await?;
+        let checkout = checkouts
+            .find_unreturned_due_before(now + Duration::days(365))
+            .await?
+            .remove(0);
+        let reminder = CheckoutReminder {
+            checkout_id: checkout.id,
+            kind: ReminderKind::DueSoon,
+            due_at: checkout.due_at,
+        };
+
+        // 同じリマインダーは一度しか記録できない
+        assert!(repo.claim(&reminder).await?);
+        assert!(!repo.claim(&reminder).await?);
+        // 種類が異なれば別のリマインダーとして記録する
+        assert!(
+            repo.claim(&CheckoutReminder {
+                kind: ReminderKind::Overdue,
+                ..reminder.clone()
+            })
+            .await?
+        );
+
+        // 取り消した記録は再び記録できる
+        repo.release(&reminder).await?;
+        assert!(repo.claim(&reminder).await?);
+
+        // 返却期限が変わった後は、古い期限のリマインダーは記録しない
+        checkouts
+            .renew(
+                RenewCheckout::new(checkout.id, book_id, user_id, now + Duration::days(1)),
+                None,
+            )
+            .await?;
+        repo.release(&reminder).await?;
+        assert!(!repo.claim(&reminder).await?);
+        let renewed = CheckoutReminder {
+            due_at: checkouts.find_unreturned_by_user_id(user_id).await?[0].due_at,
+            ..reminder
+        };
+        assert!(repo.claim(&renewed).await?);
+        repo.release(&renewed).await?;
+
+        // 返却済みの貸出のリマインダーは記録しない
+        checkouts
+            .update_returned(
+                UpdateReturned::new(checkout.id, book_id, user_id, now),
+                None,
+            )
+            .await?;
+        assert!(!repo.claim(&renewed).await?);
+
+        Ok(())
+    }
+}
@@ -1,22 +1,59 @@
 use async_trait::async_trait;
 use derive_new::new;
 use kernel::model::{
+    audit::event::CreateAuditLog,
+    domain_event::{DomainEvent, DomainEventKind},
     id::UserId,
-    role::Role,
     user::{
-        event::{CreateUser, DeleteUser, UpdateUserPassword, UpdateUserRole},
-        User,
+        event::{
+            CreateUser, DeactivateUser, DeleteUser, ProvisionExternalUser, ReactivateUser,
+            ResetUserPassword, UpdateUserBadge, UpdateUserPassword, UpdateUserRole,
+        },
+        User, UserDeletionBlockers,
     },
 };
 use kernel::repository::user::UserRepository;
 use shared::error::{AppError, AppResult};
 
-use crate::database::{model::user::UserRow, ConnectionPool};
+use crate::{
+    database::{
+        model::user::{UserDeletionBlockersRow, UserRow},
+        ConnectionPool,
+    },
+    repository::{audit::record_audit_log, outbox::record_event},
+};
 
 #[derive(new)]
 pub struct UserRepositoryImpl {
     db: ConnectionPool,
 }
+impl UserRepositoryImpl {
+    /// ユーザーの物理削除を妨げている要因(所有蔵書・未返却の貸出)を集計する
+    async fn fetch_deletion_blockers<'e, E>(
+        &self,
+        executor: E,
+        user_id: UserId,
+    ) -> AppResult<UserDeletionBlockers>
+    where
+        E: sqlx::PgExecutor<'e>,
+    {
+        sqlx::query_as!(
+            UserDeletionBlockersRow,
+            r#"
+                SELECT
+                    (SELECT COUNT(*) FROM books WHERE user_id = $1) AS "owned_books!",
+                    (SELECT COUNT(*) FROM checkouts WHERE user_id = $1) AS "unreturned_checkouts!",
+                    (SELECT COUNT(*) FROM fee_entries WHERE user_id = $1) AS "fee_entries!"
+            "#,
+            user_id as _
+        )
+        .fetch_one(executor)
+        .await
+        .map(UserDeletionBlockers::from)
+        .map_err(AppError::DatabaseOperationError)
+    }
+}
+
 #[async_trait]
 impl UserRepository for UserRepositoryImpl {
     async fn find_current_user(&self, current_user_id: UserId) -> AppResult<Option<User>> {
@@ -29,13 +66,15 @@ impl UserRepository for UserRepositoryImpl {
                     u.email,
                     r.name as role_name,
                     u.created_at,
-                    u.updated_at
+                    u.updated_at,
+                    u.deactivated_at
                 FROM
                     users AS u
                 INNER JOIN
                     roles AS r USING (role_id)
                 WHERE
                     u.user_id = $1
+                    AND u.deactivated_at IS NULL
             "#,
             current_user_id as _
         )
@@ -59,7 +98,8 @@ impl UserRepository for UserRepositoryImpl {
                     u.email,
                     r.name as role_name,
                     u.created_at,
-                    u.updated_at
+                    u.updated_at,
+                    u.deactivated_at
                 FROM
                     users AS u
                 INNER JOIN
@@ -77,11 +117,12 @@ impl UserRepository for UserRepositoryImpl {
         Ok(users)
     }
 
-    async fn create(&self, event: CreateUser) -> AppResult<User> {
+    async fn create(&self, event: CreateUser, audit: Option<CreateAuditLog>) -> AppResult<User> {
         let user_id = UserId::new();
         let hashed_password = hash_password(&event.password).await?;
-        let role = Role::User;
+        let role = event.role;
 
+        let mut tx = self.db.begin().await?;
         let res = sqlx::query!(
             r#"
                 INSERT INTO users(user_id, name, email, password_hash, role_id)
@@ -93,9 +134,14 @@ impl UserRepository for UserRepositoryImpl {
             hashed_password,
             role.as_ref()
         )
-        .execute(self.db.inner_ref())
+        .execute(&mut *tx)
         .await
-        .map_err(AppError::DatabaseOperationError)?;
+        .map_err(|e| match e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => AppError::UnprocessableEntity(
+                "指定されたメールアドレスは他のユーザーに登録されています".into(),
+            ),
+            e => AppError::DatabaseOperationError(e),
+        })?;
 
         if res.rows_affected() < 1 {
             return Err(AppError::NoRowsAffectedError(
@@ -103,15 +149,35 @@ impl UserRepository for UserRepositoryImpl {
             ));
         }
 
-        Ok(User {
+        let user = User {
             id: user_id,
             name: event.name,
             email: event.email,
             role,
-        })
+            deactivated_at: None,
+        };
+        record_event(
+            &mut tx,
+            &DomainEvent::new(DomainEventKind::UserCreated((&user).into())),
+        )
+        .await?;
+        if let Some(audit) = audit {
+            let audit = CreateAuditLog {
+                target_id: Some(user_id.to_string()),
+                ..audit
+            };
+            record_audit_log(&mut tx, audit).await?;
+        }
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(user)
     }
 
-    async fn update_password(&self, event: UpdateUserPassword) -> AppResult<()> {
+    async fn update_password(
+        &self,
+        event: UpdateUserPassword,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()> {
         let mut tx = self.db.begin().await?;
 
         // 現在のパスワードのハッシュ値を取得
@@ -124,7 +190,12 @@ impl UserRepository for UserRepositoryImpl {
         .fetch_one(&mut *tx)
         .await
         .map_err(AppError::DatabaseOperationError)?
-        .password_hash;
+        .password_hash
+        .ok_or_else(|| {
+            AppError::UnprocessableEntity(
+                "パスワードが設定されていないユーザーのため、変更できません".into(),
+            )
+        })?;
 
         // 現在のパスワードが正しいか検証
         verify_password(&event.current_password, &original_password_hash).await?;
@@ -141,13 +212,51 @@ impl UserRepository for UserRepositoryImpl {
         .execute(&mut *tx)
         .await
         .map_err(AppError::DatabaseOperationError)?;
+        if let Some(audit) = audit {
+            record_audit_log(&mut tx, audit).await?;
+        }
 
         tx.commit().await.map_err(AppError::TransactionError)?;
 
         Ok(())
     }
 
-    async fn update_role(&self, event: UpdateUserRole) -> AppResult<()> {
+    async fn reset_password(
+        &self,
+        event: ResetUserPassword,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()> {
+        let new_password_hash = hash_password(&event.new_password).await?;
+        let mut tx = self.db.begin().await?;
+        let res = sqlx::query!(
+            r#"
+                UPDATE users SET password_hash = $2 WHERE user_id = $1;
+            "#,
+            event.user_id as _,
+            new_password_hash,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError("Specified user not found".into()));
+        }
+
+        if let Some(audit) = audit {
+            record_audit_log(&mut tx, audit).await?;
+        }
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn update_role(
+        &self,
+        event: UpdateUserRole,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
         let res = sqlx::query!(
             r#"
                 UPDATE users
@@ -159,7 +268,7 @@ impl UserRepository for UserRepositoryImpl {
             event.user_id as _,
             event.role.as_ref()
         )
-        .execute(self.db.inner_ref())
+        .execute(&mut *tx)
         .await
         .map_err(AppError::DatabaseOperationError)?;
 
@@ -167,26 +276,367 @@ impl UserRepository for UserRepositoryImpl {
             return Err(AppError::NotFoundError("Specified user not found".into()));
         }
 
+        if let Some(audit) = audit {
+            record_audit_log(&mut tx, audit).await?;
+        }
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
         Ok(())
     }
 
-    async fn delete(&self, event: DeleteUser) -> AppResult<()> {
+    async fn update_badge(
+        &self,
+        event: UpdateUserBadge,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
         let res = sqlx::query!(
             r#"
-                DELETE FROM users WHERE user_id = $1;
+                UPDATE users SET badge_code = $2 WHERE user_id = $1;
+            "#,
+            event.user_id as _,
+            event.badge_code
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => AppError::UnprocessableEntity(
+                "指定されたバッジは他のユーザーに登録されています".into(),
+            ),
+            e => AppError::DatabaseOperationError(e),
+        })?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError("Specified user not found".into()));
+        }
+
+        if let Some(audit) = audit {
+            record_audit_log(&mut tx, audit).await?;
+        }
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn find_by_badge_code(&self, badge_code: &str) -> AppResult<Option<User>> {
+        sqlx::query_as!(
+            UserRow,
+            r#"
+                SELECT
+                    u.user_id,
+                    u.name,
+                    u.email,
+                    r.name as role_name,
+                    u.created_at,
+                    u.updated_at,
+                    u.deactivated_at
+                FROM
+                    users AS u
+                INNER JOIN
+                    roles AS r USING (role_id)
+                WHERE
+                    u.badge_code = $1
+                    AND u.deactivated_at IS NULL
+            "#,
+            badge_code
+        )
+        .fetch_optional(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .map(User::try_from)
+        .transpose()
+    }
+
+    async fn deactivate(
+        &self,
+        event: DeactivateUser,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+        let res = sqlx::query!(
+            r#"
+                UPDATE users
+                SET deactivated_at = CURRENT_TIMESTAMP(3)
+                WHERE user_id = $1 AND deactivated_at IS NULL;
+            "#,
+            event.user_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError(
+                "Specified active user not found".into(),
+            ));
+        }
+
+        if let Some(audit) = audit {
+            record_audit_log(&mut tx, audit).await?;
+        }
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn reactivate(
+        &self,
+        event: ReactivateUser,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+        let res = sqlx::query!(
+            r#"
+                UPDATE users
+                SET deactivated_at = NULL
+                WHERE user_id = $1 AND deactivated_at IS NOT NULL;
             "#,
             event.user_id as _
         )
-        .execute(self.db.inner_ref())
+        .execute(&mut *tx)
         .await
         .map_err(AppError::DatabaseOperationError)?;
 
         if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError(
+                "Specified deactivated user not found".into(),
+            ));
+        }
+
+        if let Some(audit) = audit {
+            record_audit_log(&mut tx, audit).await?;
+        }
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn find_deletion_blockers(&self, user_id: UserId) -> AppResult<UserDeletionBlockers> {
+        self.fetch_deletion_blockers(self.db.inner_ref(), user_id)
+            .await
+    }
+
+    async fn delete(&self, event: DeleteUser, audit: Option<CreateAuditLog>) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // 削除対象のユーザーを行ロックし、確認中に蔵書や貸出が追加されないようにする
+        let exists = sqlx::query!(
+            r#"
+                SELECT user_id FROM users WHERE user_id = $1 FOR UPDATE;
+            "#,
+            event.user_id as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .is_some();
+
+        if !exists {
             return Err(AppError::NotFoundError("Specified user not found".into()));
         }
 
+        let blockers = self
+            .fetch_deletion_blockers(&mut *tx, event.user_id)
+            .await?;
+        if !blockers.is_empty() {
+            return Err(AppError::ConflictError(format!(
+                "ユーザー({})は蔵書を{}冊所有し、{}冊を貸出中で、料金の記録が{}件あるため削除できません",
+                event.user_id,
+                blockers.owned_books,
+                blockers.unreturned_checkouts,
+                blockers.fee_entries
+            )));
+        }
+
+        sqlx::query!(
+            r#"
+                DELETE FROM users WHERE user_id = $1;
+            "#,
+            event.user_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+        if let Some(audit) = audit {
+            record_audit_log(&mut tx, audit).await?;
+        }
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
         Ok(())
     }
+
+    async fn provision_external_user(&self, event: ProvisionExternalUser) -> AppResult<User> {
+        let mut tx = self.db.begin().await?;
+
+        // 既に認証基盤のユーザーと紐付いているユーザーを探す
+        let linked = sqlx::query!(
+            r#"
+                SELECT user_id FROM users
+                WHERE external_issuer = $1 AND external_subject = $2
+                FOR UPDATE;
+            "#,
+            event.issuer,
+            event.subject
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .map(|row| UserId::from(row.user_id));
+
+        let existing = match linked {
+            Some(user_id) => {
+                // パスワードを持たない、認証基盤によって作成されたユーザーに限り、ロールを同期する
+                // 既存のユーザーに紐付けた場合は、ローカルで付与されたロールを変更しない
+                if let Some(role) = &event.role {
+                    sqlx::query!(
+                        r#"
+                            UPDATE users
+                            SET role_id = (
+                                SELECT role_id FROM roles WHERE name = $2
+                            )
+                            WHERE user_id = $1 AND password_hash IS NULL;
+                        "#,
+                        user_id as _,
+                        role.as_ref()
+                    )
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(AppError::DatabaseOperationError)?;
+                }
+                Some(user_id)
+            }
+            None => {
+                // 未検証のメールアドレスでは、既存のユーザーへの紐付けもユーザーの作成も行わない
+                if !event.email_verified {
+                    return Err(AppError::ForbiddenError);
+                }
+
+                let same_email = sqlx::query!(
+                    r#"
+                        SELECT user_id, external_issuer FROM users
+                        WHERE lower(email) = lower($1)
+                        FOR UPDATE;
+                    "#,
+                    event.email
+                )
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(AppError::DatabaseOperationError)?;
+
+                match same_email {
+                    // 同じメールアドレスのユーザーが、既に別の認証基盤のユーザーと紐付いている
+                    Some(row) if row.external_issuer.is_some() => {
+                        return Err(AppError::UnprocessableEntity(
+                            "同じメールアドレスのユーザーが、別の認証基盤のユーザーと紐付いています"
+                                .into(),
+                        ));
+                    }
+                    // 既存のユーザーへの紐付けを許可しない認証基盤では、同じメールアドレスのユーザーを作成しない
+                    Some(_) if !event.link_by_email => {
+                        return Err(AppError::UnprocessableEntity(
+                            "同じメールアドレスのユーザーが既に存在します".into(),
+                        ));
+                    }
+                    // 同じメールアドレスを持つ未紐付けの既存ユーザーに紐付ける
+                    Some(row) => {
+                        sqlx::query!(
+                            r#"
+                                UPDATE users SET external_issuer = $2, external_subject = $3
+                                WHERE user_id = $1;
+                            "#,
+                            row.user_id,
+                            event.issuer,
+                            event.subject
+                        )
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(AppError::DatabaseOperationError)?;
+                        Some(UserId::from(row.user_id))
+                    }
+                    None => None,
+                }
+            }
+        };
+
+        let (user_id, created) = match existing {
+            Some(user_id) => (user_id, false),
+            // 該当するユーザーがいなければ、パスワードを持たないユーザーとして作成する
+            None => {
+                let user_id = UserId::new();
+                let role = event.role.unwrap_or_default();
+                let res = sqlx::query!(
+                    r#"
+                        INSERT INTO users(user_id, name, email, role_id, external_issuer, external_subject)
+                        SELECT $1, $2, $3, role_id, $5, $6 FROM roles WHERE name = $4;
+                    "#,
+                    user_id as _,
+                    event.name,
+                    event.email,
+                    role.as_ref(),
+                    event.issuer,
+                    event.subject
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| match e {
+                    sqlx::Error::Database(db) if db.is_unique_violation() => AppError::UnprocessableEntity(
+                        "指定されたメールアドレスは他のユーザーに登録されています".into(),
+                    ),
+                    e => AppError::DatabaseOperationError(e),
+                })?;
+
+                if res.rows_affected() < 1 {
+                    return Err(AppError::NoRowsAffectedError(
+                        "No user has been created".into(),
+                    ));
+                }
+                (user_id, true)
+            }
+        };
+
+        let user = sqlx::query_as!(
+            UserRow,
+            r#"
+                SELECT
+                    u.user_id,
+                    u.name,
+                    u.email,
+                    r.name as role_name,
+                    u.created_at,
+                    u.updated_at,
+                    u.deactivated_at
+                FROM
+                    users AS u
+                INNER JOIN
+                    roles AS r USING (role_id)
+                WHERE
+                    u.user_id = $1
+            "#,
+            user_id as _
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseOperationError)
+        .and_then(User::try_from)?;
+
+        // 無効化されたユーザーは外部の認証基盤からもログインさせない
+        if !user.is_active() {
+            return Err(AppError::UnauthenticatedError);
+        }
+
+        if created {
+            record_event(
+                &mut tx,
+                &DomainEvent::new(DomainEventKind::UserCreated((&user).into())),
+            )
+            .await?;
+        }
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(user)
+    }
 }
 
 async fn hash_password(password: &str) -> AppResult<String> {
@@ -210,3 +660,395 @@ async fn verify_password(password: &str, hash: &str) -> AppResult<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::repository::book::BookRepositoryImpl;
+    use kernel::{
+        model::{book::event::ReassignBookOwner, role::Role},
+        repository::book::BookRepository,
+    };
+
+    // fixtures/common.sqlに記載のユーザーID
+    const FIXTURE_USER_ID: &str = "5b4c96ac-316a-4bee-8e69-cac5eb84ff4c";
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_deactivate_and_reactivate_user(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = UserRepositoryImpl::new(ConnectionPool::new(pool));
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+
+        repo.deactivate(DeactivateUser { user_id }, None).await?;
+        // 無効化されたユーザーはログイン中のユーザーとして取得できない
+        assert!(repo.find_current_user(user_id).await?.is_none());
+        // 一覧には無効化日時付きで残る
+        let users = repo.find_all().await?;
+        assert!(users.iter().any(|u| u.id == user_id && !u.is_active()));
+        // 二重の無効化はエラーとなる
+        let res = repo.deactivate(DeactivateUser { user_id }, None).await;
+        assert!(matches!(res, Err(AppError::NotFoundError(_))));
+
+        repo.reactivate(ReactivateUser { user_id }, None).await?;
+        let user = repo.find_current_user(user_id).await?;
+        assert!(user.is_some_and(|u| u.is_active()));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_reset_password(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = UserRepositoryImpl::new(ConnectionPool::new(pool));
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+
+        repo.reset_password(
+            ResetUserPassword {
+                user_id,
+                new_password: "N3wP@ssw0rd!".into(),
+            },
+            None,
+        )
+        .await?;
+        // 再設定したパスワードが現在のパスワードとして扱われる
+        repo.update_password(
+            UpdateUserPassword {
+                user_id,
+                current_password: "N3wP@ssw0rd!".into(),
+                new_password: "P@ssw0rd!".into(),
+            },
+            None,
+        )
+        .await?;
+
+        let res = repo
+            .reset_password(
+                ResetUserPassword {
+                    user_id: UserId::new(),
+                    new_password: "N3wP@ssw0rd!".into(),
+                },
+                None,
+            )
+            .await;
+        assert!(matches!(res, Err(AppError::NotFoundError(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_create_user_with_role(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = UserRepositoryImpl::new(ConnectionPool::new(pool));
+        let user = repo
+            .create(
+                CreateUser {
+                    name: "Admin".into(),
+                    email: "admin@example.com".into(),
+                    password: "test_password".into(),
+                    role: Role::Admin,
+                },
+                None,
+            )
+            .await?;
+        assert_eq!(user.role, Role::Admin);
+
+        // 作成時点で指定したロールを持つ
+        let found = repo.find_current_user(user.id).await?;
+        assert_eq!(found.map(|u| u.role), Some(Role::Admin));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_update_badge_and_find_by_badge_code(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = UserRepositoryImpl::new(ConnectionPool::new(pool));
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+        let other = repo
+            .create(
+                CreateUser {
+                    name: "Other".into(),
+                    email: "other@example.com".into(),
+                    password: "test_password".into(),
+                    role: Role::User,
+                },
+                None,
+            )
+            .await?;
+
+        repo.update_badge(
+            UpdateUserBadge {
+                user_id,
+                badge_code: Some("EMP-0001".into()),
+            },
+            None,
+        )
+        .await?;
+        let user = repo.find_by_badge_code("EMP-0001").await?;
+        assert_eq!(user.map(|u| u.id), Some(user_id));
+        assert!(repo.find_by_badge_code("EMP-0002").await?.is_none());
+
+        // 他のユーザーに登録済みのバッジは登録できない
+        let res = repo
+            .update_badge(
+                UpdateUserBadge {
+                    user_id: other.id,
+                    badge_code: Some("EMP-0001".into()),
+                },
+                None,
+            )
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 無効化されたユーザーはバッジから取得できない
+        repo.deactivate(DeactivateUser { user_id }, None).await?;
+        assert!(repo.find_by_badge_code("EMP-0001").await?.is_none());
+        repo.reactivate(ReactivateUser { user_id }, None).await?;
+
+        // 登録を解除すると、他のユーザーに登録できる
+        repo.update_badge(
+            UpdateUserBadge {
+                user_id,
+                badge_code: None,
+            },
+            None,
+        )
+        .await?;
+        repo.update_badge(
+            UpdateUserBadge {
+                user_id: other.id,
+                badge_code: Some("EMP-0001".into()),
+            },
+            None,
+        )
+        .await?;
+        let user = repo.find_by_badge_code("EMP-0001").await?;
+        assert_eq!(user.map(|u| u.id), Some(other.id));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_delete_user_blocked_by_owned_books(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let user_repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+
+        let blockers = user_repo.find_deletion_blockers(user_id).await?;
+        assert_eq!(blockers.owned_books, 3);
+        assert_eq!(blockers.unreturned_checkouts, 0);
+
+        let res = user_repo.delete(DeleteUser { user_id }, None).await;
+        assert!(matches!(res, Err(AppError::ConflictError(_))));
+
+        // 蔵書を別のユーザーへ付け替えると削除できるようになる
+        let new_owner = user_repo
+            .create(
+                CreateUser {
+                    name: "New Owner".into(),
+                    email: "new.owner@example.com".into(),
+                    password: "test_password".into(),
+                    role: Role::User,
+                },
+                None,
+            )
+            .await?;
+        book_repo
+            .reassign_owner(
+                ReassignBookOwner {
+                    current_owner: user_id,
+                    new_owner: new_owner.id,
+                },
+                None,
+            )
+            .await?;
+
+        assert!(user_repo.find_deletion_blockers(user_id).await?.is_empty());
+        user_repo.delete(DeleteUser { user_id }, None).await?;
+        assert_eq!(
+            user_repo
+                .find_deletion_blockers(new_owner.id)
+                .await?
+                .owned_books,
+            3
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_delete_user_blocked_by_fee_entries(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let user = repo
+            .create(
+                CreateUser {
+                    name: "Fee Payer".into(),
+                    email: "fee.payer@example.com".into(),
+                    password: "test_password".into(),
+                    role: Role::User,
+                },
+                None,
+            )
+            .await?;
+        // 精算済みの料金であっても、台帳を失わないよう削除を妨げる
+        sqlx::query(
+            "INSERT INTO fee_entries(user_id, kind, amount) VALUES ($1, 'Overdue', 100), ($1, 'Payment', -100)",
+        )
+        .bind(user.id.raw())
+        .execute(&pool)
+        .await?;
+
+        let blockers = repo.find_deletion_blockers(user.id).await?;
+        assert_eq!(blockers.fee_entries, 2);
+        let res = repo.delete(DeleteUser { user_id: user.id }, None).await;
+        assert!(matches!(res, Err(AppError::ConflictError(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_deactivated_user_cannot_receive_books(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let user_repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let user_id = UserId::from_str(FIXTURE_USER_ID)?;
+        let deactivated = user_repo
+            .create(
+                CreateUser {
+                    name: "Deactivated".into(),
+                    email: "deactivated@example.com".into(),
+                    password: "test_password".into(),
+                    role: Role::User,
+                },
+                None,
+            )
+            .await?;
+        user_repo
+            .deactivate(
+                DeactivateUser {
+                    user_id: deactivated.id,
+                },
+                None,
+            )
+            .await?;
+
+        let res = book_repo
+            .reassign_owner(
+                ReassignBookOwner {
+                    current_owner: user_id,
+                    new_owner: deactivated.id,
+                },
+                None,
+            )
+            .await;
+        assert!(matches!(res, Err(AppError::NotFoundError(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_provision_external_user(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let event = |subject: &str, email: &str, email_verified, role| ProvisionExternalUser {
+            issuer: "https://idp.example.com".into(),
+            subject: subject.into(),
+            email: email.into(),
+            email_verified,
+            link_by_email: true,
+            name: "SSO User".into(),
+            role,
+        };
+
+        // 初回ログイン時はパスワードを持たないユーザーとして作成される
+        let created = repo
+            .provision_external_user(event("new-subject", "sso@example.com", true, None))
+            .await?;
+        assert_eq!(created.role, Role::User);
+
+        // 2回目以降は同じユーザーとなり、ロールが同期される
+        let again = repo
+            .provision_external_user(event(
+                "new-subject",
+                "changed@example.com",
+                true,
+                Some(Role::Admin),
+            ))
+            .await?;
+        assert_eq!(again.id, created.id);
+        assert_eq!(again.role, Role::Admin);
+
+        // 検証済みのメールアドレスであれば既存のユーザーに紐付けるが、ロールは変更しない
+        let fixture_user = repo
+            .find_current_user(UserId::from_str(FIXTURE_USER_ID)?)
+            .await?
+            .unwrap();
+        let linked = repo
+            .provision_external_user(event(
+                "fixture-subject",
+                &fixture_user.email.to_uppercase(),
+                true,
+                Some(Role::User),
+            ))
+            .await?;
+        assert_eq!(linked.id, fixture_user.id);
+        assert_eq!(linked.role, Role::Admin);
+        let again = repo
+            .provision_external_user(event(
+                "fixture-subject",
+                &fixture_user.email,
+                true,
+                Some(Role::User),
+            ))
+            .await?;
+        assert_eq!(again.role, Role::Admin);
+
+        // 未検証のメールアドレスでは、紐付けもユーザーの作成も行わない
+        for email in ["sso@example.com", "unverified@example.com"] {
+            let res = repo
+                .provision_external_user(event("other-subject", email, false, None))
+                .await;
+            assert!(matches!(res, Err(AppError::ForbiddenError)));
+        }
+
+        // 別の認証基盤のユーザーと紐付いたメールアドレスのユーザーは作成しない
+        let res = repo
+            .provision_external_user(event("other-subject", "SSO@example.com", true, None))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM users WHERE lower(email) = 'sso@example.com'"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(count, 1);
+
+        // 紐付けを許可しない場合は、未紐付けの既存ユーザーとも重複させない
+        let res = repo
+            .provision_external_user(ProvisionExternalUser {
+                link_by_email: false,
+                ..event("ldap-subject", &fixture_user.email, true, None)
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // ユーザーが作成された場合にのみ、ドメインイベントが記録される
+        let created_events = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM outbox WHERE event_type = 'user.created'"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(created_events, 1);
+
+        // 無効化されたユーザーはログインできない
+        repo.deactivate(
+            DeactivateUser {
+                user_id: created.id,
+            },
+            None,
+        )
+        .await?;
+        let res = repo
+            .provision_external_user(event("new-subject", "sso@example.com", true, None))
+            .await;
+        assert!(matches!(res, Err(AppError::UnauthenticatedError)));
+
+        Ok(())
+    }
+}
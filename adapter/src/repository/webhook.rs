@@ -0,0 +1,484 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use derive_new::new;
+use kernel::{
+    model::{
+        id::{UserId, WebhookDeliveryId, WebhookEventId, WebhookId},
+        webhook::{
+            event::{
+                CreateWebhook, DeleteWebhook, DeliveryOutcome, RecordDeliveryAttempt,
+                ReplayDelivery,
+            },
+            IssuedWebhook, PendingDelivery, Webhook, WebhookDelivery, WebhookDeliveryStatus,
+            WebhookEvent,
+        },
+    },
+    repository::webhook::WebhookRepository,
+};
+use shared::error::{AppError, AppResult};
+
+use crate::database::{
+    model::webhook::{PendingDeliveryRow, WebhookDeliveryRow, WebhookRow},
+    ConnectionPool,
+};
+
+/// 取得した配信を他のインスタンスが取得しないよう確保しておく時間
+/// 送信中にプロセスが停止した場合は、この時間が過ぎると再び送信される
+const CLAIM_LEASE: Duration = Duration::minutes(5);
+
+#[derive(new)]
+pub struct WebhookRepositoryImpl {
+    db: ConnectionPool,
+}
+
+#[async_trait]
+impl WebhookRepository for WebhookRepositoryImpl {
+    async fn create(&self, event: CreateWebhook) -> AppResult<IssuedWebhook> {
+        let CreateWebhook {
+            url,
+            events,
+            created_by,
+            secret,
+        } = event;
+        let events: Vec<String> = events.iter().map(|e| e.as_ref().to_string()).collect();
+
+        let row = sqlx::query_as!(
+            WebhookRow,
+            r#"
+                INSERT INTO webhooks(webhook_id, url, events, secret, created_by)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING
+                    webhook_id,
+                    url,
+                    events,
+                    created_by AS "created_by: UserId",
+                    created_at
+            "#,
+            WebhookId::new() as _,
+            url,
+            &events,
+            secret.0,
+            created_by as _
+        )
+        .fetch_one(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        Ok(IssuedWebhook {
+            webhook: row.try_into()?,
+            secret,
+        })
+    }
+
+    async fn find_all(&self) -> AppResult<Vec<Webhook>> {
+        let rows = sqlx::query_as!(
+            WebhookRow,
+            r#"
+                SELECT
+                    webhook_id,
+                    url,
+                    events,
+                    created_by AS "created_by: UserId",
+                    created_at
+                FROM webhooks
+                ORDER BY created_at DESC
+            "#
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        rows.into_iter().map(Webhook::try_from).collect()
+    }
+
+    async fn delete(&self, event: DeleteWebhook) -> AppResult<()> {
+        let res = sqlx::query!(
+            "DELETE FROM webhooks WHERE webhook_id = $1",
+            event.webhook_id as _
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError(
+                "Specified webhook not found".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn enqueue(&self, event: &WebhookEvent) -> AppResult<()> {
+        // 配信先には、イベントの種類や発生日時を含めた封筒の形で送る
+        // 再送しても同じ内容を送れるよう、配信ごとに送信する内容を保存しておく
+        // 同じイベントが再び登録された場合は、すでに配信を登録したWebhookを除く
+        let payload = serde_json::json!({
+            "id": event.id,
+            "type": event.event_type.as_ref(),
+            "occurredAt": event.occurred_at,
+            "data": event.data,
+        });
+
+        sqlx::query!(
+            r#"
+                INSERT INTO webhook_deliveries(webhook_id, event_id, event_type, payload)
+                SELECT webhook_id, $1, $2, $3
+                FROM webhooks AS w
+                WHERE $2::VARCHAR = ANY(events)
+                    AND NOT EXISTS (
+                        SELECT 1
+                        FROM webhook_deliveries AS d
+                        WHERE d.webhook_id = w.webhook_id AND d.event_id = $1
+                    )
+            "#,
+            event.id as _,
+            event.event_type.as_ref(),
+            payload
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        Ok(())
+    }
+
+    async fn find_deliveries(
+        &self,
+        webhook_id: WebhookId,
+        limit: i64,
+    ) -> AppResult<Vec<WebhookDelivery>> {
+        let rows = sqlx::query_as!(
+            WebhookDeliveryRow,
+            r#"
+                SELECT
+                    delivery_id AS "delivery_id: WebhookDeliveryId",
+                    webhook_id AS "webhook_id: WebhookId",
+                    event_id AS "event_id: WebhookEventId",
+                    event_type,
+                    payload::TEXT AS "payload!",
+                    status,
+                    attempts,
+                    response_status,
+                    last_error,
+                    next_attempt_at,
+                    created_at,
+                    delivered_at
+                FROM webhook_deliveries
+                WHERE webhook_id = $1
+                ORDER BY created_at DESC
+                LIMIT $2
+            "#,
+            webhook_id as _,
+            limit
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        rows.into_iter().map(WebhookDelivery::try_from).collect()
+    }
+
+    async fn claim_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> AppResult<Vec<PendingDelivery>> {
+        // 次の送信時刻を先に延ばすことで、送信中の配信を他のインスタンスが取得しないようにする
+        // 他のトランザクションがロックしている配信は待たずに飛ばす
+        let rows = sqlx::query_as!(
+            PendingDeliveryRow,
+            r#"
+                UPDATE webhook_deliveries AS d
+                SET next_attempt_at = $2
+                FROM webhooks AS w
+                WHERE d.webhook_id = w.webhook_id
+                    AND d.delivery_id IN (
+                        SELECT delivery_id
+                        FROM webhook_deliveries
+                        WHERE status = 'Pending' AND next_attempt_at <= $1
+                        ORDER BY next_attempt_at
+                        LIMIT $3
+                        FOR UPDATE SKIP LOCKED
+                    )
+                RETURNING
+                    d.delivery_id AS "delivery_id: WebhookDeliveryId",
+                    d.webhook_id AS "webhook_id: WebhookId",
+                    d.event_id AS "event_id: WebhookEventId",
+                    d.event_type,
+                    d.payload::TEXT AS "payload!",
+                    d.status,
+                    d.attempts,
+                    d.response_status,
+                    d.last_error,
+                    d.next_attempt_at,
+                    d.created_at,
+                    d.delivered_at,
+                    w.url,
+                    w.secret
+            "#,
+            now,
+            now + CLAIM_LEASE,
+            limit
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        rows.into_iter().map(PendingDelivery::try_from).collect()
+    }
+
+    async fn record_attempt(&self, event: RecordDeliveryAttempt) -> AppResult<()> {
+        let RecordDeliveryAttempt {
+            delivery_id,
+            attempted_at,
+            response_status,
+            error,
+            outcome,
+        } = event;
+        let (status, next_attempt_at, delivered_at) = match outcome {
+            DeliveryOutcome::Succeeded => {
+                (WebhookDeliveryStatus::Succeeded, None, Some(attempted_at))
+            }
+            DeliveryOutcome::Retry(at) => (WebhookDeliveryStatus::Pending, Some(at), None),
+            DeliveryOutcome::Failed => (WebhookDeliveryStatus::Failed, None, None),
+        };
+
+        let res = sqlx::query!(
+            r#"
+                UPDATE webhook_deliveries
+                SET
+                    status = $2,
+                    attempts = attempts + 1,
+                    response_status = $3,
+                    last_error = $4,
+                    next_attempt_at = COALESCE($5, next_attempt_at),
+                    delivered_at = $6
+                WHERE delivery_id = $1
+            "#,
+            delivery_id as _,
+            status.as_ref(),
+            response_status,
+            error,
+            next_attempt_at,
+            delivered_at
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NotFoundError(
+                "Specified delivery not found".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn replay(&self, event: ReplayDelivery) -> AppResult<WebhookDelivery> {
+        // 元の配信の記録は残したまま、同じ内容の配信を新しく登録する
+        // 配信先は同じイベントIDで重複を判定できる
+        let row = sqlx::query_as!(
+            WebhookDeliveryRow,
+            r#"
+                INSERT INTO webhook_deliveries(webhook_id, event_id, event_type, payload)
+                SELECT webhook_id, event_id, event_type, payload
+                FROM webhook_deliveries
+                WHERE delivery_id = $1 AND webhook_id = $2
+                RETURNING
+                    delivery_id AS "delivery_id: WebhookDeliveryId",
+                    webhook_id AS "webhook_id: WebhookId",
+                    event_id AS "event_id: WebhookEventId",
+                    event_type,
+                    payload::TEXT AS "payload!",
+                    status,
+                    attempts,
+                    response_status,
+                    last_error,
+                    next_attempt_at,
+                    created_at,
+                    delivered_at
+            "#,
+            event.delivery_id as _,
+            event.webhook_id as _
+        )
+        .fetch_optional(self.db.inner_ref())
+        .await
+        .map_err(AppError::DatabaseOperationError)?
+        .ok_or_else(|| AppError::NotFoundError("Specified delivery not found".into()))?;
+
+        row.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::SubsecRound;
+    use kernel::model::{
+        id::BookId,
+        webhook::{WebhookBook, WebhookEventData, WebhookEventType},
+    };
+
+    use super::*;
+
+    fn book_created() -> WebhookEvent {
+        WebhookEvent::new(
+            WebhookEventType::BookCreated,
+            WebhookEventData::Book(WebhookBook {
+                book_id: BookId::new(),
+                title: "Rust in Action".into(),
+                author: "Tim McNamara".into(),
+                isbn: "9781617294556".into(),
+                description: "".into(),
+            }),
+        )
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_enqueue_claim_and_record(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = WebhookRepositoryImpl::new(ConnectionPool::new(pool));
+        // fixtures/common.sqlに記載のユーザーID
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        let books = repo
+            .create(CreateWebhook::new(
+                "https://example.com/books".into(),
+                vec![WebhookEventType::BookCreated, WebhookEventType::BookUpdated],
+                admin_id,
+            ))
+            .await?;
+        let users = repo
+            .create(CreateWebhook::new(
+                "https://example.com/users".into(),
+                vec![WebhookEventType::UserCreated],
+                admin_id,
+            ))
+            .await?;
+        assert_eq!(repo.find_all().await?.len(), 2);
+
+        // イベントを購読しているWebhookにのみ配信される
+        let event = book_created();
+        repo.enqueue(&event).await?;
+        // 同じイベントを再び登録しても、配信は重複しない
+        repo.enqueue(&event).await?;
+        assert!(repo.find_deliveries(users.webhook.id, 10).await?.is_empty());
+        let deliveries = repo.find_deliveries(books.webhook.id, 10).await?;
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].event_id, event.id);
+        assert_eq!(deliveries[0].status, WebhookDeliveryStatus::Pending);
+        let payload: serde_json::Value = serde_json::from_str(&deliveries[0].payload)?;
+        assert_eq!(payload["type"], "book.created");
+        assert_eq!(payload["data"]["title"], "Rust in Action");
+
+        // 取得した配信は、確保している間は再び取得されない
+        // 日時はミリ秒の精度で丸めて保存されるため、登録時刻より確実に後の時刻を用いる
+        let now = (Utc::now() + Duration::seconds(1)).trunc_subsecs(3);
+        let claimed = repo.claim_due_deliveries(now, 10).await?;
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(claimed[0].url, "https://example.com/books");
+        assert_eq!(claimed[0].secret, books.secret);
+        assert!(repo.claim_due_deliveries(now, 10).await?.is_empty());
+        assert_eq!(
+            repo.claim_due_deliveries(now + CLAIM_LEASE, 10)
+                .await?
+                .len(),
+            1
+        );
+
+        // 再送を記録すると、指定した時刻に再び取得される
+        let delivery_id = claimed[0].delivery.id;
+        let retry_at = now + Duration::minutes(1);
+        repo.record_attempt(RecordDeliveryAttempt {
+            delivery_id,
+            attempted_at: now,
+            response_status: Some(503),
+            error: None,
+            outcome: DeliveryOutcome::Retry(retry_at),
+        })
+        .await?;
+        assert!(repo.claim_due_deliveries(now, 10).await?.is_empty());
+        let claimed = repo.claim_due_deliveries(retry_at, 10).await?;
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(claimed[0].delivery.attempts, 1);
+        assert_eq!(claimed[0].delivery.response_status, Some(503));
+
+        repo.record_attempt(RecordDeliveryAttempt {
+            delivery_id,
+            attempted_at: retry_at,
+            response_status: Some(200),
+            error: None,
+            outcome: DeliveryOutcome::Succeeded,
+        })
+        .await?;
+        let delivery = &repo.find_deliveries(books.webhook.id, 10).await?[0];
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Succeeded);
+        assert_eq!(delivery.attempts, 2);
+        assert!(delivery.delivered_at.is_some());
+        assert!(repo
+            .claim_due_deliveries(retry_at + CLAIM_LEASE, 10)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_replay_and_delete(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = WebhookRepositoryImpl::new(ConnectionPool::new(pool));
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        let issued = repo
+            .create(CreateWebhook::new(
+                "https://example.com/books".into(),
+                vec![WebhookEventType::BookCreated],
+                admin_id,
+            ))
+            .await?;
+        let webhook_id = issued.webhook.id;
+        repo.enqueue(&book_created()).await?;
+        let original = repo.find_deliveries(webhook_id, 10).await?.remove(0);
+        repo.record_attempt(RecordDeliveryAttempt {
+            delivery_id: original.id,
+            attempted_at: Utc::now(),
+            response_status: None,
+            error: Some("connection refused".into()),
+            outcome: DeliveryOutcome::Failed,
+        })
+        .await?;
+
+        // 送り直すと、同じ内容の配信が新しく登録される
+        let replayed = repo
+            .replay(ReplayDelivery {
+                webhook_id,
+                delivery_id: original.id,
+            })
+            .await?;
+        assert_ne!(replayed.id, original.id);
+        assert_eq!(replayed.event_id, original.event_id);
+        assert_eq!(replayed.payload, original.payload);
+        assert_eq!(replayed.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(replayed.attempts, 0);
+        assert_eq!(repo.find_deliveries(webhook_id, 10).await?.len(), 2);
+
+        // 別のWebhookの配信は送り直せない
+        let res = repo
+            .replay(ReplayDelivery {
+                webhook_id: WebhookId::new(),
+                delivery_id: original.id,
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::NotFoundError(_))));
+
+        // Webhookを削除すると、配信の記録も削除される
+        repo.delete(DeleteWebhook { webhook_id }).await?;
+        assert!(repo.find_all().await?.is_empty());
+        assert!(repo.find_deliveries(webhook_id, 10).await?.is_empty());
+        let res = repo.delete(DeleteWebhook { webhook_id }).await;
+        assert!(matches!(res, Err(AppError::NotFoundError(_))));
+
+        Ok(())
+    }
+}
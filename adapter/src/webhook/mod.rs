@@ -0,0 +1,194 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use kernel::{
+    model::webhook::{PendingDelivery, WebhookSecret},
+    webhook::WebhookSender,
+};
+use reqwest::header::CONTENT_TYPE;
+use sha2::Sha256;
+use shared::{
+    config::WebhookConfig,
+    error::{AppError, AppResult},
+};
+
+/// 配信のID。再送した配信も同じIDで送るため、配信先は重複の判定に使える
+pub const HEADER_DELIVERY_ID: &str = "X-Webhook-Id";
+pub const HEADER_EVENT: &str = "X-Webhook-Event";
+/// 署名した時刻(UNIX時間の秒)
+pub const HEADER_TIMESTAMP: &str = "X-Webhook-Timestamp";
+/// `sha256=`に続けて、署名を16進文字列で表したもの
+pub const HEADER_SIGNATURE: &str = "X-Webhook-Signature";
+
+/// 配信先が検証する署名を計算する
+///
+/// 署名の対象に時刻を含めることで、配信先は古い配信の再送(リプレイ攻撃)を検出できる
+pub fn sign(secret: &WebhookSecret, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.0.as_bytes())
+        .expect("HMAC can take a key of any size");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    let signature: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("sha256={signature}")
+}
+
+/// HTTPのPOSTで配信を送信する
+pub struct HttpWebhookSender {
+    http: reqwest::Client,
+}
+
+impl HttpWebhookSender {
+    pub fn new(config: &WebhookConfig) -> AppResult<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            // リダイレクト先に署名付きの内容を送らないよう、リダイレクトには従わない
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| AppError::InternalError(e.into()))?;
+        Ok(Self { http })
+    }
+}
+
+#[async_trait]
+impl WebhookSender for HttpWebhookSender {
+    async fn send(&self, pending: &PendingDelivery) -> AppResult<u16> {
+        let delivery = &pending.delivery;
+        let timestamp = Utc::now().timestamp();
+        let res = self
+            .http
+            .post(&pending.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(HEADER_DELIVERY_ID, delivery.id.to_string())
+            .header(HEADER_EVENT, delivery.event_type.as_ref())
+            .header(HEADER_TIMESTAMP, timestamp)
+            .header(
+                HEADER_SIGNATURE,
+                sign(&pending.secret, timestamp, &delivery.payload),
+            )
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(|e| AppError::InternalError(e.into()))?;
+        Ok(res.status().as_u16())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{extract::State, http::HeaderMap, http::StatusCode, routing::post, Router};
+    use chrono::Utc;
+    use kernel::model::{
+        id::{WebhookDeliveryId, WebhookEventId, WebhookId},
+        webhook::{WebhookDelivery, WebhookDeliveryStatus, WebhookEventType},
+    };
+
+    use super::*;
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, String)>>>;
+
+    /// 受け取った配信を記録し、指定したステータスを返す配信先を起動する
+    async fn spawn_receiver(status: StatusCode) -> anyhow::Result<(String, Received)> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/hook", listener.local_addr()?);
+        let received = Received::default();
+        let app =
+            Router::new()
+                .route(
+                    "/hook",
+                    post(
+                        move |State(received): State<Received>,
+                              headers: HeaderMap,
+                              body: String| async move {
+                            received.lock().unwrap().push((headers, body));
+                            status
+                        },
+                    ),
+                )
+                .with_state(received.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Ok((url, received))
+    }
+
+    fn pending(url: String) -> PendingDelivery {
+        let now = Utc::now();
+        PendingDelivery {
+            delivery: WebhookDelivery {
+                id: WebhookDeliveryId::new(),
+                webhook_id: WebhookId::new(),
+                event_id: WebhookEventId::new(),
+                event_type: WebhookEventType::BookCreated,
+                payload: r#"{"type": "book.created"}"#.into(),
+                status: WebhookDeliveryStatus::Pending,
+                attempts: 0,
+                response_status: None,
+                last_error: None,
+                next_attempt_at: now,
+                created_at: now,
+                delivered_at: None,
+            },
+            url,
+            secret: WebhookSecret("whsec_test".into()),
+        }
+    }
+
+    #[test]
+    fn test_sign() {
+        let secret = WebhookSecret("whsec_test".into());
+        let signature = sign(&secret, 1700000000, "{}");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert_eq!(signature, sign(&secret, 1700000000, "{}"));
+        // 時刻、内容、シークレットのいずれが異なっても署名は一致しない
+        assert_ne!(signature, sign(&secret, 1700000001, "{}"));
+        assert_ne!(signature, sign(&secret, 1700000000, "{ }"));
+        assert_ne!(
+            signature,
+            sign(&WebhookSecret("whsec_other".into()), 1700000000, "{}")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_signed_delivery() -> anyhow::Result<()> {
+        let (url, received) = spawn_receiver(StatusCode::NO_CONTENT).await?;
+        let sender = HttpWebhookSender::new(&WebhookConfig::default())?;
+        let pending = pending(url);
+
+        assert_eq!(sender.send(&pending).await?, 204);
+
+        let received = received.lock().unwrap();
+        let (headers, body) = &received[0];
+        let header = |name: &str| headers.get(name).unwrap().to_str().unwrap().to_string();
+        assert_eq!(body, &pending.delivery.payload);
+        assert_eq!(header(HEADER_DELIVERY_ID), pending.delivery.id.to_string());
+        assert_eq!(header(HEADER_EVENT), "book.created");
+        assert_eq!(header("content-type"), "application/json");
+        // 配信先は、受け取った時刻と内容から署名を検証できる
+        let timestamp: i64 = header(HEADER_TIMESTAMP).parse()?;
+        assert_eq!(
+            header(HEADER_SIGNATURE),
+            sign(&pending.secret, timestamp, body)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_send_returns_error_status_and_connection_errors() -> anyhow::Result<()> {
+        let (url, _) = spawn_receiver(StatusCode::SERVICE_UNAVAILABLE).await?;
+        let sender = HttpWebhookSender::new(&WebhookConfig::default())?;
+
+        // 配信先が返したステータスはそのまま返す
+        assert_eq!(sender.send(&pending(url)).await?, 503);
+        // 接続できない場合はエラーになる
+        assert!(sender
+            .send(&pending("http://127.0.0.1:1/hook".into()))
+            .await
+            .is_err());
+        Ok(())
+    }
+}
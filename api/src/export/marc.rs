@@ -0,0 +1,289 @@
+//! MARC21の書誌レコード(ISO 2709・MARCXML)
+
+use std::fmt::Write;
+
+use kernel::model::book::Book;
+use shared::error::{AppError, AppResult};
+
+pub(super) const XML_HEADER: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+    "\n",
+    r#"<collection xmlns="http://www.loc.gov/MARC21/slim">"#,
+    "\n",
+);
+pub(super) const XML_FOOTER: &str = "</collection>\n";
+
+/// レコード終端記号
+const RECORD_TERMINATOR: u8 = 0x1D;
+/// フィールド終端記号
+const FIELD_TERMINATOR: u8 = 0x1E;
+/// サブフィールド識別記号
+const SUBFIELD_DELIMITER: u8 = 0x1F;
+
+const LEADER_LENGTH: usize = 24;
+const DIRECTORY_ENTRY_LENGTH: usize = 12;
+/// ISO 2709で表せるレコード長・フィールド長の上限
+const MAX_RECORD_LENGTH: usize = 99_999;
+const MAX_FIELD_LENGTH: usize = 9_999;
+
+/// 書誌レコード
+pub(super) struct Record {
+    /// 制御番号(001)
+    control_number: String,
+    fields: Vec<DataField>,
+}
+
+struct DataField {
+    tag: &'static str,
+    indicators: [char; 2],
+    subfields: Vec<(char, String)>,
+}
+
+impl DataField {
+    fn new(tag: &'static str, indicators: [char; 2], subfields: Vec<(char, String)>) -> Self {
+        Self {
+            tag,
+            indicators,
+            subfields,
+        }
+    }
+}
+
+impl From<&Book> for Record {
+    fn from(book: &Book) -> Self {
+        let mut fields = Vec::new();
+        if !book.isbn.is_empty() {
+            fields.push(DataField::new(
+                "020",
+                [' ', ' '],
+                vec![('a', book.isbn.clone())],
+            ));
+        }
+        fields.push(DataField::new(
+            "100",
+            ['1', ' '],
+            vec![('a', book.author.clone())],
+        ));
+        // 100(著者)があるため、第1指示子は1とする
+        fields.push(DataField::new(
+            "245",
+            ['1', '0'],
+            vec![('a', book.title.clone())],
+        ));
+        if !book.description.is_empty() {
+            fields.push(DataField::new(
+                "520",
+                [' ', ' '],
+                vec![('a', book.description.clone())],
+            ));
+        }
+        // 876(所蔵資料の情報)に、資料の番号と状態を記録する
+        fields.push(DataField::new(
+            "876",
+            [' ', ' '],
+            vec![
+                ('a', book.id.to_string()),
+                ('j', book.status.as_ref().to_string()),
+            ],
+        ));
+        Self {
+            control_number: book.id.to_string(),
+            fields,
+        }
+    }
+}
+
+impl Record {
+    /// ISO 2709形式で出力する
+    pub(super) fn to_iso2709(&self) -> AppResult<Vec<u8>> {
+        let mut entries = vec![("001", control_field(&self.control_number))];
+        entries.extend(self.fields.iter().map(|f| (f.tag, data_field(f))));
+
+        let base_address = LEADER_LENGTH + DIRECTORY_ENTRY_LENGTH * entries.len() + 1;
+        let data_length: usize = entries.iter().map(|(_, data)| data.len()).sum();
+        let record_length = base_address + data_length + 1;
+        if record_length > MAX_RECORD_LENGTH
+            || entries
+                .iter()
+                .any(|(_, data)| data.len() > MAX_FIELD_LENGTH)
+        {
+            return Err(AppError::ConversionEntityError(format!(
+                "MARCレコードの長さの上限を超えています: {}",
+                self.control_number
+            )));
+        }
+
+        let mut record = Vec::with_capacity(record_length);
+        record.extend(leader(record_length, base_address).into_bytes());
+        let mut start = 0;
+        for (tag, data) in &entries {
+            record.extend(format!("{tag}{:04}{start:05}", data.len()).into_bytes());
+            start += data.len();
+        }
+        record.push(FIELD_TERMINATOR);
+        for (_, data) in entries {
+            record.extend(data);
+        }
+        record.push(RECORD_TERMINATOR);
+        Ok(record)
+    }
+
+    /// MARCXMLの`record`要素として出力する
+    /// レコード長・データ開始位置は、ISO 2709に変換するまで意味を持たないため0とする
+    pub(super) fn to_xml(&self) -> String {
+        let mut xml = format!("<record><leader>{}</leader>", leader(0, 0));
+        let _ = write!(
+            xml,
+            r#"<controlfield tag="001">{}</controlfield>"#,
+            escape_xml(&self.control_number)
+        );
+        for field in &self.fields {
+            let [ind1, ind2] = field.indicators;
+            let _ = write!(
+                xml,
+                r#"<datafield tag="{}" ind1="{ind1}" ind2="{ind2}">"#,
+                field.tag
+            );
+            for (code, value) in &field.subfields {
+                let _ = write!(
+                    xml,
+                    r#"<subfield code="{code}">{}</subfield>"#,
+                    escape_xml(value)
+                );
+            }
+            xml.push_str("</datafield>");
+        }
+        xml.push_str("</record>\n");
+        xml
+    }
+}
+
+/// リーダー
+/// 新規(n)・言語資料(a)・単行資料(m)・文字コードはUnicode(a)とする
+fn leader(record_length: usize, base_address: usize) -> String {
+    format!("{record_length:05}nam a22{base_address:05}   4500")
+}
+
+fn control_field(value: &str) -> Vec<u8> {
+    let mut data = strip_delimiters(value).into_bytes();
+    data.push(FIELD_TERMINATOR);
+    data
+}
+
+fn data_field(field: &DataField) -> Vec<u8> {
+    let mut data = String::from_iter(field.indicators).into_bytes();
+    for (code, value) in &field.subfields {
+        data.push(SUBFIELD_DELIMITER);
+        data.push(*code as u8);
+        data.extend(strip_delimiters(value).into_bytes());
+    }
+    data.push(FIELD_TERMINATOR);
+    data
+}
+
+/// 値に含まれる区切り記号は、レコードの構造を壊すため取り除く
+fn strip_delimiters(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !matches!(*c as u32, 0x1D..=0x1F))
+        .collect()
+}
+
+/// XMLの特殊文字をエスケープし、XML 1.0で使えない制御文字を取り除く
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() && (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use kernel::model::{book::status::BookStatus, id::BookId, user::BookOwner};
+
+    use super::*;
+
+    fn book() -> Book {
+        Book {
+            id: BookId::new(),
+            title: "Rust <入門>".into(),
+            author: "山田太郎".into(),
+            isbn: "9784000000001".into(),
+            description: "区切り\u{1E}記号 & 引用符\"".into(),
+            owner: BookOwner {
+                id: Default::default(),
+                name: "owner".into(),
+            },
+            status: BookStatus::OnLoan,
+            checkout_info: None,
+        }
+    }
+
+    #[test]
+    fn test_to_iso2709() -> anyhow::Result<()> {
+        let book = book();
+        let record = Record::from(&book).to_iso2709()?;
+
+        // リーダーのレコード長・データ開始位置が実際の値と一致する
+        let leader = std::str::from_utf8(&record[..LEADER_LENGTH])?;
+        assert_eq!(leader[..5].parse::<usize>()?, record.len());
+        assert_eq!(&leader[5..12], "nam a22");
+        let base_address = leader[12..17].parse::<usize>()?;
+        assert_eq!(record[base_address - 1], FIELD_TERMINATOR);
+        assert_eq!(record.last(), Some(&RECORD_TERMINATOR));
+
+        // ディレクトリの各項目が、フィールドの位置を指す
+        let directory = std::str::from_utf8(&record[LEADER_LENGTH..base_address - 1])?;
+        let tags: Vec<_> = directory
+            .as_bytes()
+            .chunks(DIRECTORY_ENTRY_LENGTH)
+            .map(|entry| std::str::from_utf8(entry).unwrap())
+            .collect();
+        assert_eq!(
+            tags.iter().map(|e| &e[..3]).collect::<Vec<_>>(),
+            ["001", "020", "100", "245", "520", "876"]
+        );
+        let title_entry = tags[3];
+        let length = title_entry[3..7].parse::<usize>()?;
+        let start = base_address + title_entry[7..12].parse::<usize>()?;
+        let title = &record[start..start + length];
+        assert_eq!(title, "10\u{1F}aRust <入門>\u{1E}".as_bytes());
+
+        // 値に含まれていた区切り記号は取り除かれる
+        let description_entry = tags[4];
+        let length = description_entry[3..7].parse::<usize>()?;
+        let start = base_address + description_entry[7..12].parse::<usize>()?;
+        let description = &record[start..start + length];
+        assert_eq!(
+            description,
+            "  \u{1F}a区切り記号 & 引用符\"\u{1E}".as_bytes()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_xml() {
+        let book = book();
+        let xml = Record::from(&book).to_xml();
+        assert!(xml.starts_with("<record><leader>00000nam a2200000   4500</leader>"));
+        assert!(xml.contains(&format!(
+            r#"<controlfield tag="001">{}</controlfield>"#,
+            book.id
+        )));
+        assert!(xml.contains(
+            r#"<datafield tag="245" ind1="1" ind2="0"><subfield code="a">Rust &lt;入門&gt;</subfield></datafield>"#
+        ));
+        assert!(xml.contains(r#"<subfield code="a">区切り記号 &amp; 引用符&quot;</subfield>"#));
+        assert!(xml.contains(r#"<subfield code="j">OnLoan</subfield>"#));
+    }
+}
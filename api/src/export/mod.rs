@@ -0,0 +1,210 @@
+//! 蔵書・貸出履歴のエクスポート
+//!
+//! データベースから1件ずつ読み出したレコードを、その都度指定された形式に変換して送り出す。
+//! 出力全体をメモリに載せないため、蔵書の件数によらず使用するメモリはほぼ一定となる
+
+use axum::body::Bytes;
+use kernel::{
+    model::{book::Book, checkout::Checkout},
+    repository::export::RecordStream,
+};
+use serde::Serialize;
+use shared::error::{AppError, AppResult};
+use tokio_stream::{Stream, StreamExt};
+
+use crate::model::{book::BookResponse, checkout::CheckoutResponse, export::ExportFormat};
+
+mod marc;
+
+/// 蔵書のCSVの見出し
+/// 先頭の4列は蔵書のCSVの取り込みの既定の見出しと同じため、そのまま取り込み直せる
+const BOOK_CSV_HEADERS: [&str; 13] = [
+    "title",
+    "author",
+    "isbn",
+    "description",
+    "id",
+    "status",
+    "owner_id",
+    "owner_name",
+    "checkout_id",
+    "checked_out_by_id",
+    "checked_out_by_name",
+    "checked_out_at",
+    "due_at",
+];
+
+/// 貸出履歴のCSVの見出し
+const CHECKOUT_CSV_HEADERS: [&str; 12] = [
+    "id",
+    "book_id",
+    "title",
+    "author",
+    "isbn",
+    "checked_out_by",
+    "checked_out_at",
+    "due_at",
+    "returned_at",
+    "renewal_count",
+    "checked_out_via",
+    "returned_via",
+];
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+            Self::Marc => "application/marc",
+            Self::Marcxml => "application/marcxml+xml",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+            Self::Marc => "mrc",
+            Self::Marcxml => "xml",
+        }
+    }
+}
+
+/// 蔵書を指定された形式で出力する
+pub fn books(
+    format: ExportFormat,
+    records: RecordStream<Book>,
+) -> impl Stream<Item = AppResult<Bytes>> + Send {
+    let (header, footer) = match format {
+        ExportFormat::Csv => (Some(csv_record(BOOK_CSV_HEADERS)), None),
+        ExportFormat::Ndjson | ExportFormat::Marc => (None, None),
+        ExportFormat::Marcxml => (
+            Some(Ok(marc::XML_HEADER.as_bytes().to_vec())),
+            Some(Ok(marc::XML_FOOTER.as_bytes().to_vec())),
+        ),
+    };
+    encode(header, records, footer, move |book| match format {
+        ExportFormat::Csv => book_csv(book),
+        ExportFormat::Ndjson => json_line(BookResponse::from(book)),
+        ExportFormat::Marc => marc::Record::from(&book).to_iso2709(),
+        ExportFormat::Marcxml => Ok(marc::Record::from(&book).to_xml().into_bytes()),
+    })
+}
+
+/// 貸出履歴を指定された形式で出力する
+/// MARCは書誌情報の形式であり、貸出履歴は表せないため、レコードを取得せずにエラーとする
+pub fn checkouts(
+    format: ExportFormat,
+    records: impl FnOnce() -> RecordStream<Checkout>,
+) -> AppResult<impl Stream<Item = AppResult<Bytes>> + Send> {
+    let header = match format {
+        ExportFormat::Csv => Some(csv_record(CHECKOUT_CSV_HEADERS)),
+        ExportFormat::Ndjson => None,
+        ExportFormat::Marc | ExportFormat::Marcxml => {
+            let mut report = garde::Report::new();
+            report.append(
+                garde::Path::new("format"),
+                garde::Error::new("貸出履歴はcsvまたはndjsonでのみエクスポートできます"),
+            );
+            return Err(AppError::ValidationError(report));
+        }
+    };
+    Ok(encode(
+        header,
+        records(),
+        None,
+        move |checkout| match format {
+            ExportFormat::Csv => checkout_csv(checkout),
+            _ => json_line(CheckoutResponse::from(checkout)),
+        },
+    ))
+}
+
+/// 見出し・各レコード・末尾を順に出力する
+fn encode<T: 'static>(
+    header: Option<AppResult<Vec<u8>>>,
+    records: RecordStream<T>,
+    footer: Option<AppResult<Vec<u8>>>,
+    convert: impl Fn(T) -> AppResult<Vec<u8>> + Send + 'static,
+) -> impl Stream<Item = AppResult<Bytes>> + Send {
+    tokio_stream::iter(header)
+        .chain(records.map(move |record| record.and_then(&convert)))
+        .chain(tokio_stream::iter(footer))
+        .map(|chunk| chunk.map(Bytes::from))
+}
+
+fn json_line(value: impl Serialize) -> AppResult<Vec<u8>> {
+    let mut line =
+        serde_json::to_vec(&value).map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+fn csv_record<I>(fields: I) -> AppResult<Vec<u8>>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    writer
+        .write_record(fields)
+        .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+    writer
+        .into_inner()
+        .map_err(|e| AppError::ConversionEntityError(e.to_string()))
+}
+
+fn book_csv(book: Book) -> AppResult<Vec<u8>> {
+    let checkout = book.checkout_info.as_ref();
+    csv_record([
+        book.title,
+        book.author,
+        book.isbn,
+        book.description,
+        book.id.to_string(),
+        book.status.as_ref().to_string(),
+        book.owner.id.to_string(),
+        book.owner.name,
+        checkout
+            .map(|c| c.checkout_id.to_string())
+            .unwrap_or_default(),
+        checkout
+            .map(|c| c.checked_out_by.id.to_string())
+            .unwrap_or_default(),
+        checkout
+            .map(|c| c.checked_out_by.name.clone())
+            .unwrap_or_default(),
+        checkout
+            .map(|c| c.checked_out_at.to_rfc3339())
+            .unwrap_or_default(),
+        checkout.map(|c| c.due_at.to_rfc3339()).unwrap_or_default(),
+    ])
+}
+
+fn checkout_csv(checkout: Checkout) -> AppResult<Vec<u8>> {
+    csv_record([
+        checkout.id.to_string(),
+        checkout.book.book_id.to_string(),
+        checkout.book.title,
+        checkout.book.author,
+        checkout.book.isbn,
+        checkout.checked_out_by.to_string(),
+        checkout.checked_out_at.to_rfc3339(),
+        checkout.due_at.to_rfc3339(),
+        checkout
+            .returned_at
+            .map(|at| at.to_rfc3339())
+            .unwrap_or_default(),
+        checkout.renewal_count.to_string(),
+        checkout
+            .checked_out_via
+            .map(|id| id.to_string())
+            .unwrap_or_default(),
+        checkout
+            .returned_via
+            .map(|id| id.to_string())
+            .unwrap_or_default(),
+    ])
+}
@@ -1,23 +1,174 @@
-use axum::{async_trait, extract::FromRequestParts, http::request::Parts, RequestPartsExt};
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts, HeaderMap},
+    RequestPartsExt,
+};
 use axum_extra::{
     headers::{authorization::Bearer, Authorization},
     TypedHeader,
 };
-use kernel::model::{auth::AccessToken, id::UserId, role::Role, user::User};
+use kernel::model::{
+    api_key::{ApiKey, ApiKeyScope, ApiKeySecret, API_KEY_PREFIX},
+    auth::AccessToken,
+    id::UserId,
+    kiosk::{Kiosk, KioskSecret},
+    role::Role,
+    user::User,
+};
 use registry::AppRegistry;
-use shared::error::AppError;
+use shared::{config::ProxyConfig, error::AppError};
+
+/// APIキーを受け取るHTTPヘッダー名
+pub const API_KEY_HEADER: &str = "x-api-key";
+/// キオスクの資格情報を受け取るHTTPヘッダー名
+pub const KIOSK_KEY_HEADER: &str = "x-kiosk-key";
+/// リクエストIDを受け渡すHTTPヘッダー名
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+/// WebSocketのサブプロトコルで、アクセストークンを受け渡す際の接頭辞
+/// ブラウザはWebSocketの接続でAuthorizationヘッダーを送れないため、
+/// `Sec-WebSocket-Protocol: <応答するサブプロトコル>, bearer.<トークン>`として受け付ける
+pub const WEBSOCKET_TOKEN_PROTOCOL_PREFIX: &str = "bearer.";
+/// リバースプロキシが接続元のIPアドレスを付与するHTTPヘッダー名
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// 認証に用いられた資格情報
+pub enum Credential {
+    /// ログインにより発行されたアクセストークン
+    AccessToken(AccessToken),
+    /// ユーザーが発行したAPIキー
+    ApiKey(ApiKey),
+}
 
 /// 認証済みユーザー情報
 pub struct AuthorizedUser {
-    pub access_token: AccessToken,
+    pub credential: Credential,
     pub user: User,
 }
 impl AuthorizedUser {
     pub fn id(&self) -> UserId {
         self.user.id
     }
+    /// 管理者権限を持つかどうか
+    /// APIキーで認証された場合は、キーに`admin`スコープが付与されている必要がある
     pub fn is_admin(&self) -> bool {
         self.user.role == Role::Admin
+            && match &self.credential {
+                Credential::AccessToken(_) => true,
+                Credential::ApiKey(key) => key.has_scope(ApiKeyScope::Admin),
+            }
+    }
+    /// ログインにより発行されたアクセストークンで認証されたかどうか
+    /// パスワードの変更やAPIキーの管理など、アカウントそのものに関わる操作では必須とする
+    pub fn is_logged_in(&self) -> bool {
+        matches!(self.credential, Credential::AccessToken(_))
+    }
+}
+
+/// リクエストからAPIキーを取り出す
+/// `X-API-Key`ヘッダー、またはAPIキーのプレフィックスを持つ`Bearer`トークンを受け付ける
+fn extract_api_key(parts: &Parts, bearer: Option<&str>) -> Option<ApiKeySecret> {
+    parts
+        .headers
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .or(bearer.filter(|token| token.starts_with(API_KEY_PREFIX)))
+        .map(|key| ApiKeySecret(key.to_string()))
+}
+
+/// WebSocketの接続要求で、サブプロトコルとして送られたトークンを取り出す
+fn extract_websocket_token(parts: &Parts) -> Option<String> {
+    let is_upgrade = parts
+        .headers
+        .get(header::UPGRADE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("websocket"));
+    if !is_upgrade {
+        return None;
+    }
+    parts
+        .headers
+        .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .find_map(|protocol| {
+            protocol
+                .trim()
+                .strip_prefix(WEBSOCKET_TOKEN_PROTOCOL_PREFIX)
+        })
+        .filter(|token| !token.is_empty())
+        .map(String::from)
+}
+
+/// APIキーのスコープで、リクエストされた操作が許可されているかを判定する
+fn is_permitted_by_scopes(api_key: &ApiKey, parts: &Parts) -> bool {
+    if parts.method.is_safe() {
+        api_key.has_scope(ApiKeyScope::Read) || api_key.has_scope(ApiKeyScope::Write)
+    } else {
+        api_key.has_scope(ApiKeyScope::Write)
+    }
+}
+
+/// `Authorization`ヘッダーのBearerトークンを取り出す
+async fn extract_bearer(parts: &mut Parts) -> Option<String> {
+    parts
+        .extract::<TypedHeader<Authorization<Bearer>>>()
+        .await
+        .ok()
+        .map(|TypedHeader(Authorization(bearer))| bearer.token().to_string())
+}
+
+impl AuthorizedUser {
+    /// 送られた資格情報を検証し、認証済みユーザーを求める
+    /// `bearer`には、`Authorization`ヘッダーなどから取り出したトークンを渡す
+    async fn authorize(
+        parts: &Parts,
+        registry: &AppRegistry,
+        bearer: Option<String>,
+    ) -> Result<Self, AppError> {
+        let (credential, user_id) = match extract_api_key(parts, bearer.as_deref()) {
+            // APIキーからユーザーIDを取得
+            Some(secret) => {
+                let api_key = registry
+                    .api_key_repository()
+                    .verify(&secret)
+                    .await?
+                    .ok_or(AppError::InvalidTokenError)?;
+                if !is_permitted_by_scopes(&api_key, parts) {
+                    return Err(AppError::ForbiddenError);
+                }
+                let user_id = api_key.user_id;
+                (Credential::ApiKey(api_key), user_id)
+            }
+            // トークンからユーザーIDを取得
+            None => {
+                // 資格情報が送られていない
+                let access_token = AccessToken(bearer.ok_or(AppError::UnauthenticatedError)?);
+                // 期限切れ、またはログアウト済みのトークン
+                let user_id = registry
+                    .auth_repository()
+                    .fetch_user_id_from_token(&access_token)
+                    .await?
+                    .ok_or(AppError::InvalidTokenError)?;
+                (Credential::AccessToken(access_token), user_id)
+            }
+        };
+
+        // ユーザーIDからユーザー情報を取得
+        // トークンの発行後にユーザーが無効化された場合も、トークンは無効とみなす
+        let user = registry
+            .user_repository()
+            .find_current_user(user_id)
+            .await?
+            .ok_or(AppError::InvalidTokenError)?;
+
+        Ok(Self { credential, user })
     }
 }
 
@@ -30,30 +181,131 @@ impl FromRequestParts<AppRegistry> for AuthorizedUser {
         registry: &AppRegistry,
     ) -> Result<Self, Self::Rejection> {
         // HTTPヘッダー"Authorization"からトークン(Bearer)を取得
-        let TypedHeader(Authorization(bearer)) = parts
-            .extract::<TypedHeader<Authorization<Bearer>>>()
+        let bearer = extract_bearer(parts).await;
+        Self::authorize(parts, registry, bearer).await
+    }
+}
+
+/// WebSocketの接続要求で認証済みのユーザー
+/// `AuthorizedUser`の資格情報に加え、サブプロトコルで送られたアクセストークンも受け付ける。
+/// 他のエンドポイントに資格情報の受け渡し方を増やさないよう、WebSocketのハンドラーでのみ用いる
+pub struct WebSocketUser(pub AuthorizedUser);
+
+#[async_trait]
+impl FromRequestParts<AppRegistry> for WebSocketUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        registry: &AppRegistry,
+    ) -> Result<Self, Self::Rejection> {
+        let bearer = match extract_bearer(parts).await {
+            Some(bearer) => Some(bearer),
+            None => extract_websocket_token(parts),
+        };
+        AuthorizedUser::authorize(parts, registry, bearer)
             .await
-            .map_err(|_| AppError::UnauthorizedError)?;
-        let access_token = AccessToken(bearer.token().to_string());
+            .map(Self)
+    }
+}
 
-        // トークンからユーザーIDを取得
-        let user_id = registry
-            .auth_repository()
-            .fetch_user_id_from_token(&access_token)
-            .await?
+/// 認証済みのキオスク
+/// 利用者としての資格情報ではないため、`AuthorizedUser`としては扱えない
+pub struct AuthorizedKiosk {
+    pub kiosk: Kiosk,
+}
+
+#[async_trait]
+impl FromRequestParts<AppRegistry> for AuthorizedKiosk {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        registry: &AppRegistry,
+    ) -> Result<Self, Self::Rejection> {
+        let secret = parts
+            .headers
+            .get(KIOSK_KEY_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(|key| KioskSecret(key.to_string()))
             .ok_or(AppError::UnauthenticatedError)?;
 
-        // ユーザーIDからユーザー情報を取得
-        let user = registry
-            .user_repository()
-            .find_current_user(user_id)
+        let kiosk_repository = registry.kiosk_repository();
+        let kiosk = kiosk_repository
+            .verify(&secret)
             .await?
-            .ok_or(AppError::UnauthenticatedError)?;
+            .ok_or(AppError::InvalidTokenError)?;
+        // 端末の紛失・乗っ取りに備え、キオスクごとにリクエスト数を制限する
+        kiosk_repository.consume_rate_limit(kiosk.id).await?;
+
+        Ok(Self { kiosk })
+    }
+}
+
+/// 監査ログに記録する、リクエストの付帯情報
+/// いずれも取得できない場合があるため、抽出自体は失敗しない
+pub struct RequestMetadata {
+    pub request_id: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+#[async_trait]
+impl FromRequestParts<AppRegistry> for RequestMetadata {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        registry: &AppRegistry,
+    ) -> Result<Self, Self::Rejection> {
+        let request_id = parts
+            .headers
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(String::from);
+        let ip_address = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| {
+                client_address(addr.ip(), &parts.headers, &registry.proxy_config())
+            })
+            .map(|ip| ip.to_string());
 
-        Ok(Self { access_token, user })
+        Ok(Self {
+            request_id,
+            ip_address,
+        })
     }
 }
 
+/// 接続元のIPアドレスを求める
+/// `X-Forwarded-For`は直接の接続元が信頼するプロキシの場合にのみ参照し、
+/// 右端から信頼するプロキシを除いた最初のアドレスを接続元とする
+fn client_address(peer: IpAddr, headers: &HeaderMap, proxy: &ProxyConfig) -> IpAddr {
+    if !proxy.is_trusted(peer) {
+        return peer;
+    }
+    let forwarded = headers
+        .get_all(FORWARDED_FOR_HEADER)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|v| v.trim().parse::<IpAddr>().ok())
+        .collect::<Option<Vec<_>>>();
+    // 解析できない値を含む場合は、改ざんの可能性があるため直接の接続元を採用する
+    let Some(forwarded) = forwarded else {
+        return peer;
+    };
+    forwarded
+        .iter()
+        .rev()
+        .find(|ip| !proxy.is_trusted(**ip))
+        .or(forwarded.first())
+        .copied()
+        .unwrap_or(peer)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,43 +315,176 @@ mod tests {
     fn test_authorized_user_id() {
         let user_id = UserId::new();
         let authorized_user = AuthorizedUser {
-            access_token: AccessToken("test_token".to_string()),
+            credential: Credential::AccessToken(AccessToken("test_token".to_string())),
             user: User {
                 id: user_id,
                 name: "Test User".to_string(),
                 email: "test@example.com".to_string(),
                 role: Role::User,
+                deactivated_at: None,
             },
         };
 
         assert_eq!(authorized_user.id(), user_id);
     }
 
+    #[test]
+    fn test_extract_websocket_token() {
+        let parts = |headers: &[(&str, &str)]| {
+            let mut req = axum::http::Request::get("/");
+            for (name, value) in headers {
+                req = req.header(*name, *value);
+            }
+            req.body(()).unwrap().into_parts().0
+        };
+
+        let token = extract_websocket_token(&parts(&[
+            ("Upgrade", "websocket"),
+            ("Sec-WebSocket-Protocol", "book-watch, bearer.abc"),
+        ]));
+        assert_eq!(token.as_deref(), Some("abc"));
+
+        // WebSocketの接続要求でなければ、サブプロトコルは参照しない
+        let token = extract_websocket_token(&parts(&[(
+            "Sec-WebSocket-Protocol",
+            "book-watch, bearer.abc",
+        )]));
+        assert_eq!(token, None);
+
+        let token = extract_websocket_token(&parts(&[
+            ("Upgrade", "websocket"),
+            ("Sec-WebSocket-Protocol", "book-watch, bearer."),
+        ]));
+        assert_eq!(token, None);
+    }
+
     #[test]
     fn test_authorized_user_is_admin() {
         let user_id = UserId::new();
         let admin_user = AuthorizedUser {
-            access_token: AccessToken("test_token".to_string()),
+            credential: Credential::AccessToken(AccessToken("test_token".to_string())),
             user: User {
                 id: user_id,
                 name: "Test Admin".to_string(),
                 email: "admin@example.com".to_string(),
                 role: Role::Admin,
+                deactivated_at: None,
             },
         };
 
         assert!(admin_user.is_admin());
 
         let regular_user = AuthorizedUser {
-            access_token: AccessToken("test_token".to_string()),
+            credential: Credential::AccessToken(AccessToken("test_token".to_string())),
             user: User {
                 id: user_id,
                 name: "Test User".to_string(),
                 email: "user@example.com".to_string(),
                 role: Role::User,
+                deactivated_at: None,
             },
         };
 
         assert!(!regular_user.is_admin());
     }
+
+    fn api_key(scopes: Vec<ApiKeyScope>) -> ApiKey {
+        ApiKey {
+            id: kernel::model::id::ApiKeyId::new(),
+            user_id: UserId::new(),
+            name: "test".to_string(),
+            key_prefix: "bmk_12345678".to_string(),
+            scopes,
+            created_at: chrono::Utc::now(),
+            last_used_at: None,
+            revoked_at: None,
+        }
+    }
+
+    #[test]
+    fn test_authorized_user_is_admin_with_api_key() {
+        let admin = |scopes| AuthorizedUser {
+            credential: Credential::ApiKey(api_key(scopes)),
+            user: User {
+                id: UserId::new(),
+                name: "Test Admin".to_string(),
+                email: "admin@example.com".to_string(),
+                role: Role::Admin,
+                deactivated_at: None,
+            },
+        };
+
+        assert!(!admin(vec![ApiKeyScope::Read, ApiKeyScope::Write]).is_admin());
+        assert!(admin(vec![ApiKeyScope::Write, ApiKeyScope::Admin]).is_admin());
+    }
+
+    #[test]
+    fn test_is_permitted_by_scopes() {
+        let parts = |method: &str| {
+            axum::http::Request::builder()
+                .method(method)
+                .body(())
+                .unwrap()
+                .into_parts()
+                .0
+        };
+        let read_only = api_key(vec![ApiKeyScope::Read]);
+        let writable = api_key(vec![ApiKeyScope::Write]);
+
+        assert!(is_permitted_by_scopes(&read_only, &parts("GET")));
+        assert!(!is_permitted_by_scopes(&read_only, &parts("POST")));
+        assert!(!is_permitted_by_scopes(&read_only, &parts("DELETE")));
+        assert!(is_permitted_by_scopes(&writable, &parts("GET")));
+        assert!(is_permitted_by_scopes(&writable, &parts("PUT")));
+        assert!(!is_permitted_by_scopes(
+            &api_key(vec![ApiKeyScope::Admin]),
+            &parts("GET")
+        ));
+    }
+
+    #[test]
+    fn test_extract_api_key() {
+        let parts = |header: Option<&str>| {
+            let mut builder = axum::http::Request::builder();
+            if let Some(h) = header {
+                builder = builder.header(API_KEY_HEADER, h);
+            }
+            builder.body(()).unwrap().into_parts().0
+        };
+
+        let key = extract_api_key(&parts(Some("bmk_from_header")), Some("session-token"));
+        assert_eq!(key.map(|k| k.0).as_deref(), Some("bmk_from_header"));
+
+        let key = extract_api_key(&parts(None), Some("bmk_from_bearer"));
+        assert_eq!(key.map(|k| k.0).as_deref(), Some("bmk_from_bearer"));
+
+        assert!(extract_api_key(&parts(None), Some("session-token")).is_none());
+        assert!(extract_api_key(&parts(None), None).is_none());
+    }
+
+    #[test]
+    fn test_client_address() {
+        let proxy = ProxyConfig {
+            trusted_proxies: vec!["10.0.0.0/8".parse().unwrap()],
+        };
+        let address = |peer: [u8; 4], forwarded: Option<&str>| {
+            let mut headers = HeaderMap::new();
+            if let Some(v) = forwarded {
+                headers.insert(FORWARDED_FOR_HEADER, v.parse().unwrap());
+            }
+            client_address(IpAddr::from(peer), &headers, &proxy).to_string()
+        };
+
+        assert_eq!(address([192, 0, 2, 1], None), "192.0.2.1");
+        // 信頼しない接続元が付与したヘッダーは無視する
+        assert_eq!(address([192, 0, 2, 1], Some("198.51.100.1")), "192.0.2.1");
+        // 信頼するプロキシ経由の場合は、右端から信頼するプロキシを除いた接続元を採用する
+        assert_eq!(
+            address([10, 0, 0, 1], Some("198.51.100.1, 203.0.113.7, 10.0.0.2")),
+            "203.0.113.7"
+        );
+        assert_eq!(address([10, 0, 0, 1], Some("10.0.0.3")), "10.0.0.3");
+        assert_eq!(address([10, 0, 0, 1], Some("unknown")), "10.0.0.1");
+        assert_eq!(address([10, 0, 0, 1], None), "10.0.0.1");
+    }
 }
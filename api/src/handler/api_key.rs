@@ -0,0 +1,141 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use garde::Validate;
+use kernel::model::{
+    api_key::{
+        event::{CreateApiKey, RevokeApiKey},
+        ApiKeyScope,
+    },
+    id::ApiKeyId,
+};
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::{
+    extractor::AuthorizedUser,
+    model::api_key::{ApiKeysResponse, CreateApiKeyRequest, IssuedApiKeyResponse},
+};
+
+/// APIキーを発行する
+/// 発行されたキー本体はこのレスポンスでのみ返却される
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/users/me/api-keys",
+        responses (
+            (status = 201, description = "APIキー発行成功", body = IssuedApiKeyResponse),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "APIキーで認証されている場合、または管理者以外がadminスコープを指定した場合"),
+        ),
+        request_body = CreateApiKeyRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn create_api_key(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<CreateApiKeyRequest>,
+) -> AppResult<(StatusCode, Json<IssuedApiKeyResponse>)> {
+    // APIキーから別のAPIキーを発行できないよう、ログインによる認証を必須とする
+    if !user.is_logged_in() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    req.validate(&())?;
+
+    let mut scopes: Vec<ApiKeyScope> = Vec::new();
+    for scope in req.scopes.into_iter().map(ApiKeyScope::from) {
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    if scopes.contains(&ApiKeyScope::Admin) && !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    let issued = registry
+        .api_key_repository()
+        .create(CreateApiKey::new(user.id(), req.name, scopes))
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(issued.into())))
+}
+
+/// 自身が発行したAPIキーの一覧を取得する
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/users/me/api-keys",
+        responses (
+            (status = 200, description = "APIキー一覧取得成功", body = ApiKeysResponse),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "APIキーで認証されている場合"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn list_api_keys(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<ApiKeysResponse>> {
+    if !user.is_logged_in() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    registry
+        .api_key_repository()
+        .find_by_user_id(user.id())
+        .await
+        .map(ApiKeysResponse::from)
+        .map(Json)
+}
+
+/// 自身が発行したAPIキーを失効させる
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/users/me/api-keys/{api_key_id}",
+        responses (
+            (status = 204, description = "APIキー失効成功"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "APIキーで認証されている場合"),
+            (status = 404, description = "指定された有効なAPIキーが見つからない場合"),
+        ),
+        params(
+            ("api_key_id" = ApiKeyId, Path, description = "失効させるAPIキーのID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn revoke_api_key(
+    user: AuthorizedUser,
+    Path(api_key_id): Path<ApiKeyId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    if !user.is_logged_in() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    registry
+        .api_key_repository()
+        .revoke(RevokeApiKey {
+            api_key_id,
+            requested_user: user.id(),
+        })
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
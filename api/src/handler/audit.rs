@@ -0,0 +1,85 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use garde::Validate;
+use kernel::model::audit::event::CreateAuditLog;
+use registry::AppRegistry;
+use serde::Serialize;
+use shared::error::{AppError, AppResult};
+
+use crate::{
+    extractor::{AuthorizedUser, RequestMetadata},
+    model::audit::{AuditLogQuery, PaginatedAuditLogResponse},
+};
+
+/// 監査ログにリクエストの付帯情報を加える
+/// 変更を伴う操作では、これをリポジトリに渡して変更と同じトランザクションで記録する
+pub(crate) fn with_metadata(metadata: &RequestMetadata, event: CreateAuditLog) -> CreateAuditLog {
+    CreateAuditLog {
+        request_id: metadata.request_id.clone(),
+        ip_address: metadata.ip_address.clone(),
+        ..event
+    }
+}
+
+/// データベースの変更を伴わない操作(ログインなど)の監査ログを記録する
+pub(crate) async fn record(
+    registry: &AppRegistry,
+    metadata: &RequestMetadata,
+    event: CreateAuditLog,
+) -> AppResult<()> {
+    registry
+        .audit_log_repository()
+        .create(with_metadata(metadata, event))
+        .await
+}
+
+/// 監査ログに記録する、操作の対象の内容
+pub(crate) fn snapshot(value: impl Serialize) -> Option<serde_json::Value> {
+    serde_json::to_value(value).ok()
+}
+
+/// 監査ログを新しい順に取得する(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/audit-logs",
+        responses (
+            (status = 200, description = "監査ログ取得成功", body = PaginatedAuditLogResponse),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        params(
+            ("actorId" = Option<UserId>, Query, description = "操作を行ったユーザーのID"),
+            ("targetType" = Option<AuditTargetTypeName>, Query, description = "操作の対象の種類"),
+            ("targetId" = Option<String>, Query, description = "操作の対象のID"),
+            ("from" = Option<String>, Query, description = "この日時以降に記録されたもの(RFC 3339)"),
+            ("to" = Option<String>, Query, description = "この日時より前に記録されたもの(RFC 3339)"),
+            ("limit" = i64, Query, description = "取得件数(1〜100、既定値は50)"),
+            ("offset" = i64, Query, description = "取得開始位置"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn list_audit_logs(
+    user: AuthorizedUser,
+    Query(query): Query<AuditLogQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<PaginatedAuditLogResponse>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+    query.validate(&())?;
+
+    registry
+        .audit_log_repository()
+        .find_all(query.into())
+        .await
+        .map(PaginatedAuditLogResponse::from)
+        .map(Json)
+}
@@ -1,11 +1,18 @@
 use axum::{extract::State, http::StatusCode, Json};
-use kernel::model::auth::event::CreateToken;
+use kernel::model::{
+    audit::{event::CreateAuditLog, AuditAction, AuditTargetType},
+    auth::event::CreateToken,
+    id::UserId,
+};
 use registry::AppRegistry;
-use shared::error::AppResult;
+use shared::error::{AppError, AppResult};
 
 use crate::{
-    extractor::AuthorizedUser,
-    model::auth::{AccessTokenResponse, LoginRequest},
+    extractor::{AuthorizedUser, Credential, RequestMetadata},
+    handler::audit,
+    model::auth::{
+        AccessTokenResponse, LoginRequest, OidcAuthorizationResponse, OidcCallbackRequest,
+    },
 };
 
 /// ログイン処理
@@ -18,17 +25,31 @@ use crate::{
         request_body = LoginRequest,
         responses (
             (status = 200, description = "ログイン成功", body = AccessTokenResponse),
+            (status = 401, description = "メールアドレスまたはパスワードが誤っている場合"),
         ),
     )
 )]
 pub async fn login(
+    metadata: RequestMetadata,
     State(registry): State<AppRegistry>,
     Json(req): Json<LoginRequest>,
 ) -> AppResult<Json<AccessTokenResponse>> {
-    let user_id = registry
+    let user_id = match registry
         .auth_repository()
         .verify_user(&req.email, &req.password)
-        .await?;
+        .await
+    {
+        Ok(user_id) => user_id,
+        Err(e) => {
+            if matches!(e, AppError::UnauthenticatedError) {
+                let after = serde_json::json!({ "method": "password", "email": req.email });
+                record_login_failed(&registry, &metadata, after).await;
+            }
+            return Err(e);
+        }
+    };
+    // 記録できない場合は、追跡できないログインとならないようトークンを発行しない
+    record_login(&registry, &metadata, user_id, "password").await?;
     let access_token = registry
         .auth_repository()
         .create_token(CreateToken::new(user_id))
@@ -39,6 +60,75 @@ pub async fn login(
         access_token: access_token.0,
     }))
 }
+/// OIDCによるシングルサインオンの開始
+/// 認可リクエストを作成し、IDプロバイダーの認可エンドポイントのURLを返す
+/// クライアントは返された`clientBinding`を保持し、コールバックの際に送り返す
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/auth/oidc/authorize",
+        responses (
+            (status = 200, description = "認可リクエストの作成成功", body = OidcAuthorizationResponse),
+            (status = 404, description = "OIDCが設定されていない場合",),
+        ),
+    )
+)]
+pub async fn oidc_authorize(
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<OidcAuthorizationResponse>> {
+    registry
+        .oidc_repository()
+        .authorize()
+        .await
+        .map(OidcAuthorizationResponse::from)
+        .map(Json)
+}
+
+/// OIDCによるシングルサインオンのコールバック処理
+/// IDトークンを検証してユーザーを作成(または紐付け)し、アクセストークンを発行する
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/auth/oidc/callback",
+        request_body = OidcCallbackRequest,
+        responses (
+            (status = 200, description = "ログイン成功", body = AccessTokenResponse),
+            (status = 401, description = "state・clientBinding・IDトークンの検証に失敗した場合、または無効化されたユーザーの場合",),
+            (status = 404, description = "OIDCが設定されていない場合",),
+        ),
+    )
+)]
+pub async fn oidc_callback(
+    metadata: RequestMetadata,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<OidcCallbackRequest>,
+) -> AppResult<Json<AccessTokenResponse>> {
+    let identity = match registry.oidc_repository().verify_callback(req.into()).await {
+        Ok(identity) => identity,
+        Err(e) => {
+            let after = serde_json::json!({ "method": "oidc" });
+            record_login_failed(&registry, &metadata, after).await;
+            return Err(e);
+        }
+    };
+    let user = registry
+        .user_repository()
+        .provision_external_user(identity.into())
+        .await?;
+    record_login(&registry, &metadata, user.id, "oidc").await?;
+    let access_token = registry
+        .auth_repository()
+        .create_token(CreateToken::new(user.id))
+        .await?;
+
+    Ok(Json(AccessTokenResponse {
+        user_id: user.id,
+        access_token: access_token.0,
+    }))
+}
+
 /// ログアウト処理
 /// ユーザーのアクセストークンを削除する
 #[cfg_attr(
@@ -48,16 +138,80 @@ pub async fn login(
         path = "/api/auth/logout",
         responses (
             (status = 204, description = "ログアウト成功",),
+            (status = 422, description = "APIキーで認証されている場合",),
         ),
     )
 )]
 pub async fn logout(
     user: AuthorizedUser,
+    metadata: RequestMetadata,
     State(registry): State<AppRegistry>,
 ) -> AppResult<StatusCode> {
+    let Credential::AccessToken(access_token) = user.credential else {
+        return Err(AppError::UnprocessableEntity(
+            "APIキーによる認証ではログアウトできません".into(),
+        ));
+    };
     registry
         .auth_repository()
-        .delete_token(user.access_token)
+        .delete_token(access_token)
         .await?;
+
+    audit::record(
+        &registry,
+        &metadata,
+        CreateAuditLog::new(
+            Some(user.user.id),
+            AuditAction::Logout,
+            AuditTargetType::User,
+            Some(user.user.id.to_string()),
+        ),
+    )
+    .await?;
+
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// ログインの成功を監査ログに記録する
+async fn record_login(
+    registry: &AppRegistry,
+    metadata: &RequestMetadata,
+    user_id: UserId,
+    method: &str,
+) -> AppResult<()> {
+    audit::record(
+        registry,
+        metadata,
+        CreateAuditLog {
+            after: Some(serde_json::json!({ "method": method })),
+            ..CreateAuditLog::new(
+                Some(user_id),
+                AuditAction::Login,
+                AuditTargetType::User,
+                Some(user_id.to_string()),
+            )
+        },
+    )
+    .await
+}
+
+/// ログインの失敗を監査ログに記録する
+/// 操作を行ったユーザーは特定できないため、試行された内容のみを記録する
+/// 記録に失敗しても認証エラーの応答を妨げないよう、エラーはログに出力するのみとする
+async fn record_login_failed(
+    registry: &AppRegistry,
+    metadata: &RequestMetadata,
+    after: serde_json::Value,
+) {
+    let event = CreateAuditLog {
+        after: Some(after),
+        ..CreateAuditLog::new(None, AuditAction::LoginFailed, AuditTargetType::User, None)
+    };
+    if let Err(e) = audit::record(registry, metadata, event).await {
+        tracing::error!(
+            error.message = %e,
+            action = AuditAction::LoginFailed.as_ref(),
+            "failed to record audit log"
+        );
+    }
+}
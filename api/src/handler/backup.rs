@@ -0,0 +1,138 @@
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use kernel::model::{
+    audit::{event::CreateAuditLog, AuditAction, AuditTargetType},
+    backup::BackupArchive,
+};
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::{
+    extractor::{AuthorizedUser, RequestMetadata},
+    handler::audit,
+    model::backup::{BackupQuery, RestoreQuery, RestoreResponse},
+};
+
+/// ユーザー・ロール・蔵書・貸出(返却済みを含む)のデータ全体をバックアップする(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/backup",
+        responses (
+            (status = 200, description = "バックアップ取得成功", content_type = "application/json"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        params(
+            ("includePasswordHashes" = Option<bool>, Query, description = "パスワードのハッシュを含める(既定は含めない)"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn create_backup(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    Query(query): Query<BackupQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Response> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    let include_password_hashes = query.include_password_hashes;
+    let archive = registry.backup_repository().create(query.into()).await?;
+
+    // 記録できない場合は、誰が持ち出したか追えなくなるためバックアップを返さない
+    audit::record(
+        &registry,
+        &metadata,
+        CreateAuditLog {
+            after: Some(serde_json::json!({
+                "includePasswordHashes": include_password_hashes,
+            })),
+            ..CreateAuditLog::new(
+                Some(user.id()),
+                AuditAction::CreateBackup,
+                AuditTargetType::Backup,
+                None,
+            )
+        },
+    )
+    .await?;
+
+    let filename = format!(
+        "bookmanager-backup-{}.json",
+        archive.created_at.format("%Y%m%dT%H%M%SZ")
+    );
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!(r#"attachment; filename="{filename}""#),
+        )],
+        Json(archive),
+    )
+        .into_response())
+}
+
+/// バックアップを復元する(管理者のみ)
+///
+/// バックアップの形式・スキーマのバージョンが復元先と一致しない場合や、
+/// 内容に矛盾がある場合は、何も書き込まずに422を返す。
+/// 置き換える場合に、バックアップに含まれないAPIキー・料金・状態の履歴などが残っていると409を返す
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/backup/restore",
+        responses (
+            (status = 200, description = "復元成功", body = RestoreResponse),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 409, description = "置き換えると、バックアップに含まれないレコードが削除される"),
+            (status = 422, description = "バックアップの形式・スキーマのバージョンの不一致、または内容の矛盾"),
+        ),
+        params(
+            ("mode" = Option<RestoreModeName>, Query, description = "復元の方法(既定はmerge)"),
+        ),
+        request_body(content = String, content_type = "application/json", description = "GET /api/v1/backupで取得したバックアップ"),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn restore_backup(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    Query(query): Query<RestoreQuery>,
+    State(registry): State<AppRegistry>,
+    Json(archive): Json<BackupArchive>,
+) -> AppResult<Json<RestoreResponse>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog::new(
+            Some(user.id()),
+            AuditAction::RestoreBackup,
+            AuditTargetType::Backup,
+            None,
+        ),
+    );
+    let summary = registry
+        .backup_repository()
+        .restore(archive, query.mode.into(), Some(audit))
+        .await?;
+    let response = RestoreResponse::new(query.mode, summary);
+
+    Ok(Json(response))
+}
@@ -1,17 +1,25 @@
 use crate::{
-    extractor::AuthorizedUser,
+    extractor::{AuthorizedUser, RequestMetadata},
+    handler::audit,
+    import,
     model::book::{
-        BookListQuery, BookResponse, CreateBookRequest, PaginatedBookResponse, UpdateBookRequest,
-        UpdateBookRequestWithIds,
+        BookImportQuery, BookImportResponse, BookListQuery, BookResponse,
+        BookStatusHistoriesResponse, CreateBookRequest, PaginatedBookResponse, UpdateBookRequest,
+        UpdateBookRequestWithIds, UpdateBookStatusRequest, UpdateBookStatusRequestWithIds,
     },
 };
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
 use garde::Validate;
-use kernel::model::{book::event::DeleteBook, id::BookId};
+use kernel::model::{
+    audit::{event::CreateAuditLog, AuditAction, AuditTargetType},
+    book::event::DeleteBook,
+    id::BookId,
+};
 use registry::AppRegistry;
 use shared::error::{AppError, AppResult};
 
@@ -34,16 +42,106 @@ use shared::error::{AppError, AppResult};
 )]
 pub async fn register_book(
     user: AuthorizedUser,
+    metadata: RequestMetadata,
     State(registry): State<AppRegistry>,
     Json(req): Json<CreateBookRequest>,
 ) -> AppResult<StatusCode> {
     req.validate(&())?;
 
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog {
+            after: audit::snapshot(&req),
+            ..CreateAuditLog::new(
+                Some(user.id()),
+                AuditAction::CreateBook,
+                AuditTargetType::Book,
+                None,
+            )
+        },
+    );
     registry
         .book_repository()
-        .create(req.into(), user.id())
-        .await
-        .map(|_| StatusCode::CREATED)
+        .create(req.into(), user.id(), Some(audit))
+        .await?;
+
+    Ok(StatusCode::CREATED)
+}
+
+/// CSVによる蔵書の一括登録
+///
+/// 各行を蔵書登録と同じ規則で検証し、検証に通った行を1つのトランザクションで登録する。
+/// `dryRun=true`の場合は検証のみを行い、行ごとの誤りを返す
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/books/import",
+        responses (
+            (status = 200, description = "検証のみの場合の結果", body = BookImportResponse),
+            (status = 201, description = "一括登録成功", body = BookImportResponse),
+            (status = 400, description = "CSVの文字コード・見出しの不正"),
+            (status = 401, description = "認証エラー"),
+        ),
+        params(
+            ("dryRun" = Option<bool>, Query, description = "検証のみを行い、登録しない"),
+            ("encoding" = Option<CsvEncoding>, Query, description = "CSVの文字コード(既定値はutf-8)"),
+            ("titleColumn" = Option<String>, Query, description = "書名の列の見出し(既定値はtitle)"),
+            ("authorColumn" = Option<String>, Query, description = "著者の列の見出し(既定値はauthor)"),
+            ("isbnColumn" = Option<String>, Query, description = "ISBNの列の見出し(既定値はisbn)"),
+            ("descriptionColumn" = Option<String>, Query, description = "説明の列の見出し(既定値はdescription)"),
+        ),
+        request_body(content = String, content_type = "text/csv"),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn import_books(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    Query(query): Query<BookImportQuery>,
+    State(registry): State<AppRegistry>,
+    body: Bytes,
+) -> AppResult<(StatusCode, Json<BookImportResponse>)> {
+    query.validate(&())?;
+
+    let text = import::decode(&body, query.encoding)?;
+    let rows = import::parse_rows(&text, &query.column_mapping())?;
+    if query.dry_run {
+        return Ok((
+            StatusCode::OK,
+            Json(BookImportResponse::new(true, &rows, vec![])),
+        ));
+    }
+
+    let books = rows
+        .iter()
+        .filter_map(|row| row.result.as_ref().ok())
+        .map(|req| req.clone().into())
+        .collect::<Vec<_>>();
+    let book_ids = if books.is_empty() {
+        vec![]
+    } else {
+        let audit = audit::with_metadata(
+            &metadata,
+            CreateAuditLog::new(
+                Some(user.id()),
+                AuditAction::ImportBooks,
+                AuditTargetType::Book,
+                None,
+            ),
+        );
+        registry
+            .book_repository()
+            .create_many(books, user.id(), Some(audit))
+            .await?
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        Json(BookImportResponse::new(false, &rows, book_ids)),
+    ))
 }
 
 /// 蔵書一覧取得
@@ -146,19 +244,34 @@ pub async fn show_book(
 )]
 pub async fn update_book(
     user: AuthorizedUser,
+    metadata: RequestMetadata,
     Path(book_id): Path<BookId>,
     State(registry): State<AppRegistry>,
     Json(req): Json<UpdateBookRequest>,
 ) -> AppResult<StatusCode> {
     req.validate(&())?;
 
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog {
+            before: find_book_snapshot(&registry, book_id).await?,
+            after: audit::snapshot(&req),
+            ..CreateAuditLog::new(
+                Some(user.id()),
+                AuditAction::UpdateBook,
+                AuditTargetType::Book,
+                Some(book_id.to_string()),
+            )
+        },
+    );
     let update_book = UpdateBookRequestWithIds::new(book_id, user.id(), req);
 
     registry
         .book_repository()
-        .update(update_book.into())
-        .await
-        .map(|_| StatusCode::OK)
+        .update(update_book.into(), Some(audit))
+        .await?;
+
+    Ok(StatusCode::OK)
 }
 
 /// 蔵書削除
@@ -182,9 +295,22 @@ pub async fn update_book(
 )]
 pub async fn delete_book(
     user: AuthorizedUser,
+    metadata: RequestMetadata,
     Path(book_id): Path<BookId>,
     State(registry): State<AppRegistry>,
 ) -> AppResult<StatusCode> {
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog {
+            before: find_book_snapshot(&registry, book_id).await?,
+            ..CreateAuditLog::new(
+                Some(user.id()),
+                AuditAction::DeleteBook,
+                AuditTargetType::Book,
+                Some(book_id.to_string()),
+            )
+        },
+    );
     let delete_book = DeleteBook {
         book_id,
         requested_user: user.id(),
@@ -192,7 +318,108 @@ pub async fn delete_book(
 
     registry
         .book_repository()
-        .delete(delete_book)
+        .delete(delete_book, Some(audit))
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 蔵書の状態変更(管理者のみ)
+/// 貸出中への変更、貸出中からの変更は貸出・返却・紛失の届け出で行う
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/books/{book_id}/status",
+        responses (
+            (status = 200, description = "蔵書の状態変更成功"),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "蔵書が見つからない場合"),
+            (status = 422, description = "許可されていない状態の遷移の場合"),
+        ),
+        params(
+            ("book_id" = BookId, Path, description = "変更対象の蔵書ID"),
+        ),
+        request_body = UpdateBookStatusRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn update_book_status(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<UpdateBookStatusRequest>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    req.validate(&())?;
+
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog {
+            before: find_book_snapshot(&registry, book_id).await?,
+            after: audit::snapshot(&req),
+            ..CreateAuditLog::new(
+                Some(user.id()),
+                AuditAction::ChangeBookStatus,
+                AuditTargetType::Book,
+                Some(book_id.to_string()),
+            )
+        },
+    );
+    let update_status = UpdateBookStatusRequestWithIds::new(book_id, user.id(), req);
+
+    registry
+        .book_repository()
+        .update_status(update_status.into(), Some(audit))
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+/// 監査ログに記録する、操作前の蔵書の内容を取得する
+async fn find_book_snapshot(
+    registry: &AppRegistry,
+    book_id: BookId,
+) -> AppResult<Option<serde_json::Value>> {
+    let book = registry.book_repository().find_by_id(book_id).await?;
+    Ok(book.map(BookResponse::from).and_then(audit::snapshot))
+}
+
+/// 蔵書の状態の変更履歴を表示
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/{book_id}/status-history",
+        responses (
+            (status = 200, description = "変更履歴取得成功", body = BookStatusHistoriesResponse),
+            (status = 401, description = "認証エラー"),
+        ),
+        params(
+            ("book_id" = BookId, Path, description = "履歴を取得する蔵書ID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn show_book_status_history(
+    _user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<BookStatusHistoriesResponse>> {
+    registry
+        .book_repository()
+        .find_status_history(book_id)
         .await
-        .map(|_| StatusCode::NO_CONTENT)
+        .map(BookStatusHistoriesResponse::from)
+        .map(Json)
 }
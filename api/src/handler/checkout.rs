@@ -1,12 +1,20 @@
-use crate::{extractor::AuthorizedUser, model::checkout::CheckoutsResponse};
+use crate::{
+    extractor::{AuthorizedUser, RequestMetadata},
+    handler::audit,
+    model::checkout::{BatchCheckoutRequest, BatchCheckoutResponse, CheckoutsResponse},
+};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     Json,
 };
+use garde::Validate;
 use kernel::model::{
-    checkout::event::{CreateCheckout, UpdateReturned},
-    id::{BookId, CheckoutId},
+    audit::{event::CreateAuditLog, AuditAction, AuditTargetType},
+    checkout::event::{
+        CreateCheckout, CreateCheckouts, DeclareLost, RenewCheckout, ReturnBooks, UpdateReturned,
+    },
+    id::{BookId, CheckoutId, UserId},
 };
 use registry::AppRegistry;
 use shared::error::AppResult;
@@ -22,7 +30,7 @@ use shared::error::AppResult;
             (status = 400, description = "リクエストパラメータ不正"),
             (status = 401, description = "認証エラー"),
             (status = 404, description = "指定された書籍が見つからない場合"),
-            (status = 422, description = "既に貸出中の場合"),
+            (status = 422, description = "既に貸出中または貸出できない状態の場合、あるいは貸出ポリシーの上限に達している場合"),
         ),
         params(
             ("book_id" = BookId, Path, description = "貸出する書籍のID"),
@@ -34,6 +42,7 @@ use shared::error::AppResult;
 )]
 pub async fn checkout_book(
     user: AuthorizedUser,
+    metadata: RequestMetadata,
     Path(book_id): Path<BookId>,
     State(registry): State<AppRegistry>,
 ) -> AppResult<StatusCode> {
@@ -41,9 +50,13 @@ pub async fn checkout_book(
 
     registry
         .check_out_repository()
-        .create_checkout(create_checkout_history)
-        .await
-        .map(|_| StatusCode::CREATED)
+        .create_checkout(
+            create_checkout_history,
+            audit_log(&metadata, user.id(), AuditAction::Checkout),
+        )
+        .await?;
+
+    Ok(StatusCode::CREATED)
 }
 
 /// 書籍返却
@@ -68,6 +81,7 @@ pub async fn checkout_book(
 )]
 pub async fn return_book(
     user: AuthorizedUser,
+    metadata: RequestMetadata,
     Path((book_id, checkout_id)): Path<(BookId, CheckoutId)>,
     State(registry): State<AppRegistry>,
 ) -> AppResult<StatusCode> {
@@ -75,9 +89,170 @@ pub async fn return_book(
 
     registry
         .check_out_repository()
-        .update_returned(update_returned)
-        .await
-        .map(|_| StatusCode::OK)
+        .update_returned(
+            update_returned,
+            audit_log(&metadata, user.id(), AuditAction::Return),
+        )
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+/// 複数の書籍の一括貸出
+/// 書籍ごとの結果を返す。`atomic`が`true`の場合、1冊でも失敗したらすべての貸出を取り消す
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/checkouts/batch",
+        responses (
+            (status = 200, description = "一括貸出の処理完了(書籍ごとの成否は結果を参照)", body = BatchCheckoutResponse),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+        ),
+        request_body = BatchCheckoutRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn checkout_books(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<BatchCheckoutRequest>,
+) -> AppResult<Json<BatchCheckoutResponse>> {
+    req.validate(&())?;
+
+    let mode = req.mode();
+    let results = registry
+        .check_out_repository()
+        .create_checkouts(
+            CreateCheckouts::new(req.book_ids, user.id(), chrono::Utc::now(), mode),
+            audit_log(&metadata, user.id(), AuditAction::Checkout),
+        )
+        .await?;
+
+    Ok(Json(BatchCheckoutResponse::from(results)))
+}
+
+/// 複数の書籍の一括返却
+/// 書籍ごとに、自身が借りている貸出を返却する。`atomic`が`true`の場合、1冊でも失敗したらすべての返却を取り消す
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/returns/batch",
+        responses (
+            (status = 200, description = "一括返却の処理完了(書籍ごとの成否は結果を参照)", body = BatchCheckoutResponse),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+        ),
+        request_body = BatchCheckoutRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn return_books(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<BatchCheckoutRequest>,
+) -> AppResult<Json<BatchCheckoutResponse>> {
+    req.validate(&())?;
+
+    let mode = req.mode();
+    let results = registry
+        .check_out_repository()
+        .return_books(
+            ReturnBooks::new(req.book_ids, user.id(), chrono::Utc::now(), mode),
+            audit_log(&metadata, user.id(), AuditAction::Return),
+        )
+        .await?;
+
+    Ok(Json(BatchCheckoutResponse::from(results)))
+}
+
+/// 貸出延長
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/books/{book_id}/checkouts/{checkout_id}/renewed",
+        responses (
+            (status = 200, description = "延長処理成功"),
+            (status = 401, description = "認証エラー"),
+            (status = 404, description = "指定された貸出が見つからない場合"),
+            (status = 422, description = "返却期限を過ぎている場合、または延長回数の上限に達している場合"),
+        ),
+        params(
+            ("book_id" = BookId, Path, description = "延長する書籍のID"),
+            ("checkout_id" = CheckoutId, Path, description = "貸出履歴ID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn renew_checkout(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    Path((book_id, checkout_id)): Path<(BookId, CheckoutId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let renew_checkout = RenewCheckout::new(checkout_id, book_id, user.id(), chrono::Utc::now());
+
+    registry
+        .check_out_repository()
+        .renew(
+            renew_checkout,
+            audit_log(&metadata, user.id(), AuditAction::Renew),
+        )
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+/// 借りている書籍の紛失を届け出る
+/// 貸出は終了し、書籍は紛失状態になる
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/books/{book_id}/checkouts/{checkout_id}/lost",
+        responses (
+            (status = 200, description = "紛失の届け出成功"),
+            (status = 401, description = "認証エラー"),
+            (status = 404, description = "指定された書籍が見つからない場合"),
+            (status = 422, description = "自身が借りている貸出でない場合"),
+        ),
+        params(
+            ("book_id" = BookId, Path, description = "紛失した書籍のID"),
+            ("checkout_id" = CheckoutId, Path, description = "貸出履歴ID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn declare_lost(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    Path((book_id, checkout_id)): Path<(BookId, CheckoutId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let declare_lost = DeclareLost::new(checkout_id, book_id, user.id(), chrono::Utc::now());
+
+    registry
+        .check_out_repository()
+        .declare_lost(
+            declare_lost,
+            audit_log(&metadata, user.id(), AuditAction::DeclareLost),
+        )
+        .await?;
+
+    Ok(StatusCode::OK)
 }
 
 /// 全ての貸出中書籍一覧を表示
@@ -138,3 +313,16 @@ pub async fn checkout_history_by_book(
         .map(CheckoutsResponse::from)
         .map(Json)
 }
+
+/// 貸出の操作の監査ログ
+/// 対象の貸出と蔵書は、リポジトリが貸出ごとに記録する
+pub(crate) fn audit_log(
+    metadata: &RequestMetadata,
+    user_id: UserId,
+    action: AuditAction,
+) -> Option<CreateAuditLog> {
+    Some(audit::with_metadata(
+        metadata,
+        CreateAuditLog::new(Some(user_id), action, AuditTargetType::Checkout, None),
+    ))
+}
@@ -0,0 +1,267 @@
+use std::{collections::HashSet, time::Duration};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::HeaderMap,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
+};
+use kernel::model::{
+    id::BookId,
+    live::{LiveEvent, LiveEventId, LiveEventKind},
+};
+use registry::AppRegistry;
+use shared::error::AppResult;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+
+use crate::{
+    extractor::{AuthorizedUser, WebSocketUser},
+    model::event::{BookAvailabilityResponse, BookWatchMessage, BookWatchRequest},
+};
+
+/// 接続し直したクライアントが、最後に受け取ったイベントのIDを送るHTTPヘッダー名
+pub const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+/// 蔵書の購読の接続で応答するサブプロトコル
+/// ブラウザからはアクセストークンとともに`Sec-WebSocket-Protocol: book-watch, bearer.<トークン>`として送る
+pub const BOOK_WATCH_PROTOCOL: &str = "book-watch";
+/// 蔵書の購読の接続で、クライアントから受け取るメッセージの上限サイズ
+const MAX_WATCH_MESSAGE_SIZE: usize = 4 * 1024;
+/// 1つの接続で購読できる蔵書の上限
+const MAX_WATCHED_BOOKS: usize = 100;
+/// クライアントへのメッセージの送信を待つ上限
+/// 受け取りが滞っているクライアントの接続は切断する
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 蔵書と貸出の変更をServer-Sent Eventsで配信する
+///
+/// イベントの種類(`book.updated`など)をイベント名とし、内容をJSONで送る。
+/// `Last-Event-ID`ヘッダーを送ると、そのイベントより後に配信され、まだ保持しているイベントから再送する。
+/// 受け取りが追いつかない場合は接続を切断するため、クライアントは接続し直して再送を受ける
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/events/stream",
+        responses (
+            (status = 200, description = "イベントの配信開始", content_type = "text/event-stream"),
+            (status = 401, description = "認証エラー"),
+        ),
+        params(
+            ("Last-Event-ID" = Option<String>, Header, description = "最後に受け取ったイベントのID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn stream_events(
+    _user: AuthorizedUser,
+    headers: HeaderMap,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, axum::Error>>>> {
+    let bus = registry.live_event_bus();
+    // 再送するイベントを取得する間に配信されたイベントも受け取れるよう、先に購読する
+    let receiver = bus.subscribe();
+
+    let last_event_id = headers
+        .get(LAST_EVENT_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| LiveEventId(v.trim().to_string()));
+    let missed = match &last_event_id {
+        Some(id) => bus.find_since(id).await?,
+        None => vec![],
+    };
+    // 再送したイベントと重複して届いたイベントは送らない
+    // 配信の順序が前後したイベントを取りこぼさないよう、再送したもののみを除く
+    let replayed: HashSet<LiveEventId> = missed.iter().map(|e| e.id.clone()).collect();
+
+    let live = BroadcastStream::new(receiver)
+        .map_while(Result::ok)
+        .filter(move |event| !replayed.contains(&event.id));
+    let stream = tokio_stream::iter(missed)
+        .chain(live)
+        .map(|event| to_sse_event(&event));
+
+    Ok(Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(registry.live_event_config().heartbeat_interval)
+            .text("heartbeat"),
+    ))
+}
+
+fn to_sse_event(event: &LiveEvent) -> Result<Event, axum::Error> {
+    Event::default()
+        .id(event.id.to_string())
+        .event(event.kind.name())
+        .json_data(event)
+}
+
+/// 指定した蔵書の貸出状況の変化をWebSocketで配信する
+///
+/// 接続後、`{"type": "subscribe", "bookId": "..."}`を送ると、その時点の貸出状況を返し、
+/// 以降は変化するたびに`availability`メッセージを送る。
+/// 受け取りが追いつかずイベントを取りこぼした場合は、購読中の蔵書の貸出状況を改めて送る。
+/// Authorizationヘッダーを送れないブラウザは、アクセストークンをサブプロトコルで送る
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/events/books",
+        responses (
+            (status = 101, description = "WebSocketの接続開始"),
+            (status = 401, description = "認証エラー"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn watch_books(
+    _user: WebSocketUser,
+    ws: WebSocketUpgrade,
+    State(registry): State<AppRegistry>,
+) -> Response {
+    ws.max_message_size(MAX_WATCH_MESSAGE_SIZE)
+        .protocols([BOOK_WATCH_PROTOCOL])
+        .on_upgrade(move |socket| run_book_watch(socket, registry))
+}
+
+async fn run_book_watch(mut socket: WebSocket, registry: AppRegistry) {
+    let mut events = registry.live_event_bus().subscribe();
+    let period = registry.live_event_config().heartbeat_interval;
+    let mut heartbeat = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    let mut watch = BookWatch::new(registry);
+
+    loop {
+        let outgoing = tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    let messages = match serde_json::from_str::<BookWatchRequest>(&text) {
+                        Ok(request) => watch.handle_request(request).await,
+                        Err(e) => vec![BookWatchMessage::error(None, format!("invalid message: {e}"))],
+                    };
+                    to_messages(&messages)
+                }
+                Some(Ok(Message::Binary(_))) => {
+                    to_messages(&[BookWatchMessage::error(None, "binary messages are not supported")])
+                }
+                // Pingへの応答は送信時に自動で行われる
+                Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+            },
+            event = events.recv() => match event {
+                Ok(event) => to_messages(watch.handle_event(&event).as_slice()),
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "Book watcher lagged behind live events");
+                    to_messages(&watch.resync().await)
+                }
+                Err(RecvError::Closed) => break,
+            },
+            // 何も送らない間も接続が切れないよう、定期的にPingを送る
+            _ = heartbeat.tick() => vec![Message::Ping(vec![])],
+        };
+
+        for message in outgoing {
+            match tokio::time::timeout(SEND_TIMEOUT, socket.send(message)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) => return,
+                Err(_) => {
+                    tracing::warn!("Closing book watcher that stopped receiving messages");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn to_messages(messages: &[BookWatchMessage]) -> Vec<Message> {
+    messages
+        .iter()
+        .filter_map(|message| serde_json::to_string(message).ok())
+        .map(Message::Text)
+        .collect()
+}
+
+/// 1つの接続で購読している蔵書と、メッセージへの応答
+struct BookWatch {
+    registry: AppRegistry,
+    books: HashSet<BookId>,
+}
+
+impl BookWatch {
+    fn new(registry: AppRegistry) -> Self {
+        Self {
+            registry,
+            books: HashSet::new(),
+        }
+    }
+
+    /// クライアントから受け取ったメッセージを処理し、応答するメッセージを返す
+    async fn handle_request(&mut self, request: BookWatchRequest) -> Vec<BookWatchMessage> {
+        let message = match request {
+            BookWatchRequest::Subscribe { book_id } => self.subscribe(book_id).await,
+            BookWatchRequest::Unsubscribe { book_id } => {
+                self.books.remove(&book_id);
+                BookWatchMessage::Unsubscribed { book_id }
+            }
+            BookWatchRequest::Ping => BookWatchMessage::Pong,
+        };
+        vec![message]
+    }
+
+    async fn subscribe(&mut self, book_id: BookId) -> BookWatchMessage {
+        if !self.books.contains(&book_id) && self.books.len() >= MAX_WATCHED_BOOKS {
+            return BookWatchMessage::error(
+                Some(book_id),
+                format!("cannot subscribe to more than {MAX_WATCHED_BOOKS} books"),
+            );
+        }
+        match self.registry.book_repository().find_by_id(book_id).await {
+            Ok(Some(book)) => {
+                self.books.insert(book_id);
+                BookWatchMessage::Subscribed((&book).into())
+            }
+            Ok(None) => BookWatchMessage::error(Some(book_id), "book not found"),
+            Err(e) => {
+                tracing::error!(error.message = %e, "Failed to find book to watch");
+                BookWatchMessage::error(Some(book_id), "failed to find book")
+            }
+        }
+    }
+
+    /// 配信されたイベントのうち、購読中の蔵書の貸出状況の変化をメッセージにする
+    fn handle_event(&mut self, event: &LiveEvent) -> Option<BookWatchMessage> {
+        let book_id = event.kind.book_id();
+        if !self.books.contains(&book_id) {
+            return None;
+        }
+        if let LiveEventKind::BookDeleted { .. } = event.kind {
+            self.books.remove(&book_id);
+            return Some(BookWatchMessage::Deleted { book_id });
+        }
+        BookAvailabilityResponse::from_live_event(&event.kind).map(BookWatchMessage::Availability)
+    }
+
+    /// 購読中の蔵書の、現在の貸出状況を改めて取得する
+    async fn resync(&mut self) -> Vec<BookWatchMessage> {
+        let book_repository = self.registry.book_repository();
+        let mut messages = Vec::with_capacity(self.books.len());
+        for book_id in self.books.clone() {
+            match book_repository.find_by_id(book_id).await {
+                Ok(Some(book)) => messages.push(BookWatchMessage::Availability((&book).into())),
+                Ok(None) => {
+                    self.books.remove(&book_id);
+                    messages.push(BookWatchMessage::Deleted { book_id });
+                }
+                Err(e) => tracing::error!(error.message = %e, "Failed to find watched book"),
+            }
+        }
+        messages
+    }
+}
@@ -0,0 +1,111 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+use tokio_stream::{Stream, StreamExt};
+
+use crate::{
+    export,
+    extractor::AuthorizedUser,
+    model::export::{ExportFormat, ExportQuery},
+};
+
+/// 蔵書を、所有者・現在の貸出とともにエクスポートする(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/exports/books",
+        responses (
+            (status = 200, description = "エクスポート成功", content_type = ["text/csv", "application/x-ndjson", "application/marc", "application/marcxml+xml"]),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        params(
+            ("format" = Option<ExportFormat>, Query, description = "出力形式(既定はcsv)"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn export_books(
+    user: AuthorizedUser,
+    Query(query): Query<ExportQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Response> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    let records = registry.export_repository().stream_books();
+    Ok(attachment(
+        "books",
+        query.format,
+        export::books(query.format, records),
+    ))
+}
+
+/// 返却済みのものを含む貸出履歴をエクスポートする(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/exports/checkouts",
+        responses (
+            (status = 200, description = "エクスポート成功", content_type = ["text/csv", "application/x-ndjson"]),
+            (status = 400, description = "リクエストパラメータ不正、またはMARC形式を指定した場合"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        params(
+            ("format" = Option<ExportFormat>, Query, description = "出力形式(csvまたはndjson、既定はcsv)"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn export_checkouts(
+    user: AuthorizedUser,
+    Query(query): Query<ExportQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Response> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    let stream = export::checkouts(query.format, move || {
+        registry.export_repository().stream_checkouts()
+    })?;
+    Ok(attachment("checkouts", query.format, stream))
+}
+
+/// ストリームをダウンロードさせるレスポンスにする
+///
+/// 送信を始めた後に発生したエラーはステータスコードで伝えられないため、ログに出力して接続を打ち切る
+fn attachment(
+    name: &str,
+    format: ExportFormat,
+    stream: impl Stream<Item = AppResult<Bytes>> + Send + 'static,
+) -> Response {
+    let stream = stream.map(|chunk| {
+        chunk.inspect_err(|e| tracing::error!(error.message = %e, "failed to export records"))
+    });
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(r#"attachment; filename="{name}.{}""#, format.extension()),
+            ),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response()
+}
@@ -0,0 +1,250 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use garde::Validate;
+use kernel::model::{
+    fee::event::{RecordFeePayment, UpdateFeeSchedule, WaiveFee},
+    id::UserId,
+};
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::{
+    extractor::AuthorizedUser,
+    model::fee::{FeeCreditRequest, FeeLedgerResponse, FeeScheduleRequest},
+};
+
+/// 料金表を取得する(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/fee-schedule",
+        responses (
+            (status = 200, description = "料金表取得成功", body = FeeScheduleRequest),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn get_fee_schedule(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<FeeScheduleRequest>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    registry
+        .fee_repository()
+        .find_schedule()
+        .await
+        .map(FeeScheduleRequest::from)
+        .map(Json)
+}
+
+/// 料金表を更新する(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/fee-schedule",
+        responses (
+            (status = 200, description = "料金表更新成功"),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        request_body = FeeScheduleRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn update_fee_schedule(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<FeeScheduleRequest>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    req.validate(&())?;
+
+    registry
+        .fee_repository()
+        .update_schedule(UpdateFeeSchedule {
+            schedule: req.into(),
+        })
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+/// 自身の料金の残高と台帳を取得する
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/users/me/fees",
+        responses (
+            (status = 200, description = "自身の台帳取得成功", body = FeeLedgerResponse),
+            (status = 401, description = "認証エラー"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn get_my_fees(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<FeeLedgerResponse>> {
+    registry
+        .fee_repository()
+        .find_ledger_by_user_id(user.id())
+        .await?
+        .map(FeeLedgerResponse::from)
+        .map(Json)
+        .ok_or_else(|| AppError::NotFoundError("Specified user not found".into()))
+}
+
+/// ユーザーの料金の残高と台帳を取得する(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/users/{user_id}/fees",
+        responses (
+            (status = 200, description = "台帳取得成功", body = FeeLedgerResponse),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定されたユーザーが見つからない場合"),
+        ),
+        params(
+            ("user_id" = UserId, Path, description = "取得対象のユーザーID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn get_user_fees(
+    user: AuthorizedUser,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<FeeLedgerResponse>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    registry
+        .fee_repository()
+        .find_ledger_by_user_id(user_id)
+        .await?
+        .map(FeeLedgerResponse::from)
+        .map(Json)
+        .ok_or_else(|| AppError::NotFoundError("Specified user not found".into()))
+}
+
+/// ユーザーの料金の支払いを記録する(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/users/{user_id}/fees/payments",
+        responses (
+            (status = 201, description = "支払いの記録成功"),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定されたユーザーが見つからない場合"),
+            (status = 422, description = "未払いの残高を超える金額の場合"),
+        ),
+        params(
+            ("user_id" = UserId, Path, description = "支払ったユーザーID"),
+        ),
+        request_body = FeeCreditRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn record_fee_payment(
+    user: AuthorizedUser,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<FeeCreditRequest>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    req.validate(&())?;
+
+    registry
+        .fee_repository()
+        .record_payment(RecordFeePayment {
+            user_id,
+            amount: req.amount,
+            note: req.note,
+            recorded_by: user.id(),
+        })
+        .await?;
+
+    Ok(StatusCode::CREATED)
+}
+
+/// ユーザーの料金を免除する(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/users/{user_id}/fees/waivers",
+        responses (
+            (status = 201, description = "免除の記録成功"),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定されたユーザーが見つからない場合"),
+            (status = 422, description = "未払いの残高を超える金額の場合"),
+        ),
+        params(
+            ("user_id" = UserId, Path, description = "免除するユーザーID"),
+        ),
+        request_body = FeeCreditRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn waive_fee(
+    user: AuthorizedUser,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<FeeCreditRequest>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    req.validate(&())?;
+
+    registry
+        .fee_repository()
+        .waive(WaiveFee {
+            user_id,
+            amount: req.amount,
+            note: req.note,
+            recorded_by: user.id(),
+        })
+        .await?;
+
+    Ok(StatusCode::CREATED)
+}
@@ -1,5 +1,8 @@
-use axum::{extract::State, http::StatusCode};
+use axum::{extract::State, http::StatusCode, Json};
 use registry::AppRegistry;
+use shared::error::AppResult;
+
+use crate::model::health::MigrationStatusResponse;
 
 #[cfg_attr(
     debug_assertions,
@@ -37,3 +40,36 @@ pub async fn handler_health_check_db(State(registry): State<AppRegistry>) -> Sta
         StatusCode::INTERNAL_SERVER_ERROR
     }
 }
+
+/// データベースのマイグレーションの適用状況を返す
+/// 未適用のマイグレーションがある場合は503を返す
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/health/migrations",
+        responses (
+            (status = 200, description = "すべてのマイグレーションが適用済み", body = MigrationStatusResponse),
+            (status = 503, description = "未適用のマイグレーションがある", body = MigrationStatusResponse),
+        ),
+    )
+)]
+pub async fn handler_health_check_migrations(
+    State(registry): State<AppRegistry>,
+) -> AppResult<(StatusCode, Json<MigrationStatusResponse>)> {
+    let pending = registry
+        .health_check_repository()
+        .pending_migrations()
+        .await?;
+    let response = MigrationStatusResponse::from(pending);
+    let status = if response.up_to_date {
+        StatusCode::OK
+    } else {
+        tracing::warn!(
+            pending = response.pending.len(),
+            "database schema is behind the application"
+        );
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    Ok((status, Json(response)))
+}
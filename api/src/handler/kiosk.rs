@@ -0,0 +1,239 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use garde::Validate;
+use kernel::model::{
+    audit::{event::CreateAuditLog, AuditAction},
+    checkout::event::{CreateCheckouts, ReturnBooks},
+    id::{KioskId, UserId},
+    kiosk::event::{CreateKiosk, RevokeKiosk},
+    user::User,
+};
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::{
+    extractor::{AuthorizedKiosk, AuthorizedUser, RequestMetadata},
+    handler::checkout,
+    model::kiosk::{
+        CreateKioskRequest, IssuedKioskResponse, KioskCheckoutRequest, KioskCheckoutResponse,
+        KiosksResponse,
+    },
+};
+
+/// キオスクを登録し、端末に設定する資格情報を発行する
+/// 発行された資格情報本体はこのレスポンスでのみ返却される
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/kiosks",
+        responses (
+            (status = 201, description = "キオスク登録成功", body = IssuedKioskResponse),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        request_body = CreateKioskRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn create_kiosk(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<CreateKioskRequest>,
+) -> AppResult<(StatusCode, Json<IssuedKioskResponse>)> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+    req.validate(&())?;
+
+    let issued = registry
+        .kiosk_repository()
+        .create(CreateKiosk::new(req.name, user.id()))
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(issued.into())))
+}
+
+/// 登録されたキオスクの一覧を取得する
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/kiosks",
+        responses (
+            (status = 200, description = "キオスク一覧取得成功", body = KiosksResponse),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn list_kiosks(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<KiosksResponse>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    registry
+        .kiosk_repository()
+        .find_all()
+        .await
+        .map(KiosksResponse::from)
+        .map(Json)
+}
+
+/// キオスクの資格情報を失効させる
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/kiosks/{kiosk_id}",
+        responses (
+            (status = 204, description = "キオスク失効成功"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定された有効なキオスクが見つからない場合"),
+        ),
+        params(
+            ("kiosk_id" = KioskId, Path, description = "失効させるキオスクのID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn revoke_kiosk(
+    user: AuthorizedUser,
+    Path(kiosk_id): Path<KioskId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    registry
+        .kiosk_repository()
+        .revoke(RevokeKiosk { kiosk_id })
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// バッジのコードから、貸出・返却を行うユーザーを特定する
+async fn find_badge_holder(registry: &AppRegistry, badge_code: &str) -> AppResult<User> {
+    registry
+        .user_repository()
+        .find_by_badge_code(badge_code)
+        .await?
+        .ok_or_else(|| AppError::NotFoundError("バッジに対応するユーザーが見つかりません".into()))
+}
+
+/// キオスクでの貸出・返却の監査ログ
+/// 操作したユーザーはバッジの持ち主とし、操作に用いたキオスクを操作後の内容に記録する
+fn audit_log(
+    metadata: &RequestMetadata,
+    user_id: UserId,
+    kiosk_id: KioskId,
+    action: AuditAction,
+) -> Option<CreateAuditLog> {
+    checkout::audit_log(metadata, user_id, action)
+        .map(|audit| audit.with_after_field("kioskId", serde_json::json!(kiosk_id)))
+}
+
+/// キオスクから、バッジで識別したユーザーに書籍を貸し出す
+/// `atomic`が`true`の場合、1冊でも失敗したらすべての貸出を取り消す
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/kiosk/checkouts",
+        responses (
+            (status = 200, description = "貸出の処理完了(書籍ごとの成否は結果を参照)", body = KioskCheckoutResponse),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "キオスクの資格情報が無効な場合"),
+            (status = 404, description = "バッジに対応するユーザーが見つからない場合"),
+            (status = 429, description = "キオスクからのリクエスト数が上限を超えた場合"),
+        ),
+        request_body = KioskCheckoutRequest,
+        security(
+            ("kiosk_key" = [])
+        )
+    )
+)]
+pub async fn kiosk_checkout(
+    AuthorizedKiosk { kiosk }: AuthorizedKiosk,
+    metadata: RequestMetadata,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<KioskCheckoutRequest>,
+) -> AppResult<Json<KioskCheckoutResponse>> {
+    req.validate(&())?;
+
+    let user = find_badge_holder(&registry, &req.badge_code).await?;
+    let mode = req.books.mode();
+    let results = registry
+        .check_out_repository()
+        .create_checkouts(
+            CreateCheckouts {
+                checked_out_via: Some(kiosk.id),
+                ..CreateCheckouts::new(req.books.book_ids, user.id, chrono::Utc::now(), mode)
+            },
+            audit_log(&metadata, user.id, kiosk.id, AuditAction::Checkout),
+        )
+        .await?;
+
+    Ok(Json(KioskCheckoutResponse::new(user, results)))
+}
+
+/// キオスクから、バッジで識別したユーザーが借りている書籍を返却する
+/// `atomic`が`true`の場合、1冊でも失敗したらすべての返却を取り消す
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/kiosk/returns",
+        responses (
+            (status = 200, description = "返却の処理完了(書籍ごとの成否は結果を参照)", body = KioskCheckoutResponse),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "キオスクの資格情報が無効な場合"),
+            (status = 404, description = "バッジに対応するユーザーが見つからない場合"),
+            (status = 429, description = "キオスクからのリクエスト数が上限を超えた場合"),
+        ),
+        request_body = KioskCheckoutRequest,
+        security(
+            ("kiosk_key" = [])
+        )
+    )
+)]
+pub async fn kiosk_return(
+    AuthorizedKiosk { kiosk }: AuthorizedKiosk,
+    metadata: RequestMetadata,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<KioskCheckoutRequest>,
+) -> AppResult<Json<KioskCheckoutResponse>> {
+    req.validate(&())?;
+
+    let user = find_badge_holder(&registry, &req.badge_code).await?;
+    let mode = req.books.mode();
+    let results = registry
+        .check_out_repository()
+        .return_books(
+            ReturnBooks {
+                returned_via: Some(kiosk.id),
+                ..ReturnBooks::new(req.books.book_ids, user.id, chrono::Utc::now(), mode)
+            },
+            audit_log(&metadata, user.id, kiosk.id, AuditAction::Return),
+        )
+        .await?;
+
+    Ok(Json(KioskCheckoutResponse::new(user, results)))
+}
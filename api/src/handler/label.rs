@@ -0,0 +1,156 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use garde::Validate;
+use kernel::model::id::BookId;
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::{
+    extractor::AuthorizedUser,
+    label::{parse_book_code, render_sheet, SheetLabel, Symbol},
+    model::{
+        book::BookResponse,
+        label::{BookLookupQuery, LabelFormat, LabelQuery, LabelSheetRequest},
+    },
+};
+
+const SVG_CONTENT_TYPE: &str = "image/svg+xml";
+const PNG_CONTENT_TYPE: &str = "image/png";
+
+/// 蔵書のラベル画像(QRコードまたはCode 128のバーコード)を取得する
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/{book_id}/label",
+        responses (
+            (status = 200, description = "ラベル画像取得成功", content_type = ["image/svg+xml", "image/png"]),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 404, description = "蔵書が見つからない場合"),
+        ),
+        params(
+            ("book_id" = BookId, Path, description = "蔵書ID"),
+            ("format" = Option<LabelFormat>, Query, description = "画像形式(既定はsvg)"),
+            ("symbology" = Option<SymbologyName>, Query, description = "符号化方式(既定はqr)"),
+            ("scale" = Option<u32>, Query, description = "1モジュールあたりのピクセル数(1〜32、既定は8)"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn show_book_label(
+    _user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    Query(query): Query<LabelQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Response> {
+    query.validate(&())?;
+
+    if registry
+        .book_repository()
+        .find_by_id(book_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::NotFoundError("The book_id book not found".into()));
+    }
+
+    let symbol = Symbol::for_book(book_id, query.symbology.into(), &registry.label_config())?;
+    let response = match query.format {
+        LabelFormat::Svg => (
+            [(header::CONTENT_TYPE, SVG_CONTENT_TYPE)],
+            symbol.to_svg(query.scale),
+        )
+            .into_response(),
+        LabelFormat::Png => (
+            [(header::CONTENT_TYPE, PNG_CONTENT_TYPE)],
+            symbol.to_png(query.scale)?,
+        )
+            .into_response(),
+    };
+    Ok(response)
+}
+
+/// 複数の蔵書のラベルを並べた、A4用紙に印刷するためのラベルシート(SVG)を作成する
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/books/labels",
+        responses (
+            (status = 200, description = "ラベルシート作成成功", content_type = "image/svg+xml"),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 404, description = "蔵書が見つからない場合"),
+        ),
+        request_body = LabelSheetRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn create_label_sheet(
+    _user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<LabelSheetRequest>,
+) -> AppResult<Response> {
+    req.validate(&())?;
+
+    let book_repository = registry.book_repository();
+    let mut labels = Vec::with_capacity(req.book_ids.len());
+    for book_id in req.book_ids {
+        let book = book_repository.find_by_id(book_id).await?.ok_or_else(|| {
+            AppError::NotFoundError(format!("指定された書籍({})が見つかりません", book_id))
+        })?;
+        labels.push(SheetLabel {
+            book_id,
+            title: book.title,
+            author: book.author,
+        });
+    }
+
+    let svg = render_sheet(&labels, req.symbology.into(), &registry.label_config())?;
+    Ok(([(header::CONTENT_TYPE, SVG_CONTENT_TYPE)], svg).into_response())
+}
+
+/// ラベルから読み取った文字列に対応する蔵書を取得する
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/lookup",
+        responses (
+            (status = 200, description = "蔵書取得成功", body = BookResponse),
+            (status = 400, description = "蔵書のラベルとして読み取れない場合"),
+            (status = 401, description = "認証エラー"),
+            (status = 404, description = "蔵書が見つからない場合"),
+        ),
+        params(
+            ("code" = String, Query, description = "ラベルから読み取った文字列(蔵書のID、またはディープリンクのURL)"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn lookup_book(
+    _user: AuthorizedUser,
+    Query(query): Query<BookLookupQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<BookResponse>> {
+    query.validate(&())?;
+
+    let book_id = parse_book_code(&query.code, &registry.label_config())?;
+    registry
+        .book_repository()
+        .find_by_id(book_id)
+        .await?
+        .map(|book| Json(book.into()))
+        .ok_or_else(|| AppError::NotFoundError("The book_id book not found".into()))
+}
@@ -0,0 +1,220 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use garde::Validate;
+use kernel::model::{
+    id::UserId,
+    loan_policy::event::{DeleteUserLoanPolicy, UpdateRoleLoanPolicy, UpdateUserLoanPolicy},
+};
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::{
+    extractor::AuthorizedUser,
+    model::{
+        loan_policy::{
+            LoanPolicyOverrideRequest, RoleLoanPoliciesResponse, UpdateLoanPolicyRequest,
+            UserLoanPolicyResponse,
+        },
+        user::RoleName,
+    },
+};
+
+/// ロールごとの貸出ポリシーの一覧を取得する(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/loan-policies",
+        responses (
+            (status = 200, description = "貸出ポリシー一覧取得成功", body = RoleLoanPoliciesResponse),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn list_loan_policies(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<RoleLoanPoliciesResponse>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    registry
+        .loan_policy_repository()
+        .find_all_role_policies()
+        .await
+        .map(RoleLoanPoliciesResponse::from)
+        .map(Json)
+}
+
+/// ロールの貸出ポリシーを更新する(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/loan-policies/{role}",
+        responses (
+            (status = 200, description = "貸出ポリシー更新成功"),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定されたロールが見つからない場合"),
+        ),
+        params(
+            ("role" = RoleName, Path, description = "更新対象のロール"),
+        ),
+        request_body = UpdateLoanPolicyRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn update_loan_policy(
+    user: AuthorizedUser,
+    Path(role): Path<RoleName>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<UpdateLoanPolicyRequest>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    req.validate(&())?;
+
+    registry
+        .loan_policy_repository()
+        .update_role_policy(UpdateRoleLoanPolicy {
+            role: role.into(),
+            policy: req.into(),
+        })
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+/// ユーザーに適用される貸出ポリシーを取得する(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/users/{user_id}/loan-policy",
+        responses (
+            (status = 200, description = "貸出ポリシー取得成功", body = UserLoanPolicyResponse),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定されたユーザーが見つからない場合"),
+        ),
+        params(
+            ("user_id" = UserId, Path, description = "取得対象のユーザーID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn get_user_loan_policy(
+    user: AuthorizedUser,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<UserLoanPolicyResponse>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    registry
+        .loan_policy_repository()
+        .find_by_user_id(user_id)
+        .await?
+        .map(UserLoanPolicyResponse::from)
+        .map(Json)
+        .ok_or_else(|| AppError::NotFoundError("Specified user not found".into()))
+}
+
+/// ユーザーごとの貸出ポリシーの上書きを登録・更新する(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/users/{user_id}/loan-policy",
+        responses (
+            (status = 200, description = "貸出ポリシー更新成功"),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定されたユーザーが見つからない場合"),
+        ),
+        params(
+            ("user_id" = UserId, Path, description = "更新対象のユーザーID"),
+        ),
+        request_body = LoanPolicyOverrideRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn update_user_loan_policy(
+    user: AuthorizedUser,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<LoanPolicyOverrideRequest>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    req.validate(&())?;
+
+    registry
+        .loan_policy_repository()
+        .update_user_policy(UpdateUserLoanPolicy {
+            user_id,
+            user_override: req.into(),
+        })
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+/// ユーザーごとの貸出ポリシーの上書きを削除する(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/users/{user_id}/loan-policy",
+        responses (
+            (status = 204, description = "貸出ポリシーの上書き削除成功"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定されたユーザーに上書き設定がない場合"),
+        ),
+        params(
+            ("user_id" = UserId, Path, description = "削除対象のユーザーID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn delete_user_loan_policy(
+    user: AuthorizedUser,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    registry
+        .loan_policy_repository()
+        .delete_user_policy(DeleteUserLoanPolicy { user_id })
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,5 +1,15 @@
+pub mod api_key;
+pub mod audit;
 pub mod auth;
+pub mod backup;
 pub mod book;
 pub mod checkout;
+pub mod event;
+pub mod export;
+pub mod fee;
 pub mod health;
+pub mod kiosk;
+pub mod label;
+pub mod loan_policy;
 pub mod user;
+pub mod webhook;
@@ -1,19 +1,27 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
+    response::{IntoResponse, Response},
     Json,
 };
 use garde::Validate;
-use kernel::model::{id::UserId, user::event::DeleteUser};
+use kernel::model::{
+    audit::{event::CreateAuditLog, AuditAction, AuditTargetType},
+    id::UserId,
+    user::event::{DeactivateUser, DeleteUser, ReactivateUser, UpdateUserBadge},
+};
 use registry::AppRegistry;
 use shared::error::{AppError, AppResult};
 
 use crate::{
-    extractor::AuthorizedUser,
-    model::checkout::CheckoutsResponse,
+    extractor::{AuthorizedUser, RequestMetadata},
+    handler::audit,
+    model::checkout::{CheckoutHistoryQuery, CheckoutsResponse, PaginatedCheckoutResponse},
     model::user::{
-        CreateUserRequest, UpdateUserPasswordRequest, UpdateUserPasswordRequestWithUserId,
-        UpdateUserRoleRequest, UpdateUserRoleRequestWithUserId, UserResponse, UsersResponse,
+        CreateUserRequest, ReassignBooksRequest, ReassignBooksRequestWithUserId,
+        UpdateUserBadgeRequest, UpdateUserBadgeRequestWithUserId, UpdateUserPasswordRequest,
+        UpdateUserPasswordRequestWithUserId, UpdateUserRoleRequest,
+        UpdateUserRoleRequestWithUserId, UserDeletionBlockersResponse, UserResponse, UsersResponse,
     },
 };
 
@@ -73,6 +81,7 @@ pub async fn list_users(State(registry): State<AppRegistry>) -> AppResult<Json<U
             (status = 200, description = "パスワード更新成功"),
             (status = 400, description = "リクエストパラメータ不正"),
             (status = 401, description = "認証エラー"),
+            (status = 403, description = "APIキーで認証されている場合"),
         ),
         request_body = UpdateUserPasswordRequest,
         security(
@@ -82,15 +91,33 @@ pub async fn list_users(State(registry): State<AppRegistry>) -> AppResult<Json<U
 )]
 pub async fn change_password(
     user: AuthorizedUser,
+    metadata: RequestMetadata,
     State(registry): State<AppRegistry>,
     Json(req): Json<UpdateUserPasswordRequest>,
 ) -> AppResult<StatusCode> {
+    if !user.is_logged_in() {
+        return Err(AppError::ForbiddenError);
+    }
+
     req.validate(&())?;
 
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog::new(
+            Some(user.id()),
+            AuditAction::ChangePassword,
+            AuditTargetType::User,
+            Some(user.id().to_string()),
+        ),
+    );
     registry
         .user_repository()
-        .update_password(UpdateUserPasswordRequestWithUserId::new(user.id(), req).into())
+        .update_password(
+            UpdateUserPasswordRequestWithUserId::new(user.id(), req).into(),
+            Some(audit),
+        )
         .await?;
+
     Ok(StatusCode::OK)
 }
 
@@ -114,6 +141,7 @@ pub async fn change_password(
 )]
 pub async fn register_user(
     user: AuthorizedUser,
+    metadata: RequestMetadata,
     State(registry): State<AppRegistry>,
     Json(req): Json<CreateUserRequest>,
 ) -> AppResult<Json<UserResponse>> {
@@ -123,25 +151,44 @@ pub async fn register_user(
 
     req.validate(&())?;
 
-    let registered_user = registry.user_repository().create(req.into()).await?;
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog {
+            after: Some(req.audit_snapshot()),
+            ..CreateAuditLog::new(
+                Some(user.id()),
+                AuditAction::CreateUser,
+                AuditTargetType::User,
+                None,
+            )
+        },
+    );
+    let registered_user = UserResponse::from(
+        registry
+            .user_repository()
+            .create(req.into(), Some(audit))
+            .await?,
+    );
 
-    Ok(Json(registered_user.into()))
+    Ok(Json(registered_user))
 }
 
-/// ユーザーを削除する(管理者のみ)
+/// ユーザーを無効化する(管理者のみ)
+/// ユーザーは論理削除され、ログインできなくなる。所有する蔵書はそのまま残る
 #[cfg_attr(
     debug_assertions,
     utoipa::path(
         delete,
         path = "/api/v1/users/{user_id}",
         responses (
-            (status = 204, description = "ユーザー削除成功"),
+            (status = 204, description = "ユーザー無効化成功"),
             (status = 401, description = "認証エラー"),
             (status = 403, description = "権限不足"),
-            (status = 404, description = "指定されたユーザーが見つからない場合"),
+            (status = 404, description = "指定された有効なユーザーが見つからない場合"),
+            (status = 422, description = "自分自身を無効化しようとした場合"),
         ),
         params(
-            ("user_id" = UserId, Path, description = "削除対象のユーザーID"),
+            ("user_id" = UserId, Path, description = "無効化対象のユーザーID"),
         ),
         security(
             ("bearer_auth" = [])
@@ -150,21 +197,206 @@ pub async fn register_user(
 )]
 pub async fn delete_user(
     user: AuthorizedUser,
+    metadata: RequestMetadata,
     Path(user_id): Path<UserId>,
     State(registry): State<AppRegistry>,
 ) -> AppResult<StatusCode> {
     if !user.is_admin() {
         return Err(AppError::ForbiddenError);
     }
+    if user.id() == user_id {
+        return Err(AppError::UnprocessableEntity(
+            "自分自身を無効化することはできません".into(),
+        ));
+    }
 
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog {
+            before: find_user_snapshot(&registry, user_id).await?,
+            ..CreateAuditLog::new(
+                Some(user.id()),
+                AuditAction::DeactivateUser,
+                AuditTargetType::User,
+                Some(user_id.to_string()),
+            )
+        },
+    );
     registry
         .user_repository()
-        .delete(DeleteUser { user_id })
+        .deactivate(DeactivateUser { user_id }, Some(audit))
         .await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// 無効化されたユーザーを再度有効化する(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/users/{user_id}/reactivate",
+        responses (
+            (status = 200, description = "ユーザー再有効化成功"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定された無効化済みのユーザーが見つからない場合"),
+        ),
+        params(
+            ("user_id" = UserId, Path, description = "再有効化対象のユーザーID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn reactivate_user(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog::new(
+            Some(user.id()),
+            AuditAction::ReactivateUser,
+            AuditTargetType::User,
+            Some(user_id.to_string()),
+        ),
+    );
+    registry
+        .user_repository()
+        .reactivate(ReactivateUser { user_id }, Some(audit))
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+/// ユーザーを物理削除する(管理者のみ)
+/// 蔵書を所有している、未返却の貸出がある、または料金の記録がある場合は削除せず、その内訳を返す
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/users/{user_id}/permanent",
+        responses (
+            (status = 204, description = "ユーザー削除成功"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定されたユーザーが見つからない場合"),
+            (status = 409, description = "削除を妨げる要因がある場合", body = UserDeletionBlockersResponse),
+        ),
+        params(
+            ("user_id" = UserId, Path, description = "削除対象のユーザーID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn purge_user(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Response> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    let blockers = registry
+        .user_repository()
+        .find_deletion_blockers(user_id)
+        .await?;
+    if !blockers.is_empty() {
+        return Ok((
+            StatusCode::CONFLICT,
+            Json(UserDeletionBlockersResponse::from(blockers)),
+        )
+            .into_response());
+    }
+
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog {
+            before: find_user_snapshot(&registry, user_id).await?,
+            ..CreateAuditLog::new(
+                Some(user.id()),
+                AuditAction::PurgeUser,
+                AuditTargetType::User,
+                Some(user_id.to_string()),
+            )
+        },
+    );
+    registry
+        .user_repository()
+        .delete(DeleteUser { user_id }, Some(audit))
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// ユーザーが所有する蔵書を別のユーザーへ付け替える(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/users/{user_id}/books/owner",
+        responses (
+            (status = 200, description = "蔵書の付け替え成功"),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "付け替え先の有効なユーザーが見つからない場合"),
+        ),
+        params(
+            ("user_id" = UserId, Path, description = "付け替え元のユーザーID"),
+        ),
+        request_body = ReassignBooksRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn reassign_books(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<ReassignBooksRequest>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog {
+            after: audit::snapshot(&req),
+            ..CreateAuditLog::new(
+                Some(user.id()),
+                AuditAction::ReassignBooks,
+                AuditTargetType::User,
+                Some(user_id.to_string()),
+            )
+        },
+    );
+    registry
+        .book_repository()
+        .reassign_owner(
+            ReassignBooksRequestWithUserId::new(user_id, req).into(),
+            Some(audit),
+        )
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
 /// ユーザーのロールを更新する(管理者のみ)
 #[cfg_attr(
     debug_assertions,
@@ -189,6 +421,7 @@ pub async fn delete_user(
 )]
 pub async fn change_role(
     user: AuthorizedUser,
+    metadata: RequestMetadata,
     Path(user_id): Path<UserId>,
     State(registry): State<AppRegistry>,
     Json(req): Json<UpdateUserRoleRequest>,
@@ -197,14 +430,139 @@ pub async fn change_role(
         return Err(AppError::ForbiddenError);
     }
 
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog {
+            before: find_user_snapshot(&registry, user_id).await?,
+            after: audit::snapshot(&req),
+            ..CreateAuditLog::new(
+                Some(user.id()),
+                AuditAction::ChangeRole,
+                AuditTargetType::User,
+                Some(user_id.to_string()),
+            )
+        },
+    );
     registry
         .user_repository()
-        .update_role(UpdateUserRoleRequestWithUserId::new(user_id, req).into())
+        .update_role(
+            UpdateUserRoleRequestWithUserId::new(user_id, req).into(),
+            Some(audit),
+        )
         .await?;
 
     Ok(StatusCode::OK)
 }
 
+/// ユーザーのバッジ(社員証・利用者カード)を登録する
+/// キオスクでは、登録されたバッジのコードからユーザーを識別する
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/users/{user_id}/badge",
+        responses (
+            (status = 200, description = "バッジ登録成功"),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定されたユーザーが見つからない場合"),
+            (status = 422, description = "バッジが他のユーザーに登録されている場合"),
+        ),
+        params(
+            ("user_id" = UserId, Path, description = "登録対象のユーザーID"),
+        ),
+        request_body = UpdateUserBadgeRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn register_badge(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<UpdateUserBadgeRequest>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+    req.validate(&())?;
+
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog::new(
+            Some(user.id()),
+            AuditAction::RegisterBadge,
+            AuditTargetType::User,
+            Some(user_id.to_string()),
+        ),
+    );
+    registry
+        .user_repository()
+        .update_badge(
+            UpdateUserBadgeRequestWithUserId::new(user_id, req).into(),
+            Some(audit),
+        )
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+/// ユーザーのバッジの登録を解除する
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/users/{user_id}/badge",
+        responses (
+            (status = 204, description = "バッジ登録解除成功"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定されたユーザーが見つからない場合"),
+        ),
+        params(
+            ("user_id" = UserId, Path, description = "解除対象のユーザーID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn unregister_badge(
+    user: AuthorizedUser,
+    metadata: RequestMetadata,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    let audit = audit::with_metadata(
+        &metadata,
+        CreateAuditLog::new(
+            Some(user.id()),
+            AuditAction::UnregisterBadge,
+            AuditTargetType::User,
+            Some(user_id.to_string()),
+        ),
+    );
+    registry
+        .user_repository()
+        .update_badge(
+            UpdateUserBadge {
+                user_id,
+                badge_code: None,
+            },
+            Some(audit),
+        )
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// 自身が借りている書籍の一覧を取得
 #[cfg_attr(
     debug_assertions,
@@ -231,3 +589,96 @@ pub async fn get_checkouts(
         .map(CheckoutsResponse::from)
         .map(Json)
 }
+
+/// 自身の貸出履歴(返却済みを含む)を取得
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/users/me/checkout-history",
+        responses (
+            (status = 200, description = "自身の貸出履歴取得成功", body = PaginatedCheckoutResponse),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+        ),
+        params(
+            ("limit" = i64, Query, description = "取得件数(0〜100、既定値は20)"),
+            ("offset" = i64, Query, description = "取得開始位置"),
+            ("from" = Option<String>, Query, description = "この日時以降に貸し出されたもの(RFC 3339)"),
+            ("to" = Option<String>, Query, description = "この日時より前に貸し出されたもの(RFC 3339)"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn get_checkout_history(
+    user: AuthorizedUser,
+    Query(query): Query<CheckoutHistoryQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<PaginatedCheckoutResponse>> {
+    query.validate(&())?;
+
+    registry
+        .check_out_repository()
+        .find_history_by_user_id(user.id(), query.into())
+        .await
+        .map(PaginatedCheckoutResponse::from)
+        .map(Json)
+}
+
+/// 指定したユーザーの貸出履歴(返却済みを含む)を取得(管理者のみ)
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/users/{user_id}/checkout-history",
+        responses (
+            (status = 200, description = "貸出履歴取得成功", body = PaginatedCheckoutResponse),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        params(
+            ("user_id" = UserId, Path, description = "履歴を取得するユーザーのID"),
+            ("limit" = i64, Query, description = "取得件数(0〜100、既定値は20)"),
+            ("offset" = i64, Query, description = "取得開始位置"),
+            ("from" = Option<String>, Query, description = "この日時以降に貸し出されたもの(RFC 3339)"),
+            ("to" = Option<String>, Query, description = "この日時より前に貸し出されたもの(RFC 3339)"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn get_user_checkout_history(
+    user: AuthorizedUser,
+    Path(user_id): Path<UserId>,
+    Query(query): Query<CheckoutHistoryQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<PaginatedCheckoutResponse>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+    query.validate(&())?;
+
+    registry
+        .check_out_repository()
+        .find_history_by_user_id(user_id, query.into())
+        .await
+        .map(PaginatedCheckoutResponse::from)
+        .map(Json)
+}
+
+/// 監査ログに記録する、操作前のユーザーの内容を取得する
+/// 無効化されたユーザーの内容は取得できないため、`None`となる
+async fn find_user_snapshot(
+    registry: &AppRegistry,
+    user_id: UserId,
+) -> AppResult<Option<serde_json::Value>> {
+    let user = registry
+        .user_repository()
+        .find_current_user(user_id)
+        .await?;
+    Ok(user.map(UserResponse::from).and_then(audit::snapshot))
+}
@@ -0,0 +1,214 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use garde::Validate;
+use kernel::model::{
+    id::{WebhookDeliveryId, WebhookId},
+    webhook::event::{CreateWebhook, DeleteWebhook, ReplayDelivery},
+};
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::{
+    extractor::AuthorizedUser,
+    model::webhook::{
+        CreateWebhookRequest, IssuedWebhookResponse, WebhookDeliveriesQuery,
+        WebhookDeliveriesResponse, WebhookDeliveryResponse, WebhooksResponse,
+    },
+};
+
+/// Webhookを登録し、配信の署名に用いるシークレットを発行する
+/// 発行されたシークレットはこのレスポンスでのみ返却される
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/webhooks",
+        responses (
+            (status = 201, description = "Webhook登録成功", body = IssuedWebhookResponse),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        request_body = CreateWebhookRequest,
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn create_webhook(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<CreateWebhookRequest>,
+) -> AppResult<(StatusCode, Json<IssuedWebhookResponse>)> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+    req.validate(&())?;
+
+    let mut events = Vec::new();
+    for event in req.events.into_iter().map(Into::into) {
+        if !events.contains(&event) {
+            events.push(event);
+        }
+    }
+    let issued = registry
+        .webhook_repository()
+        .create(CreateWebhook::new(req.url, events, user.id()))
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(issued.into())))
+}
+
+/// 登録されたWebhookの一覧を取得する
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/webhooks",
+        responses (
+            (status = 200, description = "Webhook一覧取得成功", body = WebhooksResponse),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn list_webhooks(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<WebhooksResponse>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    registry
+        .webhook_repository()
+        .find_all()
+        .await
+        .map(WebhooksResponse::from)
+        .map(Json)
+}
+
+/// Webhookを削除する。配信の記録も削除される
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/webhooks/{webhook_id}",
+        responses (
+            (status = 204, description = "Webhook削除成功"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定されたWebhookが見つからない場合"),
+        ),
+        params(
+            ("webhook_id" = WebhookId, Path, description = "削除するWebhookのID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn delete_webhook(
+    user: AuthorizedUser,
+    Path(webhook_id): Path<WebhookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    registry
+        .webhook_repository()
+        .delete(DeleteWebhook { webhook_id })
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Webhookへの配信履歴を新しい順に取得する
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/webhooks/{webhook_id}/deliveries",
+        responses (
+            (status = 200, description = "配信履歴取得成功", body = WebhookDeliveriesResponse),
+            (status = 400, description = "リクエストパラメータ不正"),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+        ),
+        params(
+            ("webhook_id" = WebhookId, Path, description = "WebhookのID"),
+            ("limit" = i64, Query, description = "取得件数(1〜100、既定値は50)"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn list_webhook_deliveries(
+    user: AuthorizedUser,
+    Path(webhook_id): Path<WebhookId>,
+    Query(query): Query<WebhookDeliveriesQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<WebhookDeliveriesResponse>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+    query.validate(&())?;
+
+    registry
+        .webhook_repository()
+        .find_deliveries(webhook_id, query.limit)
+        .await
+        .map(WebhookDeliveriesResponse::from)
+        .map(Json)
+}
+
+/// 過去の配信と同じ内容を送り直す
+/// 送り直す配信は新しい配信として登録され、非同期に送信される
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/webhooks/{webhook_id}/deliveries/{delivery_id}/replay",
+        responses (
+            (status = 202, description = "再送の受付成功", body = WebhookDeliveryResponse),
+            (status = 401, description = "認証エラー"),
+            (status = 403, description = "権限不足"),
+            (status = 404, description = "指定された配信が見つからない場合"),
+        ),
+        params(
+            ("webhook_id" = WebhookId, Path, description = "WebhookのID"),
+            ("delivery_id" = WebhookDeliveryId, Path, description = "送り直す配信のID"),
+        ),
+        security(
+            ("bearer_auth" = [])
+        )
+    )
+)]
+pub async fn replay_webhook_delivery(
+    user: AuthorizedUser,
+    Path((webhook_id, delivery_id)): Path<(WebhookId, WebhookDeliveryId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<(StatusCode, Json<WebhookDeliveryResponse>)> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenError);
+    }
+
+    let delivery = registry
+        .webhook_repository()
+        .replay(ReplayDelivery {
+            webhook_id,
+            delivery_id,
+        })
+        .await?;
+
+    Ok((StatusCode::ACCEPTED, Json(delivery.into())))
+}
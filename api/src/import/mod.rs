@@ -0,0 +1,230 @@
+//! 蔵書のCSVの取り込み
+
+use encoding_rs::{Encoding, SHIFT_JIS, UTF_8};
+use garde::Validate;
+use serde::Deserialize;
+use shared::error::{AppError, AppResult};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use crate::model::book::CreateBookRequest;
+
+/// 1回に取り込める行数の上限
+pub const MAX_IMPORT_ROWS: usize = 10_000;
+/// 取り込むCSVのサイズの上限
+pub const MAX_IMPORT_SIZE: usize = 10 * 1024 * 1024;
+
+/// 取り込むCSVの文字コード
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+pub enum CsvEncoding {
+    #[default]
+    #[serde(rename = "utf-8")]
+    Utf8,
+    #[serde(rename = "shift_jis")]
+    ShiftJis,
+}
+
+impl CsvEncoding {
+    fn encoding(self) -> &'static Encoding {
+        match self {
+            Self::Utf8 => UTF_8,
+            Self::ShiftJis => SHIFT_JIS,
+        }
+    }
+}
+
+/// CSVの見出しと、蔵書の項目の対応
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMapping {
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    /// 見出しにこの列がない場合、説明は空とする
+    pub description: String,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        Self {
+            title: "title".into(),
+            author: "author".into(),
+            isbn: "isbn".into(),
+            description: "description".into(),
+        }
+    }
+}
+
+/// CSVの1行の取り込み内容
+#[derive(Debug)]
+pub struct ImportRow {
+    /// CSV上の行番号(見出しの行を1とする)
+    pub line: u64,
+    pub result: Result<CreateBookRequest, Vec<ImportRowError>>,
+}
+
+/// 行を取り込めない理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportRowError {
+    /// 誤りのある項目。行全体を読み取れない場合は`None`
+    pub field: Option<String>,
+    /// 誤りのある項目に対応するCSVの列
+    pub column: Option<String>,
+    pub message: String,
+}
+
+/// 指定された文字コードでCSVを文字列に変換する
+/// UTF-8の場合は、表計算ソフトが付与するBOMを取り除く
+pub fn decode(bytes: &[u8], encoding: CsvEncoding) -> AppResult<String> {
+    let bytes = match encoding {
+        CsvEncoding::Utf8 => bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes),
+        CsvEncoding::ShiftJis => bytes,
+    };
+    encoding
+        .encoding()
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .map(String::from)
+        .ok_or_else(|| {
+            invalid_csv(format!(
+                "CSVを{}として読み取れません",
+                encoding.encoding().name()
+            ))
+        })
+}
+
+/// CSVの各行を読み取り、蔵書登録と同じ規則で検証する
+///
+/// 見出しに必須の列がない場合は、いずれの行も取り込めないためエラーとする
+pub fn parse_rows(text: &str, mapping: &ColumnMapping) -> AppResult<Vec<ImportRow>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| invalid_csv(format!("CSVの見出しを読み取れません: {e}")))?
+        .clone();
+    let position = |column: &str| headers.iter().position(|h| h.trim() == column);
+    let required = |column: &str| {
+        position(column)
+            .ok_or_else(|| invalid_csv(format!("CSVの見出しに列「{column}」がありません")))
+    };
+    let columns = [
+        ("title", required(&mapping.title)?, &mapping.title),
+        ("author", required(&mapping.author)?, &mapping.author),
+        ("isbn", required(&mapping.isbn)?, &mapping.isbn),
+    ];
+    let description = position(&mapping.description);
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        if rows.len() >= MAX_IMPORT_ROWS {
+            return Err(invalid_csv(format!(
+                "1回に取り込めるのは{MAX_IMPORT_ROWS}行までです"
+            )));
+        }
+        let row = match record {
+            Ok(record) => {
+                let line = record.position().map(|p| p.line()).unwrap_or_default();
+                let field = |index: usize| record.get(index).unwrap_or_default().trim().to_string();
+                let [title, author, isbn] = columns.map(|(_, index, _)| field(index));
+                let req = CreateBookRequest {
+                    title,
+                    author,
+                    isbn,
+                    description: description.map(field).unwrap_or_default(),
+                };
+                let result = match req.validate(&()) {
+                    Ok(()) => Ok(req),
+                    Err(report) => Err(report
+                        .iter()
+                        .map(|(path, error)| {
+                            let field = path.to_string();
+                            let column = columns
+                                .iter()
+                                .find(|(name, _, _)| *name == field)
+                                .map(|(_, _, column)| column.to_string());
+                            ImportRowError {
+                                field: Some(field),
+                                column,
+                                message: error.to_string(),
+                            }
+                        })
+                        .collect()),
+                };
+                ImportRow { line, result }
+            }
+            Err(e) => ImportRow {
+                line: e.position().map(|p| p.line()).unwrap_or_default(),
+                result: Err(vec![ImportRowError {
+                    field: None,
+                    column: None,
+                    message: e.to_string(),
+                }]),
+            },
+        };
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn invalid_csv(message: String) -> AppError {
+    let mut report = garde::Report::new();
+    report.append(garde::Path::empty(), garde::Error::new(message));
+    AppError::ValidationError(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() -> anyhow::Result<()> {
+        // 表計算ソフトが出力したBOM付きのUTF-8
+        let text = decode(b"\xEF\xBB\xBFtitle\n", CsvEncoding::Utf8)?;
+        assert_eq!(text, "title\n");
+
+        let (bytes, _, _) = SHIFT_JIS.encode("書名\n");
+        assert_eq!(decode(&bytes, CsvEncoding::ShiftJis)?, "書名\n");
+
+        // 文字コードの指定を誤った場合は、文字化けしたまま取り込まずにエラーとする
+        assert!(decode(&bytes, CsvEncoding::Utf8).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_rows_with_mapping() -> anyhow::Result<()> {
+        let mapping = ColumnMapping {
+            title: "書名".into(),
+            author: "著者".into(),
+            isbn: "ISBN".into(),
+            description: "説明".into(),
+        };
+        let text = "ISBN,書名,著者\n\
+                    9784000000001, Rust入門 ,山田太郎\n\
+                    9784000000002,,鈴木花子\n\
+                    9784000000003,Rust実践\n";
+        let rows = parse_rows(text, &mapping)?;
+        assert_eq!(rows.len(), 3);
+
+        // 前後の空白は取り除き、説明の列がなければ空とする
+        let req = rows[0].result.as_ref().unwrap();
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(req.title, "Rust入門");
+        assert_eq!(req.isbn, "9784000000001");
+        assert_eq!(req.description, "");
+
+        let errors = rows[1].result.as_ref().unwrap_err();
+        assert_eq!(rows[1].line, 3);
+        assert_eq!(errors[0].field.as_deref(), Some("title"));
+        assert_eq!(errors[0].column.as_deref(), Some("書名"));
+
+        // 列が足りない行は、足りない項目の誤りとして報告する
+        let errors = rows[2].result.as_ref().unwrap_err();
+        assert_eq!(errors[0].column.as_deref(), Some("著者"));
+
+        // 必須の列が見出しにない
+        let res = parse_rows("title,author\n", &ColumnMapping::default());
+        assert!(matches!(res, Err(AppError::ValidationError(_))));
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+pub mod outbox;
+pub mod reminder;
+pub mod webhook;
+
+/// `attempts`回目の失敗からやり直すまでの待ち時間
+/// `base`から失敗のたびに倍にし、`max`を上限とする
+pub(crate) fn retry_delay(base: Duration, attempts: u32, max: Duration) -> chrono::Duration {
+    let delay = base
+        .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(max);
+    chrono::Duration::from_std(delay).expect("delay is capped")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_limit() {
+        let base = Duration::from_secs(30);
+        let max = Duration::from_secs(24 * 60 * 60);
+        assert_eq!(retry_delay(base, 1, max), chrono::Duration::seconds(30));
+        assert_eq!(retry_delay(base, 2, max), chrono::Duration::seconds(60));
+        assert_eq!(retry_delay(base, 4, max), chrono::Duration::seconds(240));
+        assert_eq!(retry_delay(base, 100, max), chrono::Duration::days(1));
+    }
+}
@@ -0,0 +1,257 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use derive_new::new;
+use kernel::model::domain_event::PendingEvent;
+use registry::AppRegistry;
+use shared::{config::OutboxConfig, error::AppResult};
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+
+use super::retry_delay;
+
+/// 1回の確認で配信するイベントの上限
+const BATCH_SIZE: i64 = 100;
+/// 配信し直すまでの待ち時間の上限
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// アウトボックスに記録されたドメインイベントを、各コンシューマーに届けるジョブ
+///
+/// イベントはすべてのコンシューマーが処理し終えるまで処理済みにならず、
+/// 配信中にプロセスが停止した場合も後で配信し直されるため、少なくとも1回は届く。
+/// 失敗したコンシューマーには、待ち時間を倍にしながら成功するまで配信し直す
+#[derive(new)]
+pub struct OutboxDispatcher {
+    registry: AppRegistry,
+    config: OutboxConfig,
+}
+
+impl OutboxDispatcher {
+    /// 設定された間隔でイベントを配信するタスクを起動する
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match self.run_once(Utc::now()).await {
+                    Ok(0) => {}
+                    Ok(dispatched) => tracing::debug!(dispatched, "Dispatched domain events"),
+                    Err(e) => tracing::error!(
+                        error.cause_chain = ?e,
+                        error.message = %e,
+                        "Failed to dispatch domain events",
+                    ),
+                }
+            }
+        })
+    }
+
+    /// `now`時点で配信時刻を迎えたイベントを配信し、すべてのコンシューマーに届いたイベントの数を返す
+    pub async fn run_once(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        let pendings = self
+            .registry
+            .outbox_repository()
+            .claim_pending(now, BATCH_SIZE)
+            .await?;
+
+        // コンシューマーが起きた順にイベントを受け取れるよう、1件ずつ配信する
+        let mut dispatched = 0;
+        for pending in pendings {
+            match self.dispatch(&pending, now).await {
+                Ok(true) => dispatched += 1,
+                Ok(false) => {}
+                // 記録できなかったイベントは、確保した期間が過ぎると再び配信される
+                Err(e) => tracing::warn!(
+                    event_id = %pending.event.id,
+                    error.message = %e,
+                    "Failed to record domain event dispatch",
+                ),
+            }
+        }
+        Ok(dispatched)
+    }
+
+    /// まだ処理していないコンシューマーにイベントを届け、すべてに届いたかを返す
+    async fn dispatch(&self, pending: &PendingEvent, now: DateTime<Utc>) -> AppResult<bool> {
+        let outbox = self.registry.outbox_repository();
+        let event = &pending.event;
+        let mut errors = Vec::new();
+        for consumer in self.registry.event_consumers() {
+            let name = consumer.name();
+            if pending.consumed_by.iter().any(|c| c == name) {
+                continue;
+            }
+            match consumer.consume(event).await {
+                Ok(()) => outbox.mark_consumed(event.id, name).await?,
+                Err(e) => {
+                    tracing::warn!(
+                        event_id = %event.id,
+                        event_type = event.kind.name(),
+                        consumer = name,
+                        error.message = %e,
+                        "Domain event consumer failed",
+                    );
+                    errors.push(format!("{name}: {e}"));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            outbox.complete(event.id, now).await?;
+            return Ok(true);
+        }
+        let attempts = pending.attempts.max(0) as u32 + 1;
+        outbox
+            .record_failure(
+                event.id,
+                &errors.join("; "),
+                now + retry_delay(self.config.retry_delay, attempts, MAX_RETRY_DELAY),
+            )
+            .await?;
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use kernel::{
+        consumer::{DomainEventConsumer, MockDomainEventConsumer},
+        model::{
+            domain_event::{DomainEvent, DomainEventKind},
+            id::{BookId, DomainEventId, UserId},
+        },
+        repository::outbox::MockOutboxRepository,
+    };
+    use registry::MockAppRegistryExt;
+    use shared::error::AppError;
+
+    use super::*;
+
+    fn book_deleted(consumed_by: &[&str], attempts: i32) -> PendingEvent {
+        PendingEvent {
+            event: DomainEvent::new(DomainEventKind::BookDeleted {
+                book_id: BookId::new(),
+                deleted_by: UserId::new(),
+            }),
+            consumed_by: consumed_by.iter().map(|c| c.to_string()).collect(),
+            attempts,
+        }
+    }
+
+    fn consumer(name: &'static str, fails: bool) -> Arc<dyn DomainEventConsumer> {
+        let mut consumer = MockDomainEventConsumer::new();
+        consumer.expect_name().return_const(name);
+        consumer.expect_consume().returning(move |_| {
+            if fails {
+                Err(AppError::InternalError(anyhow::anyhow!("unavailable")))
+            } else {
+                Ok(())
+            }
+        });
+        Arc::new(consumer)
+    }
+
+    #[derive(Debug, Default)]
+    struct Recorded {
+        consumed: Vec<(DomainEventId, String)>,
+        completed: Vec<DomainEventId>,
+        failed: Vec<(DomainEventId, String, DateTime<Utc>)>,
+    }
+
+    fn mock_registry(
+        pendings: Vec<PendingEvent>,
+        consumers: Vec<Arc<dyn DomainEventConsumer>>,
+        recorded: Arc<Mutex<Recorded>>,
+    ) -> AppRegistry {
+        let mut registry = MockAppRegistryExt::new();
+        let mut repo = MockOutboxRepository::new();
+        repo.expect_claim_pending()
+            .return_once(move |_, _| Ok(pendings));
+        let r = recorded.clone();
+        repo.expect_mark_consumed().returning(move |id, name| {
+            r.lock().unwrap().consumed.push((id, name.to_string()));
+            Ok(())
+        });
+        let r = recorded.clone();
+        repo.expect_complete().returning(move |id, _| {
+            r.lock().unwrap().completed.push(id);
+            Ok(())
+        });
+        repo.expect_record_failure()
+            .returning(move |id, error, retry_at| {
+                recorded
+                    .lock()
+                    .unwrap()
+                    .failed
+                    .push((id, error.to_string(), retry_at));
+                Ok(())
+            });
+        let repo = Arc::new(repo);
+        registry
+            .expect_outbox_repository()
+            .returning(move || repo.clone());
+        registry
+            .expect_event_consumers()
+            .returning(move || consumers.clone());
+        Arc::new(registry)
+    }
+
+    #[tokio::test]
+    async fn test_events_are_dispatched_to_pending_consumers() -> anyhow::Result<()> {
+        let now = Utc::now();
+        let recorded = Arc::new(Mutex::new(Recorded::default()));
+        // 1件目は初回、2件目はwebhookが処理済みで、notificationが2回失敗している
+        let pendings = vec![book_deleted(&[], 0), book_deleted(&["webhook"], 2)];
+        let ids: Vec<_> = pendings.iter().map(|p| p.event.id).collect();
+
+        // notificationのみが失敗する場合
+        let registry = mock_registry(
+            pendings,
+            vec![consumer("webhook", false), consumer("notification", true)],
+            recorded.clone(),
+        );
+        let dispatcher = OutboxDispatcher::new(
+            registry,
+            OutboxConfig {
+                retry_delay: Duration::from_secs(10),
+                ..Default::default()
+            },
+        );
+        assert_eq!(dispatcher.run_once(now).await?, 0);
+
+        let recorded = recorded.lock().unwrap();
+        // 処理済みのコンシューマーには配信し直さない
+        assert_eq!(recorded.consumed, [(ids[0], "webhook".to_string())]);
+        assert!(recorded.completed.is_empty());
+        assert_eq!(recorded.failed.len(), 2);
+        assert_eq!(recorded.failed[0].0, ids[0]);
+        assert_eq!(recorded.failed[0].2, now + chrono::Duration::seconds(10));
+        assert!(recorded.failed[0].1.starts_with("notification: "));
+        // 3回目の失敗なので、待ち時間は基準の4倍になる
+        assert_eq!(recorded.failed[1].0, ids[1]);
+        assert_eq!(recorded.failed[1].2, now + chrono::Duration::seconds(40));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_event_is_completed_when_all_consumers_succeed() -> anyhow::Result<()> {
+        let recorded = Arc::new(Mutex::new(Recorded::default()));
+        let pending = book_deleted(&["webhook"], 1);
+        let id = pending.event.id;
+        let registry = mock_registry(
+            vec![pending],
+            vec![consumer("webhook", true), consumer("notification", false)],
+            recorded.clone(),
+        );
+        let dispatcher = OutboxDispatcher::new(registry, OutboxConfig::default());
+        assert_eq!(dispatcher.run_once(Utc::now()).await?, 1);
+
+        let recorded = recorded.lock().unwrap();
+        assert_eq!(recorded.consumed, [(id, "notification".to_string())]);
+        assert_eq!(recorded.completed, [id]);
+        assert!(recorded.failed.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,349 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{DateTime, Utc};
+use derive_new::new;
+use kernel::model::{
+    checkout::Checkout,
+    id::UserId,
+    notification::{Notification, NotificationMessage, Recipient},
+    reminder::{CheckoutReminder, ReminderKind},
+};
+use registry::AppRegistry;
+use shared::{config::ReminderConfig, error::AppResult};
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+
+/// 返却期限が近い貸出や期限を過ぎた貸出を定期的に探し、利用者にリマインダーを送るジョブ
+/// 送信したリマインダーは記録し、同じ貸出に同じリマインダーを繰り返し送らない
+#[derive(new)]
+pub struct ReminderJob {
+    registry: AppRegistry,
+    config: ReminderConfig,
+}
+
+impl ReminderJob {
+    /// 設定された間隔でリマインダーを送るタスクを起動する
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match self.run_once(Utc::now()).await {
+                    Ok(0) => {}
+                    Ok(sent) => tracing::info!(sent, "Sent checkout reminders"),
+                    Err(e) => tracing::error!(
+                        error.cause_chain = ?e,
+                        error.message = %e,
+                        "Failed to send checkout reminders",
+                    ),
+                }
+            }
+        })
+    }
+
+    /// `now`時点で送るべきリマインダーを送り、送信した通知の数を返す
+    pub async fn run_once(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        let due_before = now + chrono::Duration::hours(self.config.due_soon_hours);
+        let checkouts = self
+            .registry
+            .check_out_repository()
+            .find_unreturned_due_before(due_before)
+            .await?;
+
+        // 利用者ごと・リマインダーの種類ごとにまとめて1通で通知する
+        let mut groups: HashMap<(UserId, ReminderKind), Vec<Checkout>> = HashMap::new();
+        for checkout in checkouts {
+            let kind = ReminderKind::of(checkout.due_at, now);
+            groups
+                .entry((checkout.checked_out_by, kind))
+                .or_default()
+                .push(checkout);
+        }
+
+        let mut sent = 0;
+        for ((user_id, kind), checkouts) in groups {
+            let mut claimed = Vec::new();
+            match self.remind(user_id, kind, checkouts, &mut claimed).await {
+                Ok(true) => sent += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    // 送信できなかったリマインダーは、次回に改めて送る
+                    tracing::warn!(
+                        error.message = %e,
+                        %user_id,
+                        "Failed to send checkout reminder",
+                    );
+                    for reminder in &claimed {
+                        if let Err(e) = self.registry.reminder_repository().release(reminder).await
+                        {
+                            tracing::warn!(error.message = %e, "Failed to release checkout reminder");
+                        }
+                    }
+                }
+            }
+        }
+        Ok(sent)
+    }
+
+    /// 未送信のリマインダーを送信済みとして記録してから、利用者に通知する
+    /// 記録したリマインダーは`claimed`に追加する
+    async fn remind(
+        &self,
+        user_id: UserId,
+        kind: ReminderKind,
+        checkouts: Vec<Checkout>,
+        claimed: &mut Vec<CheckoutReminder>,
+    ) -> AppResult<bool> {
+        let reminder_repository = self.registry.reminder_repository();
+        let mut targets = Vec::new();
+        for checkout in checkouts {
+            let reminder = CheckoutReminder {
+                checkout_id: checkout.id,
+                kind,
+                due_at: checkout.due_at,
+            };
+            if reminder_repository.claim(&reminder).await? {
+                claimed.push(reminder);
+                targets.push(checkout);
+            }
+        }
+        if targets.is_empty() {
+            return Ok(false);
+        }
+
+        // 無効化されたユーザーには送らない
+        let Some(user) = self
+            .registry
+            .user_repository()
+            .find_current_user(user_id)
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        self.registry
+            .notifier()
+            .notify(&Notification {
+                recipient: Recipient::from(&user),
+                message: NotificationMessage::Reminder {
+                    kind,
+                    checkouts: targets,
+                },
+            })
+            .await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use kernel::{
+        model::{
+            checkout::CheckoutBook,
+            id::{BookId, CheckoutId},
+            role::Role,
+            user::User,
+        },
+        notifier::MockNotifier,
+        repository::{
+            checkout::MockCheckoutRepository, reminder::MockReminderRepository,
+            user::MockUserRepository,
+        },
+    };
+    use registry::MockAppRegistryExt;
+    use shared::error::AppError;
+
+    use super::*;
+
+    fn checkout(user_id: UserId, due_at: DateTime<Utc>) -> Checkout {
+        Checkout {
+            id: CheckoutId::new(),
+            checked_out_by: user_id,
+            checked_out_at: due_at - chrono::Duration::days(14),
+            returned_at: None,
+            due_at,
+            renewal_count: 0,
+            checked_out_via: None,
+            returned_via: None,
+            book: CheckoutBook {
+                book_id: BookId::new(),
+                title: "Rust in Action".into(),
+                author: "Tim McNamara".into(),
+                isbn: "9781617294556".into(),
+            },
+        }
+    }
+
+    /// 指定された貸出を返し、通知した内容を記録するレジストリ
+    fn mock_registry(
+        checkouts: Vec<Checkout>,
+        claim: impl Fn(&CheckoutReminder) -> bool + Send + 'static,
+        notify: impl Fn(&Notification) -> AppResult<()> + Send + Sync + 'static,
+        released: Arc<Mutex<Vec<CheckoutReminder>>>,
+    ) -> AppRegistry {
+        let mut registry = MockAppRegistryExt::new();
+        let checkouts = Mutex::new(Some(checkouts));
+        registry.expect_check_out_repository().returning(move || {
+            let mut repo = MockCheckoutRepository::new();
+            let checkouts = checkouts.lock().unwrap().take().unwrap_or_default();
+            repo.expect_find_unreturned_due_before()
+                .return_once(move |_| Ok(checkouts));
+            Arc::new(repo)
+        });
+        let mut reminder_repository = MockReminderRepository::new();
+        reminder_repository
+            .expect_claim()
+            .returning(move |r| Ok(claim(r)));
+        reminder_repository.expect_release().returning(move |r| {
+            released.lock().unwrap().push(r.clone());
+            Ok(())
+        });
+        let reminder_repository = Arc::new(reminder_repository);
+        registry
+            .expect_reminder_repository()
+            .returning(move || reminder_repository.clone());
+        registry.expect_user_repository().returning(|| {
+            let mut repo = MockUserRepository::new();
+            repo.expect_find_current_user().returning(|id| {
+                Ok(Some(User {
+                    id,
+                    name: "dummy-user".into(),
+                    email: "dummy@example.com".into(),
+                    role: Role::User,
+                    deactivated_at: None,
+                }))
+            });
+            Arc::new(repo)
+        });
+        let mut notifier = MockNotifier::new();
+        notifier.expect_notify().returning(move |n| notify(n));
+        let notifier = Arc::new(notifier);
+        registry
+            .expect_notifier()
+            .returning(move || notifier.clone());
+        Arc::new(registry)
+    }
+
+    fn job(registry: AppRegistry) -> ReminderJob {
+        ReminderJob::new(
+            registry,
+            ReminderConfig {
+                due_soon_hours: 48,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_reminders_are_grouped_by_user_and_kind() -> anyhow::Result<()> {
+        let now = Utc::now();
+        let (alice, bob) = (UserId::new(), UserId::new());
+        let checkouts = vec![
+            checkout(alice, now - chrono::Duration::days(1)),
+            checkout(alice, now + chrono::Duration::hours(1)),
+            checkout(alice, now + chrono::Duration::hours(2)),
+            checkout(bob, now + chrono::Duration::hours(3)),
+        ];
+        let notified = Arc::new(Mutex::new(Vec::new()));
+        let registry = mock_registry(
+            checkouts,
+            |_| true,
+            {
+                let notified = notified.clone();
+                move |n| {
+                    let NotificationMessage::Reminder { kind, checkouts } = &n.message else {
+                        panic!("unexpected notification: {:?}", n.message);
+                    };
+                    notified
+                        .lock()
+                        .unwrap()
+                        .push((n.recipient.user_id, *kind, checkouts.len()));
+                    Ok(())
+                }
+            },
+            Default::default(),
+        );
+
+        assert_eq!(job(registry).run_once(now).await?, 3);
+        let mut notified = notified.lock().unwrap().clone();
+        notified
+            .sort_by_key(|(user_id, kind, _)| (*user_id == bob, *kind == ReminderKind::DueSoon));
+        assert_eq!(
+            notified,
+            vec![
+                (alice, ReminderKind::Overdue, 1),
+                (alice, ReminderKind::DueSoon, 2),
+                (bob, ReminderKind::DueSoon, 1),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reminders_already_sent_are_skipped() -> anyhow::Result<()> {
+        let now = Utc::now();
+        let user_id = UserId::new();
+        let sent = checkout(user_id, now + chrono::Duration::hours(1));
+        let sent_id = sent.id;
+        let registry = mock_registry(
+            vec![sent, checkout(user_id, now + chrono::Duration::hours(2))],
+            move |r| r.checkout_id != sent_id,
+            move |n| {
+                let NotificationMessage::Reminder { checkouts, .. } = &n.message else {
+                    panic!("unexpected notification: {:?}", n.message);
+                };
+                assert_eq!(checkouts.len(), 1);
+                assert_ne!(checkouts[0].id, sent_id);
+                Ok(())
+            },
+            Default::default(),
+        );
+        assert_eq!(job(registry).run_once(now).await?, 1);
+
+        // すべて送信済みの場合は通知しない
+        let registry = mock_registry(
+            vec![checkout(user_id, now + chrono::Duration::hours(1))],
+            |_| false,
+            |_| panic!("should not notify"),
+            Default::default(),
+        );
+        assert_eq!(job(registry).run_once(now).await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failed_reminders_are_released() -> anyhow::Result<()> {
+        let now = Utc::now();
+        let released = Arc::new(Mutex::new(Vec::new()));
+        let failing = checkout(UserId::new(), now - chrono::Duration::hours(1));
+        let failing_id = failing.id;
+        let registry = mock_registry(
+            vec![
+                failing,
+                checkout(UserId::new(), now - chrono::Duration::hours(1)),
+            ],
+            |_| true,
+            move |n| {
+                let NotificationMessage::Reminder { checkouts, .. } = &n.message else {
+                    panic!("unexpected notification: {:?}", n.message);
+                };
+                if checkouts[0].id == failing_id {
+                    Err(AppError::InternalError(anyhow::anyhow!("SMTP unavailable")))
+                } else {
+                    Ok(())
+                }
+            },
+            released.clone(),
+        );
+
+        // 他の利用者への送信は続ける
+        assert_eq!(job(registry).run_once(now).await?, 1);
+        let released = released.lock().unwrap();
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].checkout_id, failing_id);
+        assert_eq!(released[0].kind, ReminderKind::Overdue);
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use derive_new::new;
+use kernel::model::webhook::{
+    event::{DeliveryOutcome, RecordDeliveryAttempt},
+    PendingDelivery,
+};
+use registry::AppRegistry;
+use shared::{config::WebhookConfig, error::AppResult};
+use tokio::{
+    task::{JoinHandle, JoinSet},
+    time::MissedTickBehavior,
+};
+
+use super::retry_delay;
+
+/// 1回の確認で送信する配信の上限
+const BATCH_SIZE: i64 = 100;
+/// 再送までの待ち時間の上限
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 送信時刻を迎えたWebhookの配信を定期的に送信するジョブ
+/// 2xx以外の応答や接続の失敗は、待ち時間を倍にしながら上限の回数まで再送する
+#[derive(new)]
+pub struct WebhookDispatcher {
+    registry: AppRegistry,
+    config: WebhookConfig,
+}
+
+impl WebhookDispatcher {
+    /// 設定された間隔で配信を送信するタスクを起動する
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match self.run_once(Utc::now()).await {
+                    Ok(0) => {}
+                    Ok(delivered) => tracing::info!(delivered, "Delivered webhooks"),
+                    Err(e) => tracing::error!(
+                        error.cause_chain = ?e,
+                        error.message = %e,
+                        "Failed to deliver webhooks",
+                    ),
+                }
+            }
+        })
+    }
+
+    /// `now`時点で送信時刻を迎えた配信を送信し、配信先が受け付けた配信の数を返す
+    pub async fn run_once(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        let pendings = self
+            .registry
+            .webhook_repository()
+            .claim_due_deliveries(now, BATCH_SIZE)
+            .await?;
+
+        // 応答の遅い配信先が他の配信を待たせないよう、並行して送信する
+        let mut tasks = JoinSet::new();
+        for pending in pendings {
+            let registry = self.registry.clone();
+            let config = self.config.clone();
+            tasks.spawn(async move { deliver(&registry, &config, &pending, now).await });
+        }
+
+        let mut delivered = 0;
+        while let Some(res) = tasks.join_next().await {
+            match res {
+                Ok(Ok(true)) => delivered += 1,
+                Ok(Ok(false)) => {}
+                // 記録できなかった配信は、確保した期間が過ぎると再び送信される
+                Ok(Err(e)) => tracing::warn!(
+                    error.message = %e,
+                    "Failed to record webhook delivery attempt",
+                ),
+                Err(e) => tracing::error!(error.message = %e, "Webhook delivery task panicked"),
+            }
+        }
+        Ok(delivered)
+    }
+}
+
+/// 配信を送信して結果を記録し、配信先が受け付けたかを返す
+async fn deliver(
+    registry: &AppRegistry,
+    config: &WebhookConfig,
+    pending: &PendingDelivery,
+    now: DateTime<Utc>,
+) -> AppResult<bool> {
+    let delivery = &pending.delivery;
+    let (response_status, error) = match registry.webhook_sender().send(pending).await {
+        Ok(status) if (200..300).contains(&status) => (Some(status as i32), None),
+        Ok(status) => (
+            Some(status as i32),
+            Some(format!("Endpoint responded with status {status}")),
+        ),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    let succeeded = error.is_none();
+    let outcome = if succeeded {
+        DeliveryOutcome::Succeeded
+    } else {
+        let attempts = delivery.attempts.max(0) as u32 + 1;
+        if attempts >= config.max_attempts {
+            DeliveryOutcome::Failed
+        } else {
+            DeliveryOutcome::Retry(now + retry_delay(config.retry_delay, attempts, MAX_RETRY_DELAY))
+        }
+    };
+    if let Some(error) = &error {
+        tracing::warn!(
+            delivery_id = %delivery.id,
+            webhook_id = %delivery.webhook_id,
+            attempts = delivery.attempts + 1,
+            error.message = %error,
+            "Webhook delivery failed",
+        );
+    }
+
+    registry
+        .webhook_repository()
+        .record_attempt(RecordDeliveryAttempt {
+            delivery_id: delivery.id,
+            attempted_at: now,
+            response_status,
+            error,
+            outcome,
+        })
+        .await?;
+    Ok(succeeded)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use kernel::{
+        model::{
+            id::{WebhookDeliveryId, WebhookEventId, WebhookId},
+            webhook::{WebhookDelivery, WebhookDeliveryStatus, WebhookEventType, WebhookSecret},
+        },
+        repository::webhook::MockWebhookRepository,
+        webhook::MockWebhookSender,
+    };
+    use registry::MockAppRegistryExt;
+    use shared::error::AppError;
+
+    use super::*;
+
+    fn pending(url: &str, attempts: i32) -> PendingDelivery {
+        let now = Utc::now();
+        PendingDelivery {
+            delivery: WebhookDelivery {
+                id: WebhookDeliveryId::new(),
+                webhook_id: WebhookId::new(),
+                event_id: WebhookEventId::new(),
+                event_type: WebhookEventType::CheckoutCreated,
+                payload: "{}".into(),
+                status: WebhookDeliveryStatus::Pending,
+                attempts,
+                response_status: None,
+                last_error: None,
+                next_attempt_at: now,
+                created_at: now,
+                delivered_at: None,
+            },
+            url: url.into(),
+            secret: WebhookSecret("whsec_test".into()),
+        }
+    }
+
+    /// 指定された配信を返し、配信先のURLに応じた結果を返すレジストリ
+    fn mock_registry(
+        pendings: Vec<PendingDelivery>,
+        send: impl Fn(&PendingDelivery) -> AppResult<u16> + Send + Sync + 'static,
+        recorded: Arc<Mutex<Vec<RecordDeliveryAttempt>>>,
+    ) -> AppRegistry {
+        let mut registry = MockAppRegistryExt::new();
+        let mut repo = MockWebhookRepository::new();
+        repo.expect_claim_due_deliveries()
+            .return_once(move |_, _| Ok(pendings));
+        repo.expect_record_attempt().returning(move |e| {
+            recorded.lock().unwrap().push(e);
+            Ok(())
+        });
+        let repo = Arc::new(repo);
+        registry
+            .expect_webhook_repository()
+            .returning(move || repo.clone());
+        let mut sender = MockWebhookSender::new();
+        sender.expect_send().returning(move |p| send(p));
+        let sender = Arc::new(sender);
+        registry
+            .expect_webhook_sender()
+            .returning(move || sender.clone());
+        Arc::new(registry)
+    }
+
+    #[tokio::test]
+    async fn test_deliveries_are_recorded_by_outcome() -> anyhow::Result<()> {
+        let now = Utc::now();
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        let pendings = vec![
+            pending("https://ok.example.com", 0),
+            pending("https://unavailable.example.com", 1),
+            pending("https://unreachable.example.com", 7),
+        ];
+        let ids: Vec<_> = pendings.iter().map(|p| p.delivery.id).collect();
+        let registry = mock_registry(
+            pendings,
+            |p| match p.url.as_str() {
+                "https://ok.example.com" => Ok(204),
+                "https://unavailable.example.com" => Ok(503),
+                _ => Err(AppError::InternalError(anyhow::anyhow!(
+                    "connection refused"
+                ))),
+            },
+            recorded.clone(),
+        );
+        let dispatcher = WebhookDispatcher::new(
+            registry,
+            WebhookConfig {
+                max_attempts: 8,
+                retry_delay: Duration::from_secs(30),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(dispatcher.run_once(now).await?, 1);
+
+        let recorded = recorded.lock().unwrap();
+        let find = |id| recorded.iter().find(|r| r.delivery_id == id).unwrap();
+        let ok = find(ids[0]);
+        assert_eq!(ok.outcome, DeliveryOutcome::Succeeded);
+        assert_eq!(ok.response_status, Some(204));
+        assert!(ok.error.is_none());
+
+        // 2回目の失敗なので、待ち時間は基準の2倍になる
+        let unavailable = find(ids[1]);
+        assert_eq!(
+            unavailable.outcome,
+            DeliveryOutcome::Retry(now + chrono::Duration::seconds(60))
+        );
+        assert_eq!(unavailable.response_status, Some(503));
+        assert!(unavailable.error.is_some());
+
+        // 上限の回数に達した配信はあきらめる
+        let unreachable = find(ids[2]);
+        assert_eq!(unreachable.outcome, DeliveryOutcome::Failed);
+        assert_eq!(unreachable.response_status, None);
+        assert!(unreachable
+            .error
+            .as_deref()
+            .is_some_and(|e| e.contains("connection refused")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nothing_to_deliver() -> anyhow::Result<()> {
+        let registry = mock_registry(vec![], |_| panic!("should not send"), Default::default());
+        let dispatcher = WebhookDispatcher::new(registry, WebhookConfig::default());
+        assert_eq!(dispatcher.run_once(Utc::now()).await?, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+//! Code 128(コードセットB)のエンコーダー
+
+/// 各シンボルのバーとスペースの幅(モジュール数)。バーから始まり、交互に並ぶ
+/// インデックスがシンボルの値(0〜102はデータ、103〜105はスタート、106はストップ)
+const PATTERNS: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232", "2331112",
+];
+
+const START_B: usize = 104;
+const STOP: usize = 106;
+
+/// 文字列をCode 128のモジュールの並び(`true`がバー)に変換する
+/// コードセットBで表せない文字(ASCIIの印字可能文字以外)を含む場合は`None`
+pub fn encode(data: &str) -> Option<Vec<bool>> {
+    let values = data
+        .bytes()
+        .map(|b| (0x20..0x7f).contains(&b).then(|| usize::from(b - 0x20)))
+        .collect::<Option<Vec<_>>>()?;
+
+    let checksum = values
+        .iter()
+        .enumerate()
+        .fold(START_B, |acc, (i, v)| acc + (i + 1) * v)
+        % 103;
+
+    let mut modules = Vec::new();
+    for symbol in std::iter::once(START_B)
+        .chain(values)
+        .chain([checksum, STOP])
+    {
+        for (i, width) in PATTERNS[symbol].bytes().enumerate() {
+            let bar = i % 2 == 0;
+            modules.extend(std::iter::repeat_n(bar, usize::from(width - b'0')));
+        }
+    }
+    Some(modules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns() {
+        // ストップ以外のシンボルはすべて11モジュール、ストップは13モジュール
+        for (i, pattern) in PATTERNS.iter().enumerate() {
+            let width: u32 = pattern.bytes().map(|b| u32::from(b - b'0')).sum();
+            assert_eq!(width, if i == STOP { 13 } else { 11 }, "symbol {i}");
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        let modules = encode("PJJ123C").unwrap();
+        // スタート + 7文字 + チェックデジット + ストップ
+        assert_eq!(modules.len(), 11 * 9 + 13);
+        // スタートB(211214)から始まり、ストップの終端バー(2モジュール)で終わる
+        assert_eq!(
+            &modules[..11],
+            &[true, true, false, true, false, false, true, false, false, false, false]
+        );
+        assert_eq!(&modules[modules.len() - 3..], &[false, true, true]);
+
+        assert!(encode("日本語").is_none());
+    }
+}
@@ -0,0 +1,317 @@
+//! 蔵書のラベル(QRコード・バーコード)の描画
+
+use std::{fmt::Write, str::FromStr};
+
+use kernel::model::id::BookId;
+use qrcode::{Color, EcLevel, QrCode};
+use shared::{
+    config::LabelConfig,
+    error::{AppError, AppResult},
+};
+
+mod code128;
+
+/// QRコードの周囲の余白(モジュール数)
+const QR_QUIET_ZONE: usize = 4;
+/// バーコードの左右の余白(モジュール数)
+const CODE128_QUIET_ZONE: usize = 10;
+/// バーコードのバーの高さ(モジュール数)
+const CODE128_HEIGHT: usize = 60;
+
+/// ラベルの符号化方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Symbology {
+    #[default]
+    Qr,
+    Code128,
+}
+
+/// 描画したシンボル。モジュールは行優先で並び、`true`が黒を表す
+pub struct Symbol {
+    width: usize,
+    height: usize,
+    modules: Vec<bool>,
+}
+
+impl Symbol {
+    /// 蔵書のラベルを描画する
+    ///
+    /// QRコードには、設定されていればディープリンクのURLを、そうでなければ蔵書のIDを埋め込む。
+    /// バーコードは読み取りやすさのため、常に蔵書のIDのみを埋め込む
+    pub fn for_book(
+        book_id: BookId,
+        symbology: Symbology,
+        config: &LabelConfig,
+    ) -> AppResult<Self> {
+        match symbology {
+            Symbology::Qr => Self::qr(&book_link(book_id, config)),
+            Symbology::Code128 => Self::code128(&book_id.to_string()),
+        }
+    }
+
+    fn qr(data: &str) -> AppResult<Self> {
+        let code = QrCode::with_error_correction_level(data, EcLevel::M)
+            .map_err(|e| AppError::InternalError(anyhow::anyhow!(e)))?;
+        let size = code.width();
+        let colors = code.to_colors();
+        let width = size + QR_QUIET_ZONE * 2;
+        let mut modules = vec![false; width * width];
+        for (i, color) in colors.into_iter().enumerate() {
+            let (x, y) = (i % size + QR_QUIET_ZONE, i / size + QR_QUIET_ZONE);
+            modules[y * width + x] = color == Color::Dark;
+        }
+        Ok(Self {
+            width,
+            height: width,
+            modules,
+        })
+    }
+
+    fn code128(data: &str) -> AppResult<Self> {
+        let bars = code128::encode(data).ok_or_else(|| {
+            AppError::UnprocessableEntity("バーコードに含められない文字があります".into())
+        })?;
+        let width = bars.len() + CODE128_QUIET_ZONE * 2;
+        let mut row = vec![false; width];
+        row[CODE128_QUIET_ZONE..CODE128_QUIET_ZONE + bars.len()].copy_from_slice(&bars);
+        Ok(Self {
+            width,
+            height: CODE128_HEIGHT,
+            modules: row.repeat(CODE128_HEIGHT),
+        })
+    }
+
+    /// 黒のモジュールを横方向に連結した矩形を、SVGのパスとして返す
+    fn svg_path(&self) -> String {
+        let mut path = String::new();
+        for (y, row) in self.modules.chunks(self.width).enumerate() {
+            let mut x = 0;
+            while x < self.width {
+                if !row[x] {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < self.width && row[x] {
+                    x += 1;
+                }
+                let _ = write!(path, "M{start} {y}h{}v1h-{}z", x - start, x - start);
+            }
+        }
+        path
+    }
+
+    /// 1モジュールを`scale`ピクセルとしたSVG画像
+    pub fn to_svg(&self, scale: u32) -> String {
+        let scale = scale as usize;
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}" shape-rendering="crispEdges"><rect width="100%" height="100%" fill="#fff"/><path fill="#000" d="{}"/></svg>"##,
+            self.width * scale,
+            self.height * scale,
+            self.width,
+            self.height,
+            self.svg_path()
+        )
+    }
+
+    /// 1モジュールを`scale`ピクセルとした、グレースケールのPNG画像
+    pub fn to_png(&self, scale: u32) -> AppResult<Vec<u8>> {
+        let scale = scale as usize;
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in self.modules.chunks(self.width) {
+            let line = row
+                .iter()
+                .flat_map(|&dark| std::iter::repeat_n(if dark { 0 } else { 255 }, scale))
+                .collect::<Vec<u8>>();
+            for _ in 0..scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+
+        let mut buf = Vec::new();
+        let mut encoder = png::Encoder::new(&mut buf, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&pixels))
+            .map_err(|e| AppError::InternalError(e.into()))?;
+        Ok(buf)
+    }
+}
+
+/// ラベルシートに並べる蔵書
+pub struct SheetLabel {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+}
+
+// A4用紙に3列×8段で並べる、70mm×37mmのラベルシート
+const SHEET_WIDTH_MM: f64 = 210.0;
+const SHEET_HEIGHT_MM: f64 = 297.0;
+const SHEET_COLUMNS: usize = 3;
+const SHEET_ROWS: usize = 8;
+/// 1枚のシートに印刷できるラベルの数
+pub const LABELS_PER_SHEET: usize = SHEET_COLUMNS * SHEET_ROWS;
+const LABEL_WIDTH_MM: f64 = SHEET_WIDTH_MM / SHEET_COLUMNS as f64;
+const LABEL_HEIGHT_MM: f64 = SHEET_HEIGHT_MM / SHEET_ROWS as f64;
+const LABEL_PADDING_MM: f64 = 3.0;
+
+/// 複数の蔵書のラベルを並べた、印刷用のSVGを返す
+pub fn render_sheet(
+    labels: &[SheetLabel],
+    symbology: Symbology,
+    config: &LabelConfig,
+) -> AppResult<String> {
+    if labels.len() > LABELS_PER_SHEET {
+        return Err(AppError::UnprocessableEntity(format!(
+            "1枚のシートに印刷できるラベルは{}枚までです",
+            LABELS_PER_SHEET
+        )));
+    }
+
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{SHEET_WIDTH_MM}mm" height="{SHEET_HEIGHT_MM}mm" viewBox="0 0 {SHEET_WIDTH_MM} {SHEET_HEIGHT_MM}" font-family="sans-serif"><rect width="100%" height="100%" fill="#fff"/>"##
+    );
+    for (i, label) in labels.iter().enumerate() {
+        let x = (i % SHEET_COLUMNS) as f64 * LABEL_WIDTH_MM + LABEL_PADDING_MM;
+        let y = (i / SHEET_COLUMNS) as f64 * LABEL_HEIGHT_MM + LABEL_PADDING_MM;
+        let symbol = Symbol::for_book(label.book_id, symbology, config)?;
+        let inner_height = LABEL_HEIGHT_MM - LABEL_PADDING_MM * 2.0;
+        let inner_width = LABEL_WIDTH_MM - LABEL_PADDING_MM * 2.0;
+        let short_id = &label.book_id.to_string()[..8];
+
+        // QRコードは左に正方形で、バーコードは上部に横長で配置し、残りに書名などを記載する
+        let (symbol_width, symbol_height, text_x, text_y) = match symbology {
+            Symbology::Qr => (inner_height, inner_height, inner_height + 2.0, 5.0),
+            Symbology::Code128 => (
+                inner_width,
+                inner_height * 0.55,
+                0.0,
+                inner_height * 0.55 + 4.5,
+            ),
+        };
+        let _ = write!(
+            svg,
+            r##"<g transform="translate({x:.3} {y:.3})"><svg width="{symbol_width:.3}" height="{symbol_height:.3}" viewBox="0 0 {} {}" preserveAspectRatio="none" shape-rendering="crispEdges"><path fill="#000" d="{}"/></svg><text x="{text_x:.3}" y="{text_y:.3}" font-size="3.2">{}</text><text x="{text_x:.3}" y="{:.3}" font-size="2.6">{}</text><text x="{text_x:.3}" y="{:.3}" font-size="2.4" font-family="monospace">{}</text></g>"##,
+            symbol.width,
+            symbol.height,
+            symbol.svg_path(),
+            escape_xml(&truncate(&label.title, 18)),
+            text_y + 4.5,
+            escape_xml(&truncate(&label.author, 22)),
+            text_y + 9.0,
+            short_id,
+        );
+    }
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+/// QRコードに埋め込む、蔵書へのディープリンク(未設定の場合は蔵書のID)
+pub fn book_link(book_id: BookId, config: &LabelConfig) -> String {
+    match &config.link_template {
+        Some(template) => template.replace("{book_id}", &book_id.to_string()),
+        None => book_id.to_string(),
+    }
+}
+
+/// 読み取ったラベルの内容から蔵書のIDを取り出す
+/// 蔵書のID(ハイフンの有無を問わない)と、ディープリンクのURLのいずれも受け付ける
+pub fn parse_book_code(code: &str, config: &LabelConfig) -> AppResult<BookId> {
+    let code = code.trim();
+    let id = config
+        .link_template
+        .as_deref()
+        .and_then(|template| template.split_once("{book_id}"))
+        .and_then(|(prefix, suffix)| code.strip_prefix(prefix)?.strip_suffix(suffix))
+        .unwrap_or(code);
+    BookId::from_str(id)
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars) {
+        Some((i, _)) => format!("{}…", &s[..i]),
+        None => s.to_string(),
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LabelConfig {
+        LabelConfig {
+            link_template: Some("https://library.example.com/books/{book_id}?ref=label".into()),
+        }
+    }
+
+    #[test]
+    fn test_parse_book_code() -> anyhow::Result<()> {
+        let book_id = BookId::new();
+
+        // ID、ハイフン付きのID、ディープリンクのいずれからも蔵書のIDを取り出せる
+        assert_eq!(parse_book_code(&book_id.to_string(), &config())?, book_id);
+        assert_eq!(
+            parse_book_code(&format!(" {} ", book_id.raw().hyphenated()), &config())?,
+            book_id
+        );
+        let link = book_link(book_id, &config());
+        assert_eq!(
+            link,
+            format!("https://library.example.com/books/{book_id}?ref=label")
+        );
+        assert_eq!(parse_book_code(&link, &config())?, book_id);
+
+        // ディープリンクが設定されていない場合、URLは受け付けない
+        assert!(parse_book_code(&link, &LabelConfig::default()).is_err());
+        assert!(parse_book_code("not-a-book", &config()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_symbol() -> anyhow::Result<()> {
+        let book_id = BookId::new();
+
+        let qr = Symbol::for_book(book_id, Symbology::Qr, &config())?;
+        assert_eq!(qr.width, qr.height);
+        let png = qr.to_png(2)?;
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let barcode = Symbol::for_book(book_id, Symbology::Code128, &config())?;
+        // スタート + 32文字 + チェックデジット + ストップ + 左右の余白
+        assert_eq!(barcode.width, 11 * 34 + 13 + CODE128_QUIET_ZONE * 2);
+        let svg = barcode.to_svg(1);
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_sheet() -> anyhow::Result<()> {
+        let label = || SheetLabel {
+            book_id: BookId::new(),
+            title: "<Rust> & Web アプリケーション開発入門".into(),
+            author: "Yuki Toyoda".into(),
+        };
+
+        let svg = render_sheet(&[label(), label()], Symbology::Qr, &config())?;
+        assert_eq!(svg.matches("<g ").count(), 2);
+        assert!(svg.contains("&lt;Rust&gt; &amp; Web"));
+
+        let labels = (0..=LABELS_PER_SHEET).map(|_| label()).collect::<Vec<_>>();
+        assert!(render_sheet(&labels, Symbology::Code128, &config()).is_err());
+
+        Ok(())
+    }
+}
@@ -1,5 +1,9 @@
+pub mod export;
 pub mod extractor;
 pub mod handler;
+pub mod import;
+pub mod job;
+pub mod label;
 pub mod model;
 pub mod openapi;
 pub mod route;
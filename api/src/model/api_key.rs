@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use garde::Validate;
+use kernel::model::{
+    api_key::{ApiKey, ApiKeyScope, IssuedApiKey},
+    id::ApiKeyId,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+/// APIキーのスコープ
+pub enum ApiKeyScopeName {
+    Read,
+    Write,
+    Admin,
+}
+impl From<ApiKeyScope> for ApiKeyScopeName {
+    fn from(value: ApiKeyScope) -> Self {
+        match value {
+            ApiKeyScope::Read => Self::Read,
+            ApiKeyScope::Write => Self::Write,
+            ApiKeyScope::Admin => Self::Admin,
+        }
+    }
+}
+impl From<ApiKeyScopeName> for ApiKeyScope {
+    fn from(value: ApiKeyScopeName) -> Self {
+        match value {
+            ApiKeyScopeName::Read => Self::Read,
+            ApiKeyScopeName::Write => Self::Write,
+            ApiKeyScopeName::Admin => Self::Admin,
+        }
+    }
+}
+
+#[derive(Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// APIキー発行ペイロード
+pub struct CreateApiKeyRequest {
+    #[garde(length(min = 1, max = 255))]
+    pub name: String,
+    #[garde(length(min = 1))]
+    pub scopes: Vec<ApiKeyScopeName>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// APIキー情報のレスポンスモデル
+pub struct ApiKeyResponse {
+    pub id: ApiKeyId,
+    pub name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<ApiKeyScopeName>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(value: ApiKey) -> Self {
+        let ApiKey {
+            id,
+            name,
+            key_prefix,
+            scopes,
+            created_at,
+            last_used_at,
+            revoked_at,
+            ..
+        } = value;
+        Self {
+            id,
+            name,
+            key_prefix,
+            scopes: scopes.into_iter().map(ApiKeyScopeName::from).collect(),
+            created_at,
+            last_used_at,
+            revoked_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// APIキー一覧のレスポンスモデル
+pub struct ApiKeysResponse {
+    pub items: Vec<ApiKeyResponse>,
+}
+impl From<Vec<ApiKey>> for ApiKeysResponse {
+    fn from(value: Vec<ApiKey>) -> Self {
+        Self {
+            items: value.into_iter().map(ApiKeyResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 発行したAPIキーのレスポンスモデル
+/// キー本体(`key`)はこのレスポンスでのみ返却される
+pub struct IssuedApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKeyResponse,
+    pub key: String,
+}
+impl From<IssuedApiKey> for IssuedApiKeyResponse {
+    fn from(value: IssuedApiKey) -> Self {
+        let IssuedApiKey { api_key, secret } = value;
+        Self {
+            api_key: api_key.into(),
+            key: secret.0,
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use garde::Validate;
+use kernel::model::{
+    audit::{AuditLog, AuditLogListOptions, AuditTargetType},
+    id::{AuditLogId, UserId},
+    list::PaginatedList,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 監査ログに記録された操作の対象の種類
+pub enum AuditTargetTypeName {
+    Book,
+    Checkout,
+    User,
+    Backup,
+}
+impl From<AuditTargetType> for AuditTargetTypeName {
+    fn from(value: AuditTargetType) -> Self {
+        match value {
+            AuditTargetType::Book => Self::Book,
+            AuditTargetType::Checkout => Self::Checkout,
+            AuditTargetType::User => Self::User,
+            AuditTargetType::Backup => Self::Backup,
+        }
+    }
+}
+impl From<AuditTargetTypeName> for AuditTargetType {
+    fn from(value: AuditTargetTypeName) -> Self {
+        match value {
+            AuditTargetTypeName::Book => Self::Book,
+            AuditTargetTypeName::Checkout => Self::Checkout,
+            AuditTargetTypeName::User => Self::User,
+            AuditTargetTypeName::Backup => Self::Backup,
+        }
+    }
+}
+
+const DEFAULT_LIMIT: i64 = 50;
+const fn default_limit() -> i64 {
+    DEFAULT_LIMIT
+}
+
+/// 監査ログの検索条件
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogQuery {
+    /// 操作を行ったユーザー
+    #[garde(skip)]
+    pub actor_id: Option<UserId>,
+    /// 操作の対象の種類
+    #[garde(skip)]
+    pub target_type: Option<AuditTargetTypeName>,
+    /// 操作の対象のID
+    #[garde(skip)]
+    pub target_id: Option<String>,
+    /// この日時以降に記録されたもの(RFC 3339)
+    #[garde(skip)]
+    pub from: Option<DateTime<Utc>>,
+    /// この日時より前に記録されたもの(RFC 3339)
+    #[garde(custom(is_after(&self.from)))]
+    pub to: Option<DateTime<Utc>>,
+    #[garde(range(min = 1, max = 100))]
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[garde(range(min = 0))]
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn is_after(
+    from: &Option<DateTime<Utc>>,
+) -> impl FnOnce(&Option<DateTime<Utc>>, &()) -> garde::Result + '_ {
+    move |to, _| match (from, to) {
+        (Some(from), Some(to)) if from > to => {
+            Err(garde::Error::new("to must be later than or equal to from"))
+        }
+        _ => Ok(()),
+    }
+}
+
+impl From<AuditLogQuery> for AuditLogListOptions {
+    fn from(value: AuditLogQuery) -> Self {
+        let AuditLogQuery {
+            actor_id,
+            target_type,
+            target_id,
+            from,
+            to,
+            limit,
+            offset,
+        } = value;
+        Self {
+            actor_id,
+            target_type: target_type.map(AuditTargetType::from),
+            target_id,
+            from,
+            to,
+            limit,
+            offset,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 監査ログのレスポンスモデル
+pub struct AuditLogResponse {
+    pub id: AuditLogId,
+    pub actor_id: Option<UserId>,
+    /// 操作の種類(`book.update`など)
+    pub action: String,
+    pub target_type: AuditTargetTypeName,
+    pub target_id: Option<String>,
+    /// 操作前の対象の内容
+    #[cfg_attr(debug_assertions, schema(value_type = Option<Object>))]
+    pub before: Option<serde_json::Value>,
+    /// 操作後の対象の内容
+    #[cfg_attr(debug_assertions, schema(value_type = Option<Object>))]
+    pub after: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+    pub ip_address: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+impl From<AuditLog> for AuditLogResponse {
+    fn from(value: AuditLog) -> Self {
+        let AuditLog {
+            id,
+            actor_id,
+            action,
+            target_type,
+            target_id,
+            before,
+            after,
+            request_id,
+            ip_address,
+            occurred_at,
+        } = value;
+        Self {
+            id,
+            actor_id,
+            action: action.as_ref().to_string(),
+            target_type: target_type.into(),
+            target_id,
+            before,
+            after,
+            request_id,
+            ip_address,
+            occurred_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// ページングされた監査ログのレスポンスモデル
+pub struct PaginatedAuditLogResponse {
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub items: Vec<AuditLogResponse>,
+}
+impl From<PaginatedList<AuditLog>> for PaginatedAuditLogResponse {
+    fn from(value: PaginatedList<AuditLog>) -> Self {
+        let PaginatedList {
+            total,
+            limit,
+            offset,
+            items,
+        } = value;
+        Self {
+            total,
+            limit,
+            offset,
+            items: items.into_iter().map(AuditLogResponse::from).collect(),
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use kernel::model::id::UserId;
+use kernel::model::{
+    auth::{event::VerifyOidcCallback, OidcAuthorization},
+    id::UserId,
+};
 use serde::{Deserialize, Serialize};
 #[cfg(debug_assertions)]
 use utoipa::ToSchema;
@@ -11,10 +14,59 @@ pub struct LoginRequest {
     pub password: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct AccessTokenResponse {
     pub user_id: UserId,
     pub access_token: String,
 }
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct OidcAuthorizationResponse {
+    /// 利用者をリダイレクトさせるIDプロバイダーの認可エンドポイント
+    pub authorization_url: String,
+    /// クライアントが保持し、コールバックの際に`clientBinding`として送り返す値
+    pub client_binding: String,
+}
+
+impl From<OidcAuthorization> for OidcAuthorizationResponse {
+    fn from(value: OidcAuthorization) -> Self {
+        let OidcAuthorization {
+            authorization_url,
+            client_binding,
+        } = value;
+        Self {
+            authorization_url,
+            client_binding,
+        }
+    }
+}
+
+/// IDプロバイダーからリダイレクトされた際に受け取った値
+#[derive(Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct OidcCallbackRequest {
+    pub code: String,
+    pub state: String,
+    /// 認可リクエストの開始時に受け取った`clientBinding`
+    pub client_binding: String,
+}
+
+impl From<OidcCallbackRequest> for VerifyOidcCallback {
+    fn from(value: OidcCallbackRequest) -> Self {
+        let OidcCallbackRequest {
+            code,
+            state,
+            client_binding,
+        } = value;
+        Self {
+            code,
+            state,
+            client_binding,
+        }
+    }
+}
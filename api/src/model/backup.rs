@@ -0,0 +1,105 @@
+use kernel::model::backup::{BackupOptions, RestoreCount, RestoreMode, RestoreSummary};
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+/// 復元できるバックアップのサイズの上限
+pub const MAX_BACKUP_SIZE: usize = 256 * 1024 * 1024;
+
+/// バックアップの取得条件
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupQuery {
+    /// パスワードのハッシュを含める
+    #[serde(default)]
+    pub include_password_hashes: bool,
+}
+impl From<BackupQuery> for BackupOptions {
+    fn from(value: BackupQuery) -> Self {
+        Self {
+            include_password_hashes: value.include_password_hashes,
+        }
+    }
+}
+
+/// 復元の方法
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum RestoreModeName {
+    /// 既存のデータを残し、重複しないレコードのみを追加する
+    #[default]
+    Merge,
+    /// 既存のユーザー・蔵書・貸出を削除し、バックアップの内容に置き換える
+    /// バックアップに含まれないAPIキー・料金・督促などが残っている場合は行わない
+    /// キオスク・Webhookは残るが、作成者の記録は失われる
+    Replace,
+}
+impl From<RestoreModeName> for RestoreMode {
+    fn from(value: RestoreModeName) -> Self {
+        match value {
+            RestoreModeName::Merge => Self::Merge,
+            RestoreModeName::Replace => Self::Replace,
+        }
+    }
+}
+
+/// 復元の条件
+#[derive(Debug, Deserialize)]
+pub struct RestoreQuery {
+    #[serde(default)]
+    pub mode: RestoreModeName,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 復元の結果
+pub struct RestoreResponse {
+    pub mode: RestoreModeName,
+    pub users: RestoreCountResponse,
+    pub books: RestoreCountResponse,
+    pub checkouts: RestoreCountResponse,
+    pub returned_checkouts: RestoreCountResponse,
+    /// 置き換えにより作成者が記録されなくなったキオスクの件数
+    pub kiosks_without_creator: u64,
+    /// 置き換えにより作成者が記録されなくなったWebhookの件数
+    pub webhooks_without_creator: u64,
+}
+impl RestoreResponse {
+    pub fn new(mode: RestoreModeName, summary: RestoreSummary) -> Self {
+        let RestoreSummary {
+            users,
+            books,
+            checkouts,
+            returned_checkouts,
+            kiosks_without_creator,
+            webhooks_without_creator,
+        } = summary;
+        Self {
+            mode,
+            users: users.into(),
+            books: books.into(),
+            checkouts: checkouts.into(),
+            returned_checkouts: returned_checkouts.into(),
+            kiosks_without_creator,
+            webhooks_without_creator,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 種類ごとの復元した件数
+pub struct RestoreCountResponse {
+    pub restored: u64,
+    /// 既存のレコードと重複した、または参照先が復元されなかったため飛ばした件数
+    pub skipped: u64,
+}
+impl From<RestoreCount> for RestoreCountResponse {
+    fn from(value: RestoreCount) -> Self {
+        let RestoreCount { restored, skipped } = value;
+        Self { restored, skipped }
+    }
+}
@@ -1,10 +1,12 @@
 use super::user::{BookOwner, CheckOutUser};
+use crate::import::{ColumnMapping, CsvEncoding, ImportRow};
 use chrono::{DateTime, Utc};
 use derive_new::new;
 use garde::Validate;
 use kernel::model::{
     book::{
-        event::{CreateBook, UpdateBook},
+        event::{CreateBook, UpdateBook, UpdateBookStatus},
+        status::{BookStatus, BookStatusHistory},
         Book, BookListOptions, CheckoutInfo,
     },
     id::{BookId, CheckoutId, UserId},
@@ -14,17 +16,17 @@ use serde::{Deserialize, Serialize};
 #[cfg(debug_assertions)]
 use utoipa::ToSchema;
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CreateBookRequest {
-    #[garde(length(min = 1))]
+    #[garde(length(chars, min = 1, max = 255))]
     pub title: String,
-    #[garde(length(min = 1))]
+    #[garde(length(chars, min = 1, max = 255))]
     pub author: String,
-    #[garde(length(min = 1))]
+    #[garde(length(chars, min = 1, max = 255))]
     pub isbn: String,
-    #[garde(skip)]
+    #[garde(length(chars, max = 1024))]
     pub description: String,
 }
 impl From<CreateBookRequest> for CreateBook {
@@ -44,17 +46,109 @@ impl From<CreateBookRequest> for CreateBook {
     }
 }
 
+/// CSVによる蔵書の一括登録の条件
 #[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct BookImportQuery {
+    /// 検証のみを行い、登録しない
+    #[garde(skip)]
+    #[serde(default)]
+    pub dry_run: bool,
+    #[garde(skip)]
+    #[serde(default)]
+    pub encoding: CsvEncoding,
+    /// 書名の列の見出し
+    #[garde(inner(length(min = 1)))]
+    pub title_column: Option<String>,
+    /// 著者の列の見出し
+    #[garde(inner(length(min = 1)))]
+    pub author_column: Option<String>,
+    /// ISBNの列の見出し
+    #[garde(inner(length(min = 1)))]
+    pub isbn_column: Option<String>,
+    /// 説明の列の見出し
+    #[garde(inner(length(min = 1)))]
+    pub description_column: Option<String>,
+}
+impl BookImportQuery {
+    /// 指定されなかった列は、既定の見出しとする
+    pub fn column_mapping(&self) -> ColumnMapping {
+        let default = ColumnMapping::default();
+        ColumnMapping {
+            title: self.title_column.clone().unwrap_or(default.title),
+            author: self.author_column.clone().unwrap_or(default.author),
+            isbn: self.isbn_column.clone().unwrap_or(default.isbn),
+            description: self
+                .description_column
+                .clone()
+                .unwrap_or(default.description),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// CSVによる蔵書の一括登録の結果
+pub struct BookImportResponse {
+    pub dry_run: bool,
+    /// 読み取った行数
+    pub total_rows: usize,
+    /// 検証に通った行数
+    pub valid_rows: usize,
+    /// 登録した蔵書のID。検証のみの場合は空
+    pub imported_book_ids: Vec<BookId>,
+    /// 検証に通らなかった行の誤り
+    pub errors: Vec<BookImportErrorResponse>,
+}
+impl BookImportResponse {
+    pub fn new(dry_run: bool, rows: &[ImportRow], imported_book_ids: Vec<BookId>) -> Self {
+        let errors = rows
+            .iter()
+            .filter_map(|row| row.result.as_ref().err().map(|errors| (row.line, errors)))
+            .flat_map(|(line, errors)| {
+                errors.iter().map(move |error| BookImportErrorResponse {
+                    line,
+                    field: error.field.clone(),
+                    column: error.column.clone(),
+                    message: error.message.clone(),
+                })
+            })
+            .collect();
+        Self {
+            dry_run,
+            total_rows: rows.len(),
+            valid_rows: rows.iter().filter(|row| row.result.is_ok()).count(),
+            imported_book_ids,
+            errors,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookImportErrorResponse {
+    /// CSV上の行番号(見出しの行を1とする)
+    pub line: u64,
+    /// 誤りのある項目。行全体を読み取れない場合は`null`
+    pub field: Option<String>,
+    /// 誤りのある項目に対応するCSVの列の見出し
+    pub column: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateBookRequest {
-    #[garde(length(min = 1))]
+    #[garde(length(chars, min = 1, max = 255))]
     pub title: String,
-    #[garde(length(min = 1))]
+    #[garde(length(chars, min = 1, max = 255))]
     pub author: String,
-    #[garde(length(min = 1))]
+    #[garde(length(chars, min = 1, max = 255))]
     pub isbn: String,
-    #[garde(skip)]
+    #[garde(length(chars, max = 1024))]
     pub description: String,
 }
 
@@ -114,6 +208,7 @@ pub struct BookResponse {
     pub isbn: String,
     pub description: String,
     pub owner: BookOwner,
+    pub status: BookStatusName,
     pub checkout_info: Option<BookCheckoutResponse>,
 }
 impl From<Book> for BookResponse {
@@ -125,6 +220,7 @@ impl From<Book> for BookResponse {
             isbn,
             description,
             owner,
+            status,
             checkout_info,
         } = value;
         Self {
@@ -134,6 +230,7 @@ impl From<Book> for BookResponse {
             isbn,
             description,
             owner: owner.into(),
+            status: status.into(),
             checkout_info: checkout_info.map(BookCheckoutResponse::from),
         }
     }
@@ -173,6 +270,8 @@ pub struct BookCheckoutResponse {
     pub id: CheckoutId,
     pub checked_out_by: CheckOutUser,
     pub checked_out_at: DateTime<Utc>,
+    /// 返却期限
+    pub due_at: DateTime<Utc>,
 }
 impl From<CheckoutInfo> for BookCheckoutResponse {
     fn from(value: CheckoutInfo) -> Self {
@@ -180,11 +279,128 @@ impl From<CheckoutInfo> for BookCheckoutResponse {
             checkout_id,
             checked_out_by,
             checked_out_at,
+            due_at,
         } = value;
         Self {
             id: checkout_id,
             checked_out_by: checked_out_by.into(),
             checked_out_at,
+            due_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+/// 蔵書の状態
+pub enum BookStatusName {
+    Available,
+    OnLoan,
+    Lost,
+    Damaged,
+    InRepair,
+    Withdrawn,
+}
+impl From<BookStatus> for BookStatusName {
+    fn from(value: BookStatus) -> Self {
+        match value {
+            BookStatus::Available => Self::Available,
+            BookStatus::OnLoan => Self::OnLoan,
+            BookStatus::Lost => Self::Lost,
+            BookStatus::Damaged => Self::Damaged,
+            BookStatus::InRepair => Self::InRepair,
+            BookStatus::Withdrawn => Self::Withdrawn,
+        }
+    }
+}
+impl From<BookStatusName> for BookStatus {
+    fn from(value: BookStatusName) -> Self {
+        match value {
+            BookStatusName::Available => Self::Available,
+            BookStatusName::OnLoan => Self::OnLoan,
+            BookStatusName::Lost => Self::Lost,
+            BookStatusName::Damaged => Self::Damaged,
+            BookStatusName::InRepair => Self::InRepair,
+            BookStatusName::Withdrawn => Self::Withdrawn,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 蔵書の状態変更ペイロード
+pub struct UpdateBookStatusRequest {
+    #[garde(skip)]
+    pub status: BookStatusName,
+    /// 変更の理由など
+    #[garde(length(max = 1024))]
+    pub note: Option<String>,
+}
+
+#[derive(new)]
+pub struct UpdateBookStatusRequestWithIds(BookId, UserId, UpdateBookStatusRequest);
+impl From<UpdateBookStatusRequestWithIds> for UpdateBookStatus {
+    fn from(value: UpdateBookStatusRequestWithIds) -> Self {
+        let UpdateBookStatusRequestWithIds(
+            book_id,
+            user_id,
+            UpdateBookStatusRequest { status, note },
+        ) = value;
+        UpdateBookStatus {
+            book_id,
+            status: status.into(),
+            note,
+            requested_user: user_id,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 蔵書の状態の変更履歴のレスポンス
+pub struct BookStatusHistoryResponse {
+    pub status: BookStatusName,
+    /// 登録時の履歴の場合は`null`
+    pub previous_status: Option<BookStatusName>,
+    pub changed_by: Option<UserId>,
+    pub note: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+impl From<BookStatusHistory> for BookStatusHistoryResponse {
+    fn from(value: BookStatusHistory) -> Self {
+        let BookStatusHistory {
+            status,
+            previous_status,
+            changed_by,
+            note,
+            changed_at,
+            ..
+        } = value;
+        Self {
+            status: status.into(),
+            previous_status: previous_status.map(BookStatusName::from),
+            changed_by,
+            note,
+            changed_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookStatusHistoriesResponse {
+    pub items: Vec<BookStatusHistoryResponse>,
+}
+impl From<Vec<BookStatusHistory>> for BookStatusHistoriesResponse {
+    fn from(value: Vec<BookStatusHistory>) -> Self {
+        Self {
+            items: value
+                .into_iter()
+                .map(BookStatusHistoryResponse::from)
+                .collect(),
         }
     }
 }
@@ -1,14 +1,20 @@
+use axum::response::IntoResponse;
 use chrono::{DateTime, Utc};
+use garde::Validate;
 use kernel::model::{
-    checkout::{Checkout, CheckoutBook},
-    id::{BookId, CheckoutId, UserId},
+    checkout::{
+        BatchItemOutcome, BatchItemResult, BatchMode, Checkout, CheckoutBook,
+        CheckoutHistoryOptions,
+    },
+    id::{BookId, CheckoutId, KioskId, UserId},
+    list::PaginatedList,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 #[cfg(debug_assertions)]
 use utoipa::ToSchema;
 
 /// 貸出情報内の本の情報を返すレスポンス
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CheckoutBookResponse {
@@ -35,7 +41,7 @@ impl From<CheckoutBook> for CheckoutBookResponse {
 }
 
 /// 貸出情報のレスポンス
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CheckoutResponse {
@@ -43,6 +49,12 @@ pub struct CheckoutResponse {
     pub checked_out_by: UserId,
     pub checked_out_at: DateTime<Utc>,
     pub returned_at: Option<DateTime<Utc>>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+    /// 貸出を行ったキオスク。利用者自身が操作した場合は`null`
+    pub checked_out_via: Option<KioskId>,
+    /// 返却を行ったキオスク
+    pub returned_via: Option<KioskId>,
     pub book: CheckoutBookResponse,
 }
 impl From<Checkout> for CheckoutResponse {
@@ -52,6 +64,10 @@ impl From<Checkout> for CheckoutResponse {
             checked_out_by,
             checked_out_at,
             returned_at,
+            due_at,
+            renewal_count,
+            checked_out_via,
+            returned_via,
             book,
         } = value;
         Self {
@@ -59,6 +75,10 @@ impl From<Checkout> for CheckoutResponse {
             checked_out_by,
             checked_out_at,
             returned_at,
+            due_at,
+            renewal_count,
+            checked_out_via,
+            returned_via,
             book: book.into(),
         }
     }
@@ -78,3 +98,174 @@ impl From<Vec<Checkout>> for CheckoutsResponse {
         }
     }
 }
+
+const DEFAULT_LIMIT: i64 = 20;
+const fn default_limit() -> i64 {
+    DEFAULT_LIMIT
+}
+
+/// 貸出履歴の取得条件
+#[derive(Debug, Deserialize, Validate)]
+pub struct CheckoutHistoryQuery {
+    #[garde(range(min = 0, max = 100))]
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[garde(range(min = 0))]
+    #[serde(default)]
+    pub offset: i64,
+    /// この日時以降に貸し出されたもの(RFC 3339)
+    #[garde(skip)]
+    pub from: Option<DateTime<Utc>>,
+    /// この日時より前に貸し出されたもの(RFC 3339)
+    #[garde(custom(is_after(&self.from)))]
+    pub to: Option<DateTime<Utc>>,
+}
+
+fn is_after(
+    from: &Option<DateTime<Utc>>,
+) -> impl FnOnce(&Option<DateTime<Utc>>, &()) -> garde::Result + '_ {
+    move |to, _| match (from, to) {
+        (Some(from), Some(to)) if from > to => {
+            Err(garde::Error::new("to must be later than or equal to from"))
+        }
+        _ => Ok(()),
+    }
+}
+
+impl From<CheckoutHistoryQuery> for CheckoutHistoryOptions {
+    fn from(value: CheckoutHistoryQuery) -> Self {
+        let CheckoutHistoryQuery {
+            limit,
+            offset,
+            from,
+            to,
+        } = value;
+        Self {
+            limit,
+            offset,
+            from,
+            to,
+        }
+    }
+}
+
+/// ページングされた貸出履歴のレスポンス
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PaginatedCheckoutResponse {
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub items: Vec<CheckoutResponse>,
+}
+
+impl From<PaginatedList<Checkout>> for PaginatedCheckoutResponse {
+    fn from(value: PaginatedList<Checkout>) -> Self {
+        let PaginatedList {
+            total,
+            limit,
+            offset,
+            items,
+        } = value;
+        Self {
+            total,
+            limit,
+            offset,
+            items: items.into_iter().map(CheckoutResponse::from).collect(),
+        }
+    }
+}
+
+/// 一度に貸出・返却できる蔵書の冊数の上限
+const MAX_BATCH_SIZE: usize = 50;
+
+/// 複数の蔵書の一括貸出・返却のペイロード
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BatchCheckoutRequest {
+    /// 処理する蔵書のID。指定した順に処理する
+    #[garde(length(min = 1, max = MAX_BATCH_SIZE))]
+    pub book_ids: Vec<BookId>,
+    /// `true`の場合、1冊でも失敗したらすべての蔵書の処理を取り消す
+    #[garde(skip)]
+    #[serde(default)]
+    pub atomic: bool,
+}
+impl BatchCheckoutRequest {
+    pub fn mode(&self) -> BatchMode {
+        if self.atomic {
+            BatchMode::AllOrNothing
+        } else {
+            BatchMode::BestEffort
+        }
+    }
+}
+
+/// 一括処理における蔵書ごとの結果の種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+pub enum BatchItemStatus {
+    Succeeded,
+    /// 他の蔵書の処理が失敗したため取り消された
+    RolledBack,
+    Failed,
+}
+
+/// 一括処理における蔵書ごとの結果
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BatchItemResponse {
+    pub book_id: BookId,
+    pub status: BatchItemStatus,
+    /// 貸出または返却した貸出のID
+    pub checkout_id: Option<CheckoutId>,
+    /// 失敗した場合に、1冊ずつ処理した場合に返されるHTTPステータスコード
+    pub error_status: Option<u16>,
+    pub error_message: Option<String>,
+}
+impl From<BatchItemResult> for BatchItemResponse {
+    fn from(value: BatchItemResult) -> Self {
+        let BatchItemResult { book_id, outcome } = value;
+        let (status, checkout_id, error) = match outcome {
+            BatchItemOutcome::Succeeded(id) => (BatchItemStatus::Succeeded, Some(id), None),
+            BatchItemOutcome::RolledBack(id) => (BatchItemStatus::RolledBack, Some(id), None),
+            BatchItemOutcome::Failed(e) => (BatchItemStatus::Failed, None, Some(e)),
+        };
+        let (error_status, error_message) = match error {
+            Some(e) => {
+                let message = e.to_string();
+                (Some(e.into_response().status().as_u16()), Some(message))
+            }
+            None => (None, None),
+        };
+        Self {
+            book_id,
+            status,
+            checkout_id,
+            error_status,
+            error_message,
+        }
+    }
+}
+
+/// 一括貸出・返却の結果のレスポンス
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BatchCheckoutResponse {
+    /// すべての蔵書の処理に成功したかどうか
+    pub all_succeeded: bool,
+    /// 指定された順の蔵書ごとの結果
+    pub items: Vec<BatchItemResponse>,
+}
+impl From<Vec<BatchItemResult>> for BatchCheckoutResponse {
+    fn from(value: Vec<BatchItemResult>) -> Self {
+        Self {
+            all_succeeded: value.iter().all(BatchItemResult::is_succeeded),
+            items: value.into_iter().map(BatchItemResponse::from).collect(),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use super::book::BookStatusName;
+use chrono::{DateTime, Utc};
+use kernel::model::{
+    book::{status::BookStatus, Book},
+    id::BookId,
+    live::LiveEventKind,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+/// 蔵書の購読の接続で、クライアントから受け取るメッセージ
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BookWatchRequest {
+    /// 蔵書の貸出状況の購読を開始する
+    #[serde(rename_all = "camelCase")]
+    Subscribe { book_id: BookId },
+    /// 蔵書の貸出状況の購読を終了する
+    #[serde(rename_all = "camelCase")]
+    Unsubscribe { book_id: BookId },
+    /// 接続が維持されているかを確認する
+    Ping,
+}
+
+/// 蔵書の購読の接続で、クライアントに送るメッセージ
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BookWatchMessage {
+    /// 購読を開始した。購読を開始した時点の貸出状況を含む
+    Subscribed(BookAvailabilityResponse),
+    #[serde(rename_all = "camelCase")]
+    Unsubscribed {
+        book_id: BookId,
+    },
+    /// 購読中の蔵書の貸出状況が変化した
+    Availability(BookAvailabilityResponse),
+    /// 購読中の蔵書が削除された。購読は終了する
+    #[serde(rename_all = "camelCase")]
+    Deleted {
+        book_id: BookId,
+    },
+    Pong,
+    /// 受け取ったメッセージを処理できなかった
+    #[serde(rename_all = "camelCase")]
+    Error {
+        book_id: Option<BookId>,
+        message: String,
+    },
+}
+
+impl BookWatchMessage {
+    pub fn error(book_id: Option<BookId>, message: impl Into<String>) -> Self {
+        Self::Error {
+            book_id,
+            message: message.into(),
+        }
+    }
+}
+
+/// 蔵書の貸出状況
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookAvailabilityResponse {
+    pub book_id: BookId,
+    pub status: BookStatusName,
+    /// 貸出中の場合の返却期限
+    pub due_at: Option<DateTime<Utc>>,
+}
+
+impl From<&Book> for BookAvailabilityResponse {
+    fn from(book: &Book) -> Self {
+        Self {
+            book_id: book.id,
+            status: book.status.into(),
+            due_at: book.checkout_info.as_ref().map(|checkout| checkout.due_at),
+        }
+    }
+}
+
+impl BookAvailabilityResponse {
+    /// 配信されたイベントから、変化した後の貸出状況を求める
+    /// 貸出状況が変化しないイベントの場合は`None`を返す
+    pub fn from_live_event(kind: &LiveEventKind) -> Option<Self> {
+        let (status, due_at) = match kind {
+            LiveEventKind::BookStatusChanged { status, .. } => (*status, None),
+            LiveEventKind::CheckedOut(checkout) | LiveEventKind::Renewed(checkout) => {
+                (BookStatus::OnLoan, Some(checkout.due_at))
+            }
+            LiveEventKind::Returned(_) => (BookStatus::Available, None),
+            LiveEventKind::DeclaredLost(_) => (BookStatus::Lost, None),
+            LiveEventKind::BookCreated(_)
+            | LiveEventKind::BookUpdated(_)
+            | LiveEventKind::BookDeleted { .. } => return None,
+        };
+        Some(Self {
+            book_id: kind.book_id(),
+            status: status.into(),
+            due_at,
+        })
+    }
+}
@@ -0,0 +1,25 @@
+use serde::Deserialize;
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+/// エクスポートの形式
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// 1行に1件のJSONを出力する
+    Ndjson,
+    /// MARC21(ISO 2709)。蔵書のみ
+    Marc,
+    /// MARCXML。蔵書のみ
+    Marcxml,
+}
+
+/// エクスポートの条件
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
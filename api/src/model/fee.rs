@@ -0,0 +1,160 @@
+use chrono::{DateTime, Utc};
+use garde::Validate;
+use kernel::model::{
+    fee::{FeeEntry, FeeEntryKind, FeeLedger, FeeSchedule},
+    id::{BookId, CheckoutId, FeeEntryId, UserId},
+};
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 料金表の更新ペイロード・レスポンスモデル
+/// 金額はすべて円単位
+pub struct FeeScheduleRequest {
+    /// 返却期限を1日過ぎるごとに課す延滞料
+    #[garde(range(min = 0))]
+    pub overdue_fee_per_day: i64,
+    /// 1回の貸出あたりの延滞料の上限。省略した場合は上限なし
+    #[garde(range(min = 0))]
+    pub max_overdue_fee: Option<i64>,
+    /// 紛失の届け出時に課す料金
+    #[garde(range(min = 0))]
+    pub lost_book_fee: i64,
+    /// 未払いの残高がこの金額を超えると新たに借りられなくなる。省略した場合は制限しない
+    #[garde(range(min = 0))]
+    pub checkout_block_threshold: Option<i64>,
+}
+impl From<FeeScheduleRequest> for FeeSchedule {
+    fn from(value: FeeScheduleRequest) -> Self {
+        let FeeScheduleRequest {
+            overdue_fee_per_day,
+            max_overdue_fee,
+            lost_book_fee,
+            checkout_block_threshold,
+        } = value;
+        Self {
+            overdue_fee_per_day,
+            max_overdue_fee,
+            lost_book_fee,
+            checkout_block_threshold,
+        }
+    }
+}
+impl From<FeeSchedule> for FeeScheduleRequest {
+    fn from(value: FeeSchedule) -> Self {
+        let FeeSchedule {
+            overdue_fee_per_day,
+            max_overdue_fee,
+            lost_book_fee,
+            checkout_block_threshold,
+        } = value;
+        Self {
+            overdue_fee_per_day,
+            max_overdue_fee,
+            lost_book_fee,
+            checkout_block_threshold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+/// 台帳の記録の種類
+pub enum FeeEntryKindName {
+    Overdue,
+    Lost,
+    Payment,
+    Waiver,
+}
+impl From<FeeEntryKind> for FeeEntryKindName {
+    fn from(value: FeeEntryKind) -> Self {
+        match value {
+            FeeEntryKind::Overdue => Self::Overdue,
+            FeeEntryKind::Lost => Self::Lost,
+            FeeEntryKind::Payment => Self::Payment,
+            FeeEntryKind::Waiver => Self::Waiver,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 台帳の記録のレスポンスモデル
+pub struct FeeEntryResponse {
+    pub id: FeeEntryId,
+    pub kind: FeeEntryKindName,
+    /// 請求は正、支払い・免除は負の金額
+    pub amount: i64,
+    pub checkout_id: Option<CheckoutId>,
+    pub book_id: Option<BookId>,
+    pub note: Option<String>,
+    pub recorded_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<FeeEntry> for FeeEntryResponse {
+    fn from(value: FeeEntry) -> Self {
+        let FeeEntry {
+            id,
+            kind,
+            amount,
+            checkout_id,
+            book_id,
+            note,
+            recorded_by,
+            created_at,
+            ..
+        } = value;
+        Self {
+            id,
+            kind: kind.into(),
+            amount,
+            checkout_id,
+            book_id,
+            note,
+            recorded_by,
+            created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// ユーザーの料金の台帳のレスポンスモデル
+pub struct FeeLedgerResponse {
+    pub user_id: UserId,
+    /// 未払いの残高
+    pub balance: i64,
+    /// 新しい順の記録
+    pub entries: Vec<FeeEntryResponse>,
+}
+impl From<FeeLedger> for FeeLedgerResponse {
+    fn from(value: FeeLedger) -> Self {
+        let FeeLedger {
+            user_id,
+            balance,
+            entries,
+        } = value;
+        Self {
+            user_id,
+            balance,
+            entries: entries.into_iter().map(FeeEntryResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 支払い・免除の記録ペイロード
+pub struct FeeCreditRequest {
+    /// 支払われた、または免除する金額
+    #[garde(range(min = 1))]
+    pub amount: i64,
+    #[garde(length(max = 1024))]
+    pub note: Option<String>,
+}
@@ -0,0 +1,46 @@
+use kernel::model::migration::PendingMigration;
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// データベースのマイグレーションの適用状況
+pub struct MigrationStatusResponse {
+    /// 未適用のマイグレーションがない
+    pub up_to_date: bool,
+    /// 未適用のマイグレーション(適用する順)
+    pub pending: Vec<PendingMigrationResponse>,
+}
+impl From<Vec<PendingMigration>> for MigrationStatusResponse {
+    fn from(value: Vec<PendingMigration>) -> Self {
+        Self {
+            up_to_date: value.is_empty(),
+            pending: value
+                .into_iter()
+                .map(PendingMigrationResponse::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PendingMigrationResponse {
+    pub version: i64,
+    pub description: String,
+}
+impl From<PendingMigration> for PendingMigrationResponse {
+    fn from(value: PendingMigration) -> Self {
+        let PendingMigration {
+            version,
+            description,
+        } = value;
+        Self {
+            version,
+            description,
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use garde::Validate;
+use kernel::model::{
+    checkout::BatchItemResult,
+    id::{KioskId, UserId},
+    kiosk::{IssuedKiosk, Kiosk},
+    user::User,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use crate::model::checkout::{BatchCheckoutRequest, BatchCheckoutResponse};
+
+#[derive(Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// キオスク登録ペイロード
+pub struct CreateKioskRequest {
+    /// 設置場所など、キオスクを識別するための名前
+    #[garde(length(min = 1, max = 255))]
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// キオスク情報のレスポンスモデル
+pub struct KioskResponse {
+    pub id: KioskId,
+    pub name: String,
+    pub key_prefix: String,
+    pub created_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+impl From<Kiosk> for KioskResponse {
+    fn from(value: Kiosk) -> Self {
+        let Kiosk {
+            id,
+            name,
+            key_prefix,
+            created_by,
+            created_at,
+            last_used_at,
+            revoked_at,
+        } = value;
+        Self {
+            id,
+            name,
+            key_prefix,
+            created_by,
+            created_at,
+            last_used_at,
+            revoked_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// キオスク一覧のレスポンスモデル
+pub struct KiosksResponse {
+    pub items: Vec<KioskResponse>,
+}
+impl From<Vec<Kiosk>> for KiosksResponse {
+    fn from(value: Vec<Kiosk>) -> Self {
+        Self {
+            items: value.into_iter().map(KioskResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 登録したキオスクのレスポンスモデル
+/// 資格情報本体(`key`)はこのレスポンスでのみ返却される
+pub struct IssuedKioskResponse {
+    #[serde(flatten)]
+    pub kiosk: KioskResponse,
+    pub key: String,
+}
+impl From<IssuedKiosk> for IssuedKioskResponse {
+    fn from(value: IssuedKiosk) -> Self {
+        let IssuedKiosk { kiosk, secret } = value;
+        Self {
+            kiosk: kiosk.into(),
+            key: secret.0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// キオスクからの貸出・返却のペイロード
+pub struct KioskCheckoutRequest {
+    /// 読み取ったバッジのコード
+    #[garde(length(min = 1, max = 255))]
+    pub badge_code: String,
+    #[serde(flatten)]
+    #[garde(dive)]
+    pub books: BatchCheckoutRequest,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// キオスクからの貸出・返却の結果のレスポンスモデル
+/// 端末の画面で操作したユーザーを確認できるよう、ユーザーの名前を含める
+pub struct KioskCheckoutResponse {
+    pub user_id: UserId,
+    pub user_name: String,
+    #[serde(flatten)]
+    pub result: BatchCheckoutResponse,
+}
+impl KioskCheckoutResponse {
+    pub fn new(user: User, results: Vec<BatchItemResult>) -> Self {
+        Self {
+            user_id: user.id,
+            user_name: user.name,
+            result: results.into(),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use garde::Validate;
+use kernel::model::id::BookId;
+use serde::Deserialize;
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use crate::label::{Symbology, LABELS_PER_SHEET};
+
+/// ラベルの符号化方式
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum SymbologyName {
+    #[default]
+    Qr,
+    Code128,
+}
+impl From<SymbologyName> for Symbology {
+    fn from(value: SymbologyName) -> Self {
+        match value {
+            SymbologyName::Qr => Self::Qr,
+            SymbologyName::Code128 => Self::Code128,
+        }
+    }
+}
+
+/// ラベルの画像形式
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum LabelFormat {
+    Png,
+    #[default]
+    Svg,
+}
+
+const DEFAULT_SCALE: u32 = 8;
+const fn default_scale() -> u32 {
+    DEFAULT_SCALE
+}
+
+/// ラベル画像の取得条件
+#[derive(Debug, Deserialize, Validate)]
+pub struct LabelQuery {
+    #[garde(skip)]
+    #[serde(default)]
+    pub format: LabelFormat,
+    #[garde(skip)]
+    #[serde(default)]
+    pub symbology: SymbologyName,
+    /// 1モジュールあたりのピクセル数
+    #[garde(range(min = 1, max = 32))]
+    #[serde(default = "default_scale")]
+    pub scale: u32,
+}
+
+/// ラベルシートの作成ペイロード
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct LabelSheetRequest {
+    /// ラベルを作成する蔵書のID。指定した順に左上から並べる
+    #[garde(length(min = 1, max = LABELS_PER_SHEET))]
+    pub book_ids: Vec<BookId>,
+    #[garde(skip)]
+    #[serde(default)]
+    pub symbology: SymbologyName,
+}
+
+/// 読み取ったラベルから蔵書を検索する条件
+#[derive(Debug, Deserialize, Validate)]
+pub struct BookLookupQuery {
+    /// ラベルから読み取った文字列(蔵書のID、またはディープリンクのURL)
+    #[garde(length(min = 1))]
+    pub code: String,
+}
@@ -0,0 +1,169 @@
+use garde::Validate;
+use kernel::model::{
+    id::UserId,
+    loan_policy::{LoanPolicy, LoanPolicyOverride, RoleLoanPolicy, UserLoanPolicy},
+};
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use crate::model::user::RoleName;
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 貸出ポリシーのレスポンスモデル
+pub struct LoanPolicyResponse {
+    pub max_loans: i32,
+    pub loan_period_days: i32,
+    pub max_renewals: i32,
+}
+impl From<LoanPolicy> for LoanPolicyResponse {
+    fn from(value: LoanPolicy) -> Self {
+        let LoanPolicy {
+            max_loans,
+            loan_period_days,
+            max_renewals,
+        } = value;
+        Self {
+            max_loans,
+            loan_period_days,
+            max_renewals,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// ロールごとの貸出ポリシーのレスポンスモデル
+pub struct RoleLoanPolicyResponse {
+    pub role: RoleName,
+    pub policy: LoanPolicyResponse,
+}
+impl From<RoleLoanPolicy> for RoleLoanPolicyResponse {
+    fn from(value: RoleLoanPolicy) -> Self {
+        let RoleLoanPolicy { role, policy } = value;
+        Self {
+            role: RoleName::from(role),
+            policy: policy.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// RoleLoanPolicyResponseを一覧で返すためのモデル
+pub struct RoleLoanPoliciesResponse {
+    pub items: Vec<RoleLoanPolicyResponse>,
+}
+impl From<Vec<RoleLoanPolicy>> for RoleLoanPoliciesResponse {
+    fn from(value: Vec<RoleLoanPolicy>) -> Self {
+        Self {
+            items: value
+                .into_iter()
+                .map(RoleLoanPolicyResponse::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// ロールの貸出ポリシー更新ペイロード
+pub struct UpdateLoanPolicyRequest {
+    #[garde(range(min = 0))]
+    pub max_loans: i32,
+    #[garde(range(min = 1))]
+    pub loan_period_days: i32,
+    #[garde(range(min = 0))]
+    pub max_renewals: i32,
+}
+impl From<UpdateLoanPolicyRequest> for LoanPolicy {
+    fn from(value: UpdateLoanPolicyRequest) -> Self {
+        let UpdateLoanPolicyRequest {
+            max_loans,
+            loan_period_days,
+            max_renewals,
+        } = value;
+        Self {
+            max_loans,
+            loan_period_days,
+            max_renewals,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// ユーザーごとの貸出ポリシー上書きのペイロード・レスポンスモデル
+/// 省略した項目はロールのポリシーに従う
+pub struct LoanPolicyOverrideRequest {
+    #[garde(range(min = 0))]
+    pub max_loans: Option<i32>,
+    #[garde(range(min = 1))]
+    pub loan_period_days: Option<i32>,
+    #[garde(range(min = 0))]
+    pub max_renewals: Option<i32>,
+}
+impl From<LoanPolicyOverrideRequest> for LoanPolicyOverride {
+    fn from(value: LoanPolicyOverrideRequest) -> Self {
+        let LoanPolicyOverrideRequest {
+            max_loans,
+            loan_period_days,
+            max_renewals,
+        } = value;
+        Self {
+            max_loans,
+            loan_period_days,
+            max_renewals,
+        }
+    }
+}
+impl From<LoanPolicyOverride> for LoanPolicyOverrideRequest {
+    fn from(value: LoanPolicyOverride) -> Self {
+        let LoanPolicyOverride {
+            max_loans,
+            loan_period_days,
+            max_renewals,
+        } = value;
+        Self {
+            max_loans,
+            loan_period_days,
+            max_renewals,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// ユーザーに適用される貸出ポリシーのレスポンスモデル
+pub struct UserLoanPolicyResponse {
+    pub user_id: UserId,
+    /// ユーザーのロールに設定されたポリシー
+    pub role_policy: LoanPolicyResponse,
+    /// ユーザーごとの上書き設定
+    pub user_override: Option<LoanPolicyOverrideRequest>,
+    /// 上書き設定を適用した、実際に適用されるポリシー
+    pub effective: LoanPolicyResponse,
+}
+impl From<UserLoanPolicy> for UserLoanPolicyResponse {
+    fn from(value: UserLoanPolicy) -> Self {
+        let effective = value.effective();
+        let UserLoanPolicy {
+            user_id,
+            role_policy,
+            user_override,
+        } = value;
+        Self {
+            user_id,
+            role_policy: role_policy.into(),
+            user_override: user_override.map(LoanPolicyOverrideRequest::from),
+            effective: effective.into(),
+        }
+    }
+}
@@ -1,11 +1,13 @@
+use chrono::{DateTime, Utc};
 use derive_new::new;
 use garde::Validate;
 use kernel::model::{
+    book::event::ReassignBookOwner,
     id::UserId,
     role::Role,
     user::{
-        event::{CreateUser, UpdateUserPassword, UpdateUserRole},
-        User,
+        event::{CreateUser, UpdateUserBadge, UpdateUserPassword, UpdateUserRole},
+        User, UserDeletionBlockers,
     },
 };
 use serde::{Deserialize, Serialize};
@@ -55,6 +57,7 @@ pub struct UserResponse {
     pub name: String,
     pub email: String,
     pub role: RoleName,
+    pub deactivated_at: Option<DateTime<Utc>>,
 }
 impl From<User> for UserResponse {
     fn from(value: User) -> Self {
@@ -63,12 +66,14 @@ impl From<User> for UserResponse {
             name,
             email,
             role,
+            deactivated_at,
         } = value;
         Self {
             id,
             name,
             email,
             role: RoleName::from(role),
+            deactivated_at,
         }
     }
 }
@@ -106,7 +111,7 @@ impl From<UpdateUserPasswordRequestWithUserId> for UpdateUserPassword {
     }
 }
 
-#[derive(Deserialize, Validate)]
+#[derive(new, Deserialize, Validate)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 #[serde(rename_all = "camelCase")]
 /// ユーザー作成ペイロード
@@ -118,6 +123,17 @@ pub struct CreateUserRequest {
     #[garde(length(min = 1))]
     password: String,
 }
+impl CreateUserRequest {
+    /// 監査ログに記録する、作成するユーザーの内容
+    /// パスワードは記録しない
+    pub fn audit_snapshot(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "email": self.email,
+            "role": RoleName::User,
+        })
+    }
+}
 impl From<CreateUserRequest> for CreateUser {
     fn from(value: CreateUserRequest) -> Self {
         let CreateUserRequest {
@@ -125,15 +141,17 @@ impl From<CreateUserRequest> for CreateUser {
             email,
             password,
         } = value;
+        // 利用者の登録で作成されるユーザーは、常に一般の利用者とする
         Self {
             name,
             email,
             password,
+            role: Role::User,
         }
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 #[serde(rename_all = "camelCase")]
 /// ロール更新ペイロード
@@ -154,6 +172,74 @@ impl From<UpdateUserRoleRequestWithUserId> for UpdateUserRole {
     }
 }
 
+#[derive(Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// バッジ登録ペイロード
+pub struct UpdateUserBadgeRequest {
+    /// 社員証・利用者カードから読み取ったコード
+    #[garde(length(min = 1, max = 255))]
+    badge_code: String,
+}
+#[derive(new)]
+/// ユーザーIDを持つバッジ登録ペイロード
+pub struct UpdateUserBadgeRequestWithUserId(UserId, UpdateUserBadgeRequest);
+impl From<UpdateUserBadgeRequestWithUserId> for UpdateUserBadge {
+    fn from(value: UpdateUserBadgeRequestWithUserId) -> Self {
+        let UpdateUserBadgeRequestWithUserId(user_id, UpdateUserBadgeRequest { badge_code }) =
+            value;
+        Self {
+            user_id,
+            badge_code: Some(badge_code),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 蔵書の所有者付け替えペイロード
+pub struct ReassignBooksRequest {
+    new_owner_id: UserId,
+}
+#[derive(new)]
+/// 付け替え元のユーザーIDを持つ蔵書の所有者付け替えペイロード
+pub struct ReassignBooksRequestWithUserId(UserId, ReassignBooksRequest);
+impl From<ReassignBooksRequestWithUserId> for ReassignBookOwner {
+    fn from(value: ReassignBooksRequestWithUserId) -> Self {
+        let ReassignBooksRequestWithUserId(current_owner, ReassignBooksRequest { new_owner_id }) =
+            value;
+        Self {
+            current_owner,
+            new_owner: new_owner_id,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// ユーザーの物理削除を妨げている要因のレスポンスモデル
+pub struct UserDeletionBlockersResponse {
+    pub owned_books: i64,
+    pub unreturned_checkouts: i64,
+    pub fee_entries: i64,
+}
+impl From<UserDeletionBlockers> for UserDeletionBlockersResponse {
+    fn from(value: UserDeletionBlockers) -> Self {
+        let UserDeletionBlockers {
+            owned_books,
+            unreturned_checkouts,
+            fee_entries,
+        } = value;
+        Self {
+            owned_books,
+            unreturned_checkouts,
+            fee_entries,
+        }
+    }
+}
+
 #[derive(Serialize, Debug, Deserialize)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 #[serde(rename_all = "camelCase")]
@@ -0,0 +1,238 @@
+use chrono::{DateTime, Utc};
+use garde::Validate;
+use kernel::model::{
+    id::{UserId, WebhookDeliveryId, WebhookEventId, WebhookId},
+    webhook::{IssuedWebhook, Webhook, WebhookDelivery, WebhookDeliveryStatus, WebhookEventType},
+};
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+/// Webhookで通知するイベントの種類
+pub enum WebhookEventName {
+    #[serde(rename = "book.created")]
+    BookCreated,
+    #[serde(rename = "book.updated")]
+    BookUpdated,
+    #[serde(rename = "checkout.created")]
+    CheckoutCreated,
+    #[serde(rename = "checkout.returned")]
+    CheckoutReturned,
+    #[serde(rename = "user.created")]
+    UserCreated,
+}
+impl From<WebhookEventType> for WebhookEventName {
+    fn from(value: WebhookEventType) -> Self {
+        match value {
+            WebhookEventType::BookCreated => Self::BookCreated,
+            WebhookEventType::BookUpdated => Self::BookUpdated,
+            WebhookEventType::CheckoutCreated => Self::CheckoutCreated,
+            WebhookEventType::CheckoutReturned => Self::CheckoutReturned,
+            WebhookEventType::UserCreated => Self::UserCreated,
+        }
+    }
+}
+impl From<WebhookEventName> for WebhookEventType {
+    fn from(value: WebhookEventName) -> Self {
+        match value {
+            WebhookEventName::BookCreated => Self::BookCreated,
+            WebhookEventName::BookUpdated => Self::BookUpdated,
+            WebhookEventName::CheckoutCreated => Self::CheckoutCreated,
+            WebhookEventName::CheckoutReturned => Self::CheckoutReturned,
+            WebhookEventName::UserCreated => Self::UserCreated,
+        }
+    }
+}
+
+#[derive(Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// Webhook登録ペイロード
+pub struct CreateWebhookRequest {
+    /// イベントを送信するURL(http または https)
+    #[garde(url, length(max = 2048), custom(is_http_url))]
+    pub url: String,
+    /// 通知するイベントの種類
+    #[garde(length(min = 1))]
+    pub events: Vec<WebhookEventName>,
+}
+
+fn is_http_url(url: &str, _: &()) -> garde::Result {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(garde::Error::new("url must use http or https"))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// Webhook情報のレスポンスモデル
+pub struct WebhookResponse {
+    pub id: WebhookId,
+    pub url: String,
+    pub events: Vec<WebhookEventName>,
+    pub created_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<Webhook> for WebhookResponse {
+    fn from(value: Webhook) -> Self {
+        let Webhook {
+            id,
+            url,
+            events,
+            created_by,
+            created_at,
+        } = value;
+        Self {
+            id,
+            url,
+            events: events.into_iter().map(WebhookEventName::from).collect(),
+            created_by,
+            created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// Webhook一覧のレスポンスモデル
+pub struct WebhooksResponse {
+    pub items: Vec<WebhookResponse>,
+}
+impl From<Vec<Webhook>> for WebhooksResponse {
+    fn from(value: Vec<Webhook>) -> Self {
+        Self {
+            items: value.into_iter().map(WebhookResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 登録したWebhookのレスポンスモデル
+/// 署名用のシークレット(`secret`)はこのレスポンスでのみ返却される
+pub struct IssuedWebhookResponse {
+    #[serde(flatten)]
+    pub webhook: WebhookResponse,
+    pub secret: String,
+}
+impl From<IssuedWebhook> for IssuedWebhookResponse {
+    fn from(value: IssuedWebhook) -> Self {
+        let IssuedWebhook { webhook, secret } = value;
+        Self {
+            webhook: webhook.into(),
+            secret: secret.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+/// 配信の状態
+pub enum WebhookDeliveryStatusName {
+    Pending,
+    Succeeded,
+    Failed,
+}
+impl From<WebhookDeliveryStatus> for WebhookDeliveryStatusName {
+    fn from(value: WebhookDeliveryStatus) -> Self {
+        match value {
+            WebhookDeliveryStatus::Pending => Self::Pending,
+            WebhookDeliveryStatus::Succeeded => Self::Succeeded,
+            WebhookDeliveryStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+/// 配信履歴の取得条件
+#[derive(Debug, Deserialize, Validate)]
+pub struct WebhookDeliveriesQuery {
+    #[garde(range(min = 1, max = 100))]
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+const fn default_limit() -> i64 {
+    50
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 配信情報のレスポンスモデル
+pub struct WebhookDeliveryResponse {
+    pub id: WebhookDeliveryId,
+    pub webhook_id: WebhookId,
+    pub event_id: WebhookEventId,
+    pub event_type: WebhookEventName,
+    pub status: WebhookDeliveryStatusName,
+    /// 送信を試みた回数
+    pub attempts: i32,
+    /// 直近の送信で配信先が返したHTTPステータス
+    pub response_status: Option<i32>,
+    /// 直近の送信に失敗した理由
+    pub last_error: Option<String>,
+    /// 次に送信を試みる日時(送信待ちの場合)
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    /// 配信先に送信するJSON
+    #[cfg_attr(debug_assertions, schema(value_type = Object))]
+    pub payload: serde_json::Value,
+}
+impl From<WebhookDelivery> for WebhookDeliveryResponse {
+    fn from(value: WebhookDelivery) -> Self {
+        let WebhookDelivery {
+            id,
+            webhook_id,
+            event_id,
+            event_type,
+            payload,
+            status,
+            attempts,
+            response_status,
+            last_error,
+            next_attempt_at,
+            created_at,
+            delivered_at,
+        } = value;
+        Self {
+            id,
+            webhook_id,
+            event_id,
+            event_type: event_type.into(),
+            status: status.into(),
+            attempts,
+            response_status,
+            last_error,
+            next_attempt_at: (status == WebhookDeliveryStatus::Pending).then_some(next_attempt_at),
+            created_at,
+            delivered_at,
+            payload: serde_json::from_str(&payload).unwrap_or(serde_json::Value::String(payload)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+/// 配信履歴のレスポンスモデル
+pub struct WebhookDeliveriesResponse {
+    pub items: Vec<WebhookDeliveryResponse>,
+}
+impl From<Vec<WebhookDelivery>> for WebhookDeliveriesResponse {
+    fn from(value: Vec<WebhookDelivery>) -> Self {
+        Self {
+            items: value
+                .into_iter()
+                .map(WebhookDeliveryResponse::from)
+                .collect(),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{handler, model};
+use crate::{handler, import, model};
 
 #[derive(utoipa::OpenApi)]
 #[openapi(
@@ -9,15 +9,28 @@ use crate::{handler, model};
     paths(
         handler::health::handler_health_check_api,
         handler::health::handler_health_check_db,
+        handler::health::handler_health_check_migrations,
         handler::auth::login,
         handler::auth::logout,
+        handler::auth::oidc_authorize,
+        handler::auth::oidc_callback,
         handler::book::show_book_list,
         handler::book::show_book,
         handler::book::register_book,
+        handler::book::import_books,
         handler::book::update_book,
         handler::book::delete_book,
+        handler::book::update_book_status,
+        handler::book::show_book_status_history,
+        handler::label::show_book_label,
+        handler::label::create_label_sheet,
+        handler::label::lookup_book,
         handler::checkout::checkout_book,
         handler::checkout::return_book,
+        handler::checkout::checkout_books,
+        handler::checkout::return_books,
+        handler::checkout::renew_checkout,
+        handler::checkout::declare_lost,
         handler::checkout::checkout_history_by_book,
         handler::checkout::show_checked_out_list,
         handler::user::get_current_user,
@@ -25,20 +38,76 @@ use crate::{handler, model};
         handler::user::change_password,
         handler::user::register_user,
         handler::user::delete_user,
+        handler::user::reactivate_user,
+        handler::user::purge_user,
+        handler::user::reassign_books,
         handler::user::change_role,
+        handler::user::register_badge,
+        handler::user::unregister_badge,
         handler::user::get_checkouts,
+        handler::user::get_checkout_history,
+        handler::user::get_user_checkout_history,
+        handler::api_key::create_api_key,
+        handler::api_key::list_api_keys,
+        handler::api_key::revoke_api_key,
+        handler::loan_policy::list_loan_policies,
+        handler::loan_policy::update_loan_policy,
+        handler::loan_policy::get_user_loan_policy,
+        handler::loan_policy::update_user_loan_policy,
+        handler::loan_policy::delete_user_loan_policy,
+        handler::fee::get_fee_schedule,
+        handler::fee::update_fee_schedule,
+        handler::fee::get_my_fees,
+        handler::fee::get_user_fees,
+        handler::fee::record_fee_payment,
+        handler::fee::waive_fee,
+        handler::kiosk::create_kiosk,
+        handler::kiosk::list_kiosks,
+        handler::kiosk::revoke_kiosk,
+        handler::kiosk::kiosk_checkout,
+        handler::kiosk::kiosk_return,
+        handler::webhook::create_webhook,
+        handler::webhook::list_webhooks,
+        handler::webhook::delete_webhook,
+        handler::webhook::list_webhook_deliveries,
+        handler::webhook::replay_webhook_delivery,
+        handler::audit::list_audit_logs,
+        handler::backup::create_backup,
+        handler::backup::restore_backup,
+        handler::event::stream_events,
+        handler::event::watch_books,
+        handler::export::export_books,
+        handler::export::export_checkouts,
     ),
     components(schemas(
         model::auth::LoginRequest,
         model::auth::AccessTokenResponse,
+        model::auth::OidcAuthorizationResponse,
+        model::auth::OidcCallbackRequest,
         model::book::CreateBookRequest,
+        model::book::BookImportResponse,
+        model::book::BookImportErrorResponse,
+        import::CsvEncoding,
+        model::export::ExportFormat,
         model::book::UpdateBookRequest,
         model::book::BookResponse,
         model::book::PaginatedBookResponse,
         model::book::BookCheckoutResponse,
+        model::book::BookStatusName,
+        model::book::UpdateBookStatusRequest,
+        model::book::BookStatusHistoryResponse,
+        model::book::BookStatusHistoriesResponse,
+        model::label::SymbologyName,
+        model::label::LabelFormat,
+        model::label::LabelSheetRequest,
         model::checkout::CheckoutsResponse,
         model::checkout::CheckoutResponse,
         model::checkout::CheckoutBookResponse,
+        model::checkout::PaginatedCheckoutResponse,
+        model::checkout::BatchCheckoutRequest,
+        model::checkout::BatchItemStatus,
+        model::checkout::BatchItemResponse,
+        model::checkout::BatchCheckoutResponse,
         model::user::BookOwner,
         model::user::CheckOutUser,
         model::user::UpdateUserRoleRequest,
@@ -49,9 +118,59 @@ use crate::{handler, model};
         model::user::CreateUserRequest,
         model::user::UpdateUserPasswordRequestWithUserId,
         model::user::UpdateUserRoleRequestWithUserId,
+        model::user::ReassignBooksRequest,
+        model::user::UpdateUserBadgeRequest,
+        model::user::UserDeletionBlockersResponse,
+        model::api_key::ApiKeyScopeName,
+        model::api_key::CreateApiKeyRequest,
+        model::api_key::ApiKeyResponse,
+        model::api_key::ApiKeysResponse,
+        model::api_key::IssuedApiKeyResponse,
+        model::loan_policy::LoanPolicyResponse,
+        model::loan_policy::RoleLoanPolicyResponse,
+        model::loan_policy::RoleLoanPoliciesResponse,
+        model::loan_policy::UpdateLoanPolicyRequest,
+        model::loan_policy::LoanPolicyOverrideRequest,
+        model::loan_policy::UserLoanPolicyResponse,
+        model::fee::FeeScheduleRequest,
+        model::fee::FeeEntryKindName,
+        model::fee::FeeEntryResponse,
+        model::fee::FeeLedgerResponse,
+        model::fee::FeeCreditRequest,
+        model::kiosk::CreateKioskRequest,
+        model::kiosk::KioskResponse,
+        model::kiosk::KiosksResponse,
+        model::kiosk::IssuedKioskResponse,
+        model::kiosk::KioskCheckoutRequest,
+        model::kiosk::KioskCheckoutResponse,
+        model::webhook::WebhookEventName,
+        model::webhook::CreateWebhookRequest,
+        model::webhook::WebhookResponse,
+        model::webhook::WebhooksResponse,
+        model::webhook::IssuedWebhookResponse,
+        model::webhook::WebhookDeliveryStatusName,
+        model::webhook::WebhookDeliveryResponse,
+        model::webhook::WebhookDeliveriesResponse,
+        model::audit::AuditTargetTypeName,
+        model::audit::AuditLogResponse,
+        model::audit::PaginatedAuditLogResponse,
+        model::backup::RestoreModeName,
+        model::backup::RestoreResponse,
+        model::backup::RestoreCountResponse,
+        model::health::MigrationStatusResponse,
+        model::health::PendingMigrationResponse,
+        model::event::BookWatchRequest,
+        model::event::BookWatchMessage,
+        model::event::BookAvailabilityResponse,
         kernel::model::id::BookId,
         kernel::model::id::UserId,
         kernel::model::id::CheckoutId,
+        kernel::model::id::ApiKeyId,
+        kernel::model::id::FeeEntryId,
+        kernel::model::id::KioskId,
+        kernel::model::id::WebhookId,
+        kernel::model::id::WebhookDeliveryId,
+        kernel::model::id::WebhookEventId,
     ))
 )]
 pub struct ApiDoc;
@@ -0,0 +1,14 @@
+use axum::{
+    routing::{delete, get},
+    Router,
+};
+use registry::AppRegistry;
+
+use crate::handler::api_key::{create_api_key, list_api_keys, revoke_api_key};
+
+pub fn build_api_key_routers() -> Router<AppRegistry> {
+    let api_key_routers = Router::new()
+        .route("/", get(list_api_keys).post(create_api_key))
+        .route("/:api_key_id", delete(revoke_api_key));
+    Router::new().nest("/users/me/api-keys", api_key_routers)
+}
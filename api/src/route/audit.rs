@@ -0,0 +1,10 @@
+use axum::{routing::get, Router};
+use registry::AppRegistry;
+
+use crate::handler::audit::list_audit_logs;
+
+pub fn build_audit_log_routers() -> Router<AppRegistry> {
+    let routers = Router::new().route("/", get(list_audit_logs));
+
+    Router::new().nest("/audit-logs", routers)
+}
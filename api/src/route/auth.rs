@@ -1,11 +1,16 @@
-use axum::{routing::post, Router};
+use axum::{
+    routing::{get, post},
+    Router,
+};
 use registry::AppRegistry;
 
-use crate::handler::auth::{login, logout};
+use crate::handler::auth::{login, logout, oidc_authorize, oidc_callback};
 
 pub fn routes() -> Router<AppRegistry> {
     let auth_router = Router::new()
         .route("/login", post(login))
-        .route("/logout", post(logout));
+        .route("/logout", post(logout))
+        .route("/oidc/authorize", get(oidc_authorize))
+        .route("/oidc/callback", post(oidc_callback));
     Router::new().nest("/auth", auth_router)
 }
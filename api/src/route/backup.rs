@@ -0,0 +1,20 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    routing::{get, post},
+    Router,
+};
+use registry::AppRegistry;
+
+use crate::{
+    handler::backup::{create_backup, restore_backup},
+    model::backup::MAX_BACKUP_SIZE,
+};
+
+pub fn build_backup_routers() -> Router<AppRegistry> {
+    let routers = Router::new().route("/", get(create_backup)).route(
+        "/restore",
+        post(restore_backup).layer(DefaultBodyLimit::max(MAX_BACKUP_SIZE)),
+    );
+
+    Router::new().nest("/backup", routers)
+}
@@ -1,22 +1,43 @@
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{delete, get, post, put},
     Router,
 };
 use registry::AppRegistry;
 
 use crate::handler::{
-    book::{delete_book, register_book, show_book, show_book_list, update_book},
-    checkout::{checkout_book, checkout_history_by_book, return_book, show_checked_out_list},
+    book::{
+        delete_book, import_books, register_book, show_book, show_book_list,
+        show_book_status_history, update_book, update_book_status,
+    },
+    checkout::{
+        checkout_book, checkout_history_by_book, declare_lost, renew_checkout, return_book,
+        show_checked_out_list,
+    },
+    label::{create_label_sheet, lookup_book, show_book_label},
 };
+use crate::import::MAX_IMPORT_SIZE;
 
 pub fn build_book_routers() -> Router<AppRegistry> {
     // 蔵書に関するルーティング
     let book_routers = Router::new()
         .route("/", post(register_book))
         .route("/", get(show_book_list))
+        .route(
+            "/import",
+            post(import_books).layer(DefaultBodyLimit::max(MAX_IMPORT_SIZE)),
+        )
         .route("/:book_id", get(show_book))
         .route("/:book_id", put(update_book))
-        .route("/:book_id", delete(delete_book));
+        .route("/:book_id", delete(delete_book))
+        .route("/:book_id/status", put(update_book_status))
+        .route("/:book_id/status-history", get(show_book_status_history));
+
+    // ラベルに関するルーティング
+    let label_routers = Router::new()
+        .route("/labels", post(create_label_sheet))
+        .route("/lookup", get(lookup_book))
+        .route("/:book_id/label", get(show_book_label));
 
     // 貸出に関するルーティング
     let checkout_routers = Router::new()
@@ -26,7 +47,15 @@ pub fn build_book_routers() -> Router<AppRegistry> {
             "/:book_id/checkouts/:checkout_id/returned",
             put(return_book),
         )
+        .route(
+            "/:book_id/checkouts/:checkout_id/renewed",
+            put(renew_checkout),
+        )
+        .route("/:book_id/checkouts/:checkout_id/lost", put(declare_lost))
         .route("/:book_id/checkout-history", get(checkout_history_by_book));
 
-    Router::new().nest("/books", book_routers.merge(checkout_routers))
+    Router::new().nest(
+        "/books",
+        book_routers.merge(checkout_routers).merge(label_routers),
+    )
 }
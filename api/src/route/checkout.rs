@@ -0,0 +1,11 @@
+use axum::{routing::post, Router};
+use registry::AppRegistry;
+
+use crate::handler::checkout::{checkout_books, return_books};
+
+pub fn build_checkout_routers() -> Router<AppRegistry> {
+    // 複数の蔵書をまとめて処理するルーティング
+    Router::new()
+        .route("/checkouts/batch", post(checkout_books))
+        .route("/returns/batch", post(return_books))
+}
@@ -0,0 +1,12 @@
+use axum::{routing::get, Router};
+use registry::AppRegistry;
+
+use crate::handler::event::{stream_events, watch_books};
+
+pub fn build_event_routers() -> Router<AppRegistry> {
+    let routers = Router::new()
+        .route("/stream", get(stream_events))
+        .route("/books", get(watch_books));
+
+    Router::new().nest("/events", routers)
+}
@@ -0,0 +1,12 @@
+use axum::{routing::get, Router};
+use registry::AppRegistry;
+
+use crate::handler::export::{export_books, export_checkouts};
+
+pub fn build_export_routers() -> Router<AppRegistry> {
+    let routers = Router::new()
+        .route("/books", get(export_books))
+        .route("/checkouts", get(export_checkouts));
+
+    Router::new().nest("/exports", routers)
+}
@@ -0,0 +1,22 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use registry::AppRegistry;
+
+use crate::handler::fee::{
+    get_fee_schedule, get_my_fees, get_user_fees, record_fee_payment, update_fee_schedule,
+    waive_fee,
+};
+
+pub fn build_fee_routers() -> Router<AppRegistry> {
+    Router::new()
+        .route(
+            "/fee-schedule",
+            get(get_fee_schedule).put(update_fee_schedule),
+        )
+        .route("/users/me/fees", get(get_my_fees))
+        .route("/users/:user_id/fees", get(get_user_fees))
+        .route("/users/:user_id/fees/payments", post(record_fee_payment))
+        .route("/users/:user_id/fees/waivers", post(waive_fee))
+}
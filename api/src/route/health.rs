@@ -1,11 +1,14 @@
 use axum::{routing::get, Router};
 use registry::AppRegistry;
 
-use crate::handler::health::{handler_health_check_api, handler_health_check_db};
+use crate::handler::health::{
+    handler_health_check_api, handler_health_check_db, handler_health_check_migrations,
+};
 
 pub fn build_health_check_routers() -> Router<AppRegistry> {
     let health_check_routers = Router::new()
         .route("/", get(handler_health_check_api))
-        .route("/db", get(handler_health_check_db));
+        .route("/db", get(handler_health_check_db))
+        .route("/migrations", get(handler_health_check_migrations));
     Router::new().nest("/health", health_check_routers)
 }
@@ -0,0 +1,24 @@
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+use registry::AppRegistry;
+
+use crate::handler::kiosk::{
+    create_kiosk, kiosk_checkout, kiosk_return, list_kiosks, revoke_kiosk,
+};
+
+pub fn build_kiosk_routers() -> Router<AppRegistry> {
+    // キオスクの管理(管理者向け)
+    let kiosk_routers = Router::new()
+        .route("/", get(list_kiosks).post(create_kiosk))
+        .route("/:kiosk_id", delete(revoke_kiosk));
+    // キオスクの端末から呼び出す操作
+    let kiosk_operation_routers = Router::new()
+        .route("/checkouts", post(kiosk_checkout))
+        .route("/returns", post(kiosk_return));
+
+    Router::new()
+        .nest("/kiosks", kiosk_routers)
+        .nest("/kiosk", kiosk_operation_routers)
+}
@@ -0,0 +1,22 @@
+use axum::{
+    routing::{get, put},
+    Router,
+};
+use registry::AppRegistry;
+
+use crate::handler::loan_policy::{
+    delete_user_loan_policy, get_user_loan_policy, list_loan_policies, update_loan_policy,
+    update_user_loan_policy,
+};
+
+pub fn build_loan_policy_routers() -> Router<AppRegistry> {
+    Router::new()
+        .route("/loan-policies", get(list_loan_policies))
+        .route("/loan-policies/:role", put(update_loan_policy))
+        .route(
+            "/users/:user_id/loan-policy",
+            get(get_user_loan_policy)
+                .put(update_user_loan_policy)
+                .delete(delete_user_loan_policy),
+        )
+}
@@ -1,6 +1,7 @@
 use crate::handler::user::{
-    change_password, change_role, delete_user, get_checkouts, get_current_user, list_users,
-    register_user,
+    change_password, change_role, delete_user, get_checkout_history, get_checkouts,
+    get_current_user, get_user_checkout_history, list_users, purge_user, reactivate_user,
+    reassign_books, register_badge, register_user, unregister_badge,
 };
 use axum::{
     routing::{delete, get, put},
@@ -12,8 +13,20 @@ pub fn build_user_routers() -> Router<AppRegistry> {
     Router::new()
         .route("/users/me", get(get_current_user))
         .route("/users/me/checkouts", get(get_checkouts))
+        .route("/users/me/checkout-history", get(get_checkout_history))
         .route("/users/me/password", put(change_password))
         .route("/users", get(list_users).post(register_user))
         .route("/users/:user_id", delete(delete_user))
         .route("/users/:user_id/role", put(change_role))
+        .route(
+            "/users/:user_id/badge",
+            put(register_badge).delete(unregister_badge),
+        )
+        .route("/users/:user_id/reactivate", put(reactivate_user))
+        .route("/users/:user_id/permanent", delete(purge_user))
+        .route("/users/:user_id/books/owner", put(reassign_books))
+        .route(
+            "/users/:user_id/checkout-history",
+            get(get_user_checkout_history),
+        )
 }
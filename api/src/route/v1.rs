@@ -2,14 +2,28 @@ use axum::Router;
 use registry::AppRegistry;
 
 use super::{
-    book::build_book_routers, health::build_health_check_routers, user::build_user_routers,
+    api_key::build_api_key_routers, audit::build_audit_log_routers, backup::build_backup_routers,
+    book::build_book_routers, checkout::build_checkout_routers, event::build_event_routers,
+    export::build_export_routers, fee::build_fee_routers, health::build_health_check_routers,
+    kiosk::build_kiosk_routers, loan_policy::build_loan_policy_routers, user::build_user_routers,
+    webhook::build_webhook_routers,
 };
 
 pub fn routes() -> Router<AppRegistry> {
     let router = Router::new()
         .merge(build_health_check_routers())
         .merge(build_book_routers())
-        .merge(build_user_routers());
+        .merge(build_checkout_routers())
+        .merge(build_user_routers())
+        .merge(build_api_key_routers())
+        .merge(build_loan_policy_routers())
+        .merge(build_fee_routers())
+        .merge(build_kiosk_routers())
+        .merge(build_webhook_routers())
+        .merge(build_audit_log_routers())
+        .merge(build_backup_routers())
+        .merge(build_event_routers())
+        .merge(build_export_routers());
 
     Router::new().nest("/api/v1", router)
 }
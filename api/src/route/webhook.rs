@@ -0,0 +1,22 @@
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+use registry::AppRegistry;
+
+use crate::handler::webhook::{
+    create_webhook, delete_webhook, list_webhook_deliveries, list_webhooks, replay_webhook_delivery,
+};
+
+pub fn build_webhook_routers() -> Router<AppRegistry> {
+    let routers = Router::new()
+        .route("/", get(list_webhooks).post(create_webhook))
+        .route("/:webhook_id", delete(delete_webhook))
+        .route("/:webhook_id/deliveries", get(list_webhook_deliveries))
+        .route(
+            "/:webhook_id/deliveries/:delivery_id/replay",
+            post(replay_webhook_delivery),
+        );
+
+    Router::new().nest("/webhooks", routers)
+}
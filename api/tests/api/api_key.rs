@@ -0,0 +1,209 @@
+use crate::{
+    deserialize_json,
+    helper::{fixture, fixture_registry, make_router, v1, TestRequestExt},
+};
+use api::model::api_key::IssuedApiKeyResponse;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use kernel::{
+    model::{
+        api_key::{ApiKey, ApiKeyScope, IssuedApiKey},
+        id::{ApiKeyId, BookId, CheckoutId, UserId},
+        role::Role,
+        user::User,
+    },
+    repository::{
+        api_key::MockApiKeyRepository, checkout::MockCheckoutRepository, user::MockUserRepository,
+    },
+};
+use rstest::rstest;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn api_key(scopes: Vec<ApiKeyScope>) -> ApiKey {
+    ApiKey {
+        id: ApiKeyId::new(),
+        user_id: UserId::new(),
+        name: "ci-bot".to_string(),
+        key_prefix: "bmk_12345678".to_string(),
+        scopes,
+        created_at: chrono::Utc::now(),
+        last_used_at: Some(chrono::Utc::now()),
+        revoked_at: None,
+    }
+}
+
+/// APIキーによる認証が成功するようにモックを設定する
+fn with_api_key(
+    mut fixture_registry: registry::MockAppRegistryExt,
+    scopes: Vec<ApiKeyScope>,
+) -> registry::MockAppRegistryExt {
+    fixture_registry
+        .expect_api_key_repository()
+        .returning(move || {
+            let mut mock = MockApiKeyRepository::new();
+            let scopes = scopes.clone();
+            mock.expect_verify()
+                .withf(|secret| secret.0 == "bmk_secret")
+                .returning(move |_| Ok(Some(api_key(scopes.clone()))));
+            Arc::new(mock)
+        });
+    fixture_registry.expect_user_repository().returning(|| {
+        let mut mock = MockUserRepository::new();
+        mock.expect_find_current_user().returning(|id| {
+            Ok(Some(User {
+                id,
+                name: "dummy-user".to_string(),
+                email: "dummy@example.com".to_string(),
+                role: Role::User,
+                deactivated_at: None,
+            }))
+        });
+        Arc::new(mock)
+    });
+    fixture_registry
+        .expect_check_out_repository()
+        .returning(|| {
+            let mut mock = MockCheckoutRepository::new();
+            mock.expect_find_unreturned_by_user_id()
+                .returning(|_| Ok(vec![]));
+            mock.expect_create_checkout()
+                .returning(|_, _| Ok(CheckoutId::new()));
+            Arc::new(mock)
+        });
+    fixture_registry
+}
+
+#[rstest]
+#[case::x_api_key_header("x-api-key", "bmk_secret")]
+#[case::bearer_prefix("Authorization", "Bearer bmk_secret")]
+#[tokio::test]
+async fn api_key_read_200(
+    fixture_registry: registry::MockAppRegistryExt,
+    #[case] header: &str,
+    #[case] value: &str,
+) -> anyhow::Result<()> {
+    let app = make_router(with_api_key(fixture_registry, vec![ApiKeyScope::Read]));
+
+    let req = Request::get(v1("/users/me/checkouts"))
+        .header(header, value)
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(vec![ApiKeyScope::Read], StatusCode::FORBIDDEN)]
+#[case(vec![ApiKeyScope::Read, ApiKeyScope::Write], StatusCode::CREATED)]
+#[tokio::test]
+async fn api_key_write_requires_write_scope(
+    fixture_registry: registry::MockAppRegistryExt,
+    #[case] scopes: Vec<ApiKeyScope>,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let app = make_router(with_api_key(fixture_registry, scopes));
+
+    let req = Request::post(v1(&format!("/books/{}/checkouts", BookId::new())))
+        .header("x-api-key", "bmk_secret")
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case::change_password(
+    Request::put(v1("/users/me/password")),
+    r#"{"currentPassword": "old_password", "newPassword": "new_password"}"#
+)]
+#[case::create_api_key(
+    Request::post(v1("/users/me/api-keys")),
+    r#"{"name": "another-bot", "scopes": ["read"]}"#
+)]
+#[case::list_api_keys(Request::get(v1("/users/me/api-keys")), "")]
+#[case::revoke_api_key(Request::delete(v1(&format!("/users/me/api-keys/{}", ApiKeyId::new()))), "")]
+#[tokio::test]
+async fn account_operations_with_api_key_403(
+    fixture_registry: registry::MockAppRegistryExt,
+    #[case] req: axum::http::request::Builder,
+    #[case] body: &'static str,
+) -> anyhow::Result<()> {
+    // 全てのスコープを持つAPIキーであっても、アカウントそのものは操作できない
+    let app = make_router(with_api_key(
+        fixture_registry,
+        vec![ApiKeyScope::Read, ApiKeyScope::Write, ApiKeyScope::Admin],
+    ));
+
+    let req = req
+        .header("Content-Type", "application/json")
+        .header("x-api-key", "bmk_secret")
+        .body(Body::from(body))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn create_api_key_201(mut fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    fixture.expect_api_key_repository().returning(|| {
+        let mut mock = MockApiKeyRepository::new();
+        mock.expect_create()
+            .withf(|e| e.name == "slack-bot" && e.scopes == vec![ApiKeyScope::Read])
+            .returning(|e| {
+                Ok(IssuedApiKey {
+                    api_key: ApiKey {
+                        user_id: e.user_id,
+                        name: e.name,
+                        scopes: e.scopes,
+                        ..api_key(vec![])
+                    },
+                    secret: e.secret,
+                })
+            });
+        Arc::new(mock)
+    });
+    let app = make_router(fixture);
+
+    let req = Request::post(v1("/users/me/api-keys"))
+        .header("Content-Type", "application/json")
+        .bearer()
+        .body(Body::from(
+            r#"{"name": "slack-bot", "scopes": ["read", "read"]}"#,
+        ))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let result = deserialize_json!(resp, IssuedApiKeyResponse);
+    assert!(result.key.starts_with("bmk_"));
+    assert_eq!(result.api_key.name, "slack-bot");
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn create_api_key_admin_scope_403(
+    fixture: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+
+    let req = Request::post(v1("/users/me/api-keys"))
+        .header("Content-Type", "application/json")
+        .bearer()
+        .body(Body::from(r#"{"name": "admin-bot", "scopes": ["admin"]}"#))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
@@ -0,0 +1,235 @@
+use crate::{
+    deserialize_json,
+    helper::{admin_with, fixture, fixture_auth, make_router, v1, TestRequestExt},
+};
+use api::model::audit::{AuditTargetTypeName, PaginatedAuditLogResponse};
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{header, Request, StatusCode},
+};
+use kernel::{
+    model::{
+        audit::{event::CreateAuditLog, AuditAction, AuditLog, AuditTargetType},
+        book::{status::BookStatus, Book},
+        id::{AuditLogId, BookId, UserId},
+        list::PaginatedList,
+        user::BookOwner,
+    },
+    repository::{
+        audit::MockAuditLogRepository, auth::MockAuthRepository, book::MockBookRepository,
+    },
+};
+use registry::MockAppRegistryExt;
+use rstest::rstest;
+use shared::{config::ProxyConfig, error::AppError};
+use std::{net::SocketAddr, sync::Arc};
+use tower::ServiceExt;
+
+/// 監査ログの記録内容を検証するため、既定の監査ログのモックを持たないレジストリを作る
+fn registry_expecting_audit_log(
+    matcher: impl Fn(&CreateAuditLog) -> bool + Send + Sync + 'static,
+) -> MockAppRegistryExt {
+    let matcher = Arc::new(matcher);
+    let mut registry = MockAppRegistryExt::new();
+    registry
+        .expect_audit_log_repository()
+        .times(1)
+        .returning(move || {
+            let matcher = matcher.clone();
+            let mut mock = MockAuditLogRepository::new();
+            mock.expect_create()
+                .withf(move |event| matcher(event))
+                .times(1)
+                .returning(|_| Ok(()));
+            Arc::new(mock)
+        });
+    registry
+}
+
+#[rstest]
+#[tokio::test]
+async fn list_audit_logs_403(fixture: MockAppRegistryExt) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+
+    let req = Request::get(v1("/audit-logs"))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_audit_logs_200() -> anyhow::Result<()> {
+    let actor_id = UserId::new();
+    let book_id = BookId::new();
+    let from = chrono::DateTime::parse_from_rfc3339("2026-10-01T00:00:00Z")?.to_utc();
+
+    // 既定の監査ログのモックを持たないレジストリに、検索のモックを登録する
+    let mut registry = MockAppRegistryExt::new();
+    registry.expect_audit_log_repository().returning(move || {
+        let mut mock = MockAuditLogRepository::new();
+        mock.expect_find_all()
+            .withf(move |options| {
+                options.actor_id == Some(actor_id)
+                    && options.target_type == Some(AuditTargetType::Book)
+                    && options.target_id == Some(book_id.to_string())
+                    && options.from == Some(from)
+                    && options.to.is_none()
+                    && options.limit == 10
+                    && options.offset == 0
+            })
+            .returning(move |options| {
+                Ok(PaginatedList {
+                    total: 1,
+                    limit: options.limit,
+                    offset: options.offset,
+                    items: vec![AuditLog {
+                        id: AuditLogId::new(),
+                        actor_id: Some(actor_id),
+                        action: AuditAction::UpdateBook,
+                        target_type: AuditTargetType::Book,
+                        target_id: Some(book_id.to_string()),
+                        before: Some(serde_json::json!({ "title": "before" })),
+                        after: Some(serde_json::json!({ "title": "after" })),
+                        request_id: Some("req-1".into()),
+                        ip_address: Some("203.0.113.7".into()),
+                        occurred_at: chrono::Utc::now(),
+                    }],
+                })
+            });
+        Arc::new(mock)
+    });
+    let app = make_router(admin_with(fixture_auth(registry), |_| {}));
+
+    let req = Request::get(v1(&format!(
+        "/audit-logs?actorId={actor_id}&targetType=book&targetId={book_id}&from=2026-10-01T00:00:00Z&limit=10"
+    )))
+    .bearer()
+    .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = deserialize_json!(resp, PaginatedAuditLogResponse);
+    assert_eq!(result.total, 1);
+    let item = &result.items[0];
+    assert_eq!(item.action, "book.update");
+    assert_eq!(item.target_type, AuditTargetTypeName::Book);
+    assert_eq!(item.before, Some(serde_json::json!({ "title": "before" })));
+
+    Ok(())
+}
+
+#[rstest]
+#[case("/audit-logs?from=2026-10-02T00:00:00Z&to=2026-10-01T00:00:00Z")]
+#[case("/audit-logs?limit=0")]
+#[case("/audit-logs?offset=-1")]
+#[tokio::test]
+async fn list_audit_logs_400(
+    fixture_auth: MockAppRegistryExt,
+    #[case] path: &str,
+) -> anyhow::Result<()> {
+    let app = make_router(admin_with(fixture_auth, |_| {}));
+
+    let req = Request::get(v1(path)).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_login_is_recorded() -> anyhow::Result<()> {
+    let mut registry = registry_expecting_audit_log(|event| {
+        event.action == AuditAction::LoginFailed
+            && event.actor_id.is_none()
+            && event.target_type == AuditTargetType::User
+            && event.after.as_ref().and_then(|after| after.get("email"))
+                == Some(&serde_json::json!("user@example.com"))
+            && event.request_id.as_deref() == Some("req-1")
+            // 信頼するプロキシが付与した接続元のうち、最も近いものを採用する
+            && event.ip_address.as_deref() == Some("203.0.113.7")
+    });
+    registry.expect_proxy_config().returning(|| {
+        Arc::new(ProxyConfig {
+            trusted_proxies: vec!["10.0.0.0/8".parse().unwrap()],
+        })
+    });
+    registry.expect_auth_repository().returning(|| {
+        let mut mock = MockAuthRepository::new();
+        mock.expect_verify_user()
+            .returning(|_, _| Err(AppError::UnauthenticatedError));
+        Arc::new(mock)
+    });
+    let app = make_router(registry);
+
+    let mut req = Request::post("/auth/login")
+        .header(header::CONTENT_TYPE, "application/json")
+        .header("X-Request-Id", "req-1")
+        .header("X-Forwarded-For", "198.51.100.1, 203.0.113.7")
+        .body(Body::from(
+            r#"{"email": "user@example.com", "password": "wrong"}"#,
+        ))?;
+    req.extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 443))));
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn delete_book_is_recorded_with_before_snapshot() -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let mut mock = MockBookRepository::new();
+    mock.expect_find_by_id().returning(|id| {
+        Ok(Some(Book {
+            id,
+            title: "rust-web-bookmanager".to_string(),
+            isbn: "1234567890".to_string(),
+            author: "Yuki Toyoda".to_string(),
+            description: "".to_string(),
+            owner: BookOwner {
+                id: UserId::new(),
+                name: "Yuki Toyoda".to_string(),
+            },
+            status: BookStatus::Available,
+            checkout_info: None,
+        }))
+    });
+    // 削除と同じトランザクションで記録されるよう、監査ログはリポジトリに渡される
+    mock.expect_delete()
+        .withf(move |_, audit| {
+            audit.as_ref().is_some_and(|event| {
+                event.action == AuditAction::DeleteBook
+                    && event.actor_id.is_some()
+                    && event.target_type == AuditTargetType::Book
+                    && event.target_id == Some(book_id.to_string())
+                    && event.before.as_ref().and_then(|before| before.get("title"))
+                        == Some(&serde_json::json!("rust-web-bookmanager"))
+                    && event.after.is_none()
+            })
+        })
+        .times(1)
+        .returning(|_, _| Ok(()));
+    let mock = Arc::new(mock);
+    let mut registry = fixture(fixture_auth(MockAppRegistryExt::new()));
+    registry
+        .expect_book_repository()
+        .returning(move || mock.clone());
+    let app = make_router(registry);
+
+    let req = Request::delete(v1(&format!("/books/{book_id}")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    Ok(())
+}
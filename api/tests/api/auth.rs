@@ -0,0 +1,344 @@
+use crate::{
+    deserialize_json,
+    helper::{fixture, fixture_auth, fixture_registry, make_router, v1, TestRequestExt},
+};
+use api::model::auth::{AccessTokenResponse, OidcAuthorizationResponse};
+use axum::{
+    body::Body,
+    http::{header::WWW_AUTHENTICATE, Request, StatusCode},
+    response::Response,
+};
+use kernel::{
+    model::{
+        api_key::{ApiKey, ApiKeyScope},
+        auth::{OidcAuthorization, OidcIdentity},
+        id::{ApiKeyId, UserId},
+        role::Role,
+        user::User,
+    },
+    repository::{
+        api_key::MockApiKeyRepository, auth::MockAuthRepository, oidc::MockOidcRepository,
+        user::MockUserRepository,
+    },
+};
+use rstest::rstest;
+use shared::error::AppError;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn callback_request() -> Request<Body> {
+    Request::post("/auth/oidc/callback")
+        .header("Content-Type", "application/json")
+        .body(Body::from(
+            r#"{"code":"auth-code","state":"state","clientBinding":"binding"}"#,
+        ))
+        .unwrap()
+}
+
+#[rstest]
+#[tokio::test]
+async fn oidc_authorize_returns_authorization_url(
+    mut fixture_registry: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    fixture_registry.expect_oidc_repository().returning(|| {
+        let mut mock = MockOidcRepository::new();
+        mock.expect_authorize().returning(|| {
+            Ok(OidcAuthorization {
+                authorization_url: "https://idp.example.com/authorize?state=state".into(),
+                client_binding: "binding".into(),
+            })
+        });
+        Arc::new(mock)
+    });
+
+    let app: axum::Router = make_router(fixture_registry);
+    let req = Request::get("/auth/oidc/authorize")
+        .body(Body::empty())
+        .unwrap();
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let result = deserialize_json!(resp, OidcAuthorizationResponse);
+    assert_eq!(
+        result.authorization_url,
+        "https://idp.example.com/authorize?state=state"
+    );
+    assert_eq!(result.client_binding, "binding");
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn oidc_callback_provisions_user_and_issues_token(
+    mut fixture_auth: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    let user_id = UserId::new();
+    fixture_auth.expect_oidc_repository().returning(|| {
+        let mut mock = MockOidcRepository::new();
+        mock.expect_verify_callback()
+            .withf(|event| {
+                event.code == "auth-code"
+                    && event.state == "state"
+                    && event.client_binding == "binding"
+            })
+            .returning(|_| {
+                Ok(OidcIdentity {
+                    issuer: "https://idp.example.com".into(),
+                    subject: "subject".into(),
+                    email: "sso@example.com".into(),
+                    email_verified: true,
+                    name: "SSO User".into(),
+                    role: Some(Role::Admin),
+                })
+            });
+        Arc::new(mock)
+    });
+    fixture_auth.expect_user_repository().returning(move || {
+        let mut mock = MockUserRepository::new();
+        mock.expect_provision_external_user()
+            .withf(|event| event.subject == "subject" && event.role == Some(Role::Admin))
+            .returning(move |event| {
+                Ok(User {
+                    id: user_id,
+                    name: event.name,
+                    email: event.email,
+                    role: Role::Admin,
+                    deactivated_at: None,
+                })
+            });
+        Arc::new(mock)
+    });
+
+    let app: axum::Router = make_router(fixture_auth);
+    let resp = app.oneshot(callback_request()).await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let result = deserialize_json!(resp, AccessTokenResponse);
+    assert_eq!(result.user_id, user_id);
+    assert_eq!(result.access_token, "dummy");
+
+    Ok(())
+}
+
+#[rstest]
+#[case(AppError::UnauthenticatedError, StatusCode::UNAUTHORIZED)]
+#[case(
+    AppError::NotFoundError("OIDC login is not configured".into()),
+    StatusCode::NOT_FOUND
+)]
+#[tokio::test]
+async fn oidc_callback_rejected(
+    mut fixture_registry: registry::MockAppRegistryExt,
+    #[case] error: AppError,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    fixture_registry
+        .expect_oidc_repository()
+        .return_once(move || {
+            let mut mock = MockOidcRepository::new();
+            mock.expect_verify_callback()
+                .return_once(move |_| Err(error));
+            Arc::new(mock)
+        });
+
+    let app: axum::Router = make_router(fixture_registry);
+    let resp = app.oneshot(callback_request()).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+fn www_authenticate(resp: &Response) -> Option<&str> {
+    resp.headers()
+        .get(WWW_AUTHENTICATE)
+        .and_then(|v| v.to_str().ok())
+}
+
+/// 資格情報が送られていない場合は、エラーコードを含まないチャレンジを返す
+#[rstest]
+#[case(None)]
+#[case(Some("Basic dXNlcjpwYXNz"))]
+#[tokio::test]
+async fn missing_credentials_401(
+    fixture_registry: registry::MockAppRegistryExt,
+    #[case] authorization: Option<&str>,
+) -> anyhow::Result<()> {
+    let app = make_router(fixture_registry);
+
+    let mut req = Request::get(v1("/users/me"));
+    if let Some(authorization) = authorization {
+        req = req.header("Authorization", authorization);
+    }
+    let resp = app.oneshot(req.body(Body::empty())?).await?;
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let challenge = www_authenticate(&resp).unwrap();
+    assert!(challenge.starts_with("Bearer"));
+    assert!(!challenge.contains("error="));
+
+    Ok(())
+}
+
+/// 期限切れ・ログアウト済みのアクセストークン
+#[rstest]
+#[tokio::test]
+async fn expired_access_token_401(
+    mut fixture_registry: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    fixture_registry.expect_auth_repository().returning(|| {
+        let mut mock = MockAuthRepository::new();
+        mock.expect_fetch_user_id_from_token()
+            .returning(|_| Ok(None));
+        Arc::new(mock)
+    });
+    let app = make_router(fixture_registry);
+
+    let req = Request::get(v1("/users/me")).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert!(www_authenticate(&resp).is_some_and(|v| v.contains(r#"error="invalid_token""#)));
+
+    Ok(())
+}
+
+/// トークンの発行後にユーザーが無効化された場合
+#[rstest]
+#[tokio::test]
+async fn deactivated_user_token_401(
+    mut fixture_auth: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    fixture_auth.expect_user_repository().returning(|| {
+        let mut mock = MockUserRepository::new();
+        mock.expect_find_current_user().returning(|_| Ok(None));
+        Arc::new(mock)
+    });
+    let app = make_router(fixture_auth);
+
+    let req = Request::get(v1("/users/me")).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert!(www_authenticate(&resp).is_some_and(|v| v.contains(r#"error="invalid_token""#)));
+
+    Ok(())
+}
+
+/// 失効済み・存在しないAPIキー
+#[rstest]
+#[tokio::test]
+async fn invalid_api_key_401(
+    mut fixture_registry: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    fixture_registry.expect_api_key_repository().returning(|| {
+        let mut mock = MockApiKeyRepository::new();
+        mock.expect_verify().returning(|_| Ok(None));
+        Arc::new(mock)
+    });
+    let app = make_router(fixture_registry);
+
+    let req = Request::get(v1("/users/me"))
+        .header("x-api-key", "bmk_revoked")
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert!(www_authenticate(&resp).is_some_and(|v| v.contains(r#"error="invalid_token""#)));
+
+    Ok(())
+}
+
+/// APIキーのスコープが不足している場合は、認証済みとして403を返す
+#[rstest]
+#[tokio::test]
+async fn insufficient_api_key_scope_403(
+    mut fixture: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    fixture.expect_api_key_repository().returning(|| {
+        let mut mock = MockApiKeyRepository::new();
+        mock.expect_verify().returning(|_| {
+            Ok(Some(ApiKey {
+                id: ApiKeyId::new(),
+                user_id: UserId::new(),
+                name: "read-only".into(),
+                key_prefix: "bmk_12345678".into(),
+                scopes: vec![ApiKeyScope::Read],
+                created_at: chrono::Utc::now(),
+                last_used_at: None,
+                revoked_at: None,
+            }))
+        });
+        Arc::new(mock)
+    });
+    let app = make_router(fixture);
+
+    let req = Request::put(v1("/users/me/password"))
+        .header("x-api-key", "bmk_read_only")
+        .header("Content-Type", "application/json")
+        .body(Body::from(
+            r#"{"currentPassword": "old", "newPassword": "new"}"#,
+        ))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    assert!(www_authenticate(&resp).is_none());
+
+    Ok(())
+}
+
+/// 認証済みだが権限が不足している場合
+#[rstest]
+#[tokio::test]
+async fn non_admin_403(fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+
+    let req = Request::put(v1(&format!("/users/{}/reactivate", UserId::new())))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    assert!(www_authenticate(&resp).is_none());
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn valid_access_token_200(fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+
+    let req = Request::get(v1("/users/me")).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn login_with_wrong_password_401(
+    mut fixture_registry: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    fixture_registry.expect_auth_repository().returning(|| {
+        let mut mock = MockAuthRepository::new();
+        mock.expect_verify_user()
+            .returning(|_, _| Err(AppError::UnauthenticatedError));
+        Arc::new(mock)
+    });
+    let app = make_router(fixture_registry);
+
+    let req = Request::post("/auth/login")
+        .header("Content-Type", "application/json")
+        .body(Body::from(
+            r#"{"email": "user@example.com", "password": "wrong"}"#,
+        ))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert!(www_authenticate(&resp).is_some());
+
+    Ok(())
+}
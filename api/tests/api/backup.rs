@@ -0,0 +1,166 @@
+use crate::{
+    deserialize_json,
+    helper::{admin_with, fixture, fixture_auth, make_router, v1, TestRequestExt},
+};
+use api::model::backup::{RestoreModeName, RestoreResponse};
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use chrono::{TimeZone, Utc};
+use kernel::{
+    model::backup::{
+        BackupArchive, RestoreCount, RestoreMode, RestoreSummary, BACKUP_FORMAT_VERSION,
+    },
+    repository::backup::MockBackupRepository,
+};
+use registry::MockAppRegistryExt;
+use rstest::rstest;
+use shared::error::AppError;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn archive() -> BackupArchive {
+    BackupArchive {
+        format_version: BACKUP_FORMAT_VERSION,
+        schema_version: 20261018200000,
+        created_at: Utc.with_ymd_and_hms(2026, 10, 19, 9, 30, 0).unwrap(),
+        includes_password_hashes: false,
+        roles: vec!["Admin".into(), "User".into()],
+        users: vec![],
+        books: vec![],
+        checkouts: vec![],
+        returned_checkouts: vec![],
+    }
+}
+
+fn restore_request(path: &str) -> anyhow::Result<Request<Body>> {
+    Ok(Request::post(v1(path))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&archive())?))?)
+}
+
+#[rstest]
+#[tokio::test]
+async fn create_backup_200(fixture_auth: MockAppRegistryExt) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_backup_repository().returning(|| {
+        let mut mock = MockBackupRepository::new();
+        mock.expect_create()
+            .withf(|options| options.include_password_hashes)
+            .times(1)
+            .returning(|_| Ok(archive()));
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let req = Request::get(v1("/backup?includePasswordHashes=true"))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers()[header::CONTENT_DISPOSITION],
+        r#"attachment; filename="bookmanager-backup-20261019T093000Z.json""#
+    );
+    let result = deserialize_json!(resp, serde_json::Value);
+    assert_eq!(result["formatVersion"], BACKUP_FORMAT_VERSION);
+    assert_eq!(result["schemaVersion"], 20261018200000i64);
+    assert_eq!(result["roles"], serde_json::json!(["Admin", "User"]));
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn restore_backup_200(fixture_auth: MockAppRegistryExt) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_backup_repository().returning(|| {
+        let mut mock = MockBackupRepository::new();
+        mock.expect_restore()
+            .withf(|archive, mode, _| {
+                archive.schema_version == 20261018200000 && *mode == RestoreMode::Replace
+            })
+            .times(1)
+            .returning(|_, _, _| {
+                Ok(RestoreSummary {
+                    books: RestoreCount {
+                        restored: 3,
+                        skipped: 1,
+                    },
+                    kiosks_without_creator: 2,
+                    ..Default::default()
+                })
+            });
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let resp = app
+        .oneshot(restore_request("/backup/restore?mode=replace")?)
+        .await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = deserialize_json!(resp, RestoreResponse);
+    assert_eq!(result.mode, RestoreModeName::Replace);
+    assert_eq!(result.books.restored, 3);
+    assert_eq!(result.books.skipped, 1);
+    assert_eq!(result.users.restored, 0);
+    assert_eq!(result.kiosks_without_creator, 2);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn restore_backup_422(fixture_auth: MockAppRegistryExt) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_backup_repository().returning(|| {
+        let mut mock = MockBackupRepository::new();
+        // 復元先とスキーマのバージョンが一致しない
+        mock.expect_restore()
+            .returning(|_, _, _| Err(AppError::UnprocessableEntity("schema mismatch".into())));
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let resp = app.oneshot(restore_request("/backup/restore")?).await?;
+
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn restore_backup_400(fixture_auth: MockAppRegistryExt) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_backup_repository().never();
+
+    let app = make_router(registry);
+    let resp = app
+        .oneshot(restore_request("/backup/restore?mode=overwrite")?)
+        .await?;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn backup_403(mut fixture: MockAppRegistryExt) -> anyhow::Result<()> {
+    fixture.expect_backup_repository().never();
+
+    let app = make_router(fixture);
+    let req = Request::get(v1("/backup")).bearer().body(Body::empty())?;
+    let resp = app.clone().oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = app.oneshot(restore_request("/backup/restore")?).await?;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
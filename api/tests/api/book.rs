@@ -1,12 +1,20 @@
 use crate::{
     deserialize_json,
-    helper::{fixture, make_router, v1, TestRequestExt},
+    helper::{admin_with, fixture, fixture_auth, make_router, v1, TestRequestExt},
+};
+use api::model::book::{
+    BookImportResponse, BookStatusHistoriesResponse, BookStatusName, PaginatedBookResponse,
+};
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
 };
-use api::model::book::PaginatedBookResponse;
-use axum::{body::Body, http::Request};
 use kernel::{
     model::{
-        book::Book,
+        book::{
+            status::{BookStatus, BookStatusHistory},
+            Book,
+        },
         id::{BookId, UserId},
         list::PaginatedList,
         user::BookOwner,
@@ -18,6 +26,32 @@ use shared::error::AppError;
 use std::sync::Arc;
 use tower::ServiceExt;
 
+#[rstest]
+#[tokio::test]
+async fn register_book_201(mut fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_create()
+            .withf(|event, _, _| event.title == "Rust in Action")
+            .returning(move |_, _, _| Ok(book_id));
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    let req = Request::post(v1("/books"))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            r#"{"title":"Rust in Action","author":"Tim McNamara","isbn":"9781617294556","description":""}"#,
+        ))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    Ok(())
+}
+
 #[rstest]
 #[case("/books", 20, 0)]
 #[case("/books?limit=50", 50, 0)]
@@ -48,6 +82,7 @@ async fn show_book_list_with_query_200(
                     id: UserId::new(),
                     name: "Yuki Toyoda".to_string(),
                 },
+                status: BookStatus::Available,
                 checkout_info: None,
             }];
             Ok(PaginatedList {
@@ -83,7 +118,8 @@ async fn delete_book_204(mut fixture: registry::MockAppRegistryExt) -> anyhow::R
 
     fixture.expect_book_repository().returning(move || {
         let mut mock = MockBookRepository::new();
-        mock.expect_delete().returning(|_| Ok(()));
+        mock.expect_find_by_id().returning(|_| Ok(None));
+        mock.expect_delete().returning(|_, _| Ok(()));
         Arc::new(mock)
     });
 
@@ -106,8 +142,9 @@ async fn delete_book_404(mut fixture: registry::MockAppRegistryExt) -> anyhow::R
 
     fixture.expect_book_repository().returning(move || {
         let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id().returning(|_| Ok(None));
         mock.expect_delete()
-            .returning(|_| Err(AppError::NotFoundError("Not Found".into())));
+            .returning(|_, _| Err(AppError::NotFoundError("Not Found".into())));
         Arc::new(mock)
     });
 
@@ -175,6 +212,7 @@ async fn show_book_list_with_query_400(
                     id: UserId::new(),
                     name: "Ui Kozeki".to_string(),
                 },
+                status: BookStatus::Available,
                 checkout_info: None,
             }];
             Ok(PaginatedList {
@@ -197,3 +235,207 @@ async fn show_book_list_with_query_400(
 
     Ok(())
 }
+
+#[rstest]
+#[tokio::test]
+async fn update_book_status_403(fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+
+    let req = Request::put(v1(&format!("/books/{}/status", BookId::new())))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"status":"Damaged"}"#))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(r#"{"status":"Damaged","note":"水濡れ"}"#, StatusCode::OK)]
+#[case(r#"{"status":"OnLoan"}"#, StatusCode::UNPROCESSABLE_ENTITY)]
+#[case(r#"{"status":"Borrowed"}"#, StatusCode::UNPROCESSABLE_ENTITY)]
+#[tokio::test]
+async fn update_book_status(
+    fixture_auth: registry::MockAppRegistryExt,
+    #[case] body: &'static str,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id().returning(|_| Ok(None));
+        mock.expect_update_status()
+            .withf(move |event, _| event.book_id == book_id)
+            .returning(|event, _| match event.status {
+                BookStatus::OnLoan => Err(AppError::UnprocessableEntity("invalid".into())),
+                _ => Ok(()),
+            });
+        Arc::new(mock)
+    });
+    let app = make_router(registry);
+
+    let req = Request::put(v1(&format!("/books/{}/status", book_id)))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn show_book_status_history_200(
+    mut fixture: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    fixture.expect_book_repository().returning(|| {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_status_history().returning(|book_id| {
+            Ok(vec![
+                BookStatusHistory {
+                    book_id,
+                    status: BookStatus::Available,
+                    previous_status: None,
+                    changed_by: None,
+                    note: None,
+                    changed_at: chrono::Utc::now(),
+                },
+                BookStatusHistory {
+                    book_id,
+                    status: BookStatus::Lost,
+                    previous_status: Some(BookStatus::OnLoan),
+                    changed_by: Some(UserId::new()),
+                    note: None,
+                    changed_at: chrono::Utc::now(),
+                },
+            ])
+        });
+        Arc::new(mock)
+    });
+    let app = make_router(fixture);
+
+    let req = Request::get(v1(&format!("/books/{}/status-history", BookId::new())))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = deserialize_json!(resp, BookStatusHistoriesResponse);
+    assert_eq!(result.items.len(), 2);
+    assert_eq!(result.items[1].status, BookStatusName::Lost);
+    assert_eq!(
+        result.items[1].previous_status,
+        Some(BookStatusName::OnLoan)
+    );
+
+    Ok(())
+}
+
+const IMPORT_CSV: &str = "\
+書名,著者,ISBN,説明
+Rust in Action,Tim McNamara,9781617294556,
+,Steve Klabnik,9781718503106,著者のみ
+プログラミングRust,Jim Blandy,9784873119786,第2版
+";
+
+fn import_request(path: &str, body: impl Into<Body>) -> anyhow::Result<Request<Body>> {
+    Ok(Request::post(v1(path))
+        .bearer()
+        .header(header::CONTENT_TYPE, "text/csv")
+        .body(body.into())?)
+}
+
+#[rstest]
+#[tokio::test]
+async fn import_books_dry_run_200(mut fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    // 検証のみの場合は登録しない
+    fixture.expect_book_repository().never();
+
+    let app = make_router(fixture);
+    let req = import_request(
+        "/books/import?dryRun=true&titleColumn=書名&authorColumn=著者&isbnColumn=ISBN&descriptionColumn=説明",
+        IMPORT_CSV,
+    )?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = deserialize_json!(resp, BookImportResponse);
+    assert!(result.dry_run);
+    assert_eq!(result.total_rows, 3);
+    assert_eq!(result.valid_rows, 2);
+    assert!(result.imported_book_ids.is_empty());
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].line, 3);
+    assert_eq!(result.errors[0].field.as_deref(), Some("title"));
+    assert_eq!(result.errors[0].column.as_deref(), Some("書名"));
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn import_books_201(mut fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let book_ids = vec![BookId::new(), BookId::new()];
+    let returned_ids = book_ids.clone();
+    fixture.expect_book_repository().returning(move || {
+        let returned_ids = returned_ids.clone();
+        let mut mock = MockBookRepository::new();
+        // 検証に通った行のみを、まとめて登録する
+        mock.expect_create_many()
+            .withf(|events, _, _| {
+                events.len() == 2
+                    && events[0].title == "Rust in Action"
+                    && events[1].description == "第2版"
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(returned_ids.clone()));
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    // 表計算ソフトが出力したShift_JISのCSV
+    let (csv, _, _) = encoding_rs::SHIFT_JIS.encode(IMPORT_CSV);
+    let req = import_request(
+        "/books/import?encoding=shift_jis&titleColumn=書名&authorColumn=著者&isbnColumn=ISBN&descriptionColumn=説明",
+        csv.into_owned(),
+    )?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let result = deserialize_json!(resp, BookImportResponse);
+    assert!(!result.dry_run);
+    assert_eq!(result.imported_book_ids, book_ids);
+    assert_eq!(result.errors.len(), 1);
+
+    Ok(())
+}
+
+#[rstest]
+// 見出しに必須の列がない
+#[case("/books/import", IMPORT_CSV.as_bytes().to_vec())]
+// 文字コードの指定が誤っている
+#[case(
+    "/books/import?titleColumn=書名&authorColumn=著者&isbnColumn=ISBN",
+    encoding_rs::SHIFT_JIS.encode(IMPORT_CSV).0.into_owned()
+)]
+#[case("/books/import?encoding=euc-jp", IMPORT_CSV.as_bytes().to_vec())]
+#[tokio::test]
+async fn import_books_400(
+    mut fixture: registry::MockAppRegistryExt,
+    #[case] path: &str,
+    #[case] body: Vec<u8>,
+) -> anyhow::Result<()> {
+    fixture.expect_book_repository().never();
+
+    let app = make_router(fixture);
+    let resp = app.oneshot(import_request(path, body)?).await?;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use crate::{
+    deserialize_json,
+    helper::{fixture, make_router, v1, TestRequestExt},
+};
+use api::model::checkout::{BatchCheckoutResponse, BatchItemStatus};
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use kernel::{
+    model::{
+        checkout::{BatchItemOutcome, BatchItemResult, BatchMode},
+        id::{BookId, CheckoutId},
+    },
+    repository::checkout::MockCheckoutRepository,
+};
+use rstest::rstest;
+use shared::error::AppError;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+#[rstest]
+#[tokio::test]
+async fn checkout_books_200(mut fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let book_ids = [BookId::new(), BookId::new()];
+
+    fixture.expect_check_out_repository().returning(move || {
+        let mut mock = MockCheckoutRepository::new();
+        mock.expect_create_checkouts()
+            .withf(move |event, _| {
+                event.book_ids == book_ids && event.mode == BatchMode::BestEffort
+            })
+            .returning(|event, _| {
+                Ok(vec![
+                    BatchItemResult {
+                        book_id: event.book_ids[0],
+                        outcome: BatchItemOutcome::Succeeded(CheckoutId::new()),
+                    },
+                    BatchItemResult {
+                        book_id: event.book_ids[1],
+                        outcome: BatchItemOutcome::Failed(AppError::UnprocessableEntity(
+                            "貸出中です".into(),
+                        )),
+                    },
+                ])
+            });
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+
+    let req = Request::post(v1("/checkouts/batch"))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(format!(
+            r#"{{"bookIds":["{}","{}"]}}"#,
+            book_ids[0], book_ids[1]
+        )))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = deserialize_json!(resp, BatchCheckoutResponse);
+    assert!(!result.all_succeeded);
+    assert_eq!(result.items[0].status, BatchItemStatus::Succeeded);
+    assert!(result.items[0].checkout_id.is_some());
+    assert_eq!(result.items[1].status, BatchItemStatus::Failed);
+    assert_eq!(result.items[1].error_status, Some(422));
+    assert_eq!(result.items[1].error_message.as_deref(), Some("貸出中です"));
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn return_books_atomic_200(mut fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let book_ids = [BookId::new(), BookId::new()];
+
+    fixture.expect_check_out_repository().returning(move || {
+        let mut mock = MockCheckoutRepository::new();
+        mock.expect_return_books()
+            .withf(move |event, _| {
+                event.book_ids == book_ids && event.mode == BatchMode::AllOrNothing
+            })
+            .returning(|event, _| {
+                Ok(vec![
+                    BatchItemResult {
+                        book_id: event.book_ids[0],
+                        outcome: BatchItemOutcome::RolledBack(CheckoutId::new()),
+                    },
+                    BatchItemResult {
+                        book_id: event.book_ids[1],
+                        outcome: BatchItemOutcome::Failed(AppError::NotFoundError(
+                            "見つかりません".into(),
+                        )),
+                    },
+                ])
+            });
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+
+    let req = Request::post(v1("/returns/batch"))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(format!(
+            r#"{{"bookIds":["{}","{}"],"atomic":true}}"#,
+            book_ids[0], book_ids[1]
+        )))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = deserialize_json!(resp, BatchCheckoutResponse);
+    assert!(!result.all_succeeded);
+    assert_eq!(result.items[0].status, BatchItemStatus::RolledBack);
+    assert_eq!(result.items[1].error_status, Some(404));
+
+    Ok(())
+}
+
+#[rstest]
+#[case("/checkouts/batch", r#"{"bookIds":[]}"#)]
+#[case("/returns/batch", r#"{"bookIds":[]}"#)]
+#[tokio::test]
+async fn batch_400(
+    fixture: registry::MockAppRegistryExt,
+    #[case] path: &str,
+    #[case] body: &'static str,
+) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+
+    let req = Request::post(v1(path))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
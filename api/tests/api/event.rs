@@ -0,0 +1,362 @@
+use crate::helper::{fixture, make_router, v1, TestRequestExt};
+use api::model::{
+    book::BookStatusName,
+    event::{BookAvailabilityResponse, BookWatchMessage},
+};
+use axum::{body::Body, http::Request, http::StatusCode};
+use futures_util::{SinkExt, StreamExt};
+use kernel::{
+    live::MockLiveEventBus,
+    model::{
+        book::{status::BookStatus, Book, CheckoutInfo},
+        id::{BookId, CheckoutId, DomainEventId, UserId},
+        live::{LiveCheckout, LiveEvent, LiveEventId, LiveEventKind},
+        user::{BookOwner, CheckOutUser},
+    },
+    repository::book::MockBookRepository,
+};
+use registry::MockAppRegistryExt;
+use rstest::rstest;
+use shared::config::LiveEventConfig;
+use std::{future::IntoFuture, sync::Arc};
+use tokio::{net::TcpStream, sync::broadcast};
+use tokio_tungstenite::{
+    tungstenite::{client::IntoClientRequest, handshake::client::Response, Message},
+    MaybeTlsStream, WebSocketStream,
+};
+use tower::ServiceExt;
+
+fn live_event(id: &str) -> LiveEvent {
+    LiveEvent {
+        id: LiveEventId(id.into()),
+        source_id: DomainEventId::new(),
+        occurred_at: chrono::Utc::now(),
+        kind: LiveEventKind::BookDeleted {
+            book_id: BookId::new(),
+        },
+    }
+}
+
+/// `live`を配信した後にバスを閉じ、レスポンスが終わるようにする
+/// `missed`は`Last-Event-ID`に`1-0`を送った場合に再送するイベント
+fn registry_with_events(
+    mut registry: MockAppRegistryExt,
+    missed: Vec<LiveEvent>,
+    live: Vec<LiveEvent>,
+) -> MockAppRegistryExt {
+    let (sender, receiver) = broadcast::channel(16);
+    for event in live {
+        sender.send(event).unwrap();
+    }
+    drop(sender);
+    registry.expect_live_event_bus().return_once(move || {
+        let mut mock = MockLiveEventBus::new();
+        mock.expect_subscribe().return_once(move || receiver);
+        mock.expect_find_since()
+            .withf(|id| id.0 == "1-0")
+            .return_once(move |_| Ok(missed));
+        Arc::new(mock)
+    });
+    registry
+        .expect_live_event_config()
+        .returning(|| Arc::new(LiveEventConfig::default()));
+    registry
+}
+
+async fn received_ids(
+    app: axum::Router,
+    last_event_id: Option<&str>,
+) -> anyhow::Result<Vec<String>> {
+    let mut req = Request::get(v1("/events/stream")).bearer();
+    if let Some(id) = last_event_id {
+        req = req.header("Last-Event-ID", id);
+    }
+    let resp = app.oneshot(req.body(Body::empty())?).await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX).await?;
+    Ok(String::from_utf8(body.to_vec())?
+        .lines()
+        .filter_map(|line| line.strip_prefix("id: ").map(String::from))
+        .collect())
+}
+
+#[rstest]
+#[tokio::test]
+async fn stream_events_200(fixture: MockAppRegistryExt) -> anyhow::Result<()> {
+    let registry =
+        registry_with_events(fixture, vec![], vec![live_event("2-0"), live_event("3-0")]);
+
+    let ids = received_ids(make_router(registry), None).await?;
+    assert_eq!(ids, ["2-0", "3-0"]);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn stream_events_resume_from_last_event_id(
+    fixture: MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    // 再送したイベントと、購読してから届いたイベントが重なっても1回ずつ送る
+    let registry = registry_with_events(
+        fixture,
+        vec![live_event("2-0"), live_event("3-0")],
+        vec![live_event("3-0"), live_event("4-0")],
+    );
+
+    let ids = received_ids(make_router(registry), Some("1-0")).await?;
+    assert_eq!(ids, ["2-0", "3-0", "4-0"]);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn stream_events_401(mut fixture: MockAppRegistryExt) -> anyhow::Result<()> {
+    fixture.expect_live_event_bus().never();
+    let app = make_router(fixture);
+
+    let req = Request::get(v1("/events/stream")).body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn stream_events_401_with_subprotocol_token(
+    mut fixture: MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    // サブプロトコルでのトークンの受け渡しは、蔵書の購読の接続でのみ受け付ける
+    fixture.expect_live_event_bus().never();
+    let app = make_router(fixture);
+
+    let req = Request::get(v1("/events/stream"))
+        .header("Upgrade", "websocket")
+        .header("Sec-WebSocket-Protocol", "book-watch, bearer.dummy")
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// WebSocketの接続を受け付けられるよう、実際にサーバーを起動して接続する
+async fn connect(registry: MockAppRegistryExt) -> anyhow::Result<Client> {
+    let (client, _) = connect_with(registry, "Authorization", "Bearer dummy").await?;
+    Ok(client)
+}
+
+async fn connect_with(
+    registry: MockAppRegistryExt,
+    header: &'static str,
+    value: &str,
+) -> anyhow::Result<(Client, Response)> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(axum::serve(listener, make_router(registry)).into_future());
+
+    let mut req = format!("ws://{addr}{}", v1("/events/books")).into_client_request()?;
+    req.headers_mut().insert(header, value.parse()?);
+    Ok(tokio_tungstenite::connect_async(req).await?)
+}
+
+async fn send(client: &mut Client, message: serde_json::Value) -> anyhow::Result<()> {
+    client.send(Message::Text(message.to_string())).await?;
+    Ok(())
+}
+
+async fn receive(client: &mut Client) -> anyhow::Result<BookWatchMessage> {
+    loop {
+        match client.next().await {
+            Some(Ok(Message::Text(text))) => return Ok(serde_json::from_str(&text)?),
+            Some(Ok(_)) => continue,
+            other => anyhow::bail!("unexpected message: {other:?}"),
+        }
+    }
+}
+
+fn live_event_of(kind: LiveEventKind) -> LiveEvent {
+    LiveEvent {
+        kind,
+        ..live_event("1-0")
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn watch_books(mut fixture: MockAppRegistryExt) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let due_at = chrono::Utc::now();
+    let (sender, receiver) = broadcast::channel(16);
+    fixture.expect_live_event_bus().return_once(move || {
+        let mut mock = MockLiveEventBus::new();
+        mock.expect_subscribe().return_once(move || receiver);
+        Arc::new(mock)
+    });
+    fixture
+        .expect_live_event_config()
+        .returning(|| Arc::new(LiveEventConfig::default()));
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id().returning(move |id| {
+            if id != book_id {
+                return Ok(None);
+            }
+            Ok(Some(Book {
+                id,
+                title: "RustによるWebアプリケーション開発".into(),
+                author: "Yuki Toyoda".into(),
+                isbn: "1234567890".into(),
+                description: "".into(),
+                owner: BookOwner {
+                    id: UserId::new(),
+                    name: "Yuki Toyoda".into(),
+                },
+                status: BookStatus::OnLoan,
+                checkout_info: Some(CheckoutInfo {
+                    checkout_id: CheckoutId::new(),
+                    checked_out_by: CheckOutUser {
+                        id: UserId::new(),
+                        name: "dummy-user".into(),
+                    },
+                    checked_out_at: due_at,
+                    due_at,
+                }),
+            }))
+        });
+        Arc::new(mock)
+    });
+    let mut client = connect(fixture).await?;
+
+    // 購読を開始すると、その時点の貸出状況が届く
+    send(
+        &mut client,
+        serde_json::json!({ "type": "subscribe", "bookId": book_id }),
+    )
+    .await?;
+    assert_eq!(
+        receive(&mut client).await?,
+        BookWatchMessage::Subscribed(BookAvailabilityResponse {
+            book_id,
+            status: BookStatusName::OnLoan,
+            due_at: Some(due_at),
+        })
+    );
+
+    // 購読していない蔵書の変化は届かない
+    let other_book_id = BookId::new();
+    send(
+        &mut client,
+        serde_json::json!({ "type": "subscribe", "bookId": other_book_id }),
+    )
+    .await?;
+    assert!(matches!(
+        receive(&mut client).await?,
+        BookWatchMessage::Error { book_id: Some(id), .. } if id == other_book_id
+    ));
+    let checkout = |book_id| LiveCheckout {
+        book_id,
+        checkout_id: CheckoutId::new(),
+        due_at,
+    };
+    sender.send(live_event_of(LiveEventKind::Returned(checkout(
+        other_book_id,
+    ))))?;
+    sender.send(live_event_of(LiveEventKind::Returned(checkout(book_id))))?;
+    assert_eq!(
+        receive(&mut client).await?,
+        BookWatchMessage::Availability(BookAvailabilityResponse {
+            book_id,
+            status: BookStatusName::Available,
+            due_at: None,
+        })
+    );
+
+    sender.send(live_event_of(LiveEventKind::BookStatusChanged {
+        book_id,
+        status: BookStatus::Damaged,
+    }))?;
+    assert!(matches!(
+        receive(&mut client).await?,
+        BookWatchMessage::Availability(BookAvailabilityResponse {
+            status: BookStatusName::Damaged,
+            ..
+        })
+    ));
+
+    // 不正なメッセージにはエラーを返し、接続は維持する
+    client.send(Message::Text("{}".into())).await?;
+    assert!(matches!(
+        receive(&mut client).await?,
+        BookWatchMessage::Error { book_id: None, .. }
+    ));
+    send(&mut client, serde_json::json!({ "type": "ping" })).await?;
+    assert_eq!(receive(&mut client).await?, BookWatchMessage::Pong);
+
+    // 購読を終了すると、変化は届かなくなる
+    send(
+        &mut client,
+        serde_json::json!({ "type": "unsubscribe", "bookId": book_id }),
+    )
+    .await?;
+    assert_eq!(
+        receive(&mut client).await?,
+        BookWatchMessage::Unsubscribed { book_id }
+    );
+    sender.send(live_event_of(LiveEventKind::BookDeleted { book_id }))?;
+    send(&mut client, serde_json::json!({ "type": "ping" })).await?;
+    assert_eq!(receive(&mut client).await?, BookWatchMessage::Pong);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn watch_books_with_subprotocol_token(mut fixture: MockAppRegistryExt) -> anyhow::Result<()> {
+    let (_sender, receiver) = broadcast::channel::<LiveEvent>(16);
+    fixture.expect_live_event_bus().return_once(move || {
+        let mut mock = MockLiveEventBus::new();
+        mock.expect_subscribe().return_once(move || receiver);
+        Arc::new(mock)
+    });
+    fixture
+        .expect_live_event_config()
+        .returning(|| Arc::new(LiveEventConfig::default()));
+
+    // ブラウザはAuthorizationヘッダーを送れないため、トークンをサブプロトコルで送る
+    let (mut client, resp) = connect_with(
+        fixture,
+        "Sec-WebSocket-Protocol",
+        "book-watch, bearer.dummy",
+    )
+    .await?;
+    assert_eq!(resp.headers()["Sec-WebSocket-Protocol"], "book-watch");
+    send(&mut client, serde_json::json!({ "type": "ping" })).await?;
+    assert_eq!(receive(&mut client).await?, BookWatchMessage::Pong);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn watch_books_401(mut fixture: MockAppRegistryExt) -> anyhow::Result<()> {
+    fixture.expect_live_event_bus().never();
+    let app = make_router(fixture);
+
+    let req = Request::get(v1("/events/books"))
+        .header("Connection", "upgrade")
+        .header("Upgrade", "websocket")
+        .header("Sec-WebSocket-Version", "13")
+        .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
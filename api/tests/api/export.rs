@@ -0,0 +1,270 @@
+use crate::helper::{admin_with, fixture, fixture_auth, make_router, v1, TestRequestExt};
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use chrono::{TimeZone, Utc};
+use kernel::{
+    model::{
+        book::{status::BookStatus, Book, CheckoutInfo},
+        checkout::{Checkout, CheckoutBook},
+        id::{BookId, CheckoutId, UserId},
+        user::{BookOwner, CheckOutUser},
+    },
+    repository::export::MockExportRepository,
+};
+use registry::MockAppRegistryExt;
+use rstest::rstest;
+use shared::error::AppError;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn book(title: &str, checkout_info: Option<CheckoutInfo>) -> Book {
+    Book {
+        id: BookId::new(),
+        title: title.into(),
+        author: "山田太郎".into(),
+        isbn: "9784000000001".into(),
+        description: "".into(),
+        owner: BookOwner {
+            id: UserId::new(),
+            name: "owner".into(),
+        },
+        status: if checkout_info.is_some() {
+            BookStatus::OnLoan
+        } else {
+            BookStatus::Available
+        },
+        checkout_info,
+    }
+}
+
+fn checkout(returned: bool) -> Checkout {
+    let at = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+    Checkout {
+        id: CheckoutId::new(),
+        checked_out_by: UserId::new(),
+        checked_out_at: at,
+        returned_at: returned.then_some(at),
+        due_at: at,
+        renewal_count: 0,
+        checked_out_via: None,
+        returned_via: None,
+        book: CheckoutBook {
+            book_id: BookId::new(),
+            title: "Rust入門".into(),
+            author: "山田太郎".into(),
+            isbn: "9784000000001".into(),
+        },
+    }
+}
+
+async fn body_text(resp: axum::response::Response) -> anyhow::Result<String> {
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX).await?;
+    Ok(String::from_utf8(body.to_vec())?)
+}
+
+#[rstest]
+#[tokio::test]
+async fn export_books_csv_200(fixture_auth: MockAppRegistryExt) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_export_repository().returning(|| {
+        let mut mock = MockExportRepository::new();
+        mock.expect_stream_books().times(1).returning(|| {
+            let checkout_info = CheckoutInfo {
+                checkout_id: CheckoutId::new(),
+                checked_out_by: CheckOutUser {
+                    id: UserId::new(),
+                    name: "borrower".into(),
+                },
+                checked_out_at: Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+                due_at: Utc.with_ymd_and_hms(2026, 10, 15, 0, 0, 0).unwrap(),
+            };
+            Box::pin(tokio_stream::iter(vec![
+                Ok(book("Rust, 入門", None)),
+                Ok(book("Rust実践", Some(checkout_info))),
+            ]))
+        });
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let req = Request::get(v1("/exports/books"))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers()[header::CONTENT_TYPE],
+        "text/csv; charset=utf-8"
+    );
+    assert_eq!(
+        resp.headers()[header::CONTENT_DISPOSITION],
+        r#"attachment; filename="books.csv""#
+    );
+    let text = body_text(resp).await?;
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("title,author,isbn,description,id,status,"));
+    // カンマを含む値は引用符で囲む
+    assert!(lines[1].starts_with(r#""Rust, 入門",山田太郎,9784000000001,,"#));
+    assert!(lines[1].ends_with(",,,,,"));
+    assert!(lines[2].contains(",OnLoan,"));
+    assert!(lines[2].contains(",borrower,2026-10-01T00:00:00+00:00,2026-10-15T00:00:00+00:00"));
+
+    Ok(())
+}
+
+#[rstest]
+#[case("marc", "application/marc", "books.mrc")]
+#[case("marcxml", "application/marcxml+xml", "books.xml")]
+#[tokio::test]
+async fn export_books_marc_200(
+    fixture_auth: MockAppRegistryExt,
+    #[case] format: &str,
+    #[case] content_type: &str,
+    #[case] filename: &str,
+) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_export_repository().returning(|| {
+        let mut mock = MockExportRepository::new();
+        mock.expect_stream_books().returning(|| {
+            Box::pin(tokio_stream::iter(vec![
+                Ok(book("Rust入門", None)),
+                Ok(book("Rust実践", None)),
+            ]))
+        });
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let req = Request::get(v1(&format!("/exports/books?format={format}")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], content_type);
+    assert_eq!(
+        resp.headers()[header::CONTENT_DISPOSITION],
+        format!(r#"attachment; filename="{filename}""#)
+    );
+    let text = body_text(resp).await?;
+    if format == "marc" {
+        // 2件のレコードが、それぞれレコード終端記号で終わる
+        assert_eq!(text.matches('\u{1D}').count(), 2);
+        assert!(text.ends_with('\u{1D}'));
+    } else {
+        assert!(text.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+        assert_eq!(text.matches("<record>").count(), 2);
+        assert!(text.ends_with("</collection>\n"));
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn export_checkouts_ndjson_200(fixture_auth: MockAppRegistryExt) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_export_repository().returning(|| {
+        let mut mock = MockExportRepository::new();
+        mock.expect_stream_checkouts().times(1).returning(|| {
+            Box::pin(tokio_stream::iter(vec![
+                Ok(checkout(true)),
+                Ok(checkout(false)),
+            ]))
+        });
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let req = Request::get(v1("/exports/checkouts?format=ndjson"))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/x-ndjson");
+    let text = body_text(resp).await?;
+    let lines = text
+        .lines()
+        .map(serde_json::from_str::<serde_json::Value>)
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["returnedAt"], "2026-10-01T00:00:00Z");
+    assert!(lines[1]["returnedAt"].is_null());
+    assert_eq!(lines[1]["book"]["title"], "Rust入門");
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn export_stream_error_aborts_body(fixture_auth: MockAppRegistryExt) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_export_repository().returning(|| {
+        let mut mock = MockExportRepository::new();
+        mock.expect_stream_checkouts().returning(|| {
+            Box::pin(tokio_stream::iter(vec![
+                Ok(checkout(true)),
+                Err(AppError::ConversionEntityError("broken".into())),
+            ]))
+        });
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let req = Request::get(v1("/exports/checkouts"))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    // 送信を始めた後のエラーは、本文を途中で打ち切ることで伝える
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[rstest]
+#[case("marc")]
+#[case("marcxml")]
+#[case("xlsx")]
+#[tokio::test]
+async fn export_checkouts_400(
+    fixture_auth: MockAppRegistryExt,
+    #[case] format: &str,
+) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_export_repository().never();
+
+    let app = make_router(registry);
+    let req = Request::get(v1(&format!("/exports/checkouts?format={format}")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("/exports/books")]
+#[case("/exports/checkouts")]
+#[tokio::test]
+async fn export_403(mut fixture: MockAppRegistryExt, #[case] path: &str) -> anyhow::Result<()> {
+    fixture.expect_export_repository().never();
+
+    let app = make_router(fixture);
+    let req = Request::get(v1(path)).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use crate::{
+    deserialize_json,
+    helper::{
+        admin_with, assert_admin_only, fixture, fixture_auth, make_router, v1, TestRequestExt,
+    },
+};
+use api::model::fee::{FeeEntryKindName, FeeLedgerResponse};
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use chrono::Utc;
+use kernel::{
+    model::{
+        fee::{FeeEntry, FeeEntryKind, FeeLedger},
+        id::{FeeEntryId, UserId},
+    },
+    repository::fee::MockFeeRepository,
+};
+use rstest::rstest;
+use shared::error::AppError;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+#[rstest]
+#[case(Request::get(v1("/fee-schedule")))]
+#[case(Request::put(v1("/fee-schedule")))]
+#[case(Request::get(v1(&format!("/users/{}/fees", UserId::new()))))]
+#[case(Request::post(v1(&format!("/users/{}/fees/payments", UserId::new()))))]
+#[case(Request::post(v1(&format!("/users/{}/fees/waivers", UserId::new()))))]
+#[tokio::test]
+async fn fee_admin_403(
+    fixture: registry::MockAppRegistryExt,
+    #[case] req: axum::http::request::Builder,
+) -> anyhow::Result<()> {
+    assert_admin_only(
+        fixture,
+        req,
+        r#"{"amount":100,"overdueFeePerDay":0,"lostBookFee":0}"#,
+    )
+    .await
+}
+
+#[rstest]
+#[tokio::test]
+async fn get_my_fees_200(mut fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    fixture.expect_fee_repository().returning(|| {
+        let mut mock = MockFeeRepository::new();
+        mock.expect_find_ledger_by_user_id().returning(|user_id| {
+            Ok(Some(FeeLedger {
+                user_id,
+                balance: 30,
+                entries: vec![FeeEntry {
+                    id: FeeEntryId::new(),
+                    user_id,
+                    kind: FeeEntryKind::Overdue,
+                    amount: 30,
+                    checkout_id: None,
+                    book_id: None,
+                    note: None,
+                    recorded_by: None,
+                    created_at: Utc::now(),
+                }],
+            }))
+        });
+        Arc::new(mock)
+    });
+    let app = make_router(fixture);
+
+    let req = Request::get(v1("/users/me/fees"))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = deserialize_json!(resp, FeeLedgerResponse);
+    assert_eq!(result.balance, 30);
+    assert_eq!(result.entries.len(), 1);
+    assert_eq!(result.entries[0].kind, FeeEntryKindName::Overdue);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(
+    r#"{"overdueFeePerDay":10,"lostBookFee":1500,"checkoutBlockThreshold":500}"#,
+    StatusCode::OK
+)]
+#[case(
+    r#"{"overdueFeePerDay":-1,"lostBookFee":1500}"#,
+    StatusCode::BAD_REQUEST
+)]
+#[tokio::test]
+async fn update_fee_schedule(
+    fixture_auth: registry::MockAppRegistryExt,
+    #[case] body: &'static str,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_fee_repository().returning(|| {
+        let mut mock = MockFeeRepository::new();
+        mock.expect_update_schedule()
+            .withf(|event| {
+                event.schedule.overdue_fee_per_day == 10
+                    && event.schedule.max_overdue_fee.is_none()
+                    && event.schedule.checkout_block_threshold == Some(500)
+            })
+            .returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    let app = make_router(registry);
+
+    let req = Request::put(v1("/fee-schedule"))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(
+    "payments",
+    r#"{"amount":100,"note":"窓口で支払い"}"#,
+    StatusCode::CREATED
+)]
+#[case("waivers", r#"{"amount":100}"#, StatusCode::CREATED)]
+#[case("payments", r#"{"amount":0}"#, StatusCode::BAD_REQUEST)]
+#[case("waivers", r#"{"amount":5000}"#, StatusCode::UNPROCESSABLE_ENTITY)]
+#[tokio::test]
+async fn record_fee_credit(
+    fixture_auth: registry::MockAppRegistryExt,
+    #[case] path: &'static str,
+    #[case] body: &'static str,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let target = UserId::new();
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_fee_repository().returning(move || {
+        let mut mock = MockFeeRepository::new();
+        mock.expect_record_payment()
+            .withf(move |event| event.user_id == target && event.amount == 100)
+            .returning(|_| Ok(()));
+        mock.expect_waive()
+            .withf(move |event| event.user_id == target)
+            .returning(|event| {
+                if event.amount > 1000 {
+                    Err(AppError::UnprocessableEntity("exceeds balance".into()))
+                } else {
+                    Ok(())
+                }
+            });
+        Arc::new(mock)
+    });
+    let app = make_router(registry);
+
+    let req = Request::post(v1(&format!("/users/{}/fees/{}", target, path)))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
@@ -1,9 +1,13 @@
-use crate::helper::{fixture_registry, make_router, v1};
+use crate::{
+    deserialize_json,
+    helper::{fixture_registry, make_router, v1},
+};
+use api::model::health::MigrationStatusResponse;
 use axum::{
     body::Body,
     http::{Request, StatusCode},
 };
-use kernel::repository::health::MockHealthCheckRepository;
+use kernel::{model::migration::PendingMigration, repository::health::MockHealthCheckRepository};
 use rstest::rstest;
 use std::sync::Arc;
 use tower::ServiceExt;
@@ -45,3 +49,37 @@ async fn health_check_db(
     assert_eq!(resp.status(), expected_status);
     Ok(())
 }
+
+#[rstest]
+#[case(vec![], StatusCode::OK)]
+#[case(
+    vec![PendingMigration { version: 20261019090000, description: "default roles".into() }],
+    StatusCode::SERVICE_UNAVAILABLE
+)]
+#[tokio::test]
+async fn health_check_migrations(
+    mut fixture_registry: registry::MockAppRegistryExt,
+    #[case] pending: Vec<PendingMigration>,
+    #[case] expected_status: StatusCode,
+) -> anyhow::Result<()> {
+    let expected_pending = pending.len();
+    fixture_registry
+        .expect_health_check_repository()
+        .returning(move || {
+            let mut mock = MockHealthCheckRepository::new();
+            let pending = pending.clone();
+            mock.expect_pending_migrations()
+                .returning(move || Ok(pending.clone()));
+            Arc::new(mock)
+        });
+
+    let app = make_router(fixture_registry);
+    let req = Request::get(v1("/health/migrations")).body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), expected_status);
+    let result = deserialize_json!(resp, MigrationStatusResponse);
+    assert_eq!(result.up_to_date, expected_pending == 0);
+    assert_eq!(result.pending.len(), expected_pending);
+    Ok(())
+}
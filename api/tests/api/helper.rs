@@ -1,13 +1,20 @@
 use std::sync::Arc;
 
 use api::route::{auth, v1};
-use axum::{http::request::Builder, Router};
+use axum::{
+    body::Body,
+    http::{header, request::Builder, StatusCode},
+    Router,
+};
 use kernel::{
     model::{auth::AccessToken, id::UserId, role::Role, user::User},
-    repository::{auth::MockAuthRepository, user::MockUserRepository},
+    repository::{
+        audit::MockAuditLogRepository, auth::MockAuthRepository, user::MockUserRepository,
+    },
 };
 use registry::MockAppRegistryExt;
 use rstest::fixture;
+use tower::ServiceExt;
 
 pub fn v1(endpoint: &str) -> String {
     format!("/api/v1{}", endpoint)
@@ -22,7 +29,14 @@ pub fn make_router(registry: MockAppRegistryExt) -> Router {
 
 #[fixture]
 pub fn fixture_registry() -> MockAppRegistryExt {
-    MockAppRegistryExt::new()
+    let mut registry = MockAppRegistryExt::new();
+    // 監査ログの記録内容を検証しないテストのため、既定では何もせず受け付ける
+    registry.expect_audit_log_repository().returning(|| {
+        let mut mock = MockAuditLogRepository::new();
+        mock.expect_create().returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    registry
 }
 
 #[fixture]
@@ -55,6 +69,7 @@ pub fn fixture(mut fixture_auth: MockAppRegistryExt) -> MockAppRegistryExt {
                     name: "dummy-user".to_string(),
                     email: "dummy@example.com".to_string(),
                     role: Role::User,
+                    deactivated_at: None,
                 }))
             });
         Arc::new(mock_user_repository)
@@ -62,6 +77,48 @@ pub fn fixture(mut fixture_auth: MockAppRegistryExt) -> MockAppRegistryExt {
     fixture_auth
 }
 
+/// 管理者としてログインした状態で、ユーザーリポジトリのモックを差し替える
+pub fn admin_with(
+    mut fixture_auth: MockAppRegistryExt,
+    setup: impl Fn(&mut MockUserRepository) + Send + Sync + 'static,
+) -> MockAppRegistryExt {
+    fixture_auth.expect_user_repository().returning(move || {
+        let mut mock = MockUserRepository::new();
+        mock.expect_find_current_user().returning(|id| {
+            Ok(Some(User {
+                id,
+                name: "dummy-admin".to_string(),
+                email: "admin@example.com".to_string(),
+                role: Role::Admin,
+                deactivated_at: None,
+            }))
+        });
+        setup(&mut mock);
+        Arc::new(mock)
+    });
+    fixture_auth
+}
+
+/// 管理者のみが行える操作を一般の利用者として要求すると、403となることを確かめる
+/// `body`は各操作のリクエストの検証を通る内容とし、権限の確認によって拒否されることを確かめる
+pub async fn assert_admin_only(
+    registry: MockAppRegistryExt,
+    req: Builder,
+    body: &'static str,
+) -> anyhow::Result<()> {
+    let app = make_router(registry);
+
+    let req = req
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
 pub trait TestRequestExt {
     fn bearer(self) -> Builder;
 }
@@ -0,0 +1,281 @@
+use crate::{
+    deserialize_json,
+    helper::{admin_with, fixture, fixture_auth, make_router, v1, TestRequestExt},
+};
+use api::model::{
+    checkout::BatchItemStatus,
+    kiosk::{IssuedKioskResponse, KioskCheckoutResponse},
+};
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use kernel::{
+    model::{
+        audit::AuditAction,
+        checkout::{BatchItemOutcome, BatchItemResult, BatchMode},
+        id::{BookId, CheckoutId, KioskId, UserId},
+        kiosk::{IssuedKiosk, Kiosk, KioskSecret},
+        role::Role,
+        user::User,
+    },
+    repository::{
+        checkout::MockCheckoutRepository, kiosk::MockKioskRepository, user::MockUserRepository,
+    },
+};
+use registry::MockAppRegistryExt;
+use rstest::rstest;
+use shared::error::AppError;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const KIOSK_KEY: &str = "bmkiosk_dummy";
+const BADGE_CODE: &str = "EMP-0001";
+
+fn kiosk(id: KioskId) -> Kiosk {
+    Kiosk {
+        id,
+        name: "2F shelf".into(),
+        key_prefix: "bmkiosk_dummy".into(),
+        created_by: None,
+        created_at: chrono::Utc::now(),
+        last_used_at: None,
+        revoked_at: None,
+    }
+}
+
+/// キオスクの資格情報と、バッジを登録したユーザーを用意する
+fn kiosk_registry(
+    kiosk_id: KioskId,
+    badge_holder: UserId,
+    rate_limited: bool,
+) -> MockAppRegistryExt {
+    let mut registry = MockAppRegistryExt::new();
+    registry.expect_kiosk_repository().returning(move || {
+        let mut mock = MockKioskRepository::new();
+        mock.expect_verify()
+            .withf(|secret| secret.0 == KIOSK_KEY)
+            .returning(move |_| Ok(Some(kiosk(kiosk_id))));
+        mock.expect_verify().returning(|_| Ok(None));
+        mock.expect_consume_rate_limit()
+            .withf(move |id| *id == kiosk_id)
+            .returning(move |_| {
+                if rate_limited {
+                    Err(AppError::TooManyRequestsError)
+                } else {
+                    Ok(())
+                }
+            });
+        Arc::new(mock)
+    });
+    registry.expect_user_repository().returning(move || {
+        let mut mock = MockUserRepository::new();
+        mock.expect_find_by_badge_code().returning(move |code| {
+            Ok((code == BADGE_CODE).then(|| User {
+                id: badge_holder,
+                name: "Badge Holder".into(),
+                email: "holder@example.com".into(),
+                role: Role::User,
+                deactivated_at: None,
+            }))
+        });
+        Arc::new(mock)
+    });
+    registry
+}
+
+fn kiosk_request(endpoint: &str, key: Option<&str>, body: String) -> anyhow::Result<Request<Body>> {
+    let mut builder = Request::post(v1(endpoint)).header(header::CONTENT_TYPE, "application/json");
+    if let Some(key) = key {
+        builder = builder.header("X-Kiosk-Key", key);
+    }
+    Ok(builder.body(Body::from(body))?)
+}
+
+#[tokio::test]
+async fn kiosk_checkout_200() -> anyhow::Result<()> {
+    let kiosk_id = KioskId::new();
+    let user_id = UserId::new();
+    let book_id = BookId::new();
+
+    let mut registry = kiosk_registry(kiosk_id, user_id, false);
+    registry.expect_check_out_repository().returning(move || {
+        let mut mock = MockCheckoutRepository::new();
+        mock.expect_create_checkouts()
+            .withf(move |event, audit| {
+                event.book_ids == [book_id]
+                    && event.checked_out_by == user_id
+                    && event.checked_out_via == Some(kiosk_id)
+                    && event.mode == BatchMode::AllOrNothing
+                    // バッジの持ち主を操作者とし、キオスクとともに監査ログに記録する
+                    && audit.as_ref().is_some_and(|audit| {
+                        audit.action == AuditAction::Checkout
+                            && audit.actor_id == Some(user_id)
+                            && audit.after.as_ref().and_then(|after| after.get("kioskId"))
+                                == Some(&serde_json::json!(kiosk_id))
+                    })
+            })
+            .returning(|event, _| {
+                Ok(vec![BatchItemResult {
+                    book_id: event.book_ids[0],
+                    outcome: BatchItemOutcome::Succeeded(CheckoutId::new()),
+                }])
+            });
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let req = kiosk_request(
+        "/kiosk/checkouts",
+        Some(KIOSK_KEY),
+        format!(r#"{{"badgeCode":"{BADGE_CODE}","bookIds":["{book_id}"],"atomic":true}}"#),
+    )?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = deserialize_json!(resp, KioskCheckoutResponse);
+    assert_eq!(result.user_id, user_id);
+    assert_eq!(result.user_name, "Badge Holder");
+    assert!(result.result.all_succeeded);
+    assert_eq!(result.result.items[0].status, BatchItemStatus::Succeeded);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn kiosk_return_200() -> anyhow::Result<()> {
+    let kiosk_id = KioskId::new();
+    let user_id = UserId::new();
+    let book_id = BookId::new();
+
+    let mut registry = kiosk_registry(kiosk_id, user_id, false);
+    registry.expect_check_out_repository().returning(move || {
+        let mut mock = MockCheckoutRepository::new();
+        mock.expect_return_books()
+            .withf(move |event, audit| {
+                event.returned_by == user_id
+                    && event.returned_via == Some(kiosk_id)
+                    && event.mode == BatchMode::BestEffort
+                    && audit.as_ref().is_some_and(|audit| {
+                        audit.action == AuditAction::Return && audit.actor_id == Some(user_id)
+                    })
+            })
+            .returning(|event, _| {
+                Ok(vec![BatchItemResult {
+                    book_id: event.book_ids[0],
+                    outcome: BatchItemOutcome::Succeeded(CheckoutId::new()),
+                }])
+            });
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let req = kiosk_request(
+        "/kiosk/returns",
+        Some(KIOSK_KEY),
+        format!(r#"{{"badgeCode":"{BADGE_CODE}","bookIds":["{book_id}"]}}"#),
+    )?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    Ok(())
+}
+
+#[rstest]
+// キオスクの資格情報がない(利用者のアクセストークンでは呼び出せない)
+#[case(None, BADGE_CODE, false, StatusCode::UNAUTHORIZED)]
+// 資格情報が誤っている、または失効している
+#[case(Some("bmkiosk_wrong"), BADGE_CODE, false, StatusCode::UNAUTHORIZED)]
+// バッジが登録されていない
+#[case(Some(KIOSK_KEY), "EMP-9999", false, StatusCode::NOT_FOUND)]
+// バッジのコードが空
+#[case(Some(KIOSK_KEY), "", false, StatusCode::BAD_REQUEST)]
+// リクエスト数の上限を超えた
+#[case(Some(KIOSK_KEY), BADGE_CODE, true, StatusCode::TOO_MANY_REQUESTS)]
+#[tokio::test]
+async fn kiosk_checkout_rejected(
+    #[case] key: Option<&str>,
+    #[case] badge_code: &str,
+    #[case] rate_limited: bool,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let mut registry = kiosk_registry(KioskId::new(), UserId::new(), rate_limited);
+    // 貸出処理は呼び出されない
+    registry
+        .expect_check_out_repository()
+        .returning(|| Arc::new(MockCheckoutRepository::new()));
+
+    let app = make_router(registry);
+    let req = Request::post(v1("/kiosk/checkouts"))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json");
+    let req = match key {
+        Some(key) => req.header("X-Kiosk-Key", key),
+        None => req,
+    }
+    .body(Body::from(format!(
+        r#"{{"badgeCode":"{badge_code}","bookIds":["{}"]}}"#,
+        BookId::new()
+    )))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn create_kiosk_201(fixture_auth: MockAppRegistryExt) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_kiosk_repository().returning(|| {
+        let mut mock = MockKioskRepository::new();
+        mock.expect_create()
+            .withf(|event| event.name == "2F shelf")
+            .returning(|event| {
+                Ok(IssuedKiosk {
+                    kiosk: Kiosk {
+                        created_by: Some(event.created_by),
+                        ..kiosk(KioskId::new())
+                    },
+                    secret: KioskSecret(event.secret.0),
+                })
+            });
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let req = Request::post(v1("/kiosks"))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"name":"2F shelf"}"#))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let issued = deserialize_json!(resp, IssuedKioskResponse);
+    assert!(issued.key.starts_with("bmkiosk_"));
+    assert!(issued.kiosk.created_by.is_some());
+
+    Ok(())
+}
+
+#[rstest]
+#[case(Request::post(v1("/kiosks")).header(header::CONTENT_TYPE, "application/json").body(Body::from(r#"{"name":"2F shelf"}"#)))]
+#[case(Request::get(v1("/kiosks")).body(Body::empty()))]
+#[case(Request::delete(v1(&format!("/kiosks/{}", KioskId::new()))).body(Body::empty()))]
+#[tokio::test]
+async fn manage_kiosks_403(
+    fixture: MockAppRegistryExt,
+    #[case] req: Result<Request<Body>, axum::http::Error>,
+) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+    let mut req = req?;
+    req.headers_mut()
+        .insert(header::AUTHORIZATION, "Bearer dummy".parse()?);
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
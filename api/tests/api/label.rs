@@ -0,0 +1,166 @@
+use crate::{
+    deserialize_json,
+    helper::{fixture, make_router, v1, TestRequestExt},
+};
+use api::model::book::BookResponse;
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use kernel::{
+    model::{
+        book::{status::BookStatus, Book},
+        id::{BookId, UserId},
+        user::BookOwner,
+    },
+    repository::book::MockBookRepository,
+};
+use rstest::rstest;
+use shared::config::LabelConfig;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const LINK_TEMPLATE: &str = "https://library.example.com/books/{book_id}";
+
+fn book(id: BookId) -> Book {
+    Book {
+        id,
+        title: "RustによるWebアプリケーション開発".to_string(),
+        author: "Yuki Toyoda".to_string(),
+        isbn: "1234567890".to_string(),
+        description: "".to_string(),
+        owner: BookOwner {
+            id: UserId::new(),
+            name: "Yuki Toyoda".to_string(),
+        },
+        status: BookStatus::Available,
+        checkout_info: None,
+    }
+}
+
+/// `existing`の蔵書のみが登録されている状態にする
+fn with_books(
+    mut registry: registry::MockAppRegistryExt,
+    existing: Vec<BookId>,
+) -> registry::MockAppRegistryExt {
+    registry.expect_book_repository().returning(move || {
+        let existing = existing.clone();
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id()
+            .returning(move |id| Ok(existing.contains(&id).then(|| book(id))));
+        Arc::new(mock)
+    });
+    registry.expect_label_config().returning(|| {
+        Arc::new(LabelConfig {
+            link_template: Some(LINK_TEMPLATE.into()),
+        })
+    });
+    registry
+}
+
+#[rstest]
+#[case("", StatusCode::OK, Some("image/svg+xml"))]
+#[case(
+    "?format=png&symbology=code128&scale=2",
+    StatusCode::OK,
+    Some("image/png")
+)]
+#[case("?scale=0", StatusCode::BAD_REQUEST, None)]
+#[case("?symbology=ean13", StatusCode::BAD_REQUEST, None)]
+#[tokio::test]
+async fn show_book_label(
+    fixture: registry::MockAppRegistryExt,
+    #[case] query: &str,
+    #[case] expected: StatusCode,
+    #[case] content_type: Option<&str>,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let app = make_router(with_books(fixture, vec![book_id]));
+
+    let req = Request::get(v1(&format!("/books/{}/label{}", book_id, query)))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), expected);
+    if let Some(content_type) = content_type {
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], content_type);
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn show_book_label_404(fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let app = make_router(with_books(fixture, vec![]));
+
+    let req = Request::get(v1(&format!("/books/{}/label", BookId::new())))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn create_label_sheet(fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let book_ids = vec![BookId::new(), BookId::new()];
+    let app = make_router(with_books(fixture, book_ids.clone()));
+
+    let req = Request::post(v1("/books/labels"))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(format!(
+            r#"{{"bookIds":["{}","{}"],"symbology":"code128"}}"#,
+            book_ids[0], book_ids[1]
+        )))?;
+    let resp = app.clone().oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "image/svg+xml");
+
+    // 登録されていない蔵書を含む場合
+    let req = Request::post(v1("/books/labels"))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(format!(
+            r#"{{"bookIds":["{}"]}}"#,
+            BookId::new()
+        )))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn lookup_book(fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let app = make_router(with_books(fixture, vec![book_id]));
+
+    // ディープリンクのURLから蔵書を取得できる
+    let link = LINK_TEMPLATE.replace("{book_id}", &book_id.to_string());
+    let req = Request::get(v1(&format!("/books/lookup?code={}", link)))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.clone().oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = deserialize_json!(resp, BookResponse);
+    assert_eq!(result.id, book_id);
+
+    let req = Request::get(v1("/books/lookup?code=not-a-book"))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
@@ -0,0 +1,193 @@
+use crate::{
+    deserialize_json,
+    helper::{
+        admin_with, assert_admin_only, fixture, fixture_auth, make_router, v1, TestRequestExt,
+    },
+};
+use api::model::loan_policy::{RoleLoanPoliciesResponse, UserLoanPolicyResponse};
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use kernel::{
+    model::{
+        id::UserId,
+        loan_policy::{LoanPolicy, LoanPolicyOverride, RoleLoanPolicy, UserLoanPolicy},
+        role::Role,
+    },
+    repository::loan_policy::MockLoanPolicyRepository,
+};
+use rstest::rstest;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+#[rstest]
+#[case(Request::get(v1("/loan-policies")))]
+#[case(Request::put(v1("/loan-policies/User")))]
+#[case(Request::get(v1(&format!("/users/{}/loan-policy", UserId::new()))))]
+#[case(Request::delete(v1(&format!("/users/{}/loan-policy", UserId::new()))))]
+#[tokio::test]
+async fn loan_policy_403(
+    fixture: registry::MockAppRegistryExt,
+    #[case] req: axum::http::request::Builder,
+) -> anyhow::Result<()> {
+    assert_admin_only(
+        fixture,
+        req,
+        r#"{"maxLoans":1,"loanPeriodDays":1,"maxRenewals":0}"#,
+    )
+    .await
+}
+
+#[rstest]
+#[tokio::test]
+async fn list_loan_policies_200(fixture_auth: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_loan_policy_repository().returning(|| {
+        let mut mock = MockLoanPolicyRepository::new();
+        mock.expect_find_all_role_policies().returning(|| {
+            Ok(vec![
+                RoleLoanPolicy {
+                    role: Role::Admin,
+                    policy: LoanPolicy::default(),
+                },
+                RoleLoanPolicy {
+                    role: Role::User,
+                    policy: LoanPolicy {
+                        max_loans: 3,
+                        ..Default::default()
+                    },
+                },
+            ])
+        });
+        Arc::new(mock)
+    });
+    let app = make_router(registry);
+
+    let req = Request::get(v1("/loan-policies"))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = deserialize_json!(resp, RoleLoanPoliciesResponse);
+    assert_eq!(result.items.len(), 2);
+    assert_eq!(result.items[1].policy.max_loans, 3);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(
+    r#"{"maxLoans":10,"loanPeriodDays":21,"maxRenewals":1}"#,
+    StatusCode::OK
+)]
+#[case(
+    r#"{"maxLoans":-1,"loanPeriodDays":21,"maxRenewals":1}"#,
+    StatusCode::BAD_REQUEST
+)]
+#[case(
+    r#"{"maxLoans":10,"loanPeriodDays":0,"maxRenewals":1}"#,
+    StatusCode::BAD_REQUEST
+)]
+#[tokio::test]
+async fn update_loan_policy(
+    fixture_auth: registry::MockAppRegistryExt,
+    #[case] body: &'static str,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_loan_policy_repository().returning(|| {
+        let mut mock = MockLoanPolicyRepository::new();
+        mock.expect_update_role_policy()
+            .withf(|event| event.role == Role::User && event.policy.max_loans == 10)
+            .returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    let app = make_router(registry);
+
+    let req = Request::put(v1("/loan-policies/User"))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn get_user_loan_policy_200(
+    fixture_auth: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    let target = UserId::new();
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_loan_policy_repository().returning(move || {
+        let mut mock = MockLoanPolicyRepository::new();
+        mock.expect_find_by_user_id()
+            .withf(move |user_id| *user_id == target)
+            .returning(|user_id| {
+                Ok(Some(UserLoanPolicy {
+                    user_id,
+                    role_policy: LoanPolicy::default(),
+                    user_override: Some(LoanPolicyOverride {
+                        max_loans: Some(1),
+                        ..Default::default()
+                    }),
+                }))
+            });
+        Arc::new(mock)
+    });
+    let app = make_router(registry);
+
+    let req = Request::get(v1(&format!("/users/{}/loan-policy", target)))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = deserialize_json!(resp, UserLoanPolicyResponse);
+    assert_eq!(result.user_id, target);
+    assert_eq!(result.effective.max_loans, 1);
+    assert_eq!(
+        result.effective.loan_period_days,
+        LoanPolicy::default().loan_period_days
+    );
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn update_user_loan_policy_200(
+    fixture_auth: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_loan_policy_repository().returning(|| {
+        let mut mock = MockLoanPolicyRepository::new();
+        // 省略した項目はロールのポリシーに従う
+        mock.expect_update_user_policy()
+            .withf(|event| {
+                event.user_override
+                    == LoanPolicyOverride {
+                        max_loans: Some(0),
+                        ..Default::default()
+                    }
+            })
+            .returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    let app = make_router(registry);
+
+    let req = Request::put(v1(&format!("/users/{}/loan-policy", UserId::new())))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"maxLoans":0}"#))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    Ok(())
+}
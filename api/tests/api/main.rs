@@ -1,4 +1,16 @@
+mod api_key;
+mod audit;
+mod auth;
+mod backup;
 mod book;
+mod checkout;
+mod event;
+mod export;
+mod fee;
 mod health;
 mod helper;
+mod kiosk;
+mod label;
+mod loan_policy;
 mod user;
+mod webhook;
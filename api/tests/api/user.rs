@@ -1,6 +1,20 @@
-use crate::helper::{fixture, fixture_auth, fixture_registry, make_router, v1, TestRequestExt};
+use crate::{
+    deserialize_json,
+    helper::{admin_with, fixture, fixture_auth, make_router, v1, TestRequestExt},
+};
+use api::model::{checkout::PaginatedCheckoutResponse, user::UserDeletionBlockersResponse};
 use axum::{body::Body, http::Request};
+use kernel::{
+    model::{
+        checkout::{Checkout, CheckoutBook},
+        id::{BookId, CheckoutId, UserId},
+        list::PaginatedList,
+        user::UserDeletionBlockers,
+    },
+    repository::checkout::MockCheckoutRepository,
+};
 use rstest::rstest;
+use std::sync::Arc;
 use tower::ServiceExt;
 
 #[rstest]
@@ -20,3 +34,262 @@ async fn register_user_403(fixture: registry::MockAppRegistryExt) -> anyhow::Res
 
     Ok(())
 }
+
+#[rstest]
+#[tokio::test]
+async fn delete_user_deactivates_204(
+    fixture_auth: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    let target = UserId::new();
+    let app = make_router(admin_with(fixture_auth, move |mock| {
+        mock.expect_deactivate()
+            .withf(move |e, _| e.user_id == target)
+            .returning(|_, _| Ok(()));
+        mock.expect_delete().never();
+    }));
+
+    let req = Request::delete(v1(&format!("/users/{}", target)))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), axum::http::StatusCode::NO_CONTENT);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn register_and_unregister_badge(
+    fixture_auth: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    let target = UserId::new();
+    let app = make_router(admin_with(fixture_auth, move |mock| {
+        mock.expect_update_badge()
+            .withf(move |e, _| e.user_id == target && e.badge_code.as_deref() == Some("EMP-0001"))
+            .returning(|_, _| Ok(()));
+        mock.expect_update_badge()
+            .withf(move |e, _| e.user_id == target && e.badge_code.is_none())
+            .returning(|_, _| Ok(()));
+    }));
+
+    let req = Request::put(v1(&format!("/users/{}/badge", target)))
+        .header("Content-Type", "application/json")
+        .bearer()
+        .body(Body::from(r#"{"badgeCode": "EMP-0001"}"#))?;
+    let resp = app.clone().oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    let req = Request::delete(v1(&format!("/users/{}/badge", target)))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.clone().oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::NO_CONTENT);
+
+    // 空のコードは登録できない
+    let req = Request::put(v1(&format!("/users/{}/badge", target)))
+        .header("Content-Type", "application/json")
+        .bearer()
+        .body(Body::from(r#"{"badgeCode": ""}"#))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn reactivate_user_200(fixture_auth: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let target = UserId::new();
+    let app = make_router(admin_with(fixture_auth, move |mock| {
+        mock.expect_reactivate()
+            .withf(move |e, _| e.user_id == target)
+            .returning(|_, _| Ok(()));
+    }));
+
+    let req = Request::put(v1(&format!("/users/{}/reactivate", target)))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn purge_user_409_reports_blockers(
+    fixture_auth: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    let app = make_router(admin_with(fixture_auth, |mock| {
+        mock.expect_find_deletion_blockers().returning(|_| {
+            Ok(UserDeletionBlockers {
+                owned_books: 2,
+                unreturned_checkouts: 1,
+                fee_entries: 3,
+            })
+        });
+        mock.expect_delete().never();
+    }));
+
+    let req = Request::delete(v1(&format!("/users/{}/permanent", UserId::new())))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), axum::http::StatusCode::CONFLICT);
+    let result = deserialize_json!(resp, UserDeletionBlockersResponse);
+    assert_eq!(result.owned_books, 2);
+    assert_eq!(result.unreturned_checkouts, 1);
+    assert_eq!(result.fee_entries, 3);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn purge_user_204(fixture_auth: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let app = make_router(admin_with(fixture_auth, |mock| {
+        mock.expect_find_deletion_blockers()
+            .returning(|_| Ok(UserDeletionBlockers::default()));
+        mock.expect_delete().returning(|_, _| Ok(()));
+    }));
+
+    let req = Request::delete(v1(&format!("/users/{}/permanent", UserId::new())))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), axum::http::StatusCode::NO_CONTENT);
+
+    Ok(())
+}
+
+/// 貸出履歴のモックを設定する
+fn with_checkout_history(
+    mut registry: registry::MockAppRegistryExt,
+    expected_user: Option<UserId>,
+) -> registry::MockAppRegistryExt {
+    registry.expect_check_out_repository().returning(move || {
+        let mut mock = MockCheckoutRepository::new();
+        mock.expect_find_history_by_user_id()
+            .withf(move |user_id, options| {
+                expected_user.is_none_or(|u| u == *user_id)
+                    && options.limit == 1
+                    && options.offset == 1
+                    && options.from.is_some()
+                    && options.to.is_none()
+            })
+            .returning(|user_id, options| {
+                Ok(PaginatedList {
+                    total: 2,
+                    limit: options.limit,
+                    offset: options.offset,
+                    items: vec![Checkout {
+                        id: CheckoutId::new(),
+                        checked_out_by: user_id,
+                        checked_out_at: chrono::Utc::now(),
+                        returned_at: Some(chrono::Utc::now()),
+                        due_at: chrono::Utc::now(),
+                        renewal_count: 0,
+                        checked_out_via: None,
+                        returned_via: None,
+                        book: CheckoutBook {
+                            book_id: BookId::new(),
+                            title: "RustによるWebアプリケーション開発".into(),
+                            author: "豊田優貴他".into(),
+                            isbn: "978-4065369579".into(),
+                        },
+                    }],
+                })
+            });
+        Arc::new(mock)
+    });
+    registry
+}
+
+#[rstest]
+#[tokio::test]
+async fn get_checkout_history_200(fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    let app = make_router(with_checkout_history(fixture, None));
+
+    let req = Request::get(v1(
+        "/users/me/checkout-history?limit=1&offset=1&from=2024-04-01T00:00:00Z",
+    ))
+    .bearer()
+    .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let result = deserialize_json!(resp, PaginatedCheckoutResponse);
+    assert_eq!(result.total, 2);
+    assert_eq!(result.offset, 1);
+    assert_eq!(result.items.len(), 1);
+    assert!(result.items[0].returned_at.is_some());
+
+    Ok(())
+}
+
+#[rstest]
+#[case("/users/me/checkout-history?limit=-1")]
+#[case("/users/me/checkout-history?limit=101")]
+#[case("/users/me/checkout-history?from=2024-05-01T00:00:00Z&to=2024-04-01T00:00:00Z")]
+#[case("/users/me/checkout-history?from=yesterday")]
+#[tokio::test]
+async fn get_checkout_history_400(
+    fixture: registry::MockAppRegistryExt,
+    #[case] path: &str,
+) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+
+    let req = Request::get(v1(path)).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), axum::http::StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn get_user_checkout_history_403(
+    fixture: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+
+    let req = Request::get(v1(&format!("/users/{}/checkout-history", UserId::new())))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), axum::http::StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn get_user_checkout_history_200(
+    fixture_auth: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    let target = UserId::new();
+    let app = make_router(with_checkout_history(
+        admin_with(fixture_auth, |_| {}),
+        Some(target),
+    ));
+
+    let req = Request::get(v1(&format!(
+        "/users/{}/checkout-history?limit=1&offset=1&from=2024-04-01T00:00:00Z",
+        target
+    )))
+    .bearer()
+    .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let result = deserialize_json!(resp, PaginatedCheckoutResponse);
+    assert_eq!(result.items[0].checked_out_by, target);
+
+    Ok(())
+}
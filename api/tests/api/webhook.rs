@@ -0,0 +1,234 @@
+use crate::{
+    deserialize_json,
+    helper::{admin_with, fixture, fixture_auth, make_router, v1, TestRequestExt},
+};
+use api::model::webhook::{
+    IssuedWebhookResponse, WebhookDeliveriesResponse, WebhookDeliveryResponse,
+    WebhookDeliveryStatusName, WebhookEventName,
+};
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use kernel::{
+    model::{
+        id::{WebhookDeliveryId, WebhookEventId, WebhookId},
+        webhook::{
+            IssuedWebhook, Webhook, WebhookDelivery, WebhookDeliveryStatus, WebhookEventType,
+        },
+    },
+    repository::webhook::MockWebhookRepository,
+};
+use registry::MockAppRegistryExt;
+use rstest::rstest;
+use shared::error::AppError;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn delivery(webhook_id: WebhookId, status: WebhookDeliveryStatus) -> WebhookDelivery {
+    let now = chrono::Utc::now();
+    WebhookDelivery {
+        id: WebhookDeliveryId::new(),
+        webhook_id,
+        event_id: WebhookEventId::new(),
+        event_type: WebhookEventType::BookCreated,
+        payload: r#"{"type":"book.created"}"#.into(),
+        status,
+        attempts: 0,
+        response_status: None,
+        last_error: None,
+        next_attempt_at: now,
+        created_at: now,
+        delivered_at: None,
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn create_webhook_201(fixture_auth: MockAppRegistryExt) -> anyhow::Result<()> {
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_webhook_repository().returning(|| {
+        let mut mock = MockWebhookRepository::new();
+        // 重複したイベントの種類はまとめる
+        mock.expect_create()
+            .withf(|event| {
+                event.url == "https://example.com/hook"
+                    && event.events
+                        == [
+                            WebhookEventType::BookCreated,
+                            WebhookEventType::CheckoutReturned,
+                        ]
+            })
+            .returning(|event| {
+                Ok(IssuedWebhook {
+                    webhook: Webhook {
+                        id: WebhookId::new(),
+                        url: event.url,
+                        events: event.events,
+                        created_by: Some(event.created_by),
+                        created_at: chrono::Utc::now(),
+                    },
+                    secret: event.secret,
+                })
+            });
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let req = Request::post(v1("/webhooks"))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            r#"{"url":"https://example.com/hook","events":["book.created","checkout.returned","book.created"]}"#,
+        ))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let issued = deserialize_json!(resp, IssuedWebhookResponse);
+    assert!(issued.secret.starts_with("whsec_"));
+    assert_eq!(
+        issued.webhook.events,
+        [
+            WebhookEventName::BookCreated,
+            WebhookEventName::CheckoutReturned
+        ]
+    );
+
+    Ok(())
+}
+
+#[rstest]
+// httpまたはhttps以外のURL
+#[case(r#"{"url":"ftp://example.com/hook","events":["book.created"]}"#)]
+// URLとして不正
+#[case(r#"{"url":"not a url","events":["book.created"]}"#)]
+// イベントの種類が空
+#[case(r#"{"url":"https://example.com/hook","events":[]}"#)]
+#[tokio::test]
+async fn create_webhook_400(
+    fixture_auth: MockAppRegistryExt,
+    #[case] body: &'static str,
+) -> anyhow::Result<()> {
+    let registry = admin_with(fixture_auth, |_| {});
+
+    let app = make_router(registry);
+    let req = Request::post(v1("/webhooks"))
+        .bearer()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(Request::post(v1("/webhooks")).header(header::CONTENT_TYPE, "application/json").body(Body::from(r#"{"url":"https://example.com/hook","events":["book.created"]}"#)))]
+#[case(Request::get(v1("/webhooks")).body(Body::empty()))]
+#[case(Request::get(v1(&format!("/webhooks/{}/deliveries", WebhookId::new()))).body(Body::empty()))]
+#[case(Request::post(v1(&format!("/webhooks/{}/deliveries/{}/replay", WebhookId::new(), WebhookDeliveryId::new()))).body(Body::empty()))]
+#[tokio::test]
+async fn webhook_admin_endpoints_403(
+    fixture: MockAppRegistryExt,
+    #[case] req: Result<Request<Body>, axum::http::Error>,
+) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+    let (mut parts, body) = req?.into_parts();
+    parts
+        .headers
+        .insert(header::AUTHORIZATION, "Bearer dummy".parse()?);
+    let resp = app.oneshot(Request::from_parts(parts, body)).await?;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn list_webhook_deliveries_200(fixture_auth: MockAppRegistryExt) -> anyhow::Result<()> {
+    let webhook_id = WebhookId::new();
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_webhook_repository().returning(move || {
+        let mut mock = MockWebhookRepository::new();
+        mock.expect_find_deliveries()
+            .withf(move |id, limit| *id == webhook_id && *limit == 10)
+            .returning(|webhook_id, _| {
+                Ok(vec![
+                    WebhookDelivery {
+                        attempts: 3,
+                        response_status: Some(500),
+                        last_error: Some("Endpoint responded with status 500".into()),
+                        ..delivery(webhook_id, WebhookDeliveryStatus::Failed)
+                    },
+                    delivery(webhook_id, WebhookDeliveryStatus::Pending),
+                ])
+            });
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let req = Request::get(v1(&format!("/webhooks/{webhook_id}/deliveries?limit=10")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = deserialize_json!(resp, WebhookDeliveriesResponse);
+    let [failed, pending] = &result.items[..] else {
+        panic!("unexpected deliveries");
+    };
+    assert_eq!(failed.status, WebhookDeliveryStatusName::Failed);
+    assert_eq!(failed.response_status, Some(500));
+    // 送信待ちでない配信には、次の送信日時を返さない
+    assert!(failed.next_attempt_at.is_none());
+    assert!(pending.next_attempt_at.is_some());
+    // 送信する内容はJSONのまま返す
+    assert_eq!(pending.payload["type"], "book.created");
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn replay_webhook_delivery_202(fixture_auth: MockAppRegistryExt) -> anyhow::Result<()> {
+    let webhook_id = WebhookId::new();
+    let delivery_id = WebhookDeliveryId::new();
+    let mut registry = admin_with(fixture_auth, |_| {});
+    registry.expect_webhook_repository().returning(move || {
+        let mut mock = MockWebhookRepository::new();
+        mock.expect_replay()
+            .withf(move |e| e.webhook_id == webhook_id && e.delivery_id == delivery_id)
+            .returning(|e| Ok(delivery(e.webhook_id, WebhookDeliveryStatus::Pending)));
+        mock.expect_replay()
+            .returning(|_| Err(AppError::NotFoundError("not found".into())));
+        Arc::new(mock)
+    });
+
+    let app = make_router(registry);
+    let req = Request::post(v1(&format!(
+        "/webhooks/{webhook_id}/deliveries/{delivery_id}/replay"
+    )))
+    .bearer()
+    .body(Body::empty())?;
+    let resp = app.clone().oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    let replayed = deserialize_json!(resp, WebhookDeliveryResponse);
+    assert_ne!(replayed.id, delivery_id);
+    assert_eq!(replayed.status, WebhookDeliveryStatusName::Pending);
+
+    // 別のWebhookの配信は送り直せない
+    let req = Request::post(v1(&format!(
+        "/webhooks/{}/deliveries/{delivery_id}/replay",
+        WebhookId::new()
+    )))
+    .bearer()
+    .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
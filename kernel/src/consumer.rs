@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::domain_event::DomainEvent;
+
+/// アウトボックスに記録されたドメインイベントを受け取る
+///
+/// 同じイベントが複数回届くことがあるため、処理は冪等にする
+#[mockall::automock]
+#[async_trait]
+pub trait DomainEventConsumer: Send + Sync {
+    /// 処理済みのイベントを記録する際に使う、コンシューマーごとに固有の名前
+    fn name(&self) -> &'static str;
+    async fn consume(&self, event: &DomainEvent) -> AppResult<()>;
+}
@@ -1,2 +1,6 @@
+pub mod consumer;
+pub mod live;
 pub mod model;
+pub mod notifier;
 pub mod repository;
+pub mod webhook;
@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+use tokio::sync::broadcast;
+
+use crate::model::live::{LiveEvent, LiveEventId, PublishLiveEvent};
+
+/// 蔵書と貸出の変更を、接続中のクライアントにリアルタイムに届ける
+///
+/// 配信したイベントは一定数まで保持され、接続し直したクライアントは
+/// 受け取り損ねたイベントを取得できる
+#[mockall::automock]
+#[async_trait]
+pub trait LiveEventBus: Send + Sync {
+    /// イベントを保持し、すべてのインスタンスの購読者に配信する
+    async fn publish(&self, event: PublishLiveEvent) -> AppResult<LiveEventId>;
+    /// このインスタンスに届くイベントを購読する
+    /// 受け取りが追いつかない購読者は、古いイベントを受け取れずに`Lagged`となる
+    fn subscribe(&self) -> broadcast::Receiver<LiveEvent>;
+    /// `last_event_id`より後に配信され、まだ保持しているイベントを配信した順に取得する
+    async fn find_since(&self, last_event_id: &LiveEventId) -> AppResult<Vec<LiveEvent>>;
+}
@@ -0,0 +1,59 @@
+use crate::model::{
+    api_key::{generate_secret, ApiKeyScope, ApiKeySecret, API_KEY_PREFIX},
+    id::{ApiKeyId, UserId},
+};
+
+/// APIキー発行イベント
+pub struct CreateApiKey {
+    pub user_id: UserId,
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub secret: ApiKeySecret,
+}
+
+impl CreateApiKey {
+    pub fn new(user_id: UserId, name: String, scopes: Vec<ApiKeyScope>) -> Self {
+        let secret = ApiKeySecret(generate_secret(API_KEY_PREFIX));
+        Self {
+            user_id,
+            name,
+            scopes,
+            secret,
+        }
+    }
+}
+
+/// APIキー失効イベント
+pub struct RevokeApiKey {
+    pub api_key_id: ApiKeyId,
+    pub requested_user: UserId,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_api_key_new() {
+        let user_id = UserId::new();
+        let event = CreateApiKey::new(user_id, "ci".into(), vec![ApiKeyScope::Read]);
+
+        assert_eq!(event.user_id, user_id);
+        assert!(event.secret.0.starts_with(API_KEY_PREFIX));
+        assert_eq!(event.secret.0.len(), API_KEY_PREFIX.len() + 64);
+        assert!(event.secret.0[API_KEY_PREFIX.len()..]
+            .chars()
+            .all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(event.secret.prefix().len(), API_KEY_PREFIX.len() + 8);
+        assert!(event.secret.0.starts_with(&event.secret.prefix()));
+    }
+
+    #[test]
+    fn test_create_api_key_unique_secrets() {
+        let user_id = UserId::new();
+        let a = CreateApiKey::new(user_id, "a".into(), vec![]);
+        let b = CreateApiKey::new(user_id, "b".into(), vec![]);
+
+        assert_ne!(a.secret.0, b.secret.0);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::model::id::{ApiKeyId, UserId};
+use chrono::{DateTime, Utc};
+use rand::{rngs::OsRng, RngCore};
+use strum::{AsRefStr, EnumIter, EnumString};
+
+pub mod event;
+
+/// APIキーのプレフィックス
+/// `Authorization: Bearer`ヘッダーで受け取ったトークンがAPIキーかどうかの判別に用いる
+pub const API_KEY_PREFIX: &str = "bmk_";
+
+/// APIキーに付与する権限の範囲
+#[derive(Debug, Clone, Copy, EnumString, AsRefStr, EnumIter, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum ApiKeyScope {
+    /// 参照系(GET)のAPIのみ呼び出し可能
+    Read,
+    /// 更新系のAPIも呼び出し可能
+    Write,
+    /// 管理者のみに許可されたAPIを呼び出し可能(管理者ユーザーのみ付与できる)
+    Admin,
+}
+
+/// APIキーのメタデータ
+/// キー本体は発行時に一度だけ返却し、以降は参照できない
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKey {
+    pub id: ApiKeyId,
+    pub user_id: UserId,
+    pub name: String,
+    /// 一覧表示でキーを識別するための先頭部分
+    pub key_prefix: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    pub fn has_scope(&self, scope: ApiKeyScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// 発行されたAPIキー
+pub struct IssuedApiKey {
+    pub api_key: ApiKey,
+    /// APIキー本体
+    pub secret: ApiKeySecret,
+}
+
+/// APIキー本体の型定義
+pub struct ApiKeySecret(pub String);
+
+/// 推測困難な資格情報の文字列を生成する
+/// OSの乱数源から得た256bitを16進数で表し、`prefix`に続ける
+pub(crate) fn generate_secret(prefix: &str) -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let encoded: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("{prefix}{encoded}")
+}
+
+impl ApiKeySecret {
+    /// 一覧表示用のプレフィックスを取得する
+    pub fn prefix(&self) -> String {
+        self.0.chars().take(API_KEY_PREFIX.len() + 8).collect()
+    }
+}
@@ -0,0 +1,44 @@
+use derive_new::new;
+
+use crate::model::{
+    audit::{AuditAction, AuditTargetType},
+    id::UserId,
+};
+
+/// 監査ログの記録イベント
+///
+/// 変更を伴うリポジトリのメソッドに渡した場合は、変更と同じトランザクションで記録され、
+/// 変更が取り消された場合には記録も残らない
+#[derive(new, Debug, Clone)]
+pub struct CreateAuditLog {
+    pub actor_id: Option<UserId>,
+    pub action: AuditAction,
+    pub target_type: AuditTargetType,
+    pub target_id: Option<String>,
+    #[new(default)]
+    pub before: Option<serde_json::Value>,
+    #[new(default)]
+    pub after: Option<serde_json::Value>,
+    /// 操作を受け付けたリクエストのID
+    #[new(default)]
+    pub request_id: Option<String>,
+    /// 操作を行ったクライアントのIPアドレス
+    #[new(default)]
+    pub ip_address: Option<String>,
+}
+
+impl CreateAuditLog {
+    /// 操作後の内容に項目を加える
+    /// 操作後の内容がオブジェクトでない場合は、加える項目のみのオブジェクトとする
+    pub fn with_after_field(self, key: &str, value: serde_json::Value) -> Self {
+        let mut after = match self.after {
+            Some(serde_json::Value::Object(after)) => after,
+            _ => serde_json::Map::new(),
+        };
+        after.insert(key.into(), value);
+        Self {
+            after: Some(after.into()),
+            ..self
+        }
+    }
+}
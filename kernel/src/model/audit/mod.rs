@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use strum::{AsRefStr, EnumIter, EnumString};
+
+use crate::model::id::{AuditLogId, UserId};
+
+pub mod event;
+
+/// 監査ログに記録する操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr, EnumIter)]
+pub enum AuditAction {
+    #[strum(serialize = "auth.login")]
+    Login,
+    /// 資格情報の誤りなどによるログインの失敗
+    #[strum(serialize = "auth.login_failed")]
+    LoginFailed,
+    #[strum(serialize = "auth.logout")]
+    Logout,
+    #[strum(serialize = "book.create")]
+    CreateBook,
+    /// CSVによる蔵書の一括登録
+    #[strum(serialize = "book.import")]
+    ImportBooks,
+    #[strum(serialize = "book.update")]
+    UpdateBook,
+    #[strum(serialize = "book.delete")]
+    DeleteBook,
+    #[strum(serialize = "book.change_status")]
+    ChangeBookStatus,
+    #[strum(serialize = "checkout.create")]
+    Checkout,
+    #[strum(serialize = "checkout.return")]
+    Return,
+    #[strum(serialize = "checkout.renew")]
+    Renew,
+    #[strum(serialize = "checkout.declare_lost")]
+    DeclareLost,
+    #[strum(serialize = "user.create")]
+    CreateUser,
+    #[strum(serialize = "user.change_password")]
+    ChangePassword,
+    /// 管理者によるパスワードの再設定
+    #[strum(serialize = "user.reset_password")]
+    ResetPassword,
+    /// ユーザーのアクセストークンの一括削除
+    #[strum(serialize = "user.revoke_sessions")]
+    RevokeSessions,
+    #[strum(serialize = "user.deactivate")]
+    DeactivateUser,
+    #[strum(serialize = "user.reactivate")]
+    ReactivateUser,
+    #[strum(serialize = "user.purge")]
+    PurgeUser,
+    #[strum(serialize = "user.reassign_books")]
+    ReassignBooks,
+    #[strum(serialize = "user.change_role")]
+    ChangeRole,
+    #[strum(serialize = "user.register_badge")]
+    RegisterBadge,
+    #[strum(serialize = "user.unregister_badge")]
+    UnregisterBadge,
+    /// パスワードのハッシュを含みうるため、取得も記録する
+    #[strum(serialize = "backup.create")]
+    CreateBackup,
+    #[strum(serialize = "backup.restore")]
+    RestoreBackup,
+}
+
+/// 操作の対象の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr, EnumIter)]
+pub enum AuditTargetType {
+    Book,
+    Checkout,
+    User,
+    /// データ全体のバックアップ
+    Backup,
+}
+
+/// 監査ログ
+/// 記録後に変更・削除されることはない
+#[derive(Debug)]
+pub struct AuditLog {
+    pub id: AuditLogId,
+    /// 操作を行ったユーザー。ログインに失敗した場合など、特定できない場合は`None`
+    pub actor_id: Option<UserId>,
+    pub action: AuditAction,
+    pub target_type: AuditTargetType,
+    /// 操作の対象のID。ログインに失敗した場合など、特定できない場合は`None`
+    pub target_id: Option<String>,
+    /// 操作前の対象の内容
+    pub before: Option<serde_json::Value>,
+    /// 操作後の対象の内容
+    pub after: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+    pub ip_address: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// 監査ログを検索する際の条件
+#[derive(Debug, Default)]
+pub struct AuditLogListOptions {
+    pub actor_id: Option<UserId>,
+    pub target_type: Option<AuditTargetType>,
+    pub target_id: Option<String>,
+    /// この日時以降に記録されたもの
+    pub from: Option<DateTime<Utc>>,
+    /// この日時より前に記録されたもの
+    pub to: Option<DateTime<Utc>>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_action_names_round_trip() {
+        for action in AuditAction::iter() {
+            let name = action.as_ref();
+            assert!(name.contains('.'), "{name} should be namespaced");
+            assert_eq!(AuditAction::from_str(name).unwrap(), action);
+        }
+    }
+}
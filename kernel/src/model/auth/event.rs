@@ -6,6 +6,15 @@ pub struct CreateToken {
     pub access_token: AccessToken,
 }
 
+/// OIDCの認可コールバックを検証するイベント
+#[derive(Debug)]
+pub struct VerifyOidcCallback {
+    pub code: String,
+    pub state: String,
+    /// 認可リクエストの開始時に返した`OidcAuthorization::client_binding`
+    pub client_binding: String,
+}
+
 impl CreateToken {
     pub fn new(user_id: UserId) -> Self {
         let access_token = AccessToken(Uuid::new_v4().simple().to_string());
@@ -1,3 +1,28 @@
+use crate::model::role::Role;
+
 pub mod event;
 /// アクセストークン型定義
 pub struct AccessToken(pub String);
+
+/// OIDCログイン開始時に、利用者をリダイレクトさせる認可エンドポイントの情報
+#[derive(Debug)]
+pub struct OidcAuthorization {
+    pub authorization_url: String,
+    /// 認可リクエストを開始したクライアントに紐付ける値
+    /// コールバックの際に同じ値を送らせ、他人が開始した認可リクエストでログインさせられることを防ぐ
+    pub client_binding: String,
+}
+
+/// IDプロバイダーが発行したIDトークンから得られる利用者の情報
+#[derive(Debug, PartialEq, Eq)]
+pub struct OidcIdentity {
+    /// IDトークンの`iss`クレーム
+    pub issuer: String,
+    /// IDトークンの`sub`クレーム
+    pub subject: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub name: String,
+    /// クレームから判定したロール。ロールのマッピングが設定されていない場合は`None`
+    pub role: Option<Role>,
+}
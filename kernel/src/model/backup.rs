@@ -0,0 +1,293 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shared::error::{AppError, AppResult};
+use strum::{AsRefStr, EnumString};
+
+use crate::model::{
+    book::status::BookStatus,
+    id::{BookId, CheckoutId, KioskId, UserId},
+    role::Role,
+};
+
+/// バックアップの形式のバージョン
+/// 項目の意味が変わるなど、以前のバックアップと互換性がなくなる場合に上げる
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// 蔵書管理のデータ全体のバックアップ
+///
+/// ロールはデータベースごとに異なるIDが振られるため、名前で記録する
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupArchive {
+    pub format_version: u32,
+    /// 取得元のデータベースに適用されていた、最新のマイグレーションのバージョン
+    pub schema_version: i64,
+    pub created_at: DateTime<Utc>,
+    /// パスワードのハッシュを含むかどうか
+    pub includes_password_hashes: bool,
+    pub roles: Vec<String>,
+    pub users: Vec<BackupUser>,
+    pub books: Vec<BackupBook>,
+    pub checkouts: Vec<BackupCheckout>,
+    pub returned_checkouts: Vec<BackupReturnedCheckout>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupUser {
+    pub user_id: UserId,
+    pub name: String,
+    pub email: String,
+    /// パスワードのハッシュを含めない場合や、外部の認証基盤のユーザーの場合は`None`
+    pub password_hash: Option<String>,
+    pub role: String,
+    pub external_issuer: Option<String>,
+    pub external_subject: Option<String>,
+    pub badge_code: Option<String>,
+    pub deactivated_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupBook {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub description: String,
+    /// 所有者
+    pub user_id: UserId,
+    pub status: BookStatus,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupCheckout {
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub user_id: UserId,
+    pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+    pub checked_out_via: Option<KioskId>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupReturnedCheckout {
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub user_id: UserId,
+    pub checked_out_at: DateTime<Utc>,
+    pub returned_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+    pub checked_out_via: Option<KioskId>,
+    pub returned_via: Option<KioskId>,
+}
+
+/// バックアップの取得条件
+#[derive(Debug, Default)]
+pub struct BackupOptions {
+    /// パスワードのハッシュを含めるかどうか
+    pub include_password_hashes: bool,
+}
+
+/// 復元の方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString, AsRefStr)]
+#[strum(serialize_all = "lowercase")]
+pub enum RestoreMode {
+    /// 既存のデータを残し、IDが重複しないレコードのみを追加する
+    #[default]
+    Merge,
+    /// 既存のユーザー・蔵書・貸出をすべて削除し、バックアップの内容に置き換える
+    /// APIキー・料金・督促など、バックアップに含まれずユーザー・蔵書・貸出に紐づくレコードがある場合は行わない。
+    /// キオスク・Webhookは残るが、作成者の記録は失われる
+    Replace,
+}
+
+/// 復元の結果
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreSummary {
+    pub users: RestoreCount,
+    pub books: RestoreCount,
+    pub checkouts: RestoreCount,
+    pub returned_checkouts: RestoreCount,
+    /// 置き換えで作成したユーザーが削除され、作成者が記録されなくなったキオスクの件数
+    pub kiosks_without_creator: u64,
+    /// 置き換えで作成したユーザーが削除され、作成者が記録されなくなったWebhookの件数
+    pub webhooks_without_creator: u64,
+}
+
+/// 種類ごとの、復元したレコードと既存のものと重複したため飛ばしたレコードの件数
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RestoreCount {
+    pub restored: u64,
+    pub skipped: u64,
+}
+
+impl RestoreCount {
+    pub fn record(&mut self, restored: bool) {
+        if restored {
+            self.restored += 1;
+        } else {
+            self.skipped += 1;
+        }
+    }
+}
+
+impl BackupArchive {
+    /// 復元先のデータベースに対して、バックアップを復元できるかを検証する
+    ///
+    /// 復元先のスキーマのバージョンが一致しない場合や、バックアップ内で参照先のレコードが
+    /// 欠けている場合は、途中まで復元されることのないよう、書き込む前にエラーとする
+    pub fn validate(&self, schema_version: i64, mode: RestoreMode) -> AppResult<()> {
+        if self.format_version != BACKUP_FORMAT_VERSION {
+            return Err(AppError::UnprocessableEntity(format!(
+                "Unsupported backup format version: {} (expected {})",
+                self.format_version, BACKUP_FORMAT_VERSION
+            )));
+        }
+        if self.schema_version != schema_version {
+            return Err(AppError::UnprocessableEntity(format!(
+                "The backup was taken at schema version {}, but the database is at {}",
+                self.schema_version, schema_version
+            )));
+        }
+
+        let roles: HashSet<_> = self.roles.iter().map(String::as_str).collect();
+        if let Some(role) = roles.iter().find(|r| r.parse::<Role>().is_err()) {
+            return Err(invalid(format!("unknown role {role}")));
+        }
+        let users: HashSet<_> = self.users.iter().map(|u| u.user_id).collect();
+        let books: HashSet<_> = self.books.iter().map(|b| b.book_id).collect();
+        if users.len() != self.users.len() || books.len() != self.books.len() {
+            return Err(invalid("duplicate user_id or book_id".into()));
+        }
+        if let Some(user) = self.users.iter().find(|u| !roles.contains(u.role.as_str())) {
+            return Err(invalid(format!(
+                "user {} has unknown role {}",
+                user.user_id, user.role
+            )));
+        }
+        if let Some(book) = self.books.iter().find(|b| !users.contains(&b.user_id)) {
+            return Err(invalid(format!(
+                "book {} refers to missing user {}",
+                book.book_id, book.user_id
+            )));
+        }
+        let checkout_refs = self
+            .checkouts
+            .iter()
+            .map(|c| (c.checkout_id, c.book_id, c.user_id))
+            .chain(
+                self.returned_checkouts
+                    .iter()
+                    .map(|c| (c.checkout_id, c.book_id, c.user_id)),
+            );
+        for (checkout_id, book_id, user_id) in checkout_refs {
+            if !books.contains(&book_id) || !users.contains(&user_id) {
+                return Err(invalid(format!(
+                    "checkout {checkout_id} refers to missing book or user"
+                )));
+            }
+        }
+
+        // 置き換えた後にログインできる管理者がいなくなると、以降の操作ができなくなる
+        if mode == RestoreMode::Replace
+            && !self.users.iter().any(|u| {
+                u.role == Role::Admin.as_ref()
+                    && u.deactivated_at.is_none()
+                    && (u.password_hash.is_some() || u.external_subject.is_some())
+            })
+        {
+            return Err(AppError::UnprocessableEntity(
+                "Replacing requires the backup to contain an active admin who can log in".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn invalid(reason: String) -> AppError {
+    AppError::UnprocessableEntity(format!("The backup is inconsistent: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive() -> BackupArchive {
+        let now = Utc::now();
+        let admin = UserId::new();
+        let book = BookId::new();
+        BackupArchive {
+            format_version: BACKUP_FORMAT_VERSION,
+            schema_version: 1,
+            created_at: now,
+            includes_password_hashes: true,
+            roles: vec!["Admin".into(), "User".into()],
+            users: vec![BackupUser {
+                user_id: admin,
+                name: "admin".into(),
+                email: "admin@example.com".into(),
+                password_hash: Some("hash".into()),
+                role: "Admin".into(),
+                external_issuer: None,
+                external_subject: None,
+                badge_code: None,
+                deactivated_at: None,
+                created_at: now,
+                updated_at: now,
+            }],
+            books: vec![BackupBook {
+                book_id: book,
+                title: "title".into(),
+                author: "author".into(),
+                isbn: "isbn".into(),
+                description: "".into(),
+                user_id: admin,
+                status: BookStatus::OnLoan,
+                created_at: now,
+                updated_at: now,
+            }],
+            checkouts: vec![BackupCheckout {
+                checkout_id: CheckoutId::new(),
+                book_id: book,
+                user_id: admin,
+                checked_out_at: now,
+                due_at: now,
+                renewal_count: 0,
+                checked_out_via: None,
+            }],
+            returned_checkouts: vec![],
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let archive = archive();
+        assert!(archive.validate(1, RestoreMode::Replace).is_ok());
+
+        // スキーマのバージョンが異なる
+        assert!(archive.validate(2, RestoreMode::Merge).is_err());
+
+        // 参照先の蔵書がない貸出
+        let mut broken = archive.clone();
+        broken.checkouts[0].book_id = BookId::new();
+        assert!(broken.validate(1, RestoreMode::Merge).is_err());
+
+        // パスワードのハッシュを含まない場合、置き換えるとログインできる管理者がいなくなる
+        let mut without_hashes = archive.clone();
+        without_hashes.users[0].password_hash = None;
+        assert!(without_hashes.validate(1, RestoreMode::Merge).is_ok());
+        assert!(without_hashes.validate(1, RestoreMode::Replace).is_err());
+    }
+}
@@ -1,4 +1,7 @@
-use crate::model::id::{BookId, UserId};
+use crate::model::{
+    book::status::BookStatus,
+    id::{BookId, UserId},
+};
 /// 蔵書作成イベント
 pub struct CreateBook {
     pub title: String,
@@ -22,3 +25,18 @@ pub struct DeleteBook {
     pub book_id: BookId,
     pub requested_user: UserId,
 }
+
+/// 蔵書の所有者付け替えイベント
+/// `current_owner`が所有する全ての蔵書を`new_owner`へ移す
+pub struct ReassignBookOwner {
+    pub current_owner: UserId,
+    pub new_owner: UserId,
+}
+
+/// 蔵書の状態変更イベント
+pub struct UpdateBookStatus {
+    pub book_id: BookId,
+    pub status: BookStatus,
+    pub note: Option<String>,
+    pub requested_user: UserId,
+}
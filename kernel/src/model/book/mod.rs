@@ -1,16 +1,20 @@
 use crate::model::{
+    book::status::BookStatus,
     id::{BookId, CheckoutId},
     user::{BookOwner, CheckOutUser},
 };
 use chrono::{DateTime, Utc};
 
 pub mod event;
+pub mod status;
 
 #[derive(Debug)]
 pub struct CheckoutInfo {
     pub checkout_id: CheckoutId,
     pub checked_out_by: CheckOutUser,
     pub checked_out_at: DateTime<Utc>,
+    /// 返却期限
+    pub due_at: DateTime<Utc>,
 }
 
 /// 蔵書データ
@@ -22,6 +26,7 @@ pub struct Book {
     pub isbn: String,
     pub description: String,
     pub owner: BookOwner,
+    pub status: BookStatus,
     pub checkout_info: Option<CheckoutInfo>,
 }
 
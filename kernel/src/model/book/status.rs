@@ -0,0 +1,96 @@
+use crate::model::id::{BookId, UserId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, EnumIter, EnumString};
+
+/// 蔵書の状態
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    EnumString,
+    AsRefStr,
+    EnumIter,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub enum BookStatus {
+    /// 貸出可能
+    #[default]
+    Available,
+    /// 貸出中
+    OnLoan,
+    /// 紛失
+    Lost,
+    /// 破損
+    Damaged,
+    /// 修理中
+    InRepair,
+    /// 除籍
+    Withdrawn,
+}
+
+impl BookStatus {
+    /// `next`の状態へ遷移できるかどうか
+    pub fn can_transition_to(self, next: BookStatus) -> bool {
+        use BookStatus::*;
+        matches!(
+            (self, next),
+            (Available, OnLoan | Lost | Damaged | InRepair | Withdrawn)
+                | (OnLoan, Available | Lost)
+                | (Lost, Available | Withdrawn)
+                | (Damaged, Available | InRepair | Withdrawn)
+                | (InRepair, Available | Damaged | Withdrawn)
+        )
+    }
+
+    /// 貸出・返却・紛失の届け出によってのみ変化する状態かどうか
+    /// 貸出中への変更や貸出中からの変更は、貸出記録と整合させるため手動では行えない
+    pub fn is_managed_by_checkout(self) -> bool {
+        self == BookStatus::OnLoan
+    }
+}
+
+/// 蔵書の状態の変更履歴
+#[derive(Debug)]
+pub struct BookStatusHistory {
+    pub book_id: BookId,
+    pub status: BookStatus,
+    /// 登録時の履歴の場合は`None`
+    pub previous_status: Option<BookStatus>,
+    /// 変更したユーザー(削除済みの場合は`None`)
+    pub changed_by: Option<UserId>,
+    pub note: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_can_transition_to() {
+        use BookStatus::*;
+
+        assert!(Available.can_transition_to(OnLoan));
+        assert!(OnLoan.can_transition_to(Lost));
+        assert!(Lost.can_transition_to(Available));
+        assert!(Damaged.can_transition_to(InRepair));
+        assert!(InRepair.can_transition_to(Available));
+
+        // 貸出中の蔵書は、返却か紛失のいずれかでしか状態が変わらない
+        assert!(!OnLoan.can_transition_to(Damaged));
+        assert!(!OnLoan.can_transition_to(Withdrawn));
+        // 貸出できるのは貸出可能な蔵書のみ
+        assert!(!Damaged.can_transition_to(OnLoan));
+        assert!(!Lost.can_transition_to(OnLoan));
+        // 除籍は最終状態
+        assert!(BookStatus::iter().all(|s| !Withdrawn.can_transition_to(s)));
+        // 同じ状態への遷移は行わない
+        assert!(BookStatus::iter().all(|s| !s.can_transition_to(s)));
+    }
+}
@@ -1,19 +1,69 @@
 use chrono::{DateTime, Utc};
 use derive_new::new;
 
-use crate::model::id::{BookId, CheckoutId, UserId};
+use crate::model::{
+    checkout::BatchMode,
+    id::{BookId, CheckoutId, KioskId, UserId},
+};
 
-#[derive(new)]
+#[derive(new, Clone, Copy)]
 pub struct CreateCheckout {
     pub book_id: BookId,
     pub checked_out_by: UserId,
     pub checked_out_at: DateTime<Utc>,
+    /// キオスクから貸出を行った場合のキオスク
+    #[new(default)]
+    pub checked_out_via: Option<KioskId>,
 }
 
-#[derive(new)]
+#[derive(new, Clone, Copy)]
 pub struct UpdateReturned {
     pub checkout_id: CheckoutId,
     pub book_id: BookId,
     pub returned_by: UserId,
     pub returned_at: DateTime<Utc>,
 }
+
+/// 貸出延長イベント
+#[derive(new, Clone, Copy)]
+pub struct RenewCheckout {
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub renewed_by: UserId,
+    pub renewed_at: DateTime<Utc>,
+}
+
+/// 借りている蔵書の紛失届け出イベント
+/// 貸出は終了し、蔵書は紛失状態になる
+#[derive(new, Clone, Copy)]
+pub struct DeclareLost {
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub declared_by: UserId,
+    pub declared_at: DateTime<Utc>,
+}
+
+/// 複数の蔵書の一括貸出イベント
+#[derive(new, Clone)]
+pub struct CreateCheckouts {
+    pub book_ids: Vec<BookId>,
+    pub checked_out_by: UserId,
+    pub checked_out_at: DateTime<Utc>,
+    pub mode: BatchMode,
+    /// キオスクから貸出を行った場合のキオスク
+    #[new(default)]
+    pub checked_out_via: Option<KioskId>,
+}
+
+/// 複数の蔵書の一括返却イベント
+/// 蔵書ごとに、返却するユーザーが借りている貸出を返却する
+#[derive(new, Clone)]
+pub struct ReturnBooks {
+    pub book_ids: Vec<BookId>,
+    pub returned_by: UserId,
+    pub returned_at: DateTime<Utc>,
+    pub mode: BatchMode,
+    /// キオスクから返却を行った場合のキオスク
+    #[new(default)]
+    pub returned_via: Option<KioskId>,
+}
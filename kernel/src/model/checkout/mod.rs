@@ -1,21 +1,75 @@
-use crate::model::id::{BookId, CheckoutId, UserId};
+use crate::model::id::{BookId, CheckoutId, KioskId, UserId};
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shared::error::AppError;
 
 pub mod event;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Checkout {
     pub id: CheckoutId,
     pub checked_out_by: UserId,
     pub checked_out_at: DateTime<Utc>,
     pub returned_at: Option<DateTime<Utc>>,
+    /// 返却期限
+    pub due_at: DateTime<Utc>,
+    /// 貸出を延長した回数
+    pub renewal_count: i32,
+    /// 貸出を行ったキオスク。利用者自身が操作した場合は`None`
+    pub checked_out_via: Option<KioskId>,
+    /// 返却を行ったキオスク
+    pub returned_via: Option<KioskId>,
     pub book: CheckoutBook,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CheckoutBook {
     pub book_id: BookId,
     pub title: String,
     pub author: String,
     pub isbn: String,
 }
+
+/// ユーザーごとの貸出履歴を取得する際の条件
+#[derive(Debug, Default)]
+pub struct CheckoutHistoryOptions {
+    pub limit: i64,
+    pub offset: i64,
+    /// この日時以降に貸し出されたもの
+    pub from: Option<DateTime<Utc>>,
+    /// この日時より前に貸し出されたもの
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// 複数の蔵書をまとめて貸出・返却する際の処理方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchMode {
+    /// 1冊でも失敗した場合は、すべての蔵書の処理を取り消す
+    AllOrNothing,
+    /// 成功した蔵書の処理のみを反映する
+    BestEffort,
+}
+
+/// 一括処理における蔵書ごとの結果
+#[derive(Debug)]
+pub struct BatchItemResult {
+    pub book_id: BookId,
+    pub outcome: BatchItemOutcome,
+}
+
+#[derive(Debug)]
+pub enum BatchItemOutcome {
+    /// 処理に成功した。対象の貸出のIDを持つ
+    Succeeded(CheckoutId),
+    /// 処理には成功したが、他の蔵書の処理が失敗したため取り消された
+    RolledBack(CheckoutId),
+    Failed(AppError),
+}
+
+impl BatchItemResult {
+    pub fn is_succeeded(&self) -> bool {
+        matches!(self.outcome, BatchItemOutcome::Succeeded(_))
+    }
+}
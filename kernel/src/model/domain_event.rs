@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    book::status::BookStatus,
+    checkout::Checkout,
+    id::{BookId, DomainEventId, UserId},
+    user::User,
+};
+
+/// 状態の変更とともに記録される、起きたことを表すイベント
+///
+/// 変更と同じトランザクションでアウトボックスに記録され、
+/// コミットされた変更についてのみ、後から各コンシューマーに届けられる
+#[derive(Debug)]
+pub struct DomainEvent {
+    pub id: DomainEventId,
+    pub occurred_at: DateTime<Utc>,
+    pub kind: DomainEventKind,
+}
+
+impl DomainEvent {
+    pub fn new(kind: DomainEventKind) -> Self {
+        Self {
+            id: DomainEventId::new(),
+            occurred_at: Utc::now(),
+            kind,
+        }
+    }
+}
+
+/// イベントの種類と内容
+/// アウトボックスにJSONとして保存するため、名前を変更する場合は保存済みのイベントとの互換性に注意する
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DomainEventKind {
+    #[serde(rename = "book.created")]
+    BookCreated(BookRecord),
+    #[serde(rename = "book.updated")]
+    BookUpdated(BookRecord),
+    #[serde(rename = "book.deleted", rename_all = "camelCase")]
+    BookDeleted { book_id: BookId, deleted_by: UserId },
+    /// 破損・修理などによる蔵書の状態の手動での変更
+    /// 貸出・返却による状態の変化は、貸出のイベントとして記録する
+    #[serde(rename = "book.status_changed", rename_all = "camelCase")]
+    BookStatusChanged {
+        book_id: BookId,
+        status: BookStatus,
+        previous_status: BookStatus,
+        changed_by: UserId,
+    },
+    /// 1回の操作で貸し出された蔵書。一括貸出の場合は複数になる
+    #[serde(rename = "checkout.created", rename_all = "camelCase")]
+    CheckedOut {
+        user_id: UserId,
+        checkouts: Vec<Checkout>,
+    },
+    #[serde(rename = "checkout.renewed")]
+    Renewed(Checkout),
+    /// 1回の操作で返却された蔵書。一括返却の場合は複数になる
+    #[serde(rename = "checkout.returned", rename_all = "camelCase")]
+    Returned {
+        user_id: UserId,
+        checkouts: Vec<Checkout>,
+    },
+    #[serde(rename = "checkout.lost")]
+    DeclaredLost(Checkout),
+    #[serde(rename = "user.created")]
+    UserCreated(UserRecord),
+}
+
+impl DomainEventKind {
+    /// イベントの種類の名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BookCreated(_) => "book.created",
+            Self::BookUpdated(_) => "book.updated",
+            Self::BookDeleted { .. } => "book.deleted",
+            Self::BookStatusChanged { .. } => "book.status_changed",
+            Self::CheckedOut { .. } => "checkout.created",
+            Self::Renewed(_) => "checkout.renewed",
+            Self::Returned { .. } => "checkout.returned",
+            Self::DeclaredLost(_) => "checkout.lost",
+            Self::UserCreated(_) => "user.created",
+        }
+    }
+}
+
+/// イベントが起きた時点の蔵書の内容
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookRecord {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub description: String,
+    pub owned_by: UserId,
+}
+
+/// イベントが起きた時点のユーザーの内容
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserRecord {
+    pub user_id: UserId,
+    pub name: String,
+    pub email: String,
+    pub role: String,
+}
+
+impl From<&User> for UserRecord {
+    fn from(user: &User) -> Self {
+        Self {
+            user_id: user.id,
+            name: user.name.clone(),
+            email: user.email.clone(),
+            role: user.role.as_ref().to_string(),
+        }
+    }
+}
+
+/// アウトボックスから取り出した、コンシューマーへの配信を待つイベント
+#[derive(Debug)]
+pub struct PendingEvent {
+    pub event: DomainEvent,
+    /// すでにイベントを処理し終えたコンシューマーの名前
+    pub consumed_by: Vec<String>,
+    /// これまでに配信を試みた回数
+    pub attempts: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_matches_serialized_type() {
+        let kinds = [
+            DomainEventKind::BookDeleted {
+                book_id: BookId::new(),
+                deleted_by: UserId::new(),
+            },
+            DomainEventKind::BookStatusChanged {
+                book_id: BookId::new(),
+                status: BookStatus::Damaged,
+                previous_status: BookStatus::Available,
+                changed_by: UserId::new(),
+            },
+            DomainEventKind::CheckedOut {
+                user_id: UserId::new(),
+                checkouts: vec![],
+            },
+            DomainEventKind::UserCreated(UserRecord {
+                user_id: UserId::new(),
+                name: "Alice".into(),
+                email: "alice@example.com".into(),
+                role: "User".into(),
+            }),
+        ];
+        for kind in kinds {
+            let value = serde_json::to_value(&kind).unwrap();
+            assert_eq!(value["type"], kind.name());
+            // 保存した内容から元のイベントを復元できる
+            let restored: DomainEventKind = serde_json::from_value(value).unwrap();
+            assert_eq!(restored.name(), kind.name());
+        }
+    }
+}
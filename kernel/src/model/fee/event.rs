@@ -0,0 +1,27 @@
+use crate::model::{fee::FeeSchedule, id::UserId};
+
+/// 料金表の更新イベント
+#[derive(Debug)]
+pub struct UpdateFeeSchedule {
+    pub schedule: FeeSchedule,
+}
+
+/// 料金の支払いを記録するイベント
+#[derive(Debug, Clone)]
+pub struct RecordFeePayment {
+    pub user_id: UserId,
+    /// 支払われた金額(正の値)
+    pub amount: i64,
+    pub note: Option<String>,
+    pub recorded_by: UserId,
+}
+
+/// 料金の免除を記録するイベント
+#[derive(Debug, Clone)]
+pub struct WaiveFee {
+    pub user_id: UserId,
+    /// 免除する金額(正の値)
+    pub amount: i64,
+    pub note: Option<String>,
+    pub recorded_by: UserId,
+}
@@ -0,0 +1,128 @@
+use crate::model::id::{BookId, CheckoutId, FeeEntryId, UserId};
+use chrono::{DateTime, Utc};
+use strum::{AsRefStr, EnumString};
+
+pub mod event;
+
+/// 延滞・紛失の料金表
+/// 金額はすべて円単位の整数で扱う
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeSchedule {
+    /// 返却期限を1日過ぎるごとに課す延滞料
+    pub overdue_fee_per_day: i64,
+    /// 1回の貸出あたりの延滞料の上限。`None`の場合は上限なし
+    pub max_overdue_fee: Option<i64>,
+    /// 紛失の届け出時に課す料金
+    pub lost_book_fee: i64,
+    /// 未払いの残高がこの金額を超えると新たに借りられなくなる。`None`の場合は制限しない
+    pub checkout_block_threshold: Option<i64>,
+}
+
+impl FeeSchedule {
+    /// 返却期限から返却日時までの延滞料を計算する
+    /// 1日に満たない延滞も1日として数える
+    pub fn overdue_fee(&self, due_at: DateTime<Utc>, returned_at: DateTime<Utc>) -> i64 {
+        let overdue = returned_at - due_at;
+        if overdue <= chrono::Duration::zero() {
+            return 0;
+        }
+        let mut days = overdue.num_days();
+        if overdue > chrono::Duration::days(days) {
+            days += 1;
+        }
+        let fee = self.overdue_fee_per_day.saturating_mul(days);
+        match self.max_overdue_fee {
+            Some(max) => fee.min(max),
+            None => fee,
+        }
+    }
+
+    /// 残高が貸出を制限する金額を超えているかどうか
+    pub fn blocks_checkout(&self, balance: i64) -> bool {
+        self.checkout_block_threshold
+            .is_some_and(|threshold| balance > threshold)
+    }
+}
+
+/// 台帳の記録の種類
+#[derive(Debug, Clone, Copy, EnumString, AsRefStr, PartialEq, Eq)]
+pub enum FeeEntryKind {
+    /// 延滞料の請求
+    Overdue,
+    /// 紛失の請求
+    Lost,
+    /// 支払い
+    Payment,
+    /// 免除
+    Waiver,
+}
+
+/// 台帳の記録
+#[derive(Debug)]
+pub struct FeeEntry {
+    pub id: FeeEntryId,
+    pub user_id: UserId,
+    pub kind: FeeEntryKind,
+    /// 請求は正、支払い・免除は負の金額
+    pub amount: i64,
+    /// 請求の元となった貸出と蔵書
+    pub checkout_id: Option<CheckoutId>,
+    pub book_id: Option<BookId>,
+    pub note: Option<String>,
+    /// 支払い・免除を記録したユーザー
+    pub recorded_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// ユーザーごとの料金の台帳
+#[derive(Debug)]
+pub struct FeeLedger {
+    pub user_id: UserId,
+    /// 未払いの残高(全記録の合計)
+    pub balance: i64,
+    /// 新しい順の記録
+    pub entries: Vec<FeeEntry>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_overdue_fee() {
+        let schedule = FeeSchedule {
+            overdue_fee_per_day: 10,
+            max_overdue_fee: Some(100),
+            ..Default::default()
+        };
+        let due_at = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+
+        // 期限内の返却には課さない
+        assert_eq!(schedule.overdue_fee(due_at, due_at), 0);
+        assert_eq!(schedule.overdue_fee(due_at, due_at - Duration::days(1)), 0);
+        // 1日に満たない延滞も1日として数える
+        assert_eq!(
+            schedule.overdue_fee(due_at, due_at + Duration::hours(1)),
+            10
+        );
+        assert_eq!(schedule.overdue_fee(due_at, due_at + Duration::days(3)), 30);
+        // 上限を超えない
+        assert_eq!(
+            schedule.overdue_fee(due_at, due_at + Duration::days(30)),
+            100
+        );
+    }
+
+    #[test]
+    fn test_blocks_checkout() {
+        assert!(!FeeSchedule::default().blocks_checkout(i64::MAX));
+
+        let schedule = FeeSchedule {
+            checkout_block_threshold: Some(500),
+            ..Default::default()
+        };
+        assert!(!schedule.blocks_checkout(500));
+        assert!(schedule.blocks_checkout(501));
+    }
+}
@@ -65,6 +65,13 @@ macro_rules! define_id {
 define_id!(UserId);
 define_id!(BookId);
 define_id!(CheckoutId);
+define_id!(ApiKeyId);
+define_id!(FeeEntryId);
+define_id!(KioskId);
+define_id!(WebhookId);
+define_id!(WebhookDeliveryId);
+define_id!(WebhookEventId);
+define_id!(DomainEventId);
 
 #[cfg(test)]
 mod tests {
@@ -87,3 +94,4 @@ mod tests {
         assert!(user_id.is_err());
     }
 }
+define_id!(AuditLogId);
@@ -0,0 +1,44 @@
+use crate::model::{
+    api_key::generate_secret,
+    id::{KioskId, UserId},
+    kiosk::{KioskSecret, KIOSK_KEY_PREFIX},
+};
+
+/// キオスク登録イベント
+pub struct CreateKiosk {
+    pub name: String,
+    pub created_by: UserId,
+    pub secret: KioskSecret,
+}
+
+impl CreateKiosk {
+    pub fn new(name: String, created_by: UserId) -> Self {
+        // APIキーと同様に、推測困難な文字列を生成する
+        let secret = KioskSecret(generate_secret(KIOSK_KEY_PREFIX));
+        Self {
+            name,
+            created_by,
+            secret,
+        }
+    }
+}
+
+/// キオスクの資格情報の失効イベント
+pub struct RevokeKiosk {
+    pub kiosk_id: KioskId,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_kiosk_new() {
+        let event = CreateKiosk::new("2F shelf".into(), UserId::new());
+
+        assert!(event.secret.0.starts_with(KIOSK_KEY_PREFIX));
+        assert_eq!(event.secret.0.len(), KIOSK_KEY_PREFIX.len() + 64);
+        assert_eq!(event.secret.prefix().len(), KIOSK_KEY_PREFIX.len() + 8);
+        assert!(event.secret.0.starts_with(&event.secret.prefix()));
+    }
+}
@@ -0,0 +1,41 @@
+use crate::model::id::{KioskId, UserId};
+use chrono::{DateTime, Utc};
+
+pub mod event;
+
+/// キオスクの資格情報のプレフィックス
+/// 利用者のAPIキー(`bmk_`)と取り違えないよう、別のプレフィックスを用いる
+pub const KIOSK_KEY_PREFIX: &str = "bmkiosk_";
+
+/// 書架に設置した共用端末(キオスク)
+/// 利用者のパスワードの代わりに端末ごとの資格情報で認証し、
+/// 社員証などのバッジで識別したユーザーに代わって貸出・返却を行う
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kiosk {
+    pub id: KioskId,
+    pub name: String,
+    /// 一覧表示でキーを識別するための先頭部分
+    pub key_prefix: String,
+    /// キオスクを登録した管理者
+    pub created_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// 登録したキオスク
+pub struct IssuedKiosk {
+    pub kiosk: Kiosk,
+    /// キオスクの資格情報本体
+    pub secret: KioskSecret,
+}
+
+/// キオスクの資格情報本体の型定義
+pub struct KioskSecret(pub String);
+
+impl KioskSecret {
+    /// 一覧表示用のプレフィックスを取得する
+    pub fn prefix(&self) -> String {
+        self.0.chars().take(KIOSK_KEY_PREFIX.len() + 8).collect()
+    }
+}
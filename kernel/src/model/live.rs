@@ -0,0 +1,229 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    book::status::BookStatus,
+    checkout::Checkout,
+    domain_event::{BookRecord, DomainEvent, DomainEventKind},
+    id::{BookId, CheckoutId, DomainEventId},
+};
+
+/// 接続中のクライアントにリアルタイムに届ける、蔵書と貸出の変更
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveEvent {
+    /// 配信した順に大きくなるID。接続し直す際に、どこまで受け取ったかを示すのに用いる
+    pub id: LiveEventId,
+    /// 変換元のドメインイベントのID
+    /// ドメインイベントは複数回届くことがあるため、クライアントはこのIDで重複を判定できる
+    pub source_id: DomainEventId,
+    pub occurred_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: LiveEventKind,
+}
+
+/// 配信したイベントのID
+/// Redis Streamsのエントリ ID(`<ミリ秒>-<連番>`)で、配信した順に比較できる
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LiveEventId(pub String);
+
+impl LiveEventId {
+    fn parts(&self) -> Option<(u64, u64)> {
+        let (ms, seq) = self.0.split_once('-')?;
+        Some((ms.parse().ok()?, seq.parse().ok()?))
+    }
+
+    /// エントリ IDとして正しい形式かどうか
+    pub fn is_well_formed(&self) -> bool {
+        self.parts().is_some()
+    }
+
+    /// `other`より後に配信されたイベントかどうか
+    /// いずれかのIDが不正な形式の場合は、後に配信されたものとみなす
+    pub fn is_after(&self, other: &LiveEventId) -> bool {
+        match (self.parts(), other.parts()) {
+            (Some(this), Some(other)) => this > other,
+            _ => true,
+        }
+    }
+}
+
+impl std::fmt::Display for LiveEventId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// 配信するイベントの種類と内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum LiveEventKind {
+    #[serde(rename = "book.created")]
+    BookCreated(BookRecord),
+    #[serde(rename = "book.updated")]
+    BookUpdated(BookRecord),
+    #[serde(rename = "book.deleted", rename_all = "camelCase")]
+    BookDeleted { book_id: BookId },
+    #[serde(rename = "book.status_changed", rename_all = "camelCase")]
+    BookStatusChanged { book_id: BookId, status: BookStatus },
+    #[serde(rename = "checkout.created")]
+    CheckedOut(LiveCheckout),
+    #[serde(rename = "checkout.renewed")]
+    Renewed(LiveCheckout),
+    #[serde(rename = "checkout.returned")]
+    Returned(LiveCheckout),
+    #[serde(rename = "checkout.lost")]
+    DeclaredLost(LiveCheckout),
+}
+
+impl LiveEventKind {
+    /// イベントの種類の名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BookCreated(_) => "book.created",
+            Self::BookUpdated(_) => "book.updated",
+            Self::BookDeleted { .. } => "book.deleted",
+            Self::BookStatusChanged { .. } => "book.status_changed",
+            Self::CheckedOut(_) => "checkout.created",
+            Self::Renewed(_) => "checkout.renewed",
+            Self::Returned(_) => "checkout.returned",
+            Self::DeclaredLost(_) => "checkout.lost",
+        }
+    }
+
+    /// 変更された蔵書のID
+    pub fn book_id(&self) -> BookId {
+        match self {
+            Self::BookCreated(book) | Self::BookUpdated(book) => book.book_id,
+            Self::BookDeleted { book_id } | Self::BookStatusChanged { book_id, .. } => *book_id,
+            Self::CheckedOut(checkout)
+            | Self::Renewed(checkout)
+            | Self::Returned(checkout)
+            | Self::DeclaredLost(checkout) => checkout.book_id,
+        }
+    }
+
+    /// ドメインイベントを、配信するイベントに変換する
+    ///
+    /// 一括貸出・返却のイベントは、蔵書ごとのイベントに分ける。
+    /// 利用者の情報は接続中のすべてのクライアントに届くため、誰が借りたかは含めない
+    pub fn from_domain(event: &DomainEvent) -> Vec<Self> {
+        let per_checkout = |kind: fn(LiveCheckout) -> Self, checkouts: &[Checkout]| {
+            checkouts
+                .iter()
+                .map(|checkout| kind(checkout.into()))
+                .collect()
+        };
+        match &event.kind {
+            DomainEventKind::BookCreated(book) => vec![Self::BookCreated(book.clone())],
+            DomainEventKind::BookUpdated(book) => vec![Self::BookUpdated(book.clone())],
+            DomainEventKind::BookDeleted { book_id, .. } => {
+                vec![Self::BookDeleted { book_id: *book_id }]
+            }
+            DomainEventKind::BookStatusChanged {
+                book_id, status, ..
+            } => vec![Self::BookStatusChanged {
+                book_id: *book_id,
+                status: *status,
+            }],
+            DomainEventKind::CheckedOut { checkouts, .. } => {
+                per_checkout(Self::CheckedOut, checkouts)
+            }
+            DomainEventKind::Renewed(checkout) => vec![Self::Renewed(checkout.into())],
+            DomainEventKind::Returned { checkouts, .. } => per_checkout(Self::Returned, checkouts),
+            DomainEventKind::DeclaredLost(checkout) => vec![Self::DeclaredLost(checkout.into())],
+            DomainEventKind::UserCreated(_) => vec![],
+        }
+    }
+}
+
+/// 配信するイベント
+/// IDは配信時に割り当てられる
+#[derive(Debug, Clone)]
+pub struct PublishLiveEvent {
+    pub source_id: DomainEventId,
+    pub occurred_at: DateTime<Utc>,
+    pub kind: LiveEventKind,
+}
+
+/// 配信する貸出の内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveCheckout {
+    pub book_id: BookId,
+    pub checkout_id: CheckoutId,
+    /// 返却期限
+    pub due_at: DateTime<Utc>,
+}
+
+impl From<&Checkout> for LiveCheckout {
+    fn from(checkout: &Checkout) -> Self {
+        Self {
+            book_id: checkout.book.book_id,
+            checkout_id: checkout.id,
+            due_at: checkout.due_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{checkout::CheckoutBook, id::UserId};
+
+    #[test]
+    fn test_event_id_order() {
+        let id = |s: &str| LiveEventId(s.into());
+        assert!(id("1700000000001-0").is_after(&id("1700000000000-5")));
+        assert!(id("1700000000000-10").is_after(&id("1700000000000-9")));
+        assert!(!id("1700000000000-0").is_after(&id("1700000000000-0")));
+        assert!(!id("1699999999999-9").is_after(&id("1700000000000-0")));
+        assert!(id("1700000000000-0").is_after(&id("unknown")));
+        assert!(!id("unknown").is_well_formed());
+    }
+
+    #[test]
+    fn test_from_domain_event() {
+        let user_id = UserId::new();
+        let checkout = || Checkout {
+            id: CheckoutId::new(),
+            checked_out_by: user_id,
+            checked_out_at: Utc::now(),
+            returned_at: None,
+            due_at: Utc::now(),
+            renewal_count: 0,
+            checked_out_via: None,
+            returned_via: None,
+            book: CheckoutBook {
+                book_id: BookId::new(),
+                title: "".into(),
+                author: "".into(),
+                isbn: "".into(),
+            },
+        };
+        let checkouts = vec![checkout(), checkout()];
+        let event = DomainEvent::new(DomainEventKind::CheckedOut {
+            user_id,
+            checkouts: checkouts.clone(),
+        });
+
+        // 蔵書ごとのイベントに分けられ、借りた利用者は含まれない
+        let kinds = LiveEventKind::from_domain(&event);
+        assert_eq!(kinds.len(), 2);
+        assert_eq!(kinds[1].book_id(), checkouts[1].book.book_id);
+        let value = serde_json::to_value(&kinds[0]).unwrap();
+        assert_eq!(value["type"], kinds[0].name());
+        assert!(!value.to_string().contains(&user_id.to_string()));
+
+        let created = DomainEvent::new(DomainEventKind::UserCreated(
+            crate::model::domain_event::UserRecord {
+                user_id,
+                name: "Alice".into(),
+                email: "alice@example.com".into(),
+                role: "User".into(),
+            },
+        ));
+        assert!(LiveEventKind::from_domain(&created).is_empty());
+    }
+}
@@ -0,0 +1,25 @@
+use crate::model::{
+    id::UserId,
+    loan_policy::{LoanPolicy, LoanPolicyOverride},
+    role::Role,
+};
+
+/// ロールの貸出ポリシー更新イベント
+#[derive(Debug)]
+pub struct UpdateRoleLoanPolicy {
+    pub role: Role,
+    pub policy: LoanPolicy,
+}
+
+/// ユーザーごとの貸出ポリシー上書きイベント
+#[derive(Debug)]
+pub struct UpdateUserLoanPolicy {
+    pub user_id: UserId,
+    pub user_override: LoanPolicyOverride,
+}
+
+/// ユーザーごとの貸出ポリシー上書きの削除イベント
+#[derive(Debug)]
+pub struct DeleteUserLoanPolicy {
+    pub user_id: UserId,
+}
@@ -0,0 +1,114 @@
+use crate::model::{id::UserId, role::Role};
+use chrono::Duration;
+
+pub mod event;
+
+/// 貸出ポリシー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoanPolicy {
+    /// 同時に借りられる冊数の上限
+    pub max_loans: i32,
+    /// 貸出期間(日数)
+    pub loan_period_days: i32,
+    /// 貸出を延長できる回数の上限
+    pub max_renewals: i32,
+}
+
+/// ロールにポリシーが設定されていない場合に適用する既定値
+impl Default for LoanPolicy {
+    fn default() -> Self {
+        Self {
+            max_loans: 5,
+            loan_period_days: 14,
+            max_renewals: 2,
+        }
+    }
+}
+
+impl LoanPolicy {
+    pub fn loan_period(&self) -> Duration {
+        Duration::days(self.loan_period_days.into())
+    }
+
+    /// ユーザーごとの上書き設定を適用したポリシーを返す
+    pub fn with_override(self, value: &LoanPolicyOverride) -> Self {
+        Self {
+            max_loans: value.max_loans.unwrap_or(self.max_loans),
+            loan_period_days: value.loan_period_days.unwrap_or(self.loan_period_days),
+            max_renewals: value.max_renewals.unwrap_or(self.max_renewals),
+        }
+    }
+}
+
+/// ユーザーごとの貸出ポリシーの上書き設定
+/// `None`の項目はロールのポリシーに従う
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoanPolicyOverride {
+    pub max_loans: Option<i32>,
+    pub loan_period_days: Option<i32>,
+    pub max_renewals: Option<i32>,
+}
+
+/// ロールごとの貸出ポリシー
+#[derive(Debug)]
+pub struct RoleLoanPolicy {
+    pub role: Role,
+    pub policy: LoanPolicy,
+}
+
+/// ユーザーに適用される貸出ポリシー
+#[derive(Debug)]
+pub struct UserLoanPolicy {
+    pub user_id: UserId,
+    /// ユーザーのロールに設定されたポリシー
+    pub role_policy: LoanPolicy,
+    pub user_override: Option<LoanPolicyOverride>,
+}
+
+impl UserLoanPolicy {
+    /// 上書き設定を適用した、実際に適用されるポリシー
+    pub fn effective(&self) -> LoanPolicy {
+        match &self.user_override {
+            Some(o) => self.role_policy.with_override(o),
+            None => self.role_policy,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effective_policy() {
+        let role_policy = LoanPolicy {
+            max_loans: 3,
+            loan_period_days: 7,
+            max_renewals: 1,
+        };
+
+        let policy = UserLoanPolicy {
+            user_id: UserId::new(),
+            role_policy,
+            user_override: None,
+        };
+        assert_eq!(policy.effective(), role_policy);
+
+        // 上書きされた項目のみ変わる
+        let policy = UserLoanPolicy {
+            user_override: Some(LoanPolicyOverride {
+                max_loans: Some(10),
+                ..Default::default()
+            }),
+            ..policy
+        };
+        assert_eq!(
+            policy.effective(),
+            LoanPolicy {
+                max_loans: 10,
+                ..role_policy
+            }
+        );
+        assert_eq!(policy.effective().loan_period(), Duration::days(7));
+    }
+}
@@ -0,0 +1,6 @@
+/// データベースに未適用のマイグレーション
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingMigration {
+    pub version: i64,
+    pub description: String,
+}
@@ -1,7 +1,19 @@
+pub mod api_key;
+pub mod audit;
 pub mod auth;
+pub mod backup;
 pub mod book;
 pub mod checkout;
+pub mod domain_event;
+pub mod fee;
 pub mod id;
+pub mod kiosk;
 pub mod list;
+pub mod live;
+pub mod loan_policy;
+pub mod migration;
+pub mod notification;
+pub mod reminder;
 pub mod role;
 pub mod user;
+pub mod webhook;
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+
+use crate::model::{checkout::Checkout, id::UserId, reminder::ReminderKind, user::User};
+
+/// 通知の宛先
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipient {
+    pub user_id: UserId,
+    pub name: String,
+    pub email: String,
+}
+
+impl From<&User> for Recipient {
+    fn from(user: &User) -> Self {
+        Self {
+            user_id: user.id,
+            name: user.name.clone(),
+            email: user.email.clone(),
+        }
+    }
+}
+
+/// 利用者への通知
+#[derive(Debug)]
+pub struct Notification {
+    pub recipient: Recipient,
+    pub message: NotificationMessage,
+}
+
+#[derive(Debug)]
+pub enum NotificationMessage {
+    /// 返却期限のリマインダー。同じ種類のリマインダーを送る貸出をまとめて通知する
+    Reminder {
+        kind: ReminderKind,
+        checkouts: Vec<Checkout>,
+    },
+    /// 貸出の完了
+    CheckoutConfirmed { checkouts: Vec<Checkout> },
+    /// 返却の完了。`checkouts`は返却日時が設定された貸出
+    ReturnConfirmed { checkouts: Vec<Checkout> },
+    /// 予約した蔵書の受け取りの準備ができた
+    HoldReady {
+        title: String,
+        /// 受け取りの期限
+        pickup_until: DateTime<Utc>,
+    },
+    /// パスワードの再設定
+    PasswordReset {
+        /// 再設定用のページのURL
+        reset_url: String,
+        expires_at: DateTime<Utc>,
+    },
+}
@@ -0,0 +1,52 @@
+use crate::model::id::CheckoutId;
+use chrono::{DateTime, Utc};
+use strum::{AsRefStr, EnumString};
+
+/// 返却期限のリマインダーの種類
+#[derive(Debug, Clone, Copy, EnumString, AsRefStr, PartialEq, Eq, Hash)]
+pub enum ReminderKind {
+    /// 返却期限が近づいている
+    DueSoon,
+    /// 返却期限を過ぎている
+    Overdue,
+}
+
+impl ReminderKind {
+    /// `now`時点で返却期限が`due_at`の貸出に送るリマインダーの種類
+    pub fn of(due_at: DateTime<Utc>, now: DateTime<Utc>) -> Self {
+        if due_at <= now {
+            Self::Overdue
+        } else {
+            Self::DueSoon
+        }
+    }
+}
+
+/// 貸出ごとに送信したリマインダー
+/// 返却期限が延長された場合は、新しい期限に対して改めて送信する
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckoutReminder {
+    pub checkout_id: CheckoutId,
+    pub kind: ReminderKind,
+    pub due_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_reminder_kind_of() {
+        let now = Utc::now();
+        assert_eq!(
+            ReminderKind::of(now + Duration::hours(1), now),
+            ReminderKind::DueSoon
+        );
+        assert_eq!(ReminderKind::of(now, now), ReminderKind::Overdue);
+        assert_eq!(
+            ReminderKind::of(now - Duration::days(1), now),
+            ReminderKind::Overdue
+        );
+    }
+}
@@ -1,10 +1,11 @@
-use crate::model::{id::UserId, role::Role};
+use crate::model::{auth::OidcIdentity, id::UserId, role::Role};
 
 #[derive(Debug)]
 pub struct CreateUser {
     pub name: String,
     pub email: String,
     pub password: String,
+    pub role: Role,
 }
 
 #[derive(Debug)]
@@ -20,7 +21,77 @@ pub struct UpdateUserPassword {
     pub new_password: String,
 }
 
+/// 管理者によるパスワードの再設定イベント
+/// 現在のパスワードを確認せずに、新しいパスワードを設定する
+#[derive(Debug)]
+pub struct ResetUserPassword {
+    pub user_id: UserId,
+    pub new_password: String,
+}
+
+/// ユーザー無効化(論理削除)イベント
+#[derive(Debug)]
+pub struct DeactivateUser {
+    pub user_id: UserId,
+}
+
+/// バッジ(社員証・利用者カード)のコードの登録イベント
+/// `badge_code`が`None`の場合は登録を解除する
+#[derive(Debug)]
+pub struct UpdateUserBadge {
+    pub user_id: UserId,
+    pub badge_code: Option<String>,
+}
+
+/// ユーザー再有効化イベント
+#[derive(Debug)]
+pub struct ReactivateUser {
+    pub user_id: UserId,
+}
+
+/// ユーザー物理削除イベント
 #[derive(Debug)]
 pub struct DeleteUser {
     pub user_id: UserId,
 }
+
+/// 外部の認証基盤(OIDC・LDAP)でログインしたユーザーを、
+/// 初回ログイン時に作成(または既存ユーザーに紐付け)するイベント
+#[derive(Debug)]
+pub struct ProvisionExternalUser {
+    /// 認証基盤の識別子(OIDCのIssuer、LDAPサーバーのURL)
+    pub issuer: String,
+    /// 認証基盤におけるユーザーの識別子(OIDCの`sub`、LDAPのDN)
+    pub subject: String,
+    pub email: String,
+    pub email_verified: bool,
+    /// 未紐付けの場合に、メールアドレスが一致する既存のユーザーへ紐付けるかどうか
+    /// `false`の場合、同じメールアドレスのユーザーが存在すれば紐付けずにエラーとする
+    pub link_by_email: bool,
+    pub name: String,
+    /// `Some`の場合は、認証基盤によって作成されたユーザーのロールをログインのたびに同期する
+    /// 既存のユーザーに紐付けた場合は、ローカルで付与されたロールを変更しない
+    pub role: Option<Role>,
+}
+
+impl From<OidcIdentity> for ProvisionExternalUser {
+    fn from(value: OidcIdentity) -> Self {
+        let OidcIdentity {
+            issuer,
+            subject,
+            email,
+            email_verified,
+            name,
+            role,
+        } = value;
+        Self {
+            issuer,
+            subject,
+            email,
+            email_verified,
+            link_by_email: true,
+            name,
+            role,
+        }
+    }
+}
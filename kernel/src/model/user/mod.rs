@@ -1,4 +1,5 @@
 use crate::model::{id::UserId, role::Role};
+use chrono::{DateTime, Utc};
 
 pub mod event;
 
@@ -8,6 +9,32 @@ pub struct User {
     pub name: String,
     pub email: String,
     pub role: Role,
+    /// 無効化された日時。有効なユーザーの場合は`None`
+    pub deactivated_at: Option<DateTime<Utc>>,
+}
+
+impl User {
+    pub fn is_active(&self) -> bool {
+        self.deactivated_at.is_none()
+    }
+}
+
+/// ユーザーの物理削除を妨げている要因
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct UserDeletionBlockers {
+    /// 所有している蔵書の数
+    pub owned_books: i64,
+    /// 未返却の貸出の数
+    pub unreturned_checkouts: i64,
+    /// 料金の台帳の記録の数
+    /// 物理削除すると台帳も失われるため、精算済みであっても削除を妨げる
+    pub fee_entries: i64,
+}
+
+impl UserDeletionBlockers {
+    pub fn is_empty(&self) -> bool {
+        self.owned_books == 0 && self.unreturned_checkouts == 0 && self.fee_entries == 0
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::model::{
+    id::{UserId, WebhookDeliveryId, WebhookId},
+    webhook::{WebhookEventType, WebhookSecret, WEBHOOK_SECRET_PREFIX},
+};
+
+/// Webhook登録イベント
+pub struct CreateWebhook {
+    pub url: String,
+    pub events: Vec<WebhookEventType>,
+    pub created_by: UserId,
+    pub secret: WebhookSecret,
+}
+
+impl CreateWebhook {
+    pub fn new(url: String, events: Vec<WebhookEventType>, created_by: UserId) -> Self {
+        let secret = WebhookSecret(format!(
+            "{}{}{}",
+            WEBHOOK_SECRET_PREFIX,
+            Uuid::new_v4().simple(),
+            Uuid::new_v4().simple()
+        ));
+        Self {
+            url,
+            events,
+            created_by,
+            secret,
+        }
+    }
+}
+
+/// Webhook削除イベント。配信の記録も削除される
+pub struct DeleteWebhook {
+    pub webhook_id: WebhookId,
+}
+
+/// 配信の送信結果の記録イベント
+#[derive(Debug)]
+pub struct RecordDeliveryAttempt {
+    pub delivery_id: WebhookDeliveryId,
+    pub attempted_at: DateTime<Utc>,
+    /// 配信先が返したHTTPステータス。接続できなかった場合は`None`
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub outcome: DeliveryOutcome,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryOutcome {
+    Succeeded,
+    /// 指定された日時に再送する
+    Retry(DateTime<Utc>),
+    /// 再送の上限に達したため、配信をあきらめる
+    Failed,
+}
+
+/// 過去の配信と同じ内容を、新しい配信として送り直すイベント
+pub struct ReplayDelivery {
+    pub webhook_id: WebhookId,
+    pub delivery_id: WebhookDeliveryId,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_webhook_generates_secret() {
+        let a = CreateWebhook::new("https://example.com/hook".into(), vec![], UserId::new());
+        let b = CreateWebhook::new("https://example.com/hook".into(), vec![], UserId::new());
+        assert!(a.secret.0.starts_with(WEBHOOK_SECRET_PREFIX));
+        assert_eq!(a.secret.0.len(), WEBHOOK_SECRET_PREFIX.len() + 64);
+        assert_ne!(a.secret, b.secret);
+    }
+}
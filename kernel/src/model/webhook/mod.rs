@@ -0,0 +1,322 @@
+use crate::model::{
+    checkout::Checkout,
+    domain_event::{BookRecord, DomainEvent, DomainEventKind, UserRecord},
+    id::{BookId, CheckoutId, UserId, WebhookDeliveryId, WebhookEventId, WebhookId},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use strum::{AsRefStr, EnumIter, EnumString};
+use uuid::Uuid;
+
+pub mod event;
+
+/// Webhookの署名に用いるシークレットのプレフィックス
+pub const WEBHOOK_SECRET_PREFIX: &str = "whsec_";
+
+/// Webhookで通知するイベントの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr, EnumIter)]
+pub enum WebhookEventType {
+    #[strum(serialize = "book.created")]
+    BookCreated,
+    #[strum(serialize = "book.updated")]
+    BookUpdated,
+    #[strum(serialize = "checkout.created")]
+    CheckoutCreated,
+    #[strum(serialize = "checkout.returned")]
+    CheckoutReturned,
+    #[strum(serialize = "user.created")]
+    UserCreated,
+}
+
+/// イベントの通知先として登録されたエンドポイント
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Webhook {
+    pub id: WebhookId,
+    pub url: String,
+    /// 通知するイベントの種類
+    pub events: Vec<WebhookEventType>,
+    /// Webhookを登録した管理者
+    pub created_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 登録したWebhook
+pub struct IssuedWebhook {
+    pub webhook: Webhook,
+    /// 配信の署名に用いるシークレット
+    pub secret: WebhookSecret,
+}
+
+/// 配信先が署名を検証するためのシークレット
+/// 署名の計算に用いるため、APIキーと異なりハッシュ化せずに保存する
+#[derive(Clone, PartialEq, Eq)]
+pub struct WebhookSecret(pub String);
+
+impl std::fmt::Debug for WebhookSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("WebhookSecret(..)")
+    }
+}
+
+/// Webhookで通知するイベント
+#[derive(Debug, Clone)]
+pub struct WebhookEvent {
+    pub id: WebhookEventId,
+    pub event_type: WebhookEventType,
+    pub occurred_at: DateTime<Utc>,
+    pub data: WebhookEventData,
+}
+
+impl WebhookEvent {
+    pub fn new(event_type: WebhookEventType, data: WebhookEventData) -> Self {
+        Self {
+            id: WebhookEventId::new(),
+            event_type,
+            occurred_at: Utc::now(),
+            data,
+        }
+    }
+
+    /// ドメインイベントを、Webhookで通知するイベントに変換する
+    ///
+    /// 一括貸出・返却のイベントは、貸出ごとのイベントに分ける。
+    /// イベントのIDはドメインイベントのIDから決まるため、同じドメインイベントを
+    /// 再び変換しても同じIDになり、配信先は重複を判定できる
+    pub fn from_domain(event: &DomainEvent) -> Vec<Self> {
+        let single = |event_type, data| {
+            vec![Self {
+                id: WebhookEventId::from(event.id.raw()),
+                event_type,
+                occurred_at: event.occurred_at,
+                data,
+            }]
+        };
+        let per_checkout = |event_type, checkouts: &[Checkout]| {
+            checkouts
+                .iter()
+                .map(|checkout| Self {
+                    id: WebhookEventId::from(Uuid::new_v5(
+                        &event.id.raw(),
+                        checkout.id.raw().as_bytes(),
+                    )),
+                    event_type,
+                    occurred_at: event.occurred_at,
+                    data: WebhookEventData::Checkout(checkout.into()),
+                })
+                .collect()
+        };
+        match &event.kind {
+            DomainEventKind::BookCreated(book) => single(
+                WebhookEventType::BookCreated,
+                WebhookEventData::Book(book.into()),
+            ),
+            DomainEventKind::BookUpdated(book) => single(
+                WebhookEventType::BookUpdated,
+                WebhookEventData::Book(book.into()),
+            ),
+            DomainEventKind::CheckedOut { checkouts, .. } => {
+                per_checkout(WebhookEventType::CheckoutCreated, checkouts)
+            }
+            DomainEventKind::Returned { checkouts, .. } => {
+                per_checkout(WebhookEventType::CheckoutReturned, checkouts)
+            }
+            DomainEventKind::UserCreated(user) => single(
+                WebhookEventType::UserCreated,
+                WebhookEventData::User(user.into()),
+            ),
+            DomainEventKind::BookDeleted { .. }
+            | DomainEventKind::BookStatusChanged { .. }
+            | DomainEventKind::Renewed(_)
+            | DomainEventKind::DeclaredLost(_) => vec![],
+        }
+    }
+}
+
+/// 配信先に送るイベントの内容
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum WebhookEventData {
+    Book(WebhookBook),
+    Checkout(WebhookCheckout),
+    User(WebhookUser),
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookBook {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub description: String,
+}
+
+impl From<&BookRecord> for WebhookBook {
+    fn from(book: &BookRecord) -> Self {
+        Self {
+            book_id: book.book_id,
+            title: book.title.clone(),
+            author: book.author.clone(),
+            isbn: book.isbn.clone(),
+            description: book.description.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookCheckout {
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub title: String,
+    pub user_id: UserId,
+    pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub returned_at: Option<DateTime<Utc>>,
+}
+
+impl From<&Checkout> for WebhookCheckout {
+    fn from(checkout: &Checkout) -> Self {
+        Self {
+            checkout_id: checkout.id,
+            book_id: checkout.book.book_id,
+            title: checkout.book.title.clone(),
+            user_id: checkout.checked_out_by,
+            checked_out_at: checkout.checked_out_at,
+            due_at: checkout.due_at,
+            returned_at: checkout.returned_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookUser {
+    pub user_id: UserId,
+    pub name: String,
+    pub email: String,
+    pub role: String,
+}
+
+impl From<&UserRecord> for WebhookUser {
+    fn from(user: &UserRecord) -> Self {
+        Self {
+            user_id: user.user_id,
+            name: user.name.clone(),
+            email: user.email.clone(),
+            role: user.role.clone(),
+        }
+    }
+}
+
+/// 配信の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr)]
+pub enum WebhookDeliveryStatus {
+    /// 配信待ち(再送待ちを含む)
+    Pending,
+    Succeeded,
+    /// 再送の上限に達した
+    Failed,
+}
+
+/// Webhookへのイベントの配信
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+    pub id: WebhookDeliveryId,
+    pub webhook_id: WebhookId,
+    pub event_id: WebhookEventId,
+    pub event_type: WebhookEventType,
+    /// 配信先に送信するJSON
+    pub payload: String,
+    pub status: WebhookDeliveryStatus,
+    /// 送信を試みた回数
+    pub attempts: i32,
+    /// 直近の送信で配信先が返したHTTPステータス
+    pub response_status: Option<i32>,
+    /// 直近の送信に失敗した理由
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// 送信する配信と、その配信先
+#[derive(Debug, Clone)]
+pub struct PendingDelivery {
+    pub delivery: WebhookDelivery,
+    pub url: String,
+    pub secret: WebhookSecret,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_event_type_names() {
+        let names: Vec<_> = WebhookEventType::iter()
+            .map(|t| t.as_ref().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "book.created",
+                "book.updated",
+                "checkout.created",
+                "checkout.returned",
+                "user.created"
+            ]
+        );
+        for name in names {
+            assert_eq!(WebhookEventType::from_str(&name).unwrap().as_ref(), name);
+        }
+        assert!(WebhookEventType::from_str("book.deleted").is_err());
+    }
+
+    #[test]
+    fn test_from_domain_event() {
+        let user_id = UserId::new();
+        let checkout = |title: &str| Checkout {
+            id: CheckoutId::new(),
+            checked_out_by: user_id,
+            checked_out_at: Utc::now(),
+            returned_at: None,
+            due_at: Utc::now(),
+            renewal_count: 0,
+            checked_out_via: None,
+            returned_via: None,
+            book: crate::model::checkout::CheckoutBook {
+                book_id: BookId::new(),
+                title: title.into(),
+                author: "".into(),
+                isbn: "".into(),
+            },
+        };
+        let event = DomainEvent::new(DomainEventKind::CheckedOut {
+            user_id,
+            checkouts: vec![checkout("A"), checkout("B")],
+        });
+
+        // 貸出ごとのイベントに分けられ、IDは変換するたびに同じになる
+        let events = WebhookEvent::from_domain(&event);
+        assert_eq!(events.len(), 2);
+        assert_ne!(events[0].id, events[1].id);
+        let again: Vec<_> = WebhookEvent::from_domain(&event)
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(again, [events[0].id, events[1].id]);
+        assert!(events
+            .iter()
+            .all(|e| e.event_type == WebhookEventType::CheckoutCreated));
+
+        // Webhookで通知しないイベント
+        let deleted = DomainEvent::new(DomainEventKind::BookDeleted {
+            book_id: BookId::new(),
+            deleted_by: user_id,
+        });
+        assert!(WebhookEvent::from_domain(&deleted).is_empty());
+    }
+}
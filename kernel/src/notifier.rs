@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::notification::Notification;
+
+/// 利用者に通知を届ける手段
+#[mockall::automock]
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, notification: &Notification) -> AppResult<()>;
+}
@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::{
+    api_key::{
+        event::{CreateApiKey, RevokeApiKey},
+        ApiKey, ApiKeySecret, IssuedApiKey,
+    },
+    id::UserId,
+};
+
+#[mockall::automock]
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    /// APIキーを発行する
+    async fn create(&self, event: CreateApiKey) -> AppResult<IssuedApiKey>;
+    /// ユーザーが発行したAPIキーの一覧を取得する
+    async fn find_by_user_id(&self, user_id: UserId) -> AppResult<Vec<ApiKey>>;
+    /// APIキーを失効させる
+    async fn revoke(&self, event: RevokeApiKey) -> AppResult<()>;
+    /// APIキー本体から有効なAPIキーを取得し、最終利用日時を更新する
+    async fn verify(&self, secret: &ApiKeySecret) -> AppResult<Option<ApiKey>>;
+}
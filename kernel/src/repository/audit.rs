@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::{
+    audit::{event::CreateAuditLog, AuditLog, AuditLogListOptions},
+    list::PaginatedList,
+};
+
+#[mockall::automock]
+#[async_trait]
+pub trait AuditLogRepository: Send + Sync {
+    /// 監査ログを追記する
+    async fn create(&self, event: CreateAuditLog) -> AppResult<()>;
+    /// 条件に合う監査ログを、新しいものから取得する
+    async fn find_all(&self, options: AuditLogListOptions) -> AppResult<PaginatedList<AuditLog>>;
+}
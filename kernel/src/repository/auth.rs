@@ -20,4 +20,6 @@ pub trait AuthRepository: Send + Sync {
     async fn create_token(&self, event: CreateToken) -> AppResult<AccessToken>;
     /// アクセストークンの削除
     async fn delete_token(&self, access_token: AccessToken) -> AppResult<()>;
+    /// ユーザーのアクセストークンをすべて削除し、削除した件数を返す
+    async fn delete_tokens_by_user_id(&self, user_id: UserId) -> AppResult<u64>;
 }
@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::{
+    audit::event::CreateAuditLog,
+    backup::{BackupArchive, BackupOptions, RestoreMode, RestoreSummary},
+};
+
+#[mockall::automock]
+#[async_trait]
+pub trait BackupRepository: Send + Sync {
+    /// ユーザー・蔵書・貸出のデータ全体を、ひとつの時点のものとして取得する
+    async fn create(&self, options: BackupOptions) -> AppResult<BackupArchive>;
+    /// バックアップを復元する
+    /// 検証に失敗した場合や、途中で失敗した場合は何も書き込まない
+    /// 監査ログには、復元の方法と復元した件数を操作後の内容として記録する
+    async fn restore(
+        &self,
+        archive: BackupArchive,
+        mode: RestoreMode,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<RestoreSummary>;
+}
@@ -2,8 +2,10 @@ use async_trait::async_trait;
 use shared::error::AppResult;
 
 use crate::model::{
+    audit::event::CreateAuditLog,
     book::{
-        event::{CreateBook, DeleteBook, UpdateBook},
+        event::{CreateBook, DeleteBook, ReassignBookOwner, UpdateBook, UpdateBookStatus},
+        status::BookStatusHistory,
         Book, BookListOptions,
     },
     id::{BookId, UserId},
@@ -14,13 +16,43 @@ use crate::model::{
 #[async_trait]
 pub trait BookRepository: Send + Sync {
     /// 蔵書レコード作成
-    async fn create(&self, event: CreateBook, user_id: UserId) -> AppResult<()>;
+    /// 蔵書を登録し、登録した蔵書のIDを返す
+    /// 監査ログの対象のIDには、登録した蔵書のIDを記録する
+    async fn create(
+        &self,
+        event: CreateBook,
+        user_id: UserId,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<BookId>;
+    /// 複数の蔵書を1つのトランザクションで登録し、登録した蔵書のIDを同じ順に返す
+    /// いずれかの登録に失敗した場合は、すべての登録を取り消す
+    /// 監査ログには、登録した蔵書のIDを`bookIds`として操作後の内容に加える
+    async fn create_many(
+        &self,
+        events: Vec<CreateBook>,
+        user_id: UserId,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<Vec<BookId>>;
     /// 蔵書の一覧を取得
     async fn find_all(&self, options: BookListOptions) -> AppResult<PaginatedList<Book>>;
     /// 蔵書IDを指定して蔵書データを取得
     async fn find_by_id(&self, book_id: BookId) -> AppResult<Option<Book>>;
     /// 蔵書データを更新
-    async fn update(&self, event: UpdateBook) -> AppResult<()>;
+    async fn update(&self, event: UpdateBook, audit: Option<CreateAuditLog>) -> AppResult<()>;
     /// 蔵書データを削除
-    async fn delete(&self, event: DeleteBook) -> AppResult<()>;
+    async fn delete(&self, event: DeleteBook, audit: Option<CreateAuditLog>) -> AppResult<()>;
+    /// 蔵書の所有者を別のユーザーへ付け替える
+    async fn reassign_owner(
+        &self,
+        event: ReassignBookOwner,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()>;
+    /// 蔵書の状態を変更する(貸出・返却に伴う変更を除く)
+    async fn update_status(
+        &self,
+        event: UpdateBookStatus,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()>;
+    /// 蔵書の状態の変更履歴を古い順に取得
+    async fn find_status_history(&self, book_id: BookId) -> AppResult<Vec<BookStatusHistory>>;
 }
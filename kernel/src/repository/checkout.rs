@@ -1,19 +1,67 @@
 use crate::model::{
+    audit::event::CreateAuditLog,
     checkout::{
-        event::{CreateCheckout, UpdateReturned},
-        Checkout,
+        event::{
+            CreateCheckout, CreateCheckouts, DeclareLost, RenewCheckout, ReturnBooks,
+            UpdateReturned,
+        },
+        BatchItemResult, Checkout, CheckoutHistoryOptions,
     },
-    id::{BookId, UserId},
+    id::{BookId, CheckoutId, UserId},
+    list::PaginatedList,
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use shared::error::AppResult;
 
+/// 貸出を変更するメソッドでは、監査ログを貸出ごとに記録する
+/// 対象のIDには貸出のIDを、操作後の内容には蔵書のIDを`bookId`として記録する
 #[mockall::automock]
 #[async_trait]
 pub trait CheckoutRepository: Send + Sync {
-    async fn create_checkout(&self, event: CreateCheckout) -> AppResult<()>;
-    async fn update_returned(&self, event: UpdateReturned) -> AppResult<()>;
+    /// 蔵書を貸し出し、作成した貸出のIDを返す
+    async fn create_checkout(
+        &self,
+        event: CreateCheckout,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<CheckoutId>;
+    async fn update_returned(
+        &self,
+        event: UpdateReturned,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()>;
+    /// 貸出期限を延長する
+    async fn renew(&self, event: RenewCheckout, audit: Option<CreateAuditLog>) -> AppResult<()>;
+    /// 借りている蔵書の紛失を届け出て、貸出を終了する
+    async fn declare_lost(
+        &self,
+        event: DeclareLost,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()>;
+    /// 複数の蔵書をまとめて貸し出し、蔵書ごとの結果を指定された順に返す
+    async fn create_checkouts(
+        &self,
+        event: CreateCheckouts,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<Vec<BatchItemResult>>;
+    /// 複数の蔵書をまとめて返却し、蔵書ごとの結果を指定された順に返す
+    async fn return_books(
+        &self,
+        event: ReturnBooks,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<Vec<BatchItemResult>>;
     async fn find_unreturned_all(&self) -> AppResult<Vec<Checkout>>;
+    /// 返却期限が`due_before`より前の未返却の貸出を、返却期限の早い順に取得する
+    async fn find_unreturned_due_before(
+        &self,
+        due_before: DateTime<Utc>,
+    ) -> AppResult<Vec<Checkout>>;
     async fn find_unreturned_by_user_id(&self, user_id: UserId) -> AppResult<Vec<Checkout>>;
     async fn find_history_by_book_id(&self, book_id: BookId) -> AppResult<Vec<Checkout>>;
+    /// ユーザーの貸出履歴(未返却・返却済みの両方)を、貸出日時の新しい順に取得する
+    async fn find_history_by_user_id(
+        &self,
+        user_id: UserId,
+        options: CheckoutHistoryOptions,
+    ) -> AppResult<PaginatedList<Checkout>>;
 }
@@ -0,0 +1,19 @@
+use std::pin::Pin;
+
+use shared::error::AppResult;
+use tokio_stream::Stream;
+
+use crate::model::{book::Book, checkout::Checkout};
+
+/// 取得したレコードを1件ずつ受け取るストリーム
+pub type RecordStream<T> = Pin<Box<dyn Stream<Item = AppResult<T>> + Send>>;
+
+/// 外部へのエクスポートのため、すべてのレコードを順に取得する
+/// 件数が多くてもメモリの使用量が増えないよう、結果はまとめずに1件ずつ返す
+#[mockall::automock]
+pub trait ExportRepository: Send + Sync {
+    /// すべての蔵書を、所有者・現在の貸出とともに登録順に取得する
+    fn stream_books(&self) -> RecordStream<Book>;
+    /// 未返却・返却済みのすべての貸出を、貸出日時の古い順に取得する
+    fn stream_checkouts(&self) -> RecordStream<Checkout>;
+}
@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::{
+    fee::{
+        event::{RecordFeePayment, UpdateFeeSchedule, WaiveFee},
+        FeeLedger, FeeSchedule,
+    },
+    id::UserId,
+};
+
+#[mockall::automock]
+#[async_trait]
+pub trait FeeRepository: Send + Sync {
+    /// 料金表を取得する
+    async fn find_schedule(&self) -> AppResult<FeeSchedule>;
+    /// 料金表を更新する
+    async fn update_schedule(&self, event: UpdateFeeSchedule) -> AppResult<()>;
+    /// ユーザーの台帳を取得する
+    async fn find_ledger_by_user_id(&self, user_id: UserId) -> AppResult<Option<FeeLedger>>;
+    /// 支払いを記録する
+    async fn record_payment(&self, event: RecordFeePayment) -> AppResult<()>;
+    /// 料金を免除する
+    async fn waive(&self, event: WaiveFee) -> AppResult<()>;
+}
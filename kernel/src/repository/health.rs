@@ -1,7 +1,12 @@
 use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::migration::PendingMigration;
 
 #[mockall::automock]
 #[async_trait]
 pub trait HealthCheckRepository: Send + Sync {
     async fn check_db(&self) -> bool;
+    /// アプリケーションに埋め込まれたマイグレーションのうち、データベースに未適用のものを取得する
+    async fn pending_migrations(&self) -> AppResult<Vec<PendingMigration>>;
 }
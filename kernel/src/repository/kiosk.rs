@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::{
+    id::KioskId,
+    kiosk::{
+        event::{CreateKiosk, RevokeKiosk},
+        IssuedKiosk, Kiosk, KioskSecret,
+    },
+};
+
+#[mockall::automock]
+#[async_trait]
+pub trait KioskRepository: Send + Sync {
+    /// キオスクを登録し、資格情報を発行する
+    async fn create(&self, event: CreateKiosk) -> AppResult<IssuedKiosk>;
+    /// 登録されたキオスクの一覧を取得する
+    async fn find_all(&self) -> AppResult<Vec<Kiosk>>;
+    /// キオスクの資格情報を失効させる
+    async fn revoke(&self, event: RevokeKiosk) -> AppResult<()>;
+    /// 資格情報本体から有効なキオスクを取得し、最終利用日時を更新する
+    async fn verify(&self, secret: &KioskSecret) -> AppResult<Option<Kiosk>>;
+    /// キオスクからのリクエストを数え、一定時間あたりの上限を超えている場合はエラーとする
+    async fn consume_rate_limit(&self, kiosk_id: KioskId) -> AppResult<()>;
+}
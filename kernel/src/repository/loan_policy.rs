@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::{
+    id::UserId,
+    loan_policy::{
+        event::{DeleteUserLoanPolicy, UpdateRoleLoanPolicy, UpdateUserLoanPolicy},
+        RoleLoanPolicy, UserLoanPolicy,
+    },
+};
+
+#[mockall::automock]
+#[async_trait]
+pub trait LoanPolicyRepository: Send + Sync {
+    /// 全ロールの貸出ポリシーを取得する
+    async fn find_all_role_policies(&self) -> AppResult<Vec<RoleLoanPolicy>>;
+    /// ロールの貸出ポリシーを更新する
+    async fn update_role_policy(&self, event: UpdateRoleLoanPolicy) -> AppResult<()>;
+    /// ユーザーに適用される貸出ポリシーを取得する
+    async fn find_by_user_id(&self, user_id: UserId) -> AppResult<Option<UserLoanPolicy>>;
+    /// ユーザーごとの上書き設定を登録・更新する
+    async fn update_user_policy(&self, event: UpdateUserLoanPolicy) -> AppResult<()>;
+    /// ユーザーごとの上書き設定を削除し、ロールのポリシーに戻す
+    async fn delete_user_policy(&self, event: DeleteUserLoanPolicy) -> AppResult<()>;
+}
@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::auth::{event::VerifyOidcCallback, OidcAuthorization, OidcIdentity};
+
+#[mockall::automock]
+#[async_trait]
+pub trait OidcRepository: Send + Sync {
+    /// 認可リクエストを開始し、IDプロバイダーの認可エンドポイントのURLを返す
+    async fn authorize(&self) -> AppResult<OidcAuthorization>;
+    /// 認可コードをトークンと交換し、IDトークンを検証する
+    async fn verify_callback(&self, event: VerifyOidcCallback) -> AppResult<OidcIdentity>;
+}
@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use shared::error::AppResult;
+
+use crate::model::{domain_event::PendingEvent, id::DomainEventId};
+
+#[mockall::automock]
+#[async_trait]
+pub trait OutboxRepository: Send + Sync {
+    /// 配信時刻を迎えた未処理のイベントを、古いものから取得する
+    /// 取得したイベントは、他のインスタンスが同時に配信しないよう一定時間確保される
+    async fn claim_pending(&self, now: DateTime<Utc>, limit: i64) -> AppResult<Vec<PendingEvent>>;
+    /// コンシューマーがイベントを処理し終えたことを記録する
+    async fn mark_consumed(&self, event_id: DomainEventId, consumer: &str) -> AppResult<()>;
+    /// すべてのコンシューマーへの配信が終わったイベントを処理済みにする
+    async fn complete(&self, event_id: DomainEventId, at: DateTime<Utc>) -> AppResult<()>;
+    /// 配信に失敗したイベントを、`retry_at`に配信し直すよう記録する
+    async fn record_failure(
+        &self,
+        event_id: DomainEventId,
+        error: &str,
+        retry_at: DateTime<Utc>,
+    ) -> AppResult<()>;
+}
@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::reminder::CheckoutReminder;
+
+#[mockall::automock]
+#[async_trait]
+pub trait ReminderRepository: Send + Sync {
+    /// リマインダーを送信済みとして記録する
+    /// すでに記録されている場合や、対象の貸出が返却・延長された場合は`false`を返す
+    async fn claim(&self, reminder: &CheckoutReminder) -> AppResult<bool>;
+    /// 送信に失敗したリマインダーの記録を取り消し、次回の送信対象に戻す
+    async fn release(&self, reminder: &CheckoutReminder) -> AppResult<()>;
+}
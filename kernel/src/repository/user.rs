@@ -2,10 +2,14 @@ use async_trait::async_trait;
 use shared::error::AppResult;
 
 use crate::model::{
+    audit::event::CreateAuditLog,
     id::UserId,
     user::{
-        event::{CreateUser, DeleteUser, UpdateUserPassword, UpdateUserRole},
-        User,
+        event::{
+            CreateUser, DeactivateUser, DeleteUser, ProvisionExternalUser, ReactivateUser,
+            ResetUserPassword, UpdateUserBadge, UpdateUserPassword, UpdateUserRole,
+        },
+        User, UserDeletionBlockers,
     },
 };
 
@@ -14,8 +18,54 @@ use crate::model::{
 pub trait UserRepository: Send + Sync {
     async fn find_current_user(&self, current_user_id: UserId) -> AppResult<Option<User>>;
     async fn find_all(&self) -> AppResult<Vec<User>>;
-    async fn create(&self, event: CreateUser) -> AppResult<User>;
-    async fn update_password(&self, event: UpdateUserPassword) -> AppResult<()>;
-    async fn update_role(&self, event: UpdateUserRole) -> AppResult<()>;
-    async fn delete(&self, event: DeleteUser) -> AppResult<()>;
+    /// 監査ログの対象のIDには、作成したユーザーのIDを記録する
+    async fn create(&self, event: CreateUser, audit: Option<CreateAuditLog>) -> AppResult<User>;
+    async fn update_password(
+        &self,
+        event: UpdateUserPassword,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()>;
+    /// 現在のパスワードを確認せずに、パスワードを設定し直す
+    /// パスワードを持たないユーザーの場合は、新たにパスワードを設定する
+    async fn reset_password(
+        &self,
+        event: ResetUserPassword,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()>;
+    async fn update_role(
+        &self,
+        event: UpdateUserRole,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()>;
+    /// バッジのコードを登録・解除する
+    /// 他のユーザーに登録済みのコードの場合はエラーとなる
+    async fn update_badge(
+        &self,
+        event: UpdateUserBadge,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()>;
+    /// バッジのコードから有効なユーザーを取得する
+    async fn find_by_badge_code(&self, badge_code: &str) -> AppResult<Option<User>>;
+    /// ユーザーを無効化する(論理削除)
+    async fn deactivate(
+        &self,
+        event: DeactivateUser,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()>;
+    /// 無効化されたユーザーを再度有効化する
+    async fn reactivate(
+        &self,
+        event: ReactivateUser,
+        audit: Option<CreateAuditLog>,
+    ) -> AppResult<()>;
+    /// ユーザーの物理削除を妨げている要因を取得する
+    async fn find_deletion_blockers(&self, user_id: UserId) -> AppResult<UserDeletionBlockers>;
+    /// ユーザーを物理削除する
+    /// 削除を妨げる要因が存在する場合はエラーとなる
+    async fn delete(&self, event: DeleteUser, audit: Option<CreateAuditLog>) -> AppResult<()>;
+    /// 外部の認証基盤の識別子に対応するユーザーを取得し、存在しなければ作成する
+    /// 未紐付けの場合は、検証済みのメールアドレスが一致する既存のユーザーに紐付ける
+    /// メールアドレスが未検証の場合や、他のユーザーと重複して紐付けられない場合、
+    /// 無効化されたユーザーの場合はエラーとなる
+    async fn provision_external_user(&self, event: ProvisionExternalUser) -> AppResult<User>;
 }
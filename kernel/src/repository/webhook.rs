@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use shared::error::AppResult;
+
+use crate::model::{
+    id::WebhookId,
+    webhook::{
+        event::{CreateWebhook, DeleteWebhook, RecordDeliveryAttempt, ReplayDelivery},
+        IssuedWebhook, PendingDelivery, Webhook, WebhookDelivery, WebhookEvent,
+    },
+};
+
+#[mockall::automock]
+#[async_trait]
+pub trait WebhookRepository: Send + Sync {
+    /// Webhookを登録し、署名用のシークレットを発行する
+    async fn create(&self, event: CreateWebhook) -> AppResult<IssuedWebhook>;
+    async fn find_all(&self) -> AppResult<Vec<Webhook>>;
+    async fn delete(&self, event: DeleteWebhook) -> AppResult<()>;
+    /// イベントを購読しているWebhookごとに、配信を登録する
+    async fn enqueue(&self, event: &WebhookEvent) -> AppResult<()>;
+    /// Webhookへの配信を、新しいものから`limit`件取得する
+    async fn find_deliveries(
+        &self,
+        webhook_id: WebhookId,
+        limit: i64,
+    ) -> AppResult<Vec<WebhookDelivery>>;
+    /// 送信時刻を迎えた配信を取得する
+    /// 取得した配信は、他のインスタンスが同時に送信しないよう一定時間確保される
+    async fn claim_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> AppResult<Vec<PendingDelivery>>;
+    async fn record_attempt(&self, event: RecordDeliveryAttempt) -> AppResult<()>;
+    /// 過去の配信と同じ内容を送り直す配信を登録する
+    async fn replay(&self, event: ReplayDelivery) -> AppResult<WebhookDelivery>;
+}
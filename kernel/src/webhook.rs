@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::webhook::PendingDelivery;
+
+/// Webhookの配信先にイベントを送信する
+#[mockall::automock]
+#[async_trait]
+pub trait WebhookSender: Send + Sync {
+    /// 配信を送信し、配信先が返したHTTPステータスを返す
+    /// 配信先に接続できなかった場合はエラーとする
+    async fn send(&self, delivery: &PendingDelivery) -> AppResult<u16>;
+}
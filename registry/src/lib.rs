@@ -1,21 +1,56 @@
 use std::sync::Arc;
 
 use adapter::{
+    consumer::{
+        live::LiveEventConsumer, notification::NotificationConsumer, webhook::WebhookConsumer,
+    },
     database::ConnectionPool,
+    ldap::{LdapClient, LdapPasswordVerifier},
+    live::RedisLiveEventBus,
+    mail::{queue::MailQueue, SmtpMailer},
+    notifier::{log::LogNotifier, mail::MailNotifier},
+    oidc::OidcClient,
     redis::RedisClient,
     repository::{
-        auth::AuthRepositoryImpl, checkout::CheckoutRepositoryImpl,
-        health::HealthCheckRepositoryImpl, user::UserRepositoryImpl,
+        api_key::ApiKeyRepositoryImpl,
+        audit::AuditLogRepositoryImpl,
+        auth::{AuthRepositoryImpl, LocalPasswordVerifier, PasswordVerifier},
+        backup::BackupRepositoryImpl,
+        checkout::CheckoutRepositoryImpl,
+        export::ExportRepositoryImpl,
+        fee::FeeRepositoryImpl,
+        health::HealthCheckRepositoryImpl,
+        kiosk::KioskRepositoryImpl,
+        loan_policy::LoanPolicyRepositoryImpl,
+        oidc::OidcRepositoryImpl,
+        outbox::OutboxRepositoryImpl,
+        reminder::ReminderRepositoryImpl,
+        user::UserRepositoryImpl,
+        webhook::WebhookRepositoryImpl,
     },
+    webhook::HttpWebhookSender,
 };
 
 use adapter::repository::book::BookRepositoryImpl;
-use kernel::repository::{
-    auth::AuthRepository, book::BookRepository, checkout::CheckoutRepository,
-    health::HealthCheckRepository, user::UserRepository,
+use kernel::{
+    consumer::DomainEventConsumer,
+    live::LiveEventBus,
+    notifier::Notifier,
+    repository::{
+        api_key::ApiKeyRepository, audit::AuditLogRepository, auth::AuthRepository,
+        backup::BackupRepository, book::BookRepository, checkout::CheckoutRepository,
+        export::ExportRepository, fee::FeeRepository, health::HealthCheckRepository,
+        kiosk::KioskRepository, loan_policy::LoanPolicyRepository, oidc::OidcRepository,
+        outbox::OutboxRepository, reminder::ReminderRepository, user::UserRepository,
+        webhook::WebhookRepository,
+    },
+    webhook::WebhookSender,
 };
 
-use shared::config::AppConfig;
+use shared::{
+    config::{AppConfig, LabelConfig, LiveEventConfig, ProxyConfig},
+    error::AppResult,
+};
 
 #[derive(Clone)]
 pub struct AppRegistryImpl {
@@ -24,6 +59,24 @@ pub struct AppRegistryImpl {
     auth_repository: Arc<dyn AuthRepository>,
     user_repository: Arc<dyn UserRepository>,
     check_out_repository: Arc<dyn CheckoutRepository>,
+    api_key_repository: Arc<dyn ApiKeyRepository>,
+    oidc_repository: Arc<dyn OidcRepository>,
+    loan_policy_repository: Arc<dyn LoanPolicyRepository>,
+    fee_repository: Arc<dyn FeeRepository>,
+    kiosk_repository: Arc<dyn KioskRepository>,
+    reminder_repository: Arc<dyn ReminderRepository>,
+    notifier: Arc<dyn Notifier>,
+    webhook_repository: Arc<dyn WebhookRepository>,
+    webhook_sender: Arc<dyn WebhookSender>,
+    outbox_repository: Arc<dyn OutboxRepository>,
+    event_consumers: Vec<Arc<dyn DomainEventConsumer>>,
+    audit_log_repository: Arc<dyn AuditLogRepository>,
+    export_repository: Arc<dyn ExportRepository>,
+    backup_repository: Arc<dyn BackupRepository>,
+    live_event_bus: Arc<dyn LiveEventBus>,
+    label_config: Arc<LabelConfig>,
+    live_event_config: Arc<LiveEventConfig>,
+    proxy_config: Arc<ProxyConfig>,
 }
 
 impl AppRegistryImpl {
@@ -31,23 +84,90 @@ impl AppRegistryImpl {
         pool: ConnectionPool,
         redis_client: Arc<RedisClient>,
         app_config: AppConfig,
-    ) -> Self {
+    ) -> AppResult<Self> {
         let health_check_repository = Arc::new(HealthCheckRepositoryImpl::new(pool.clone()));
         let book_repository = Arc::new(BookRepositoryImpl::new(pool.clone()));
+        let user_repository = Arc::new(UserRepositoryImpl::new(pool.clone()));
+        // ローカルのパスワードを先に検証し、LDAPが利用できない場合もローカルの管理者がログインできるようにする
+        let mut password_verifiers: Vec<Arc<dyn PasswordVerifier>> =
+            vec![Arc::new(LocalPasswordVerifier::new(pool.clone()))];
+        if let Some(ldap) = app_config.ldap {
+            password_verifiers.push(Arc::new(LdapPasswordVerifier::new(
+                LdapClient::new(ldap),
+                user_repository.clone(),
+            )));
+        }
         let auth_repository = Arc::new(AuthRepositoryImpl::new(
-            pool.clone(),
-            redis_client,
+            redis_client.clone(),
             app_config.auth.ttl,
+            password_verifiers,
         ));
-        let user_repository = Arc::new(UserRepositoryImpl::new(pool.clone()));
-        let check_out_repository = Arc::new(CheckoutRepositoryImpl::new(pool));
-        Self {
+        let check_out_repository = Arc::new(CheckoutRepositoryImpl::new(pool.clone()));
+        let api_key_repository = Arc::new(ApiKeyRepositoryImpl::new(pool.clone()));
+        let loan_policy_repository = Arc::new(LoanPolicyRepositoryImpl::new(pool.clone()));
+        let fee_repository = Arc::new(FeeRepositoryImpl::new(pool.clone()));
+        let reminder_repository = Arc::new(ReminderRepositoryImpl::new(pool.clone()));
+        let webhook_repository = Arc::new(WebhookRepositoryImpl::new(pool.clone()));
+        let webhook_sender = Arc::new(HttpWebhookSender::new(&app_config.webhook)?);
+        let outbox_repository = Arc::new(OutboxRepositoryImpl::new(pool.clone()));
+        let audit_log_repository = Arc::new(AuditLogRepositoryImpl::new(pool.clone()));
+        let export_repository = Arc::new(ExportRepositoryImpl::new(pool.clone()));
+        let backup_repository = Arc::new(BackupRepositoryImpl::new(pool.clone()));
+        let live_event_bus = Arc::new(RedisLiveEventBus::spawn(
+            redis_client.clone(),
+            app_config.live_event.clone(),
+        ));
+        let kiosk_repository = Arc::new(KioskRepositoryImpl::new(
+            pool,
+            redis_client.clone(),
+            app_config.kiosk,
+        ));
+        let oidc_repository = Arc::new(OidcRepositoryImpl::new(
+            app_config.oidc.map(OidcClient::new),
+            redis_client,
+        ));
+        // SMTPサーバーが設定されていない場合は、通知をログに出力する
+        let locale = app_config.mail.locale;
+        let notifier: Arc<dyn Notifier> = match app_config.smtp {
+            Some(smtp) => Arc::new(MailNotifier::new(
+                MailQueue::spawn(SmtpMailer::new(smtp)?, app_config.mail),
+                locale,
+            )),
+            None => Arc::new(LogNotifier::new(locale)),
+        };
+        let event_consumers: Vec<Arc<dyn DomainEventConsumer>> = vec![
+            Arc::new(WebhookConsumer::new(webhook_repository.clone())),
+            Arc::new(NotificationConsumer::new(
+                user_repository.clone(),
+                notifier.clone(),
+            )),
+            Arc::new(LiveEventConsumer::new(live_event_bus.clone())),
+        ];
+        Ok(Self {
             health_check_repository,
             book_repository,
             auth_repository,
             user_repository,
             check_out_repository,
-        }
+            api_key_repository,
+            oidc_repository,
+            loan_policy_repository,
+            fee_repository,
+            kiosk_repository,
+            reminder_repository,
+            notifier,
+            webhook_repository,
+            webhook_sender,
+            outbox_repository,
+            event_consumers,
+            audit_log_repository,
+            export_repository,
+            backup_repository,
+            live_event_bus,
+            label_config: Arc::new(app_config.label),
+            live_event_config: Arc::new(app_config.live_event),
+            proxy_config: Arc::new(app_config.proxy),
+        })
     }
 }
 
@@ -61,6 +181,25 @@ pub trait AppRegistryExt {
     fn auth_repository(&self) -> Arc<dyn AuthRepository>;
     fn user_repository(&self) -> Arc<dyn UserRepository>;
     fn check_out_repository(&self) -> Arc<dyn CheckoutRepository>;
+    fn api_key_repository(&self) -> Arc<dyn ApiKeyRepository>;
+    fn oidc_repository(&self) -> Arc<dyn OidcRepository>;
+    fn loan_policy_repository(&self) -> Arc<dyn LoanPolicyRepository>;
+    fn fee_repository(&self) -> Arc<dyn FeeRepository>;
+    fn kiosk_repository(&self) -> Arc<dyn KioskRepository>;
+    fn reminder_repository(&self) -> Arc<dyn ReminderRepository>;
+    fn notifier(&self) -> Arc<dyn Notifier>;
+    fn webhook_repository(&self) -> Arc<dyn WebhookRepository>;
+    fn webhook_sender(&self) -> Arc<dyn WebhookSender>;
+    fn outbox_repository(&self) -> Arc<dyn OutboxRepository>;
+    /// ドメインイベントを届けるコンシューマー
+    fn event_consumers(&self) -> Vec<Arc<dyn DomainEventConsumer>>;
+    fn audit_log_repository(&self) -> Arc<dyn AuditLogRepository>;
+    fn export_repository(&self) -> Arc<dyn ExportRepository>;
+    fn backup_repository(&self) -> Arc<dyn BackupRepository>;
+    fn live_event_bus(&self) -> Arc<dyn LiveEventBus>;
+    fn label_config(&self) -> Arc<LabelConfig>;
+    fn live_event_config(&self) -> Arc<LiveEventConfig>;
+    fn proxy_config(&self) -> Arc<ProxyConfig>;
 }
 impl AppRegistryExt for AppRegistryImpl {
     fn health_check_repository(&self) -> Arc<dyn HealthCheckRepository> {
@@ -82,6 +221,78 @@ impl AppRegistryExt for AppRegistryImpl {
     fn check_out_repository(&self) -> Arc<dyn CheckoutRepository> {
         self.check_out_repository.clone()
     }
+
+    fn api_key_repository(&self) -> Arc<dyn ApiKeyRepository> {
+        self.api_key_repository.clone()
+    }
+
+    fn oidc_repository(&self) -> Arc<dyn OidcRepository> {
+        self.oidc_repository.clone()
+    }
+
+    fn loan_policy_repository(&self) -> Arc<dyn LoanPolicyRepository> {
+        self.loan_policy_repository.clone()
+    }
+
+    fn fee_repository(&self) -> Arc<dyn FeeRepository> {
+        self.fee_repository.clone()
+    }
+
+    fn kiosk_repository(&self) -> Arc<dyn KioskRepository> {
+        self.kiosk_repository.clone()
+    }
+
+    fn reminder_repository(&self) -> Arc<dyn ReminderRepository> {
+        self.reminder_repository.clone()
+    }
+
+    fn notifier(&self) -> Arc<dyn Notifier> {
+        self.notifier.clone()
+    }
+
+    fn webhook_repository(&self) -> Arc<dyn WebhookRepository> {
+        self.webhook_repository.clone()
+    }
+
+    fn webhook_sender(&self) -> Arc<dyn WebhookSender> {
+        self.webhook_sender.clone()
+    }
+
+    fn outbox_repository(&self) -> Arc<dyn OutboxRepository> {
+        self.outbox_repository.clone()
+    }
+
+    fn event_consumers(&self) -> Vec<Arc<dyn DomainEventConsumer>> {
+        self.event_consumers.clone()
+    }
+
+    fn audit_log_repository(&self) -> Arc<dyn AuditLogRepository> {
+        self.audit_log_repository.clone()
+    }
+
+    fn export_repository(&self) -> Arc<dyn ExportRepository> {
+        self.export_repository.clone()
+    }
+
+    fn backup_repository(&self) -> Arc<dyn BackupRepository> {
+        self.backup_repository.clone()
+    }
+
+    fn live_event_bus(&self) -> Arc<dyn LiveEventBus> {
+        self.live_event_bus.clone()
+    }
+
+    fn label_config(&self) -> Arc<LabelConfig> {
+        self.label_config.clone()
+    }
+
+    fn live_event_config(&self) -> Arc<LiveEventConfig> {
+        self.live_event_config.clone()
+    }
+
+    fn proxy_config(&self) -> Arc<ProxyConfig> {
+        self.proxy_config.clone()
+    }
 }
 
 //　従来AppRegistry型に依存していた処理に対し、
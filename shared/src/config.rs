@@ -1,9 +1,24 @@
+use std::{net::IpAddr, time::Duration};
+
 use anyhow::Result;
+use ipnet::IpNet;
+use strum::EnumString;
 
 pub struct AppConfig {
     pub database: DatabaseConfig,
     pub redis: RedisConfig,
     pub auth: AuthConfig,
+    pub oidc: Option<OidcConfig>,
+    pub ldap: Option<LdapConfig>,
+    pub label: LabelConfig,
+    pub kiosk: KioskConfig,
+    pub smtp: Option<SmtpConfig>,
+    pub mail: MailConfig,
+    pub reminder: ReminderConfig,
+    pub webhook: WebhookConfig,
+    pub outbox: OutboxConfig,
+    pub live_event: LiveEventConfig,
+    pub proxy: ProxyConfig,
 }
 
 impl AppConfig {
@@ -22,10 +37,32 @@ impl AppConfig {
         let auth = AuthConfig {
             ttl: std::env::var("AUTH_TOKEN_TTL")?.parse::<u64>()?,
         };
+        let oidc = OidcConfig::from_env()?;
+        let ldap = LdapConfig::from_env()?;
+        let label = LabelConfig::from_env()?;
+        let kiosk = KioskConfig::from_env()?;
+        let smtp = SmtpConfig::from_env()?;
+        let mail = MailConfig::from_env()?;
+        let reminder = ReminderConfig::from_env()?;
+        let webhook = WebhookConfig::from_env()?;
+        let outbox = OutboxConfig::from_env()?;
+        let live_event = LiveEventConfig::from_env()?;
+        let proxy = ProxyConfig::from_env()?;
         Ok(Self {
             database,
             redis,
             auth,
+            oidc,
+            ldap,
+            label,
+            kiosk,
+            smtp,
+            mail,
+            reminder,
+            webhook,
+            outbox,
+            live_event,
+            proxy,
         })
     }
 }
@@ -47,6 +84,534 @@ pub struct AuthConfig {
     pub ttl: u64,
 }
 
+/// OpenID Connectによるシングルサインオンの設定
+/// 環境変数`OIDC_ISSUER_URL`が未設定の場合は無効となる
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// IDプロバイダーのIssuer URL(`/.well-known/openid-configuration`の取得元)
+    pub issuer_url: String,
+    pub client_id: String,
+    /// パブリッククライアントとして登録されている場合は未設定
+    pub client_secret: Option<String>,
+    /// 認可コードを受け取るフロントエンドのURL
+    pub redirect_url: String,
+    pub scopes: Vec<String>,
+    /// ロールの判定に用いるIDトークンのクレーム名(例: `groups`)
+    /// 未設定の場合、ロールはIDプロバイダーの情報から変更しない
+    pub role_claim: Option<String>,
+    /// `role_claim`にこれらの値のいずれかが含まれる場合に管理者とみなす
+    pub admin_role_values: Vec<String>,
+}
+
+impl OidcConfig {
+    fn from_env() -> Result<Option<Self>> {
+        let Ok(issuer_url) = std::env::var("OIDC_ISSUER_URL") else {
+            return Ok(None);
+        };
+        let split = |v: String| -> Vec<String> {
+            v.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect()
+        };
+        Ok(Some(Self {
+            issuer_url,
+            client_id: std::env::var("OIDC_CLIENT_ID")?,
+            client_secret: std::env::var("OIDC_CLIENT_SECRET").ok(),
+            redirect_url: std::env::var("OIDC_REDIRECT_URL")?,
+            scopes: split(
+                std::env::var("OIDC_SCOPES").unwrap_or_else(|_| "openid email profile".into()),
+            ),
+            role_claim: std::env::var("OIDC_ROLE_CLAIM").ok(),
+            admin_role_values: split(std::env::var("OIDC_ADMIN_ROLE_VALUES").unwrap_or_default()),
+        }))
+    }
+}
+
+/// LDAPによるパスワード認証の設定
+/// 環境変数`LDAP_URL`が未設定の場合は無効となる
+#[derive(Debug, Clone)]
+pub struct LdapConfig {
+    /// `ldap://`または`ldaps://`から始まるサーバーのURL
+    pub url: String,
+    /// ユーザーの検索に用いるサービスアカウント。未設定の場合は匿名で検索する
+    pub bind_dn: Option<String>,
+    pub bind_password: Option<String>,
+    /// ユーザーを検索するベースDN
+    pub user_base_dn: String,
+    /// ユーザーの検索フィルター。`{email}`がログイン時のメールアドレスに置き換えられる
+    pub user_filter: String,
+    /// 表示名として用いる属性
+    pub name_attribute: String,
+    /// 所属グループを検索するベースDN
+    /// 未設定の場合はユーザーエントリの`memberOf`属性を所属グループとみなす
+    pub group_base_dn: Option<String>,
+    /// これらのグループ(DN)のいずれかに所属する場合に管理者とみなす
+    /// 未設定の場合、ロールはLDAPの情報から変更しない
+    pub admin_groups: Vec<String>,
+}
+
+impl LdapConfig {
+    fn from_env() -> Result<Option<Self>> {
+        let Ok(url) = std::env::var("LDAP_URL") else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            url,
+            bind_dn: std::env::var("LDAP_BIND_DN").ok(),
+            bind_password: std::env::var("LDAP_BIND_PASSWORD").ok(),
+            user_base_dn: std::env::var("LDAP_USER_BASE_DN")?,
+            user_filter: std::env::var("LDAP_USER_FILTER")
+                .unwrap_or_else(|_| "(&(objectClass=inetOrgPerson)(mail={email}))".into()),
+            name_attribute: std::env::var("LDAP_NAME_ATTRIBUTE").unwrap_or_else(|_| "cn".into()),
+            group_base_dn: std::env::var("LDAP_GROUP_BASE_DN").ok(),
+            // DNはカンマを含むため、セミコロンで区切る
+            admin_groups: std::env::var("LDAP_ADMIN_GROUPS")
+                .unwrap_or_default()
+                .split(';')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect(),
+        }))
+    }
+}
+
+/// 蔵書のラベル(QRコード・バーコード)の設定
+#[derive(Debug, Clone, Default)]
+pub struct LabelConfig {
+    /// QRコードに埋め込むURLのテンプレート。`{book_id}`が蔵書のIDに置き換えられる
+    /// (例: `https://library.example.com/books/{book_id}`)
+    /// 未設定の場合は蔵書のIDのみを埋め込む
+    pub link_template: Option<String>,
+}
+
+impl LabelConfig {
+    fn from_env() -> Result<Self> {
+        let link_template = std::env::var("BOOK_LABEL_LINK_TEMPLATE").ok();
+        if let Some(template) = &link_template {
+            anyhow::ensure!(
+                template.contains("{book_id}"),
+                "BOOK_LABEL_LINK_TEMPLATE must contain {{book_id}}"
+            );
+        }
+        Ok(Self { link_template })
+    }
+}
+
+/// キオスク(共用端末)の設定
+#[derive(Debug, Clone)]
+pub struct KioskConfig {
+    /// 1台のキオスクから受け付けるリクエスト数の上限(`rate_limit_window`秒あたり)
+    pub rate_limit: u64,
+    pub rate_limit_window: u64,
+}
+
+impl Default for KioskConfig {
+    fn default() -> Self {
+        Self {
+            rate_limit: 30,
+            rate_limit_window: 60,
+        }
+    }
+}
+
+impl KioskConfig {
+    fn from_env() -> Result<Self> {
+        let default = Self::default();
+        let rate_limit = match std::env::var("KIOSK_RATE_LIMIT") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.rate_limit,
+        };
+        let rate_limit_window = match std::env::var("KIOSK_RATE_LIMIT_WINDOW") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.rate_limit_window,
+        };
+        anyhow::ensure!(
+            rate_limit_window > 0,
+            "KIOSK_RATE_LIMIT_WINDOW must be greater than 0"
+        );
+        Ok(Self {
+            rate_limit,
+            rate_limit_window,
+        })
+    }
+}
+
+/// メール送信に用いるSMTPサーバーの設定
+/// 環境変数`SMTP_HOST`が未設定の場合、通知はメールで送らずログに出力する
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    /// 送信元のメールアドレス(例: `Library <library@example.com>`)
+    pub from: String,
+    pub tls: SmtpTls,
+    /// SMTP認証の資格情報。未設定の場合は認証しない
+    pub credentials: Option<SmtpCredentials>,
+}
+
+/// SMTPサーバーとの通信の暗号化方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum SmtpTls {
+    /// 暗号化しない。ローカルの開発用サーバーなどでのみ用いる
+    None,
+    /// 平文で接続した後、STARTTLSで暗号化する。サーバーが対応していない場合は送信しない
+    StartTls,
+    /// 接続時からTLSで暗号化する(SMTPS)
+    Tls,
+}
+
+impl SmtpTls {
+    fn default_port(self) -> u16 {
+        match self {
+            Self::None => 25,
+            Self::StartTls => 587,
+            Self::Tls => 465,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SmtpCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl std::fmt::Debug for SmtpCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmtpCredentials")
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SmtpConfig {
+    fn from_env() -> Result<Option<Self>> {
+        let Ok(host) = std::env::var("SMTP_HOST") else {
+            return Ok(None);
+        };
+        let tls = match std::env::var("SMTP_TLS") {
+            Ok(v) => v
+                .parse()
+                .map_err(|_| anyhow::anyhow!("SMTP_TLS must be one of none, starttls or tls"))?,
+            Err(_) => SmtpTls::StartTls,
+        };
+        let port = match std::env::var("SMTP_PORT") {
+            Ok(v) => v.parse()?,
+            Err(_) => tls.default_port(),
+        };
+        let credentials = match std::env::var("SMTP_USERNAME") {
+            Ok(username) => Some(SmtpCredentials {
+                username,
+                password: std::env::var("SMTP_PASSWORD")?,
+            }),
+            Err(_) => None,
+        };
+        Ok(Some(Self {
+            host,
+            port,
+            from: std::env::var("SMTP_FROM")?,
+            tls,
+            credentials,
+        }))
+    }
+}
+
+/// メールの本文に用いる言語
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum MailLocale {
+    #[default]
+    Ja,
+    En,
+}
+
+/// メールの送信に関する設定
+#[derive(Debug, Clone)]
+pub struct MailConfig {
+    pub locale: MailLocale,
+    /// 1通のメールを送信する回数の上限(初回を含む)
+    pub max_attempts: u32,
+    /// 送信に失敗してから再送するまでの待ち時間。再送のたびに倍にする
+    pub retry_delay: Duration,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            locale: MailLocale::default(),
+            max_attempts: 5,
+            retry_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl MailConfig {
+    fn from_env() -> Result<Self> {
+        let default = Self::default();
+        let locale = match std::env::var("MAIL_LOCALE") {
+            Ok(v) => v
+                .parse()
+                .map_err(|_| anyhow::anyhow!("MAIL_LOCALE must be one of ja or en"))?,
+            Err(_) => default.locale,
+        };
+        let max_attempts = match std::env::var("MAIL_MAX_ATTEMPTS") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.max_attempts,
+        };
+        let retry_delay = match std::env::var("MAIL_RETRY_DELAY") {
+            Ok(v) => Duration::from_secs(v.parse()?),
+            Err(_) => default.retry_delay,
+        };
+        anyhow::ensure!(max_attempts > 0, "MAIL_MAX_ATTEMPTS must be greater than 0");
+        Ok(Self {
+            locale,
+            max_attempts,
+            retry_delay,
+        })
+    }
+}
+
+/// 返却期限のリマインダーを送るジョブの設定
+#[derive(Debug, Clone)]
+pub struct ReminderConfig {
+    pub enabled: bool,
+    /// 貸出を確認する間隔(秒)
+    pub interval: u64,
+    /// 返却期限の何時間前からリマインダーを送るか
+    pub due_soon_hours: i64,
+}
+
+impl Default for ReminderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: 3600,
+            due_soon_hours: 48,
+        }
+    }
+}
+
+impl ReminderConfig {
+    fn from_env() -> Result<Self> {
+        let default = Self::default();
+        let enabled = match std::env::var("REMINDER_ENABLED") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.enabled,
+        };
+        let interval = match std::env::var("REMINDER_INTERVAL") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.interval,
+        };
+        let due_soon_hours = match std::env::var("REMINDER_DUE_SOON_HOURS") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.due_soon_hours,
+        };
+        anyhow::ensure!(interval > 0, "REMINDER_INTERVAL must be greater than 0");
+        anyhow::ensure!(
+            due_soon_hours >= 0,
+            "REMINDER_DUE_SOON_HOURS must not be negative"
+        );
+        Ok(Self {
+            enabled,
+            interval,
+            due_soon_hours,
+        })
+    }
+}
+
+/// Webhookの配信を送信するジョブの設定
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub enabled: bool,
+    /// 送信待ちの配信を確認する間隔(秒)
+    pub interval: u64,
+    /// 1件の配信を送信する回数の上限(初回を含む)
+    pub max_attempts: u32,
+    /// 送信に失敗してから再送するまでの待ち時間。再送のたびに倍にする
+    pub retry_delay: Duration,
+    /// 配信先の応答を待つ時間
+    pub timeout: Duration,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: 5,
+            max_attempts: 8,
+            retry_delay: Duration::from_secs(30),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl WebhookConfig {
+    fn from_env() -> Result<Self> {
+        let default = Self::default();
+        let enabled = match std::env::var("WEBHOOK_ENABLED") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.enabled,
+        };
+        let interval = match std::env::var("WEBHOOK_INTERVAL") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.interval,
+        };
+        let max_attempts = match std::env::var("WEBHOOK_MAX_ATTEMPTS") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.max_attempts,
+        };
+        let retry_delay = match std::env::var("WEBHOOK_RETRY_DELAY") {
+            Ok(v) => Duration::from_secs(v.parse()?),
+            Err(_) => default.retry_delay,
+        };
+        let timeout = match std::env::var("WEBHOOK_TIMEOUT") {
+            Ok(v) => Duration::from_secs(v.parse()?),
+            Err(_) => default.timeout,
+        };
+        anyhow::ensure!(interval > 0, "WEBHOOK_INTERVAL must be greater than 0");
+        anyhow::ensure!(
+            max_attempts > 0,
+            "WEBHOOK_MAX_ATTEMPTS must be greater than 0"
+        );
+        anyhow::ensure!(!timeout.is_zero(), "WEBHOOK_TIMEOUT must be greater than 0");
+        Ok(Self {
+            enabled,
+            interval,
+            max_attempts,
+            retry_delay,
+            timeout,
+        })
+    }
+}
+
+/// ドメインイベントをコンシューマーに届けるジョブの設定
+#[derive(Debug, Clone)]
+pub struct OutboxConfig {
+    pub enabled: bool,
+    /// 未処理のイベントを確認する間隔(秒)
+    pub interval: u64,
+    /// 配信に失敗してから配信し直すまでの待ち時間。失敗するたびに倍にする
+    pub retry_delay: Duration,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: 1,
+            retry_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl OutboxConfig {
+    fn from_env() -> Result<Self> {
+        let default = Self::default();
+        let enabled = match std::env::var("OUTBOX_ENABLED") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.enabled,
+        };
+        let interval = match std::env::var("OUTBOX_INTERVAL") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.interval,
+        };
+        let retry_delay = match std::env::var("OUTBOX_RETRY_DELAY") {
+            Ok(v) => Duration::from_secs(v.parse()?),
+            Err(_) => default.retry_delay,
+        };
+        anyhow::ensure!(interval > 0, "OUTBOX_INTERVAL must be greater than 0");
+        Ok(Self {
+            enabled,
+            interval,
+            retry_delay,
+        })
+    }
+}
+
+/// 蔵書と貸出の変更をリアルタイムに配信する設定
+#[derive(Debug, Clone)]
+pub struct LiveEventConfig {
+    /// 接続し直したクライアントに再送するため、保持しておくイベントの数
+    pub retention: usize,
+    /// 1つのインスタンスで、配信を待つイベントを溜めておける数
+    /// 受け取りが追いつかずにこれを超えた接続は切断される
+    pub buffer: usize,
+    /// 接続を維持するため、イベントがない間に送るハートビートの間隔
+    pub heartbeat_interval: Duration,
+}
+
+impl Default for LiveEventConfig {
+    fn default() -> Self {
+        Self {
+            retention: 1000,
+            buffer: 256,
+            heartbeat_interval: Duration::from_secs(15),
+        }
+    }
+}
+
+impl LiveEventConfig {
+    fn from_env() -> Result<Self> {
+        let default = Self::default();
+        let retention = match std::env::var("LIVE_EVENT_RETENTION") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.retention,
+        };
+        let buffer = match std::env::var("LIVE_EVENT_BUFFER") {
+            Ok(v) => v.parse()?,
+            Err(_) => default.buffer,
+        };
+        let heartbeat_interval = match std::env::var("LIVE_EVENT_HEARTBEAT_INTERVAL") {
+            Ok(v) => Duration::from_secs(v.parse()?),
+            Err(_) => default.heartbeat_interval,
+        };
+        anyhow::ensure!(retention > 0, "LIVE_EVENT_RETENTION must be greater than 0");
+        anyhow::ensure!(buffer > 0, "LIVE_EVENT_BUFFER must be greater than 0");
+        anyhow::ensure!(
+            !heartbeat_interval.is_zero(),
+            "LIVE_EVENT_HEARTBEAT_INTERVAL must be greater than 0"
+        );
+        Ok(Self {
+            retention,
+            buffer,
+            heartbeat_interval,
+        })
+    }
+}
+
+/// リバースプロキシの設定
+#[derive(Debug, Clone, Default)]
+pub struct ProxyConfig {
+    /// `X-Forwarded-For`ヘッダーを付与するリバースプロキシのアドレス(CIDR表記も可)
+    /// 直接の接続元がこれらに含まれる場合にのみ、ヘッダーに記録された接続元を採用する
+    pub trusted_proxies: Vec<IpNet>,
+}
+
+impl ProxyConfig {
+    fn from_env() -> Result<Self> {
+        let trusted_proxies = std::env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                v.parse::<IpNet>()
+                    .or_else(|_| v.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| {
+                        anyhow::anyhow!("TRUSTED_PROXIES contains an invalid address: {v}")
+                    })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { trusted_proxies })
+    }
+
+    pub fn is_trusted(&self, addr: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|net| net.contains(&addr))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,6 +656,22 @@ mod tests {
         // Assert auth config
         assert_eq!(config.auth.ttl, 3600);
 
+        // OIDC is disabled unless OIDC_ISSUER_URL is set
+        assert!(config.oidc.is_none());
+        // LDAP is disabled unless LDAP_URL is set
+        assert!(config.ldap.is_none());
+        // Labels encode only the book ID unless a link template is set
+        assert!(config.label.link_template.is_none());
+        // Kiosks fall back to the default rate limit
+        assert_eq!(config.kiosk.rate_limit, 30);
+        assert_eq!(config.kiosk.rate_limit_window, 60);
+        // Notifications are logged unless SMTP_HOST is set
+        assert!(config.smtp.is_none());
+        assert_eq!(config.mail.locale, MailLocale::Ja);
+        assert!(config.reminder.enabled);
+        // X-Forwarded-For is ignored unless TRUSTED_PROXIES is set
+        assert!(config.proxy.trusted_proxies.is_empty());
+
         // Clean up the environment variables
         std::env::remove_var("DATABASE_HOST");
         std::env::remove_var("DATABASE_PORT");
@@ -102,6 +683,337 @@ mod tests {
         std::env::remove_var("AUTH_TOKEN_TTL");
     }
 
+    #[test]
+    fn test_proxy_config_from_env() {
+        let _lock = lock_env();
+
+        std::env::set_var("TRUSTED_PROXIES", "10.0.0.0/8, 192.0.2.1 ::1");
+        let config = ProxyConfig::from_env().expect("Failed to create ProxyConfig");
+        assert!(config.is_trusted("10.1.2.3".parse().unwrap()));
+        assert!(config.is_trusted("192.0.2.1".parse().unwrap()));
+        assert!(config.is_trusted("::1".parse().unwrap()));
+        assert!(!config.is_trusted("192.0.2.2".parse().unwrap()));
+
+        std::env::set_var("TRUSTED_PROXIES", "not-an-address");
+        assert!(ProxyConfig::from_env().is_err());
+
+        std::env::remove_var("TRUSTED_PROXIES");
+    }
+
+    #[test]
+    fn test_oidc_config_from_env() {
+        let _lock = lock_env();
+
+        std::env::set_var("OIDC_ISSUER_URL", "https://idp.example.com");
+        std::env::set_var("OIDC_CLIENT_ID", "bookmanager");
+        std::env::set_var("OIDC_REDIRECT_URL", "http://localhost:3000/login/callback");
+        std::env::set_var("OIDC_ROLE_CLAIM", "groups");
+        std::env::set_var("OIDC_ADMIN_ROLE_VALUES", "library-admins, it-admins");
+        std::env::remove_var("OIDC_CLIENT_SECRET");
+        std::env::remove_var("OIDC_SCOPES");
+
+        let config = OidcConfig::from_env()
+            .expect("Failed to create OidcConfig")
+            .expect("OIDC should be enabled");
+        assert_eq!(config.issuer_url, "https://idp.example.com");
+        assert_eq!(config.client_id, "bookmanager");
+        assert!(config.client_secret.is_none());
+        assert_eq!(config.scopes, vec!["openid", "email", "profile"]);
+        assert_eq!(config.role_claim.as_deref(), Some("groups"));
+        assert_eq!(
+            config.admin_role_values,
+            vec!["library-admins", "it-admins"]
+        );
+
+        // Missing required OIDC variables are reported as errors
+        std::env::remove_var("OIDC_CLIENT_ID");
+        assert!(OidcConfig::from_env().is_err());
+
+        std::env::remove_var("OIDC_ISSUER_URL");
+        std::env::remove_var("OIDC_REDIRECT_URL");
+        std::env::remove_var("OIDC_ROLE_CLAIM");
+        std::env::remove_var("OIDC_ADMIN_ROLE_VALUES");
+        assert!(OidcConfig::from_env().expect("should not fail").is_none());
+    }
+
+    #[test]
+    fn test_ldap_config_from_env() {
+        let _lock = lock_env();
+
+        std::env::set_var("LDAP_URL", "ldap://localhost:389");
+        std::env::set_var("LDAP_USER_BASE_DN", "ou=people,dc=example,dc=com");
+        std::env::set_var(
+            "LDAP_ADMIN_GROUPS",
+            "cn=admins,ou=groups,dc=example,dc=com; cn=it,ou=groups,dc=example,dc=com",
+        );
+        std::env::remove_var("LDAP_BIND_DN");
+        std::env::remove_var("LDAP_USER_FILTER");
+
+        let config = LdapConfig::from_env()
+            .expect("Failed to create LdapConfig")
+            .expect("LDAP should be enabled");
+        assert_eq!(config.url, "ldap://localhost:389");
+        assert!(config.bind_dn.is_none());
+        assert_eq!(
+            config.user_filter,
+            "(&(objectClass=inetOrgPerson)(mail={email}))"
+        );
+        assert_eq!(config.name_attribute, "cn");
+        assert_eq!(
+            config.admin_groups,
+            vec![
+                "cn=admins,ou=groups,dc=example,dc=com",
+                "cn=it,ou=groups,dc=example,dc=com"
+            ]
+        );
+
+        // Missing required LDAP variables are reported as errors
+        std::env::remove_var("LDAP_USER_BASE_DN");
+        assert!(LdapConfig::from_env().is_err());
+
+        std::env::remove_var("LDAP_URL");
+        std::env::remove_var("LDAP_ADMIN_GROUPS");
+        assert!(LdapConfig::from_env().expect("should not fail").is_none());
+    }
+
+    #[test]
+    fn test_label_config_from_env() {
+        let _lock = lock_env();
+
+        std::env::set_var(
+            "BOOK_LABEL_LINK_TEMPLATE",
+            "https://library.example.com/books/{book_id}",
+        );
+        let config = LabelConfig::from_env().expect("Failed to create LabelConfig");
+        assert_eq!(
+            config.link_template.as_deref(),
+            Some("https://library.example.com/books/{book_id}")
+        );
+
+        // A template without the placeholder is reported as an error
+        std::env::set_var("BOOK_LABEL_LINK_TEMPLATE", "https://library.example.com/");
+        assert!(LabelConfig::from_env().is_err());
+
+        std::env::remove_var("BOOK_LABEL_LINK_TEMPLATE");
+        assert!(LabelConfig::from_env()
+            .expect("should not fail")
+            .link_template
+            .is_none());
+    }
+
+    #[test]
+    fn test_kiosk_config_from_env() {
+        let _lock = lock_env();
+
+        std::env::set_var("KIOSK_RATE_LIMIT", "10");
+        std::env::set_var("KIOSK_RATE_LIMIT_WINDOW", "30");
+        let config = KioskConfig::from_env().expect("Failed to create KioskConfig");
+        assert_eq!(config.rate_limit, 10);
+        assert_eq!(config.rate_limit_window, 30);
+
+        // An empty window is reported as an error
+        std::env::set_var("KIOSK_RATE_LIMIT_WINDOW", "0");
+        assert!(KioskConfig::from_env().is_err());
+
+        std::env::set_var("KIOSK_RATE_LIMIT", "many");
+        std::env::remove_var("KIOSK_RATE_LIMIT_WINDOW");
+        assert!(KioskConfig::from_env().is_err());
+
+        std::env::remove_var("KIOSK_RATE_LIMIT");
+    }
+
+    #[test]
+    fn test_smtp_config_from_env() {
+        let _lock = lock_env();
+
+        std::env::set_var("SMTP_HOST", "smtp.example.com");
+        std::env::set_var("SMTP_FROM", "library@example.com");
+        std::env::set_var("SMTP_USERNAME", "library");
+        std::env::set_var("SMTP_PASSWORD", "secret");
+        std::env::remove_var("SMTP_PORT");
+        std::env::remove_var("SMTP_TLS");
+        let config = SmtpConfig::from_env()
+            .expect("Failed to create SmtpConfig")
+            .expect("SMTP should be enabled");
+        assert_eq!(config.host, "smtp.example.com");
+        assert_eq!(config.from, "library@example.com");
+        // STARTTLS is required by default
+        assert_eq!(config.tls, SmtpTls::StartTls);
+        assert_eq!(config.port, 587);
+        let credentials = config.credentials.expect("credentials should be set");
+        assert_eq!(credentials.username, "library");
+        assert_eq!(credentials.password, "secret");
+        // The password is never printed
+        assert!(!format!("{credentials:?}").contains("secret"));
+
+        // The default port follows the TLS mode
+        std::env::set_var("SMTP_TLS", "tls");
+        let config = SmtpConfig::from_env().unwrap().unwrap();
+        assert_eq!((config.tls, config.port), (SmtpTls::Tls, 465));
+        std::env::set_var("SMTP_TLS", "none");
+        std::env::set_var("SMTP_PORT", "1025");
+        let config = SmtpConfig::from_env().unwrap().unwrap();
+        assert_eq!((config.tls, config.port), (SmtpTls::None, 1025));
+
+        std::env::set_var("SMTP_TLS", "ssl");
+        assert!(SmtpConfig::from_env().is_err());
+        std::env::remove_var("SMTP_TLS");
+
+        // A username without a password is reported as an error
+        std::env::remove_var("SMTP_PASSWORD");
+        assert!(SmtpConfig::from_env().is_err());
+        std::env::remove_var("SMTP_USERNAME");
+        assert!(SmtpConfig::from_env()
+            .unwrap()
+            .unwrap()
+            .credentials
+            .is_none());
+
+        // Missing sender address is reported as an error
+        std::env::remove_var("SMTP_FROM");
+        assert!(SmtpConfig::from_env().is_err());
+
+        std::env::remove_var("SMTP_HOST");
+        std::env::remove_var("SMTP_PORT");
+        assert!(SmtpConfig::from_env().expect("should not fail").is_none());
+    }
+
+    #[test]
+    fn test_mail_config_from_env() {
+        let _lock = lock_env();
+
+        std::env::set_var("MAIL_LOCALE", "en");
+        std::env::set_var("MAIL_MAX_ATTEMPTS", "3");
+        std::env::set_var("MAIL_RETRY_DELAY", "10");
+        let config = MailConfig::from_env().expect("Failed to create MailConfig");
+        assert_eq!(config.locale, MailLocale::En);
+        assert_eq!(config.max_attempts, 3);
+        assert_eq!(config.retry_delay, Duration::from_secs(10));
+
+        std::env::set_var("MAIL_LOCALE", "fr");
+        assert!(MailConfig::from_env().is_err());
+        std::env::remove_var("MAIL_LOCALE");
+
+        // At least one attempt is required
+        std::env::set_var("MAIL_MAX_ATTEMPTS", "0");
+        assert!(MailConfig::from_env().is_err());
+
+        std::env::remove_var("MAIL_MAX_ATTEMPTS");
+        std::env::remove_var("MAIL_RETRY_DELAY");
+        let config = MailConfig::from_env().expect("should not fail");
+        assert_eq!(config.locale, MailLocale::Ja);
+        assert_eq!(config.max_attempts, 5);
+    }
+
+    #[test]
+    fn test_reminder_config_from_env() {
+        let _lock = lock_env();
+
+        std::env::set_var("REMINDER_ENABLED", "false");
+        std::env::set_var("REMINDER_INTERVAL", "600");
+        std::env::set_var("REMINDER_DUE_SOON_HOURS", "24");
+        let config = ReminderConfig::from_env().expect("Failed to create ReminderConfig");
+        assert!(!config.enabled);
+        assert_eq!(config.interval, 600);
+        assert_eq!(config.due_soon_hours, 24);
+
+        // An empty interval is reported as an error
+        std::env::set_var("REMINDER_INTERVAL", "0");
+        assert!(ReminderConfig::from_env().is_err());
+
+        std::env::remove_var("REMINDER_ENABLED");
+        std::env::remove_var("REMINDER_INTERVAL");
+        std::env::remove_var("REMINDER_DUE_SOON_HOURS");
+        let config = ReminderConfig::from_env().expect("should not fail");
+        assert!(config.enabled);
+        assert_eq!(config.interval, 3600);
+    }
+
+    #[test]
+    fn test_webhook_config_from_env() {
+        let _lock = lock_env();
+
+        std::env::set_var("WEBHOOK_ENABLED", "false");
+        std::env::set_var("WEBHOOK_INTERVAL", "1");
+        std::env::set_var("WEBHOOK_MAX_ATTEMPTS", "3");
+        std::env::set_var("WEBHOOK_RETRY_DELAY", "60");
+        std::env::set_var("WEBHOOK_TIMEOUT", "5");
+        let config = WebhookConfig::from_env().expect("Failed to create WebhookConfig");
+        assert!(!config.enabled);
+        assert_eq!(config.interval, 1);
+        assert_eq!(config.max_attempts, 3);
+        assert_eq!(config.retry_delay, Duration::from_secs(60));
+        assert_eq!(config.timeout, Duration::from_secs(5));
+
+        // A zero timeout would make every delivery fail
+        std::env::set_var("WEBHOOK_TIMEOUT", "0");
+        assert!(WebhookConfig::from_env().is_err());
+
+        for key in [
+            "WEBHOOK_ENABLED",
+            "WEBHOOK_INTERVAL",
+            "WEBHOOK_MAX_ATTEMPTS",
+            "WEBHOOK_RETRY_DELAY",
+            "WEBHOOK_TIMEOUT",
+        ] {
+            std::env::remove_var(key);
+        }
+        let config = WebhookConfig::from_env().expect("should not fail");
+        assert!(config.enabled);
+        assert_eq!(config.max_attempts, 8);
+        assert_eq!(config.timeout, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_outbox_config_from_env() {
+        let _lock = lock_env();
+
+        std::env::set_var("OUTBOX_ENABLED", "false");
+        std::env::set_var("OUTBOX_INTERVAL", "5");
+        std::env::set_var("OUTBOX_RETRY_DELAY", "60");
+        let config = OutboxConfig::from_env().expect("Failed to create OutboxConfig");
+        assert!(!config.enabled);
+        assert_eq!(config.interval, 5);
+        assert_eq!(config.retry_delay, Duration::from_secs(60));
+
+        std::env::set_var("OUTBOX_INTERVAL", "0");
+        assert!(OutboxConfig::from_env().is_err());
+
+        for key in ["OUTBOX_ENABLED", "OUTBOX_INTERVAL", "OUTBOX_RETRY_DELAY"] {
+            std::env::remove_var(key);
+        }
+        let config = OutboxConfig::from_env().expect("should not fail");
+        assert!(config.enabled);
+        assert_eq!(config.interval, 1);
+    }
+
+    #[test]
+    fn test_live_event_config_from_env() {
+        let _lock = lock_env();
+
+        std::env::set_var("LIVE_EVENT_RETENTION", "50");
+        std::env::set_var("LIVE_EVENT_BUFFER", "16");
+        std::env::set_var("LIVE_EVENT_HEARTBEAT_INTERVAL", "30");
+        let config = LiveEventConfig::from_env().expect("Failed to create LiveEventConfig");
+        assert_eq!(config.retention, 50);
+        assert_eq!(config.buffer, 16);
+        assert_eq!(config.heartbeat_interval, Duration::from_secs(30));
+
+        std::env::set_var("LIVE_EVENT_BUFFER", "0");
+        assert!(LiveEventConfig::from_env().is_err());
+
+        for key in [
+            "LIVE_EVENT_RETENTION",
+            "LIVE_EVENT_BUFFER",
+            "LIVE_EVENT_HEARTBEAT_INTERVAL",
+        ] {
+            std::env::remove_var(key);
+        }
+        let config = LiveEventConfig::from_env().expect("should not fail");
+        assert_eq!(config.retention, 1000);
+        assert_eq!(config.heartbeat_interval, Duration::from_secs(15));
+    }
+
     #[test]
     fn test_app_config_new_missing_env() {
         let _lock = lock_env();
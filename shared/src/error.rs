@@ -1,4 +1,7 @@
-use axum::{http::StatusCode, response::IntoResponse};
+use axum::{
+    http::{header::WWW_AUTHENTICATE, StatusCode},
+    response::IntoResponse,
+};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -7,6 +10,9 @@ pub enum AppError {
     UnprocessableEntity(String),
     #[error("{0}")]
     NotFoundError(String),
+    /// 現在のリソースの状態と競合するため、操作を行えない(409)
+    #[error("{0}")]
+    ConflictError(String),
     #[error("{0}")]
     ValidationError(#[from] garde::Report),
     #[error("トランザクションを実行できませんでした。")]
@@ -21,12 +27,21 @@ pub enum AppError {
     BcryptError(#[from] bcrypt::BcryptError),
     #[error("{0}")]
     ConvertToUuidError(#[from] uuid::Error),
-    #[error("ログインに失敗しました。")]
+    /// 資格情報が送られていない、またはログイン時の資格情報が誤っている(401)
+    #[error("認証に失敗しました。")]
     UnauthenticatedError,
-    #[error("認可に失敗しました。")]
-    UnauthorizedError,
+    /// 送られたアクセストークン・APIキーが無効、または有効期限が切れている(401)
+    #[error("認証情報が無効、または有効期限が切れています。")]
+    InvalidTokenError,
+    /// 認証済みだが、操作を行う権限がない(403)
     #[error("許可されていない操作です。")]
     ForbiddenError,
+    /// 一定時間あたりのリクエスト数の上限を超えた(429)
+    #[error("リクエストが多すぎます。しばらく待ってから再度お試しください。")]
+    TooManyRequestsError,
+    /// 同時に行われた他の操作との競合が、再試行しても解消しなかった(503)
+    #[error("他の操作と競合しました。しばらく待ってから再度お試しください。")]
+    TransactionConflictError,
     #[error("{0}")]
     ConversionEntityError(String),
     #[error("{0}")]
@@ -34,14 +49,27 @@ pub enum AppError {
 }
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
+        // 401を返す場合は、RFC 6750 に従って認証方式をチャレンジとして示す
+        let challenge = match self {
+            AppError::UnauthenticatedError => Some(r#"Bearer realm="bookmanager""#),
+            AppError::InvalidTokenError => Some(
+                r#"Bearer realm="bookmanager", error="invalid_token", error_description="The access token is invalid or expired""#,
+            ),
+            _ => None,
+        };
         let status_code = match self {
             AppError::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::NotFoundError(_) => StatusCode::NOT_FOUND,
+            AppError::ConflictError(_) => StatusCode::CONFLICT,
             AppError::ValidationError(_) | AppError::ConvertToUuidError(_) => {
                 StatusCode::BAD_REQUEST
             }
-            AppError::UnauthenticatedError | AppError::ForbiddenError => StatusCode::FORBIDDEN,
-            AppError::UnauthorizedError => StatusCode::UNAUTHORIZED,
+            AppError::UnauthenticatedError | AppError::InvalidTokenError => {
+                StatusCode::UNAUTHORIZED
+            }
+            AppError::ForbiddenError => StatusCode::FORBIDDEN,
+            AppError::TooManyRequestsError => StatusCode::TOO_MANY_REQUESTS,
+            AppError::TransactionConflictError => StatusCode::SERVICE_UNAVAILABLE,
             e @ (AppError::TransactionError(_)
             | AppError::DatabaseOperationError(_)
             | AppError::NoRowsAffectedError(_)
@@ -58,7 +86,10 @@ impl IntoResponse for AppError {
             }
         };
 
-        status_code.into_response()
+        match challenge {
+            Some(challenge) => (status_code, [(WWW_AUTHENTICATE, challenge)]).into_response(),
+            None => status_code.into_response(),
+        }
     }
 }
 
@@ -82,6 +113,9 @@ mod tests {
         let err = AppError::NotFoundError("test".to_string());
         assert_eq!(err.into_response().status(), StatusCode::NOT_FOUND);
 
+        let err = AppError::ConflictError("test".to_string());
+        assert_eq!(err.into_response().status(), StatusCode::CONFLICT);
+
         let mut report = garde::Report::new();
         report.append(garde::Path::empty(), garde::Error::new("test error"));
         let err = AppError::ValidationError(report);
@@ -92,13 +126,22 @@ mod tests {
         assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
 
         let err = AppError::UnauthenticatedError;
-        assert_eq!(err.into_response().status(), StatusCode::FORBIDDEN);
+        assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
+
+        let err = AppError::InvalidTokenError;
+        assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
 
         let err = AppError::ForbiddenError;
         assert_eq!(err.into_response().status(), StatusCode::FORBIDDEN);
 
-        let err = AppError::UnauthorizedError;
-        assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
+        let err = AppError::TooManyRequestsError;
+        assert_eq!(err.into_response().status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let err = AppError::TransactionConflictError;
+        assert_eq!(
+            err.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
 
         let err = AppError::TransactionError(sqlx::Error::RowNotFound);
         assert_eq!(
@@ -143,4 +186,24 @@ mod tests {
             StatusCode::INTERNAL_SERVER_ERROR
         );
     }
+
+    #[test]
+    fn test_www_authenticate_challenge() {
+        let challenge = |err: AppError| {
+            err.into_response()
+                .headers()
+                .get(WWW_AUTHENTICATE)
+                .map(|v| v.to_str().unwrap().to_string())
+        };
+
+        assert_eq!(
+            challenge(AppError::UnauthenticatedError).as_deref(),
+            Some(r#"Bearer realm="bookmanager""#)
+        );
+        assert!(challenge(AppError::InvalidTokenError)
+            .is_some_and(|v| v.starts_with("Bearer ") && v.contains(r#"error="invalid_token""#)));
+        // 権限不足の場合はチャレンジを返さない
+        assert!(challenge(AppError::ForbiddenError).is_none());
+        assert!(challenge(AppError::NotFoundError("test".into())).is_none());
+    }
 }
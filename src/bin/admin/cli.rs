@@ -0,0 +1,273 @@
+use std::path::PathBuf;
+
+use api::{import::CsvEncoding, model::backup::RestoreModeName, model::export::ExportFormat};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use kernel::model::role::Role;
+
+/// 蔵書管理アプリケーションの管理用コマンド
+///
+/// 接続先のデータベース・Redisは、アプリケーションと同じ環境変数から読み込む
+#[derive(Debug, Parser)]
+#[command(name = "bookmanager-admin", version)]
+pub struct Cli {
+    /// 結果をJSONで出力する
+    #[arg(long, global = true)]
+    pub json: bool,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// ユーザーの作成・管理
+    #[command(subcommand)]
+    User(UserCommand),
+    /// ログイン中のセッションの管理
+    #[command(subcommand)]
+    Session(SessionCommand),
+    /// 貸出の確認
+    #[command(subcommand)]
+    Loan(LoanCommand),
+    /// データベースのマイグレーション
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+    /// 蔵書・貸出履歴のエクスポート
+    #[command(subcommand)]
+    Export(ExportCommand),
+    /// 蔵書のCSVの取り込み
+    #[command(subcommand)]
+    Import(ImportCommand),
+    /// ユーザー・蔵書・貸出のデータ全体のバックアップ
+    Backup(BackupArgs),
+    /// バックアップの復元
+    Restore(RestoreArgs),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum UserCommand {
+    /// ユーザーの一覧を表示する
+    List,
+    /// ユーザーを作成する
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        email: String,
+        #[command(flatten)]
+        password: PasswordArgs,
+        #[arg(long, value_enum, default_value_t = RoleArg::User)]
+        role: RoleArg,
+    },
+    /// ユーザーのロールを変更する
+    Promote {
+        /// ユーザーのIDまたはメールアドレス
+        user: String,
+        #[arg(long, value_enum, default_value_t = RoleArg::Admin)]
+        role: RoleArg,
+    },
+    /// 現在のパスワードを確認せずに、パスワードを設定し直す
+    ResetPassword {
+        /// ユーザーのIDまたはメールアドレス
+        user: String,
+        #[command(flatten)]
+        password: PasswordArgs,
+    },
+}
+
+/// パスワードの指定方法
+/// コマンドラインの引数はプロセスの一覧から見えるため、`--password-stdin`を推奨する
+#[derive(Debug, Args)]
+#[group(required = true, multiple = false)]
+pub struct PasswordArgs {
+    #[arg(long)]
+    pub password: Option<String>,
+    /// 標準入力の1行目をパスワードとして読み込む
+    #[arg(long)]
+    pub password_stdin: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SessionCommand {
+    /// ユーザーのアクセストークンをすべて削除し、ログアウトさせる
+    Revoke {
+        /// ユーザーのIDまたはメールアドレス
+        user: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum LoanCommand {
+    /// 返却期限を過ぎた未返却の貸出を、返却期限の早い順に表示する
+    Overdue,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateCommand {
+    /// 未適用のマイグレーションを適用する
+    Run,
+    /// 未適用のマイグレーションを表示する
+    Status,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ExportCommand {
+    /// 蔵書をエクスポートする
+    Books {
+        #[arg(long, value_enum, default_value_t = ExportFormatArg::Csv)]
+        format: ExportFormatArg,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// 貸出履歴をエクスポートする
+    Checkouts {
+        #[arg(long, value_enum, default_value_t = ExportFormatArg::Csv)]
+        format: ExportFormatArg,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ImportCommand {
+    /// CSVから蔵書を一括登録する
+    Books {
+        /// 取り込むCSVのファイル
+        file: PathBuf,
+        /// 蔵書の所有者とするユーザーのIDまたはメールアドレス
+        #[arg(long)]
+        owner: String,
+        #[arg(long, value_enum, default_value_t = EncodingArg::Utf8)]
+        encoding: EncodingArg,
+        /// 検証のみを行い、登録しない
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct BackupArgs {
+    /// パスワードのハッシュを含める
+    #[arg(long)]
+    pub include_password_hashes: bool,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct RestoreArgs {
+    /// `backup`で出力したファイル
+    pub file: PathBuf,
+    #[arg(long, value_enum, default_value_t = RestoreModeArg::Merge)]
+    pub mode: RestoreModeArg,
+}
+
+#[derive(Debug, Args)]
+pub struct OutputArgs {
+    /// 出力先のファイル。省略した場合は標準出力に書き出す
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum RoleArg {
+    Admin,
+    User,
+}
+impl From<RoleArg> for Role {
+    fn from(value: RoleArg) -> Self {
+        match value {
+            RoleArg::Admin => Self::Admin,
+            RoleArg::User => Self::User,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormatArg {
+    Csv,
+    Ndjson,
+    Marc,
+    Marcxml,
+}
+impl From<ExportFormatArg> for ExportFormat {
+    fn from(value: ExportFormatArg) -> Self {
+        match value {
+            ExportFormatArg::Csv => Self::Csv,
+            ExportFormatArg::Ndjson => Self::Ndjson,
+            ExportFormatArg::Marc => Self::Marc,
+            ExportFormatArg::Marcxml => Self::Marcxml,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum EncodingArg {
+    #[value(name = "utf-8")]
+    Utf8,
+    #[value(name = "shift_jis")]
+    ShiftJis,
+}
+impl From<EncodingArg> for CsvEncoding {
+    fn from(value: EncodingArg) -> Self {
+        match value {
+            EncodingArg::Utf8 => Self::Utf8,
+            EncodingArg::ShiftJis => Self::ShiftJis,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum RestoreModeArg {
+    Merge,
+    Replace,
+}
+impl From<RestoreModeArg> for RestoreModeName {
+    fn from(value: RestoreModeArg) -> Self {
+        match value {
+            RestoreModeArg::Merge => Self::Merge,
+            RestoreModeArg::Replace => Self::Replace,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_password_is_required_once() {
+        let parse = |args: &[&str]| Cli::try_parse_from([&["bookmanager-admin"], args].concat());
+
+        assert!(parse(&[
+            "user",
+            "reset-password",
+            "a@example.com",
+            "--password-stdin"
+        ])
+        .is_ok());
+        assert!(parse(&["user", "reset-password", "a@example.com"]).is_err());
+        assert!(parse(&[
+            "user",
+            "reset-password",
+            "a@example.com",
+            "--password",
+            "secret",
+            "--password-stdin",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_json_flag_is_global() -> anyhow::Result<()> {
+        let cli = Cli::try_parse_from(["bookmanager-admin", "loan", "overdue", "--json"])?;
+        assert!(cli.json);
+        assert!(matches!(cli.command, Command::Loan(LoanCommand::Overdue)));
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use std::pin::Pin;
+
+use adapter::repository::{
+    backup::BackupRepositoryImpl, book::BookRepositoryImpl, export::ExportRepositoryImpl,
+};
+use anyhow::{Context as _, Result};
+use api::{
+    export,
+    import::{self, ColumnMapping},
+    model::{
+        backup::{RestoreModeName, RestoreResponse},
+        book::BookImportResponse,
+    },
+};
+use axum::body::Bytes;
+use itertools::Itertools;
+use kernel::{
+    model::{
+        audit::{event::CreateAuditLog, AuditAction, AuditTargetType},
+        backup::{BackupArchive, BackupOptions},
+    },
+    repository::{backup::BackupRepository, book::BookRepository, export::ExportRepository},
+};
+use shared::error::AppResult;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio_stream::{Stream, StreamExt};
+
+use crate::{
+    cli::{BackupArgs, EncodingArg, ExportFormatArg, OutputArgs, RestoreArgs},
+    output::Output,
+    user::find_user,
+    Context,
+};
+
+/// 出力を1片ずつ書き出す
+/// 出力先のファイルを指定した場合は、書き出した内容の概要を結果とする
+async fn write_all(
+    output: OutputArgs,
+    chunks: impl Stream<Item = AppResult<Bytes>>,
+) -> Result<Output> {
+    let mut writer: Pin<Box<dyn AsyncWrite + Send>> = match &output.output {
+        Some(path) => Box::pin(
+            tokio::fs::File::create(path)
+                .await
+                .with_context(|| format!("{}を作成できません", path.display()))?,
+        ),
+        None => Box::pin(tokio::io::stdout()),
+    };
+    tokio::pin!(chunks);
+    let mut bytes = 0;
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk?;
+        writer.write_all(&chunk).await?;
+        bytes += chunk.len();
+    }
+    writer.flush().await?;
+
+    match output.output {
+        Some(path) => Output::report(
+            format!("{}に書き出しました({bytes}バイト)", path.display()),
+            serde_json::json!({ "path": path, "bytes": bytes }),
+        ),
+        None => Ok(Output::Written),
+    }
+}
+
+pub async fn export_books(
+    ctx: &Context,
+    format: ExportFormatArg,
+    output: OutputArgs,
+) -> Result<Output> {
+    let repo = ExportRepositoryImpl::new(ctx.db.clone());
+    write_all(output, export::books(format.into(), repo.stream_books())).await
+}
+
+pub async fn export_checkouts(
+    ctx: &Context,
+    format: ExportFormatArg,
+    output: OutputArgs,
+) -> Result<Output> {
+    let repo = ExportRepositoryImpl::new(ctx.db.clone());
+    let chunks = export::checkouts(format.into(), || repo.stream_checkouts())?;
+    write_all(output, chunks).await
+}
+
+pub async fn import_books(
+    ctx: &Context,
+    file: &std::path::Path,
+    owner: &str,
+    encoding: EncodingArg,
+    dry_run: bool,
+) -> Result<Output> {
+    let bytes = tokio::fs::read(file)
+        .await
+        .with_context(|| format!("{}を読み込めません", file.display()))?;
+    let owner = find_user(ctx, owner).await?;
+    let text = import::decode(&bytes, encoding.into())?;
+    let rows = import::parse_rows(&text, &ColumnMapping::default())?;
+
+    let books = rows
+        .iter()
+        .filter_map(|row| row.result.as_ref().ok())
+        .map(|req| req.clone().into())
+        .collect_vec();
+    let book_ids = if dry_run || books.is_empty() {
+        vec![]
+    } else {
+        let audit = CreateAuditLog {
+            after: Some(serde_json::json!({ "ownerId": owner.id })),
+            ..CreateAuditLog::new(None, AuditAction::ImportBooks, AuditTargetType::Book, None)
+        };
+        BookRepositoryImpl::new(ctx.db.clone())
+            .create_many(books, owner.id, Some(audit))
+            .await?
+    };
+
+    let response = BookImportResponse::new(dry_run, &rows, book_ids);
+    let summary = if dry_run {
+        format!(
+            "{}行中{}行が取り込めます(検証のみ)",
+            response.total_rows, response.valid_rows
+        )
+    } else {
+        format!(
+            "{}行中{}行を{}の蔵書として登録しました",
+            response.total_rows,
+            response.imported_book_ids.len(),
+            owner.email
+        )
+    };
+    let text = std::iter::once(summary)
+        .chain(response.errors.iter().map(|e| {
+            let column = e.column.as_deref().unwrap_or("-");
+            format!("{}行目\t{column}\t{}", e.line, e.message)
+        }))
+        .join("\n");
+    Output::report(text, response)
+}
+
+pub async fn backup(ctx: &Context, args: BackupArgs) -> Result<Output> {
+    let BackupArgs {
+        include_password_hashes,
+        output,
+    } = args;
+    let archive = BackupRepositoryImpl::new(ctx.db.clone())
+        .create(BackupOptions {
+            include_password_hashes,
+        })
+        .await?;
+
+    ctx.audit(CreateAuditLog {
+        after: Some(serde_json::json!({
+            "includePasswordHashes": include_password_hashes,
+        })),
+        ..CreateAuditLog::new(
+            None,
+            AuditAction::CreateBackup,
+            AuditTargetType::Backup,
+            None,
+        )
+    })
+    .await?;
+
+    let mut body = serde_json::to_vec(&archive)?;
+    body.push(b'\n');
+    write_all(output, tokio_stream::once(Ok(Bytes::from(body)))).await
+}
+
+pub async fn restore(ctx: &Context, args: RestoreArgs) -> Result<Output> {
+    let bytes = tokio::fs::read(&args.file)
+        .await
+        .with_context(|| format!("{}を読み込めません", args.file.display()))?;
+    let archive: BackupArchive = serde_json::from_slice(&bytes)
+        .with_context(|| format!("{}はバックアップとして読み取れません", args.file.display()))?;
+
+    let mode = RestoreModeName::from(args.mode);
+    let audit = CreateAuditLog::new(
+        None,
+        AuditAction::RestoreBackup,
+        AuditTargetType::Backup,
+        None,
+    );
+    let summary = BackupRepositoryImpl::new(ctx.db.clone())
+        .restore(archive, mode.into(), Some(audit))
+        .await?;
+    let response = RestoreResponse::new(mode, summary);
+
+    let text = [
+        ("ユーザー", &response.users),
+        ("蔵書", &response.books),
+        ("貸出", &response.checkouts),
+        ("返却済みの貸出", &response.returned_checkouts),
+    ]
+    .iter()
+    .map(|(name, count)| {
+        format!(
+            "{name}\t復元 {}件\t重複などにより除外 {}件",
+            count.restored, count.skipped
+        )
+    })
+    .join("\n");
+    Output::report(text, response)
+}
@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use adapter::repository::{checkout::CheckoutRepositoryImpl, user::UserRepositoryImpl};
+use anyhow::Result;
+use api::model::checkout::CheckoutResponse;
+use chrono::Utc;
+use itertools::Itertools;
+use kernel::repository::{checkout::CheckoutRepository, user::UserRepository};
+
+use crate::{output::Output, Context};
+
+pub async fn overdue(ctx: &Context) -> Result<Output> {
+    let now = Utc::now();
+    let checkouts = CheckoutRepositoryImpl::new(ctx.db.clone())
+        .find_unreturned_due_before(now)
+        .await?;
+    let emails: HashMap<_, _> = UserRepositoryImpl::new(ctx.db.clone())
+        .find_all()
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user.email))
+        .collect();
+
+    if checkouts.is_empty() {
+        return Output::report("返却期限を過ぎた貸出はありません", serde_json::json!([]));
+    }
+    let text = checkouts
+        .iter()
+        .map(|checkout| {
+            let borrower = emails
+                .get(&checkout.checked_out_by)
+                .cloned()
+                .unwrap_or_else(|| checkout.checked_out_by.to_string());
+            format!(
+                "{}\t{}日超過\t{}\t{borrower}",
+                checkout.due_at.format("%Y-%m-%d"),
+                (now - checkout.due_at).num_days(),
+                checkout.book.title,
+            )
+        })
+        .join("\n");
+    let checkouts = checkouts
+        .into_iter()
+        .map(CheckoutResponse::from)
+        .collect_vec();
+    Output::report(text, checkouts)
+}
@@ -0,0 +1,126 @@
+//! 蔵書管理アプリケーションの管理用コマンド
+//!
+//! 初期の管理者の作成や、パスワードの再設定などの運用作業を、
+//! SQLを直接実行せずにアプリケーションと同じリポジトリを通して行う
+
+use std::{process::ExitCode, sync::Arc};
+
+use adapter::{
+    database::{connect_database_with, ConnectionPool},
+    redis::RedisClient,
+    repository::audit::AuditLogRepositoryImpl,
+};
+use anyhow::Result;
+use clap::Parser;
+use kernel::{model::audit::event::CreateAuditLog, repository::audit::AuditLogRepository};
+use shared::config::AppConfig;
+use tracing_subscriber::EnvFilter;
+
+use crate::{
+    cli::{
+        Cli, Command, ExportCommand, ImportCommand, LoanCommand, MigrateCommand, SessionCommand,
+        UserCommand,
+    },
+    output::Output,
+};
+
+mod cli;
+mod data;
+mod loan;
+mod migrate;
+mod output;
+mod user;
+
+/// コマンドの実行に用いる設定と接続
+pub struct Context {
+    config: AppConfig,
+    db: ConnectionPool,
+}
+
+impl Context {
+    fn new() -> Result<Self> {
+        let config = AppConfig::new()?;
+        let db = connect_database_with(&config.database);
+        Ok(Self { config, db })
+    }
+
+    /// Redisのクライアント
+    /// Redisを使わないコマンドで接続先を求めないよう、必要になった時点で作成する
+    fn redis(&self) -> Result<Arc<RedisClient>> {
+        Ok(Arc::new(RedisClient::new(&self.config.redis)?))
+    }
+
+    /// データベースの変更を伴わない操作の監査ログを記録する
+    /// 変更を伴う操作では、リポジトリに渡して変更と同じトランザクションで記録する
+    /// いずれも操作を行ったユーザーは存在しないため、操作者は空とする
+    async fn audit(&self, event: CreateAuditLog) -> Result<()> {
+        AuditLogRepositoryImpl::new(self.db.clone())
+            .create(event)
+            .await?;
+        Ok(())
+    }
+}
+
+async fn run(command: Command) -> Result<Output> {
+    let ctx = Context::new()?;
+    match command {
+        Command::User(command) => match command {
+            UserCommand::List => user::list(&ctx).await,
+            UserCommand::Create {
+                name,
+                email,
+                password,
+                role,
+            } => user::create(&ctx, name, email, password, role).await,
+            UserCommand::Promote { user, role } => user::promote(&ctx, &user, role).await,
+            UserCommand::ResetPassword { user, password } => {
+                user::reset_password(&ctx, &user, password).await
+            }
+        },
+        Command::Session(SessionCommand::Revoke { user }) => {
+            user::revoke_sessions(&ctx, &user).await
+        }
+        Command::Loan(LoanCommand::Overdue) => loan::overdue(&ctx).await,
+        Command::Migrate(command) => match command {
+            MigrateCommand::Run => migrate::run(&ctx).await,
+            MigrateCommand::Status => migrate::status(&ctx).await,
+        },
+        Command::Export(command) => match command {
+            ExportCommand::Books { format, output } => {
+                data::export_books(&ctx, format, output).await
+            }
+            ExportCommand::Checkouts { format, output } => {
+                data::export_checkouts(&ctx, format, output).await
+            }
+        },
+        Command::Import(ImportCommand::Books {
+            file,
+            owner,
+            encoding,
+            dry_run,
+        }) => data::import_books(&ctx, &file, &owner, encoding, dry_run).await,
+        Command::Backup(args) => data::backup(&ctx, args).await,
+        Command::Restore(args) => data::restore(&ctx, args).await,
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    // 標準出力はコマンドの結果に用いるため、ログは標準エラー出力に書き出す
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "warn".into()))
+        .init();
+
+    match run(cli.command).await {
+        Ok(output) => {
+            output.print(cli.json);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            output::print_error(&e, cli.json);
+            ExitCode::FAILURE
+        }
+    }
+}
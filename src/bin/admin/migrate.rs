@@ -0,0 +1,33 @@
+use adapter::database::migration::{pending_migrations, run_migrations};
+use anyhow::Result;
+use api::model::health::PendingMigrationResponse;
+use itertools::Itertools;
+use kernel::model::migration::PendingMigration;
+
+use crate::{output::Output, Context};
+
+fn report(summary: &str, migrations: Vec<PendingMigration>) -> Result<Output> {
+    let text = std::iter::once(format!("{summary}: {}件", migrations.len()))
+        .chain(
+            migrations
+                .iter()
+                .map(|m| format!("{}\t{}", m.version, m.description)),
+        )
+        .join("\n");
+    let value = migrations
+        .into_iter()
+        .map(PendingMigrationResponse::from)
+        .collect_vec();
+    Output::report(text, value)
+}
+
+pub async fn run(ctx: &Context) -> Result<Output> {
+    report("適用したマイグレーション", run_migrations(&ctx.db).await?)
+}
+
+pub async fn status(ctx: &Context) -> Result<Output> {
+    report(
+        "未適用のマイグレーション",
+        pending_migrations(&ctx.db).await?,
+    )
+}
@@ -0,0 +1,45 @@
+use std::io::Write;
+
+use anyhow::Result;
+use serde::Serialize;
+
+/// コマンドの結果
+pub enum Output {
+    /// 人が読むための文章と、`--json`を指定した場合に出力する値
+    Report {
+        text: String,
+        value: serde_json::Value,
+    },
+    /// 結果そのものを標準出力に書き出し済み(出力先を指定しないエクスポートなど)
+    Written,
+}
+
+impl Output {
+    pub fn report(text: impl Into<String>, value: impl Serialize) -> Result<Self> {
+        Ok(Self::Report {
+            text: text.into(),
+            value: serde_json::to_value(value)?,
+        })
+    }
+
+    /// 結果を標準出力に書き出す
+    /// `head`などに渡して出力先が先に閉じられた場合でも、エラーとしない
+    pub fn print(self, json: bool) {
+        let mut stdout = std::io::stdout().lock();
+        let _ = match self {
+            Self::Report { value, .. } if json => writeln!(stdout, "{value}"),
+            Self::Report { text, .. } => writeln!(stdout, "{text}"),
+            Self::Written => Ok(()),
+        };
+    }
+}
+
+/// エラーを標準エラー出力に書き出す
+/// `--json`を指定した場合は、スクリプトから扱えるよう`{"error": ...}`の形で書き出す
+pub fn print_error(error: &anyhow::Error, json: bool) {
+    if json {
+        eprintln!("{}", serde_json::json!({ "error": format!("{error:#}") }));
+    } else {
+        eprintln!("error: {error:#}");
+    }
+}
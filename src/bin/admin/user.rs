@@ -0,0 +1,182 @@
+use std::{io::BufRead, str::FromStr};
+
+use adapter::repository::{auth::AuthRepositoryImpl, user::UserRepositoryImpl};
+use anyhow::{ensure, Context as _, Result};
+use api::model::user::{CreateUserRequest, RoleName, UserResponse};
+use garde::Validate;
+use itertools::Itertools;
+use kernel::{
+    model::{
+        audit::{event::CreateAuditLog, AuditAction, AuditTargetType},
+        id::UserId,
+        role::Role,
+        user::{
+            event::{CreateUser, ResetUserPassword, UpdateUserRole},
+            User,
+        },
+    },
+    repository::{auth::AuthRepository, user::UserRepository},
+};
+
+use crate::{
+    cli::{PasswordArgs, RoleArg},
+    output::Output,
+    Context,
+};
+
+/// ユーザーのIDまたはメールアドレスから、ユーザーを取得する
+/// 無効化されたユーザーも対象とする
+pub async fn find_user(ctx: &Context, user: &str) -> Result<User> {
+    let users = UserRepositoryImpl::new(ctx.db.clone()).find_all().await?;
+    let found = match UserId::from_str(user) {
+        Ok(user_id) => users.into_iter().find(|u| u.id == user_id),
+        Err(_) => users
+            .into_iter()
+            .find(|u| u.email.eq_ignore_ascii_case(user)),
+    };
+    found.with_context(|| format!("ユーザー「{user}」が見つかりません"))
+}
+
+/// 引数または標準入力からパスワードを読み込む
+fn read_password(args: PasswordArgs) -> Result<String> {
+    let password = match args.password {
+        Some(password) => password,
+        None => {
+            let mut line = String::new();
+            std::io::stdin().lock().read_line(&mut line)?;
+            line.trim_end_matches(['\r', '\n']).to_string()
+        }
+    };
+    ensure!(!password.is_empty(), "パスワードが空です");
+    Ok(password)
+}
+
+fn describe(user: &User) -> String {
+    let role = user.role.as_ref();
+    let state = if user.is_active() { "" } else { " (無効)" };
+    format!("{}\t{}\t{}\t{role}{state}", user.id, user.email, user.name)
+}
+
+pub async fn list(ctx: &Context) -> Result<Output> {
+    let users = UserRepositoryImpl::new(ctx.db.clone()).find_all().await?;
+    let text = users.iter().map(describe).join("\n");
+    let users = users.into_iter().map(UserResponse::from).collect_vec();
+    Output::report(text, users)
+}
+
+pub async fn create(
+    ctx: &Context,
+    name: String,
+    email: String,
+    password: PasswordArgs,
+    role: RoleArg,
+) -> Result<Output> {
+    let req = CreateUserRequest::new(name, email, read_password(password)?);
+    req.validate(&())?;
+
+    let audit = CreateAuditLog {
+        after: Some(req.audit_snapshot()),
+        ..CreateAuditLog::new(None, AuditAction::CreateUser, AuditTargetType::User, None)
+    }
+    .with_after_field("role", serde_json::json!(RoleName::from(Role::from(role))));
+    // ロールの変更を別に行うと、失敗した場合に異なるロールのユーザーが残るため、作成時に指定する
+    let event = CreateUser {
+        role: role.into(),
+        ..req.into()
+    };
+    let user = UserRepositoryImpl::new(ctx.db.clone())
+        .create(event, Some(audit))
+        .await?;
+
+    let text = format!("ユーザーを作成しました\n{}", describe(&user));
+    Output::report(text, UserResponse::from(user))
+}
+
+pub async fn promote(ctx: &Context, user: &str, role: RoleArg) -> Result<Output> {
+    let user = find_user(ctx, user).await?;
+    let text = format!(
+        "{}のロールを{}から{}に変更しました",
+        user.email,
+        user.role.as_ref(),
+        Role::from(role).as_ref()
+    );
+    let user_id = user.id;
+    let after = serde_json::json!({ "role": RoleName::from(Role::from(role)) });
+    let audit = CreateAuditLog {
+        before: Some(serde_json::to_value(UserResponse::from(user))?),
+        after: Some(after.clone()),
+        ..CreateAuditLog::new(
+            None,
+            AuditAction::ChangeRole,
+            AuditTargetType::User,
+            Some(user_id.to_string()),
+        )
+    };
+    UserRepositoryImpl::new(ctx.db.clone())
+        .update_role(
+            UpdateUserRole {
+                user_id,
+                role: role.into(),
+            },
+            Some(audit),
+        )
+        .await?;
+
+    Output::report(text, after)
+}
+
+pub async fn reset_password(ctx: &Context, user: &str, password: PasswordArgs) -> Result<Output> {
+    let user = find_user(ctx, user).await?;
+    let new_password = read_password(password)?;
+    // Redisの設定に誤りがある場合は、パスワードを再設定する前に中断する
+    let auth = AuthRepositoryImpl::new(ctx.redis()?, ctx.config.auth.ttl, vec![]);
+    let audit = CreateAuditLog::new(
+        None,
+        AuditAction::ResetPassword,
+        AuditTargetType::User,
+        Some(user.id.to_string()),
+    );
+    UserRepositoryImpl::new(ctx.db.clone())
+        .reset_password(
+            ResetUserPassword {
+                user_id: user.id,
+                new_password,
+            },
+            Some(audit),
+        )
+        .await?;
+    // 以前のパスワードで発行されたアクセストークンは使えないようにする
+    let revoked = auth.delete_tokens_by_user_id(user.id).await?;
+
+    Output::report(
+        format!(
+            "{}のパスワードを再設定し、アクセストークンを{revoked}件削除しました",
+            user.email
+        ),
+        serde_json::json!({ "id": user.id, "revokedTokens": revoked }),
+    )
+}
+
+pub async fn revoke_sessions(ctx: &Context, user: &str) -> Result<Output> {
+    let user = find_user(ctx, user).await?;
+    // トークンの削除のみを行うため、パスワードの認証方式は不要
+    let repo = AuthRepositoryImpl::new(ctx.redis()?, ctx.config.auth.ttl, vec![]);
+    let revoked = repo.delete_tokens_by_user_id(user.id).await?;
+
+    let value = serde_json::json!({ "id": user.id, "revokedTokens": revoked });
+    ctx.audit(CreateAuditLog {
+        after: Some(value.clone()),
+        ..CreateAuditLog::new(
+            None,
+            AuditAction::RevokeSessions,
+            AuditTargetType::User,
+            Some(user.id.to_string()),
+        )
+    })
+    .await?;
+
+    Output::report(
+        format!("{}のアクセストークンを{revoked}件削除しました", user.email),
+        value,
+    )
+}
@@ -3,19 +3,31 @@ use std::{
     sync::Arc,
 };
 
-use adapter::{database::connect_database_with, redis::RedisClient};
-use anyhow::{Context, Result};
-use api::route::{auth, v1};
+use adapter::{
+    database::{
+        connect_database_with,
+        migration::{pending_migrations, run_migrations},
+        ConnectionPool,
+    },
+    redis::RedisClient,
+};
+use anyhow::{bail, Context, Result};
+use api::{
+    job::{outbox::OutboxDispatcher, reminder::ReminderJob, webhook::WebhookDispatcher},
+    route::{auth, v1},
+};
 use axum::{
     http::{header, Method},
     Router,
 };
+use clap::{Parser, ValueEnum};
 use opentelemetry::global;
 use registry::AppRegistryImpl;
 use shared::config::AppConfig;
 use tokio::net::TcpListener;
 use tower_http::{
     cors::CorsLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
     set_header::SetResponseHeaderLayer,
     trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer},
     LatencyUnit,
@@ -77,21 +89,90 @@ fn init_logger() -> Result<()> {
     Ok(())
 }
 
+/// 起動時のマイグレーションの扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MigrateMode {
+    /// 未適用のマイグレーションを適用してから起動する
+    Auto,
+    /// 未適用のマイグレーションがある場合は起動しない
+    Check,
+    /// 確認せずに起動する。適用状況は`/api/v1/health/migrations`で確認できる
+    Off,
+}
+
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Args {
+    /// 起動時のマイグレーションの扱い
+    #[arg(long, value_enum, env = "MIGRATE_MODE", default_value_t = MigrateMode::Auto)]
+    migrate: MigrateMode,
+}
+
+/// 起動時のマイグレーションの扱いに従い、データベースのスキーマを確認・更新する
+async fn prepare_database(pool: &ConnectionPool, mode: MigrateMode) -> Result<()> {
+    match mode {
+        MigrateMode::Auto => {
+            for migration in run_migrations(pool).await? {
+                tracing::info!(
+                    migration.version,
+                    migration.description,
+                    "applied database migration"
+                );
+            }
+        }
+        MigrateMode::Check => {
+            let pending = pending_migrations(pool).await?;
+            if !pending.is_empty() {
+                let versions = pending
+                    .iter()
+                    .map(|m| format!("{} ({})", m.version, m.description))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                bail!(
+                    "Database schema is behind the application; pending migrations: {versions}. \
+                     Start with --migrate=auto or run `bookmanager-admin migrate run`"
+                );
+            }
+        }
+        MigrateMode::Off => {}
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    let args = Args::parse();
     init_logger()?;
-    bootstrap().await
+    bootstrap(args).await
 }
 
-async fn bootstrap() -> Result<()> {
+async fn bootstrap(args: Args) -> Result<()> {
     // app_configの初期化
     let app_config = AppConfig::new()?;
     // データベース接続処理
     let pool = connect_database_with(&app_config.database);
+    // マイグレーションの確認・適用
+    prepare_database(&pool, args.migrate).await?;
     // Redis接続処理
     let kv = Arc::new(RedisClient::new(&app_config.redis)?);
+    let reminder_config = app_config.reminder.clone();
+    let webhook_config = app_config.webhook.clone();
+    let outbox_config = app_config.outbox.clone();
     // registryの初期化
-    let registry = Arc::new(AppRegistryImpl::new(pool, kv, app_config));
+    let registry = Arc::new(AppRegistryImpl::new(pool, kv, app_config)?);
+    // 返却期限のリマインダーを送るジョブの起動
+    // サーバーと同じプロセスで動かし、サーバーの終了とともに止める
+    let reminder_job = reminder_config
+        .enabled
+        .then(|| ReminderJob::new(registry.clone(), reminder_config).spawn());
+    // Webhookの配信を送信するジョブの起動
+    let webhook_dispatcher = webhook_config
+        .enabled
+        .then(|| WebhookDispatcher::new(registry.clone(), webhook_config).spawn());
+    // ドメインイベントをコンシューマーに届けるジョブの起動
+    let outbox_dispatcher = outbox_config
+        .enabled
+        .then(|| OutboxDispatcher::new(registry.clone(), outbox_config).spawn());
     let frontend_url =
         std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:3000".into());
     let frontend_origin = frontend_url
@@ -104,8 +185,14 @@ async fn bootstrap() -> Result<()> {
         })?;
 
     let cors = CorsLayer::new()
-        // allow `Authorization` and `Content-Type` headers when accessing the resource
-        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+        // allow `Authorization`, `Content-Type`, `X-API-Key`, `X-Kiosk-Key` and `Last-Event-ID` headers when accessing the resource
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            header::HeaderName::from_static(api::extractor::API_KEY_HEADER),
+            header::HeaderName::from_static(api::extractor::KIOSK_KEY_HEADER),
+            header::HeaderName::from_static(api::handler::event::LAST_EVENT_ID_HEADER),
+        ])
         // allow `GET`,`POST`,`PUT`,`DELETE` when accessing the resource
         .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
         // allow requests from the specified origin
@@ -145,16 +232,30 @@ async fn bootstrap() -> Result<()> {
                         .latency_unit(LatencyUnit::Millis),
                 ),
         )
+        // リクエストIDを付与し、監査ログとレスポンスヘッダーに引き継ぐ
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         .with_state(registry);
 
     // サーバーの起動
     let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 8080);
     let listener = TcpListener::bind(addr).await?;
     tracing::info!("Listening on: {}", addr);
-    axum::serve(listener, app)
-        // グレースフルシャットダウン時に実行する処理
-        .with_graceful_shutdown(shutdown_signal())
-        .await
+    // 監査ログに記録するため、接続元のアドレスをハンドラーから参照できるようにする
+    let result = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    // グレースフルシャットダウン時に実行する処理
+    .with_graceful_shutdown(shutdown_signal())
+    .await;
+    for job in [reminder_job, webhook_dispatcher, outbox_dispatcher]
+        .into_iter()
+        .flatten()
+    {
+        job.abort();
+    }
+    result
         // 起動失敗時のログを tracing::error! で出力する
         .context("Unexpected error in server")
         .inspect_err(|e| {